@@ -21,14 +21,25 @@ export async function fetchAttendanceRecap(q: AttendanceRekapQuery) {
   return data.data
 }
 
-export async function fetchSelfieBlob(key: string) {
+export async function fetchSelfieBlob(key: string, size: "thumb" | "medium" | "original" = "medium") {
   const { data } = await http.get<ArrayBuffer>("/files/selfie", {
-    params: { key },
+    params: size === "original" ? { key } : { key, size },
     responseType: "arraybuffer",
   })
   return new Blob([data])
 }
 
+/** Rekap bulanan resmi satker (kode harian + total + tanda tangan kepala) dari server. */
+export async function downloadMonthlyRekap(month: string, format: "xlsx" | "pdf", satkerId?: string) {
+  const { data, headers } = await http.get<ArrayBuffer>("/attendance/rekap/monthly", {
+    params: { month, format, satker_id: satkerId || undefined },
+    responseType: "arraybuffer",
+  })
+  const disposition = String(headers["content-disposition"] ?? "")
+  const filename = /filename="([^"]+)"/.exec(disposition)?.[1] ?? `rekap-absensi-${month}.${format}`
+  return { blob: new Blob([data], { type: String(headers["content-type"] ?? "") }), filename }
+}
+
 export async function upsertAttendanceAdmin(userId: string, workDateYmd: string, body: UpsertAttendanceAdminBody) {
   const { data } = await http.put(`/attendance/admin/${userId}/${workDateYmd}`, body)
   return data
@@ -9,7 +9,7 @@ import { useUsers } from "@/features/users/hooks"
 import { useTimezoneQuery } from "@/features/settings/hooks"
 
 import { useAttendanceRecap } from "@/features/attendance/hooks"
-import { fetchSelfieBlob } from "@/features/attendance/api"
+import { downloadMonthlyRekap, fetchSelfieBlob } from "@/features/attendance/api"
 import type { AttendanceLeaveType, AttendanceRekapRow } from "@/features/attendance/types"
 
 import { useWorkingDays } from "@/features/working-days/hooks"
@@ -1429,11 +1429,39 @@ export default function AttendanceRecapPage() {
     doc.save(`rekap_absensi_${from}_to_${to}.pdf`)
   }
 
+  const [rekapDownloading, setRekapDownloading] = React.useState(false)
+  const exportMonthlyRekap = async (format: "xlsx" | "pdf") => {
+    setRekapDownloading(true)
+    try {
+      const { blob, filename } = await downloadMonthlyRekap(month, format, role === "SUPERADMIN" ? satkerId : undefined)
+      const url = URL.createObjectURL(blob)
+      const a = document.createElement("a")
+      a.href = url
+      a.download = filename
+      a.click()
+      URL.revokeObjectURL(url)
+    } catch {
+      toast.error("Gagal mengunduh rekap bulanan")
+    } finally {
+      setRekapDownloading(false)
+    }
+  }
+
   return (
       <Card>
         <CardHeader className="flex flex-row items-center justify-between gap-4">
           <CardTitle>Rekap Absensi</CardTitle>
           <div className="flex gap-2">
+            {role !== "MEMBER" && (
+              <>
+                <Button variant="outline" onClick={() => exportMonthlyRekap("xlsx")} disabled={!satkerId || rekapDownloading}>
+                  Rekap Bulanan (Excel)
+                </Button>
+                <Button variant="outline" onClick={() => exportMonthlyRekap("pdf")} disabled={!satkerId || rekapDownloading}>
+                  Rekap Bulanan (PDF)
+                </Button>
+              </>
+            )}
             <Button variant="secondary" onClick={exportExcel} disabled={!userId || mergedRows.length === 0}>
               Export Excel
             </Button>
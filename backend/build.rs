@@ -0,0 +1,4 @@
+// sqlx::migrate! meng-embed folder migrations; rebuild kalau isinya berubah.
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
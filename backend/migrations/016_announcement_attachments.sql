@@ -0,0 +1,40 @@
+-- 016_announcement_attachments.sql
+-- Feature: Pengumuman dengan lampiran (surat edaran PDF/gambar) + body Markdown
+--  - body_format: PLAIN | MARKDOWN (body menyimpan Markdown yang sudah disanitasi)
+--  - body_text: fallback plain-text hasil render (dipakai aplikasi Android)
+
+ALTER TABLE announcements
+    ADD COLUMN IF NOT EXISTS body_format text NOT NULL DEFAULT 'PLAIN',
+    ADD COLUMN IF NOT EXISTS body_text text NULL;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint WHERE conname = 'announcements_body_format_check'
+    ) THEN
+        ALTER TABLE announcements
+            ADD CONSTRAINT announcements_body_format_check
+            CHECK (body_format IN ('PLAIN','MARKDOWN'));
+    END IF;
+END $$;
+
+UPDATE announcements SET body_text = body WHERE body_text IS NULL;
+
+ALTER TABLE announcements ALTER COLUMN body_text SET NOT NULL;
+
+CREATE TABLE IF NOT EXISTS announcement_attachments (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    announcement_id uuid NOT NULL REFERENCES announcements(id) ON DELETE CASCADE,
+    object_key text NOT NULL,
+    file_name text NOT NULL,
+    content_type text NOT NULL,
+    size_bytes bigint NOT NULL,
+    uploaded_by uuid NOT NULL REFERENCES users(id) ON DELETE RESTRICT,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_announcement_attachments_object_key
+    ON announcement_attachments (object_key);
+
+CREATE INDEX IF NOT EXISTS idx_announcement_attachments_announcement
+    ON announcement_attachments (announcement_id, created_at);
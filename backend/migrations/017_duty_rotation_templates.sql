@@ -0,0 +1,64 @@
+-- Template rotasi piket per satker (mis. 3 regu, shift 24 jam).
+-- Satu siklus = cycle_days hari, dihitung dari anchor_date (hari ke-0).
+-- Setiap slot: hari ke-N dalam siklus, regu, jam mulai, durasi, tipe jadwal.
+
+CREATE TABLE IF NOT EXISTS duty_rotation_templates
+(
+    id          UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    satker_id   UUID        NOT NULL REFERENCES satkers (id) ON DELETE CASCADE,
+    name        TEXT        NOT NULL,
+    cycle_days  INT         NOT NULL,
+    anchor_date DATE        NOT NULL,
+    is_active   BOOLEAN     NOT NULL DEFAULT TRUE,
+    created_by  UUID REFERENCES users (id) ON DELETE SET NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT duty_rotation_templates_cycle_check CHECK (cycle_days BETWEEN 1 AND 62)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_duty_rotation_templates_satker_name
+    ON duty_rotation_templates (satker_id, lower(name));
+
+CREATE TABLE IF NOT EXISTS duty_rotation_teams
+(
+    id          UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    template_id UUID NOT NULL REFERENCES duty_rotation_templates (id) ON DELETE CASCADE,
+    name        TEXT NOT NULL,
+    sort_order  INT  NOT NULL DEFAULT 0
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_duty_rotation_teams_template_name
+    ON duty_rotation_teams (template_id, lower(name));
+
+CREATE TABLE IF NOT EXISTS duty_rotation_team_members
+(
+    team_id UUID NOT NULL REFERENCES duty_rotation_teams (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    PRIMARY KEY (team_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS duty_rotation_slots
+(
+    id               UUID PRIMARY KEY       DEFAULT gen_random_uuid(),
+    template_id      UUID          NOT NULL REFERENCES duty_rotation_templates (id) ON DELETE CASCADE,
+    team_id          UUID          NOT NULL REFERENCES duty_rotation_teams (id) ON DELETE CASCADE,
+    day_index        INT           NOT NULL,
+    start_time       TIME          NOT NULL,
+    duration_minutes INT           NOT NULL,
+    schedule_type    schedule_type NOT NULL DEFAULT 'SHIFT',
+    title            TEXT,
+
+    CONSTRAINT duty_rotation_slots_day_check CHECK (day_index >= 0),
+    CONSTRAINT duty_rotation_slots_duration_check CHECK (duration_minutes BETWEEN 1 AND 2880)
+);
+
+CREATE INDEX IF NOT EXISTS idx_duty_rotation_slots_template ON duty_rotation_slots (template_id, day_index);
+
+-- Jadwal hasil generate ditandai template-nya, supaya generate ulang tidak dobel.
+ALTER TABLE duty_schedules
+    ADD COLUMN IF NOT EXISTS rotation_template_id UUID REFERENCES duty_rotation_templates (id) ON DELETE SET NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_duty_schedules_rotation_occurrence
+    ON duty_schedules (rotation_template_id, user_id, start_at)
+    WHERE deleted_at IS NULL AND rotation_template_id IS NOT NULL;
@@ -0,0 +1,78 @@
+-- Tukar / oper jadwal piket antar anggota.
+-- Alur: pemohon mengajukan -> rekan menerima -> admin/kepala satker menyetujui -> user_id ditukar.
+
+CREATE TABLE IF NOT EXISTS duty_schedule_swaps
+(
+    id                      UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    satker_id               UUID        NOT NULL REFERENCES satkers (id),
+
+    requester_id            UUID        NOT NULL REFERENCES users (id),
+    requester_schedule_id   UUID        NOT NULL REFERENCES duty_schedules (id),
+    counterpart_id          UUID        NOT NULL REFERENCES users (id),
+    -- NULL = oper jadwal (tanpa tukar balik)
+    counterpart_schedule_id UUID REFERENCES duty_schedules (id),
+
+    -- snapshot waktu jadwal saat pengajuan (riwayat tetap utuh walau jadwal diubah)
+    requester_start_at      TIMESTAMPTZ NOT NULL,
+    requester_end_at        TIMESTAMPTZ NOT NULL,
+    counterpart_start_at    TIMESTAMPTZ,
+    counterpart_end_at      TIMESTAMPTZ,
+
+    note                    TEXT,
+    status                  TEXT        NOT NULL DEFAULT 'PENDING_PARTNER',
+    reject_reason           TEXT,
+    decided_by              UUID REFERENCES users (id),
+    decided_at              TIMESTAMPTZ,
+
+    created_at              TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at              TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT duty_schedule_swaps_status_check
+        CHECK (status IN ('PENDING_PARTNER', 'ACCEPTED', 'APPROVED', 'REJECTED', 'DECLINED', 'CANCELED')),
+
+    CONSTRAINT duty_schedule_swaps_users_check
+        CHECK (requester_id <> counterpart_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dss_satker_status ON duty_schedule_swaps (satker_id, status);
+CREATE INDEX IF NOT EXISTS idx_dss_requester ON duty_schedule_swaps (requester_id, status);
+CREATE INDEX IF NOT EXISTS idx_dss_counterpart ON duty_schedule_swaps (counterpart_id, status);
+
+-- Satu jadwal hanya boleh ada di satu pengajuan tukar yang masih berjalan.
+CREATE UNIQUE INDEX IF NOT EXISTS ux_dss_open_requester_schedule
+    ON duty_schedule_swaps (requester_schedule_id)
+    WHERE status IN ('PENDING_PARTNER', 'ACCEPTED');
+CREATE UNIQUE INDEX IF NOT EXISTS ux_dss_open_counterpart_schedule
+    ON duty_schedule_swaps (counterpart_schedule_id)
+    WHERE status IN ('PENDING_PARTNER', 'ACCEPTED') AND counterpart_schedule_id IS NOT NULL;
+
+-- Riwayat setiap perubahan status pengajuan tukar.
+CREATE TABLE IF NOT EXISTS duty_schedule_swap_events
+(
+    id          UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    swap_id     UUID        NOT NULL REFERENCES duty_schedule_swaps (id) ON DELETE CASCADE,
+    actor_id    UUID REFERENCES users (id) ON DELETE SET NULL,
+    action      TEXT        NOT NULL,
+    from_status TEXT,
+    to_status   TEXT        NOT NULL,
+    note        TEXT,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_dsse_swap ON duty_schedule_swap_events (swap_id, created_at);
+
+-- Jadwal hasil rotasi yang sudah ditukar tetap dikenali sebagai slot milik user asal,
+-- supaya generate ulang tidak membuat jadwal baru untuk user asal.
+ALTER TABLE duty_schedules
+    ADD COLUMN IF NOT EXISTS rotation_user_id UUID REFERENCES users (id) ON DELETE SET NULL;
+
+UPDATE duty_schedules
+SET rotation_user_id = user_id
+WHERE rotation_template_id IS NOT NULL
+  AND rotation_user_id IS NULL;
+
+DROP INDEX IF EXISTS ux_duty_schedules_rotation_occurrence;
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_duty_schedules_rotation_slot
+    ON duty_schedules (rotation_template_id, rotation_user_id, start_at)
+    WHERE deleted_at IS NULL AND rotation_template_id IS NOT NULL;
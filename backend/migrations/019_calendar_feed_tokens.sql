@@ -0,0 +1,25 @@
+-- Token feed kalender (ICS) per user. Token asli hanya ditampilkan sekali saat dibuat;
+-- yang disimpan hanya hash SHA-256-nya.
+-- scope USER   = jadwal dinas + cuti milik user + hari libur satker-nya (satker_id NULL, ikut satker user saat ini)
+-- scope SATKER = seluruh jadwal dinas + cuti anggota satker + hari libur (admin/kepala satker)
+
+CREATE TABLE IF NOT EXISTS calendar_feed_tokens
+(
+    id           UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id      UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    scope        TEXT        NOT NULL,
+    satker_id    UUID        NULL REFERENCES satkers (id) ON DELETE CASCADE,
+    token_hash   TEXT        NOT NULL,
+    label        TEXT,
+    last_used_at TIMESTAMPTZ,
+    revoked_at   TIMESTAMPTZ,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT calendar_feed_tokens_scope_check CHECK (
+        (scope = 'USER' AND satker_id IS NULL) OR
+        (scope = 'SATKER' AND satker_id IS NOT NULL)
+        )
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_calendar_feed_tokens_hash ON calendar_feed_tokens (token_hash);
+CREATE INDEX IF NOT EXISTS idx_calendar_feed_tokens_user ON calendar_feed_tokens (user_id);
@@ -0,0 +1,31 @@
+-- Materialisasi kalender otomatis: perubahan work pattern / libur masuk antrian job
+-- dan diproses worker di background. Hari yang diubah manual (PUT /working-days)
+-- ditandai is_manual dan tidak ditimpa saat generate ulang.
+
+ALTER TABLE satker_calendar_days
+    ADD COLUMN IF NOT EXISTS is_manual BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS calendar_regen_jobs
+(
+    id          UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    satker_id   UUID        NOT NULL REFERENCES satkers (id) ON DELETE CASCADE,
+    from_date   DATE        NOT NULL,
+    -- NULL = sampai akhir kalender yang sudah ter-generate
+    to_date     DATE,
+    reason      TEXT        NOT NULL,
+    status      TEXT        NOT NULL DEFAULT 'PENDING',
+    attempts    INT         NOT NULL DEFAULT 0,
+    generated   BIGINT,
+    error       TEXT,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    started_at  TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ,
+
+    CONSTRAINT calendar_regen_jobs_status_check CHECK (status IN ('PENDING', 'RUNNING', 'DONE', 'FAILED')),
+    CONSTRAINT calendar_regen_jobs_range_check CHECK (to_date IS NULL OR to_date >= from_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_calendar_regen_jobs_pending
+    ON calendar_regen_jobs (created_at) WHERE status = 'PENDING';
+CREATE INDEX IF NOT EXISTS idx_calendar_regen_jobs_satker
+    ON calendar_regen_jobs (satker_id, created_at DESC);
@@ -0,0 +1,75 @@
+-- Jam kerja per user / per kelompok (override work pattern satker).
+-- Hari kerja/libur tetap dari kalender satker; override hanya mengganti jam.
+-- Mode FLEXI: work_start..work_end = rentang boleh hadir, core_start..core_end = jam inti
+-- (terlambat dihitung dari core_start), required_minutes = durasi kerja wajib per hari.
+
+DO
+$$
+    BEGIN
+        CREATE TYPE work_pattern_mode AS ENUM ('FIXED', 'FLEXI');
+    EXCEPTION
+        WHEN duplicate_object THEN NULL;
+    END
+$$;
+
+CREATE TABLE IF NOT EXISTS work_groups
+(
+    id          UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    satker_id   UUID        NOT NULL REFERENCES satkers (id) ON DELETE CASCADE,
+    name        TEXT        NOT NULL,
+    description TEXT,
+    created_by  UUID REFERENCES users (id) ON DELETE SET NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_work_groups_satker_name
+    ON work_groups (satker_id, lower(name));
+
+CREATE TABLE IF NOT EXISTS work_group_members
+(
+    group_id UUID NOT NULL REFERENCES work_groups (id) ON DELETE CASCADE,
+    user_id  UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    PRIMARY KEY (group_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_work_group_members_user ON work_group_members (user_id);
+
+CREATE TABLE IF NOT EXISTS work_pattern_overrides
+(
+    id               UUID PRIMARY KEY           DEFAULT gen_random_uuid(),
+    satker_id        UUID              NOT NULL REFERENCES satkers (id) ON DELETE CASCADE,
+    user_id          UUID REFERENCES users (id) ON DELETE CASCADE,
+    group_id         UUID REFERENCES work_groups (id) ON DELETE CASCADE,
+    effective_from   DATE              NOT NULL,
+    -- NULL = berlaku seterusnya
+    effective_to     DATE,
+    mode             work_pattern_mode NOT NULL DEFAULT 'FIXED',
+    work_start       TIME              NOT NULL,
+    work_end         TIME              NOT NULL,
+    core_start       TIME,
+    core_end         TIME,
+    required_minutes INT,
+    note             TEXT,
+    created_by       UUID REFERENCES users (id) ON DELETE SET NULL,
+    created_at       TIMESTAMPTZ       NOT NULL DEFAULT now(),
+    updated_at       TIMESTAMPTZ       NOT NULL DEFAULT now(),
+
+    CONSTRAINT work_pattern_overrides_target_check CHECK ((user_id IS NULL) <> (group_id IS NULL)),
+    CONSTRAINT work_pattern_overrides_range_check CHECK (effective_to IS NULL OR effective_to >= effective_from),
+    CONSTRAINT work_pattern_overrides_hours_check CHECK (work_end > work_start),
+    CONSTRAINT work_pattern_overrides_mode_check CHECK (
+        (mode = 'FIXED' AND core_start IS NULL AND core_end IS NULL AND required_minutes IS NULL)
+        OR (mode = 'FLEXI'
+            AND core_start IS NOT NULL AND core_end IS NOT NULL AND required_minutes IS NOT NULL
+            AND core_start >= work_start AND core_end <= work_end AND core_end > core_start
+            AND required_minutes > 0)
+        )
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_work_pattern_overrides_user_from
+    ON work_pattern_overrides (user_id, effective_from) WHERE user_id IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS ux_work_pattern_overrides_group_from
+    ON work_pattern_overrides (group_id, effective_from) WHERE group_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_work_pattern_overrides_satker
+    ON work_pattern_overrides (satker_id, effective_from);
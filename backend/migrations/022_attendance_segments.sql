@@ -0,0 +1,68 @@
+-- Segmen absensi: satu session (user + work_date) bisa punya beberapa pasangan check-in/out
+-- (shift terpisah pos jaga/klinik, keluar-masuk tugas lapangan).
+-- Session tetap menyimpan check-in pertama & check-out terakhir; durasi kerja dihitung dari segmen.
+
+CREATE TABLE IF NOT EXISTS attendance_segments
+(
+    id           UUID PRIMARY KEY     DEFAULT uuid_generate_v4(),
+    session_id   UUID        NOT NULL REFERENCES attendance_sessions (id) ON DELETE CASCADE,
+    seq          INT         NOT NULL,
+    check_in_at  TIMESTAMPTZ NOT NULL,
+    check_out_at TIMESTAMPTZ NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT attendance_segments_seq_chk CHECK (seq >= 1),
+    CONSTRAINT attendance_segments_range_chk CHECK (check_out_at IS NULL OR check_out_at >= check_in_at),
+    CONSTRAINT attendance_segments_session_seq_uniq UNIQUE (session_id, seq)
+);
+
+-- maksimal satu segmen terbuka per session
+CREATE UNIQUE INDEX IF NOT EXISTS attendance_segments_open_uniq
+    ON attendance_segments (session_id)
+    WHERE check_out_at IS NULL;
+
+ALTER TABLE attendance_events
+    ADD COLUMN IF NOT EXISTS segment_id UUID NULL REFERENCES attendance_segments (id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS attendance_events_segment_idx ON attendance_events (segment_id);
+
+-- Jumlah segmen yang diizinkan per hari. Default 1 = perilaku lama (satu pasang check-in/out).
+ALTER TABLE satker_work_patterns
+    ADD COLUMN IF NOT EXISTS max_segments SMALLINT NOT NULL DEFAULT 1;
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint WHERE conname = 'satker_work_patterns_max_segments_chk'
+    ) THEN
+        ALTER TABLE satker_work_patterns
+            ADD CONSTRAINT satker_work_patterns_max_segments_chk
+            CHECK (max_segments BETWEEN 1 AND 10);
+    END IF;
+END $$;
+
+ALTER TABLE work_pattern_overrides
+    ADD COLUMN IF NOT EXISTS max_segments SMALLINT NOT NULL DEFAULT 1;
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint WHERE conname = 'work_pattern_overrides_max_segments_chk'
+    ) THEN
+        ALTER TABLE work_pattern_overrides
+            ADD CONSTRAINT work_pattern_overrides_max_segments_chk
+            CHECK (max_segments BETWEEN 1 AND 10);
+    END IF;
+END $$;
+
+-- Backfill: session lama = satu segmen. Session yang belum check-out tetap jadi segmen terbuka.
+INSERT INTO attendance_segments (session_id, seq, check_in_at, check_out_at)
+SELECT s.id, 1, s.check_in_at, s.check_out_at
+FROM attendance_sessions s
+WHERE s.check_in_at IS NOT NULL
+ON CONFLICT (session_id, seq) DO NOTHING;
+
+UPDATE attendance_events e
+SET segment_id = g.id
+FROM attendance_segments g
+WHERE g.session_id = e.session_id
+  AND e.segment_id IS NULL;
@@ -0,0 +1,86 @@
+-- Lembur: kandidat dihitung dari attendance session + satker_calendar_days,
+-- anggota mengajukan klaim, kepala satker menyetujui, rekap bulanan untuk payroll.
+
+-- Tarif lembur per jam per pangkat & jenis hari. rank_id NULL = tarif default (semua pangkat).
+CREATE TABLE IF NOT EXISTS overtime_rates
+(
+    id                  UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    rank_id             UUID REFERENCES ranks (id) ON DELETE CASCADE,
+    day_kind            TEXT        NOT NULL,
+    hourly_rate         BIGINT      NOT NULL,
+    -- batas menit lembur yang dibayar per hari (NULL = tanpa batas)
+    max_minutes_per_day INT,
+
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT overtime_rates_day_kind_check
+        CHECK (day_kind IN ('WEEKDAY', 'WEEKEND', 'HOLIDAY')),
+    CONSTRAINT overtime_rates_hourly_rate_check CHECK (hourly_rate >= 0),
+    CONSTRAINT overtime_rates_max_minutes_check
+        CHECK (max_minutes_per_day IS NULL OR max_minutes_per_day > 0)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_overtime_rates_rank_kind
+    ON overtime_rates (COALESCE(rank_id, '00000000-0000-0000-0000-000000000000'::uuid), day_kind);
+
+CREATE TABLE IF NOT EXISTS overtime_claims
+(
+    id                UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    satker_id         UUID        NOT NULL REFERENCES satkers (id),
+    user_id           UUID        NOT NULL REFERENCES users (id),
+    session_id        UUID REFERENCES attendance_sessions (id) ON DELETE SET NULL,
+    duty_schedule_id  UUID REFERENCES duty_schedules (id) ON DELETE SET NULL,
+
+    work_date         DATE        NOT NULL,
+    day_kind          TEXT        NOT NULL,
+
+    -- snapshot kandidat saat diajukan
+    start_at          TIMESTAMPTZ NOT NULL,
+    end_at            TIMESTAMPTZ NOT NULL,
+    candidate_minutes INT         NOT NULL,
+    claimed_minutes   INT         NOT NULL,
+    approved_minutes  INT,
+
+    note              TEXT,
+    status            TEXT        NOT NULL DEFAULT 'SUBMITTED',
+    reject_reason     TEXT,
+    decided_by        UUID REFERENCES users (id),
+    decided_at        TIMESTAMPTZ,
+
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at        TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT overtime_claims_status_check
+        CHECK (status IN ('SUBMITTED', 'APPROVED', 'REJECTED', 'CANCELED')),
+    CONSTRAINT overtime_claims_day_kind_check
+        CHECK (day_kind IN ('WEEKDAY', 'WEEKEND', 'HOLIDAY')),
+    CONSTRAINT overtime_claims_range_check CHECK (end_at > start_at),
+    CONSTRAINT overtime_claims_minutes_check
+        CHECK (claimed_minutes > 0 AND claimed_minutes <= candidate_minutes),
+    CONSTRAINT overtime_claims_approved_check
+        CHECK (approved_minutes IS NULL OR (approved_minutes > 0 AND approved_minutes <= claimed_minutes))
+);
+
+CREATE INDEX IF NOT EXISTS idx_overtime_claims_satker_status ON overtime_claims (satker_id, status);
+CREATE INDEX IF NOT EXISTS idx_overtime_claims_user_date ON overtime_claims (user_id, work_date);
+
+-- Satu klaim aktif per user per hari.
+CREATE UNIQUE INDEX IF NOT EXISTS ux_overtime_claims_open_user_date
+    ON overtime_claims (user_id, work_date)
+    WHERE status IN ('SUBMITTED', 'APPROVED');
+
+-- Riwayat setiap perubahan status klaim lembur.
+CREATE TABLE IF NOT EXISTS overtime_claim_events
+(
+    id          UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    claim_id    UUID        NOT NULL REFERENCES overtime_claims (id) ON DELETE CASCADE,
+    actor_id    UUID REFERENCES users (id) ON DELETE SET NULL,
+    action      TEXT        NOT NULL,
+    from_status TEXT,
+    to_status   TEXT        NOT NULL,
+    note        TEXT,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_oce_claim ON overtime_claim_events (claim_id, created_at);
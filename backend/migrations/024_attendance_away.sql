@@ -0,0 +1,72 @@
+-- Istirahat & tugas lapangan (keluar kantor) di dalam satu segmen check-in/out.
+-- Event BREAK_START/BREAK_END dan FIELD_OUT/FIELD_IN membuka/menutup satu periode "away".
+-- Periode yang masih terbuka ikut ditutup saat check-out.
+
+ALTER TYPE attendance_event_type ADD VALUE IF NOT EXISTS 'BREAK_START';
+ALTER TYPE attendance_event_type ADD VALUE IF NOT EXISTS 'BREAK_END';
+ALTER TYPE attendance_event_type ADD VALUE IF NOT EXISTS 'FIELD_OUT';
+ALTER TYPE attendance_event_type ADD VALUE IF NOT EXISTS 'FIELD_IN';
+
+CREATE TABLE IF NOT EXISTS attendance_away_periods
+(
+    id          UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    session_id  UUID        NOT NULL REFERENCES attendance_sessions (id) ON DELETE CASCADE,
+    segment_id  UUID REFERENCES attendance_segments (id) ON DELETE SET NULL,
+    -- BREAK | FIELD_TASK
+    kind        TEXT        NOT NULL,
+    started_at  TIMESTAMPTZ NOT NULL,
+    ended_at    TIMESTAMPTZ NULL,
+    -- tugas lapangan: tujuan & keperluan (wajib untuk FIELD_TASK)
+    destination TEXT,
+    purpose     TEXT,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT attendance_away_periods_kind_chk CHECK (kind IN ('BREAK', 'FIELD_TASK')),
+    CONSTRAINT attendance_away_periods_range_chk CHECK (ended_at IS NULL OR ended_at >= started_at),
+    CONSTRAINT attendance_away_periods_field_chk
+        CHECK (kind <> 'FIELD_TASK' OR (destination IS NOT NULL AND purpose IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS attendance_away_periods_session_idx
+    ON attendance_away_periods (session_id, started_at);
+
+-- maksimal satu periode away terbuka per session
+CREATE UNIQUE INDEX IF NOT EXISTS attendance_away_periods_open_uniq
+    ON attendance_away_periods (session_id)
+    WHERE ended_at IS NULL;
+
+ALTER TABLE attendance_events
+    ADD COLUMN IF NOT EXISTS away_period_id UUID NULL REFERENCES attendance_away_periods (id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS destination TEXT NULL,
+    ADD COLUMN IF NOT EXISTS purpose TEXT NULL;
+
+CREATE INDEX IF NOT EXISTS attendance_events_away_period_idx ON attendance_events (away_period_id);
+
+-- Batas total menit istirahat per hari. NULL = tanpa batas.
+-- Kelebihan istirahat dihitung sebagai menit terlambat di tukin.
+ALTER TABLE satker_work_patterns
+    ADD COLUMN IF NOT EXISTS max_break_minutes SMALLINT NULL;
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint WHERE conname = 'satker_work_patterns_max_break_minutes_chk'
+    ) THEN
+        ALTER TABLE satker_work_patterns
+            ADD CONSTRAINT satker_work_patterns_max_break_minutes_chk
+            CHECK (max_break_minutes IS NULL OR max_break_minutes BETWEEN 1 AND 480);
+    END IF;
+END $$;
+
+ALTER TABLE work_pattern_overrides
+    ADD COLUMN IF NOT EXISTS max_break_minutes SMALLINT NULL;
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint WHERE conname = 'work_pattern_overrides_max_break_minutes_chk'
+    ) THEN
+        ALTER TABLE work_pattern_overrides
+            ADD CONSTRAINT work_pattern_overrides_max_break_minutes_chk
+            CHECK (max_break_minutes IS NULL OR max_break_minutes BETWEEN 1 AND 480);
+    END IF;
+END $$;
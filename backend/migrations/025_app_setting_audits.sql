@@ -0,0 +1,14 @@
+-- Audit perubahan app_settings (timezone, aturan absensi global / per satker).
+-- old_value NULL = setting baru dibuat; new_value NULL = setting dihapus (kembali ke default).
+
+CREATE TABLE IF NOT EXISTS app_setting_audits
+(
+    id         UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    key        TEXT        NOT NULL,
+    old_value  TEXT,
+    new_value  TEXT,
+    changed_by UUID REFERENCES users (id) ON DELETE SET NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_app_setting_audits_key ON app_setting_audits (key, changed_at DESC);
@@ -0,0 +1,45 @@
+-- Manajemen device: riwayat binding + permintaan device baru yang harus disetujui admin satker.
+-- Device pertama user tetap ter-bind otomatis saat check-in; device berikutnya lewat permintaan.
+
+ALTER TABLE user_devices
+    ADD COLUMN IF NOT EXISTS bound_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+    -- NULL = bind otomatis oleh user sendiri saat check-in pertama
+    ADD COLUMN IF NOT EXISTS bound_by     UUID        NULL REFERENCES users (id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ NULL;
+
+CREATE INDEX IF NOT EXISTS user_devices_user_idx ON user_devices (user_id);
+
+CREATE TABLE IF NOT EXISTS device_binding_requests
+(
+    id                 UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id            UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    satker_id          UUID        NOT NULL REFERENCES satkers (id) ON DELETE CASCADE,
+    device_id          TEXT        NOT NULL,
+    device_model       TEXT,
+    android_version    TEXT,
+    app_build          TEXT,
+    client_version     TEXT,
+    reason             TEXT,
+    -- pemilik device saat permintaan dibuat (device dipakai akun lain)
+    previous_user_id   UUID        NULL REFERENCES users (id) ON DELETE SET NULL,
+    -- device lama yang dilepas saat permintaan disetujui
+    replaced_device_id TEXT        NULL,
+    -- PENDING | APPROVED | REJECTED | CANCELED
+    status             TEXT        NOT NULL DEFAULT 'PENDING',
+    reject_reason      TEXT,
+    decided_by         UUID        NULL REFERENCES users (id) ON DELETE SET NULL,
+    decided_at         TIMESTAMPTZ NULL,
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT device_binding_requests_status_chk
+        CHECK (status IN ('PENDING', 'APPROVED', 'REJECTED', 'CANCELED'))
+);
+
+CREATE INDEX IF NOT EXISTS device_binding_requests_satker_idx
+    ON device_binding_requests (satker_id, status, created_at DESC);
+
+-- satu permintaan PENDING per user + device
+CREATE UNIQUE INDEX IF NOT EXISTS device_binding_requests_pending_uniq
+    ON device_binding_requests (user_id, device_id)
+    WHERE status = 'PENDING';
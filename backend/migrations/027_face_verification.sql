@@ -0,0 +1,37 @@
+-- Verifikasi wajah di server: foto referensi per user (disetujui admin) + hasil
+-- pencocokan selfie check-in/out dengan template hasil enrolment.
+
+CREATE TABLE IF NOT EXISTS face_enrollments
+(
+    id               UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id          UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    satker_id        UUID        NOT NULL REFERENCES satkers (id) ON DELETE CASCADE,
+    photo_object_key TEXT        NOT NULL,
+    -- PENDING | APPROVED | REJECTED | REVOKED
+    status           TEXT        NOT NULL DEFAULT 'PENDING',
+    -- template dari verifier saat disetujui (format bergantung verifier)
+    template         TEXT        NULL,
+    verifier         TEXT        NULL,
+    submitted_by     UUID        NULL REFERENCES users (id) ON DELETE SET NULL,
+    reject_reason    TEXT,
+    decided_by       UUID        NULL REFERENCES users (id) ON DELETE SET NULL,
+    decided_at       TIMESTAMPTZ NULL,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT face_enrollments_status_chk
+        CHECK (status IN ('PENDING', 'APPROVED', 'REJECTED', 'REVOKED')),
+    CONSTRAINT face_enrollments_template_chk
+        CHECK (status <> 'APPROVED' OR template IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS face_enrollments_user_idx
+    ON face_enrollments (user_id, status);
+CREATE INDEX IF NOT EXISTS face_enrollments_satker_idx
+    ON face_enrollments (satker_id, status, created_at DESC);
+
+-- Hasil verifikasi server. Skor dari aplikasi (face_match_score) tetap disimpan apa adanya.
+-- ACCEPTED | FLAGGED | NOT_ENROLLED | NO_SELFIE; NULL = verifikasi tidak aktif
+ALTER TABLE attendance_events
+    ADD COLUMN IF NOT EXISTS server_face_score        DOUBLE PRECISION NULL,
+    ADD COLUMN IF NOT EXISTS face_verification_status TEXT             NULL;
@@ -0,0 +1,42 @@
+-- Antrian tinjauan absensi mencurigakan: event tetap diterima tapi ditandai, admin satker
+-- mengonfirmasi (sah) atau membatalkan (sesi menjadi INVALID).
+
+-- Perceptual hash (dHash 64-bit, hex) selfie event untuk deteksi selfie dipakai ulang.
+ALTER TABLE attendance_events
+    ADD COLUMN IF NOT EXISTS selfie_phash TEXT NULL;
+
+CREATE INDEX IF NOT EXISTS attendance_events_selfie_phash_idx
+    ON attendance_events (selfie_phash, occurred_at DESC)
+    WHERE selfie_phash IS NOT NULL;
+CREATE INDEX IF NOT EXISTS attendance_events_device_idx
+    ON attendance_events (device_id, occurred_at DESC)
+    WHERE device_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS attendance_anomalies
+(
+    id          UUID PRIMARY KEY               DEFAULT gen_random_uuid(),
+    event_id    UUID                  NOT NULL UNIQUE REFERENCES attendance_events (id) ON DELETE CASCADE,
+    session_id  UUID                  NOT NULL REFERENCES attendance_sessions (id) ON DELETE CASCADE,
+    satker_id   UUID                  NOT NULL REFERENCES satkers (id) ON DELETE CASCADE,
+    user_id     UUID                  NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    event_type  attendance_event_type NOT NULL,
+    -- LOW_FACE_SCORE | SHARED_DEVICE | SELFIE_REUSED | SAME_COORDINATES | OUT_OF_FENCE_LEAVE
+    reasons     TEXT[]                NOT NULL,
+    -- PENDING | CONFIRMED | INVALIDATED
+    status      TEXT                  NOT NULL DEFAULT 'PENDING',
+    review_note TEXT,
+    reviewed_by UUID                  NULL REFERENCES users (id) ON DELETE SET NULL,
+    reviewed_at TIMESTAMPTZ           NULL,
+    created_at  TIMESTAMPTZ           NOT NULL DEFAULT now(),
+    updated_at  TIMESTAMPTZ           NOT NULL DEFAULT now(),
+
+    CONSTRAINT attendance_anomalies_status_chk
+        CHECK (status IN ('PENDING', 'CONFIRMED', 'INVALIDATED')),
+    CONSTRAINT attendance_anomalies_reasons_chk
+        CHECK (cardinality(reasons) > 0)
+);
+
+CREATE INDEX IF NOT EXISTS attendance_anomalies_satker_idx
+    ON attendance_anomalies (satker_id, status, created_at DESC);
+CREATE INDEX IF NOT EXISTS attendance_anomalies_user_idx
+    ON attendance_anomalies (user_id, created_at DESC);
@@ -0,0 +1,27 @@
+-- Penanda kedekatan per geofence: BSSID Wi-Fi / beacon BLE terdaftar yang bisa dipakai sebagai
+-- bukti lokasi alternatif (gedung dengan sinyal GPS buruk).
+
+CREATE TABLE IF NOT EXISTS geofence_proximity_markers
+(
+    id          UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    geofence_id UUID        NOT NULL REFERENCES geofences (id) ON DELETE CASCADE,
+    -- WIFI | BLE
+    kind        TEXT        NOT NULL,
+    -- WIFI: BSSID "aa:bb:cc:dd:ee:ff"; BLE: "uuid:major:minor" (huruf kecil)
+    identifier  TEXT        NOT NULL,
+    label       TEXT,
+    created_by  UUID        NULL REFERENCES users (id) ON DELETE SET NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT geofence_proximity_markers_kind_chk CHECK (kind IN ('WIFI', 'BLE')),
+    CONSTRAINT geofence_proximity_markers_uniq UNIQUE (geofence_id, kind, identifier)
+);
+
+CREATE INDEX IF NOT EXISTS geofence_proximity_markers_identifier_idx
+    ON geofence_proximity_markers (kind, identifier);
+
+-- Bukti lokasi event: GPS | WIFI | BLE, beserta penanda yang cocok.
+ALTER TABLE attendance_events
+    ADD COLUMN IF NOT EXISTS location_proof      TEXT NULL,
+    ADD COLUMN IF NOT EXISTS proximity_marker_id UUID NULL
+        REFERENCES geofence_proximity_markers (id) ON DELETE SET NULL;
@@ -0,0 +1,14 @@
+-- Attestation request absensi: aplikasi menandatangani payload + nonce challenge dengan kunci
+-- per device (EC P-256) yang didaftarkan saat binding.
+
+-- Kunci publik device (base64 X.509 SubjectPublicKeyInfo DER).
+ALTER TABLE user_devices
+    ADD COLUMN IF NOT EXISTS public_key               TEXT        NULL,
+    ADD COLUMN IF NOT EXISTS public_key_registered_at TIMESTAMPTZ NULL;
+
+ALTER TABLE device_binding_requests
+    ADD COLUMN IF NOT EXISTS public_key TEXT NULL;
+
+-- Verifier yang memvalidasi request (mis. device_key); NULL = request tidak ber-attestation.
+ALTER TABLE attendance_events
+    ADD COLUMN IF NOT EXISTS attestation TEXT NULL;
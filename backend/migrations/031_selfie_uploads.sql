@@ -0,0 +1,23 @@
+-- Metadata selfie yang diolah saat upload (EXIF dibuang, orientasi diluruskan, varian
+-- thumb/medium dibuat). Dipakai saat check-in/out untuk phash & waktu ambil foto.
+
+CREATE TABLE IF NOT EXISTS selfie_uploads
+(
+    id          UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    object_key  TEXT        NOT NULL UNIQUE,
+    user_id     UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    width       INT         NOT NULL,
+    height      INT         NOT NULL,
+    size_bytes  BIGINT      NOT NULL,
+    -- dHash 64-bit (hex) dari foto yang sudah diluruskan
+    phash       TEXT        NOT NULL,
+    -- EXIF DateTimeOriginal; NULL kalau foto tidak membawa waktu ambil
+    captured_at TIMESTAMPTZ NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS selfie_uploads_created_at_idx
+    ON selfie_uploads (created_at);
+
+ALTER TABLE attendance_events
+    ADD COLUMN IF NOT EXISTS selfie_captured_at TIMESTAMPTZ NULL;
@@ -0,0 +1,99 @@
+-- Retensi objek upload (selfie, foto profil) + legal hold.
+--  - Periode retensi per kelas objek disimpan di app_settings key `retention_policy` (JSON).
+--  - Purge berkala di background mencatat hasilnya di retention_runs.
+--  - Legal hold melindungi data user (opsional rentang tanggal kerja) dari purge.
+
+CREATE TABLE IF NOT EXISTS legal_holds
+(
+    id          UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    satker_id   UUID        NOT NULL REFERENCES satkers (id) ON DELETE CASCADE,
+    user_id     UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    -- rentang tanggal kerja yang ditahan; NULL = tanpa batas
+    from_date   DATE        NULL,
+    to_date     DATE        NULL,
+    reason      TEXT        NOT NULL,
+    created_by  UUID        NOT NULL REFERENCES users (id) ON DELETE RESTRICT,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    released_at TIMESTAMPTZ NULL,
+    released_by UUID        NULL REFERENCES users (id) ON DELETE SET NULL,
+
+    CONSTRAINT legal_holds_range_chk
+        CHECK (from_date IS NULL OR to_date IS NULL OR to_date >= from_date)
+);
+
+CREATE INDEX IF NOT EXISTS legal_holds_active_user_idx
+    ON legal_holds (user_id)
+    WHERE released_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS legal_holds_satker_idx
+    ON legal_holds (satker_id, created_at DESC);
+
+-- Event yang tidak boleh dipurge: ada legal hold aktif yang mencakup tanggal kerjanya,
+-- atau anomalinya masih menunggu tinjauan admin.
+CREATE OR REPLACE FUNCTION attendance_event_held(p_event_id UUID) RETURNS BOOLEAN
+    LANGUAGE sql
+    STABLE
+AS
+$$
+SELECT EXISTS (SELECT 1
+               FROM attendance_events e
+                        JOIN attendance_sessions s ON s.id = e.session_id
+                        JOIN legal_holds h ON h.user_id = e.user_id AND h.released_at IS NULL
+               WHERE e.id = p_event_id
+                 AND (h.from_date IS NULL OR s.work_date >= h.from_date)
+                 AND (h.to_date IS NULL OR s.work_date <= h.to_date))
+           OR EXISTS (SELECT 1
+                      FROM attendance_anomalies a
+                      WHERE a.event_id = p_event_id
+                        AND a.status = 'PENDING');
+$$;
+
+-- Tombstone: selfie_object_key dikosongkan oleh retensi (bukan karena tidak pernah ada selfie).
+ALTER TABLE attendance_events
+    ADD COLUMN IF NOT EXISTS selfie_purged_at TIMESTAMPTZ NULL;
+
+-- Kapan user dinonaktifkan (dasar retensi foto profil).
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS deactivated_at TIMESTAMPTZ NULL;
+
+UPDATE users
+SET deactivated_at = updated_at
+WHERE is_active = false
+  AND deactivated_at IS NULL;
+
+CREATE OR REPLACE FUNCTION set_users_deactivated_at() RETURNS TRIGGER AS
+$$
+BEGIN
+    IF NEW.is_active THEN
+        NEW.deactivated_at := NULL;
+    ELSIF OLD.is_active OR NEW.deactivated_at IS NULL THEN
+        NEW.deactivated_at := now();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_users_deactivated_at ON users;
+CREATE TRIGGER trg_users_deactivated_at
+    BEFORE UPDATE OF is_active
+    ON users
+    FOR EACH ROW
+EXECUTE FUNCTION set_users_deactivated_at();
+
+CREATE TABLE IF NOT EXISTS retention_runs
+(
+    id           UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    -- SCHEDULED (worker) | MANUAL (admin / CLI)
+    kind         TEXT        NOT NULL,
+    requested_by UUID        NULL REFERENCES users (id) ON DELETE SET NULL,
+    dry_run      BOOLEAN     NOT NULL DEFAULT false,
+    started_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    finished_at  TIMESTAMPTZ NULL,
+    report       JSONB       NULL,
+    error        TEXT        NULL,
+
+    CONSTRAINT retention_runs_kind_chk CHECK (kind IN ('SCHEDULED', 'MANUAL'))
+);
+
+CREATE INDEX IF NOT EXISTS retention_runs_started_idx
+    ON retention_runs (started_at DESC);
@@ -0,0 +1,23 @@
+-- Daftar lampiran pengumuman sebagai JSON (urut waktu upload), dipakai semua query
+-- pengumuman supaya bentuk objek lampiran cukup didefinisikan di satu tempat.
+
+CREATE OR REPLACE FUNCTION announcement_attachments_json(p_announcement_id uuid)
+    RETURNS json
+    LANGUAGE sql
+    STABLE
+AS
+$$
+SELECT COALESCE(
+               json_agg(json_build_object(
+                       'id', x.id,
+                       'object_key', x.object_key,
+                       'file_name', x.file_name,
+                       'content_type', x.content_type,
+                       'size_bytes', x.size_bytes,
+                       'created_at', x.created_at
+                        ) ORDER BY x.created_at),
+               '[]'::json
+       )
+FROM announcement_attachments x
+WHERE x.announcement_id = p_announcement_id
+$$;
@@ -0,0 +1,576 @@
+use chrono::{Duration, NaiveDate, Utc};
+use clap::Subcommand;
+use serde::Serialize;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::auth::rbac::UserRole;
+use crate::cli::{print_error, print_ok};
+use crate::config::config::StorageConfig;
+use crate::constants::{HolidayScope, SUPERUSER_SATKER_ID, SUPERUSER_USER_ID};
+use crate::database::holiday::HolidayRepo;
+use crate::database::satker::SatkerRepo;
+use crate::database::user::{NewUser, UserRepo};
+use crate::database::user_device::UserDeviceRepo;
+use crate::db::DBClient;
+use crate::dtos::admin::{
+    AdminUserResult, CalendarRegenResult, HolidayImportResult, SatkerJobResult, SelfiePurgeReport,
+    StorageMigrationReport, TukinGenerateResult, UnbindDeviceResult,
+};
+use crate::dtos::holiday::{BulkHolidayItem, BulkHolidayReq};
+use crate::dtos::retention::{RETENTION_RUN_MANUAL, RetentionReport};
+use crate::error::HttpError;
+use crate::models::{Satker, User};
+use crate::services::calendar::{MAX_CALENDAR_GENERATE_DAYS, generate_calendar_days};
+use crate::services::calendar_regen::enqueue_holiday_regen;
+use crate::services::holiday::{
+    authorize_holiday_scope_access, build_holiday_upsert_items, parse_holiday_csv,
+};
+use crate::services::retention::{purge_selfies, run_recorded_retention};
+use crate::services::storage::{Storage, build_storage};
+use crate::services::storage_migration::migrate_local_objects;
+use crate::services::tukin::{generate_tukin_calculations, parse_month};
+use crate::utils::password::hash_password;
+use crate::utils::timezone_cache::get_timezone_from_db;
+
+#[derive(Debug, Subcommand)]
+pub enum AdminCommand {
+    /// Buat akun SUPERADMIN pertama (password dibaca dari stdin kalau --password tidak diisi)
+    CreateSuperadmin {
+        #[arg(long)]
+        nrp: String,
+        #[arg(long)]
+        full_name: String,
+        #[arg(long)]
+        email: Option<String>,
+        #[arg(long)]
+        phone: Option<String>,
+        /// Default: satker superuser bawaan
+        #[arg(long)]
+        satker_id: Option<Uuid>,
+        #[arg(long)]
+        password: Option<String>,
+        /// Tetap buat walaupun sudah ada SUPERADMIN aktif lain
+        #[arg(long)]
+        force: bool,
+    },
+    /// Reset password user (password dibaca dari stdin kalau --password tidak diisi)
+    ResetPassword {
+        #[arg(long)]
+        nrp: String,
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Lepas binding device di user_devices (per device atau semua device milik user)
+    UnbindDevice {
+        #[arg(long, conflicts_with = "nrp", required_unless_present = "nrp")]
+        device_id: Option<String>,
+        #[arg(long)]
+        nrp: Option<String>,
+    },
+    /// Generate ulang kalender kerja untuk range tanggal (semua satker aktif kalau --satker-id kosong)
+    RegenCalendar {
+        #[arg(long)]
+        from: NaiveDate,
+        #[arg(long)]
+        to: NaiveDate,
+        #[arg(long)]
+        satker_id: Option<Uuid>,
+    },
+    /// Hitung & simpan tukin bulan YYYY-MM (semua satker aktif kalau --satker-id kosong)
+    GenerateTukin {
+        #[arg(long)]
+        month: String,
+        #[arg(long)]
+        satker_id: Option<Uuid>,
+        /// Hitung ulang walaupun cache bulan tsb sudah ada
+        #[arg(long)]
+        force: bool,
+    },
+    /// Import hari libur dari file .json (format body POST /holidays/bulk) atau .csv
+    ImportHolidays {
+        #[arg(long)]
+        file: PathBuf,
+        /// Wajib untuk .csv / .json berisi array item; menimpa scope di file .json
+        #[arg(long)]
+        scope: Option<HolidayScope>,
+        #[arg(long)]
+        satker_id: Option<Uuid>,
+    },
+    /// Hapus selfie absensi yang lebih lama dari N hari (storage dari env STORAGE_BACKEND/S3_*)
+    PurgeSelfies {
+        #[arg(long)]
+        older_than_days: i64,
+        /// Default: env UPLOAD_DIR atau ./uploads
+        #[arg(long)]
+        upload_dir: Option<PathBuf>,
+        /// Hanya hitung, tidak menghapus apa pun
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Jalankan kebijakan retensi (setting retention_policy) sekarang; legal hold dihormati
+    RunRetention {
+        /// Default: env UPLOAD_DIR atau ./uploads
+        #[arg(long)]
+        upload_dir: Option<PathBuf>,
+        /// Hanya hitung, tidak menghapus apa pun
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Pindahkan file `local://` ke storage utama (STORAGE_BACKEND=s3) dan ganti key di DB
+    MigrateStorage {
+        /// Default: env UPLOAD_DIR atau ./uploads
+        #[arg(long)]
+        upload_dir: Option<PathBuf>,
+        #[arg(long, default_value_t = 500)]
+        batch_size: i64,
+        /// Hapus file lokal setelah berhasil dipindah
+        #[arg(long)]
+        delete_local: bool,
+        /// Hanya hitung, tidak memindah apa pun
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Return exit code proses.
+pub async fn run(cmd: AdminCommand, db: &DBClient) -> i32 {
+    match cmd {
+        AdminCommand::CreateSuperadmin {
+            nrp,
+            full_name,
+            email,
+            phone,
+            satker_id,
+            password,
+            force,
+        } => finish(
+            create_superadmin(
+                db,
+                SuperadminInput {
+                    nrp,
+                    full_name,
+                    email,
+                    phone,
+                    satker_id: satker_id.unwrap_or(SUPERUSER_SATKER_ID),
+                    password,
+                    force,
+                },
+            )
+            .await,
+        ),
+        AdminCommand::ResetPassword { nrp, password } => {
+            finish(reset_password(db, nrp, password).await)
+        }
+        AdminCommand::UnbindDevice { device_id, nrp } => {
+            finish(unbind_device(db, device_id, nrp).await)
+        }
+        AdminCommand::RegenCalendar {
+            from,
+            to,
+            satker_id,
+        } => finish(regen_calendar(db, from, to, satker_id).await),
+        AdminCommand::GenerateTukin {
+            month,
+            satker_id,
+            force,
+        } => finish(generate_tukin(db, month, satker_id, force).await),
+        AdminCommand::ImportHolidays {
+            file,
+            scope,
+            satker_id,
+        } => finish(import_holidays(db, &file, scope, satker_id).await),
+        AdminCommand::PurgeSelfies {
+            older_than_days,
+            upload_dir,
+            dry_run,
+        } => finish(purge_old_selfies(db, older_than_days, upload_dir, dry_run).await),
+        AdminCommand::RunRetention {
+            upload_dir,
+            dry_run,
+        } => finish(run_retention_policy(db, upload_dir, dry_run).await),
+        AdminCommand::MigrateStorage {
+            upload_dir,
+            batch_size,
+            delete_local,
+            dry_run,
+        } => finish(migrate_storage(db, upload_dir, batch_size, dry_run, delete_local).await),
+    }
+}
+
+fn finish<T: Serialize>(result: Result<T, HttpError>) -> i32 {
+    match result {
+        Ok(data) => {
+            print_ok(&data);
+            0
+        }
+        Err(e) => {
+            print_error(&e.message);
+            1
+        }
+    }
+}
+
+fn db_err(e: sqlx::Error) -> HttpError {
+    HttpError::server_error(e.to_string())
+}
+
+/// Password dari argumen, atau baris pertama stdin (supaya tidak tersimpan di shell history).
+fn read_password(password: Option<String>) -> Result<String, HttpError> {
+    if let Some(p) = password {
+        return Ok(p);
+    }
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| HttpError::bad_request(format!("gagal membaca password dari stdin: {e}")))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn user_result(user: User) -> AdminUserResult {
+    AdminUserResult {
+        user_id: user.id,
+        satker_id: user.satker_id,
+        nrp: user.nrp,
+        full_name: user.full_name,
+        role: user.role,
+    }
+}
+
+struct SuperadminInput {
+    nrp: String,
+    full_name: String,
+    email: Option<String>,
+    phone: Option<String>,
+    satker_id: Uuid,
+    password: Option<String>,
+    force: bool,
+}
+
+async fn create_superadmin(
+    db: &DBClient,
+    input: SuperadminInput,
+) -> Result<AdminUserResult, HttpError> {
+    let SuperadminInput {
+        nrp,
+        full_name,
+        email,
+        phone,
+        satker_id,
+        password,
+        force,
+    } = input;
+    let nrp = nrp.trim().to_string();
+    let full_name = full_name.trim().to_string();
+    if nrp.is_empty() || full_name.is_empty() {
+        return Err(HttpError::bad_request("nrp dan full_name wajib diisi"));
+    }
+
+    // akun seed `superuser` (migration 001) tidak dihitung
+    let existing = db
+        .count_active_users_by_role(UserRole::Superadmin, SUPERUSER_USER_ID)
+        .await
+        .map_err(db_err)?;
+    if existing > 0 && !force {
+        return Err(HttpError::bad_request(format!(
+            "sudah ada {} SUPERADMIN aktif, pakai --force untuk tetap membuat",
+            existing
+        )));
+    }
+
+    if db
+        .find_user_by_nrp(nrp.clone())
+        .await
+        .map_err(db_err)?
+        .is_some()
+    {
+        return Err(HttpError::bad_request(format!("nrp {} sudah dipakai", nrp)));
+    }
+    db.find_satker_by_id(satker_id)
+        .await
+        .map_err(db_err)?
+        .ok_or_else(|| HttpError::bad_request(format!("satker {} tidak ditemukan", satker_id)))?;
+
+    let password_hash = hash_password(read_password(password)?)
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let user = db
+        .create_user(NewUser {
+            satker_id,
+            rank_id: None,
+            nrp,
+            full_name,
+            email,
+            phone,
+            role: UserRole::Superadmin,
+            password_hash,
+        })
+        .await
+        .map_err(db_err)?;
+
+    Ok(user_result(user))
+}
+
+async fn reset_password(
+    db: &DBClient,
+    nrp: String,
+    password: Option<String>,
+) -> Result<AdminUserResult, HttpError> {
+    let user = db
+        .find_user_by_nrp(nrp.trim().to_string())
+        .await
+        .map_err(db_err)?
+        .ok_or_else(|| {
+            HttpError::bad_request(format!("user dengan nrp {} tidak ditemukan", nrp))
+        })?;
+
+    let password_hash = hash_password(read_password(password)?)
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    db.update_password_hash(user.id, password_hash)
+        .await
+        .map_err(db_err)?;
+
+    Ok(user_result(user))
+}
+
+async fn unbind_device(
+    db: &DBClient,
+    device_id: Option<String>,
+    nrp: Option<String>,
+) -> Result<UnbindDeviceResult, HttpError> {
+    if let Some(device_id) = device_id {
+        let user_id = db.unbind_device(&device_id).await.map_err(db_err)?;
+        if user_id.is_none() {
+            return Err(HttpError::bad_request(format!(
+                "device {} tidak terdaftar",
+                device_id
+            )));
+        }
+        return Ok(UnbindDeviceResult {
+            user_id,
+            device_ids: vec![device_id],
+        });
+    }
+
+    let nrp = nrp.ok_or_else(|| HttpError::bad_request("--device-id atau --nrp wajib diisi"))?;
+    let user = db
+        .find_user_by_nrp(nrp.trim().to_string())
+        .await
+        .map_err(db_err)?
+        .ok_or_else(|| {
+            HttpError::bad_request(format!("user dengan nrp {} tidak ditemukan", nrp))
+        })?;
+
+    let device_ids = db.unbind_user_devices(user.id).await.map_err(db_err)?;
+    Ok(UnbindDeviceResult {
+        user_id: Some(user.id),
+        device_ids,
+    })
+}
+
+/// Satker target: satu satker (harus ada) atau semua satker aktif selain satker superuser.
+async fn target_satkers(db: &DBClient, satker_id: Option<Uuid>) -> Result<Vec<Satker>, HttpError> {
+    match satker_id {
+        Some(id) => {
+            let satker = db
+                .find_satker_by_id(id)
+                .await
+                .map_err(db_err)?
+                .ok_or_else(|| HttpError::bad_request(format!("satker {} tidak ditemukan", id)))?;
+            Ok(vec![satker])
+        }
+        None => Ok(db
+            .get_satker_all()
+            .await
+            .map_err(db_err)?
+            .into_iter()
+            .filter(|s| s.is_active && s.id != SUPERUSER_SATKER_ID)
+            .collect()),
+    }
+}
+
+async fn regen_calendar(
+    db: &DBClient,
+    from: NaiveDate,
+    to: NaiveDate,
+    satker_id: Option<Uuid>,
+) -> Result<CalendarRegenResult, HttpError> {
+    if to < from {
+        return Err(HttpError::bad_request("to: harus >= from"));
+    }
+
+    let mut satkers = Vec::new();
+    for satker in target_satkers(db, satker_id).await? {
+        // range panjang dipecah per MAX_CALENDAR_GENERATE_DAYS hari
+        let mut count = 0;
+        let mut error = None;
+        let mut chunk_start = from;
+        while chunk_start <= to {
+            let chunk_end = (chunk_start + Duration::days(MAX_CALENDAR_GENERATE_DAYS - 1)).min(to);
+            match generate_calendar_days(db, satker.id, chunk_start, chunk_end).await {
+                Ok(n) => count += n,
+                Err(e) => {
+                    error = Some(e.message);
+                    break;
+                }
+            }
+            chunk_start = chunk_end + Duration::days(1);
+        }
+
+        satkers.push(SatkerJobResult {
+            satker_id: satker.id,
+            satker_code: satker.code,
+            count,
+            error,
+        });
+    }
+
+    Ok(CalendarRegenResult { from, to, satkers })
+}
+
+async fn generate_tukin(
+    db: &DBClient,
+    month: String,
+    satker_id: Option<Uuid>,
+    force: bool,
+) -> Result<TukinGenerateResult, HttpError> {
+    parse_month(&month)?;
+    let tz = get_timezone_from_db(db).await?;
+
+    let mut satkers = Vec::new();
+    for satker in target_satkers(db, satker_id).await? {
+        let (count, error) =
+            match generate_tukin_calculations(db, tz, &month, Some(satker.id), None, force).await {
+                Ok(rows) => (rows.len() as i64, None),
+                Err(e) => (0, Some(e.message)),
+            };
+        satkers.push(SatkerJobResult {
+            satker_id: satker.id,
+            satker_code: satker.code,
+            count,
+            error,
+        });
+    }
+
+    Ok(TukinGenerateResult {
+        month,
+        force,
+        satkers,
+    })
+}
+
+async fn import_holidays(
+    db: &DBClient,
+    file: &Path,
+    scope: Option<HolidayScope>,
+    satker_id: Option<Uuid>,
+) -> Result<HolidayImportResult, HttpError> {
+    let data = tokio::fs::read(file)
+        .await
+        .map_err(|e| HttpError::bad_request(format!("{}: {}", file.display(), e)))?;
+
+    let is_csv = file
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+
+    let (scope, satker_id, items) = if is_csv {
+        let scope = scope.ok_or_else(|| HttpError::bad_request("--scope wajib untuk file .csv"))?;
+        (scope, satker_id, parse_holiday_csv(&data)?)
+    } else {
+        match serde_json::from_slice::<BulkHolidayReq>(&data) {
+            Ok(req) => (
+                scope.unwrap_or(req.scope),
+                satker_id.or(req.satker_id),
+                req.items,
+            ),
+            Err(_) => {
+                let items: Vec<BulkHolidayItem> = serde_json::from_slice(&data)
+                    .map_err(|e| HttpError::bad_request(format!("file json tidak valid: {}", e)))?;
+                let scope = scope.ok_or_else(|| {
+                    HttpError::bad_request("--scope wajib kalau file berisi array item")
+                })?;
+                (scope, satker_id, items)
+            }
+        }
+    };
+
+    if items.is_empty() {
+        return Err(HttpError::bad_request("file tidak berisi item libur"));
+    }
+
+    // validasi kombinasi scope/satker_id sama seperti endpoint bulk (sebagai SUPERADMIN)
+    authorize_holiday_scope_access(UserRole::Superadmin, SUPERUSER_SATKER_ID, scope, satker_id)?;
+
+    let count = items.len();
+    let items = build_holiday_upsert_items(items)?;
+    let from = items.iter().map(|it| it.holiday_date).min();
+    let to = items.iter().map(|it| it.holiday_date).max();
+    let affected_rows = db
+        .bulk_upsert_holidays(scope, satker_id, items)
+        .await
+        .map_err(db_err)?;
+
+    // diproses worker pada proses `serve`
+    if let Some((from, to)) = from.zip(to) {
+        enqueue_holiday_regen(db, scope, satker_id, from, to, "HOLIDAY_CLI_IMPORT").await;
+    }
+
+    Ok(HolidayImportResult {
+        scope,
+        satker_id,
+        items: count,
+        affected_rows,
+    })
+}
+
+/// Storage dari env yang sama dengan proses `serve`; `--upload-dir` menimpa UPLOAD_DIR.
+fn cli_storage(upload_dir: Option<PathBuf>) -> Result<Storage, HttpError> {
+    let mut config = StorageConfig::from_env();
+    if let Some(dir) = upload_dir {
+        config.upload_dir = dir;
+    }
+    build_storage(&config).map_err(HttpError::bad_request)
+}
+
+async fn purge_old_selfies(
+    db: &DBClient,
+    older_than_days: i64,
+    upload_dir: Option<PathBuf>,
+    dry_run: bool,
+) -> Result<SelfiePurgeReport, HttpError> {
+    if older_than_days < 1 {
+        return Err(HttpError::bad_request("--older-than-days minimal 1"));
+    }
+    let storage = cli_storage(upload_dir)?;
+    let before = Utc::now() - Duration::days(older_than_days);
+    purge_selfies(db, &storage, before, dry_run).await
+}
+
+async fn run_retention_policy(
+    db: &DBClient,
+    upload_dir: Option<PathBuf>,
+    dry_run: bool,
+) -> Result<RetentionReport, HttpError> {
+    let storage = cli_storage(upload_dir)?;
+    run_recorded_retention(db, &storage, RETENTION_RUN_MANUAL, None, dry_run, None)
+        .await?
+        .ok_or(HttpError::bad_request(
+            "purge retensi lain sedang berjalan, coba lagi nanti",
+        ))
+}
+
+async fn migrate_storage(
+    db: &DBClient,
+    upload_dir: Option<PathBuf>,
+    batch_size: i64,
+    dry_run: bool,
+    delete_local: bool,
+) -> Result<StorageMigrationReport, HttpError> {
+    let storage = cli_storage(upload_dir)?;
+    storage.check().await.map_err(HttpError::server_error)?;
+    migrate_local_objects(db, &storage, batch_size, dry_run, delete_local).await
+}
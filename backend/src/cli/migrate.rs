@@ -0,0 +1,62 @@
+use clap::Subcommand;
+
+use crate::cli::{print_error, print_ok};
+use crate::db::DBClient;
+use crate::services::migration::{baseline_migrations, migration_report, run_migrations};
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateCommand {
+    /// Terapkan semua migration yang pending
+    Run,
+    /// Tampilkan status migration (applied/pending) tanpa mengubah apa pun
+    Status,
+    /// Seperti `status`, tapi exit code 1 kalau schema belum up to date
+    Check,
+    /// Tandai migration s.d. VERSION sebagai sudah diterapkan tanpa menjalankannya
+    /// (untuk database lama yang migration-nya diterapkan manual)
+    Baseline {
+        #[arg(long)]
+        version: i64,
+    },
+}
+
+/// Return exit code proses.
+pub async fn run(cmd: MigrateCommand, db: &DBClient) -> i32 {
+    match cmd {
+        MigrateCommand::Run => match run_migrations(db).await {
+            Ok(report) => {
+                print_ok(&report);
+                0
+            }
+            Err(e) => {
+                print_error(&e);
+                1
+            }
+        },
+        MigrateCommand::Status | MigrateCommand::Check => match migration_report(db).await {
+            Ok(report) => {
+                let up_to_date = report.up_to_date;
+                print_ok(&report);
+                if matches!(cmd, MigrateCommand::Check) && !up_to_date {
+                    1
+                } else {
+                    0
+                }
+            }
+            Err(e) => {
+                print_error(&e.to_string());
+                1
+            }
+        },
+        MigrateCommand::Baseline { version } => match baseline_migrations(db, version).await {
+            Ok(result) => {
+                print_ok(&result);
+                0
+            }
+            Err(e) => {
+                print_error(&e);
+                1
+            }
+        },
+    }
+}
@@ -0,0 +1,46 @@
+//! Subcommand CLI `backend` (tanpa argumen = jalankan server).
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use serde_json::json;
+use std::io::Write;
+
+pub mod admin;
+pub mod migrate;
+
+#[derive(Debug, Parser)]
+#[command(name = "backend", about = "Backend absensi", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Jalankan HTTP server (default)
+    Serve,
+    /// Kelola migration schema database
+    #[command(subcommand)]
+    Migrate(migrate::MigrateCommand),
+    /// Tugas operasional admin (user, device, kalender, tukin, libur, selfie, storage)
+    #[command(subcommand)]
+    Admin(admin::AdminCommand),
+}
+
+/// Output CLI selalu JSON (satu dokumen di stdout) supaya mudah dipakai script:
+/// `{"status":"ok","data":...}` atau `{"status":"error","message":...}`.
+pub fn print_ok<T: Serialize>(data: &T) {
+    match serde_json::to_string_pretty(&json!({ "status": "ok", "data": data })) {
+        Ok(s) => write_stdout(&s),
+        Err(e) => print_error(&e.to_string()),
+    }
+}
+
+pub fn print_error(message: &str) {
+    write_stdout(&json!({ "status": "error", "message": message }).to_string());
+}
+
+/// Abaikan error tulis (mis. stdout di-pipe ke `head`) supaya tidak panic.
+fn write_stdout(s: &str) {
+    let _ = writeln!(std::io::stdout(), "{s}");
+}
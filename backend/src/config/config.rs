@@ -1,10 +1,25 @@
+use std::path::PathBuf;
+
 #[derive(Debug, Clone)]
 pub struct Config {
+    /// Penyimpanan file upload (selfie, foto, lampiran).
+    pub storage: StorageConfig,
     pub database_url: String,
     pub jwt_secret: String,
     pub redis_url: String,
     pub jwt_maxage: i64,
     pub port: u16,
+    /// Jika diset, GET /metrics wajib `Authorization: Bearer <token>`.
+    pub metrics_token: Option<String>,
+    /// AUTO_MIGRATE=true: terapkan migration yang pending saat server start.
+    pub auto_migrate: bool,
+    /// Base URL publik (mis. https://absensi.example.go.id) untuk link feed kalender.
+    pub public_base_url: Option<String>,
+    /// FACE_VERIFIER: implementasi verifikasi wajah server (`stub`); kosong / `off` = tidak aktif.
+    pub face_verifier: Option<String>,
+    /// ATTESTATION_VERIFIER: verifikasi attestation request absensi (`device_key`, default);
+    /// `off` = tidak diperiksa.
+    pub attestation_verifier: Option<String>,
 }
 
 impl Config {
@@ -23,13 +38,107 @@ impl Config {
         let port = std::env::var("PORT")
             .map(|v| v.parse::<u16>().unwrap_or(8000))
             .unwrap_or(8000);
+        let metrics_token = std::env::var("METRICS_TOKEN")
+            .ok()
+            .filter(|v| !v.trim().is_empty());
+        let auto_migrate = std::env::var("AUTO_MIGRATE")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+        let public_base_url = std::env::var("PUBLIC_BASE_URL")
+            .ok()
+            .map(|v| v.trim().trim_end_matches('/').to_string())
+            .filter(|v| !v.is_empty());
+        let face_verifier = std::env::var("FACE_VERIFIER")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        let attestation_verifier = std::env::var("ATTESTATION_VERIFIER")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
 
         Config {
+            storage: StorageConfig::from_env(),
             database_url,
             redis_url,
             jwt_secret,
             jwt_maxage,
             port,
+            metrics_token,
+            auto_migrate,
+            public_base_url,
+            face_verifier,
+            attestation_verifier,
         }
     }
 }
+
+/// STORAGE_BACKEND=local (default) | s3. Upload baru ditulis ke backend ini; key `local://`
+/// lama tetap dibaca dari UPLOAD_DIR sampai dipindah dengan `backend admin migrate-storage`.
+#[derive(Debug, Clone)]
+pub struct StorageConfig {
+    pub backend: String,
+    /// UPLOAD_DIR, default `./uploads`.
+    pub upload_dir: PathBuf,
+    pub s3: Option<S3Config>,
+    /// STORAGE_PRESIGN_TTL_SECS > 0: /files/* me-redirect ke presigned URL (khusus s3)
+    /// alih-alih meneruskan isi file lewat backend.
+    pub presign_ttl_secs: Option<u64>,
+}
+
+/// Bucket S3-compatible (AWS S3, MinIO, dll).
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    /// S3_ENDPOINT, mis. `http://minio:9000` atau `https://s3.ap-southeast-3.amazonaws.com`.
+    pub endpoint: String,
+    pub bucket: String,
+    /// S3_REGION, default `us-east-1` (MinIO).
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// S3_PATH_STYLE (default true): `endpoint/bucket/key`; false = `bucket.endpoint/key`.
+    pub path_style: bool,
+}
+
+impl StorageConfig {
+    pub fn from_env() -> Self {
+        let backend = env_opt("STORAGE_BACKEND")
+            .map(|v| v.to_ascii_lowercase())
+            .unwrap_or_else(|| "local".to_string());
+        let upload_dir = env_opt("UPLOAD_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("./uploads"));
+        let presign_ttl_secs = env_opt("STORAGE_PRESIGN_TTL_SECS")
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0);
+
+        // bucket lengkap baru dianggap terkonfigurasi; validasi per field saat storage dibangun
+        let s3 = match (env_opt("S3_ENDPOINT"), env_opt("S3_BUCKET")) {
+            (Some(endpoint), Some(bucket)) => Some(S3Config {
+                endpoint: endpoint.trim_end_matches('/').to_string(),
+                bucket,
+                region: env_opt("S3_REGION").unwrap_or_else(|| "us-east-1".to_string()),
+                access_key: env_opt("S3_ACCESS_KEY_ID").unwrap_or_default(),
+                secret_key: env_opt("S3_SECRET_ACCESS_KEY").unwrap_or_default(),
+                path_style: env_opt("S3_PATH_STYLE")
+                    .map(|v| !matches!(v.to_ascii_lowercase().as_str(), "0" | "false" | "no"))
+                    .unwrap_or(true),
+            }),
+            _ => None,
+        };
+
+        StorageConfig {
+            backend,
+            upload_dir,
+            s3,
+            presign_ttl_secs,
+        }
+    }
+}
+
+fn env_opt(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
@@ -58,7 +58,6 @@ impl FromStr for LeaveStatus {
     }
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum AttendanceStatus {
@@ -67,6 +66,17 @@ pub enum AttendanceStatus {
     Invalid,
 }
 
+impl AttendanceStatus {
+    /// Nilai kolom attendance_sessions.status.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttendanceStatus::Open => "OPEN",
+            AttendanceStatus::Closed => "CLOSED",
+            AttendanceStatus::Invalid => "INVALID",
+        }
+    }
+}
+
 impl FromStr for AttendanceStatus {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -88,6 +98,24 @@ impl FromStr for AttendanceStatus {
 pub enum AttendanceEventType {
     CheckIn,
     CheckOut,
+    BreakStart,
+    BreakEnd,
+    FieldOut,
+    FieldIn,
+}
+
+impl AttendanceEventType {
+    /// Nilai enum attendance_event_type (juga dipakai di pesan attestation).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttendanceEventType::CheckIn => "CHECK_IN",
+            AttendanceEventType::CheckOut => "CHECK_OUT",
+            AttendanceEventType::BreakStart => "BREAK_START",
+            AttendanceEventType::BreakEnd => "BREAK_END",
+            AttendanceEventType::FieldOut => "FIELD_OUT",
+            AttendanceEventType::FieldIn => "FIELD_IN",
+        }
+    }
 }
 
 impl FromStr for AttendanceEventType {
@@ -96,6 +124,10 @@ impl FromStr for AttendanceEventType {
         match s.to_uppercase().as_str() {
             "CHECK_IN" => Ok(AttendanceEventType::CheckIn),
             "CHECK_OUT" => Ok(AttendanceEventType::CheckOut),
+            "BREAK_START" => Ok(AttendanceEventType::BreakStart),
+            "BREAK_END" => Ok(AttendanceEventType::BreakEnd),
+            "FIELD_OUT" => Ok(AttendanceEventType::FieldOut),
+            "FIELD_IN" => Ok(AttendanceEventType::FieldIn),
             _ => Err(format!("bukan attendance event type yang valid {}", s)),
         }
     }
@@ -140,6 +172,20 @@ pub enum AttendanceLeaveType {
     Sakit,
 }
 
+impl AttendanceLeaveType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttendanceLeaveType::Normal => "NORMAL",
+            AttendanceLeaveType::JadwalDinas => "JADWAL_DINAS",
+            AttendanceLeaveType::DinasLuar => "DINAS_LUAR",
+            AttendanceLeaveType::Wfa => "WFA",
+            AttendanceLeaveType::Wfh => "WFH",
+            AttendanceLeaveType::Ijin => "IJIN",
+            AttendanceLeaveType::Sakit => "SAKIT",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[sqlx(type_name = "holiday_scope", rename_all = "SCREAMING_SNAKE_CASE")]
@@ -198,3 +244,13 @@ impl FromStr for CalendarDayType {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "work_pattern_mode", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WorkPatternMode {
+    /// Jam masuk/pulang tetap.
+    Fixed,
+    /// Jam inti + durasi kerja wajib per hari.
+    Flexi,
+}
@@ -1,9 +1,13 @@
 use async_trait::async_trait;
 use sqlx::Error;
+use sqlx::types::Json;
 use uuid::Uuid;
 
 use crate::db::DBClient;
-use crate::dtos::announcement::{AnnouncementDto, CreateAnnouncementReq, UpdateAnnouncementReq};
+use crate::dtos::announcement::{
+    AnnouncementAttachmentAccess, AnnouncementAttachmentDto, AnnouncementDto, BODY_FORMAT_PLAIN,
+    CreateAnnouncementReq, NewAnnouncementAttachment, UpdateAnnouncementReq,
+};
 
 #[async_trait]
 pub trait AnnouncementRepo {
@@ -17,13 +21,41 @@ pub trait AnnouncementRepo {
         satker_id: Uuid,
     ) -> Result<Vec<AnnouncementDto>, Error>;
     async fn find_announcement_by_id(&self, id: Uuid) -> Result<Option<AnnouncementDto>, Error>;
+    /// `req.body` / `req.body_format` sudah dinormalisasi handler, `body_text` = fallback plain-text.
     async fn create_announcement(
         &self,
         created_by: Uuid,
         req: CreateAnnouncementReq,
+        body_text: String,
     ) -> Result<Uuid, Error>;
-    async fn update_announcement(&self, id: Uuid, req: UpdateAnnouncementReq) -> Result<(), Error>;
+    async fn update_announcement(
+        &self,
+        id: Uuid,
+        req: UpdateAnnouncementReq,
+        body_text: Option<String>,
+    ) -> Result<(), Error>;
     async fn deactivate_announcement(&self, id: Uuid) -> Result<(), Error>;
+
+    // lampiran
+    async fn count_announcement_attachments(&self, announcement_id: Uuid) -> Result<i64, Error>;
+    /// Insert lampiran selama jumlahnya belum mencapai `max_attachments`.
+    /// Row pengumuman dikunci supaya upload paralel tidak bisa melewati batas;
+    /// `None` kalau batas sudah tercapai.
+    async fn add_announcement_attachment(
+        &self,
+        item: NewAnnouncementAttachment,
+        max_attachments: i64,
+    ) -> Result<Option<AnnouncementAttachmentDto>, Error>;
+    async fn find_announcement_attachment(
+        &self,
+        announcement_id: Uuid,
+        attachment_id: Uuid,
+    ) -> Result<Option<AnnouncementAttachmentDto>, Error>;
+    async fn delete_announcement_attachment(&self, attachment_id: Uuid) -> Result<(), Error>;
+    async fn find_announcement_attachment_access(
+        &self,
+        object_key: &str,
+    ) -> Result<Option<AnnouncementAttachmentAccess>, Error>;
 }
 
 #[async_trait]
@@ -43,7 +75,10 @@ impl AnnouncementRepo for DBClient {
                 s.code as "satker_code?",
                 a.title,
                 a.body,
+                a.body_format,
+                a.body_text,
                 a.is_active,
+                announcement_attachments_json(a.id) as "attachments!: Json<Vec<AnnouncementAttachmentDto>>",
                 a.created_by,
                 u.full_name as created_by_name,
                 a.created_at,
@@ -84,7 +119,10 @@ impl AnnouncementRepo for DBClient {
                     s.code as "satker_code?",
                     a.title,
                     a.body,
+                    a.body_format,
+                    a.body_text,
                     a.is_active,
+                    announcement_attachments_json(a.id) as "attachments!: Json<Vec<AnnouncementAttachmentDto>>",
                     a.created_by,
                     u.full_name as created_by_name,
                     a.created_at,
@@ -111,7 +149,10 @@ impl AnnouncementRepo for DBClient {
                 s.code as "satker_code?",
                 a.title,
                 a.body,
+                a.body_format,
+                a.body_text,
                 a.is_active,
+                announcement_attachments_json(a.id) as "attachments!: Json<Vec<AnnouncementAttachmentDto>>",
                 a.created_by,
                 u.full_name as created_by_name,
                 a.created_at,
@@ -143,7 +184,10 @@ impl AnnouncementRepo for DBClient {
                 s.code as "satker_code?",
                 a.title,
                 a.body,
+                a.body_format,
+                a.body_text,
                 a.is_active,
+                announcement_attachments_json(a.id) as "attachments!: Json<Vec<AnnouncementAttachmentDto>>",
                 a.created_by,
                 u.full_name as created_by_name,
                 a.created_at,
@@ -165,18 +209,25 @@ impl AnnouncementRepo for DBClient {
         &self,
         created_by: Uuid,
         req: CreateAnnouncementReq,
+        body_text: String,
     ) -> Result<Uuid, Error> {
         let is_active = req.is_active.unwrap_or(true);
+        let body_format = req
+            .body_format
+            .unwrap_or_else(|| BODY_FORMAT_PLAIN.to_string());
         let row = sqlx::query!(
             r#"
-            INSERT INTO announcements (scope, satker_id, title, body, is_active, created_by)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO announcements
+                (scope, satker_id, title, body, body_format, body_text, is_active, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             RETURNING id
             "#,
             req.scope,
             req.satker_id,
             req.title,
             req.body,
+            body_format,
+            body_text,
             is_active,
             created_by
         )
@@ -186,7 +237,12 @@ impl AnnouncementRepo for DBClient {
         Ok(row.id)
     }
 
-    async fn update_announcement(&self, id: Uuid, req: UpdateAnnouncementReq) -> Result<(), Error> {
+    async fn update_announcement(
+        &self,
+        id: Uuid,
+        req: UpdateAnnouncementReq,
+        body_text: Option<String>,
+    ) -> Result<(), Error> {
         // Keep existing fields when None.
         sqlx::query!(
             r#"
@@ -196,7 +252,9 @@ impl AnnouncementRepo for DBClient {
                 satker_id = COALESCE($3, satker_id),
                 title = COALESCE($4, title),
                 body = COALESCE($5, body),
-                is_active = COALESCE($6, is_active)
+                body_format = COALESCE($6, body_format),
+                body_text = COALESCE($7, body_text),
+                is_active = COALESCE($8, is_active)
             WHERE id = $1
             "#,
             id,
@@ -204,6 +262,8 @@ impl AnnouncementRepo for DBClient {
             req.satker_id,
             req.title,
             req.body,
+            req.body_format,
+            body_text,
             req.is_active
         )
         .execute(&self.pool)
@@ -221,4 +281,125 @@ impl AnnouncementRepo for DBClient {
         .await?;
         Ok(())
     }
+
+    async fn count_announcement_attachments(&self, announcement_id: Uuid) -> Result<i64, Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "cnt!"
+            FROM announcement_attachments
+            WHERE announcement_id = $1
+            "#,
+            announcement_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.cnt)
+    }
+
+    async fn add_announcement_attachment(
+        &self,
+        item: NewAnnouncementAttachment,
+        max_attachments: i64,
+    ) -> Result<Option<AnnouncementAttachmentDto>, Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"SELECT id FROM announcements WHERE id = $1 FOR UPDATE"#,
+            item.announcement_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "cnt!"
+            FROM announcement_attachments
+            WHERE announcement_id = $1
+            "#,
+            item.announcement_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if count >= max_attachments {
+            return Ok(None);
+        }
+
+        let row = sqlx::query_as!(
+            AnnouncementAttachmentDto,
+            r#"
+            INSERT INTO announcement_attachments
+                (announcement_id, object_key, file_name, content_type, size_bytes, uploaded_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, object_key, file_name, content_type, size_bytes, created_at
+            "#,
+            item.announcement_id,
+            item.object_key,
+            item.file_name,
+            item.content_type,
+            item.size_bytes,
+            item.uploaded_by
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(row))
+    }
+
+    async fn find_announcement_attachment(
+        &self,
+        announcement_id: Uuid,
+        attachment_id: Uuid,
+    ) -> Result<Option<AnnouncementAttachmentDto>, Error> {
+        let row = sqlx::query_as!(
+            AnnouncementAttachmentDto,
+            r#"
+            SELECT id, object_key, file_name, content_type, size_bytes, created_at
+            FROM announcement_attachments
+            WHERE id = $1 AND announcement_id = $2
+            "#,
+            attachment_id,
+            announcement_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn delete_announcement_attachment(&self, attachment_id: Uuid) -> Result<(), Error> {
+        sqlx::query!(
+            r#"DELETE FROM announcement_attachments WHERE id = $1"#,
+            attachment_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn find_announcement_attachment_access(
+        &self,
+        object_key: &str,
+    ) -> Result<Option<AnnouncementAttachmentAccess>, Error> {
+        let row = sqlx::query_as!(
+            AnnouncementAttachmentAccess,
+            r#"
+            SELECT
+                a.scope,
+                a.satker_id,
+                a.is_active,
+                x.file_name,
+                x.content_type
+            FROM announcement_attachments x
+            JOIN announcements a ON a.id = x.announcement_id
+            WHERE x.object_key = $1
+            "#,
+            object_key
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
 }
@@ -1,14 +1,18 @@
 use crate::DBClient;
-use crate::constants::{AttendanceEventType, AttendanceLeaveType};
+use crate::constants::{AttendanceEventType, AttendanceLeaveType, AttendanceStatus};
 use crate::dtos::attendance::AttendanceRekapDto;
+use crate::dtos::attendance_admin::{AttendanceAdminCountsDto, AttendanceListSort};
+use crate::dtos::attendance_session::{AttendanceAwayPeriodDto, AttendanceSegmentDto};
 use crate::models::AttendanceEvent;
 use async_trait::async_trait;
 use chrono::{DateTime, NaiveDate, Utc};
-use sqlx::Error;
+use sqlx::types::Json;
+use sqlx::{Error, PgConnection};
 use uuid::Uuid;
 
 pub struct AddAttendanceEvent {
     pub session_id: Uuid,
+    pub segment_id: Option<Uuid>,
     pub satker_id: Uuid,
     pub user_id: Uuid,
     pub event_type: AttendanceEventType,
@@ -21,6 +25,18 @@ pub struct AddAttendanceEvent {
     pub selfie_object_key: Option<String>,
     pub liveness_score: Option<f64>,
     pub face_match_score: Option<f64>,
+    /// Hasil verifikasi wajah di server; None = verifier tidak aktif.
+    pub server_face_score: Option<f64>,
+    pub face_verification_status: Option<String>,
+    /// Waktu ambil selfie dari EXIF (hasil olah upload).
+    pub selfie_captured_at: Option<DateTime<Utc>>,
+    /// dHash selfie untuk deteksi selfie dipakai ulang.
+    pub selfie_phash: Option<String>,
+    /// GPS | WIFI | BLE, dan penanda Wi-Fi/BLE yang cocok.
+    pub location_proof: Option<String>,
+    pub proximity_marker_id: Option<Uuid>,
+    /// Verifier attestation request (mis. device_key); None = tanpa attestation.
+    pub attestation: Option<String>,
 
     // Metadata Perangkat
     pub device_id: Option<String>,
@@ -33,11 +49,34 @@ pub struct AddAttendanceEvent {
 
     pub attendance_leave_type: AttendanceLeaveType,
     pub attendance_leave_notes: Option<String>,
+
+    // Istirahat / tugas lapangan
+    pub away_period_id: Option<Uuid>,
+    pub destination: Option<String>,
+    pub purpose: Option<String>,
+}
+
+/// Filter listing absensi admin; `satker_id` sudah di-scope sesuai role pemanggil.
+pub struct AttendanceListFilter<'a> {
+    pub satker_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub status: Option<AttendanceStatus>,
+    pub late_only: bool,
+    pub missing_checkout: bool,
+    pub out_of_geofence: bool,
+    pub manual_only: bool,
+    pub leave_type: Option<AttendanceLeaveType>,
+    /// Zona waktu aplikasi untuk membandingkan jam masuk kalender.
+    pub tz: &'a str,
+    /// Hari ini (lokal); hari ini belum dihitung lupa check-out.
+    pub today: NaiveDate,
 }
 
 #[async_trait]
 pub trait AttendanceEventRepo {
-    async fn add_attendance_event(&self, add_row: AddAttendanceEvent) -> Result<(), Error>;
+    async fn add_attendance_event(&self, add_row: AddAttendanceEvent) -> Result<Uuid, Error>;
 
     async fn find_attendance_event_by_session(
         &self,
@@ -58,63 +97,56 @@ pub trait AttendanceEventRepo {
         to: NaiveDate,
     ) -> Result<Vec<AttendanceRekapDto>, Error>;
 
+    /// Listing absensi lintas anggota (admin), keyset pagination berdasarkan `cursor`
+    /// (session_id baris terakhir halaman sebelumnya).
+    async fn list_attendance_admin(
+        &self,
+        filter: &AttendanceListFilter<'_>,
+        sort: AttendanceListSort,
+        desc: bool,
+        cursor: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<AttendanceRekapDto>, Error>;
+
+    /// Agregat seluruh session yang cocok dengan `filter`.
+    async fn count_attendance_admin(
+        &self,
+        filter: &AttendanceListFilter<'_>,
+    ) -> Result<AttendanceAdminCountsDto, Error>;
+
+    /// Cek `cursor` listing admin: session ada dan masuk rentang tanggal / satker / user `filter`.
+    async fn attendance_admin_cursor_exists(
+        &self,
+        filter: &AttendanceListFilter<'_>,
+        cursor: Uuid,
+    ) -> Result<bool, Error>;
+
     /// Delete an attendance event (CHECK_IN / CHECK_OUT) by session and type.
     async fn delete_attendance_event_by_session_type(
         &self,
         session_id: Uuid,
         event_type: AttendanceEventType,
     ) -> Result<u64, Error>;
+
+    /// Selfie key dari event sebelum `before` yang boleh dipurge (untuk dry-run).
+    async fn list_selfie_keys_before(&self, before: DateTime<Utc>) -> Result<Vec<String>, Error>;
+
+    /// Kosongkan selfie_object_key event sebelum `before` (tandai selfie_purged_at),
+    /// kecuali yang ditahan. Return key yang dikosongkan.
+    async fn clear_selfie_keys_before(&self, before: DateTime<Utc>) -> Result<Vec<String>, Error>;
+
+    /// Selfie key event sebelum `before` yang ditahan legal hold / anomali belum ditinjau.
+    async fn list_held_selfie_keys_before(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<Vec<String>, Error>;
 }
 
 #[async_trait]
 impl AttendanceEventRepo for DBClient {
-    async fn add_attendance_event(&self, add_row: AddAttendanceEvent) -> Result<(), Error> {
-        sqlx::query!(
-            r#"
-            INSERT INTO attendance_events (
-              session_id, satker_id, user_id, event_type, occurred_at,
-              latitude, longitude, accuracy_meters,
-              geofence_id, distance_to_fence_m,
-              selfie_object_key, liveness_score, face_match_score,
-              device_id, client_version, server_challenge_id,
-              device_model, android_version, app_build,
-              attendance_leave_type, attendance_leave_notes
-            )
-            VALUES (
-              $1, $2, $3, $4, $5,
-              $6, $7, $8,
-              $9, $10,
-              $11, $12, $13,
-              $14, $15, $16, $17, $18, $19,
-              $20, $21
-            )
-        "#,
-            add_row.session_id,
-            add_row.satker_id,
-            add_row.user_id,
-            add_row.event_type as AttendanceEventType,
-            add_row.now,
-            add_row.latitude,
-            add_row.longitude,
-            add_row.accuracy_meters,
-            add_row.geofence_id,
-            add_row.distance_to_fence_m,
-            add_row.selfie_object_key,
-            add_row.liveness_score,
-            add_row.face_match_score,
-            add_row.device_id,
-            add_row.client_version,
-            add_row.server_challenge_id,
-            add_row.device_model,
-            add_row.android_version,
-            add_row.app_build,
-            add_row.attendance_leave_type as AttendanceLeaveType,
-            add_row.attendance_leave_notes,
-        )
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
+    async fn add_attendance_event(&self, add_row: AddAttendanceEvent) -> Result<Uuid, Error> {
+        let mut conn = self.pool.acquire().await?;
+        insert_attendance_event(&mut conn, add_row).await
     }
 
     async fn find_attendance_event_by_session(
@@ -176,6 +208,7 @@ impl AttendanceEventRepo for DBClient {
                 u.nrp AS nrp,
                 st.name AS satker_name,
                 st.code AS satker_code,
+                s.status AS status,
                 s.check_in_at AS "check_in_at?",
                 s.check_out_at AS "check_out_at?",
                 ci.geofence_id AS "check_in_geofence_id?",
@@ -205,12 +238,91 @@ impl AttendanceEventRepo for DBClient {
 
                 s.is_manual AS "is_manual?",
                 s.manual_note AS "manual_note?",
-                s.manual_updated_at AS "manual_updated_at?"
+                s.manual_updated_at AS "manual_updated_at?",
+
+                (
+                    SELECT SUM(FLOOR(EXTRACT(EPOCH FROM (g.check_out_at - g.check_in_at)) / 60))::bigint
+                    FROM attendance_segments g
+                    WHERE g.session_id = s.id AND g.check_out_at IS NOT NULL
+                ) AS "worked_minutes?",
+                COALESCE(
+                    (
+                        SELECT json_agg(json_build_object(
+                            'seq', g.seq,
+                            'check_in_at', g.check_in_at,
+                            'check_out_at', g.check_out_at,
+                            'worked_minutes',
+                                FLOOR(EXTRACT(EPOCH FROM (g.check_out_at - g.check_in_at)) / 60)::bigint,
+                            'check_in_geofence_name', sgi.name,
+                            'check_out_geofence_name', sgo.name,
+                            'check_in_attendance_leave_type', sei.attendance_leave_type,
+                            'check_out_attendance_leave_type', seo.attendance_leave_type
+                        ) ORDER BY g.seq)
+                        FROM attendance_segments g
+                        LEFT JOIN attendance_events sei
+                            ON sei.segment_id = g.id AND sei.event_type = 'CHECK_IN'
+                        LEFT JOIN attendance_events seo
+                            ON seo.segment_id = g.id AND seo.event_type = 'CHECK_OUT'
+                        LEFT JOIN geofences sgi ON sei.geofence_id = sgi.id
+                        LEFT JOIN geofences sgo ON seo.geofence_id = sgo.id
+                        WHERE g.session_id = s.id
+                    ),
+                    '[]'::json
+                ) AS "segments!: Json<Vec<AttendanceSegmentDto>>",
+                (
+                    SELECT SUM(FLOOR(EXTRACT(EPOCH FROM (p.ended_at - p.started_at)) / 60))::bigint
+                    FROM attendance_away_periods p
+                    WHERE p.session_id = s.id AND p.kind = 'BREAK' AND p.ended_at IS NOT NULL
+                ) AS "break_minutes?",
+                (
+                    SELECT SUM(FLOOR(EXTRACT(EPOCH FROM (p.ended_at - p.started_at)) / 60))::bigint
+                    FROM attendance_away_periods p
+                    WHERE p.session_id = s.id AND p.kind = 'FIELD_TASK' AND p.ended_at IS NOT NULL
+                ) AS "field_task_minutes?",
+                COALESCE(
+                    (
+                        SELECT json_agg(json_build_object(
+                            'id', p.id,
+                            'kind', p.kind,
+                            'started_at', p.started_at,
+                            'ended_at', p.ended_at,
+                            'minutes',
+                                FLOOR(EXTRACT(EPOCH FROM (p.ended_at - p.started_at)) / 60)::bigint,
+                            'destination', p.destination,
+                            'purpose', p.purpose,
+                            'start_geofence_name', pgs.name,
+                            'start_distance_to_fence_m', pes.distance_to_fence_m,
+                            'end_geofence_name', pge.name,
+                            'end_distance_to_fence_m', pee.distance_to_fence_m
+                        ) ORDER BY p.started_at)
+                        FROM attendance_away_periods p
+                        LEFT JOIN attendance_events pes
+                            ON pes.away_period_id = p.id
+                            AND pes.event_type IN ('BREAK_START', 'FIELD_OUT')
+                        LEFT JOIN attendance_events pee
+                            ON pee.away_period_id = p.id
+                            AND pee.event_type IN ('BREAK_END', 'FIELD_IN')
+                        LEFT JOIN geofences pgs ON pes.geofence_id = pgs.id
+                        LEFT JOIN geofences pge ON pee.geofence_id = pge.id
+                        WHERE p.session_id = s.id
+                    ),
+                    '[]'::json
+                ) AS "away_periods!: Json<Vec<AttendanceAwayPeriodDto>>"
             FROM attendance_sessions s
             JOIN users u ON s.user_id=u.id
             JOIN satkers st ON s.satker_id=st.id
-            LEFT JOIN attendance_events ci ON s.id=ci.session_id AND ci.event_type = 'CHECK_IN'
-            LEFT JOIN attendance_events co ON s.id=co.session_id AND co.event_type = 'CHECK_OUT'
+            LEFT JOIN LATERAL (
+                SELECT * FROM attendance_events e
+                WHERE e.session_id = s.id AND e.event_type = 'CHECK_IN'
+                ORDER BY e.occurred_at ASC
+                LIMIT 1
+            ) ci ON TRUE
+            LEFT JOIN LATERAL (
+                SELECT * FROM attendance_events e
+                WHERE e.session_id = s.id AND e.event_type = 'CHECK_OUT'
+                ORDER BY e.occurred_at DESC
+                LIMIT 1
+            ) co ON TRUE
             LEFT JOIN geofences gci ON ci.geofence_id=gci.id
             LEFT JOIN geofences gco ON co.geofence_id=gco.id
             LEFT JOIN user_devices uci ON ci.device_id=uci.device_id
@@ -244,6 +356,7 @@ impl AttendanceEventRepo for DBClient {
                 u.nrp AS nrp,
                 st.name AS satker_name,
                 st.code AS satker_code,
+                s.status AS status,
                 s.check_in_at AS "check_in_at?",
                 s.check_out_at AS "check_out_at?",
                 ci.geofence_id AS "check_in_geofence_id?",
@@ -273,12 +386,91 @@ impl AttendanceEventRepo for DBClient {
 
                 s.is_manual AS "is_manual?",
                 s.manual_note AS "manual_note?",
-                s.manual_updated_at AS "manual_updated_at?"
+                s.manual_updated_at AS "manual_updated_at?",
+
+                (
+                    SELECT SUM(FLOOR(EXTRACT(EPOCH FROM (g.check_out_at - g.check_in_at)) / 60))::bigint
+                    FROM attendance_segments g
+                    WHERE g.session_id = s.id AND g.check_out_at IS NOT NULL
+                ) AS "worked_minutes?",
+                COALESCE(
+                    (
+                        SELECT json_agg(json_build_object(
+                            'seq', g.seq,
+                            'check_in_at', g.check_in_at,
+                            'check_out_at', g.check_out_at,
+                            'worked_minutes',
+                                FLOOR(EXTRACT(EPOCH FROM (g.check_out_at - g.check_in_at)) / 60)::bigint,
+                            'check_in_geofence_name', sgi.name,
+                            'check_out_geofence_name', sgo.name,
+                            'check_in_attendance_leave_type', sei.attendance_leave_type,
+                            'check_out_attendance_leave_type', seo.attendance_leave_type
+                        ) ORDER BY g.seq)
+                        FROM attendance_segments g
+                        LEFT JOIN attendance_events sei
+                            ON sei.segment_id = g.id AND sei.event_type = 'CHECK_IN'
+                        LEFT JOIN attendance_events seo
+                            ON seo.segment_id = g.id AND seo.event_type = 'CHECK_OUT'
+                        LEFT JOIN geofences sgi ON sei.geofence_id = sgi.id
+                        LEFT JOIN geofences sgo ON seo.geofence_id = sgo.id
+                        WHERE g.session_id = s.id
+                    ),
+                    '[]'::json
+                ) AS "segments!: Json<Vec<AttendanceSegmentDto>>",
+                (
+                    SELECT SUM(FLOOR(EXTRACT(EPOCH FROM (p.ended_at - p.started_at)) / 60))::bigint
+                    FROM attendance_away_periods p
+                    WHERE p.session_id = s.id AND p.kind = 'BREAK' AND p.ended_at IS NOT NULL
+                ) AS "break_minutes?",
+                (
+                    SELECT SUM(FLOOR(EXTRACT(EPOCH FROM (p.ended_at - p.started_at)) / 60))::bigint
+                    FROM attendance_away_periods p
+                    WHERE p.session_id = s.id AND p.kind = 'FIELD_TASK' AND p.ended_at IS NOT NULL
+                ) AS "field_task_minutes?",
+                COALESCE(
+                    (
+                        SELECT json_agg(json_build_object(
+                            'id', p.id,
+                            'kind', p.kind,
+                            'started_at', p.started_at,
+                            'ended_at', p.ended_at,
+                            'minutes',
+                                FLOOR(EXTRACT(EPOCH FROM (p.ended_at - p.started_at)) / 60)::bigint,
+                            'destination', p.destination,
+                            'purpose', p.purpose,
+                            'start_geofence_name', pgs.name,
+                            'start_distance_to_fence_m', pes.distance_to_fence_m,
+                            'end_geofence_name', pge.name,
+                            'end_distance_to_fence_m', pee.distance_to_fence_m
+                        ) ORDER BY p.started_at)
+                        FROM attendance_away_periods p
+                        LEFT JOIN attendance_events pes
+                            ON pes.away_period_id = p.id
+                            AND pes.event_type IN ('BREAK_START', 'FIELD_OUT')
+                        LEFT JOIN attendance_events pee
+                            ON pee.away_period_id = p.id
+                            AND pee.event_type IN ('BREAK_END', 'FIELD_IN')
+                        LEFT JOIN geofences pgs ON pes.geofence_id = pgs.id
+                        LEFT JOIN geofences pge ON pee.geofence_id = pge.id
+                        WHERE p.session_id = s.id
+                    ),
+                    '[]'::json
+                ) AS "away_periods!: Json<Vec<AttendanceAwayPeriodDto>>"
             FROM attendance_sessions s
             JOIN users u ON s.user_id=u.id
             JOIN satkers st ON s.satker_id=st.id
-            LEFT JOIN attendance_events ci ON s.id=ci.session_id AND ci.event_type = 'CHECK_IN'
-            LEFT JOIN attendance_events co ON s.id=co.session_id AND co.event_type = 'CHECK_OUT'
+            LEFT JOIN LATERAL (
+                SELECT * FROM attendance_events e
+                WHERE e.session_id = s.id AND e.event_type = 'CHECK_IN'
+                ORDER BY e.occurred_at ASC
+                LIMIT 1
+            ) ci ON TRUE
+            LEFT JOIN LATERAL (
+                SELECT * FROM attendance_events e
+                WHERE e.session_id = s.id AND e.event_type = 'CHECK_OUT'
+                ORDER BY e.occurred_at DESC
+                LIMIT 1
+            ) co ON TRUE
             LEFT JOIN geofences gci ON ci.geofence_id=gci.id
             LEFT JOIN geofences gco ON co.geofence_id=gco.id
             LEFT JOIN user_devices uci ON ci.device_id=uci.device_id
@@ -297,6 +489,338 @@ impl AttendanceEventRepo for DBClient {
         Ok(rows)
     }
 
+    async fn list_attendance_admin(
+        &self,
+        filter: &AttendanceListFilter<'_>,
+        sort: AttendanceListSort,
+        desc: bool,
+        cursor: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<AttendanceRekapDto>, Error> {
+        let rows = sqlx::query_as!(
+            AttendanceRekapDto,
+            r#"
+            SELECT
+                s.id AS session_id,
+                s.work_date AS work_date,
+                s.user_id AS user_id,
+                u.full_name AS full_name,
+                u.nrp AS nrp,
+                st.name AS satker_name,
+                st.code AS satker_code,
+                s.status AS status,
+                s.check_in_at AS "check_in_at?",
+                s.check_out_at AS "check_out_at?",
+                ci.geofence_id AS "check_in_geofence_id?",
+                co.geofence_id AS "check_out_geofence_id?",
+                ci.distance_to_fence_m AS "check_in_distance_to_fence_m?",
+                co.distance_to_fence_m AS "check_out_distance_to_fence_m?",
+                gci.name AS "check_in_geofence_name?",
+                gco.name AS "check_out_geofence_name?",
+                ci.latitude AS "check_in_latitude?",
+                ci.longitude AS "check_in_longitute?",
+                co.latitude AS "check_out_latitude?",
+                co.longitude AS "check_out_longitute?",
+                ci.selfie_object_key AS "check_in_selfie_object_key?",
+                co.selfie_object_key AS "check_out_selfie_object_key?",
+                ci.accuracy_meters AS "check_in_accuracy_meters?",
+                co.accuracy_meters AS "check_out_accuracy_meters?",
+                ci.attendance_leave_type AS "check_in_attendance_leave_type?: AttendanceLeaveType",
+                co.attendance_leave_type AS "check_out_attendance_leave_type?: AttendanceLeaveType",
+                ci.attendance_leave_notes AS "check_in_attendance_leave_notes?",
+                co.attendance_leave_notes AS "check_out_attendance_leave_notes?",
+                ci.device_id AS "check_in_device_id?",
+                co.device_id AS "check_out_device_id?",
+                ci.device_model AS "check_in_device_model?",
+                co.device_model AS "check_out_device_model?",
+                ui.full_name AS "check_in_device_name?",
+                uo.full_name AS "check_out_device_name?",
+
+                s.is_manual AS "is_manual?",
+                s.manual_note AS "manual_note?",
+                s.manual_updated_at AS "manual_updated_at?",
+
+                (
+                    SELECT SUM(FLOOR(EXTRACT(EPOCH FROM (g.check_out_at - g.check_in_at)) / 60))::bigint
+                    FROM attendance_segments g
+                    WHERE g.session_id = s.id AND g.check_out_at IS NOT NULL
+                ) AS "worked_minutes?",
+                COALESCE(
+                    (
+                        SELECT json_agg(json_build_object(
+                            'seq', g.seq,
+                            'check_in_at', g.check_in_at,
+                            'check_out_at', g.check_out_at,
+                            'worked_minutes',
+                                FLOOR(EXTRACT(EPOCH FROM (g.check_out_at - g.check_in_at)) / 60)::bigint,
+                            'check_in_geofence_name', sgi.name,
+                            'check_out_geofence_name', sgo.name,
+                            'check_in_attendance_leave_type', sei.attendance_leave_type,
+                            'check_out_attendance_leave_type', seo.attendance_leave_type
+                        ) ORDER BY g.seq)
+                        FROM attendance_segments g
+                        LEFT JOIN attendance_events sei
+                            ON sei.segment_id = g.id AND sei.event_type = 'CHECK_IN'
+                        LEFT JOIN attendance_events seo
+                            ON seo.segment_id = g.id AND seo.event_type = 'CHECK_OUT'
+                        LEFT JOIN geofences sgi ON sei.geofence_id = sgi.id
+                        LEFT JOIN geofences sgo ON seo.geofence_id = sgo.id
+                        WHERE g.session_id = s.id
+                    ),
+                    '[]'::json
+                ) AS "segments!: Json<Vec<AttendanceSegmentDto>>",
+                (
+                    SELECT SUM(FLOOR(EXTRACT(EPOCH FROM (p.ended_at - p.started_at)) / 60))::bigint
+                    FROM attendance_away_periods p
+                    WHERE p.session_id = s.id AND p.kind = 'BREAK' AND p.ended_at IS NOT NULL
+                ) AS "break_minutes?",
+                (
+                    SELECT SUM(FLOOR(EXTRACT(EPOCH FROM (p.ended_at - p.started_at)) / 60))::bigint
+                    FROM attendance_away_periods p
+                    WHERE p.session_id = s.id AND p.kind = 'FIELD_TASK' AND p.ended_at IS NOT NULL
+                ) AS "field_task_minutes?",
+                COALESCE(
+                    (
+                        SELECT json_agg(json_build_object(
+                            'id', p.id,
+                            'kind', p.kind,
+                            'started_at', p.started_at,
+                            'ended_at', p.ended_at,
+                            'minutes',
+                                FLOOR(EXTRACT(EPOCH FROM (p.ended_at - p.started_at)) / 60)::bigint,
+                            'destination', p.destination,
+                            'purpose', p.purpose,
+                            'start_geofence_name', pgs.name,
+                            'start_distance_to_fence_m', pes.distance_to_fence_m,
+                            'end_geofence_name', pge.name,
+                            'end_distance_to_fence_m', pee.distance_to_fence_m
+                        ) ORDER BY p.started_at)
+                        FROM attendance_away_periods p
+                        LEFT JOIN attendance_events pes
+                            ON pes.away_period_id = p.id
+                            AND pes.event_type IN ('BREAK_START', 'FIELD_OUT')
+                        LEFT JOIN attendance_events pee
+                            ON pee.away_period_id = p.id
+                            AND pee.event_type IN ('BREAK_END', 'FIELD_IN')
+                        LEFT JOIN geofences pgs ON pes.geofence_id = pgs.id
+                        LEFT JOIN geofences pge ON pee.geofence_id = pge.id
+                        WHERE p.session_id = s.id
+                    ),
+                    '[]'::json
+                ) AS "away_periods!: Json<Vec<AttendanceAwayPeriodDto>>"
+            FROM attendance_sessions s
+            JOIN users u ON s.user_id=u.id
+            JOIN satkers st ON s.satker_id=st.id
+            LEFT JOIN LATERAL (
+                SELECT * FROM attendance_events e
+                WHERE e.session_id = s.id AND e.event_type = 'CHECK_IN'
+                ORDER BY e.occurred_at ASC
+                LIMIT 1
+            ) ci ON TRUE
+            LEFT JOIN LATERAL (
+                SELECT * FROM attendance_events e
+                WHERE e.session_id = s.id AND e.event_type = 'CHECK_OUT'
+                ORDER BY e.occurred_at DESC
+                LIMIT 1
+            ) co ON TRUE
+            LEFT JOIN geofences gci ON ci.geofence_id=gci.id
+            LEFT JOIN geofences gco ON co.geofence_id=gco.id
+            LEFT JOIN user_devices uci ON ci.device_id=uci.device_id
+            LEFT JOIN user_devices uco ON co.device_id=uco.device_id
+            LEFT JOIN users ui ON uci.user_id=ui.id
+            LEFT JOIN users uo ON uco.user_id=uo.id
+            LEFT JOIN satker_calendar_days cd
+                ON cd.satker_id = s.satker_id AND cd.work_date = s.work_date
+            CROSS JOIN LATERAL (
+                SELECT
+                    (
+                        COALESCE(ci.attendance_leave_type, 'NORMAL') = 'NORMAL'
+                        AND s.check_in_at > (s.work_date + cd.expected_start) AT TIME ZONE $11
+                    ) IS TRUE AS is_late,
+                    (
+                        s.check_in_at IS NOT NULL
+                        AND s.check_out_at IS NULL
+                        AND s.work_date < $12
+                    ) AS is_missing_checkout,
+                    (
+                        ci.distance_to_fence_m > gci.radius_meters
+                        OR co.distance_to_fence_m > gco.radius_meters
+                    ) IS TRUE AS is_out_of_fence,
+                    CASE $13
+                        WHEN 'full_name' THEN lower(u.full_name) || '|' || s.work_date::text
+                        WHEN 'check_in_at' THEN COALESCE(
+                            to_char(s.check_in_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.US'),
+                            ''
+                        )
+                        ELSE s.work_date::text || '|' || lower(u.full_name)
+                    END COLLATE "C" AS sort_key
+            ) f
+            LEFT JOIN (
+                SELECT CASE $13
+                    WHEN 'full_name' THEN lower(u2.full_name) || '|' || s2.work_date::text
+                    WHEN 'check_in_at' THEN COALESCE(
+                        to_char(s2.check_in_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.US'),
+                        ''
+                    )
+                    ELSE s2.work_date::text || '|' || lower(u2.full_name)
+                END COLLATE "C" AS sort_key
+                FROM attendance_sessions s2
+                JOIN users u2 ON u2.id = s2.user_id
+                WHERE s2.id = $15
+            ) cur ON TRUE
+            WHERE s.work_date >= $1 AND s.work_date <= $2
+              AND ($3::uuid IS NULL OR s.satker_id = $3)
+              AND ($4::uuid IS NULL OR s.user_id = $4)
+              AND ($5::text IS NULL OR s.status = $5)
+              AND (NOT $6 OR f.is_late)
+              AND (NOT $7 OR f.is_missing_checkout)
+              AND (NOT $8 OR f.is_out_of_fence)
+              AND (NOT $9 OR s.is_manual)
+              AND ($10::attendance_leave_type IS NULL OR ci.attendance_leave_type = $10)
+              AND (
+                  $15::uuid IS NULL
+                  OR CASE
+                      WHEN $14 THEN (f.sort_key, s.id) < (cur.sort_key, $15)
+                      ELSE (f.sort_key, s.id) > (cur.sort_key, $15)
+                  END
+              )
+            ORDER BY
+                CASE WHEN $14 THEN f.sort_key END DESC,
+                CASE WHEN $14 THEN s.id END DESC,
+                CASE WHEN NOT $14 THEN f.sort_key END ASC,
+                CASE WHEN NOT $14 THEN s.id END ASC
+            LIMIT $16
+            "#,
+            filter.from,
+            filter.to,
+            filter.satker_id,
+            filter.user_id,
+            filter.status.as_ref().map(|st| st.as_str()),
+            filter.late_only,
+            filter.missing_checkout,
+            filter.out_of_geofence,
+            filter.manual_only,
+            filter.leave_type as Option<AttendanceLeaveType>,
+            filter.tz,
+            filter.today,
+            sort.as_str(),
+            desc,
+            cursor,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn count_attendance_admin(
+        &self,
+        filter: &AttendanceListFilter<'_>,
+    ) -> Result<AttendanceAdminCountsDto, Error> {
+        let row = sqlx::query_as!(
+            AttendanceAdminCountsDto,
+            r#"
+            SELECT
+                COUNT(*) AS "total!",
+                COUNT(*) FILTER (WHERE s.status = 'OPEN') AS "open!",
+                COUNT(*) FILTER (WHERE s.status = 'CLOSED') AS "closed!",
+                COUNT(*) FILTER (WHERE s.status = 'INVALID') AS "invalid!",
+                COUNT(*) FILTER (WHERE f.is_late) AS "late!",
+                COUNT(*) FILTER (WHERE f.is_missing_checkout) AS "missing_checkout!",
+                COUNT(*) FILTER (WHERE f.is_out_of_fence) AS "out_of_geofence!",
+                COUNT(*) FILTER (WHERE s.is_manual) AS "manual!",
+                COUNT(*) FILTER (
+                    WHERE COALESCE(ci.attendance_leave_type, 'NORMAL') <> 'NORMAL'
+                ) AS "leave!"
+            FROM attendance_sessions s
+            LEFT JOIN LATERAL (
+                SELECT e.attendance_leave_type, e.distance_to_fence_m, e.geofence_id
+                FROM attendance_events e
+                WHERE e.session_id = s.id AND e.event_type = 'CHECK_IN'
+                ORDER BY e.occurred_at ASC
+                LIMIT 1
+            ) ci ON TRUE
+            LEFT JOIN LATERAL (
+                SELECT e.distance_to_fence_m, e.geofence_id
+                FROM attendance_events e
+                WHERE e.session_id = s.id AND e.event_type = 'CHECK_OUT'
+                ORDER BY e.occurred_at DESC
+                LIMIT 1
+            ) co ON TRUE
+            LEFT JOIN geofences gci ON ci.geofence_id=gci.id
+            LEFT JOIN geofences gco ON co.geofence_id=gco.id
+            LEFT JOIN satker_calendar_days cd
+                ON cd.satker_id = s.satker_id AND cd.work_date = s.work_date
+            CROSS JOIN LATERAL (
+                SELECT
+                    (
+                        COALESCE(ci.attendance_leave_type, 'NORMAL') = 'NORMAL'
+                        AND s.check_in_at > (s.work_date + cd.expected_start) AT TIME ZONE $11
+                    ) IS TRUE AS is_late,
+                    (
+                        s.check_in_at IS NOT NULL
+                        AND s.check_out_at IS NULL
+                        AND s.work_date < $12
+                    ) AS is_missing_checkout,
+                    (
+                        ci.distance_to_fence_m > gci.radius_meters
+                        OR co.distance_to_fence_m > gco.radius_meters
+                    ) IS TRUE AS is_out_of_fence
+            ) f
+            WHERE s.work_date >= $1 AND s.work_date <= $2
+              AND ($3::uuid IS NULL OR s.satker_id = $3)
+              AND ($4::uuid IS NULL OR s.user_id = $4)
+              AND ($5::text IS NULL OR s.status = $5)
+              AND (NOT $6 OR f.is_late)
+              AND (NOT $7 OR f.is_missing_checkout)
+              AND (NOT $8 OR f.is_out_of_fence)
+              AND (NOT $9 OR s.is_manual)
+              AND ($10::attendance_leave_type IS NULL OR ci.attendance_leave_type = $10)
+            "#,
+            filter.from,
+            filter.to,
+            filter.satker_id,
+            filter.user_id,
+            filter.status.as_ref().map(|st| st.as_str()),
+            filter.late_only,
+            filter.missing_checkout,
+            filter.out_of_geofence,
+            filter.manual_only,
+            filter.leave_type as Option<AttendanceLeaveType>,
+            filter.tz,
+            filter.today
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    async fn attendance_admin_cursor_exists(
+        &self,
+        filter: &AttendanceListFilter<'_>,
+        cursor: Uuid,
+    ) -> Result<bool, Error> {
+        let exists = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM attendance_sessions s
+                WHERE s.id = $1
+                  AND s.work_date >= $2 AND s.work_date <= $3
+                  AND ($4::uuid IS NULL OR s.satker_id = $4)
+                  AND ($5::uuid IS NULL OR s.user_id = $5)
+            ) AS "exists!"
+            "#,
+            cursor,
+            filter.from,
+            filter.to,
+            filter.satker_id,
+            filter.user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(exists)
+    }
+
     async fn delete_attendance_event_by_session_type(
         &self,
         session_id: Uuid,
@@ -312,4 +836,133 @@ impl AttendanceEventRepo for DBClient {
 
         Ok(res.rows_affected())
     }
+
+    async fn list_selfie_keys_before(&self, before: DateTime<Utc>) -> Result<Vec<String>, Error> {
+        let keys = sqlx::query_scalar!(
+            r#"
+            SELECT selfie_object_key AS "selfie_object_key!"
+            FROM attendance_events
+            WHERE occurred_at < $1 AND selfie_object_key IS NOT NULL
+              AND NOT attendance_event_held(id)
+            ORDER BY occurred_at
+            "#,
+            before
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(keys)
+    }
+
+    async fn clear_selfie_keys_before(&self, before: DateTime<Utc>) -> Result<Vec<String>, Error> {
+        let keys = sqlx::query_scalar!(
+            r#"
+            WITH purged AS (
+                SELECT id, selfie_object_key
+                FROM attendance_events
+                WHERE occurred_at < $1 AND selfie_object_key IS NOT NULL
+                  AND NOT attendance_event_held(id)
+                FOR UPDATE
+            )
+            UPDATE attendance_events e
+            SET selfie_object_key = NULL, selfie_purged_at = now()
+            FROM purged
+            WHERE e.id = purged.id
+            RETURNING purged.selfie_object_key AS "selfie_object_key!"
+            "#,
+            before
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(keys)
+    }
+    async fn list_held_selfie_keys_before(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<Vec<String>, Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT selfie_object_key AS "selfie_object_key!"
+            FROM attendance_events
+            WHERE occurred_at < $1 AND selfie_object_key IS NOT NULL
+              AND attendance_event_held(id)
+            "#,
+            before
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+/// Insert event absensi di koneksi/transaksi milik pemanggil, supaya bisa satu transaksi
+/// dengan buka/tutup segmen.
+pub async fn insert_attendance_event(
+    conn: &mut PgConnection,
+    add_row: AddAttendanceEvent,
+) -> Result<Uuid, Error> {
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO attendance_events (
+          session_id, satker_id, user_id, event_type, occurred_at,
+          latitude, longitude, accuracy_meters,
+          geofence_id, distance_to_fence_m,
+          selfie_object_key, liveness_score, face_match_score,
+          device_id, client_version, server_challenge_id,
+          device_model, android_version, app_build,
+          attendance_leave_type, attendance_leave_notes,
+          segment_id, away_period_id, destination, purpose,
+          server_face_score, face_verification_status, selfie_phash,
+          location_proof, proximity_marker_id, attestation, selfie_captured_at
+        )
+        VALUES (
+          $1, $2, $3, $4, $5,
+          $6, $7, $8,
+          $9, $10,
+          $11, $12, $13,
+          $14, $15, $16, $17, $18, $19,
+          $20, $21,
+          $22, $23, $24, $25,
+          $26, $27, $28,
+          $29, $30, $31, $32
+        )
+        RETURNING id
+    "#,
+        add_row.session_id,
+        add_row.satker_id,
+        add_row.user_id,
+        add_row.event_type as AttendanceEventType,
+        add_row.now,
+        add_row.latitude,
+        add_row.longitude,
+        add_row.accuracy_meters,
+        add_row.geofence_id,
+        add_row.distance_to_fence_m,
+        add_row.selfie_object_key,
+        add_row.liveness_score,
+        add_row.face_match_score,
+        add_row.device_id,
+        add_row.client_version,
+        add_row.server_challenge_id,
+        add_row.device_model,
+        add_row.android_version,
+        add_row.app_build,
+        add_row.attendance_leave_type as AttendanceLeaveType,
+        add_row.attendance_leave_notes,
+        add_row.segment_id,
+        add_row.away_period_id,
+        add_row.destination,
+        add_row.purpose,
+        add_row.server_face_score,
+        add_row.face_verification_status,
+        add_row.selfie_phash,
+        add_row.location_proof,
+        add_row.proximity_marker_id,
+        add_row.attestation,
+        add_row.selfie_captured_at,
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(id)
 }
@@ -0,0 +1,310 @@
+use crate::DBClient;
+use crate::constants::{AttendanceEventType, AttendanceStatus};
+use crate::dtos::attendance_anomaly::{
+    ANOMALY_PENDING, AttendanceAnomalyDto, AttendanceAnomalyLockRow,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{Error, PgConnection};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait AttendanceAnomalyRepo {
+    async fn create_attendance_anomaly(
+        &self,
+        event_id: Uuid,
+        session_id: Uuid,
+        satker_id: Uuid,
+        user_id: Uuid,
+        event_type: AttendanceEventType,
+        reasons: &[String],
+    ) -> Result<Uuid, Error>;
+
+    async fn list_attendance_anomalies(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        status: Option<&str>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Vec<AttendanceAnomalyDto>, Error>;
+
+    async fn find_attendance_anomaly(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<AttendanceAnomalyDto>, Error>;
+
+    /// Ada event user lain dengan device ini sejak `since`?
+    async fn device_used_by_other_user(
+        &self,
+        device_id: &str,
+        user_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<bool, Error>;
+
+    /// Ada event (user mana pun) dengan selfie ber-hash sama sejak `since`?
+    async fn selfie_phash_used(&self, phash: &str, since: DateTime<Utc>) -> Result<bool, Error>;
+
+    /// Koordinat event terakhir user per sesi (hari) selain `exclude_session_id`, terbaru dulu.
+    async fn list_recent_event_coordinates(
+        &self,
+        user_id: Uuid,
+        event_type: AttendanceEventType,
+        exclude_session_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<(Option<f64>, Option<f64>)>, Error>;
+}
+
+#[async_trait]
+impl AttendanceAnomalyRepo for DBClient {
+    async fn create_attendance_anomaly(
+        &self,
+        event_id: Uuid,
+        session_id: Uuid,
+        satker_id: Uuid,
+        user_id: Uuid,
+        event_type: AttendanceEventType,
+        reasons: &[String],
+    ) -> Result<Uuid, Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO attendance_anomalies
+                (event_id, session_id, satker_id, user_id, event_type, reasons, status)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
+            "#,
+            event_id,
+            session_id,
+            satker_id,
+            user_id,
+            event_type as AttendanceEventType,
+            reasons,
+            ANOMALY_PENDING
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn list_attendance_anomalies(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        status: Option<&str>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Vec<AttendanceAnomalyDto>, Error> {
+        let rows = sqlx::query_as!(
+            AttendanceAnomalyDto,
+            r#"
+            SELECT a.id, a.event_id, a.session_id,
+                   s.work_date, s.status AS session_status,
+                   a.satker_id, a.user_id,
+                   u.full_name AS user_full_name, u.nrp AS user_nrp,
+                   a.event_type AS "event_type: AttendanceEventType",
+                   e.occurred_at, e.latitude, e.longitude, e.distance_to_fence_m,
+                   e.selfie_object_key, e.face_match_score, e.server_face_score, e.device_id,
+                   a.reasons, a.status, a.review_note,
+                   a.reviewed_by, r.full_name AS "reviewed_by_name?", a.reviewed_at,
+                   a.created_at
+            FROM attendance_anomalies a
+            JOIN attendance_events e ON e.id = a.event_id
+            JOIN attendance_sessions s ON s.id = a.session_id
+            JOIN users u ON u.id = a.user_id
+            LEFT JOIN users r ON r.id = a.reviewed_by
+            WHERE ($1::uuid IS NULL OR a.satker_id = $1)
+              AND ($2::uuid IS NULL OR a.user_id = $2)
+              AND ($3::text IS NULL OR a.status = $3)
+              AND ($4::date IS NULL OR s.work_date >= $4)
+              AND ($5::date IS NULL OR s.work_date <= $5)
+            ORDER BY a.created_at DESC
+            "#,
+            satker_id,
+            user_id,
+            status,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn find_attendance_anomaly(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<AttendanceAnomalyDto>, Error> {
+        let row = sqlx::query_as!(
+            AttendanceAnomalyDto,
+            r#"
+            SELECT a.id, a.event_id, a.session_id,
+                   s.work_date, s.status AS session_status,
+                   a.satker_id, a.user_id,
+                   u.full_name AS user_full_name, u.nrp AS user_nrp,
+                   a.event_type AS "event_type: AttendanceEventType",
+                   e.occurred_at, e.latitude, e.longitude, e.distance_to_fence_m,
+                   e.selfie_object_key, e.face_match_score, e.server_face_score, e.device_id,
+                   a.reasons, a.status, a.review_note,
+                   a.reviewed_by, r.full_name AS "reviewed_by_name?", a.reviewed_at,
+                   a.created_at
+            FROM attendance_anomalies a
+            JOIN attendance_events e ON e.id = a.event_id
+            JOIN attendance_sessions s ON s.id = a.session_id
+            JOIN users u ON u.id = a.user_id
+            LEFT JOIN users r ON r.id = a.reviewed_by
+            WHERE a.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn device_used_by_other_user(
+        &self,
+        device_id: &str,
+        user_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<bool, Error> {
+        let found = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM attendance_events
+                WHERE device_id = $1 AND user_id <> $2 AND occurred_at >= $3
+            ) AS "found!"
+            "#,
+            device_id,
+            user_id,
+            since
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(found)
+    }
+
+    async fn selfie_phash_used(&self, phash: &str, since: DateTime<Utc>) -> Result<bool, Error> {
+        let found = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM attendance_events
+                WHERE selfie_phash = $1 AND occurred_at >= $2
+            ) AS "found!"
+            "#,
+            phash,
+            since
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(found)
+    }
+
+    async fn list_recent_event_coordinates(
+        &self,
+        user_id: Uuid,
+        event_type: AttendanceEventType,
+        exclude_session_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<(Option<f64>, Option<f64>)>, Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT latitude, longitude
+            FROM (
+                SELECT DISTINCT ON (session_id) latitude, longitude, occurred_at
+                FROM attendance_events
+                WHERE user_id = $1 AND event_type = $2 AND session_id <> $3
+                ORDER BY session_id, occurred_at DESC
+            ) e
+            ORDER BY occurred_at DESC
+            LIMIT $4
+            "#,
+            user_id,
+            event_type as AttendanceEventType,
+            exclude_session_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.latitude, r.longitude))
+            .collect())
+    }
+}
+
+pub async fn lock_attendance_anomaly(
+    conn: &mut PgConnection,
+    id: Uuid,
+) -> Result<Option<AttendanceAnomalyLockRow>, Error> {
+    let row = sqlx::query_as!(
+        AttendanceAnomalyLockRow,
+        r#"
+        SELECT session_id, satker_id, status
+        FROM attendance_anomalies
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(row)
+}
+
+pub async fn update_attendance_anomaly_status(
+    conn: &mut PgConnection,
+    id: Uuid,
+    to_status: &str,
+    reviewed_by: Uuid,
+    review_note: Option<&str>,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE attendance_anomalies
+        SET status = $2,
+            review_note = $4,
+            reviewed_by = $3,
+            reviewed_at = now(),
+            updated_at = now()
+        WHERE id = $1
+        "#,
+        id,
+        to_status,
+        reviewed_by,
+        review_note
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn set_attendance_session_status(
+    conn: &mut PgConnection,
+    session_id: Uuid,
+    status: AttendanceStatus,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE attendance_sessions
+        SET status = $2,
+            updated_at = now()
+        WHERE id = $1
+        "#,
+        session_id,
+        status.as_str()
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
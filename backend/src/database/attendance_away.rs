@@ -0,0 +1,203 @@
+use crate::db::DBClient;
+use crate::dtos::attendance_session::AttendanceAwayPeriodDto;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::Error;
+use uuid::Uuid;
+
+/// Periode istirahat / tugas lapangan baru (dibuka oleh BREAK_START / FIELD_OUT).
+#[derive(Debug, Clone)]
+pub struct AttendanceAwayStart {
+    pub session_id: Uuid,
+    pub segment_id: Uuid,
+    pub kind: &'static str,
+    pub now: DateTime<Utc>,
+    pub destination: Option<String>,
+    pub purpose: Option<String>,
+}
+
+#[async_trait]
+pub trait AttendanceAwayRepo {
+    /// Buka periode away. Gagal (unique violation) kalau masih ada periode terbuka.
+    async fn start_attendance_away(&self, item: AttendanceAwayStart) -> Result<Uuid, Error>;
+
+    /// Tutup periode away terbuka dengan `kind` tsb. None = tidak ada yang terbuka.
+    async fn end_attendance_away(
+        &self,
+        session_id: Uuid,
+        kind: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Uuid>, Error>;
+
+    async fn find_open_attendance_away(
+        &self,
+        session_id: Uuid,
+    ) -> Result<Option<AttendanceAwayPeriodDto>, Error>;
+
+    async fn find_attendance_away(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<AttendanceAwayPeriodDto>, Error>;
+
+    async fn list_attendance_away_periods(
+        &self,
+        session_id: Uuid,
+    ) -> Result<Vec<AttendanceAwayPeriodDto>, Error>;
+}
+
+#[async_trait]
+impl AttendanceAwayRepo for DBClient {
+    async fn start_attendance_away(&self, item: AttendanceAwayStart) -> Result<Uuid, Error> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO attendance_away_periods (
+                session_id, segment_id, kind, started_at, destination, purpose
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id
+            "#,
+            item.session_id,
+            item.segment_id,
+            item.kind,
+            item.now,
+            item.destination,
+            item.purpose
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.id)
+    }
+
+    async fn end_attendance_away(
+        &self,
+        session_id: Uuid,
+        kind: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Uuid>, Error> {
+        let row = sqlx::query!(
+            r#"
+            UPDATE attendance_away_periods
+            SET ended_at = $3,
+                updated_at = now()
+            WHERE session_id = $1 AND kind = $2 AND ended_at IS NULL
+            RETURNING id
+            "#,
+            session_id,
+            kind,
+            now
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| r.id))
+    }
+
+    async fn find_open_attendance_away(
+        &self,
+        session_id: Uuid,
+    ) -> Result<Option<AttendanceAwayPeriodDto>, Error> {
+        let row = sqlx::query_as!(
+            AttendanceAwayPeriodDto,
+            r#"
+            SELECT
+                p.id,
+                p.kind,
+                p.started_at,
+                p.ended_at,
+                NULL::bigint AS "minutes?",
+                p.destination,
+                p.purpose,
+                gs.name AS "start_geofence_name?",
+                es.distance_to_fence_m AS "start_distance_to_fence_m?",
+                NULL::text AS "end_geofence_name?",
+                NULL::float8 AS "end_distance_to_fence_m?"
+            FROM attendance_away_periods p
+            LEFT JOIN attendance_events es
+                ON es.away_period_id = p.id AND es.event_type IN ('BREAK_START', 'FIELD_OUT')
+            LEFT JOIN geofences gs ON es.geofence_id = gs.id
+            WHERE p.session_id = $1 AND p.ended_at IS NULL
+            "#,
+            session_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn find_attendance_away(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<AttendanceAwayPeriodDto>, Error> {
+        let row = sqlx::query_as!(
+            AttendanceAwayPeriodDto,
+            r#"
+            SELECT
+                p.id,
+                p.kind,
+                p.started_at,
+                p.ended_at,
+                FLOOR(EXTRACT(EPOCH FROM (p.ended_at - p.started_at)) / 60)::bigint
+                    AS "minutes?",
+                p.destination,
+                p.purpose,
+                gs.name AS "start_geofence_name?",
+                es.distance_to_fence_m AS "start_distance_to_fence_m?",
+                ge.name AS "end_geofence_name?",
+                ee.distance_to_fence_m AS "end_distance_to_fence_m?"
+            FROM attendance_away_periods p
+            LEFT JOIN attendance_events es
+                ON es.away_period_id = p.id AND es.event_type IN ('BREAK_START', 'FIELD_OUT')
+            LEFT JOIN attendance_events ee
+                ON ee.away_period_id = p.id AND ee.event_type IN ('BREAK_END', 'FIELD_IN')
+            LEFT JOIN geofences gs ON es.geofence_id = gs.id
+            LEFT JOIN geofences ge ON ee.geofence_id = ge.id
+            WHERE p.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list_attendance_away_periods(
+        &self,
+        session_id: Uuid,
+    ) -> Result<Vec<AttendanceAwayPeriodDto>, Error> {
+        let rows = sqlx::query_as!(
+            AttendanceAwayPeriodDto,
+            r#"
+            SELECT
+                p.id,
+                p.kind,
+                p.started_at,
+                p.ended_at,
+                FLOOR(EXTRACT(EPOCH FROM (p.ended_at - p.started_at)) / 60)::bigint
+                    AS "minutes?",
+                p.destination,
+                p.purpose,
+                gs.name AS "start_geofence_name?",
+                es.distance_to_fence_m AS "start_distance_to_fence_m?",
+                ge.name AS "end_geofence_name?",
+                ee.distance_to_fence_m AS "end_distance_to_fence_m?"
+            FROM attendance_away_periods p
+            LEFT JOIN attendance_events es
+                ON es.away_period_id = p.id AND es.event_type IN ('BREAK_START', 'FIELD_OUT')
+            LEFT JOIN attendance_events ee
+                ON ee.away_period_id = p.id AND ee.event_type IN ('BREAK_END', 'FIELD_IN')
+            LEFT JOIN geofences gs ON es.geofence_id = gs.id
+            LEFT JOIN geofences ge ON ee.geofence_id = ge.id
+            WHERE p.session_id = $1
+            ORDER BY p.started_at
+            "#,
+            session_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
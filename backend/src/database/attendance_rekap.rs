@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use sqlx::Error;
+use uuid::Uuid;
+
+use crate::auth::rbac::UserRole;
+use crate::constants::{AttendanceEventType, AttendanceLeaveType};
+use crate::db::DBClient;
+use crate::dtos::attendance_rekap::{RekapMemberRow, RekapSessionRow, RekapSignatoryDto};
+
+#[async_trait]
+pub trait AttendanceRekapRepo {
+    /// Anggota satker yang aktif pada rentang [from, to] (user nonaktif tetap muncul
+    /// kalau dinonaktifkan setelah `from`). Superadmin tidak ikut direkap.
+    async fn list_rekap_members(
+        &self,
+        satker_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<RekapMemberRow>, Error>;
+
+    /// Session absensi satker per work_date, beserta jenis absen event check-in pertama.
+    async fn list_rekap_sessions(
+        &self,
+        satker_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<RekapSessionRow>, Error>;
+
+    async fn find_current_satker_head(
+        &self,
+        satker_id: Uuid,
+    ) -> Result<Option<RekapSignatoryDto>, Error>;
+}
+
+#[async_trait]
+impl AttendanceRekapRepo for DBClient {
+    async fn list_rekap_members(
+        &self,
+        satker_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<RekapMemberRow>, Error> {
+        let rows = sqlx::query_as!(
+            RekapMemberRow,
+            r#"
+            SELECT u.id AS user_id,
+                   u.nrp,
+                   u.full_name,
+                   r.name AS "rank_name?"
+            FROM users u
+            LEFT JOIN ranks r ON r.id = u.rank_id
+            WHERE u.satker_id = $1
+              AND u.role <> $2
+              AND (u.is_active OR u.deactivated_at::date >= $3)
+              AND u.created_at::date <= $4
+            ORDER BY u.full_name, u.nrp
+            "#,
+            satker_id,
+            UserRole::Superadmin as UserRole,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn list_rekap_sessions(
+        &self,
+        satker_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<RekapSessionRow>, Error> {
+        let rows = sqlx::query_as!(
+            RekapSessionRow,
+            r#"
+            SELECT s.user_id,
+                   s.work_date,
+                   s.status,
+                   s.check_in_at,
+                   ci.attendance_leave_type AS "check_in_leave_type?: AttendanceLeaveType"
+            FROM attendance_sessions s
+            LEFT JOIN LATERAL (
+                SELECT e.attendance_leave_type
+                FROM attendance_events e
+                WHERE e.session_id = s.id AND e.event_type = $4
+                ORDER BY e.occurred_at
+                LIMIT 1
+            ) ci ON TRUE
+            WHERE s.satker_id = $1
+              AND s.work_date BETWEEN $2 AND $3
+            "#,
+            satker_id,
+            from,
+            to,
+            AttendanceEventType::CheckIn as AttendanceEventType
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn find_current_satker_head(
+        &self,
+        satker_id: Uuid,
+    ) -> Result<Option<RekapSignatoryDto>, Error> {
+        let row = sqlx::query_as!(
+            RekapSignatoryDto,
+            r#"
+            SELECT u.id AS user_id,
+                   u.full_name,
+                   u.nrp,
+                   r.name AS "rank_name?"
+            FROM satker_heads sh
+            JOIN users u ON u.id = sh.user_id
+            LEFT JOIN ranks r ON r.id = u.rank_id
+            WHERE sh.satker_id = $1
+              AND sh.active_to IS NULL
+            ORDER BY sh.active_from DESC
+            LIMIT 1
+            "#,
+            satker_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+}
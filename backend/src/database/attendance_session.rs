@@ -1,8 +1,12 @@
+use crate::constants::AttendanceLeaveType;
 use crate::db::DBClient;
-use crate::dtos::attendance_session::{RowAttendanceSession, UpdateAttendanceSession};
+use crate::dtos::attendance_session::{
+    AttendanceSegmentChange, AttendanceSegmentDto, OpenAttendanceSegment, RowAttendanceSession,
+    UpdateAttendanceSession,
+};
 use async_trait::async_trait;
 use chrono::{DateTime, NaiveDate, Utc};
-use sqlx::Error;
+use sqlx::{Error, PgConnection};
 use uuid::Uuid;
 
 #[derive(Debug, Clone)]
@@ -22,17 +26,18 @@ pub trait AttendanceSessionRepo {
         work_date: NaiveDate,
     ) -> Result<Uuid, Error>;
 
-    async fn update_check_in_attendance_session(
+    async fn list_attendance_segments(
         &self,
         session_id: Uuid,
-        now: Option<DateTime<Utc>>,
-    ) -> Result<UpdateAttendanceSession, Error>;
+    ) -> Result<Vec<AttendanceSegmentDto>, Error>;
 
-    async fn update_check_out_attendance_session(
+    /// Segmen terbuka terbaru milik user dengan work_date >= `since`
+    /// (since = kemarin supaya shift lintas hari ikut terbaca).
+    async fn find_open_attendance_segment(
         &self,
-        session_id: Uuid,
-        now: Option<DateTime<Utc>>,
-    ) -> Result<UpdateAttendanceSession, Error>;
+        user_id: Uuid,
+        since: NaiveDate,
+    ) -> Result<Option<OpenAttendanceSegment>, Error>;
 
     async fn find_attendance_session(
         &self,
@@ -40,6 +45,7 @@ pub trait AttendanceSessionRepo {
     ) -> Result<RowAttendanceSession, Error>;
 
     /// SUPERADMIN only: overwrite check-in/out timestamps and mark as manual correction.
+    /// Segmen diganti jadi satu pasang; return id segmen baru (None kalau check_in_at kosong).
     async fn admin_set_attendance_session(
         &self,
         session_id: Uuid,
@@ -47,7 +53,7 @@ pub trait AttendanceSessionRepo {
         check_out_at: Option<DateTime<Utc>>,
         manual_note: &str,
         updated_by: Uuid,
-    ) -> Result<(), Error>;
+    ) -> Result<Option<Uuid>, Error>;
 
     /// Delete a session (cascade deletes events) by user + work_date.
     async fn delete_attendance_session_by_user_date(
@@ -89,48 +95,64 @@ impl AttendanceSessionRepo for DBClient {
         Ok(row.id)
     }
 
-    async fn update_check_in_attendance_session(
+    async fn list_attendance_segments(
         &self,
         session_id: Uuid,
-        now: Option<DateTime<Utc>>,
-    ) -> Result<UpdateAttendanceSession, Error> {
-        let row = sqlx::query_as!(
-            UpdateAttendanceSession,
+    ) -> Result<Vec<AttendanceSegmentDto>, Error> {
+        let rows = sqlx::query_as!(
+            AttendanceSegmentDto,
             r#"
-            UPDATE attendance_sessions
-            SET check_in_at = COALESCE(check_in_at, $2),
-                updated_at = now()
-            WHERE id = $1
-            RETURNING id, work_date, check_in_at, check_out_at
+            SELECT
+                g.seq,
+                g.check_in_at,
+                g.check_out_at,
+                FLOOR(EXTRACT(EPOCH FROM (g.check_out_at - g.check_in_at)) / 60)::bigint
+                    AS "worked_minutes?",
+                gci.name AS "check_in_geofence_name?",
+                gco.name AS "check_out_geofence_name?",
+                ci.attendance_leave_type AS "check_in_attendance_leave_type?: AttendanceLeaveType",
+                co.attendance_leave_type AS "check_out_attendance_leave_type?: AttendanceLeaveType"
+            FROM attendance_segments g
+            LEFT JOIN attendance_events ci ON ci.segment_id = g.id AND ci.event_type = 'CHECK_IN'
+            LEFT JOIN attendance_events co ON co.segment_id = g.id AND co.event_type = 'CHECK_OUT'
+            LEFT JOIN geofences gci ON ci.geofence_id = gci.id
+            LEFT JOIN geofences gco ON co.geofence_id = gco.id
+            WHERE g.session_id = $1
+            ORDER BY g.seq
             "#,
-            session_id,
-            now
+            session_id
         )
-        .fetch_one(&self.pool)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(row)
+        Ok(rows)
     }
 
-    async fn update_check_out_attendance_session(
+    async fn find_open_attendance_segment(
         &self,
-        session_id: Uuid,
-        now: Option<DateTime<Utc>>,
-    ) -> Result<UpdateAttendanceSession, Error> {
+        user_id: Uuid,
+        since: NaiveDate,
+    ) -> Result<Option<OpenAttendanceSegment>, Error> {
         let row = sqlx::query_as!(
-            UpdateAttendanceSession,
+            OpenAttendanceSegment,
             r#"
-            UPDATE attendance_sessions
-            SET check_out_at = COALESCE(check_out_at, $2),
-                status = 'CLOSED',
-                updated_at = now()
-            WHERE id = $1
-            RETURNING id, work_date, check_in_at, check_out_at
+            SELECT
+                s.id AS session_id,
+                g.id AS segment_id,
+                s.work_date,
+                g.seq
+            FROM attendance_segments g
+            JOIN attendance_sessions s ON s.id = g.session_id
+            WHERE s.user_id = $1
+              AND s.work_date >= $2
+              AND g.check_out_at IS NULL
+            ORDER BY g.check_in_at DESC
+            LIMIT 1
             "#,
-            session_id,
-            now
+            user_id,
+            since
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
 
         Ok(row)
@@ -160,7 +182,9 @@ impl AttendanceSessionRepo for DBClient {
         check_out_at: Option<DateTime<Utc>>,
         manual_note: &str,
         updated_by: Uuid,
-    ) -> Result<(), Error> {
+    ) -> Result<Option<Uuid>, Error> {
+        let mut tx = self.pool.begin().await?;
+
         sqlx::query!(
             r#"
             UPDATE attendance_sessions
@@ -181,10 +205,36 @@ impl AttendanceSessionRepo for DBClient {
             manual_note,
             updated_by
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        Ok(())
+        // koreksi manual = satu pasang check-in/out
+        sqlx::query!(
+            r#"DELETE FROM attendance_segments WHERE session_id = $1"#,
+            session_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let mut segment_id = None;
+        if let Some(check_in_at) = check_in_at {
+            let row = sqlx::query!(
+                r#"
+                INSERT INTO attendance_segments (session_id, seq, check_in_at, check_out_at)
+                VALUES ($1, 1, $2, $3)
+                RETURNING id
+                "#,
+                session_id,
+                check_in_at,
+                check_out_at
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+            segment_id = Some(row.id);
+        }
+
+        tx.commit().await?;
+        Ok(segment_id)
     }
 
     async fn delete_attendance_session_by_user_date(
@@ -225,3 +275,208 @@ impl AttendanceSessionRepo for DBClient {
         Ok(row)
     }
 }
+
+/// Buka segmen baru (check-in). Session: check_in_at tetap check-in pertama,
+/// check_out_at dikosongkan lagi selama segmen terbuka.
+///
+/// Jalankan di transaksi yang sama dengan insert event check-in, supaya segmen
+/// tidak tertinggal terbuka tanpa event.
+pub async fn open_attendance_segment(
+    conn: &mut PgConnection,
+    session_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<AttendanceSegmentChange, Error> {
+    // serialize check-in/out per session
+    sqlx::query!(
+        r#"SELECT id FROM attendance_sessions WHERE id = $1 FOR UPDATE"#,
+        session_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let segment = sqlx::query!(
+        r#"
+        INSERT INTO attendance_segments (session_id, seq, check_in_at)
+        SELECT $1, COALESCE(MAX(seq), 0) + 1, $2
+        FROM attendance_segments
+        WHERE session_id = $1
+        RETURNING id, seq
+        "#,
+        session_id,
+        now
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let session = sqlx::query_as!(
+        UpdateAttendanceSession,
+        r#"
+        UPDATE attendance_sessions
+        SET check_in_at = COALESCE(check_in_at, $2),
+            check_out_at = NULL,
+            status = CASE WHEN status = 'INVALID' THEN status ELSE 'OPEN' END,
+            updated_at = now()
+        WHERE id = $1
+        RETURNING id, work_date, check_in_at, check_out_at
+        "#,
+        session_id,
+        now
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(AttendanceSegmentChange {
+        session,
+        segment_id: segment.id,
+        seq: segment.seq,
+    })
+}
+
+/// Tutup segmen yang masih terbuka (check-out), termasuk istirahat / tugas lapangan
+/// yang belum diakhiri. None = tidak ada segmen terbuka.
+///
+/// Jalankan di transaksi yang sama dengan insert event check-out.
+pub async fn close_attendance_segment(
+    conn: &mut PgConnection,
+    session_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Option<AttendanceSegmentChange>, Error> {
+    let segment = sqlx::query!(
+        r#"
+        UPDATE attendance_segments
+        SET check_out_at = $2,
+            updated_at = now()
+        WHERE session_id = $1 AND check_out_at IS NULL
+        RETURNING id, seq
+        "#,
+        session_id,
+        now
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some(segment) = segment else {
+        return Ok(None);
+    };
+
+    sqlx::query!(
+        r#"
+        UPDATE attendance_away_periods
+        SET ended_at = $2,
+            updated_at = now()
+        WHERE session_id = $1 AND ended_at IS NULL
+        "#,
+        session_id,
+        now
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let session = sqlx::query_as!(
+        UpdateAttendanceSession,
+        r#"
+        UPDATE attendance_sessions
+        SET check_out_at = $2,
+            status = CASE WHEN status = 'INVALID' THEN status ELSE 'CLOSED' END,
+            updated_at = now()
+        WHERE id = $1
+        RETURNING id, work_date, check_in_at, check_out_at
+        "#,
+        session_id,
+        now
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(Some(AttendanceSegmentChange {
+        session,
+        segment_id: segment.id,
+        seq: segment.seq,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use sqlx::Connection;
+
+    /// Butuh Postgres dengan migrasi terbaru (`DATABASE_URL`); jalankan dengan
+    /// `cargo test -- --ignored`. Semua data dibuat di transaksi yang di-rollback.
+    #[tokio::test]
+    #[ignore = "butuh database (DATABASE_URL)"]
+    async fn check_out_closes_open_away_period() {
+        dotenv::dotenv().ok();
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL belum diset");
+        let mut conn = PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        let suffix = Uuid::new_v4().simple().to_string();
+        let satker_id: Uuid =
+            sqlx::query_scalar("INSERT INTO satkers (code, name) VALUES ($1, $1) RETURNING id")
+                .bind(format!("T-{}", suffix))
+                .fetch_one(&mut *tx)
+                .await
+                .unwrap();
+        let user_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO users (satker_id, nrp, full_name, password_hash)
+             VALUES ($1, $2, 'Test', '-') RETURNING id",
+        )
+        .bind(satker_id)
+        .bind(suffix.clone())
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        let session_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO attendance_sessions (satker_id, user_id, work_date)
+             VALUES ($1, $2, CURRENT_DATE) RETURNING id",
+        )
+        .bind(satker_id)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+
+        let now = Utc::now();
+        open_attendance_segment(&mut tx, session_id, now - Duration::hours(4))
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO attendance_away_periods (session_id, kind, started_at)
+             VALUES ($1, 'BREAK', $2)",
+        )
+        .bind(session_id)
+        .bind(now - Duration::hours(1))
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+        let closed = close_attendance_segment(&mut tx, session_id, now)
+            .await
+            .unwrap();
+        assert!(closed.is_some());
+
+        let (open_left, ended_at_checkout): (i64, bool) = sqlx::query_as(
+            "SELECT COUNT(*) FILTER (WHERE ended_at IS NULL),
+                    COALESCE(BOOL_AND(ended_at = $2), FALSE)
+             FROM attendance_away_periods WHERE session_id = $1",
+        )
+        .bind(session_id)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        assert_eq!(open_left, 0);
+        assert!(ended_at_checkout);
+
+        // tidak ada segmen terbuka lagi
+        assert!(
+            close_attendance_segment(&mut tx, session_id, now)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        tx.rollback().await.unwrap();
+    }
+}
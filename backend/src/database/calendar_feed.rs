@@ -0,0 +1,268 @@
+use crate::DBClient;
+use crate::auth::rbac::UserRole;
+use crate::constants::{CalendarDayType, LeaveType};
+use crate::dtos::calendar_feed::{
+    CalendarFeedDto, CalendarFeedTokenRow, FeedCalendarDayRow, FeedLeaveRow,
+};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use sqlx::Error;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait CalendarFeedRepo {
+    async fn create_calendar_feed(
+        &self,
+        user_id: Uuid,
+        scope: &str,
+        satker_id: Option<Uuid>,
+        token_hash: &str,
+        label: Option<String>,
+    ) -> Result<Uuid, Error>;
+
+    async fn find_calendar_feed(&self, id: Uuid) -> Result<Option<CalendarFeedDto>, Error>;
+
+    async fn list_calendar_feeds(&self, user_id: Uuid) -> Result<Vec<CalendarFeedDto>, Error>;
+
+    /// Cabut feed milik user. false kalau tidak ada / sudah dicabut.
+    async fn revoke_calendar_feed(&self, id: Uuid, user_id: Uuid) -> Result<bool, Error>;
+
+    /// Token yang belum dicabut, berdasarkan hash.
+    async fn find_active_calendar_feed_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<CalendarFeedTokenRow>, Error>;
+
+    /// Catat waktu akses terakhir (maks. sekali per 5 menit, klien kalender polling rutin).
+    async fn touch_calendar_feed(&self, id: Uuid) -> Result<(), Error>;
+
+    /// Ijin/cuti yang sudah disetujui dan beririsan dengan rentang tanggal.
+    async fn list_feed_leaves(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<FeedLeaveRow>, Error>;
+
+    /// Hari libur / setengah hari dari kalender satker yang berasal dari tabel holidays
+    /// (libur akhir pekan biasa tidak ikut).
+    async fn list_feed_calendar_days(
+        &self,
+        satker_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<FeedCalendarDayRow>, Error>;
+}
+
+#[async_trait]
+impl CalendarFeedRepo for DBClient {
+    async fn create_calendar_feed(
+        &self,
+        user_id: Uuid,
+        scope: &str,
+        satker_id: Option<Uuid>,
+        token_hash: &str,
+        label: Option<String>,
+    ) -> Result<Uuid, Error> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO calendar_feed_tokens (user_id, scope, satker_id, token_hash, label)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+            user_id,
+            scope,
+            satker_id,
+            token_hash,
+            label
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.id)
+    }
+
+    async fn find_calendar_feed(&self, id: Uuid) -> Result<Option<CalendarFeedDto>, Error> {
+        let row = sqlx::query_as!(
+            CalendarFeedDto,
+            r#"
+            SELECT f.id,
+                   f.scope,
+                   f.satker_id,
+                   s.code as "satker_code?",
+                   s.name as "satker_name?",
+                   f.label,
+                   f.last_used_at,
+                   f.revoked_at,
+                   f.created_at
+            FROM calendar_feed_tokens f
+            LEFT JOIN satkers s ON s.id = f.satker_id
+            WHERE f.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list_calendar_feeds(&self, user_id: Uuid) -> Result<Vec<CalendarFeedDto>, Error> {
+        let rows = sqlx::query_as!(
+            CalendarFeedDto,
+            r#"
+            SELECT f.id,
+                   f.scope,
+                   f.satker_id,
+                   s.code as "satker_code?",
+                   s.name as "satker_name?",
+                   f.label,
+                   f.last_used_at,
+                   f.revoked_at,
+                   f.created_at
+            FROM calendar_feed_tokens f
+            LEFT JOIN satkers s ON s.id = f.satker_id
+            WHERE f.user_id = $1
+            ORDER BY f.revoked_at IS NOT NULL, f.created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn revoke_calendar_feed(&self, id: Uuid, user_id: Uuid) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE calendar_feed_tokens
+            SET revoked_at = now()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+            id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn find_active_calendar_feed_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<CalendarFeedTokenRow>, Error> {
+        let row = sqlx::query_as!(
+            CalendarFeedTokenRow,
+            r#"
+            SELECT f.id,
+                   f.scope,
+                   f.satker_id,
+                   f.label,
+                   f.user_id,
+                   u.satker_id as owner_satker_id,
+                   u.role as "owner_role: UserRole",
+                   u.is_active as owner_is_active
+            FROM calendar_feed_tokens f
+            JOIN users u ON u.id = f.user_id
+            WHERE f.token_hash = $1 AND f.revoked_at IS NULL
+            "#,
+            token_hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn touch_calendar_feed(&self, id: Uuid) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE calendar_feed_tokens
+            SET last_used_at = now()
+            WHERE id = $1
+              AND (last_used_at IS NULL OR last_used_at < now() - interval '5 minutes')
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_feed_leaves(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<FeedLeaveRow>, Error> {
+        let rows = sqlx::query_as!(
+            FeedLeaveRow,
+            r#"
+            SELECT lr.id,
+                   u.full_name as user_full_name,
+                   lr.tipe as "tipe: LeaveType",
+                   lr.start_date,
+                   lr.end_date,
+                   lr.reason,
+                   lr.updated_at
+            FROM leave_requests lr
+            JOIN users u ON u.id = lr.user_id
+            WHERE ($1::uuid IS NULL OR lr.satker_id = $1)
+              AND ($2::uuid IS NULL OR lr.user_id = $2)
+              AND lr.status = 'APPROVED'
+              AND lr.end_date >= $3
+              AND lr.start_date <= $4
+            ORDER BY lr.start_date ASC
+            "#,
+            satker_id,
+            user_id,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn list_feed_calendar_days(
+        &self,
+        satker_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<FeedCalendarDayRow>, Error> {
+        let rows = sqlx::query_as!(
+            FeedCalendarDayRow,
+            r#"
+            SELECT d.work_date,
+                   d.day_type as "day_type: CalendarDayType",
+                   d.expected_end,
+                   d.note,
+                   d.updated_at
+            FROM satker_calendar_days d
+            WHERE d.satker_id = $1
+              AND d.work_date BETWEEN $2 AND $3
+              AND d.day_type IN ('HOLIDAY', 'HALF_DAY')
+              AND EXISTS (
+                  SELECT 1
+                  FROM holidays h
+                  WHERE h.holiday_date = d.work_date
+                    AND (h.scope = 'NATIONAL' OR h.satker_id = d.satker_id)
+              )
+            ORDER BY d.work_date ASC
+            "#,
+            satker_id,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
@@ -0,0 +1,221 @@
+use crate::DBClient;
+use crate::dtos::work_calendar::{
+    CalendarRegenJobDto, ClaimedCalendarRegenJob, REGEN_DONE, REGEN_FAILED, REGEN_PENDING,
+    REGEN_RUNNING,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::Error;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait CalendarRegenRepo {
+    /// `to = None` = sampai akhir kalender yang sudah ter-generate.
+    async fn enqueue_calendar_regen(
+        &self,
+        satker_id: Uuid,
+        from: NaiveDate,
+        to: Option<NaiveDate>,
+        reason: &str,
+    ) -> Result<Uuid, Error>;
+
+    /// Ambil satu job PENDING (SKIP LOCKED, aman untuk banyak instance) lalu gabungkan
+    /// job PENDING lain milik satker yang sama ke dalamnya.
+    async fn claim_calendar_regen_job(&self) -> Result<Option<ClaimedCalendarRegenJob>, Error>;
+
+    async fn finish_calendar_regen_job(
+        &self,
+        id: Uuid,
+        generated: i64,
+        error: Option<&str>,
+    ) -> Result<(), Error>;
+
+    /// Job RUNNING yang tertinggal (server mati di tengah proses) dikembalikan ke PENDING.
+    /// Hanya job yang mulai sebelum `started_before`, supaya job yang masih dikerjakan
+    /// replica lain tidak ikut diambil ulang.
+    async fn requeue_running_calendar_regen_jobs(
+        &self,
+        started_before: DateTime<Utc>,
+    ) -> Result<u64, Error>;
+
+    async fn list_calendar_regen_jobs(
+        &self,
+        satker_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<CalendarRegenJobDto>, Error>;
+}
+
+#[async_trait]
+impl CalendarRegenRepo for DBClient {
+    async fn enqueue_calendar_regen(
+        &self,
+        satker_id: Uuid,
+        from: NaiveDate,
+        to: Option<NaiveDate>,
+        reason: &str,
+    ) -> Result<Uuid, Error> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO calendar_regen_jobs (satker_id, from_date, to_date, reason, status)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+            satker_id,
+            from,
+            to,
+            reason,
+            REGEN_PENDING
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.id)
+    }
+
+    async fn claim_calendar_regen_job(&self) -> Result<Option<ClaimedCalendarRegenJob>, Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(job) = sqlx::query!(
+            r#"
+            SELECT id, satker_id
+            FROM calendar_regen_jobs
+            WHERE status = $1
+            ORDER BY created_at ASC
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+            "#,
+            REGEN_PENDING
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        // job pending lain satker yang sama ikut diproses sekarang (range digabung)
+        let merged = sqlx::query!(
+            r#"
+            WITH pending AS (
+                SELECT id, from_date, to_date
+                FROM calendar_regen_jobs
+                WHERE satker_id = $2 AND status = $3
+                FOR UPDATE SKIP LOCKED
+            ),
+            dropped AS (
+                DELETE FROM calendar_regen_jobs j
+                USING pending p
+                WHERE j.id = p.id AND j.id <> $1
+            )
+            SELECT MIN(from_date) as "from_date!",
+                   CASE WHEN bool_or(to_date IS NULL) THEN NULL ELSE MAX(to_date) END as to_date
+            FROM pending
+            "#,
+            job.id,
+            job.satker_id,
+            REGEN_PENDING
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE calendar_regen_jobs
+            SET status = $2,
+                from_date = $3,
+                to_date = $4,
+                attempts = attempts + 1,
+                started_at = now()
+            WHERE id = $1
+            "#,
+            job.id,
+            REGEN_RUNNING,
+            merged.from_date,
+            merged.to_date
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(ClaimedCalendarRegenJob {
+            id: job.id,
+            satker_id: job.satker_id,
+            from_date: merged.from_date,
+            to_date: merged.to_date,
+        }))
+    }
+
+    async fn finish_calendar_regen_job(
+        &self,
+        id: Uuid,
+        generated: i64,
+        error: Option<&str>,
+    ) -> Result<(), Error> {
+        let status = if error.is_some() {
+            REGEN_FAILED
+        } else {
+            REGEN_DONE
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE calendar_regen_jobs
+            SET status = $2, generated = $3, error = $4, finished_at = now()
+            WHERE id = $1
+            "#,
+            id,
+            status,
+            generated,
+            error
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn requeue_running_calendar_regen_jobs(
+        &self,
+        started_before: DateTime<Utc>,
+    ) -> Result<u64, Error> {
+        let res = sqlx::query!(
+            r#"
+            UPDATE calendar_regen_jobs
+            SET status = $1, started_at = NULL
+            WHERE status = $2
+              AND (started_at IS NULL OR started_at < $3)
+            "#,
+            REGEN_PENDING,
+            REGEN_RUNNING,
+            started_before
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+
+    async fn list_calendar_regen_jobs(
+        &self,
+        satker_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<CalendarRegenJobDto>, Error> {
+        let rows = sqlx::query_as!(
+            CalendarRegenJobDto,
+            r#"
+            SELECT id, satker_id, from_date, to_date, reason, status, attempts,
+                   generated, error, created_at, started_at, finished_at
+            FROM calendar_regen_jobs
+            WHERE satker_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+            satker_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
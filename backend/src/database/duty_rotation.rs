@@ -0,0 +1,500 @@
+use crate::DBClient;
+use crate::constants::{LeaveStatus, LeaveType, ScheduleType};
+use crate::dtos::duty_rotation::{
+    DutyRotationTemplateDto, RotationDefinition, RotationScheduleInsert, RotationSlotDto,
+    RotationTeamDto, ScheduleSpanRow, UserLeaveSpanRow,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::types::Json;
+use sqlx::{Error, Postgres, Transaction};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait DutyRotationRepo {
+    async fn list_duty_rotations(
+        &self,
+        satker_id: Option<Uuid>,
+    ) -> Result<Vec<DutyRotationTemplateDto>, Error>;
+
+    async fn find_duty_rotation(&self, id: Uuid) -> Result<Option<DutyRotationTemplateDto>, Error>;
+
+    async fn create_duty_rotation(
+        &self,
+        satker_id: Uuid,
+        def: &RotationDefinition,
+        created_by: Uuid,
+    ) -> Result<Uuid, Error>;
+
+    /// Ganti seluruh definisi (regu + slot) template.
+    async fn update_duty_rotation(&self, id: Uuid, def: &RotationDefinition) -> Result<(), Error>;
+
+    /// Jadwal yang sudah di-generate tetap ada (rotation_template_id jadi NULL).
+    async fn delete_duty_rotation(&self, id: Uuid) -> Result<(), Error>;
+
+    /// User dari `user_ids` yang bukan anggota aktif satker tsb.
+    async fn find_users_outside_satker(
+        &self,
+        satker_id: Uuid,
+        user_ids: &[Uuid],
+    ) -> Result<Vec<Uuid>, Error>;
+
+    /// Jadwal dinas aktif milik `user_ids` atau hasil template `template_id`
+    /// yang beririsan dengan [from, to).
+    async fn list_schedule_spans(
+        &self,
+        user_ids: &[Uuid],
+        template_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ScheduleSpanRow>, Error>;
+
+    async fn list_approved_leaves_for_users(
+        &self,
+        user_ids: &[Uuid],
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<UserLeaveSpanRow>, Error>;
+
+    /// Simpan hasil generate dalam satu transaksi. Return (inserted, removed).
+    async fn apply_duty_rotation(
+        &self,
+        satker_id: Uuid,
+        template_id: Uuid,
+        remove_ids: &[Uuid],
+        inserts: &[RotationScheduleInsert],
+        created_by: Uuid,
+    ) -> Result<(i64, i64), Error>;
+}
+
+async fn insert_rotation_children(
+    tx: &mut Transaction<'_, Postgres>,
+    template_id: Uuid,
+    def: &RotationDefinition,
+) -> Result<(), Error> {
+    let mut team_ids = Vec::with_capacity(def.teams.len());
+    for (idx, team) in def.teams.iter().enumerate() {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO duty_rotation_teams (template_id, name, sort_order)
+            VALUES ($1, $2, $3)
+            RETURNING id
+            "#,
+            template_id,
+            team.name,
+            idx as i32
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO duty_rotation_team_members (team_id, user_id)
+            SELECT $1, UNNEST($2::uuid[])
+            ON CONFLICT DO NOTHING
+            "#,
+            row.id,
+            &team.member_ids
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        team_ids.push(row.id);
+    }
+
+    for slot in &def.slots {
+        sqlx::query!(
+            r#"
+            INSERT INTO duty_rotation_slots
+                (template_id, team_id, day_index, start_time, duration_minutes, schedule_type, title)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            template_id,
+            team_ids[slot.team_index],
+            slot.day_index,
+            slot.start_time,
+            slot.duration_minutes,
+            slot.schedule_type as ScheduleType,
+            slot.title
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl DutyRotationRepo for DBClient {
+    async fn list_duty_rotations(
+        &self,
+        satker_id: Option<Uuid>,
+    ) -> Result<Vec<DutyRotationTemplateDto>, Error> {
+        let rows = sqlx::query_as!(
+            DutyRotationTemplateDto,
+            r#"
+            SELECT
+                t.id,
+                t.satker_id,
+                s.code AS satker_code,
+                s.name AS satker_name,
+                t.name,
+                t.cycle_days,
+                t.anchor_date,
+                t.is_active,
+                COALESCE(
+                    (
+                        SELECT json_agg(json_build_object(
+                            'id', tm.id,
+                            'name', tm.name,
+                            'sort_order', tm.sort_order,
+                            'members', COALESCE(
+                                (
+                                    SELECT json_agg(json_build_object(
+                                        'user_id', u.id,
+                                        'full_name', u.full_name,
+                                        'nrp', u.nrp
+                                    ) ORDER BY u.full_name)
+                                    FROM duty_rotation_team_members m
+                                    JOIN users u ON u.id = m.user_id
+                                    WHERE m.team_id = tm.id AND u.is_active = true
+                                ),
+                                '[]'::json
+                            )
+                        ) ORDER BY tm.sort_order)
+                        FROM duty_rotation_teams tm
+                        WHERE tm.template_id = t.id
+                    ),
+                    '[]'::json
+                ) as "teams!: Json<Vec<RotationTeamDto>>",
+                COALESCE(
+                    (
+                        SELECT json_agg(json_build_object(
+                            'id', sl.id,
+                            'team_id', sl.team_id,
+                            'team_name', tm.name,
+                            'day_index', sl.day_index,
+                            'start_time', sl.start_time,
+                            'duration_minutes', sl.duration_minutes,
+                            'schedule_type', sl.schedule_type,
+                            'title', sl.title
+                        ) ORDER BY sl.day_index, sl.start_time, tm.sort_order)
+                        FROM duty_rotation_slots sl
+                        JOIN duty_rotation_teams tm ON tm.id = sl.team_id
+                        WHERE sl.template_id = t.id
+                    ),
+                    '[]'::json
+                ) as "slots!: Json<Vec<RotationSlotDto>>",
+                t.created_by,
+                t.created_at,
+                t.updated_at
+            FROM duty_rotation_templates t
+            JOIN satkers s ON s.id = t.satker_id
+            WHERE ($1::uuid IS NULL OR t.satker_id = $1)
+            ORDER BY s.code, t.name
+            "#,
+            satker_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn find_duty_rotation(&self, id: Uuid) -> Result<Option<DutyRotationTemplateDto>, Error> {
+        let row = sqlx::query_as!(
+            DutyRotationTemplateDto,
+            r#"
+            SELECT
+                t.id,
+                t.satker_id,
+                s.code AS satker_code,
+                s.name AS satker_name,
+                t.name,
+                t.cycle_days,
+                t.anchor_date,
+                t.is_active,
+                COALESCE(
+                    (
+                        SELECT json_agg(json_build_object(
+                            'id', tm.id,
+                            'name', tm.name,
+                            'sort_order', tm.sort_order,
+                            'members', COALESCE(
+                                (
+                                    SELECT json_agg(json_build_object(
+                                        'user_id', u.id,
+                                        'full_name', u.full_name,
+                                        'nrp', u.nrp
+                                    ) ORDER BY u.full_name)
+                                    FROM duty_rotation_team_members m
+                                    JOIN users u ON u.id = m.user_id
+                                    WHERE m.team_id = tm.id AND u.is_active = true
+                                ),
+                                '[]'::json
+                            )
+                        ) ORDER BY tm.sort_order)
+                        FROM duty_rotation_teams tm
+                        WHERE tm.template_id = t.id
+                    ),
+                    '[]'::json
+                ) as "teams!: Json<Vec<RotationTeamDto>>",
+                COALESCE(
+                    (
+                        SELECT json_agg(json_build_object(
+                            'id', sl.id,
+                            'team_id', sl.team_id,
+                            'team_name', tm.name,
+                            'day_index', sl.day_index,
+                            'start_time', sl.start_time,
+                            'duration_minutes', sl.duration_minutes,
+                            'schedule_type', sl.schedule_type,
+                            'title', sl.title
+                        ) ORDER BY sl.day_index, sl.start_time, tm.sort_order)
+                        FROM duty_rotation_slots sl
+                        JOIN duty_rotation_teams tm ON tm.id = sl.team_id
+                        WHERE sl.template_id = t.id
+                    ),
+                    '[]'::json
+                ) as "slots!: Json<Vec<RotationSlotDto>>",
+                t.created_by,
+                t.created_at,
+                t.updated_at
+            FROM duty_rotation_templates t
+            JOIN satkers s ON s.id = t.satker_id
+            WHERE t.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn create_duty_rotation(
+        &self,
+        satker_id: Uuid,
+        def: &RotationDefinition,
+        created_by: Uuid,
+    ) -> Result<Uuid, Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO duty_rotation_templates
+                (satker_id, name, cycle_days, anchor_date, is_active, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id
+            "#,
+            satker_id,
+            def.name,
+            def.cycle_days,
+            def.anchor_date,
+            def.is_active,
+            created_by
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        insert_rotation_children(&mut tx, row.id, def).await?;
+
+        tx.commit().await?;
+        Ok(row.id)
+    }
+
+    async fn update_duty_rotation(&self, id: Uuid, def: &RotationDefinition) -> Result<(), Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE duty_rotation_templates
+            SET name = $2,
+                cycle_days = $3,
+                anchor_date = $4,
+                is_active = $5,
+                updated_at = now()
+            WHERE id = $1
+            "#,
+            id,
+            def.name,
+            def.cycle_days,
+            def.anchor_date,
+            def.is_active
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // slot & anggota ikut terhapus (ON DELETE CASCADE)
+        sqlx::query!(
+            r#"DELETE FROM duty_rotation_teams WHERE template_id = $1"#,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        insert_rotation_children(&mut tx, id, def).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_duty_rotation(&self, id: Uuid) -> Result<(), Error> {
+        sqlx::query!(r#"DELETE FROM duty_rotation_templates WHERE id = $1"#, id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn find_users_outside_satker(
+        &self,
+        satker_id: Uuid,
+        user_ids: &[Uuid],
+    ) -> Result<Vec<Uuid>, Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT x.id as "id!"
+            FROM UNNEST($2::uuid[]) AS x(id)
+            LEFT JOIN users u
+              ON u.id = x.id AND u.satker_id = $1 AND u.is_active = true
+            WHERE u.id IS NULL
+            "#,
+            satker_id,
+            user_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r.id).collect())
+    }
+
+    async fn list_schedule_spans(
+        &self,
+        user_ids: &[Uuid],
+        template_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ScheduleSpanRow>, Error> {
+        let rows = sqlx::query_as!(
+            ScheduleSpanRow,
+            r#"
+            SELECT id,
+                   user_id,
+                   start_at as "start_at!",
+                   end_at as "end_at!",
+                   rotation_template_id,
+                   rotation_user_id
+            FROM duty_schedules
+            WHERE deleted_at IS NULL
+              AND (user_id = ANY($1::uuid[]) OR rotation_template_id = $2)
+              AND start_at < $4
+              AND end_at > $3
+            ORDER BY start_at
+            "#,
+            user_ids,
+            template_id,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn list_approved_leaves_for_users(
+        &self,
+        user_ids: &[Uuid],
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<UserLeaveSpanRow>, Error> {
+        let rows = sqlx::query_as!(
+            UserLeaveSpanRow,
+            r#"
+            SELECT user_id,
+                   tipe as "tipe: LeaveType",
+                   start_date,
+                   end_date
+            FROM leave_requests
+            WHERE user_id = ANY($1::uuid[])
+              AND status = $2
+              AND NOT (end_date < $3 OR start_date > $4)
+            "#,
+            user_ids,
+            LeaveStatus::Approved as LeaveStatus,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn apply_duty_rotation(
+        &self,
+        satker_id: Uuid,
+        template_id: Uuid,
+        remove_ids: &[Uuid],
+        inserts: &[RotationScheduleInsert],
+        created_by: Uuid,
+    ) -> Result<(i64, i64), Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let removed = sqlx::query!(
+            r#"
+            UPDATE duty_schedules
+            SET deleted_at = now(), updated_at = now()
+            WHERE id = ANY($1::uuid[])
+              AND rotation_template_id = $2
+              AND deleted_at IS NULL
+            "#,
+            remove_ids,
+            template_id
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected() as i64;
+
+        let mut inserted: i64 = 0;
+        for item in inserts {
+            // Keep legacy columns (schedule_date/start_time/end_time) populated for compatibility.
+            let res = sqlx::query!(
+                r#"
+                INSERT INTO duty_schedules (
+                    satker_id, user_id,
+                    schedule_date, start_time, end_time,
+                    start_at, end_at,
+                    type, title, created_by, rotation_template_id, rotation_user_id
+                )
+                VALUES (
+                    $1, $2,
+                    ($3 AT TIME ZONE 'UTC')::date,
+                    ($3 AT TIME ZONE 'UTC')::time,
+                    ($4 AT TIME ZONE 'UTC')::time,
+                    $3, $4,
+                    $5, $6, $7, $8, $2
+                )
+                ON CONFLICT (rotation_template_id, rotation_user_id, start_at)
+                    WHERE deleted_at IS NULL AND rotation_template_id IS NOT NULL
+                DO NOTHING
+                "#,
+                satker_id,
+                item.user_id,
+                item.start_at,
+                item.end_at,
+                item.schedule_type as ScheduleType,
+                item.title,
+                created_by,
+                template_id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            inserted += res.rows_affected() as i64;
+        }
+
+        tx.commit().await?;
+        Ok((inserted, removed))
+    }
+}
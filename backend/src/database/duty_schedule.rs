@@ -3,22 +3,23 @@ use crate::constants::ScheduleType;
 use crate::dtos::duty_schedule::DutyScheduleDto;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use sqlx::Error;
+use sqlx::{Error, PgConnection};
 use uuid::Uuid;
 
+pub struct NewDutySchedule {
+    pub satker_id: Uuid,
+    pub user_id: Uuid,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+    pub schedule_type: ScheduleType,
+    pub title: Option<String>,
+    pub note: Option<String>,
+    pub created_by: Uuid,
+}
+
 #[async_trait]
 pub trait DutyScheduleRepo {
-    async fn create_duty_schedule(
-        &self,
-        satker_id: Uuid,
-        user_id: Uuid,
-        start_at: DateTime<Utc>,
-        end_at: DateTime<Utc>,
-        schedule_type: ScheduleType,
-        title: Option<String>,
-        note: Option<String>,
-        created_by: Uuid,
-    ) -> Result<(), Error>;
+    async fn create_duty_schedule(&self, new_schedule: NewDutySchedule) -> Result<(), Error>;
 
     async fn update_duty_schedule(
         &self,
@@ -53,17 +54,7 @@ pub trait DutyScheduleRepo {
 
 #[async_trait]
 impl DutyScheduleRepo for DBClient {
-    async fn create_duty_schedule(
-        &self,
-        satker_id: Uuid,
-        user_id: Uuid,
-        start_at: DateTime<Utc>,
-        end_at: DateTime<Utc>,
-        schedule_type: ScheduleType,
-        title: Option<String>,
-        note: Option<String>,
-        created_by: Uuid,
-    ) -> Result<(), Error> {
+    async fn create_duty_schedule(&self, new_schedule: NewDutySchedule) -> Result<(), Error> {
         // Keep legacy columns (schedule_date/start_time/end_time) populated for compatibility.
         sqlx::query!(
             r#"
@@ -82,14 +73,14 @@ impl DutyScheduleRepo for DBClient {
                 $5, $6, $7, $8
             )
             "#,
-            satker_id,
-            user_id,
-            start_at,
-            end_at,
-            schedule_type as ScheduleType,
-            title,
-            note,
-            created_by
+            new_schedule.satker_id,
+            new_schedule.user_id,
+            new_schedule.start_at,
+            new_schedule.end_at,
+            new_schedule.schedule_type as ScheduleType,
+            new_schedule.title,
+            new_schedule.note,
+            new_schedule.created_by
         )
         .execute(&self.pool)
         .await?;
@@ -231,25 +222,37 @@ impl DutyScheduleRepo for DBClient {
         end_at: DateTime<Utc>,
         exclude_id: Option<Uuid>,
     ) -> Result<bool, Error> {
-        // overlap condition: existing.start < new_end AND existing.end > new_start
-        let row = sqlx::query!(
-            r#"
-            SELECT 1 as one
-            FROM duty_schedules
-            WHERE deleted_at IS NULL
-              AND user_id = $1
-              AND start_at < $3
-              AND end_at > $2
-              AND ($4::uuid IS NULL OR id <> $4)
-            LIMIT 1
-            "#,
-            user_id,
-            start_at,
-            end_at,
-            exclude_id
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-        Ok(row.is_some())
+        let mut conn = self.pool.acquire().await?;
+        has_overlap_in(&mut conn, user_id, start_at, end_at, exclude_id).await
     }
 }
+
+/// Same as [`DutyScheduleRepo::has_overlap`], on an existing connection/transaction.
+pub async fn has_overlap_in(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    start_at: DateTime<Utc>,
+    end_at: DateTime<Utc>,
+    exclude_id: Option<Uuid>,
+) -> Result<bool, Error> {
+    // overlap condition: existing.start < new_end AND existing.end > new_start
+    let row = sqlx::query!(
+        r#"
+        SELECT 1 as one
+        FROM duty_schedules
+        WHERE deleted_at IS NULL
+          AND user_id = $1
+          AND start_at < $3
+          AND end_at > $2
+          AND ($4::uuid IS NULL OR id <> $4)
+        LIMIT 1
+        "#,
+        user_id,
+        start_at,
+        end_at,
+        exclude_id
+    )
+    .fetch_optional(conn)
+    .await?;
+    Ok(row.is_some())
+}
@@ -6,17 +6,21 @@ use chrono::{DateTime, Utc};
 use sqlx::Error;
 use uuid::Uuid;
 
+pub struct NewDutyScheduleRequest {
+    pub satker_id: Uuid,
+    pub user_id: Uuid,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+    pub schedule_type: ScheduleType,
+    pub title: Option<String>,
+    pub note: Option<String>,
+}
+
 #[async_trait]
 pub trait DutyScheduleRequestRepo {
     async fn create_duty_schedule_request(
         &self,
-        satker_id: Uuid,
-        user_id: Uuid,
-        start_at: DateTime<Utc>,
-        end_at: DateTime<Utc>,
-        schedule_type: ScheduleType,
-        title: Option<String>,
-        note: Option<String>,
+        new_request: NewDutyScheduleRequest,
     ) -> Result<Uuid, Error>;
 
     async fn list_duty_schedule_requests(
@@ -42,13 +46,7 @@ pub trait DutyScheduleRequestRepo {
 impl DutyScheduleRequestRepo for DBClient {
     async fn create_duty_schedule_request(
         &self,
-        satker_id: Uuid,
-        user_id: Uuid,
-        start_at: DateTime<Utc>,
-        end_at: DateTime<Utc>,
-        schedule_type: ScheduleType,
-        title: Option<String>,
-        note: Option<String>,
+        new_request: NewDutyScheduleRequest,
     ) -> Result<Uuid, Error> {
         let row = sqlx::query!(
             r#"
@@ -59,13 +57,13 @@ impl DutyScheduleRequestRepo for DBClient {
             VALUES ($1, $2, $3, $4, $5, $6, $7, 'SUBMITTED')
             RETURNING id
             "#,
-            satker_id,
-            user_id,
-            start_at,
-            end_at,
-            schedule_type as ScheduleType,
-            title,
-            note
+            new_request.satker_id,
+            new_request.user_id,
+            new_request.start_at,
+            new_request.end_at,
+            new_request.schedule_type as ScheduleType,
+            new_request.title,
+            new_request.note
         )
         .fetch_one(&self.pool)
         .await?;
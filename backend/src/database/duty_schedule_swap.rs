@@ -0,0 +1,402 @@
+use crate::DBClient;
+use crate::dtos::duty_schedule_swap::{
+    DutyScheduleSwapDto, DutyScheduleSwapEventDto, SWAP_ACCEPTED, SWAP_APPROVED,
+    SWAP_PENDING_PARTNER, SwapLockRow, SwapScheduleRow,
+};
+use async_trait::async_trait;
+use sqlx::types::Json;
+use sqlx::{Error, PgConnection};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait DutyScheduleSwapRepo {
+    async fn find_swap_schedule(&self, schedule_id: Uuid)
+    -> Result<Option<SwapScheduleRow>, Error>;
+
+    /// Jadwal sedang ada di pengajuan tukar yang belum selesai (sebagai pemohon / rekan).
+    async fn has_open_swap_for_schedule(&self, schedule_id: Uuid) -> Result<bool, Error>;
+
+    async fn create_duty_schedule_swap(
+        &self,
+        satker_id: Uuid,
+        requester_schedule: &SwapScheduleRow,
+        counterpart_id: Uuid,
+        counterpart_schedule: Option<&SwapScheduleRow>,
+        note: Option<String>,
+    ) -> Result<Uuid, Error>;
+
+    /// `user_id` cocok dengan pemohon atau rekan.
+    async fn list_duty_schedule_swaps(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        status: Option<&str>,
+    ) -> Result<Vec<DutyScheduleSwapDto>, Error>;
+
+    async fn find_duty_schedule_swap(&self, id: Uuid)
+    -> Result<Option<DutyScheduleSwapDto>, Error>;
+}
+
+#[async_trait]
+impl DutyScheduleSwapRepo for DBClient {
+    async fn find_swap_schedule(
+        &self,
+        schedule_id: Uuid,
+    ) -> Result<Option<SwapScheduleRow>, Error> {
+        let row = sqlx::query_as!(
+            SwapScheduleRow,
+            r#"
+            SELECT id, satker_id, user_id, start_at as "start_at!", end_at as "end_at!"
+            FROM duty_schedules
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+            schedule_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn has_open_swap_for_schedule(&self, schedule_id: Uuid) -> Result<bool, Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT 1 as one
+            FROM duty_schedule_swaps
+            WHERE status IN ($2, $3)
+              AND (requester_schedule_id = $1 OR counterpart_schedule_id = $1)
+            LIMIT 1
+            "#,
+            schedule_id,
+            SWAP_PENDING_PARTNER,
+            SWAP_ACCEPTED
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.is_some())
+    }
+
+    async fn create_duty_schedule_swap(
+        &self,
+        satker_id: Uuid,
+        requester_schedule: &SwapScheduleRow,
+        counterpart_id: Uuid,
+        counterpart_schedule: Option<&SwapScheduleRow>,
+        note: Option<String>,
+    ) -> Result<Uuid, Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO duty_schedule_swaps (
+                satker_id,
+                requester_id, requester_schedule_id, requester_start_at, requester_end_at,
+                counterpart_id, counterpart_schedule_id, counterpart_start_at, counterpart_end_at,
+                note, status
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING id
+            "#,
+            satker_id,
+            requester_schedule.user_id,
+            requester_schedule.id,
+            requester_schedule.start_at,
+            requester_schedule.end_at,
+            counterpart_id,
+            counterpart_schedule.map(|s| s.id),
+            counterpart_schedule.map(|s| s.start_at),
+            counterpart_schedule.map(|s| s.end_at),
+            note,
+            SWAP_PENDING_PARTNER
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        insert_swap_event(
+            &mut tx,
+            row.id,
+            Some(requester_schedule.user_id),
+            "CREATED",
+            None,
+            SWAP_PENDING_PARTNER,
+            note.as_deref(),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(row.id)
+    }
+
+    async fn list_duty_schedule_swaps(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        status: Option<&str>,
+    ) -> Result<Vec<DutyScheduleSwapDto>, Error> {
+        let rows = sqlx::query_as!(
+            DutyScheduleSwapDto,
+            r#"
+            SELECT w.id,
+                   w.satker_id,
+                   s.code AS satker_code,
+                   s.name AS satker_name,
+                   w.requester_id,
+                   ru.full_name AS requester_full_name,
+                   ru.nrp AS requester_nrp,
+                   w.requester_schedule_id,
+                   w.requester_start_at,
+                   w.requester_end_at,
+                   w.counterpart_id,
+                   cu.full_name AS counterpart_full_name,
+                   cu.nrp AS counterpart_nrp,
+                   w.counterpart_schedule_id,
+                   w.counterpart_start_at,
+                   w.counterpart_end_at,
+                   w.note,
+                   w.status,
+                   w.reject_reason,
+                   w.decided_by,
+                   w.decided_at,
+                   COALESCE(
+                       (
+                           SELECT json_agg(json_build_object(
+                               'id', e.id,
+                               'actor_id', e.actor_id,
+                               'actor_name', au.full_name,
+                               'action', e.action,
+                               'from_status', e.from_status,
+                               'to_status', e.to_status,
+                               'note', e.note,
+                               'created_at', e.created_at
+                           ) ORDER BY e.created_at)
+                           FROM duty_schedule_swap_events e
+                           LEFT JOIN users au ON au.id = e.actor_id
+                           WHERE e.swap_id = w.id
+                       ),
+                       '[]'::json
+                   ) as "history!: Json<Vec<DutyScheduleSwapEventDto>>",
+                   w.created_at,
+                   w.updated_at
+            FROM duty_schedule_swaps w
+            JOIN satkers s ON s.id = w.satker_id
+            JOIN users ru ON ru.id = w.requester_id
+            JOIN users cu ON cu.id = w.counterpart_id
+            WHERE ($1::uuid IS NULL OR w.satker_id = $1)
+              AND ($2::uuid IS NULL OR w.requester_id = $2 OR w.counterpart_id = $2)
+              AND ($3::text IS NULL OR w.status = $3)
+            ORDER BY w.created_at DESC
+            "#,
+            satker_id,
+            user_id,
+            status
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn find_duty_schedule_swap(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<DutyScheduleSwapDto>, Error> {
+        let row = sqlx::query_as!(
+            DutyScheduleSwapDto,
+            r#"
+            SELECT w.id,
+                   w.satker_id,
+                   s.code AS satker_code,
+                   s.name AS satker_name,
+                   w.requester_id,
+                   ru.full_name AS requester_full_name,
+                   ru.nrp AS requester_nrp,
+                   w.requester_schedule_id,
+                   w.requester_start_at,
+                   w.requester_end_at,
+                   w.counterpart_id,
+                   cu.full_name AS counterpart_full_name,
+                   cu.nrp AS counterpart_nrp,
+                   w.counterpart_schedule_id,
+                   w.counterpart_start_at,
+                   w.counterpart_end_at,
+                   w.note,
+                   w.status,
+                   w.reject_reason,
+                   w.decided_by,
+                   w.decided_at,
+                   COALESCE(
+                       (
+                           SELECT json_agg(json_build_object(
+                               'id', e.id,
+                               'actor_id', e.actor_id,
+                               'actor_name', au.full_name,
+                               'action', e.action,
+                               'from_status', e.from_status,
+                               'to_status', e.to_status,
+                               'note', e.note,
+                               'created_at', e.created_at
+                           ) ORDER BY e.created_at)
+                           FROM duty_schedule_swap_events e
+                           LEFT JOIN users au ON au.id = e.actor_id
+                           WHERE e.swap_id = w.id
+                       ),
+                       '[]'::json
+                   ) as "history!: Json<Vec<DutyScheduleSwapEventDto>>",
+                   w.created_at,
+                   w.updated_at
+            FROM duty_schedule_swaps w
+            JOIN satkers s ON s.id = w.satker_id
+            JOIN users ru ON ru.id = w.requester_id
+            JOIN users cu ON cu.id = w.counterpart_id
+            WHERE w.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+}
+
+/// Kunci baris pengajuan tukar (FOR UPDATE) di dalam transaksi.
+pub async fn lock_duty_schedule_swap(
+    conn: &mut PgConnection,
+    id: Uuid,
+) -> Result<Option<SwapLockRow>, Error> {
+    let row = sqlx::query_as!(
+        SwapLockRow,
+        r#"
+        SELECT id, satker_id,
+               requester_id, requester_schedule_id, requester_start_at, requester_end_at,
+               counterpart_id, counterpart_schedule_id, counterpart_start_at, counterpart_end_at,
+               status
+        FROM duty_schedule_swaps
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(row)
+}
+
+/// Kunci jadwal dinas (FOR UPDATE); None kalau sudah dihapus.
+pub async fn lock_swap_schedule(
+    conn: &mut PgConnection,
+    schedule_id: Uuid,
+) -> Result<Option<SwapScheduleRow>, Error> {
+    let row = sqlx::query_as!(
+        SwapScheduleRow,
+        r#"
+        SELECT id, satker_id, user_id, start_at as "start_at!", end_at as "end_at!"
+        FROM duty_schedules
+        WHERE id = $1 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        schedule_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(row)
+}
+
+/// Pindahkan jadwal ke user lain (hasil tukar jadwal disetujui).
+pub async fn reassign_duty_schedule(
+    conn: &mut PgConnection,
+    schedule_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE duty_schedules
+        SET user_id = $2, updated_at = now()
+        WHERE id = $1 AND deleted_at IS NULL
+        "#,
+        schedule_id,
+        user_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Ubah status pengajuan tukar dan catat ke riwayat.
+/// `decided` = true untuk keputusan admin/kepala satker (APPROVED/REJECTED).
+pub async fn update_duty_schedule_swap_status(
+    conn: &mut PgConnection,
+    swap: &SwapLockRow,
+    actor_id: Uuid,
+    action: &str,
+    to_status: &str,
+    note: Option<&str>,
+    decided: bool,
+) -> Result<(), Error> {
+    let reject_reason = if decided && to_status != SWAP_APPROVED {
+        note
+    } else {
+        None
+    };
+
+    sqlx::query!(
+        r#"
+        UPDATE duty_schedule_swaps
+        SET status = $2,
+            reject_reason = COALESCE($3, reject_reason),
+            decided_by = CASE WHEN $4 THEN $5 ELSE decided_by END,
+            decided_at = CASE WHEN $4 THEN now() ELSE decided_at END,
+            updated_at = now()
+        WHERE id = $1
+        "#,
+        swap.id,
+        to_status,
+        reject_reason,
+        decided,
+        actor_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    insert_swap_event(
+        conn,
+        swap.id,
+        Some(actor_id),
+        action,
+        Some(&swap.status),
+        to_status,
+        note,
+    )
+    .await
+}
+
+async fn insert_swap_event(
+    conn: &mut PgConnection,
+    swap_id: Uuid,
+    actor_id: Option<Uuid>,
+    action: &str,
+    from_status: Option<&str>,
+    to_status: &str,
+    note: Option<&str>,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO duty_schedule_swap_events (swap_id, actor_id, action, from_status, to_status, note)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        swap_id,
+        actor_id,
+        action,
+        from_status,
+        to_status,
+        note
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use crate::DBClient;
+use crate::dtos::face_enrollment::{
+    FACE_ENROLLMENT_APPROVED, FACE_ENROLLMENT_PENDING, FaceEnrollmentDto, FaceEnrollmentLockRow,
+    FaceTemplateRow,
+};
+use async_trait::async_trait;
+use sqlx::{Error, PgConnection};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait FaceEnrollmentRepo {
+    async fn create_face_enrollment(
+        &self,
+        user_id: Uuid,
+        satker_id: Uuid,
+        photo_object_key: &str,
+        submitted_by: Uuid,
+    ) -> Result<Uuid, Error>;
+
+    async fn list_face_enrollments(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        status: Option<&str>,
+    ) -> Result<Vec<FaceEnrollmentDto>, Error>;
+
+    async fn find_face_enrollment(&self, id: Uuid) -> Result<Option<FaceEnrollmentDto>, Error>;
+
+    /// Enrolment pemilik foto referensi (untuk cek akses file).
+    async fn find_face_enrollment_by_key(
+        &self,
+        photo_object_key: &str,
+    ) -> Result<Option<FaceEnrollmentDto>, Error>;
+
+    /// Template dari enrolment APPROVED milik user.
+    async fn list_face_templates(&self, user_id: Uuid) -> Result<Vec<FaceTemplateRow>, Error>;
+}
+
+#[async_trait]
+impl FaceEnrollmentRepo for DBClient {
+    async fn create_face_enrollment(
+        &self,
+        user_id: Uuid,
+        satker_id: Uuid,
+        photo_object_key: &str,
+        submitted_by: Uuid,
+    ) -> Result<Uuid, Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO face_enrollments (user_id, satker_id, photo_object_key, status, submitted_by)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+            user_id,
+            satker_id,
+            photo_object_key,
+            FACE_ENROLLMENT_PENDING,
+            submitted_by
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn list_face_enrollments(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        status: Option<&str>,
+    ) -> Result<Vec<FaceEnrollmentDto>, Error> {
+        let rows = sqlx::query_as!(
+            FaceEnrollmentDto,
+            r#"
+            SELECT f.id, f.satker_id,
+                   f.user_id, u.full_name AS user_full_name, u.nrp AS user_nrp,
+                   f.photo_object_key, f.status, f.verifier,
+                   f.submitted_by, s.full_name AS "submitted_by_name?",
+                   f.reject_reason,
+                   f.decided_by, d.full_name AS "decided_by_name?", f.decided_at,
+                   f.created_at, f.updated_at
+            FROM face_enrollments f
+            JOIN users u ON u.id = f.user_id
+            LEFT JOIN users s ON s.id = f.submitted_by
+            LEFT JOIN users d ON d.id = f.decided_by
+            WHERE ($1::uuid IS NULL OR f.satker_id = $1)
+              AND ($2::uuid IS NULL OR f.user_id = $2)
+              AND ($3::text IS NULL OR f.status = $3)
+            ORDER BY f.created_at DESC
+            "#,
+            satker_id,
+            user_id,
+            status
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn find_face_enrollment(&self, id: Uuid) -> Result<Option<FaceEnrollmentDto>, Error> {
+        let row = sqlx::query_as!(
+            FaceEnrollmentDto,
+            r#"
+            SELECT f.id, f.satker_id,
+                   f.user_id, u.full_name AS user_full_name, u.nrp AS user_nrp,
+                   f.photo_object_key, f.status, f.verifier,
+                   f.submitted_by, s.full_name AS "submitted_by_name?",
+                   f.reject_reason,
+                   f.decided_by, d.full_name AS "decided_by_name?", f.decided_at,
+                   f.created_at, f.updated_at
+            FROM face_enrollments f
+            JOIN users u ON u.id = f.user_id
+            LEFT JOIN users s ON s.id = f.submitted_by
+            LEFT JOIN users d ON d.id = f.decided_by
+            WHERE f.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn find_face_enrollment_by_key(
+        &self,
+        photo_object_key: &str,
+    ) -> Result<Option<FaceEnrollmentDto>, Error> {
+        let row = sqlx::query_as!(
+            FaceEnrollmentDto,
+            r#"
+            SELECT f.id, f.satker_id,
+                   f.user_id, u.full_name AS user_full_name, u.nrp AS user_nrp,
+                   f.photo_object_key, f.status, f.verifier,
+                   f.submitted_by, s.full_name AS "submitted_by_name?",
+                   f.reject_reason,
+                   f.decided_by, d.full_name AS "decided_by_name?", f.decided_at,
+                   f.created_at, f.updated_at
+            FROM face_enrollments f
+            JOIN users u ON u.id = f.user_id
+            LEFT JOIN users s ON s.id = f.submitted_by
+            LEFT JOIN users d ON d.id = f.decided_by
+            WHERE f.photo_object_key = $1
+            "#,
+            photo_object_key
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list_face_templates(&self, user_id: Uuid) -> Result<Vec<FaceTemplateRow>, Error> {
+        let rows = sqlx::query_as!(
+            FaceTemplateRow,
+            r#"
+            SELECT template AS "template!", verifier
+            FROM face_enrollments
+            WHERE user_id = $1 AND status = $2 AND template IS NOT NULL
+            ORDER BY decided_at
+            "#,
+            user_id,
+            FACE_ENROLLMENT_APPROVED
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
+
+pub async fn lock_face_enrollment(
+    conn: &mut PgConnection,
+    id: Uuid,
+) -> Result<Option<FaceEnrollmentLockRow>, Error> {
+    let row = sqlx::query_as!(
+        FaceEnrollmentLockRow,
+        r#"
+        SELECT satker_id, user_id, photo_object_key, status
+        FROM face_enrollments
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(row)
+}
+
+/// Ubah status enrolment. `template`/`verifier` hanya diisi saat APPROVED.
+pub async fn update_face_enrollment_status(
+    conn: &mut PgConnection,
+    id: Uuid,
+    to_status: &str,
+    actor_id: Uuid,
+    template: Option<&str>,
+    verifier: Option<&str>,
+    reject_reason: Option<&str>,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE face_enrollments
+        SET status = $2,
+            template = COALESCE($4, template),
+            verifier = COALESCE($5, verifier),
+            reject_reason = $6,
+            decided_by = $3,
+            decided_at = now(),
+            updated_at = now()
+        WHERE id = $1
+        "#,
+        id,
+        to_status,
+        actor_id,
+        template,
+        verifier,
+        reject_reason
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Sinkronkan users.face_template_version/hash dengan set template APPROVED user.
+pub async fn refresh_user_face_template(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE users u
+        SET face_template_version = u.face_template_version + 1,
+            face_template_hash = (
+                SELECT encode(sha256(convert_to(string_agg(f.id::text || ':' || f.template, ',' ORDER BY f.id), 'UTF8')), 'hex')
+                FROM face_enrollments f
+                WHERE f.user_id = u.id AND f.status = $2
+            ),
+            updated_at = now()
+        WHERE u.id = $1
+        "#,
+        user_id,
+        FACE_ENROLLMENT_APPROVED
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
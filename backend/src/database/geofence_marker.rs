@@ -0,0 +1,125 @@
+use crate::DBClient;
+use crate::dtos::geofence_marker::{GeofenceMarkerDto, ProximityMarkerMatch};
+use async_trait::async_trait;
+use sqlx::Error;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait GeofenceMarkerRepo {
+    async fn create_geofence_marker(
+        &self,
+        geofence_id: Uuid,
+        kind: &str,
+        identifier: &str,
+        label: Option<&str>,
+        created_by: Uuid,
+    ) -> Result<GeofenceMarkerDto, Error>;
+
+    async fn list_geofence_markers(
+        &self,
+        geofence_id: Uuid,
+    ) -> Result<Vec<GeofenceMarkerDto>, Error>;
+
+    async fn delete_geofence_marker(&self, geofence_id: Uuid, id: Uuid) -> Result<u64, Error>;
+
+    /// Penanda pada geofence aktif satker yang identifier-nya ada di hasil scan.
+    async fn find_matching_proximity_markers(
+        &self,
+        satker_id: Uuid,
+        wifi: &[String],
+        ble: &[String],
+    ) -> Result<Vec<ProximityMarkerMatch>, Error>;
+}
+
+#[async_trait]
+impl GeofenceMarkerRepo for DBClient {
+    async fn create_geofence_marker(
+        &self,
+        geofence_id: Uuid,
+        kind: &str,
+        identifier: &str,
+        label: Option<&str>,
+        created_by: Uuid,
+    ) -> Result<GeofenceMarkerDto, Error> {
+        let row = sqlx::query_as!(
+            GeofenceMarkerDto,
+            r#"
+            INSERT INTO geofence_proximity_markers (geofence_id, kind, identifier, label, created_by)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, geofence_id, kind, identifier, label, created_by, created_at
+            "#,
+            geofence_id,
+            kind,
+            identifier,
+            label,
+            created_by
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list_geofence_markers(
+        &self,
+        geofence_id: Uuid,
+    ) -> Result<Vec<GeofenceMarkerDto>, Error> {
+        let rows = sqlx::query_as!(
+            GeofenceMarkerDto,
+            r#"
+            SELECT id, geofence_id, kind, identifier, label, created_by, created_at
+            FROM geofence_proximity_markers
+            WHERE geofence_id = $1
+            ORDER BY kind, identifier
+            "#,
+            geofence_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn delete_geofence_marker(&self, geofence_id: Uuid, id: Uuid) -> Result<u64, Error> {
+        let res = sqlx::query!(
+            r#"
+            DELETE FROM geofence_proximity_markers
+            WHERE id = $1 AND geofence_id = $2
+            "#,
+            id,
+            geofence_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+
+    async fn find_matching_proximity_markers(
+        &self,
+        satker_id: Uuid,
+        wifi: &[String],
+        ble: &[String],
+    ) -> Result<Vec<ProximityMarkerMatch>, Error> {
+        let rows = sqlx::query_as!(
+            ProximityMarkerMatch,
+            r#"
+            SELECT m.id, m.kind, m.identifier,
+                   g.id AS geofence_id, g.latitude, g.longitude, g.radius_meters
+            FROM geofence_proximity_markers m
+            JOIN geofences g ON g.id = m.geofence_id
+            WHERE g.satker_id = $1
+              AND g.is_active = TRUE
+              AND ((m.kind = 'WIFI' AND m.identifier = ANY($2))
+                OR (m.kind = 'BLE' AND m.identifier = ANY($3)))
+            "#,
+            satker_id,
+            wifi,
+            ble
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
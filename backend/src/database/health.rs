@@ -0,0 +1,32 @@
+use crate::db::DBClient;
+
+pub trait HealthRepo {
+    async fn ping_db(&self) -> Result<(), sqlx::Error>;
+
+    /// Versi migration tertinggi yang sukses di `_sqlx_migrations`.
+    /// `None` kalau tabel belum ada (schema diterapkan manual).
+    async fn applied_migration_version(&self) -> Result<Option<i64>, sqlx::Error>;
+}
+
+impl HealthRepo for DBClient {
+    async fn ping_db(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    async fn applied_migration_version(&self) -> Result<Option<i64>, sqlx::Error> {
+        let exists: bool =
+            sqlx::query_scalar("SELECT to_regclass('public._sqlx_migrations') IS NOT NULL")
+                .fetch_one(&self.pool)
+                .await?;
+        if !exists {
+            return Ok(None);
+        }
+
+        let version: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(version)
+    }
+}
@@ -3,7 +3,7 @@ use crate::db::DBClient;
 use crate::models::Holiday;
 use async_trait::async_trait;
 use chrono::{NaiveDate, NaiveTime};
-use sqlx::Error;
+use sqlx::{Error, PgConnection};
 use uuid::Uuid;
 
 #[derive(Debug, Clone)]
@@ -295,3 +295,84 @@ impl HolidayRepo for DBClient {
         Ok(res.rows_affected())
     }
 }
+
+/// Libur satu scope di range tanggal, dikunci (FOR UPDATE) untuk import.
+pub async fn lock_scope_holidays(
+    conn: &mut PgConnection,
+    scope: HolidayScope,
+    satker_id: Option<Uuid>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<Holiday>, Error> {
+    let rows = sqlx::query_as!(
+        Holiday,
+        r#"
+        SELECT
+          id,
+          scope as "scope: HolidayScope",
+          satker_id,
+          holiday_date,
+          kind as "kind: HolidayKind",
+          name,
+          half_day_end
+        FROM holidays
+        WHERE scope = $1
+          AND satker_id IS NOT DISTINCT FROM $2
+          AND holiday_date BETWEEN $3 AND $4
+        ORDER BY holiday_date ASC
+        FOR UPDATE
+        "#,
+        scope as HolidayScope,
+        satker_id,
+        from,
+        to
+    )
+    .fetch_all(conn)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn insert_holiday_in(
+    conn: &mut PgConnection,
+    scope: HolidayScope,
+    satker_id: Option<Uuid>,
+    item: &HolidayUpsertItem,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO holidays (scope, satker_id, holiday_date, kind, name, half_day_end)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        scope as HolidayScope,
+        satker_id,
+        item.holiday_date,
+        item.kind as HolidayKind,
+        item.name,
+        item.half_day_end,
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+pub async fn update_holiday_in(
+    conn: &mut PgConnection,
+    id: Uuid,
+    item: &HolidayUpsertItem,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE holidays
+        SET kind = $2, name = $3, half_day_end = $4, updated_at = now()
+        WHERE id = $1
+        "#,
+        id,
+        item.kind as HolidayKind,
+        item.name,
+        item.half_day_end,
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
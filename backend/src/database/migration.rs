@@ -0,0 +1,91 @@
+use sqlx::migrate::Migrate;
+
+use crate::db::DBClient;
+
+#[derive(Debug, Clone)]
+pub struct AppliedMigrationRow {
+    pub version: i64,
+    pub checksum: Vec<u8>,
+    pub success: bool,
+}
+
+pub trait MigrationRepo {
+    /// Apakah tabel `_sqlx_migrations` sudah ada.
+    async fn has_migration_table(&self) -> Result<bool, sqlx::Error>;
+
+    /// Apakah schema aplikasi sudah ada (tabel `users`), walaupun belum tercatat
+    /// di `_sqlx_migrations` (migration dulu diterapkan manual lewat psql).
+    async fn has_app_schema(&self) -> Result<bool, sqlx::Error>;
+
+    async fn list_applied_migrations(&self) -> Result<Vec<AppliedMigrationRow>, sqlx::Error>;
+
+    async fn ensure_migration_table(&self) -> Result<(), sqlx::Error>;
+
+    /// Catat migration sebagai sudah diterapkan tanpa menjalankan SQL-nya.
+    async fn insert_baseline_migration(
+        &self,
+        version: i64,
+        description: &str,
+        checksum: &[u8],
+    ) -> Result<bool, sqlx::Error>;
+}
+
+impl MigrationRepo for DBClient {
+    async fn has_migration_table(&self) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT to_regclass('public._sqlx_migrations') IS NOT NULL")
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn has_app_schema(&self) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT to_regclass('public.users') IS NOT NULL")
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn list_applied_migrations(&self) -> Result<Vec<AppliedMigrationRow>, sqlx::Error> {
+        let rows: Vec<(i64, Vec<u8>, bool)> = sqlx::query_as(
+            "SELECT version, checksum, success FROM _sqlx_migrations ORDER BY version",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(version, checksum, success)| AppliedMigrationRow {
+                version,
+                checksum,
+                success,
+            })
+            .collect())
+    }
+
+    async fn ensure_migration_table(&self) -> Result<(), sqlx::Error> {
+        let mut conn = self.pool.acquire().await?;
+        conn.ensure_migrations_table()
+            .await
+            .map_err(|e| sqlx::Error::Migrate(Box::new(e)))
+    }
+
+    async fn insert_baseline_migration(
+        &self,
+        version: i64,
+        description: &str,
+        checksum: &[u8],
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+            VALUES ($1, $2, TRUE, $3, 0)
+            ON CONFLICT (version) DO NOTHING
+            "#,
+        )
+        .bind(version)
+        .bind(description)
+        .bind(checksum)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -1,19 +1,36 @@
 pub mod announcement;
 pub mod attendance;
+pub mod attendance_anomaly;
 pub mod attendance_apel;
+pub mod attendance_away;
+pub mod attendance_rekap;
 pub mod attendance_session;
+pub mod calendar_feed;
+pub mod calendar_regen;
+pub mod duty_rotation;
 pub mod duty_schedule;
 pub mod duty_schedule_request;
+pub mod duty_schedule_swap;
+pub mod face_enrollment;
 pub mod geofence;
+pub mod geofence_marker;
+pub mod health;
 pub mod holiday;
 pub mod leave_request;
+pub mod migration;
+pub mod object_ref;
+pub mod overtime;
 pub mod rank;
+pub mod retention;
 pub mod satker;
 pub mod satker_head;
 pub mod schedule;
+pub mod selfie_upload;
 pub mod settings;
 pub mod tukin;
 pub mod user;
 pub mod user_device;
 pub mod work_calendar;
+pub mod work_group;
 pub mod work_pattern;
+pub mod work_pattern_override;
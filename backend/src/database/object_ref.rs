@@ -0,0 +1,210 @@
+use crate::DBClient;
+use crate::dtos::upload::{ObjectRefSource, StoredObjectRef};
+use async_trait::async_trait;
+use sqlx::Error;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait ObjectRefRepo {
+    /// Baris `source` yang key-nya diawali `key_prefix` (mis. `local://`), urut id, setelah `after`.
+    async fn list_object_refs_with_prefix(
+        &self,
+        source: ObjectRefSource,
+        key_prefix: &str,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<StoredObjectRef>, Error>;
+
+    /// Ganti key satu baris; 0 kalau key baris sudah berubah sejak dibaca.
+    async fn replace_object_ref(
+        &self,
+        source: ObjectRefSource,
+        id: Uuid,
+        old_key: &str,
+        new_key: &str,
+    ) -> Result<u64, Error>;
+}
+
+#[async_trait]
+impl ObjectRefRepo for DBClient {
+    async fn list_object_refs_with_prefix(
+        &self,
+        source: ObjectRefSource,
+        key_prefix: &str,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<StoredObjectRef>, Error> {
+        let rows = match source {
+            ObjectRefSource::AttendanceSelfie => {
+                sqlx::query_as!(
+                    StoredObjectRef,
+                    r#"
+                    SELECT id, selfie_object_key AS "object_key!", NULL::text AS content_type
+                    FROM attendance_events
+                    WHERE starts_with(selfie_object_key, $1)
+                      AND ($2::uuid IS NULL OR id > $2)
+                    ORDER BY id
+                    LIMIT $3
+                    "#,
+                    key_prefix,
+                    after,
+                    limit
+                )
+                .fetch_all(&self.pool)
+                .await?
+            }
+            ObjectRefSource::ProfilePhoto => {
+                sqlx::query_as!(
+                    StoredObjectRef,
+                    r#"
+                    SELECT id, profile_photo_key AS "object_key!", NULL::text AS content_type
+                    FROM users
+                    WHERE starts_with(profile_photo_key, $1)
+                      AND ($2::uuid IS NULL OR id > $2)
+                    ORDER BY id
+                    LIMIT $3
+                    "#,
+                    key_prefix,
+                    after,
+                    limit
+                )
+                .fetch_all(&self.pool)
+                .await?
+            }
+            ObjectRefSource::FacePhoto => {
+                sqlx::query_as!(
+                    StoredObjectRef,
+                    r#"
+                    SELECT id, photo_object_key AS object_key, NULL::text AS content_type
+                    FROM face_enrollments
+                    WHERE starts_with(photo_object_key, $1)
+                      AND ($2::uuid IS NULL OR id > $2)
+                    ORDER BY id
+                    LIMIT $3
+                    "#,
+                    key_prefix,
+                    after,
+                    limit
+                )
+                .fetch_all(&self.pool)
+                .await?
+            }
+            ObjectRefSource::AnnouncementAttachment => {
+                sqlx::query_as!(
+                    StoredObjectRef,
+                    r#"
+                    SELECT id, object_key, content_type AS "content_type?"
+                    FROM announcement_attachments
+                    WHERE starts_with(object_key, $1)
+                      AND ($2::uuid IS NULL OR id > $2)
+                    ORDER BY id
+                    LIMIT $3
+                    "#,
+                    key_prefix,
+                    after,
+                    limit
+                )
+                .fetch_all(&self.pool)
+                .await?
+            }
+            ObjectRefSource::SelfieUpload => {
+                sqlx::query_as!(
+                    StoredObjectRef,
+                    r#"
+                    SELECT id, object_key, 'image/jpeg'::text AS "content_type?"
+                    FROM selfie_uploads
+                    WHERE starts_with(object_key, $1)
+                      AND ($2::uuid IS NULL OR id > $2)
+                    ORDER BY id
+                    LIMIT $3
+                    "#,
+                    key_prefix,
+                    after,
+                    limit
+                )
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        Ok(rows)
+    }
+
+    async fn replace_object_ref(
+        &self,
+        source: ObjectRefSource,
+        id: Uuid,
+        old_key: &str,
+        new_key: &str,
+    ) -> Result<u64, Error> {
+        let res = match source {
+            ObjectRefSource::AttendanceSelfie => {
+                sqlx::query!(
+                    r#"
+                    UPDATE attendance_events SET selfie_object_key = $3
+                    WHERE id = $1 AND selfie_object_key = $2
+                    "#,
+                    id,
+                    old_key,
+                    new_key
+                )
+                .execute(&self.pool)
+                .await?
+            }
+            ObjectRefSource::ProfilePhoto => {
+                sqlx::query!(
+                    r#"
+                    UPDATE users SET profile_photo_key = $3
+                    WHERE id = $1 AND profile_photo_key = $2
+                    "#,
+                    id,
+                    old_key,
+                    new_key
+                )
+                .execute(&self.pool)
+                .await?
+            }
+            ObjectRefSource::FacePhoto => {
+                sqlx::query!(
+                    r#"
+                    UPDATE face_enrollments SET photo_object_key = $3
+                    WHERE id = $1 AND photo_object_key = $2
+                    "#,
+                    id,
+                    old_key,
+                    new_key
+                )
+                .execute(&self.pool)
+                .await?
+            }
+            ObjectRefSource::AnnouncementAttachment => {
+                sqlx::query!(
+                    r#"
+                    UPDATE announcement_attachments SET object_key = $3
+                    WHERE id = $1 AND object_key = $2
+                    "#,
+                    id,
+                    old_key,
+                    new_key
+                )
+                .execute(&self.pool)
+                .await?
+            }
+            ObjectRefSource::SelfieUpload => {
+                sqlx::query!(
+                    r#"
+                    UPDATE selfie_uploads SET object_key = $3
+                    WHERE id = $1 AND object_key = $2
+                    "#,
+                    id,
+                    old_key,
+                    new_key
+                )
+                .execute(&self.pool)
+                .await?
+            }
+        };
+
+        Ok(res.rows_affected())
+    }
+}
@@ -0,0 +1,466 @@
+use crate::DBClient;
+use crate::dtos::overtime::{
+    ApprovedOvertimeRow, OVERTIME_APPROVED, OVERTIME_SUBMITTED, OvertimeClaimDto,
+    OvertimeClaimEventDto, OvertimeClaimInsert, OvertimeClaimLockRow, OvertimeRateDto,
+    OvertimeRateInput,
+};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use sqlx::types::Json;
+use sqlx::{Error, PgConnection};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait OvertimeRepo {
+    async fn list_overtime_rates(&self) -> Result<Vec<OvertimeRateDto>, Error>;
+
+    /// Ganti seluruh tabel tarif lembur dalam satu transaksi.
+    async fn replace_overtime_rates(
+        &self,
+        rates: Vec<OvertimeRateInput>,
+    ) -> Result<Vec<OvertimeRateDto>, Error>;
+
+    async fn create_overtime_claim(&self, claim: &OvertimeClaimInsert) -> Result<Uuid, Error>;
+
+    async fn list_overtime_claims(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        status: Option<&str>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Vec<OvertimeClaimDto>, Error>;
+
+    async fn find_overtime_claim(&self, id: Uuid) -> Result<Option<OvertimeClaimDto>, Error>;
+
+    /// Klaim aktif (SUBMITTED/APPROVED) user di range tanggal: (work_date, id, status).
+    async fn list_open_overtime_claims(
+        &self,
+        user_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Uuid, String)>, Error>;
+
+    /// Klaim APPROVED satu satker di range tanggal, untuk rekap bulanan.
+    async fn list_approved_overtime(
+        &self,
+        satker_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<ApprovedOvertimeRow>, Error>;
+}
+
+#[async_trait]
+impl OvertimeRepo for DBClient {
+    async fn list_overtime_rates(&self) -> Result<Vec<OvertimeRateDto>, Error> {
+        let rows = sqlx::query_as!(
+            OvertimeRateDto,
+            r#"
+            SELECT o.id,
+                   o.rank_id,
+                   r.code as "rank_code?",
+                   r.name as "rank_name?",
+                   o.day_kind,
+                   o.hourly_rate,
+                   o.max_minutes_per_day,
+                   o.updated_at
+            FROM overtime_rates o
+            LEFT JOIN ranks r ON r.id = o.rank_id
+            ORDER BY r.code NULLS FIRST, o.day_kind
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn replace_overtime_rates(
+        &self,
+        rates: Vec<OvertimeRateInput>,
+    ) -> Result<Vec<OvertimeRateDto>, Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(r#"DELETE FROM overtime_rates"#)
+            .execute(&mut *tx)
+            .await?;
+
+        for r in rates {
+            sqlx::query!(
+                r#"
+                INSERT INTO overtime_rates (rank_id, day_kind, hourly_rate, max_minutes_per_day)
+                VALUES ($1, $2, $3, $4)
+                "#,
+                r.rank_id,
+                r.day_kind,
+                r.hourly_rate,
+                r.max_minutes_per_day
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        self.list_overtime_rates().await
+    }
+
+    async fn create_overtime_claim(&self, claim: &OvertimeClaimInsert) -> Result<Uuid, Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO overtime_claims (
+                satker_id, user_id, session_id, duty_schedule_id,
+                work_date, day_kind, start_at, end_at,
+                candidate_minutes, claimed_minutes, note, status
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING id
+            "#,
+            claim.satker_id,
+            claim.user_id,
+            claim.session_id,
+            claim.duty_schedule_id,
+            claim.work_date,
+            claim.day_kind,
+            claim.start_at,
+            claim.end_at,
+            claim.candidate_minutes,
+            claim.claimed_minutes,
+            claim.note,
+            OVERTIME_SUBMITTED
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        insert_overtime_claim_event(
+            &mut tx,
+            row.id,
+            Some(claim.user_id),
+            "CREATED",
+            None,
+            OVERTIME_SUBMITTED,
+            claim.note.as_deref(),
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(row.id)
+    }
+
+    async fn list_overtime_claims(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        status: Option<&str>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Vec<OvertimeClaimDto>, Error> {
+        let rows = sqlx::query_as!(
+            OvertimeClaimDto,
+            r#"
+            SELECT c.id,
+                   c.satker_id,
+                   s.code AS satker_code,
+                   s.name AS satker_name,
+                   c.user_id,
+                   u.full_name AS user_full_name,
+                   u.nrp AS user_nrp,
+                   c.session_id,
+                   c.duty_schedule_id,
+                   c.work_date,
+                   c.day_kind,
+                   c.start_at,
+                   c.end_at,
+                   c.candidate_minutes,
+                   c.claimed_minutes,
+                   c.approved_minutes,
+                   c.note,
+                   c.status,
+                   c.reject_reason,
+                   c.decided_by,
+                   c.decided_at,
+                   COALESCE(
+                       (
+                           SELECT json_agg(json_build_object(
+                               'id', e.id,
+                               'actor_id', e.actor_id,
+                               'actor_name', au.full_name,
+                               'action', e.action,
+                               'from_status', e.from_status,
+                               'to_status', e.to_status,
+                               'note', e.note,
+                               'created_at', e.created_at
+                           ) ORDER BY e.created_at)
+                           FROM overtime_claim_events e
+                           LEFT JOIN users au ON au.id = e.actor_id
+                           WHERE e.claim_id = c.id
+                       ),
+                       '[]'::json
+                   ) as "history!: Json<Vec<OvertimeClaimEventDto>>",
+                   c.created_at,
+                   c.updated_at
+            FROM overtime_claims c
+            JOIN satkers s ON s.id = c.satker_id
+            JOIN users u ON u.id = c.user_id
+            WHERE ($1::uuid IS NULL OR c.satker_id = $1)
+              AND ($2::uuid IS NULL OR c.user_id = $2)
+              AND ($3::text IS NULL OR c.status = $3)
+              AND ($4::date IS NULL OR c.work_date >= $4)
+              AND ($5::date IS NULL OR c.work_date <= $5)
+            ORDER BY c.work_date DESC, c.created_at DESC
+            "#,
+            satker_id,
+            user_id,
+            status,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn find_overtime_claim(&self, id: Uuid) -> Result<Option<OvertimeClaimDto>, Error> {
+        let row = sqlx::query_as!(
+            OvertimeClaimDto,
+            r#"
+            SELECT c.id,
+                   c.satker_id,
+                   s.code AS satker_code,
+                   s.name AS satker_name,
+                   c.user_id,
+                   u.full_name AS user_full_name,
+                   u.nrp AS user_nrp,
+                   c.session_id,
+                   c.duty_schedule_id,
+                   c.work_date,
+                   c.day_kind,
+                   c.start_at,
+                   c.end_at,
+                   c.candidate_minutes,
+                   c.claimed_minutes,
+                   c.approved_minutes,
+                   c.note,
+                   c.status,
+                   c.reject_reason,
+                   c.decided_by,
+                   c.decided_at,
+                   COALESCE(
+                       (
+                           SELECT json_agg(json_build_object(
+                               'id', e.id,
+                               'actor_id', e.actor_id,
+                               'actor_name', au.full_name,
+                               'action', e.action,
+                               'from_status', e.from_status,
+                               'to_status', e.to_status,
+                               'note', e.note,
+                               'created_at', e.created_at
+                           ) ORDER BY e.created_at)
+                           FROM overtime_claim_events e
+                           LEFT JOIN users au ON au.id = e.actor_id
+                           WHERE e.claim_id = c.id
+                       ),
+                       '[]'::json
+                   ) as "history!: Json<Vec<OvertimeClaimEventDto>>",
+                   c.created_at,
+                   c.updated_at
+            FROM overtime_claims c
+            JOIN satkers s ON s.id = c.satker_id
+            JOIN users u ON u.id = c.user_id
+            WHERE c.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn list_open_overtime_claims(
+        &self,
+        user_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Uuid, String)>, Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT work_date, id, status
+            FROM overtime_claims
+            WHERE user_id = $1
+              AND work_date BETWEEN $2 AND $3
+              AND status IN ($4, $5)
+            "#,
+            user_id,
+            from,
+            to,
+            OVERTIME_SUBMITTED,
+            OVERTIME_APPROVED
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.work_date, r.id, r.status))
+            .collect())
+    }
+
+    async fn list_approved_overtime(
+        &self,
+        satker_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<ApprovedOvertimeRow>, Error> {
+        let rows = sqlx::query_as!(
+            ApprovedOvertimeRow,
+            r#"
+            SELECT c.user_id,
+                   u.full_name,
+                   u.nrp,
+                   u.rank_id,
+                   r.code as "rank_code?",
+                   r.name as "rank_name?",
+                   c.day_kind,
+                   c.approved_minutes as "approved_minutes!"
+            FROM overtime_claims c
+            JOIN users u ON u.id = c.user_id
+            LEFT JOIN ranks r ON r.id = u.rank_id
+            WHERE c.satker_id = $1
+              AND c.work_date BETWEEN $2 AND $3
+              AND c.status = $4
+              AND c.approved_minutes IS NOT NULL
+            ORDER BY u.full_name, c.work_date
+            "#,
+            satker_id,
+            from,
+            to,
+            OVERTIME_APPROVED
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
+
+/// Kunci baris klaim lembur (FOR UPDATE) di dalam transaksi.
+pub async fn lock_overtime_claim(
+    conn: &mut PgConnection,
+    id: Uuid,
+) -> Result<Option<OvertimeClaimLockRow>, Error> {
+    let row = sqlx::query_as!(
+        OvertimeClaimLockRow,
+        r#"
+        SELECT id, satker_id, user_id, claimed_minutes, status
+        FROM overtime_claims
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(row)
+}
+
+/// Menit lembur yang disetujui (dipanggil sebelum status diubah ke APPROVED).
+pub async fn set_overtime_approved_minutes(
+    conn: &mut PgConnection,
+    id: Uuid,
+    approved_minutes: i32,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE overtime_claims
+        SET approved_minutes = $2, updated_at = now()
+        WHERE id = $1
+        "#,
+        id,
+        approved_minutes
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Ubah status klaim lembur dan catat ke riwayat.
+/// `decided` = true untuk keputusan kepala satker (APPROVED/REJECTED).
+pub async fn update_overtime_claim_status(
+    conn: &mut PgConnection,
+    claim: &OvertimeClaimLockRow,
+    actor_id: Uuid,
+    action: &str,
+    to_status: &str,
+    note: Option<&str>,
+    decided: bool,
+) -> Result<(), Error> {
+    let reject_reason = if decided && to_status != OVERTIME_APPROVED {
+        note
+    } else {
+        None
+    };
+
+    sqlx::query!(
+        r#"
+        UPDATE overtime_claims
+        SET status = $2,
+            reject_reason = COALESCE($3, reject_reason),
+            decided_by = CASE WHEN $4 THEN $5 ELSE decided_by END,
+            decided_at = CASE WHEN $4 THEN now() ELSE decided_at END,
+            updated_at = now()
+        WHERE id = $1
+        "#,
+        claim.id,
+        to_status,
+        reject_reason,
+        decided,
+        actor_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    insert_overtime_claim_event(
+        conn,
+        claim.id,
+        Some(actor_id),
+        action,
+        Some(&claim.status),
+        to_status,
+        note,
+    )
+    .await
+}
+
+async fn insert_overtime_claim_event(
+    conn: &mut PgConnection,
+    claim_id: Uuid,
+    actor_id: Option<Uuid>,
+    action: &str,
+    from_status: Option<&str>,
+    to_status: &str,
+    note: Option<&str>,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO overtime_claim_events (claim_id, actor_id, action, from_status, to_status, note)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        claim_id,
+        actor_id,
+        action,
+        from_status,
+        to_status,
+        note
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
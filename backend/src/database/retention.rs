@@ -0,0 +1,393 @@
+use crate::DBClient;
+use crate::dtos::retention::{CreateLegalHoldReq, LegalHoldDto, RetentionRunDto, StorageUsageRow};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::Error;
+use uuid::Uuid;
+
+/// Kunci advisory untuk klaim putaran retensi (satu purge berjalan di semua instance).
+const RETENTION_LOCK_KEY: i64 = 0x7265_7465_6e74;
+
+#[async_trait]
+pub trait RetentionRepo {
+    async fn create_legal_hold(
+        &self,
+        satker_id: Uuid,
+        req: &CreateLegalHoldReq,
+        created_by: Uuid,
+    ) -> Result<Uuid, Error>;
+
+    async fn find_legal_hold(&self, id: Uuid) -> Result<Option<LegalHoldDto>, Error>;
+
+    async fn list_legal_holds(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        active_only: bool,
+    ) -> Result<Vec<LegalHoldDto>, Error>;
+
+    /// 0 kalau hold sudah dilepas sebelumnya.
+    async fn release_legal_hold(&self, id: Uuid, released_by: Uuid) -> Result<u64, Error>;
+
+    /// User yang punya legal hold aktif (rentang tanggal apa pun).
+    async fn list_held_user_ids(&self) -> Result<Vec<Uuid>, Error>;
+
+    /// Foto profil user nonaktif sejak sebelum `before` yang tidak ditahan legal hold.
+    async fn list_expired_profile_photos(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<Vec<(Uuid, String)>, Error>;
+
+    async fn count_held_profile_photos(&self, before: DateTime<Utc>) -> Result<i64, Error>;
+
+    /// Kosongkan foto profil kalau key-nya belum berubah sejak dibaca.
+    async fn clear_profile_photo_key(&self, user_id: Uuid, key: &str) -> Result<u64, Error>;
+
+    /// Catat putaran retensi baru. None kalau purge lain sedang berjalan, atau (bila
+    /// `not_since` diisi) sudah ada purge sungguhan yang dimulai setelah `not_since`.
+    async fn claim_retention_run(
+        &self,
+        kind: &str,
+        requested_by: Option<Uuid>,
+        dry_run: bool,
+        not_since: Option<DateTime<Utc>>,
+    ) -> Result<Option<Uuid>, Error>;
+
+    async fn finish_retention_run(
+        &self,
+        id: Uuid,
+        report: Option<serde_json::Value>,
+        error: Option<&str>,
+    ) -> Result<(), Error>;
+
+    async fn list_retention_runs(&self, limit: i64) -> Result<Vec<RetentionRunDto>, Error>;
+
+    /// Jumlah & ukuran objek upload per satker per bulan (`tz` = zona waktu aplikasi).
+    async fn storage_usage(
+        &self,
+        tz: &str,
+        satker_id: Option<Uuid>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Vec<StorageUsageRow>, Error>;
+}
+
+#[async_trait]
+impl RetentionRepo for DBClient {
+    async fn create_legal_hold(
+        &self,
+        satker_id: Uuid,
+        req: &CreateLegalHoldReq,
+        created_by: Uuid,
+    ) -> Result<Uuid, Error> {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO legal_holds (satker_id, user_id, from_date, to_date, reason, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id
+            "#,
+            satker_id,
+            req.user_id,
+            req.from_date,
+            req.to_date,
+            req.reason.trim(),
+            created_by
+        )
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn find_legal_hold(&self, id: Uuid) -> Result<Option<LegalHoldDto>, Error> {
+        sqlx::query_as!(
+            LegalHoldDto,
+            r#"
+            SELECT h.id, h.satker_id, h.user_id,
+                   u.full_name AS user_full_name, u.nrp AS user_nrp,
+                   h.from_date, h.to_date, h.reason,
+                   h.created_by, c.full_name AS "created_by_name?",
+                   h.created_at, h.released_at, h.released_by
+            FROM legal_holds h
+            JOIN users u ON u.id = h.user_id
+            LEFT JOIN users c ON c.id = h.created_by
+            WHERE h.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn list_legal_holds(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        active_only: bool,
+    ) -> Result<Vec<LegalHoldDto>, Error> {
+        sqlx::query_as!(
+            LegalHoldDto,
+            r#"
+            SELECT h.id, h.satker_id, h.user_id,
+                   u.full_name AS user_full_name, u.nrp AS user_nrp,
+                   h.from_date, h.to_date, h.reason,
+                   h.created_by, c.full_name AS "created_by_name?",
+                   h.created_at, h.released_at, h.released_by
+            FROM legal_holds h
+            JOIN users u ON u.id = h.user_id
+            LEFT JOIN users c ON c.id = h.created_by
+            WHERE ($1::uuid IS NULL OR h.satker_id = $1)
+              AND ($2::uuid IS NULL OR h.user_id = $2)
+              AND (NOT $3 OR h.released_at IS NULL)
+            ORDER BY h.created_at DESC
+            "#,
+            satker_id,
+            user_id,
+            active_only
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn release_legal_hold(&self, id: Uuid, released_by: Uuid) -> Result<u64, Error> {
+        let res = sqlx::query!(
+            r#"
+            UPDATE legal_holds
+            SET released_at = now(), released_by = $2
+            WHERE id = $1 AND released_at IS NULL
+            "#,
+            id,
+            released_by
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+
+    async fn list_held_user_ids(&self) -> Result<Vec<Uuid>, Error> {
+        sqlx::query_scalar!(r#"SELECT DISTINCT user_id FROM legal_holds WHERE released_at IS NULL"#)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn list_expired_profile_photos(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<Vec<(Uuid, String)>, Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT u.id, u.profile_photo_key AS "profile_photo_key!"
+            FROM users u
+            WHERE u.is_active = false
+              AND u.deactivated_at < $1
+              AND u.profile_photo_key IS NOT NULL
+              AND NOT EXISTS (
+                  SELECT 1 FROM legal_holds h
+                  WHERE h.user_id = u.id AND h.released_at IS NULL
+              )
+            ORDER BY u.deactivated_at
+            "#,
+            before
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.id, r.profile_photo_key))
+            .collect())
+    }
+
+    async fn count_held_profile_photos(&self, before: DateTime<Utc>) -> Result<i64, Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM users u
+            WHERE u.is_active = false
+              AND u.deactivated_at < $1
+              AND u.profile_photo_key IS NOT NULL
+              AND EXISTS (
+                  SELECT 1 FROM legal_holds h
+                  WHERE h.user_id = u.id AND h.released_at IS NULL
+              )
+            "#,
+            before
+        )
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn clear_profile_photo_key(&self, user_id: Uuid, key: &str) -> Result<u64, Error> {
+        let res = sqlx::query!(
+            r#"
+            UPDATE users SET profile_photo_key = NULL
+            WHERE id = $1 AND profile_photo_key = $2
+            "#,
+            user_id,
+            key
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+
+    async fn claim_retention_run(
+        &self,
+        kind: &str,
+        requested_by: Option<Uuid>,
+        dry_run: bool,
+        not_since: Option<DateTime<Utc>>,
+    ) -> Result<Option<Uuid>, Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let locked = sqlx::query_scalar!(
+            r#"SELECT pg_try_advisory_xact_lock($1) AS "locked!""#,
+            RETENTION_LOCK_KEY
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if !locked {
+            return Ok(None);
+        }
+
+        // dry-run tidak mengubah apa pun, jadi boleh jalan kapan saja
+        if !dry_run {
+            // putaran yang macet > 6 jam dianggap mati
+            let busy = sqlx::query_scalar!(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM retention_runs
+                    WHERE NOT dry_run
+                      AND ((finished_at IS NULL AND started_at > now() - interval '6 hours')
+                           OR ($1::timestamptz IS NOT NULL AND started_at > $1))
+                ) AS "busy!"
+                "#,
+                not_since
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+            if busy {
+                return Ok(None);
+            }
+        }
+
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO retention_runs (kind, requested_by, dry_run)
+            VALUES ($1, $2, $3)
+            RETURNING id
+            "#,
+            kind,
+            requested_by,
+            dry_run
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(id))
+    }
+
+    async fn finish_retention_run(
+        &self,
+        id: Uuid,
+        report: Option<serde_json::Value>,
+        error: Option<&str>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE retention_runs
+            SET finished_at = now(), report = $2, error = $3
+            WHERE id = $1
+            "#,
+            id,
+            report,
+            error
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_retention_runs(&self, limit: i64) -> Result<Vec<RetentionRunDto>, Error> {
+        sqlx::query_as!(
+            RetentionRunDto,
+            r#"
+            SELECT id, kind, requested_by, dry_run, started_at, finished_at,
+                   report AS "report: serde_json::Value", error
+            FROM retention_runs
+            ORDER BY started_at DESC
+            LIMIT $1
+            "#,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn storage_usage(
+        &self,
+        tz: &str,
+        satker_id: Option<Uuid>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Vec<StorageUsageRow>, Error> {
+        sqlx::query_as!(
+            StorageUsageRow,
+            r#"
+            WITH objects AS (
+                SELECT u.satker_id,
+                       date_trunc('month', su.created_at AT TIME ZONE $1)::date AS month,
+                       'selfie' AS object_class,
+                       su.size_bytes
+                FROM selfie_uploads su
+                JOIN users u ON u.id = su.user_id
+                UNION ALL
+                -- selfie lama (sebelum upload diolah) tidak punya catatan ukuran
+                SELECT e.satker_id,
+                       date_trunc('month', e.occurred_at AT TIME ZONE $1)::date,
+                       'selfie',
+                       NULL::bigint
+                FROM attendance_events e
+                WHERE e.selfie_object_key IS NOT NULL
+                  AND NOT EXISTS (
+                      SELECT 1 FROM selfie_uploads su WHERE su.object_key = e.selfie_object_key
+                  )
+                UNION ALL
+                -- bulan upload diambil dari path key (profiles/YYYY/MM/DD/...)
+                SELECT u.satker_id,
+                       COALESCE(
+                           to_date(substring(u.profile_photo_key FROM '/profiles/(\d{4}/\d{2})/'), 'YYYY/MM'),
+                           date_trunc('month', u.updated_at AT TIME ZONE $1)::date
+                       ),
+                       'profile_photo',
+                       NULL::bigint
+                FROM users u
+                WHERE u.profile_photo_key IS NOT NULL
+            )
+            SELECT o.satker_id AS "satker_id!",
+                   s.code AS satker_code,
+                   s.name AS satker_name,
+                   o.month AS "month!",
+                   o.object_class AS "object_class!",
+                   COUNT(*) AS "object_count!",
+                   COALESCE(SUM(o.size_bytes), 0)::bigint AS "total_bytes!",
+                   COUNT(*) FILTER (WHERE o.size_bytes IS NULL) AS "unknown_size_count!"
+            FROM objects o
+            JOIN satkers s ON s.id = o.satker_id
+            WHERE ($2::uuid IS NULL OR o.satker_id = $2)
+              AND ($3::date IS NULL OR o.month >= date_trunc('month', $3::date)::date)
+              AND ($4::date IS NULL OR o.month <= $4::date)
+            GROUP BY o.satker_id, s.code, s.name, o.month, o.object_class
+            ORDER BY s.code, o.month, o.object_class
+            "#,
+            tz,
+            satker_id,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+}
@@ -6,19 +6,20 @@ use chrono::{NaiveDate, NaiveTime};
 use sqlx::Error;
 use uuid::Uuid;
 
+pub struct NewSchedule {
+    pub satker_id: Uuid,
+    pub user_id: Uuid,
+    pub schedule_date: NaiveDate,
+    pub schedule_type: ScheduleType,
+    pub start_time: Option<NaiveTime>,
+    pub end_time: Option<NaiveTime>,
+    pub notes: Option<String>,
+    pub create_by: Uuid,
+}
+
 #[async_trait]
 pub trait ScheduleRepo {
-    async fn create_schedule(
-        &self,
-        satker_id: Uuid,
-        user_id: Uuid,
-        schedule_date: NaiveDate,
-        schedule_type: ScheduleType,
-        start_time: Option<NaiveTime>,
-        end_time: Option<NaiveTime>,
-        notes: Option<String>,
-        create_by: Uuid,
-    ) -> Result<(), Error>;
+    async fn create_schedule(&self, new_schedule: NewSchedule) -> Result<(), Error>;
 
     async fn list_satker_schedule(
         &self,
@@ -45,30 +46,20 @@ pub trait ScheduleRepo {
 
 #[async_trait]
 impl ScheduleRepo for DBClient {
-    async fn create_schedule(
-        &self,
-        satker_id: Uuid,
-        user_id: Uuid,
-        schedule_date: NaiveDate,
-        schedule_type: ScheduleType,
-        start_time: Option<NaiveTime>,
-        end_time: Option<NaiveTime>,
-        notes: Option<String>,
-        create_by: Uuid,
-    ) -> Result<(), Error> {
+    async fn create_schedule(&self, new_schedule: NewSchedule) -> Result<(), Error> {
         sqlx::query!(
         r#"
         INSERT INTO duty_schedules (satker_id, user_id, schedule_date, type, start_time, end_time, notes, created_by)
         VALUES ($1, $2, $3, $4, $5::time, $6::time, $7, $8)
         "#,
-        satker_id,
-        user_id,
-        schedule_date,
-        schedule_type as ScheduleType,
-        start_time,
-        end_time,
-        notes,
-        create_by,
+        new_schedule.satker_id,
+        new_schedule.user_id,
+        new_schedule.schedule_date,
+        new_schedule.schedule_type as ScheduleType,
+        new_schedule.start_time,
+        new_schedule.end_time,
+        new_schedule.notes,
+        new_schedule.create_by,
         ).execute(&self.pool).await?;
 
         Ok(())
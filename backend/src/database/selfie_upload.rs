@@ -0,0 +1,95 @@
+use crate::DBClient;
+use crate::dtos::upload::{NewSelfieUpload, SelfieUploadMeta};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::Error;
+
+#[async_trait]
+pub trait SelfieUploadRepo {
+    async fn create_selfie_upload(&self, row: NewSelfieUpload<'_>) -> Result<(), Error>;
+
+    /// None untuk selfie lama (sebelum upload diolah) atau key yang tidak dikenal.
+    async fn find_selfie_upload(&self, object_key: &str)
+    -> Result<Option<SelfieUploadMeta>, Error>;
+
+    /// Metadata upload sebelum `before`, kecuali milik user dengan legal hold aktif.
+    async fn count_selfie_uploads_before(&self, before: DateTime<Utc>) -> Result<i64, Error>;
+
+    async fn delete_selfie_uploads_before(&self, before: DateTime<Utc>) -> Result<u64, Error>;
+}
+
+#[async_trait]
+impl SelfieUploadRepo for DBClient {
+    async fn create_selfie_upload(&self, row: NewSelfieUpload<'_>) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO selfie_uploads
+                (object_key, user_id, width, height, size_bytes, phash, captured_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            row.object_key,
+            row.user_id,
+            row.width,
+            row.height,
+            row.size_bytes,
+            row.phash,
+            row.captured_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_selfie_upload(
+        &self,
+        object_key: &str,
+    ) -> Result<Option<SelfieUploadMeta>, Error> {
+        sqlx::query_as!(
+            SelfieUploadMeta,
+            r#"
+            SELECT phash, captured_at
+            FROM selfie_uploads
+            WHERE object_key = $1
+            "#,
+            object_key
+        )
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn count_selfie_uploads_before(&self, before: DateTime<Utc>) -> Result<i64, Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM selfie_uploads su
+            WHERE su.created_at < $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM legal_holds h
+                  WHERE h.user_id = su.user_id AND h.released_at IS NULL
+              )
+            "#,
+            before
+        )
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn delete_selfie_uploads_before(&self, before: DateTime<Utc>) -> Result<u64, Error> {
+        let res = sqlx::query!(
+            r#"
+            DELETE FROM selfie_uploads su
+            WHERE su.created_at < $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM legal_holds h
+                  WHERE h.user_id = su.user_id AND h.released_at IS NULL
+              )
+            "#,
+            before
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected())
+    }
+}
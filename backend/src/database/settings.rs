@@ -1,19 +1,48 @@
 use crate::db::DBClient;
+use crate::dtos::settings::SettingAuditRow;
 use sqlx::Row;
 use uuid::Uuid;
 
 pub const SETTING_DEFAULT_TIMEZONE: &str = "default_timezone";
 pub const DEFAULT_TIMEZONE_VALUE: &str = "Asia/Jakarta";
 
+/// Aturan check-in/out global (JSON `AttendanceRulesOverride`).
+pub const SETTING_ATTENDANCE_RULES: &str = "attendance_rules";
+
+/// Periode retensi file upload per kelas objek (JSON `RetentionPolicy`).
+pub const SETTING_RETENTION_POLICY: &str = "retention_policy";
+
+/// Key app_settings aturan absensi: global (None) atau khusus satker.
+pub fn attendance_rules_key(satker_id: Option<Uuid>) -> String {
+    match satker_id {
+        Some(id) => format!("{SETTING_ATTENDANCE_RULES}:satker:{id}"),
+        None => SETTING_ATTENDANCE_RULES.to_string(),
+    }
+}
+
 pub trait SettingsRepo {
     async fn get_setting(&self, key: &str) -> Result<Option<String>, sqlx::Error>;
-    async fn upsert_setting(
+    /// Set (Some) atau hapus (None) setting sekaligus mencatat audit kalau nilainya berubah.
+    async fn set_setting_audited(
         &self,
         key: &str,
-        value: &str,
-        updated_by: Uuid,
+        value: Option<&str>,
+        changed_by: Uuid,
     ) -> Result<(), sqlx::Error>;
 
+    /// Semua setting (key, value) dengan key LIKE `key_pattern`.
+    async fn list_settings_like(
+        &self,
+        key_pattern: &str,
+    ) -> Result<Vec<(String, String)>, sqlx::Error>;
+
+    /// Riwayat perubahan setting dengan key LIKE `key_pattern`, terbaru dulu.
+    async fn list_setting_audits(
+        &self,
+        key_pattern: &str,
+        limit: i64,
+    ) -> Result<Vec<SettingAuditRow>, sqlx::Error>;
+
     /// Returns the configured operational timezone, or a safe default.
     async fn get_timezone_value(&self) -> Result<String, sqlx::Error>;
 }
@@ -27,23 +56,87 @@ impl SettingsRepo for DBClient {
         Ok(row.map(|r| r.get::<String, _>("value")))
     }
 
-    async fn upsert_setting(
+    async fn set_setting_audited(
         &self,
         key: &str,
-        value: &str,
-        updated_by: Uuid,
+        value: Option<&str>,
+        changed_by: Uuid,
     ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let old: Option<String> =
+            sqlx::query("SELECT value FROM app_settings WHERE key = $1 FOR UPDATE")
+                .bind(key)
+                .fetch_optional(&mut *tx)
+                .await?
+                .map(|r| r.get::<String, _>("value"));
+
+        if old.as_deref() == value {
+            return Ok(());
+        }
+
+        match value {
+            Some(v) => {
+                sqlx::query(
+                    "INSERT INTO app_settings(key, value, updated_at, updated_by)\n             VALUES ($1, $2, NOW(), $3)\n             ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value, updated_at = NOW(), updated_by = EXCLUDED.updated_by",
+                )
+                .bind(key)
+                .bind(v)
+                .bind(changed_by)
+                .execute(&mut *tx)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM app_settings WHERE key = $1")
+                    .bind(key)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
         sqlx::query(
-            "INSERT INTO app_settings(key, value, updated_at, updated_by)\n             VALUES ($1, $2, NOW(), $3)\n             ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value, updated_at = NOW(), updated_by = EXCLUDED.updated_by",
+            "INSERT INTO app_setting_audits(key, old_value, new_value, changed_by)\n             VALUES ($1, $2, $3, $4)",
         )
         .bind(key)
+        .bind(old)
         .bind(value)
-        .bind(updated_by)
-        .execute(&self.pool)
+        .bind(changed_by)
+        .execute(&mut *tx)
         .await?;
+
+        tx.commit().await?;
         Ok(())
     }
 
+    async fn list_settings_like(
+        &self,
+        key_pattern: &str,
+    ) -> Result<Vec<(String, String)>, sqlx::Error> {
+        let rows =
+            sqlx::query("SELECT key, value FROM app_settings WHERE key LIKE $1 ORDER BY key")
+                .bind(key_pattern)
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.get::<String, _>("key"), r.get::<String, _>("value")))
+            .collect())
+    }
+
+    async fn list_setting_audits(
+        &self,
+        key_pattern: &str,
+        limit: i64,
+    ) -> Result<Vec<SettingAuditRow>, sqlx::Error> {
+        sqlx::query_as::<_, SettingAuditRow>(
+            "SELECT a.id, a.key, a.old_value, a.new_value, a.changed_by,\n                    u.full_name AS changed_by_name, a.changed_at\n             FROM app_setting_audits a\n             LEFT JOIN users u ON u.id = a.changed_by\n             WHERE a.key LIKE $1\n             ORDER BY a.changed_at DESC\n             LIMIT $2",
+        )
+        .bind(key_pattern)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
     async fn get_timezone_value(&self) -> Result<String, sqlx::Error> {
         Ok(self
             .get_setting(SETTING_DEFAULT_TIMEZONE)
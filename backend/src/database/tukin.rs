@@ -4,7 +4,7 @@ use serde_json::Value as JsonValue;
 use sqlx::Error;
 use uuid::Uuid;
 
-use crate::constants::{AttendanceEventType, LeaveStatus, LeaveType};
+use crate::constants::{AttendanceEventType, AttendanceStatus, LeaveStatus, LeaveType};
 use crate::db::DBClient;
 use crate::dtos::tukin::{
     CreateTukinPolicyReq, LeaveRuleInput, TukinCalculationDto, TukinCalculationRowDto,
@@ -21,8 +21,6 @@ pub struct LeaveSpanRow {
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct AttendanceEventLite {
     pub occurred_at: DateTime<Utc>,
-    pub geofence_id: Option<Uuid>,
-    pub distance_to_fence_m: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
@@ -85,13 +83,6 @@ pub trait TukinRepo {
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Option<AttendanceEventLite>, Error>;
-    async fn find_last_event_in_range(
-        &self,
-        user_id: Uuid,
-        event_type: AttendanceEventType,
-        from: DateTime<Utc>,
-        to: DateTime<Utc>,
-    ) -> Result<Option<AttendanceEventLite>, Error>;
 
     // cache
     async fn upsert_tukin_calculation(
@@ -639,49 +630,22 @@ impl TukinRepo for DBClient {
         let row = sqlx::query_as!(
             AttendanceEventLite,
             r#"
-            SELECT occurred_at, geofence_id, distance_to_fence_m
-            FROM attendance_events
-            WHERE user_id = $1
-              AND event_type = $2
-              AND occurred_at >= $3
-              AND occurred_at <= $4
-            ORDER BY occurred_at ASC
+            SELECT e.occurred_at
+            FROM attendance_events e
+            LEFT JOIN attendance_sessions s ON s.id = e.session_id
+            WHERE e.user_id = $1
+              AND e.event_type = $2
+              AND e.occurred_at >= $3
+              AND e.occurred_at <= $4
+              AND s.status IS DISTINCT FROM $5
+            ORDER BY e.occurred_at ASC
             LIMIT 1
             "#,
             user_id,
             event_type as AttendanceEventType,
             from,
-            to
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-
-        Ok(row)
-    }
-
-    async fn find_last_event_in_range(
-        &self,
-        user_id: Uuid,
-        event_type: AttendanceEventType,
-        from: DateTime<Utc>,
-        to: DateTime<Utc>,
-    ) -> Result<Option<AttendanceEventLite>, Error> {
-        let row = sqlx::query_as!(
-            AttendanceEventLite,
-            r#"
-            SELECT occurred_at, geofence_id, distance_to_fence_m
-            FROM attendance_events
-            WHERE user_id = $1
-              AND event_type = $2
-              AND occurred_at >= $3
-              AND occurred_at <= $4
-            ORDER BY occurred_at DESC
-            LIMIT 1
-            "#,
-            user_id,
-            event_type as AttendanceEventType,
-            from,
-            to
+            to,
+            AttendanceStatus::Invalid.as_str()
         )
         .fetch_optional(&self.pool)
         .await?;
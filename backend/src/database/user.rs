@@ -5,6 +5,27 @@ use async_trait::async_trait;
 use sqlx::Error;
 use uuid::Uuid;
 
+pub struct NewUser {
+    pub satker_id: Uuid,
+    pub rank_id: Option<Uuid>,
+    pub nrp: String,
+    pub full_name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub role: UserRole,
+    pub password_hash: String,
+}
+
+/// Field yang None tidak diubah.
+pub struct UpdateUser {
+    pub satker_id: Option<Uuid>,
+    pub rank_id: Option<Uuid>,
+    pub nrp: Option<String>,
+    pub full_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+}
+
 #[async_trait]
 pub trait UserRepo {
     async fn find_user_by_id(&self, id: Uuid) -> Result<Option<User>, Error>;
@@ -15,28 +36,9 @@ pub trait UserRepo {
     async fn get_user_all(&self) -> Result<Vec<User>, Error>;
 
     async fn get_user_by_satker_id(&self, satker_id: Uuid) -> Result<Vec<User>, Error>;
-    async fn create_user(
-        &self,
-        satker_id: Uuid,
-        rank_id: Option<Uuid>,
-        nrp: String,
-        full_name: String,
-        email: Option<String>,
-        phone: Option<String>,
-        role: UserRole,
-        password: String,
-    ) -> Result<User, Error>;
+    async fn create_user(&self, new_user: NewUser) -> Result<User, Error>;
 
-    async fn update_user(
-        &self,
-        id: Uuid,
-        satker_id: Option<Uuid>,
-        rank_id: Option<Uuid>,
-        nrp: Option<String>,
-        full_name: Option<String>,
-        email: Option<String>,
-        phone: Option<String>,
-    ) -> Result<(), Error>;
+    async fn update_user(&self, id: Uuid, changes: UpdateUser) -> Result<(), Error>;
 
     async fn update_my_profile(
         &self,
@@ -56,6 +58,12 @@ pub trait UserRepo {
     async fn delete_user(&self, id: Uuid) -> Result<(), Error>;
 
     async fn set_satker_head(&self, id: Uuid) -> Result<(), Error>;
+
+    async fn count_active_users_by_role(
+        &self,
+        role: UserRole,
+        exclude_user_id: Uuid,
+    ) -> Result<i64, Error>;
 }
 
 #[async_trait]
@@ -106,17 +114,7 @@ impl UserRepo for DBClient {
         Ok(row)
     }
 
-    async fn create_user(
-        &self,
-        satker_id: Uuid,
-        rank_id: Option<Uuid>,
-        nrp: String,
-        full_name: String,
-        email: Option<String>,
-        phone: Option<String>,
-        role: UserRole,
-        password: String,
-    ) -> Result<User, Error> {
+    async fn create_user(&self, new_user: NewUser) -> Result<User, Error> {
         let row = sqlx::query_as!(
             User,
             r#"
@@ -125,14 +123,14 @@ impl UserRepo for DBClient {
             RETURNING id, satker_id, rank_id, nrp, full_name, email, phone, profile_photo_key, role as "role: UserRole",
             password_hash, is_active, face_template_version, face_template_hash, created_at, updated_at
             "#,
-            satker_id,
-            rank_id,
-            nrp,
-            full_name,
-            email,
-            phone,
-            role as UserRole,
-            password
+            new_user.satker_id,
+            new_user.rank_id,
+            new_user.nrp,
+            new_user.full_name,
+            new_user.email,
+            new_user.phone,
+            new_user.role as UserRole,
+            new_user.password_hash
         )
             .fetch_one(&self.pool)
             .await?;
@@ -165,16 +163,7 @@ impl UserRepo for DBClient {
         Ok(rows)
     }
 
-    async fn update_user(
-        &self,
-        id: Uuid,
-        satker_id: Option<Uuid>,
-        rank_id: Option<Uuid>,
-        nrp: Option<String>,
-        full_name: Option<String>,
-        email: Option<String>,
-        phone: Option<String>,
-    ) -> Result<(), Error> {
+    async fn update_user(&self, id: Uuid, changes: UpdateUser) -> Result<(), Error> {
         sqlx::query!(
             r#"
             UPDATE users
@@ -188,12 +177,12 @@ impl UserRepo for DBClient {
             WHERE id = $1
             "#,
             id,
-            satker_id,
-            rank_id,
-            nrp,
-            full_name,
-            email,
-            phone,
+            changes.satker_id,
+            changes.rank_id,
+            changes.nrp,
+            changes.full_name,
+            changes.email,
+            changes.phone,
         )
         .execute(&self.pool)
         .await?;
@@ -278,4 +267,23 @@ impl UserRepo for DBClient {
         .await?;
         Ok(())
     }
+
+    async fn count_active_users_by_role(
+        &self,
+        role: UserRole,
+        exclude_user_id: Uuid,
+    ) -> Result<i64, Error> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM users
+            WHERE role = $1::user_role AND is_active = true AND id <> $2
+            "#,
+            role as UserRole,
+            exclude_user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count)
+    }
 }
@@ -1,46 +1,489 @@
-use crate::DBClient;
-use async_trait::async_trait;
-use sqlx::Error;
-use uuid::Uuid;
-
-#[async_trait]
-pub trait UserDeviceRepo {
-    async fn ensure_device_bound_first_user(
-        &self,
-        user_id: Uuid,
-        device_id: &str,
-        device_model: Option<String>,
-        android_version: Option<String>,
-        app_build: Option<String>,
-        client_version: Option<String>,
-    ) -> Result<(), Error>;
-}
-
-#[async_trait]
-impl UserDeviceRepo for DBClient {
-    async fn ensure_device_bound_first_user(
-        &self,
-        user_id: Uuid,
-        device_id: &str,
-        device_model: Option<String>,
-        android_version: Option<String>,
-        app_build: Option<String>,
-        client_version: Option<String>,
-    ) -> Result<(), Error> {
-        sqlx::query!(
-            r#"
-            INSERT INTO user_devices (user_id, device_id, device_model, android_version, app_build, client_version)
-            VALUES ($1, $2, $3, $4, $5, $6)
-            ON CONFLICT (device_id) DO NOTHING
-            "#,
-            user_id,
-            device_id,
-            device_model,
-            android_version,
-            app_build,
-            client_version
-        ).execute(&self.pool).await?;
-
-        Ok(())
-    }
-}
+use crate::DBClient;
+use crate::dtos::user_device::{
+    DEVICE_REQUEST_PENDING, DeviceBindingLockRow, DeviceBindingRequestDto, DeviceInfo,
+    UserDeviceDto,
+};
+use async_trait::async_trait;
+use sqlx::{Error, PgConnection};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait UserDeviceRepo {
+    async fn find_user_device(&self, device_id: &str) -> Result<Option<UserDeviceDto>, Error>;
+
+    async fn find_user_device_by_id(&self, id: Uuid) -> Result<Option<UserDeviceDto>, Error>;
+
+    /// Kunci publik attestation device (base64 SPKI DER), kalau sudah didaftarkan.
+    async fn find_device_public_key(&self, device_id: &str) -> Result<Option<String>, Error>;
+
+    async fn list_user_devices(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+    ) -> Result<Vec<UserDeviceDto>, Error>;
+
+    /// Bind device ke user hanya kalau user belum punya device sama sekali dan device belum
+    /// dipakai akun lain. Return true kalau binding dibuat.
+    async fn bind_first_user_device(&self, user_id: Uuid, info: &DeviceInfo)
+    -> Result<bool, Error>;
+
+    /// Catat pemakaian device (last_seen_at) + perbarui info aplikasi. Kunci attestation tidak
+    /// pernah disimpan di sini: hanya saat bind pertama atau permintaan device yang disetujui.
+    async fn touch_user_device(&self, info: &DeviceInfo) -> Result<(), Error>;
+
+    /// Lepas binding satu device. Return user_id pemilik sebelumnya (None kalau tidak ada).
+    async fn unbind_device(&self, device_id: &str) -> Result<Option<Uuid>, Error>;
+
+    /// Lepas semua device milik user. Return device_id yang dilepas.
+    async fn unbind_user_devices(&self, user_id: Uuid) -> Result<Vec<String>, Error>;
+
+    /// Buat permintaan device baru (PENDING). Gagal (unique violation) kalau masih ada yang PENDING.
+    async fn create_device_binding_request(
+        &self,
+        user_id: Uuid,
+        satker_id: Uuid,
+        info: &DeviceInfo,
+        reason: Option<&str>,
+        previous_user_id: Option<Uuid>,
+    ) -> Result<Uuid, Error>;
+
+    async fn list_device_binding_requests(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        status: Option<&str>,
+    ) -> Result<Vec<DeviceBindingRequestDto>, Error>;
+
+    async fn find_device_binding_request(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<DeviceBindingRequestDto>, Error>;
+}
+
+#[async_trait]
+impl UserDeviceRepo for DBClient {
+    async fn find_user_device(&self, device_id: &str) -> Result<Option<UserDeviceDto>, Error> {
+        let row = sqlx::query_as!(
+            UserDeviceDto,
+            r#"
+            SELECT d.id, d.device_id,
+                   d.user_id, u.full_name AS user_full_name, u.nrp AS user_nrp, u.satker_id,
+                   d.device_model, d.android_version, d.app_build, d.client_version,
+                   d.bound_at, d.bound_by, b.full_name AS "bound_by_name?", d.last_seen_at,
+                   d.public_key_registered_at
+            FROM user_devices d
+            JOIN users u ON u.id = d.user_id
+            LEFT JOIN users b ON b.id = d.bound_by
+            WHERE d.device_id = $1
+            "#,
+            device_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn find_user_device_by_id(&self, id: Uuid) -> Result<Option<UserDeviceDto>, Error> {
+        let row = sqlx::query_as!(
+            UserDeviceDto,
+            r#"
+            SELECT d.id, d.device_id,
+                   d.user_id, u.full_name AS user_full_name, u.nrp AS user_nrp, u.satker_id,
+                   d.device_model, d.android_version, d.app_build, d.client_version,
+                   d.bound_at, d.bound_by, b.full_name AS "bound_by_name?", d.last_seen_at,
+                   d.public_key_registered_at
+            FROM user_devices d
+            JOIN users u ON u.id = d.user_id
+            LEFT JOIN users b ON b.id = d.bound_by
+            WHERE d.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn find_device_public_key(&self, device_id: &str) -> Result<Option<String>, Error> {
+        let key = sqlx::query_scalar!(
+            r#"SELECT public_key FROM user_devices WHERE device_id = $1"#,
+            device_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(key.flatten())
+    }
+
+    async fn list_user_devices(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+    ) -> Result<Vec<UserDeviceDto>, Error> {
+        let rows = sqlx::query_as!(
+            UserDeviceDto,
+            r#"
+            SELECT d.id, d.device_id,
+                   d.user_id, u.full_name AS user_full_name, u.nrp AS user_nrp, u.satker_id,
+                   d.device_model, d.android_version, d.app_build, d.client_version,
+                   d.bound_at, d.bound_by, b.full_name AS "bound_by_name?", d.last_seen_at,
+                   d.public_key_registered_at
+            FROM user_devices d
+            JOIN users u ON u.id = d.user_id
+            LEFT JOIN users b ON b.id = d.bound_by
+            WHERE ($1::uuid IS NULL OR u.satker_id = $1)
+              AND ($2::uuid IS NULL OR d.user_id = $2)
+            ORDER BY u.full_name, d.bound_at
+            "#,
+            satker_id,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn bind_first_user_device(
+        &self,
+        user_id: Uuid,
+        info: &DeviceInfo,
+    ) -> Result<bool, Error> {
+        let mut tx = self.pool.begin().await?;
+
+        // serialisasi binding per user supaya dua device baru tidak lolos bersamaan
+        lock_user_devices(&mut tx, user_id).await?;
+
+        let row = sqlx::query_scalar!(
+            r#"
+            INSERT INTO user_devices (
+                user_id, device_id, device_model, android_version, app_build, client_version,
+                last_seen_at, public_key, public_key_registered_at
+            )
+            SELECT $1, $2, $3, $4, $5, $6, now(), $7, CASE WHEN $7::text IS NULL THEN NULL ELSE now() END
+            WHERE NOT EXISTS (SELECT 1 FROM user_devices WHERE user_id = $1)
+            ON CONFLICT (device_id) DO NOTHING
+            RETURNING id
+            "#,
+            user_id,
+            info.device_id,
+            info.device_model,
+            info.android_version,
+            info.app_build,
+            info.client_version,
+            info.public_key
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(row.is_some())
+    }
+
+    async fn touch_user_device(&self, info: &DeviceInfo) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE user_devices
+            SET last_seen_at = now(),
+                device_model = COALESCE($2, device_model),
+                android_version = COALESCE($3, android_version),
+                app_build = COALESCE($4, app_build),
+                client_version = COALESCE($5, client_version)
+            WHERE device_id = $1
+            "#,
+            info.device_id,
+            info.device_model,
+            info.android_version,
+            info.app_build,
+            info.client_version
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn unbind_device(&self, device_id: &str) -> Result<Option<Uuid>, Error> {
+        let user_id = sqlx::query_scalar!(
+            r#"
+            DELETE FROM user_devices
+            WHERE device_id = $1
+            RETURNING user_id
+            "#,
+            device_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user_id)
+    }
+
+    async fn unbind_user_devices(&self, user_id: Uuid) -> Result<Vec<String>, Error> {
+        let device_ids = sqlx::query_scalar!(
+            r#"
+            DELETE FROM user_devices
+            WHERE user_id = $1
+            RETURNING device_id
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(device_ids)
+    }
+
+    async fn create_device_binding_request(
+        &self,
+        user_id: Uuid,
+        satker_id: Uuid,
+        info: &DeviceInfo,
+        reason: Option<&str>,
+        previous_user_id: Option<Uuid>,
+    ) -> Result<Uuid, Error> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO device_binding_requests (
+                user_id, satker_id, device_id, device_model, android_version, app_build,
+                client_version, reason, previous_user_id, status, public_key
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING id
+            "#,
+            user_id,
+            satker_id,
+            info.device_id,
+            info.device_model,
+            info.android_version,
+            info.app_build,
+            info.client_version,
+            reason,
+            previous_user_id,
+            DEVICE_REQUEST_PENDING,
+            info.public_key
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn list_device_binding_requests(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        status: Option<&str>,
+    ) -> Result<Vec<DeviceBindingRequestDto>, Error> {
+        let rows = sqlx::query_as!(
+            DeviceBindingRequestDto,
+            r#"
+            SELECT r.id, r.satker_id,
+                   r.user_id, u.full_name AS user_full_name, u.nrp AS user_nrp,
+                   r.device_id, r.device_model, r.android_version, r.app_build,
+                   r.client_version, r.reason,
+                   r.previous_user_id, p.full_name AS "previous_user_name?",
+                   r.replaced_device_id,
+                   r.status, r.reject_reason,
+                   r.decided_by, d.full_name AS "decided_by_name?", r.decided_at,
+                   r.created_at, r.updated_at
+            FROM device_binding_requests r
+            JOIN users u ON u.id = r.user_id
+            LEFT JOIN users p ON p.id = r.previous_user_id
+            LEFT JOIN users d ON d.id = r.decided_by
+            WHERE ($1::uuid IS NULL OR r.satker_id = $1)
+              AND ($2::uuid IS NULL OR r.user_id = $2)
+              AND ($3::text IS NULL OR r.status = $3)
+            ORDER BY r.created_at DESC
+            "#,
+            satker_id,
+            user_id,
+            status
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn find_device_binding_request(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<DeviceBindingRequestDto>, Error> {
+        let row = sqlx::query_as!(
+            DeviceBindingRequestDto,
+            r#"
+            SELECT r.id, r.satker_id,
+                   r.user_id, u.full_name AS user_full_name, u.nrp AS user_nrp,
+                   r.device_id, r.device_model, r.android_version, r.app_build,
+                   r.client_version, r.reason,
+                   r.previous_user_id, p.full_name AS "previous_user_name?",
+                   r.replaced_device_id,
+                   r.status, r.reject_reason,
+                   r.decided_by, d.full_name AS "decided_by_name?", r.decided_at,
+                   r.created_at, r.updated_at
+            FROM device_binding_requests r
+            JOIN users u ON u.id = r.user_id
+            LEFT JOIN users p ON p.id = r.previous_user_id
+            LEFT JOIN users d ON d.id = r.decided_by
+            WHERE r.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+}
+
+/// Kunci baris user supaya jumlah device user tidak berubah selama transaksi.
+pub async fn lock_user_devices(conn: &mut PgConnection, user_id: Uuid) -> Result<(), Error> {
+    sqlx::query_scalar!(r#"SELECT id FROM users WHERE id = $1 FOR UPDATE"#, user_id)
+        .fetch_optional(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn count_user_devices(conn: &mut PgConnection, user_id: Uuid) -> Result<i64, Error> {
+    let n = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "n!" FROM user_devices WHERE user_id = $1"#,
+        user_id
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(n)
+}
+
+pub async fn user_has_device(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    device_id: &str,
+) -> Result<bool, Error> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM user_devices WHERE user_id = $1 AND device_id = $2) AS "exists!""#,
+        user_id,
+        device_id
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(exists)
+}
+
+/// Lepas device tertentu milik user. Return true kalau ada yang dihapus.
+pub async fn delete_user_device(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    device_id: &str,
+) -> Result<bool, Error> {
+    let res = sqlx::query!(
+        r#"DELETE FROM user_devices WHERE user_id = $1 AND device_id = $2"#,
+        user_id,
+        device_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(res.rows_affected() > 0)
+}
+
+/// Bind device ke user oleh admin; kalau device sudah dipakai akun lain, binding dipindah.
+/// Kunci attestation baru (dari permintaan device) menggantikan kunci lama.
+pub async fn assign_user_device(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    info: &DeviceInfo,
+    bound_by: Uuid,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_devices (
+            user_id, device_id, device_model, android_version, app_build, client_version,
+            bound_at, bound_by, public_key, public_key_registered_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, now(), $7, $8, CASE WHEN $8::text IS NULL THEN NULL ELSE now() END)
+        ON CONFLICT (device_id) DO UPDATE
+        SET user_id = EXCLUDED.user_id,
+            device_model = COALESCE(EXCLUDED.device_model, user_devices.device_model),
+            android_version = COALESCE(EXCLUDED.android_version, user_devices.android_version),
+            app_build = COALESCE(EXCLUDED.app_build, user_devices.app_build),
+            client_version = COALESCE(EXCLUDED.client_version, user_devices.client_version),
+            bound_at = now(),
+            bound_by = EXCLUDED.bound_by,
+            last_seen_at = NULL,
+            public_key = COALESCE(EXCLUDED.public_key, user_devices.public_key),
+            public_key_registered_at = COALESCE(
+                EXCLUDED.public_key_registered_at,
+                user_devices.public_key_registered_at
+            )
+        "#,
+        user_id,
+        info.device_id,
+        info.device_model,
+        info.android_version,
+        info.app_build,
+        info.client_version,
+        bound_by,
+        info.public_key
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn lock_device_binding_request(
+    conn: &mut PgConnection,
+    id: Uuid,
+) -> Result<Option<DeviceBindingLockRow>, Error> {
+    let row = sqlx::query_as!(
+        DeviceBindingLockRow,
+        r#"
+        SELECT id, satker_id, user_id, device_id, device_model, android_version, app_build,
+               client_version, public_key, status
+        FROM device_binding_requests
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(row)
+}
+
+pub async fn update_device_binding_status(
+    conn: &mut PgConnection,
+    id: Uuid,
+    to_status: &str,
+    actor_id: Uuid,
+    reject_reason: Option<&str>,
+    replaced_device_id: Option<&str>,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        UPDATE device_binding_requests
+        SET status = $2,
+            reject_reason = $4,
+            replaced_device_id = $5,
+            decided_by = $3,
+            decided_at = now(),
+            updated_at = now()
+        WHERE id = $1
+        "#,
+        id,
+        to_status,
+        actor_id,
+        reject_reason,
+        replaced_device_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
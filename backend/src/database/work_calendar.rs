@@ -18,6 +18,17 @@ pub trait WorkCalendarRepo {
         note: Option<String>,
     ) -> Result<(), Error>;
 
+    /// Override manual (PUT /working-days): ditandai is_manual.
+    async fn upsert_manual_calendar_day(
+        &self,
+        satker_id: Uuid,
+        work_date: NaiveDate,
+        day_type: CalendarDayType,
+        expected_start: Option<NaiveTime>,
+        expected_end: Option<NaiveTime>,
+        note: Option<String>,
+    ) -> Result<(), Error>;
+
     async fn list_calendar_days(
         &self,
         satker_id: Uuid,
@@ -25,8 +36,21 @@ pub trait WorkCalendarRepo {
         to: NaiveDate,
     ) -> Result<Vec<SatkerCalendarDay>, Error>;
 
+    /// Tanggal pertama & terakhir kalender satker yang sudah ter-generate.
+    async fn calendar_bounds(
+        &self,
+        satker_id: Uuid,
+    ) -> Result<Option<(NaiveDate, NaiveDate)>, Error>;
+
     async fn delete_calendar_day(&self, satker_id: Uuid, work_date: NaiveDate)
     -> Result<(), Error>;
+
+    /// Satker yang sudah punya kalender ter-generate di range tsb.
+    async fn list_satkers_with_calendar(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Uuid>, Error>;
 }
 
 #[async_trait]
@@ -40,6 +64,7 @@ impl WorkCalendarRepo for DBClient {
         expected_end: Option<NaiveTime>,
         note: Option<String>,
     ) -> Result<(), Error> {
+        // hari yang di-override manual tidak disentuh
         sqlx::query!(
             r#"
             INSERT INTO satker_calendar_days (satker_id, work_date, day_type, expected_start, expected_end, note)
@@ -50,6 +75,7 @@ impl WorkCalendarRepo for DBClient {
               expected_end = EXCLUDED.expected_end,
               note = EXCLUDED.note,
               updated_at = now()
+            WHERE satker_calendar_days.is_manual = FALSE
             "#,
             satker_id,
             work_date,
@@ -64,6 +90,58 @@ impl WorkCalendarRepo for DBClient {
         Ok(())
     }
 
+    async fn upsert_manual_calendar_day(
+        &self,
+        satker_id: Uuid,
+        work_date: NaiveDate,
+        day_type: CalendarDayType,
+        expected_start: Option<NaiveTime>,
+        expected_end: Option<NaiveTime>,
+        note: Option<String>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO satker_calendar_days (satker_id, work_date, day_type, expected_start, expected_end, note, is_manual)
+            VALUES ($1, $2, $3, $4, $5, $6, TRUE)
+            ON CONFLICT (satker_id, work_date) DO UPDATE SET
+              day_type = EXCLUDED.day_type,
+              expected_start = EXCLUDED.expected_start,
+              expected_end = EXCLUDED.expected_end,
+              note = EXCLUDED.note,
+              is_manual = TRUE,
+              updated_at = now()
+            "#,
+            satker_id,
+            work_date,
+            day_type as CalendarDayType,
+            expected_start,
+            expected_end,
+            note
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn calendar_bounds(
+        &self,
+        satker_id: Uuid,
+    ) -> Result<Option<(NaiveDate, NaiveDate)>, Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT MIN(work_date) as first_date, MAX(work_date) as last_date
+            FROM satker_calendar_days
+            WHERE satker_id = $1
+            "#,
+            satker_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.first_date.zip(row.last_date))
+    }
+
     async fn list_calendar_days(
         &self,
         satker_id: Uuid,
@@ -79,7 +157,8 @@ impl WorkCalendarRepo for DBClient {
               day_type as "day_type: CalendarDayType",
               expected_start,
               expected_end,
-              note
+              note,
+              is_manual
             FROM satker_calendar_days
             WHERE satker_id = $1
               AND work_date BETWEEN $2 AND $3
@@ -110,4 +189,24 @@ impl WorkCalendarRepo for DBClient {
 
         Ok(())
     }
+
+    async fn list_satkers_with_calendar(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Uuid>, Error> {
+        let rows = sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT satker_id
+            FROM satker_calendar_days
+            WHERE work_date BETWEEN $1 AND $2
+            ORDER BY satker_id
+            "#,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
 }
@@ -0,0 +1,203 @@
+use crate::DBClient;
+use crate::dtos::work_group::{WorkGroupDto, WorkGroupMemberDto};
+use async_trait::async_trait;
+use sqlx::types::Json;
+use sqlx::{Error, Postgres, Transaction};
+use uuid::Uuid;
+
+#[async_trait]
+pub trait WorkGroupRepo {
+    async fn list_work_groups(&self, satker_id: Option<Uuid>) -> Result<Vec<WorkGroupDto>, Error>;
+
+    async fn find_work_group(&self, id: Uuid) -> Result<Option<WorkGroupDto>, Error>;
+
+    async fn create_work_group(
+        &self,
+        satker_id: Uuid,
+        name: &str,
+        description: Option<String>,
+        member_ids: &[Uuid],
+        created_by: Uuid,
+    ) -> Result<Uuid, Error>;
+
+    /// Ganti nama/deskripsi dan seluruh anggota kelompok.
+    async fn update_work_group(
+        &self,
+        id: Uuid,
+        name: &str,
+        description: Option<String>,
+        member_ids: &[Uuid],
+    ) -> Result<(), Error>;
+
+    /// Override jam kerja milik kelompok ikut terhapus.
+    async fn delete_work_group(&self, id: Uuid) -> Result<u64, Error>;
+}
+
+async fn replace_work_group_members(
+    tx: &mut Transaction<'_, Postgres>,
+    group_id: Uuid,
+    member_ids: &[Uuid],
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"DELETE FROM work_group_members WHERE group_id = $1"#,
+        group_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO work_group_members (group_id, user_id)
+        SELECT $1, UNNEST($2::uuid[])
+        ON CONFLICT DO NOTHING
+        "#,
+        group_id,
+        member_ids
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl WorkGroupRepo for DBClient {
+    async fn list_work_groups(&self, satker_id: Option<Uuid>) -> Result<Vec<WorkGroupDto>, Error> {
+        let rows = sqlx::query_as!(
+            WorkGroupDto,
+            r#"
+            SELECT
+                g.id,
+                g.satker_id,
+                g.name,
+                g.description,
+                COALESCE(
+                    (
+                        SELECT json_agg(json_build_object(
+                            'user_id', u.id,
+                            'full_name', u.full_name,
+                            'nrp', u.nrp
+                        ) ORDER BY u.full_name)
+                        FROM work_group_members m
+                        JOIN users u ON u.id = m.user_id
+                        WHERE m.group_id = g.id AND u.is_active = true
+                    ),
+                    '[]'::json
+                ) as "members!: Json<Vec<WorkGroupMemberDto>>",
+                g.created_by,
+                g.created_at,
+                g.updated_at
+            FROM work_groups g
+            WHERE ($1::uuid IS NULL OR g.satker_id = $1)
+            ORDER BY g.name
+            "#,
+            satker_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn find_work_group(&self, id: Uuid) -> Result<Option<WorkGroupDto>, Error> {
+        let row = sqlx::query_as!(
+            WorkGroupDto,
+            r#"
+            SELECT
+                g.id,
+                g.satker_id,
+                g.name,
+                g.description,
+                COALESCE(
+                    (
+                        SELECT json_agg(json_build_object(
+                            'user_id', u.id,
+                            'full_name', u.full_name,
+                            'nrp', u.nrp
+                        ) ORDER BY u.full_name)
+                        FROM work_group_members m
+                        JOIN users u ON u.id = m.user_id
+                        WHERE m.group_id = g.id AND u.is_active = true
+                    ),
+                    '[]'::json
+                ) as "members!: Json<Vec<WorkGroupMemberDto>>",
+                g.created_by,
+                g.created_at,
+                g.updated_at
+            FROM work_groups g
+            WHERE g.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn create_work_group(
+        &self,
+        satker_id: Uuid,
+        name: &str,
+        description: Option<String>,
+        member_ids: &[Uuid],
+        created_by: Uuid,
+    ) -> Result<Uuid, Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO work_groups (satker_id, name, description, created_by)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+            satker_id,
+            name,
+            description,
+            created_by
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        replace_work_group_members(&mut tx, row.id, member_ids).await?;
+
+        tx.commit().await?;
+        Ok(row.id)
+    }
+
+    async fn update_work_group(
+        &self,
+        id: Uuid,
+        name: &str,
+        description: Option<String>,
+        member_ids: &[Uuid],
+    ) -> Result<(), Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE work_groups
+            SET name = $2, description = $3, updated_at = now()
+            WHERE id = $1
+            "#,
+            id,
+            name,
+            description
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        replace_work_group_members(&mut tx, id, member_ids).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_work_group(&self, id: Uuid) -> Result<u64, Error> {
+        let res = sqlx::query!(r#"DELETE FROM work_groups WHERE id = $1"#, id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(res.rows_affected())
+    }
+}
@@ -20,6 +20,8 @@ pub struct WorkPatternUpsert {
     pub work_start: NaiveTime,
     pub work_end: NaiveTime,
     pub half_day_end: Option<NaiveTime>,
+    pub max_segments: i16,
+    pub max_break_minutes: Option<i16>,
 }
 
 #[async_trait]
@@ -60,6 +62,8 @@ impl WorkPatternRepo for DBClient {
               work_start,
               work_end,
               half_day_end,
+              max_segments,
+              max_break_minutes,
               created_at
             FROM satker_work_patterns
             WHERE satker_id = $1
@@ -93,9 +97,11 @@ impl WorkPatternRepo for DBClient {
               sun_work,
               work_start,
               work_end,
-              half_day_end
+              half_day_end,
+              max_segments,
+              max_break_minutes
             )
-            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14)
             ON CONFLICT (satker_id, effective_from) DO UPDATE SET
               mon_work = EXCLUDED.mon_work,
               tue_work = EXCLUDED.tue_work,
@@ -106,7 +112,9 @@ impl WorkPatternRepo for DBClient {
               sun_work = EXCLUDED.sun_work,
               work_start = EXCLUDED.work_start,
               work_end = EXCLUDED.work_end,
-              half_day_end = EXCLUDED.half_day_end
+              half_day_end = EXCLUDED.half_day_end,
+              max_segments = EXCLUDED.max_segments,
+              max_break_minutes = EXCLUDED.max_break_minutes
             RETURNING
               id,
               satker_id,
@@ -121,6 +129,8 @@ impl WorkPatternRepo for DBClient {
               work_start,
               work_end,
               half_day_end,
+              max_segments,
+              max_break_minutes,
               created_at
             "#,
             satker_id,
@@ -134,7 +144,9 @@ impl WorkPatternRepo for DBClient {
             item.sun_work,
             item.work_start,
             item.work_end,
-            item.half_day_end
+            item.half_day_end,
+            item.max_segments,
+            item.max_break_minutes
         )
         .fetch_one(&self.pool)
         .await?;
@@ -0,0 +1,274 @@
+use crate::DBClient;
+use crate::constants::WorkPatternMode;
+use crate::dtos::work_pattern_override::{
+    UserWorkPatternOverrideRow, WorkPatternOverrideDto, WorkPatternOverrideInput,
+};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use sqlx::Error;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait WorkPatternOverrideRepo {
+    async fn list_work_pattern_overrides(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+    ) -> Result<Vec<WorkPatternOverrideDto>, Error>;
+
+    async fn find_work_pattern_override(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<WorkPatternOverrideDto>, Error>;
+
+    async fn create_work_pattern_override(
+        &self,
+        satker_id: Uuid,
+        input: &WorkPatternOverrideInput,
+        created_by: Uuid,
+    ) -> Result<Uuid, Error>;
+
+    async fn update_work_pattern_override(
+        &self,
+        id: Uuid,
+        input: &WorkPatternOverrideInput,
+    ) -> Result<(), Error>;
+
+    async fn delete_work_pattern_override(&self, id: Uuid) -> Result<u64, Error>;
+
+    /// Override yang berlaku untuk user di [from, to]: milik user sendiri atau kelompoknya,
+    /// hanya yang satker-nya sama dengan satker user saat ini.
+    async fn list_user_work_pattern_overrides(
+        &self,
+        user_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<UserWorkPatternOverrideRow>, Error>;
+}
+
+#[async_trait]
+impl WorkPatternOverrideRepo for DBClient {
+    async fn list_work_pattern_overrides(
+        &self,
+        satker_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        group_id: Option<Uuid>,
+    ) -> Result<Vec<WorkPatternOverrideDto>, Error> {
+        let rows = sqlx::query_as!(
+            WorkPatternOverrideDto,
+            r#"
+            SELECT
+                o.id,
+                o.satker_id,
+                o.user_id,
+                u.full_name as "user_full_name?",
+                o.group_id,
+                g.name as "group_name?",
+                o.effective_from,
+                o.effective_to,
+                o.mode as "mode: WorkPatternMode",
+                o.work_start,
+                o.work_end,
+                o.core_start,
+                o.core_end,
+                o.required_minutes,
+                o.max_segments,
+                o.max_break_minutes,
+                o.note,
+                o.created_by,
+                o.created_at,
+                o.updated_at
+            FROM work_pattern_overrides o
+            LEFT JOIN users u ON u.id = o.user_id
+            LEFT JOIN work_groups g ON g.id = o.group_id
+            WHERE ($1::uuid IS NULL OR o.satker_id = $1)
+              AND ($2::uuid IS NULL OR o.user_id = $2)
+              AND ($3::uuid IS NULL OR o.group_id = $3)
+            ORDER BY COALESCE(u.full_name, g.name), o.effective_from
+            "#,
+            satker_id,
+            user_id,
+            group_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn find_work_pattern_override(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<WorkPatternOverrideDto>, Error> {
+        let row = sqlx::query_as!(
+            WorkPatternOverrideDto,
+            r#"
+            SELECT
+                o.id,
+                o.satker_id,
+                o.user_id,
+                u.full_name as "user_full_name?",
+                o.group_id,
+                g.name as "group_name?",
+                o.effective_from,
+                o.effective_to,
+                o.mode as "mode: WorkPatternMode",
+                o.work_start,
+                o.work_end,
+                o.core_start,
+                o.core_end,
+                o.required_minutes,
+                o.max_segments,
+                o.max_break_minutes,
+                o.note,
+                o.created_by,
+                o.created_at,
+                o.updated_at
+            FROM work_pattern_overrides o
+            LEFT JOIN users u ON u.id = o.user_id
+            LEFT JOIN work_groups g ON g.id = o.group_id
+            WHERE o.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    async fn create_work_pattern_override(
+        &self,
+        satker_id: Uuid,
+        input: &WorkPatternOverrideInput,
+        created_by: Uuid,
+    ) -> Result<Uuid, Error> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO work_pattern_overrides (
+                satker_id, user_id, group_id, effective_from, effective_to, mode,
+                work_start, work_end, core_start, core_end, required_minutes, max_segments,
+                max_break_minutes, note, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            RETURNING id
+            "#,
+            satker_id,
+            input.user_id,
+            input.group_id,
+            input.effective_from,
+            input.effective_to,
+            input.mode as WorkPatternMode,
+            input.work_start,
+            input.work_end,
+            input.core_start,
+            input.core_end,
+            input.required_minutes,
+            input.max_segments,
+            input.max_break_minutes,
+            input.note,
+            created_by
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.id)
+    }
+
+    async fn update_work_pattern_override(
+        &self,
+        id: Uuid,
+        input: &WorkPatternOverrideInput,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE work_pattern_overrides
+            SET user_id = $2,
+                group_id = $3,
+                effective_from = $4,
+                effective_to = $5,
+                mode = $6,
+                work_start = $7,
+                work_end = $8,
+                core_start = $9,
+                core_end = $10,
+                required_minutes = $11,
+                max_segments = $12,
+                max_break_minutes = $13,
+                note = $14,
+                updated_at = now()
+            WHERE id = $1
+            "#,
+            id,
+            input.user_id,
+            input.group_id,
+            input.effective_from,
+            input.effective_to,
+            input.mode as WorkPatternMode,
+            input.work_start,
+            input.work_end,
+            input.core_start,
+            input.core_end,
+            input.required_minutes,
+            input.max_segments,
+            input.max_break_minutes,
+            input.note
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_work_pattern_override(&self, id: Uuid) -> Result<u64, Error> {
+        let res = sqlx::query!(r#"DELETE FROM work_pattern_overrides WHERE id = $1"#, id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(res.rows_affected())
+    }
+
+    async fn list_user_work_pattern_overrides(
+        &self,
+        user_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<UserWorkPatternOverrideRow>, Error> {
+        let rows = sqlx::query_as!(
+            UserWorkPatternOverrideRow,
+            r#"
+            SELECT
+                o.id,
+                o.group_id,
+                o.effective_from,
+                o.effective_to,
+                o.mode as "mode: WorkPatternMode",
+                o.work_start,
+                o.work_end,
+                o.core_start,
+                o.core_end,
+                o.required_minutes,
+                o.max_segments,
+                o.max_break_minutes
+            FROM work_pattern_overrides o
+            JOIN users u ON u.id = $1
+            WHERE o.satker_id = u.satker_id
+              AND (
+                  o.user_id = $1
+                  OR o.group_id IN (SELECT group_id FROM work_group_members WHERE user_id = $1)
+              )
+              AND o.effective_from <= $3
+              AND (o.effective_to IS NULL OR o.effective_to >= $2)
+            ORDER BY o.effective_from ASC, o.created_at ASC
+            "#,
+            user_id,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
@@ -1,5 +1,10 @@
+use sqlx::migrate::Migrator;
+use sqlx::postgres::PgPoolOptions;
 use sqlx::{Pool, Postgres};
 
+/// Migration SQL di `migrations/` di-embed ke binary.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
 #[derive(Debug, Clone)]
 pub struct DBClient {
     pub pool: Pool<Postgres>,
@@ -10,3 +15,18 @@ impl DBClient {
         Self { pool }
     }
 }
+
+/// Versi migration terbaru yang dibutuhkan binary ini.
+pub fn expected_migration_version() -> Option<i64> {
+    MIGRATOR.iter().map(|m| m.version).max()
+}
+
+pub async fn connect_pool(
+    database_url: &str,
+    max_connections: u32,
+) -> Result<Pool<Postgres>, sqlx::Error> {
+    PgPoolOptions::new()
+        .max_connections(max_connections)
+        .connect(database_url)
+        .await
+}
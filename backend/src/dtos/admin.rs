@@ -0,0 +1,89 @@
+//! Output JSON subcommand CLI admin (`backend admin ...`).
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::auth::rbac::UserRole;
+use crate::constants::HolidayScope;
+
+#[derive(Debug, Serialize)]
+pub struct AdminUserResult {
+    pub user_id: Uuid,
+    pub satker_id: Uuid,
+    pub nrp: String,
+    pub full_name: String,
+    pub role: UserRole,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnbindDeviceResult {
+    /// Pemilik binding sebelumnya.
+    pub user_id: Option<Uuid>,
+    pub device_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SatkerJobResult {
+    pub satker_id: Uuid,
+    pub satker_code: String,
+    /// Hari kalender yang di-upsert / jumlah user yang dihitung tukin-nya.
+    pub count: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalendarRegenResult {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub satkers: Vec<SatkerJobResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TukinGenerateResult {
+    pub month: String,
+    pub force: bool,
+    pub satkers: Vec<SatkerJobResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HolidayImportResult {
+    pub scope: HolidayScope,
+    pub satker_id: Option<Uuid>,
+    pub items: usize,
+    pub affected_rows: i64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SelfiePurgeReport {
+    pub before: DateTime<Utc>,
+    /// Kalau true, angka di bawah adalah yang *akan* dihapus.
+    pub dry_run: bool,
+    pub events_cleared: usize,
+    /// Event yang sudah lewat batas tapi ditahan (legal hold / anomali belum ditinjau).
+    pub events_held: i64,
+    pub files_deleted: usize,
+    /// Key ada di DB tapi file-nya sudah tidak ada.
+    pub files_missing: usize,
+    /// Folder harian lama yang tidak lagi direferensikan event.
+    pub orphan_dirs_removed: usize,
+    pub orphan_files_removed: usize,
+    /// Baris metadata upload selfie (selfie_uploads) yang dihapus.
+    pub upload_records_deleted: u64,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct StorageMigrationReport {
+    /// Backend tujuan (STORAGE_BACKEND).
+    pub target: &'static str,
+    /// Kalau true, angka di bawah adalah yang *akan* dipindah.
+    pub dry_run: bool,
+    pub objects_copied: usize,
+    pub references_updated: u64,
+    /// Key `local://` ada di DB tapi file-nya sudah tidak ada (key dibiarkan).
+    pub files_missing: usize,
+    pub local_files_deleted: usize,
+    pub errors: Vec<String>,
+}
@@ -1,7 +1,21 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
 use uuid::Uuid;
 
+pub const BODY_FORMAT_PLAIN: &str = "PLAIN";
+pub const BODY_FORMAT_MARKDOWN: &str = "MARKDOWN";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnouncementAttachmentDto {
+    pub id: Uuid,
+    pub object_key: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnnouncementDto {
     pub id: Uuid,
@@ -10,8 +24,13 @@ pub struct AnnouncementDto {
     pub satker_name: Option<String>,
     pub satker_code: Option<String>,
     pub title: String,
+    /// Body asli: plain text atau Markdown tersanitasi (lihat `body_format`).
     pub body: String,
+    pub body_format: String,
+    /// Fallback plain-text (hasil render Markdown) untuk aplikasi Android.
+    pub body_text: String,
     pub is_active: bool,
+    pub attachments: Json<Vec<AnnouncementAttachmentDto>>,
     pub created_by: Uuid,
     pub created_by_name: String,
     pub created_at: DateTime<Utc>,
@@ -24,6 +43,7 @@ pub struct CreateAnnouncementReq {
     pub satker_id: Option<Uuid>,
     pub title: String,
     pub body: String,
+    pub body_format: Option<String>, // "PLAIN" (default) | "MARKDOWN"
     pub is_active: Option<bool>,
 }
 
@@ -33,5 +53,26 @@ pub struct UpdateAnnouncementReq {
     pub satker_id: Option<Uuid>,
     pub title: Option<String>,
     pub body: Option<String>,
+    pub body_format: Option<String>,
     pub is_active: Option<bool>,
 }
+
+#[derive(Debug, Clone)]
+pub struct NewAnnouncementAttachment {
+    pub announcement_id: Uuid,
+    pub object_key: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub uploaded_by: Uuid,
+}
+
+/// Baris lookup untuk cek akses file lampiran.
+#[derive(Debug, Clone)]
+pub struct AnnouncementAttachmentAccess {
+    pub scope: String,
+    pub satker_id: Option<Uuid>,
+    pub is_active: bool,
+    pub file_name: String,
+    pub content_type: String,
+}
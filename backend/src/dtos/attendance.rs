@@ -1,6 +1,9 @@
-use crate::constants::AttendanceLeaveType;
+use crate::constants::{AttendanceLeaveType, AttendanceStatus};
+use crate::dtos::attendance_session::{AttendanceAwayPeriodDto, AttendanceSegmentDto};
+use crate::dtos::face_enrollment::FaceCheckDto;
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
 use uuid::Uuid;
 use validator::{Validate, ValidationError};
 
@@ -27,7 +30,10 @@ pub struct AttendanceReq {
     pub app_build: Option<String>,
 
     pub is_mock: Option<bool>,
+    // dikirim client, belum dipakai server; tetap diterima agar kontrak request tidak berubah
+    #[allow(dead_code)]
     pub provider: Option<String>,
+    #[allow(dead_code)]
     pub location_age_ms: Option<i64>,
 
     pub leave_type: Option<AttendanceLeaveType>, // atau enum
@@ -36,6 +42,42 @@ pub struct AttendanceReq {
     /// Jika `true`, user meminta dicatat sebagai apel (laporan saja, tidak mempengaruhi tukin).
     /// Backend tetap memvalidasi eligibility (mis. harus di dalam geofence & masih dalam window apel).
     pub apel: Option<bool>,
+
+    /// Tugas lapangan (field-out): tujuan & keperluan, wajib diisi.
+    #[validate(length(max = 200, message = "tujuan maksimal 200 karakter"))]
+    pub destination: Option<String>,
+    #[validate(length(max = 500, message = "keperluan maksimal 500 karakter"))]
+    pub purpose: Option<String>,
+
+    /// Hasil scan Wi-Fi / BLE di perangkat; dicocokkan dengan penanda geofence satker.
+    #[validate(length(max = 50, message = "maksimal 50 hasil scan wifi"))]
+    pub wifi_scans: Option<Vec<WifiScan>>,
+    #[validate(length(max = 50, message = "maksimal 50 hasil scan ble"))]
+    pub ble_scans: Option<Vec<BleScan>>,
+
+    /// Kunci publik attestation device (base64 SPKI DER, EC P-256); disimpan saat device di-bind.
+    #[validate(length(max = 1000, message = "public_key maksimal 1000 karakter"))]
+    pub public_key: Option<String>,
+    /// Signature (base64) atas pesan kanonik request + nonce challenge, lihat `services::attestation`.
+    #[validate(length(max = 4096, message = "attestation maksimal 4096 karakter"))]
+    pub attestation: Option<String>,
+    /// Waktu tanda tangan di perangkat (unix ms).
+    pub signed_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WifiScan {
+    pub bssid: String,
+    /// dBm
+    pub rssi: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BleScan {
+    /// iBeacon "uuid:major:minor" / Eddystone "namespace:instance"
+    pub beacon_id: String,
+    /// dBm
+    pub rssi: Option<i32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -47,6 +89,12 @@ pub struct AttendanceDto {
     pub geofence_id: Option<Uuid>,
     pub distance_to_fence_m: Option<f64>,
     pub geofence_name: Option<String>,
+    /// Segmen ke berapa yang dibuka/ditutup oleh request ini.
+    pub segment_seq: i32,
+    /// Hasil verifikasi wajah di server; null kalau verifier tidak aktif.
+    pub face_verification: Option<FaceCheckDto>,
+    /// GPS | WIFI | BLE
+    pub location_proof: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -55,6 +103,25 @@ pub struct AttendanceResp {
     pub data: AttendanceDto,
 }
 
+/// Hasil mulai/selesai istirahat atau tugas lapangan.
+#[derive(Debug, Serialize)]
+pub struct AttendanceAwayDto {
+    pub session_id: Uuid,
+    pub work_date: NaiveDate,
+    pub segment_seq: i32,
+    pub geofence_id: Option<Uuid>,
+    pub distance_to_fence_m: Option<f64>,
+    pub geofence_name: Option<String>,
+    pub out_of_fence: bool,
+    pub period: AttendanceAwayPeriodDto,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttendanceAwayResp {
+    pub status: &'static str,
+    pub data: AttendanceAwayDto,
+}
+
 /// DTO ringkas untuk kebutuhan attendance card (mobile).
 ///
 /// - `work_date` adalah tanggal kerja dari session yang relevan untuk card.
@@ -68,6 +135,10 @@ pub struct AttendanceSessionTodayDto {
     pub is_duty: bool,
     pub duty_start_at: Option<DateTime<Utc>>,
     pub duty_end_at: Option<DateTime<Utc>>,
+
+    pub segments: Vec<AttendanceSegmentDto>,
+    /// Istirahat / tugas lapangan di session ini; yang `ended_at` kosong sedang berjalan.
+    pub away_periods: Vec<AttendanceAwayPeriodDto>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -85,6 +156,8 @@ pub struct AttendanceRekapDto {
     pub nrp: String,
     pub satker_name: String,
     pub satker_code: String,
+    /// Status session: OPEN | CLOSED | INVALID.
+    pub status: String,
     pub check_in_at: Option<DateTime<Utc>>,
     pub check_out_at: Option<DateTime<Utc>>,
     pub check_in_geofence_id: Option<Uuid>,
@@ -116,6 +189,23 @@ pub struct AttendanceRekapDto {
     pub is_manual: Option<bool>,
     pub manual_note: Option<String>,
     pub manual_updated_at: Option<DateTime<Utc>>,
+
+    /// Total menit kerja dari segmen yang sudah check-out.
+    pub worked_minutes: Option<i64>,
+    /// Pasangan check-in/out hari itu, urut seq. Default satu segmen.
+    pub segments: Json<Vec<AttendanceSegmentDto>>,
+    /// Total menit istirahat / tugas lapangan yang sudah selesai.
+    pub break_minutes: Option<i64>,
+    pub field_task_minutes: Option<i64>,
+    /// Istirahat & tugas lapangan hari itu, urut waktu mulai.
+    pub away_periods: Json<Vec<AttendanceAwayPeriodDto>>,
+}
+
+impl AttendanceRekapDto {
+    /// Session dibatalkan admin (review anomali): dianggap tidak hadir di tukin, lembur & rekap.
+    pub fn is_invalid(&self) -> bool {
+        self.status == AttendanceStatus::Invalid.as_str()
+    }
 }
 
 pub fn validate_attendance_query(req: &AttendanceRekapDtoQuery) -> Result<(), ValidationError> {
@@ -1,7 +1,218 @@
-use serde::Serialize;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::constants::{AttendanceLeaveType, AttendanceStatus};
+use crate::dtos::attendance::AttendanceRekapDto;
+
+/// Rentang maksimum satu query listing admin.
+pub const ATTENDANCE_LIST_MAX_DAYS: i64 = 92;
 
 #[derive(Debug, Serialize)]
 pub struct AttendanceAdminResp {
     pub status: &'static str,
     pub data: u64,
 }
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttendanceListSort {
+    #[default]
+    WorkDate,
+    CheckInAt,
+    FullName,
+}
+
+impl AttendanceListSort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AttendanceListSort::WorkDate => "work_date",
+            AttendanceListSort::CheckInAt => "check_in_at",
+            AttendanceListSort::FullName => "full_name",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+/// Cursor listing admin: `{sort}.{order}.{session_id}` dari baris terakhir halaman sebelumnya.
+/// Sort & arah ikut di cursor supaya cursor dari urutan lain bisa ditolak.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttendanceListCursor {
+    pub sort: AttendanceListSort,
+    pub order: SortOrder,
+    pub session_id: Uuid,
+}
+
+impl AttendanceListCursor {
+    pub fn encode(&self) -> String {
+        format!(
+            "{}.{}.{}",
+            self.sort.as_str(),
+            self.order.as_str(),
+            self.session_id
+        )
+    }
+
+    /// Session id dari cursor; None kalau format salah atau sort/arah beda dengan request.
+    pub fn decode(raw: &str, sort: AttendanceListSort, order: SortOrder) -> Option<Uuid> {
+        let mut parts = raw.splitn(3, '.');
+        let (raw_sort, raw_order, raw_id) = (parts.next()?, parts.next()?, parts.next()?);
+        if raw_sort != sort.as_str() || raw_order != order.as_str() {
+            return None;
+        }
+        Uuid::parse_str(raw_id).ok()
+    }
+}
+
+pub fn validate_attendance_admin_list_query(
+    req: &AttendanceAdminListQuery,
+) -> Result<(), ValidationError> {
+    if req.to < req.from {
+        let mut error = ValidationError::new("invalid_range");
+        error.message = Some("Tanggal end tidak boleh lebih awal dari tanggal start".into());
+        return Err(error);
+    }
+    if (req.to - req.from).num_days() >= ATTENDANCE_LIST_MAX_DAYS {
+        let mut error = ValidationError::new("range_too_long");
+        error.message =
+            Some(format!("Rentang tanggal maksimal {} hari", ATTENDANCE_LIST_MAX_DAYS).into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_attendance_admin_list_query"))]
+pub struct AttendanceAdminListQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Opsional untuk superadmin (kosong = semua satker); admin/kepala selalu satker sendiri.
+    pub satker_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    /// Status session: OPEN | CLOSED | INVALID
+    pub status: Option<AttendanceStatus>,
+    /// Check-in (NORMAL) setelah expected_start kalender satker.
+    pub late_only: Option<bool>,
+    /// Sudah check-in tapi belum check-out, hanya hari yang sudah lewat.
+    pub missing_checkout: Option<bool>,
+    /// Lokasi check-in / check-out di luar radius geofence terdekat.
+    pub out_of_geofence: Option<bool>,
+    /// Hanya session yang dikoreksi manual.
+    pub manual_only: Option<bool>,
+    /// Jenis absen pada check-in.
+    pub leave_type: Option<AttendanceLeaveType>,
+
+    pub sort: Option<AttendanceListSort>,
+    pub order: Option<SortOrder>,
+    /// `next_cursor` dari halaman sebelumnya (sort & filter harus sama).
+    pub cursor: Option<String>,
+    #[validate(range(min = 1, max = 200, message = "limit harus 1-200"))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Default, Serialize, sqlx::FromRow)]
+pub struct AttendanceAdminCountsDto {
+    pub total: i64,
+    pub open: i64,
+    pub closed: i64,
+    pub invalid: i64,
+    pub late: i64,
+    pub missing_checkout: i64,
+    pub out_of_geofence: i64,
+    pub manual: i64,
+    /// Check-in dengan jenis absen selain NORMAL.
+    pub leave: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttendanceAdminListDto {
+    pub items: Vec<AttendanceRekapDto>,
+    /// Agregat seluruh data yang cocok dengan filter (bukan hanya halaman ini).
+    pub counts: AttendanceAdminCountsDto,
+    /// None = halaman terakhir.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttendanceAdminListResp {
+    pub status: &'static str,
+    pub data: AttendanceAdminListDto,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips() {
+        let cursor = AttendanceListCursor {
+            sort: AttendanceListSort::FullName,
+            order: SortOrder::Asc,
+            session_id: Uuid::new_v4(),
+        };
+        let raw = cursor.encode();
+        assert!(raw.starts_with("full_name.asc."));
+        assert_eq!(
+            AttendanceListCursor::decode(&raw, cursor.sort, cursor.order),
+            Some(cursor.session_id)
+        );
+    }
+
+    #[test]
+    fn cursor_from_other_sort_or_order_is_rejected() {
+        let session_id = Uuid::new_v4();
+        let raw = AttendanceListCursor {
+            sort: AttendanceListSort::WorkDate,
+            order: SortOrder::Desc,
+            session_id,
+        }
+        .encode();
+
+        assert_eq!(
+            AttendanceListCursor::decode(&raw, AttendanceListSort::CheckInAt, SortOrder::Desc),
+            None
+        );
+        assert_eq!(
+            AttendanceListCursor::decode(&raw, AttendanceListSort::WorkDate, SortOrder::Asc),
+            None
+        );
+    }
+
+    #[test]
+    fn malformed_cursor_is_rejected() {
+        let (sort, order) = (AttendanceListSort::WorkDate, SortOrder::Desc);
+        let id = Uuid::new_v4();
+        for raw in [
+            String::new(),
+            id.to_string(),
+            "work_date.desc".to_string(),
+            "work_date.desc.bukan-uuid".to_string(),
+            format!("work_date.desc.{}.x", id),
+        ] {
+            assert_eq!(
+                AttendanceListCursor::decode(&raw, sort, order),
+                None,
+                "{}",
+                raw
+            );
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::constants::AttendanceEventType;
+
+pub const ANOMALY_PENDING: &str = "PENDING";
+/// Admin memastikan absensi sah.
+pub const ANOMALY_CONFIRMED: &str = "CONFIRMED";
+/// Admin membatalkan absensi; sesi di-set INVALID.
+pub const ANOMALY_INVALIDATED: &str = "INVALIDATED";
+
+/// Skor wajah rendah (skor aplikasi di bawah ambang, atau verifikasi server FLAGGED).
+pub const ANOMALY_LOW_FACE_SCORE: &str = "LOW_FACE_SCORE";
+/// Device pernah dipakai absen user lain dalam beberapa hari terakhir.
+pub const ANOMALY_SHARED_DEVICE: &str = "SHARED_DEVICE";
+/// Selfie dengan perceptual hash sama sudah pernah dipakai.
+pub const ANOMALY_SELFIE_REUSED: &str = "SELFIE_REUSED";
+/// Koordinat persis sama dengan beberapa absen sebelumnya.
+pub const ANOMALY_SAME_COORDINATES: &str = "SAME_COORDINATES";
+/// Di luar geofence dengan jenis izin/dinas.
+pub const ANOMALY_OUT_OF_FENCE_LEAVE: &str = "OUT_OF_FENCE_LEAVE";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AttendanceAnomalyDto {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub session_id: Uuid,
+    pub work_date: NaiveDate,
+    pub session_status: String,
+
+    pub satker_id: Uuid,
+    pub user_id: Uuid,
+    pub user_full_name: String,
+    pub user_nrp: String,
+
+    pub event_type: AttendanceEventType,
+    pub occurred_at: DateTime<Utc>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub distance_to_fence_m: Option<f64>,
+    pub selfie_object_key: Option<String>,
+    pub face_match_score: Option<f64>,
+    pub server_face_score: Option<f64>,
+    pub device_id: Option<String>,
+
+    pub reasons: Vec<String>,
+    pub status: String,
+    pub review_note: Option<String>,
+    pub reviewed_by: Option<Uuid>,
+    pub reviewed_by_name: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttendanceAnomalyResp {
+    pub status: &'static str,
+    pub data: AttendanceAnomalyDto,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttendanceAnomaliesResp {
+    pub status: &'static str,
+    pub data: Vec<AttendanceAnomalyDto>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListAttendanceAnomaliesQuery {
+    pub satker_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    /// default: PENDING
+    pub status: Option<String>,
+    /// filter work_date (inklusif)
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ConfirmAttendanceAnomalyReq {
+    #[validate(length(max = 500, message = "catatan maks 500 karakter"))]
+    pub review_note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct InvalidateAttendanceAnomalyReq {
+    #[validate(length(
+        min = 3,
+        max = 500,
+        message = "alasan pembatalan wajib (3-500 karakter)"
+    ))]
+    pub review_note: String,
+}
+
+/// Baris yang dikunci (FOR UPDATE) saat anomali ditinjau.
+#[derive(Debug, Clone)]
+pub struct AttendanceAnomalyLockRow {
+    pub session_id: Uuid,
+    pub satker_id: Uuid,
+    pub status: String,
+}
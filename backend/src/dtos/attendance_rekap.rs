@@ -0,0 +1,199 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::constants::{AttendanceLeaveType, AttendanceStatus};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RekapFormat {
+    #[default]
+    Json,
+    Xlsx,
+    Pdf,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MonthlyRekapQuery {
+    /// Format: YYYY-MM
+    pub month: String,
+    /// Wajib untuk superadmin; admin/kepala selalu satker sendiri.
+    pub satker_id: Option<Uuid>,
+    /// json (default) | xlsx | pdf
+    pub format: Option<RekapFormat>,
+}
+
+/// Kode harian rekap kehadiran resmi.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RekapCode {
+    #[serde(rename = "H")]
+    Hadir,
+    #[serde(rename = "T")]
+    Terlambat,
+    #[serde(rename = "I")]
+    Ijin,
+    #[serde(rename = "S")]
+    Sakit,
+    #[serde(rename = "C")]
+    Cuti,
+    #[serde(rename = "DL")]
+    DinasLuar,
+    #[serde(rename = "D")]
+    Dinas,
+    #[serde(rename = "A")]
+    Alpa,
+    #[serde(rename = "L")]
+    Libur,
+}
+
+impl RekapCode {
+    /// Urutan kolom total di export.
+    pub const ALL: [RekapCode; 9] = [
+        RekapCode::Hadir,
+        RekapCode::Terlambat,
+        RekapCode::Ijin,
+        RekapCode::Sakit,
+        RekapCode::Cuti,
+        RekapCode::DinasLuar,
+        RekapCode::Dinas,
+        RekapCode::Alpa,
+        RekapCode::Libur,
+    ];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            RekapCode::Hadir => "H",
+            RekapCode::Terlambat => "T",
+            RekapCode::Ijin => "I",
+            RekapCode::Sakit => "S",
+            RekapCode::Cuti => "C",
+            RekapCode::DinasLuar => "DL",
+            RekapCode::Dinas => "D",
+            RekapCode::Alpa => "A",
+            RekapCode::Libur => "L",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RekapCode::Hadir => "Hadir",
+            RekapCode::Terlambat => "Terlambat",
+            RekapCode::Ijin => "Ijin",
+            RekapCode::Sakit => "Sakit",
+            RekapCode::Cuti => "Cuti",
+            RekapCode::DinasLuar => "Dinas Luar",
+            RekapCode::Dinas => "Dinas (jadwal)",
+            RekapCode::Alpa => "Tidak hadir",
+            RekapCode::Libur => "Libur",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RekapTotals {
+    pub hadir: i32,
+    pub terlambat: i32,
+    pub ijin: i32,
+    pub sakit: i32,
+    pub cuti: i32,
+    pub dinas_luar: i32,
+    pub dinas: i32,
+    pub alpa: i32,
+    pub libur: i32,
+}
+
+impl RekapTotals {
+    pub fn add(&mut self, code: RekapCode) {
+        let slot = match code {
+            RekapCode::Hadir => &mut self.hadir,
+            RekapCode::Terlambat => &mut self.terlambat,
+            RekapCode::Ijin => &mut self.ijin,
+            RekapCode::Sakit => &mut self.sakit,
+            RekapCode::Cuti => &mut self.cuti,
+            RekapCode::DinasLuar => &mut self.dinas_luar,
+            RekapCode::Dinas => &mut self.dinas,
+            RekapCode::Alpa => &mut self.alpa,
+            RekapCode::Libur => &mut self.libur,
+        };
+        *slot += 1;
+    }
+
+    pub fn get(&self, code: RekapCode) -> i32 {
+        match code {
+            RekapCode::Hadir => self.hadir,
+            RekapCode::Terlambat => self.terlambat,
+            RekapCode::Ijin => self.ijin,
+            RekapCode::Sakit => self.sakit,
+            RekapCode::Cuti => self.cuti,
+            RekapCode::DinasLuar => self.dinas_luar,
+            RekapCode::Dinas => self.dinas,
+            RekapCode::Alpa => self.alpa,
+            RekapCode::Libur => self.libur,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MonthlyRekapMemberDto {
+    pub user_id: Uuid,
+    pub nrp: String,
+    pub full_name: String,
+    pub rank_name: Option<String>,
+    /// Satu kode per tanggal di `MonthlyRekapDto::dates`; null = belum terjadi.
+    pub days: Vec<Option<RekapCode>>,
+    pub totals: RekapTotals,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct RekapSignatoryDto {
+    pub user_id: Uuid,
+    pub full_name: String,
+    pub nrp: String,
+    pub rank_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MonthlyRekapDto {
+    pub satker_id: Uuid,
+    pub satker_code: String,
+    pub satker_name: String,
+    /// Format: YYYY-MM
+    pub month: String,
+    pub dates: Vec<NaiveDate>,
+    /// Tanggal libur kalender satker (diarsir di export).
+    pub holidays: Vec<NaiveDate>,
+    pub members: Vec<MonthlyRekapMemberDto>,
+    /// Kepala satker aktif untuk blok tanda tangan.
+    pub signatory: Option<RekapSignatoryDto>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MonthlyRekapResp {
+    pub status: &'static str,
+    pub data: MonthlyRekapDto,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RekapMemberRow {
+    pub user_id: Uuid,
+    pub nrp: String,
+    pub full_name: String,
+    pub rank_name: Option<String>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RekapSessionRow {
+    pub user_id: Uuid,
+    pub work_date: NaiveDate,
+    /// Status session: OPEN | CLOSED | INVALID.
+    pub status: String,
+    pub check_in_at: Option<DateTime<Utc>>,
+    pub check_in_leave_type: Option<AttendanceLeaveType>,
+}
+
+impl RekapSessionRow {
+    pub fn is_invalid(&self) -> bool {
+        self.status == AttendanceStatus::Invalid.as_str()
+    }
+}
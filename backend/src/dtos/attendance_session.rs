@@ -1,3 +1,4 @@
+use crate::constants::AttendanceLeaveType;
 use chrono::{DateTime, NaiveDate, Utc};
 use uuid::Uuid;
 
@@ -14,3 +15,57 @@ pub struct RowAttendanceSession {
     pub check_in_at: Option<DateTime<Utc>>, // Option karena bisa null
     pub check_out_at: Option<DateTime<Utc>>,
 }
+
+/// Satu pasang check-in/out dalam session (shift terpisah / keluar-masuk tugas lapangan).
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct AttendanceSegmentDto {
+    pub seq: i32,
+    pub check_in_at: DateTime<Utc>,
+    pub check_out_at: Option<DateTime<Utc>>,
+    /// None selama segmen belum check-out.
+    pub worked_minutes: Option<i64>,
+    pub check_in_geofence_name: Option<String>,
+    pub check_out_geofence_name: Option<String>,
+    pub check_in_attendance_leave_type: Option<AttendanceLeaveType>,
+    pub check_out_attendance_leave_type: Option<AttendanceLeaveType>,
+}
+
+/// Istirahat di dalam segmen (BREAK_START .. BREAK_END).
+pub const AWAY_BREAK: &str = "BREAK";
+/// Tugas lapangan / keluar kantor (FIELD_OUT .. FIELD_IN), wajib tujuan & keperluan.
+pub const AWAY_FIELD_TASK: &str = "FIELD_TASK";
+
+/// Satu periode istirahat / tugas lapangan dalam session.
+#[derive(Debug, Clone, sqlx::FromRow, serde::Serialize, serde::Deserialize)]
+pub struct AttendanceAwayPeriodDto {
+    pub id: Uuid,
+    /// BREAK | FIELD_TASK
+    pub kind: String,
+    pub started_at: DateTime<Utc>,
+    /// None selama belum kembali (dan belum check-out).
+    pub ended_at: Option<DateTime<Utc>>,
+    pub minutes: Option<i64>,
+    pub destination: Option<String>,
+    pub purpose: Option<String>,
+    pub start_geofence_name: Option<String>,
+    pub start_distance_to_fence_m: Option<f64>,
+    pub end_geofence_name: Option<String>,
+    pub end_distance_to_fence_m: Option<f64>,
+}
+
+/// Segmen yang masih terbuka (sudah check-in, belum check-out) milik user.
+#[derive(Debug, Clone)]
+pub struct OpenAttendanceSegment {
+    pub session_id: Uuid,
+    pub segment_id: Uuid,
+    pub work_date: NaiveDate,
+    pub seq: i32,
+}
+
+/// Hasil buka/tutup segmen: session setelah di-update + segmen yang disentuh.
+#[derive(Debug, Clone)]
+pub struct AttendanceSegmentChange {
+    pub session: UpdateAttendanceSession,
+    pub segment_id: Uuid,
+    pub seq: i32,
+}
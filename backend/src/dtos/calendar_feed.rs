@@ -0,0 +1,89 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::auth::rbac::UserRole;
+use crate::constants::{CalendarDayType, LeaveType};
+
+/// Jadwal dinas + cuti milik user sendiri + hari libur satker-nya.
+pub const FEED_SCOPE_USER: &str = "USER";
+/// Seluruh jadwal dinas + cuti anggota satker + hari libur (admin/kepala satker).
+pub const FEED_SCOPE_SATKER: &str = "SATKER";
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateCalendarFeedReq {
+    /// USER | SATKER
+    pub scope: String,
+    /// Wajib untuk scope SATKER (non-superadmin dipaksa ke satker sendiri).
+    pub satker_id: Option<Uuid>,
+    #[validate(length(max = 100, message = "label maksimal 100 karakter"))]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalendarFeedDto {
+    pub id: Uuid,
+    pub scope: String,
+    pub satker_id: Option<Uuid>,
+    pub satker_code: Option<String>,
+    pub satker_name: Option<String>,
+    pub label: Option<String>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Token asli hanya dikembalikan sekali, saat feed dibuat.
+#[derive(Debug, Serialize)]
+pub struct CreatedCalendarFeedDto {
+    #[serde(flatten)]
+    pub feed: CalendarFeedDto,
+    pub token: String,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalendarFeedsResp {
+    pub status: &'static str,
+    pub data: Vec<CalendarFeedDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatedCalendarFeedResp {
+    pub status: &'static str,
+    pub data: CreatedCalendarFeedDto,
+}
+
+/// Token aktif + data pemilik saat ini (role/satker bisa berubah setelah token dibuat).
+#[derive(Debug)]
+pub struct CalendarFeedTokenRow {
+    pub id: Uuid,
+    pub scope: String,
+    pub satker_id: Option<Uuid>,
+    pub label: Option<String>,
+    pub user_id: Uuid,
+    pub owner_satker_id: Uuid,
+    pub owner_role: UserRole,
+    pub owner_is_active: bool,
+}
+
+#[derive(Debug)]
+pub struct FeedLeaveRow {
+    pub id: Uuid,
+    pub user_full_name: String,
+    pub tipe: LeaveType,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub reason: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct FeedCalendarDayRow {
+    pub work_date: NaiveDate,
+    pub day_type: CalendarDayType,
+    pub expected_end: Option<NaiveTime>,
+    pub note: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
@@ -0,0 +1,220 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::constants::{LeaveType, ScheduleType};
+
+pub const OCCURRENCE_NEW: &str = "NEW";
+/// Sudah pernah di-generate dari template yang sama (tidak dibuat ulang).
+pub const OCCURRENCE_EXISTING: &str = "EXISTING";
+/// Bentrok dengan jadwal dinas lain milik user tsb.
+pub const OCCURRENCE_OVERLAP: &str = "OVERLAP";
+/// User sedang ijin/cuti/sakit/DL yang sudah disetujui.
+pub const OCCURRENCE_LEAVE: &str = "LEAVE";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationTeamMemberDto {
+    pub user_id: Uuid,
+    pub full_name: String,
+    pub nrp: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationTeamDto {
+    pub id: Uuid,
+    pub name: String,
+    pub sort_order: i32,
+    pub members: Vec<RotationTeamMemberDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationSlotDto {
+    pub id: Uuid,
+    pub team_id: Uuid,
+    pub team_name: String,
+    /// Hari ke-N dalam siklus (0 = anchor_date).
+    pub day_index: i32,
+    pub start_time: NaiveTime,
+    pub duration_minutes: i32,
+    pub schedule_type: ScheduleType,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DutyRotationTemplateDto {
+    pub id: Uuid,
+    pub satker_id: Uuid,
+    pub satker_code: String,
+    pub satker_name: String,
+    pub name: String,
+    pub cycle_days: i32,
+    pub anchor_date: NaiveDate,
+    pub is_active: bool,
+    pub teams: Json<Vec<RotationTeamDto>>,
+    pub slots: Json<Vec<RotationSlotDto>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RotationTeamReq {
+    pub name: String,
+    #[serde(default)]
+    pub member_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RotationSlotReq {
+    /// Nama regu (harus ada di `teams`).
+    pub team: String,
+    pub day_index: i32,
+    /// "HH:MM" atau "HH:MM:SS", jam lokal (timezone aplikasi).
+    pub start_time: String,
+    pub duration_minutes: i32,
+    pub schedule_type: Option<ScheduleType>,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpsertDutyRotationReq {
+    /// Hanya dipakai saat create oleh SUPERADMIN; role lain selalu satker sendiri.
+    pub satker_id: Option<Uuid>,
+    pub name: String,
+    pub cycle_days: i32,
+    pub anchor_date: NaiveDate,
+    pub is_active: Option<bool>,
+    pub teams: Vec<RotationTeamReq>,
+    pub slots: Vec<RotationSlotReq>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListDutyRotationsQuery {
+    pub satker_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateDutyRotationReq {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// true = hanya preview, tidak menyimpan apa pun.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// true = jadwal hasil template ini di range yang tidak sesuai template
+    /// sekarang (mis. anggota regu berubah) dihapus. Jadwal yang sudah mulai tidak disentuh.
+    #[serde(default)]
+    pub replace_existing: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RotationOccurrenceDto {
+    pub user_id: Uuid,
+    pub user_full_name: String,
+    pub user_nrp: String,
+    pub team_name: String,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+    pub schedule_type: ScheduleType,
+    pub title: Option<String>,
+    /// NEW | EXISTING | OVERLAP | LEAVE
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflict_schedule_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leave_type: Option<LeaveType>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GenerateDutyRotationData {
+    pub dry_run: bool,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub created: i64,
+    pub existing: i64,
+    pub overlaps: i64,
+    pub leave_conflicts: i64,
+    pub removed: i64,
+    pub removed_schedule_ids: Vec<Uuid>,
+    pub occurrences: Vec<RotationOccurrenceDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DutyRotationResp {
+    pub status: &'static str,
+    pub data: DutyRotationTemplateDto,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DutyRotationsResp {
+    pub status: &'static str,
+    pub data: Vec<DutyRotationTemplateDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GenerateDutyRotationResp {
+    pub status: &'static str,
+    pub data: GenerateDutyRotationData,
+}
+
+/// Definisi template yang sudah divalidasi (slot merujuk regu lewat index).
+#[derive(Debug, Clone)]
+pub struct RotationDefinition {
+    pub name: String,
+    pub cycle_days: i32,
+    pub anchor_date: NaiveDate,
+    pub is_active: bool,
+    pub teams: Vec<RotationTeamInput>,
+    pub slots: Vec<RotationSlotInput>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RotationTeamInput {
+    pub name: String,
+    pub member_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RotationSlotInput {
+    pub team_index: usize,
+    pub day_index: i32,
+    pub start_time: NaiveTime,
+    pub duration_minutes: i32,
+    pub schedule_type: ScheduleType,
+    pub title: Option<String>,
+}
+
+/// Jadwal yang akan dibuat dari hasil generate.
+#[derive(Debug, Clone)]
+pub struct RotationScheduleInsert {
+    pub user_id: Uuid,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+    pub schedule_type: ScheduleType,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ScheduleSpanRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+    pub rotation_template_id: Option<Uuid>,
+    /// User yang dijadwalkan template (bisa beda dengan user_id setelah tukar jadwal).
+    pub rotation_user_id: Option<Uuid>,
+}
+
+impl ScheduleSpanRow {
+    pub fn rotation_key(&self) -> (Uuid, DateTime<Utc>) {
+        (self.rotation_user_id.unwrap_or(self.user_id), self.start_at)
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct UserLeaveSpanRow {
+    pub user_id: Uuid,
+    pub tipe: LeaveType,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
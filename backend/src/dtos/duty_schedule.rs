@@ -42,15 +42,6 @@ pub struct UpdateDutyScheduleReq {
     pub note: Option<String>,
 }
 
-pub fn validate_list_query(from: DateTime<Utc>, to: DateTime<Utc>) -> Result<(), ValidationError> {
-    if to < from {
-        let mut error = ValidationError::new("invalid_range");
-        error.message = Some("Tanggal end tidak boleh lebih awal dari tanggal start".into());
-        return Err(error);
-    }
-    Ok(())
-}
-
 #[derive(Debug, Deserialize, Validate)]
 pub struct ListDutySchedulesQuery {
     pub from: Option<DateTime<Utc>>,
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Menunggu rekan menerima / menolak.
+pub const SWAP_PENDING_PARTNER: &str = "PENDING_PARTNER";
+/// Sudah diterima rekan, menunggu persetujuan admin/kepala satker.
+pub const SWAP_ACCEPTED: &str = "ACCEPTED";
+pub const SWAP_APPROVED: &str = "APPROVED";
+pub const SWAP_REJECTED: &str = "REJECTED";
+pub const SWAP_DECLINED: &str = "DECLINED";
+pub const SWAP_CANCELED: &str = "CANCELED";
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateDutyScheduleSwapReq {
+    /// Jadwal milik pemohon yang ingin ditukar / dioper.
+    pub schedule_id: Uuid,
+    pub counterpart_id: Uuid,
+    /// Jadwal milik rekan sebagai gantinya. Kosong = oper jadwal saja.
+    pub counterpart_schedule_id: Option<Uuid>,
+    #[validate(length(max = 500, message = "catatan maksimal 500 karakter"))]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListDutyScheduleSwapsQuery {
+    pub satker_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    /// default: semua status
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DutyScheduleSwapNoteReq {
+    #[validate(length(max = 500, message = "catatan maksimal 500 karakter"))]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RejectDutyScheduleSwapReq {
+    #[validate(length(min = 1, message = "catatan reject wajib diisi"))]
+    pub reject_reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DutyScheduleSwapEventDto {
+    pub id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DutyScheduleSwapDto {
+    pub id: Uuid,
+
+    pub satker_id: Uuid,
+    pub satker_code: String,
+    pub satker_name: String,
+
+    pub requester_id: Uuid,
+    pub requester_full_name: String,
+    pub requester_nrp: String,
+    pub requester_schedule_id: Uuid,
+    pub requester_start_at: DateTime<Utc>,
+    pub requester_end_at: DateTime<Utc>,
+
+    pub counterpart_id: Uuid,
+    pub counterpart_full_name: String,
+    pub counterpart_nrp: String,
+    pub counterpart_schedule_id: Option<Uuid>,
+    pub counterpart_start_at: Option<DateTime<Utc>>,
+    pub counterpart_end_at: Option<DateTime<Utc>>,
+
+    pub note: Option<String>,
+    pub status: String,
+    pub reject_reason: Option<String>,
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<DateTime<Utc>>,
+
+    pub history: Json<Vec<DutyScheduleSwapEventDto>>,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DutyScheduleSwapResp {
+    pub status: &'static str,
+    pub data: DutyScheduleSwapDto,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DutyScheduleSwapsResp {
+    pub status: &'static str,
+    pub data: Vec<DutyScheduleSwapDto>,
+}
+
+/// Data jadwal yang dibutuhkan untuk validasi tukar jadwal.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SwapScheduleRow {
+    pub id: Uuid,
+    pub satker_id: Uuid,
+    pub user_id: Uuid,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+}
+
+/// Baris pengajuan tukar yang dikunci (FOR UPDATE) saat transisi status.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SwapLockRow {
+    pub id: Uuid,
+    pub satker_id: Uuid,
+    pub requester_id: Uuid,
+    pub requester_schedule_id: Uuid,
+    pub requester_start_at: DateTime<Utc>,
+    pub requester_end_at: DateTime<Utc>,
+    pub counterpart_id: Uuid,
+    pub counterpart_schedule_id: Option<Uuid>,
+    pub counterpart_start_at: Option<DateTime<Utc>>,
+    pub counterpart_end_at: Option<DateTime<Utc>>,
+    pub status: String,
+}
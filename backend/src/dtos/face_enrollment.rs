@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Menunggu persetujuan admin satker.
+pub const FACE_ENROLLMENT_PENDING: &str = "PENDING";
+pub const FACE_ENROLLMENT_APPROVED: &str = "APPROVED";
+pub const FACE_ENROLLMENT_REJECTED: &str = "REJECTED";
+/// Dicabut admin; template tidak dipakai lagi untuk verifikasi.
+pub const FACE_ENROLLMENT_REVOKED: &str = "REVOKED";
+
+/// Hasil verifikasi wajah server di attendance_events.face_verification_status.
+pub const FACE_ACCEPTED: &str = "ACCEPTED";
+/// Diterima tapi skor di bawah `face_accept_at`, perlu ditinjau admin.
+pub const FACE_FLAGGED: &str = "FLAGGED";
+pub const FACE_NOT_ENROLLED: &str = "NOT_ENROLLED";
+pub const FACE_NO_SELFIE: &str = "NO_SELFIE";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct FaceEnrollmentDto {
+    pub id: Uuid,
+
+    pub satker_id: Uuid,
+    pub user_id: Uuid,
+    pub user_full_name: String,
+    pub user_nrp: String,
+
+    pub photo_object_key: String,
+    pub status: String,
+    pub verifier: Option<String>,
+
+    pub submitted_by: Option<Uuid>,
+    pub submitted_by_name: Option<String>,
+    pub reject_reason: Option<String>,
+    pub decided_by: Option<Uuid>,
+    pub decided_by_name: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FaceEnrollmentResp {
+    pub status: &'static str,
+    pub data: FaceEnrollmentDto,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FaceEnrollmentsResp {
+    pub status: &'static str,
+    pub data: Vec<FaceEnrollmentDto>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFaceEnrollmentQuery {
+    /// Admin/kepala: upload foto referensi untuk anggota; default diri sendiri.
+    pub user_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListFaceEnrollmentsQuery {
+    pub satker_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    /// default: semua status
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RejectFaceEnrollmentReq {
+    #[validate(length(
+        min = 1,
+        max = 500,
+        message = "alasan penolakan wajib (maks 500 karakter)"
+    ))]
+    pub reject_reason: String,
+}
+
+/// Baris yang dikunci (FOR UPDATE) saat enrolment diputus / dicabut.
+#[derive(Debug, Clone)]
+pub struct FaceEnrollmentLockRow {
+    pub satker_id: Uuid,
+    pub user_id: Uuid,
+    pub photo_object_key: String,
+    pub status: String,
+}
+
+/// Template aktif user untuk dicocokkan dengan selfie.
+#[derive(Debug, Clone)]
+pub struct FaceTemplateRow {
+    pub template: String,
+    pub verifier: Option<String>,
+}
+
+/// Hasil verifikasi wajah satu event check-in/out.
+#[derive(Debug, Clone, Serialize)]
+pub struct FaceCheckDto {
+    pub status: &'static str,
+    /// Skor terbaik terhadap semua foto referensi yang disetujui.
+    pub score: Option<f64>,
+}
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Access point Wi-Fi (BSSID).
+pub const MARKER_WIFI: &str = "WIFI";
+/// Beacon BLE (iBeacon "uuid:major:minor" / Eddystone "namespace:instance").
+pub const MARKER_BLE: &str = "BLE";
+
+/// Bukti lokasi event: GPS saja, atau penanda WIFI / BLE yang cocok.
+pub const LOCATION_PROOF_GPS: &str = "GPS";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct GeofenceMarkerDto {
+    pub id: Uuid,
+    pub geofence_id: Uuid,
+    pub kind: String,
+    pub identifier: String,
+    pub label: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GeofenceMarkersResp {
+    pub status: &'static str,
+    pub data: Vec<GeofenceMarkerDto>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateGeofenceMarkerReq {
+    /// WIFI | BLE
+    pub kind: String,
+    #[validate(length(min = 1, max = 100, message = "identifier wajib (maks 100 karakter)"))]
+    pub identifier: String,
+    #[validate(length(max = 100, message = "label maks 100 karakter"))]
+    pub label: Option<String>,
+}
+
+/// Penanda satker yang cocok dengan hasil scan, beserta geofence-nya.
+#[derive(Debug, Clone)]
+pub struct ProximityMarkerMatch {
+    pub id: Uuid,
+    pub kind: String,
+    pub identifier: String,
+    pub geofence_id: Uuid,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_meters: i32,
+}
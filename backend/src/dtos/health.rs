@@ -0,0 +1,41 @@
+use serde::Serialize;
+
+pub const COMPONENT_OK: &str = "ok";
+pub const COMPONENT_ERROR: &str = "error";
+
+#[derive(Debug, Serialize)]
+pub struct ComponentStatus {
+    pub status: &'static str,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrationStatus {
+    pub status: &'static str,
+    pub applied_version: Option<i64>,
+    pub expected_version: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessChecks {
+    pub database: ComponentStatus,
+    pub migrations: MigrationStatus,
+    pub redis: ComponentStatus,
+    /// Backend storage upload utama (UPLOAD_DIR bisa ditulisi / bucket S3 bisa diakses).
+    pub storage: ComponentStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessResp {
+    pub status: &'static str, // "ok" | "degraded"
+    pub checks: ReadinessChecks,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LivenessResp {
+    pub status: &'static str,
+}
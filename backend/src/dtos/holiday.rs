@@ -1,5 +1,5 @@
 use crate::constants::{HolidayKind, HolidayScope};
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveTime};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -10,8 +10,9 @@ pub struct BulkHolidayReq {
     pub items: Vec<BulkHolidayItem>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct BulkHolidayItem {
+    #[serde(alias = "date")]
     pub holiday_date: chrono::NaiveDate,
     pub name: String,
     #[serde(default)]
@@ -81,3 +82,63 @@ pub struct DeleteHolidayResp {
     pub status: String,
     pub data: String,
 }
+
+/// Belum ada libur di tanggal tsb (scope yang sama).
+pub const IMPORT_NEW: &str = "NEW";
+/// Sudah ada, nama/kind/half_day_end berbeda (akan di-update).
+pub const IMPORT_CHANGED: &str = "CHANGED";
+pub const IMPORT_UNCHANGED: &str = "UNCHANGED";
+/// Tidak diterapkan: tanggal ganda di file dengan isi berbeda, atau data lama ganda.
+pub const IMPORT_CONFLICT: &str = "CONFLICT";
+
+/// Field multipart selain `file` pada POST /holidays/import.
+#[derive(Debug)]
+pub struct HolidayImportOptions {
+    pub scope: HolidayScope,
+    pub satker_id: Option<Uuid>,
+    /// true = hanya preview diff, tidak menyimpan apa pun.
+    pub dry_run: bool,
+    /// true = generate ulang satker_calendar_days di range tanggal yang berubah.
+    pub regenerate_calendar: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HolidayImportItemDto {
+    pub holiday_date: NaiveDate,
+    pub name: String,
+    pub kind: HolidayKind,
+    pub half_day_end: Option<NaiveTime>,
+    pub action: &'static str,
+    pub existing_name: Option<String>,
+    pub existing_kind: Option<HolidayKind>,
+    pub existing_half_day_end: Option<NaiveTime>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalendarRegenerateDto {
+    pub satker_id: Uuid,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub generated: i64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HolidayImportData {
+    pub dry_run: bool,
+    pub scope: HolidayScope,
+    pub satker_id: Option<Uuid>,
+    pub created: i64,
+    pub changed: i64,
+    pub unchanged: i64,
+    pub conflicts: i64,
+    pub items: Vec<HolidayImportItemDto>,
+    pub calendar: Vec<CalendarRegenerateDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HolidayImportResp {
+    pub status: String,
+    pub data: HolidayImportData,
+}
@@ -0,0 +1,33 @@
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+}
+
+/// Status schema database dibanding migration yang di-embed di binary.
+#[derive(Debug, Serialize)]
+pub struct MigrationReport {
+    pub expected_version: Option<i64>,
+    pub applied_version: Option<i64>,
+    /// `false` kalau tabel `_sqlx_migrations` belum ada.
+    pub tracked: bool,
+    /// Schema sudah ada tapi belum tercatat (perlu `migrate baseline`).
+    pub untracked_schema: bool,
+    pub pending: Vec<MigrationInfo>,
+    /// Sudah diterapkan tapi isi file SQL-nya berubah (checksum beda).
+    pub modified: Vec<i64>,
+    /// Tercatat gagal (dirty), perlu diperbaiki manual.
+    pub failed: Vec<i64>,
+    /// Ada di database tapi tidak dikenal binary ini (binary lebih lama).
+    pub unknown: Vec<i64>,
+    pub up_to_date: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BaselineResult {
+    pub version: i64,
+    pub recorded: Vec<i64>,
+    pub already_recorded: Vec<i64>,
+}
@@ -0,0 +1,261 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Lembur setelah jam pulang di hari kerja (termasuk hari setengah).
+pub const OVERTIME_WEEKDAY: &str = "WEEKDAY";
+/// Hari libur rutin dari work pattern (Sabtu/Minggu).
+pub const OVERTIME_WEEKEND: &str = "WEEKEND";
+/// Hari libur nasional / satker dari tabel holidays.
+pub const OVERTIME_HOLIDAY: &str = "HOLIDAY";
+pub const OVERTIME_DAY_KINDS: [&str; 3] = [OVERTIME_WEEKDAY, OVERTIME_WEEKEND, OVERTIME_HOLIDAY];
+
+/// Menunggu keputusan kepala satker.
+pub const OVERTIME_SUBMITTED: &str = "SUBMITTED";
+pub const OVERTIME_APPROVED: &str = "APPROVED";
+pub const OVERTIME_REJECTED: &str = "REJECTED";
+pub const OVERTIME_CANCELED: &str = "CANCELED";
+
+#[derive(Debug, Deserialize)]
+pub struct OvertimeCandidatesQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// default: diri sendiri
+    pub user_id: Option<Uuid>,
+}
+
+/// Kandidat lembur satu hari, hasil hitung dari attendance session & kalender satker.
+#[derive(Debug, Clone, Serialize)]
+pub struct OvertimeCandidateDto {
+    pub work_date: NaiveDate,
+    pub day_kind: &'static str,
+    pub session_id: Uuid,
+    pub duty_schedule_id: Option<Uuid>,
+    /// Batas mulai lembur (jam pulang / akhir jadwal dinas); None = seluruh jam kerja dihitung.
+    pub threshold_at: Option<DateTime<Utc>>,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+    pub minutes: i64,
+    /// Klaim aktif (SUBMITTED/APPROVED) untuk hari ini, kalau sudah diajukan.
+    pub claim_id: Option<Uuid>,
+    pub claim_status: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OvertimeCandidatesResp {
+    pub status: &'static str,
+    pub data: Vec<OvertimeCandidateDto>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateOvertimeClaimReq {
+    pub work_date: NaiveDate,
+    /// default: seluruh menit kandidat
+    #[validate(range(min = 1, message = "minutes minimal 1"))]
+    pub minutes: Option<i32>,
+    #[validate(length(max = 500, message = "catatan maksimal 500 karakter"))]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListOvertimeClaimsQuery {
+    pub satker_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    /// default: semua status
+    pub status: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ApproveOvertimeClaimReq {
+    /// default: seluruh menit yang diklaim
+    #[validate(range(min = 1, message = "approved_minutes minimal 1"))]
+    pub approved_minutes: Option<i32>,
+    #[validate(length(max = 500, message = "catatan maksimal 500 karakter"))]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RejectOvertimeClaimReq {
+    #[validate(length(min = 1, message = "catatan reject wajib diisi"))]
+    pub reject_reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OvertimeClaimEventDto {
+    pub id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OvertimeClaimDto {
+    pub id: Uuid,
+
+    pub satker_id: Uuid,
+    pub satker_code: String,
+    pub satker_name: String,
+
+    pub user_id: Uuid,
+    pub user_full_name: String,
+    pub user_nrp: String,
+
+    pub session_id: Option<Uuid>,
+    pub duty_schedule_id: Option<Uuid>,
+    pub work_date: NaiveDate,
+    pub day_kind: String,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+    pub candidate_minutes: i32,
+    pub claimed_minutes: i32,
+    pub approved_minutes: Option<i32>,
+
+    pub note: Option<String>,
+    pub status: String,
+    pub reject_reason: Option<String>,
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<DateTime<Utc>>,
+
+    pub history: Json<Vec<OvertimeClaimEventDto>>,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OvertimeClaimResp {
+    pub status: &'static str,
+    pub data: OvertimeClaimDto,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OvertimeClaimsResp {
+    pub status: &'static str,
+    pub data: Vec<OvertimeClaimDto>,
+}
+
+/// Baris klaim lembur yang dikunci (FOR UPDATE) saat transisi status.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OvertimeClaimLockRow {
+    pub id: Uuid,
+    pub satker_id: Uuid,
+    pub user_id: Uuid,
+    pub claimed_minutes: i32,
+    pub status: String,
+}
+
+/// Snapshot kandidat yang disimpan saat klaim diajukan.
+#[derive(Debug, Clone)]
+pub struct OvertimeClaimInsert {
+    pub satker_id: Uuid,
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+    pub duty_schedule_id: Option<Uuid>,
+    pub work_date: NaiveDate,
+    pub day_kind: &'static str,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+    pub candidate_minutes: i32,
+    pub claimed_minutes: i32,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct OvertimeRateDto {
+    pub id: Uuid,
+    /// None = tarif default untuk pangkat yang tidak punya tarif sendiri.
+    pub rank_id: Option<Uuid>,
+    pub rank_code: Option<String>,
+    pub rank_name: Option<String>,
+    pub day_kind: String,
+    pub hourly_rate: i64,
+    pub max_minutes_per_day: Option<i32>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OvertimeRateInput {
+    pub rank_id: Option<Uuid>,
+    pub day_kind: String,
+    pub hourly_rate: i64,
+    pub max_minutes_per_day: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplaceOvertimeRatesReq {
+    pub rates: Vec<OvertimeRateInput>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OvertimeRatesResp {
+    pub status: &'static str,
+    pub data: Vec<OvertimeRateDto>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OvertimeReportQuery {
+    /// YYYY-MM
+    pub month: String,
+    pub satker_id: Option<Uuid>,
+}
+
+/// Klaim lembur APPROVED untuk rekap bulanan.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ApprovedOvertimeRow {
+    pub user_id: Uuid,
+    pub full_name: String,
+    pub nrp: String,
+    pub rank_id: Option<Uuid>,
+    pub rank_code: Option<String>,
+    pub rank_name: Option<String>,
+    pub day_kind: String,
+    pub approved_minutes: i32,
+}
+
+/// Total satu jenis hari untuk satu user.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OvertimeKindTotalDto {
+    pub days: i32,
+    /// menit yang disetujui
+    pub minutes: i64,
+    /// menit yang dibayar (setelah batas per hari)
+    pub paid_minutes: i64,
+    pub hourly_rate: i64,
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OvertimeReportRowDto {
+    pub user_id: Uuid,
+    pub full_name: String,
+    pub nrp: String,
+    pub rank_code: Option<String>,
+    pub rank_name: Option<String>,
+    pub weekday: OvertimeKindTotalDto,
+    pub weekend: OvertimeKindTotalDto,
+    pub holiday: OvertimeKindTotalDto,
+    pub total_minutes: i64,
+    pub total_amount: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OvertimeReportDto {
+    pub month: String,
+    pub satker_id: Uuid,
+    pub rows: Vec<OvertimeReportRowDto>,
+    pub total_amount: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OvertimeReportResp {
+    pub status: &'static str,
+    pub data: OvertimeReportDto,
+}
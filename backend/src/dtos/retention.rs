@@ -0,0 +1,213 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::dtos::admin::SelfiePurgeReport;
+
+pub const RETENTION_RUN_SCHEDULED: &str = "SCHEDULED";
+pub const RETENTION_RUN_MANUAL: &str = "MANUAL";
+
+/// Batas periode retensi (hari) yang boleh diset admin.
+pub const MIN_RETENTION_DAYS: i64 = 30;
+pub const MAX_RETENTION_DAYS: i64 = 3650;
+
+/// Kelas objek upload yang punya periode retensi sendiri.
+///
+/// Belum ada kelas untuk lampiran cuti: `leave_requests` belum menyimpan
+/// lampiran apa pun, jadi tidak ada objek yang perlu dihapus. Tambahkan kelas
+/// baru di sini begitu upload lampiran cuti tersedia.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionClass {
+    /// Selfie absensi; umur dihitung dari waktu event.
+    Selfie,
+    /// Foto profil user nonaktif; umur dihitung sejak user dinonaktifkan.
+    ProfilePhoto,
+}
+
+/// Disimpan di app_settings (`retention_policy`). Field kosong = objek disimpan selamanya.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionPolicy {
+    #[validate(custom(function = "validate_retention_days"))]
+    pub selfie_days: Option<i64>,
+    #[validate(custom(function = "validate_retention_days"))]
+    pub profile_photo_days: Option<i64>,
+}
+
+impl RetentionPolicy {
+    pub fn days(&self, class: RetentionClass) -> Option<i64> {
+        match class {
+            RetentionClass::Selfie => self.selfie_days,
+            RetentionClass::ProfilePhoto => self.profile_photo_days,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selfie_days.is_none() && self.profile_photo_days.is_none()
+    }
+}
+
+fn validate_retention_days(days: i64) -> Result<(), ValidationError> {
+    if !(MIN_RETENTION_DAYS..=MAX_RETENTION_DAYS).contains(&days) {
+        let mut error = ValidationError::new("range");
+        error.message = Some(
+            format!(
+                "periode retensi harus {} - {} hari",
+                MIN_RETENTION_DAYS, MAX_RETENTION_DAYS
+            )
+            .into(),
+        );
+        return Err(error);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionPolicyResp {
+    pub status: &'static str,
+    pub data: RetentionPolicy,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ProfilePhotoPurgeReport {
+    pub before: DateTime<Utc>,
+    pub dry_run: bool,
+    pub users_cleared: usize,
+    pub files_deleted: usize,
+    pub files_missing: usize,
+    /// User nonaktif yang fotonya sudah lewat retensi tapi ditahan legal hold.
+    pub users_held: i64,
+    pub errors: Vec<String>,
+}
+
+/// Hasil satu putaran retensi; kelas tanpa periode retensi bernilai null.
+#[derive(Debug, Default, Serialize)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub selfies: Option<SelfiePurgeReport>,
+    pub profile_photos: Option<ProfilePhotoPurgeReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionReportResp {
+    pub status: &'static str,
+    pub data: RetentionReport,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunRetentionQuery {
+    /// default: true (hanya hitung)
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct RetentionRunDto {
+    pub id: Uuid,
+    pub kind: String,
+    pub requested_by: Option<Uuid>,
+    pub dry_run: bool,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub report: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionRunsResp {
+    pub status: &'static str,
+    pub data: Vec<RetentionRunDto>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListRetentionRunsQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct LegalHoldDto {
+    pub id: Uuid,
+    pub satker_id: Uuid,
+    pub user_id: Uuid,
+    pub user_full_name: String,
+    pub user_nrp: String,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub reason: String,
+    pub created_by: Uuid,
+    pub created_by_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+    pub released_by: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LegalHoldResp {
+    pub status: &'static str,
+    pub data: LegalHoldDto,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LegalHoldsResp {
+    pub status: &'static str,
+    pub data: Vec<LegalHoldDto>,
+}
+
+fn validate_legal_hold_range(req: &CreateLegalHoldReq) -> Result<(), ValidationError> {
+    if let (Some(from), Some(to)) = (req.from_date, req.to_date)
+        && to < from
+    {
+        let mut error = ValidationError::new("invalid_range");
+        error.message = Some("to_date tidak boleh lebih awal dari from_date".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_legal_hold_range"))]
+pub struct CreateLegalHoldReq {
+    pub user_id: Uuid,
+    /// Rentang tanggal kerja yang ditahan; kosong = semua data user.
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    #[validate(length(min = 3, max = 1000, message = "alasan 3-1000 karakter"))]
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListLegalHoldsQuery {
+    pub satker_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    /// default: true (hanya yang belum dilepas)
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct StorageUsageRow {
+    pub satker_id: Uuid,
+    pub satker_code: String,
+    pub satker_name: String,
+    /// Hari pertama bulan (zona waktu aplikasi).
+    pub month: NaiveDate,
+    pub object_class: String,
+    pub object_count: i64,
+    /// Total ukuran file yang tercatat; objek lama tidak punya catatan ukuran.
+    pub total_bytes: i64,
+    pub unknown_size_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StorageUsageResp {
+    pub status: &'static str,
+    pub data: Vec<StorageUsageRow>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StorageUsageQuery {
+    pub satker_id: Option<Uuid>,
+    /// Rentang bulan (tanggal mana pun di bulan tsb, inklusif).
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
@@ -1,4 +1,6 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Debug, Serialize)]
 pub struct TimezoneData {
@@ -16,3 +18,265 @@ pub struct TimezoneResp {
 pub struct UpdateTimezoneReq {
     pub timezone: String,
 }
+
+/// Aturan check-in/out efektif untuk satu satker (default kode < global < satker).
+#[derive(Debug, Clone, Serialize)]
+pub struct AttendanceRules {
+    /// Boleh check-in paling cepat N jam sebelum jam masuk / mulai dinas; juga window apel.
+    pub early_checkin_hours: i64,
+    /// Dinas: check-in masih diterima (dan sesi kemarin masih dianggap aktif) N jam setelah selesai.
+    pub duty_grace_hours: i64,
+    /// Dinas: check-out paling lambat N jam setelah jadwal selesai.
+    pub duty_max_checkout_hours: i64,
+    /// Attendance card: dinas tetap ditampilkan N menit setelah selesai.
+    pub duty_card_grace_minutes: i64,
+    /// Akurasi GPS terburuk yang diterima (meter).
+    pub max_accuracy_meters: f64,
+    /// Teleport: pindah lebih dari `teleport_distance_m` dalam `teleport_window_secs` ditolak.
+    pub teleport_distance_m: f64,
+    pub teleport_window_secs: i64,
+    /// Kecepatan perpindahan maksimal antar event (m/s).
+    pub max_speed_mps: f64,
+    /// Umur challenge check-in/out (detik).
+    pub challenge_ttl_secs: i64,
+    /// Batas request challenge per menit per user / per device.
+    pub challenge_user_limit_per_min: i64,
+    pub challenge_device_limit_per_min: i64,
+    /// Maksimal device ter-bind per user (device baru di atas batas harus mengganti device lama).
+    pub max_devices_per_user: i64,
+    /// Verifikasi wajah server: tolak absen kalau user belum enrol / tidak kirim selfie.
+    pub face_required: bool,
+    /// Skor wajah di bawah ini ditolak; di antara ini dan `face_accept_at` diterima tapi ditandai.
+    pub face_reject_below: f64,
+    pub face_accept_at: f64,
+    /// Anomali: skor wajah dari aplikasi di bawah ini ditandai untuk ditinjau.
+    pub anomaly_face_score_below: f64,
+    /// Anomali: device dipakai absen user lain dalam N hari terakhir (0 = nonaktif).
+    pub anomaly_device_share_days: i64,
+    /// Anomali: selfie sama (perceptual hash) sudah dipakai dalam N hari terakhir (0 = nonaktif).
+    pub anomaly_selfie_reuse_days: i64,
+    /// Anomali: koordinat persis sama dengan N hari absen sebelumnya (0 = nonaktif).
+    pub anomaly_same_coords_days: i64,
+    /// Scan Wi-Fi/BLE yang cocok dengan penanda geofence menggantikan GPS (lolos cek akurasi &
+    /// geofence). `false`: hanya melengkapi GPS (lolos cek akurasi, posisi tetap harus di dalam).
+    pub proximity_substitutes_gps: bool,
+    /// Sinyal Wi-Fi/BLE lebih lemah dari ini (dBm) diabaikan.
+    pub proximity_min_rssi: i32,
+    /// Tolak request check-in/out tanpa attestation (signature kunci device) dari device yang
+    /// belum punya kunci terdaftar; device berkunci selalu wajib mengirim attestation.
+    pub attestation_required: bool,
+}
+
+impl Default for AttendanceRules {
+    fn default() -> Self {
+        Self {
+            early_checkin_hours: 2,
+            duty_grace_hours: 6,
+            duty_max_checkout_hours: 24,
+            duty_card_grace_minutes: 30,
+            max_accuracy_meters: 50.0,
+            teleport_distance_m: 5000.0,
+            teleport_window_secs: 120,
+            max_speed_mps: 45.0,
+            challenge_ttl_secs: 60,
+            challenge_user_limit_per_min: 10,
+            challenge_device_limit_per_min: 6,
+            max_devices_per_user: 2,
+            face_required: false,
+            face_reject_below: 0.5,
+            face_accept_at: 0.8,
+            anomaly_face_score_below: 0.6,
+            anomaly_device_share_days: 30,
+            anomaly_selfie_reuse_days: 90,
+            anomaly_same_coords_days: 3,
+            proximity_substitutes_gps: false,
+            proximity_min_rssi: -85,
+            attestation_required: false,
+        }
+    }
+}
+
+/// Aturan yang disimpan di app_settings (global / per satker). Field kosong = ikut level di atasnya.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AttendanceRulesOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub early_checkin_hours: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duty_grace_hours: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duty_max_checkout_hours: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duty_card_grace_minutes: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_accuracy_meters: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub teleport_distance_m: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub teleport_window_secs: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_speed_mps: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenge_ttl_secs: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenge_user_limit_per_min: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenge_device_limit_per_min: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_devices_per_user: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub face_required: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub face_reject_below: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub face_accept_at: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anomaly_face_score_below: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anomaly_device_share_days: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anomaly_selfie_reuse_days: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anomaly_same_coords_days: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proximity_substitutes_gps: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proximity_min_rssi: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attestation_required: Option<bool>,
+}
+
+impl AttendanceRules {
+    pub fn apply(mut self, o: &AttendanceRulesOverride) -> Self {
+        if let Some(v) = o.early_checkin_hours {
+            self.early_checkin_hours = v;
+        }
+        if let Some(v) = o.duty_grace_hours {
+            self.duty_grace_hours = v;
+        }
+        if let Some(v) = o.duty_max_checkout_hours {
+            self.duty_max_checkout_hours = v;
+        }
+        if let Some(v) = o.duty_card_grace_minutes {
+            self.duty_card_grace_minutes = v;
+        }
+        if let Some(v) = o.max_accuracy_meters {
+            self.max_accuracy_meters = v;
+        }
+        if let Some(v) = o.teleport_distance_m {
+            self.teleport_distance_m = v;
+        }
+        if let Some(v) = o.teleport_window_secs {
+            self.teleport_window_secs = v;
+        }
+        if let Some(v) = o.max_speed_mps {
+            self.max_speed_mps = v;
+        }
+        if let Some(v) = o.challenge_ttl_secs {
+            self.challenge_ttl_secs = v;
+        }
+        if let Some(v) = o.challenge_user_limit_per_min {
+            self.challenge_user_limit_per_min = v;
+        }
+        if let Some(v) = o.challenge_device_limit_per_min {
+            self.challenge_device_limit_per_min = v;
+        }
+        if let Some(v) = o.max_devices_per_user {
+            self.max_devices_per_user = v;
+        }
+        if let Some(v) = o.face_required {
+            self.face_required = v;
+        }
+        if let Some(v) = o.face_reject_below {
+            self.face_reject_below = v;
+        }
+        if let Some(v) = o.face_accept_at {
+            self.face_accept_at = v;
+        }
+        if let Some(v) = o.anomaly_face_score_below {
+            self.anomaly_face_score_below = v;
+        }
+        if let Some(v) = o.anomaly_device_share_days {
+            self.anomaly_device_share_days = v;
+        }
+        if let Some(v) = o.anomaly_selfie_reuse_days {
+            self.anomaly_selfie_reuse_days = v;
+        }
+        if let Some(v) = o.anomaly_same_coords_days {
+            self.anomaly_same_coords_days = v;
+        }
+        if let Some(v) = o.proximity_substitutes_gps {
+            self.proximity_substitutes_gps = v;
+        }
+        if let Some(v) = o.proximity_min_rssi {
+            self.proximity_min_rssi = v;
+        }
+        if let Some(v) = o.attestation_required {
+            self.attestation_required = v;
+        }
+        self
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AttendanceRulesQuery {
+    /// Default: satker user yang login.
+    pub satker_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttendanceRulesDto {
+    pub satker_id: Uuid,
+    /// Nilai bawaan aplikasi.
+    pub defaults: AttendanceRules,
+    /// Yang diset superadmin untuk semua satker.
+    pub global: AttendanceRulesOverride,
+    /// Khusus satker ini (None = ikut global).
+    pub satker: Option<AttendanceRulesOverride>,
+    pub effective: AttendanceRules,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttendanceRulesResp {
+    pub status: &'static str,
+    pub data: AttendanceRulesDto,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SettingAuditQuery {
+    /// Kosong = semua perubahan aturan absensi (global & satker).
+    pub satker_id: Option<Uuid>,
+    /// Default 100, maksimal 500.
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SettingAuditRow {
+    pub id: Uuid,
+    pub key: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_by: Option<Uuid>,
+    pub changed_by_name: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SettingAuditDto {
+    pub id: Uuid,
+    pub key: String,
+    /// None = global.
+    pub satker_id: Option<Uuid>,
+    /// None = belum ada / dihapus.
+    pub old_value: Option<serde_json::Value>,
+    pub new_value: Option<serde_json::Value>,
+    pub changed_by: Option<Uuid>,
+    pub changed_by_name: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SettingAuditsResp {
+    pub status: &'static str,
+    pub data: Vec<SettingAuditDto>,
+}
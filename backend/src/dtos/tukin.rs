@@ -4,6 +4,7 @@ use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
 use crate::constants::LeaveType;
+use crate::dtos::attendance_session::AttendanceSegmentDto;
 
 #[derive(Debug, Deserialize)]
 pub struct TukinPreviewQuery {
@@ -112,6 +113,15 @@ pub struct TukinDayBreakdownDto {
     pub check_in_at: Option<DateTime<Utc>>,
     pub check_out_at: Option<DateTime<Utc>>,
     pub late_minutes: Option<i64>,
+    /// Mode FLEXI: kekurangan dari durasi kerja wajib (butuh check-in & check-out).
+    pub short_minutes: Option<i64>,
+    /// Total menit kerja dari segmen check-in/out yang sudah ditutup.
+    pub worked_minutes: Option<i64>,
+    pub segments: Vec<AttendanceSegmentDto>,
+    /// Total menit istirahat (BREAK_START..BREAK_END) hari itu.
+    pub break_minutes: Option<i64>,
+    /// Kelebihan dari batas istirahat work pattern; ikut dihitung ke `late_minutes`.
+    pub break_excess_minutes: Option<i64>,
 
     pub leave_type: Option<LeaveType>,
     pub leave_credit: Option<f64>,
@@ -142,6 +152,8 @@ pub struct TukinUserSummaryDto {
     pub duty_absent: i32,
 
     pub total_late_minutes: i64,
+    pub total_short_minutes: i64,
+    pub total_break_excess_minutes: i64,
 
     pub days: Vec<TukinDayBreakdownDto>,
 }
@@ -1,8 +1,14 @@
+use chrono::{DateTime, Utc};
 use serde::Serialize;
+use uuid::Uuid;
 
 #[derive(Debug, Serialize)]
 pub struct UploadSelfieData {
     pub selfie_object_key: String,
+    pub width: u32,
+    pub height: u32,
+    /// Waktu ambil foto dari EXIF (metadata lain sudah dibuang).
+    pub captured_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -10,3 +16,55 @@ pub struct UploadSelfieResp {
     pub status: &'static str,
     pub data: UploadSelfieData,
 }
+
+pub struct NewSelfieUpload<'a> {
+    pub object_key: &'a str,
+    pub user_id: Uuid,
+    pub width: i32,
+    pub height: i32,
+    pub size_bytes: i64,
+    pub phash: &'a str,
+    pub captured_at: Option<DateTime<Utc>>,
+}
+
+/// Metadata selfie hasil olah upload, dipakai saat check-in/out.
+#[derive(Debug, Clone)]
+pub struct SelfieUploadMeta {
+    pub phash: String,
+    pub captured_at: Option<DateTime<Utc>>,
+}
+
+/// Kolom DB yang menyimpan key objek upload (sumber referensi untuk migrasi storage).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectRefSource {
+    /// attendance_events.selfie_object_key
+    AttendanceSelfie,
+    /// users.profile_photo_key
+    ProfilePhoto,
+    /// face_enrollments.photo_object_key
+    FacePhoto,
+    /// announcement_attachments.object_key
+    AnnouncementAttachment,
+    /// selfie_uploads.object_key
+    SelfieUpload,
+}
+
+impl ObjectRefSource {
+    pub const ALL: [ObjectRefSource; 5] = [
+        ObjectRefSource::AttendanceSelfie,
+        ObjectRefSource::ProfilePhoto,
+        ObjectRefSource::FacePhoto,
+        ObjectRefSource::AnnouncementAttachment,
+        ObjectRefSource::SelfieUpload,
+    ];
+}
+
+/// Satu baris yang mereferensikan objek upload.
+#[derive(Debug, Clone)]
+pub struct StoredObjectRef {
+    pub id: Uuid,
+    pub object_key: String,
+    /// Content-type tersimpan (lampiran); lainnya ditebak dari ekstensi.
+    pub content_type: Option<String>,
+}
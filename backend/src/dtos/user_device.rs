@@ -0,0 +1,169 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Menunggu keputusan admin satker.
+pub const DEVICE_REQUEST_PENDING: &str = "PENDING";
+pub const DEVICE_REQUEST_APPROVED: &str = "APPROVED";
+pub const DEVICE_REQUEST_REJECTED: &str = "REJECTED";
+pub const DEVICE_REQUEST_CANCELED: &str = "CANCELED";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct UserDeviceDto {
+    pub id: Uuid,
+    pub device_id: String,
+
+    pub user_id: Uuid,
+    pub user_full_name: String,
+    pub user_nrp: String,
+    pub satker_id: Uuid,
+
+    pub device_model: Option<String>,
+    pub android_version: Option<String>,
+    pub app_build: Option<String>,
+    pub client_version: Option<String>,
+
+    pub bound_at: DateTime<Utc>,
+    /// None = bind otomatis saat check-in pertama
+    pub bound_by: Option<Uuid>,
+    pub bound_by_name: Option<String>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    /// Kapan kunci attestation device didaftarkan; None = belum ada.
+    pub public_key_registered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserDeviceResp {
+    pub status: &'static str,
+    pub data: UserDeviceDto,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserDevicesResp {
+    pub status: &'static str,
+    pub data: Vec<UserDeviceDto>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListUserDevicesQuery {
+    /// superadmin: filter satker; admin/kepala: selalu satker sendiri
+    pub satker_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransferUserDeviceReq {
+    /// user tujuan (harus satu satker dengan pemilik sekarang)
+    pub user_id: Uuid,
+}
+
+/// Data device dari aplikasi, dipakai saat bind / validasi kepemilikan.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceInfo {
+    pub device_id: String,
+    pub device_model: Option<String>,
+    pub android_version: Option<String>,
+    pub app_build: Option<String>,
+    pub client_version: Option<String>,
+    /// Kunci publik attestation (base64 SPKI DER, EC P-256).
+    pub public_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateDeviceBindingReq {
+    #[validate(length(min = 1, max = 200, message = "device_id wajib (maks 200 karakter)"))]
+    pub device_id: String,
+    pub device_model: Option<String>,
+    pub android_version: Option<String>,
+    pub app_build: Option<String>,
+    pub client_version: Option<String>,
+    /// Kunci publik attestation device (base64 SPKI DER, EC P-256).
+    #[validate(length(max = 1000, message = "public_key maksimal 1000 karakter"))]
+    pub public_key: Option<String>,
+    #[validate(length(max = 500, message = "alasan maksimal 500 karakter"))]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListDeviceBindingQuery {
+    pub satker_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    /// default: semua status
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ApproveDeviceBindingReq {
+    /// Device lama milik user yang dilepas; wajib kalau user sudah mencapai batas device.
+    #[validate(length(min = 1, max = 200))]
+    pub replace_device_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RejectDeviceBindingReq {
+    #[validate(length(
+        min = 1,
+        max = 500,
+        message = "alasan penolakan wajib (maks 500 karakter)"
+    ))]
+    pub reject_reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DeviceBindingRequestDto {
+    pub id: Uuid,
+
+    pub satker_id: Uuid,
+    pub user_id: Uuid,
+    pub user_full_name: String,
+    pub user_nrp: String,
+
+    pub device_id: String,
+    pub device_model: Option<String>,
+    pub android_version: Option<String>,
+    pub app_build: Option<String>,
+    pub client_version: Option<String>,
+    pub reason: Option<String>,
+
+    /// Pemilik device saat permintaan dibuat (kalau device dipakai akun lain).
+    pub previous_user_id: Option<Uuid>,
+    pub previous_user_name: Option<String>,
+    pub replaced_device_id: Option<String>,
+
+    pub status: String,
+    pub reject_reason: Option<String>,
+    pub decided_by: Option<Uuid>,
+    pub decided_by_name: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeviceBindingRequestResp {
+    pub status: &'static str,
+    pub data: DeviceBindingRequestDto,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeviceBindingRequestsResp {
+    pub status: &'static str,
+    pub data: Vec<DeviceBindingRequestDto>,
+}
+
+/// Baris yang dikunci (FOR UPDATE) saat permintaan diputus.
+#[derive(Debug, Clone)]
+pub struct DeviceBindingLockRow {
+    pub id: Uuid,
+    pub satker_id: Uuid,
+    pub user_id: Uuid,
+    pub device_id: String,
+    pub device_model: Option<String>,
+    pub android_version: Option<String>,
+    pub app_build: Option<String>,
+    pub client_version: Option<String>,
+    pub public_key: Option<String>,
+    pub status: String,
+}
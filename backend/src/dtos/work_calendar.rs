@@ -1,5 +1,7 @@
 use crate::models::SatkerCalendarDay;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Debug, Deserialize)]
 pub struct GenerateCalendarQuery {
@@ -29,3 +31,41 @@ pub struct ListCalendarResp {
     pub status: String,
     pub data: Vec<SatkerCalendarDay>,
 }
+
+pub const REGEN_PENDING: &str = "PENDING";
+pub const REGEN_RUNNING: &str = "RUNNING";
+pub const REGEN_DONE: &str = "DONE";
+pub const REGEN_FAILED: &str = "FAILED";
+
+/// Job generate ulang kalender di background (antrian `calendar_regen_jobs`).
+#[derive(Debug, Serialize)]
+pub struct CalendarRegenJobDto {
+    pub id: Uuid,
+    pub satker_id: Uuid,
+    pub from_date: NaiveDate,
+    /// NULL = sampai akhir kalender yang sudah ter-generate.
+    pub to_date: Option<NaiveDate>,
+    pub reason: String,
+    pub status: String,
+    pub attempts: i32,
+    pub generated: Option<i64>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalendarRegenJobsResp {
+    pub status: String,
+    pub data: Vec<CalendarRegenJobDto>,
+}
+
+/// Job yang sudah di-claim worker (range sudah digabung dengan job pending lain).
+#[derive(Debug)]
+pub struct ClaimedCalendarRegenJob {
+    pub id: Uuid,
+    pub satker_id: Uuid,
+    pub from_date: NaiveDate,
+    pub to_date: Option<NaiveDate>,
+}
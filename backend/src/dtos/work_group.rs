@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkGroupMemberDto {
+    pub user_id: Uuid,
+    pub full_name: String,
+    pub nrp: String,
+}
+
+/// Kelompok user dalam satu satker yang berbagi jam kerja (mis. "Tenaga Medis").
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkGroupDto {
+    pub id: Uuid,
+    pub satker_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub members: Json<Vec<WorkGroupMemberDto>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpsertWorkGroupReq {
+    /// Hanya dipakai saat create oleh SUPERADMIN; role lain selalu satker sendiri.
+    pub satker_id: Option<Uuid>,
+    #[validate(length(min = 1, max = 100, message = "name: 1..100 karakter"))]
+    pub name: String,
+    #[validate(length(max = 500, message = "description: maksimal 500 karakter"))]
+    pub description: Option<String>,
+    /// Daftar anggota lengkap (menggantikan anggota sebelumnya).
+    #[serde(default)]
+    pub member_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListWorkGroupsQuery {
+    pub satker_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkGroupResp {
+    pub status: &'static str,
+    pub data: WorkGroupDto,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkGroupsResp {
+    pub status: &'static str,
+    pub data: Vec<WorkGroupDto>,
+}
@@ -0,0 +1,165 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::constants::WorkPatternMode;
+
+/// Asal jam kerja efektif user.
+pub const PATTERN_SOURCE_SATKER: &str = "SATKER";
+pub const PATTERN_SOURCE_GROUP: &str = "GROUP";
+pub const PATTERN_SOURCE_USER: &str = "USER";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkPatternOverrideDto {
+    pub id: Uuid,
+    pub satker_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub user_full_name: Option<String>,
+    pub group_id: Option<Uuid>,
+    pub group_name: Option<String>,
+    pub effective_from: NaiveDate,
+    pub effective_to: Option<NaiveDate>,
+    pub mode: WorkPatternMode,
+    pub work_start: NaiveTime,
+    pub work_end: NaiveTime,
+    pub core_start: Option<NaiveTime>,
+    pub core_end: Option<NaiveTime>,
+    pub required_minutes: Option<i32>,
+    pub max_segments: i16,
+    pub max_break_minutes: Option<i16>,
+    pub note: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpsertWorkPatternOverrideReq {
+    /// Isi salah satu: user_id atau group_id.
+    pub user_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub effective_from: NaiveDate,
+    /// Kosong = berlaku seterusnya.
+    pub effective_to: Option<NaiveDate>,
+    /// Default FIXED.
+    pub mode: Option<WorkPatternMode>,
+    /// Format: "HH:MM" or "HH:MM:SS". FLEXI: paling awal boleh mulai kerja.
+    pub work_start: String,
+    /// Format: "HH:MM" or "HH:MM:SS". FLEXI: paling akhir selesai kerja.
+    pub work_end: String,
+    /// FLEXI saja: jam inti, terlambat dihitung dari core_start.
+    pub core_start: Option<String>,
+    pub core_end: Option<String>,
+    /// FLEXI saja: durasi kerja wajib per hari (menit).
+    pub required_minutes: Option<i32>,
+    /// Jumlah pasangan check-in/out per hari (shift terpisah). Default 1.
+    pub max_segments: Option<i16>,
+    /// Batas total menit istirahat per hari. Kosong = tanpa batas.
+    pub max_break_minutes: Option<i16>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListWorkPatternOverridesQuery {
+    pub satker_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EffectiveWorkPatternQuery {
+    /// Default: user yang login.
+    pub user_id: Option<Uuid>,
+    /// Default: hari ini (timezone aplikasi).
+    pub date: Option<NaiveDate>,
+}
+
+/// Jam kerja yang berlaku untuk seorang user di satu tanggal.
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveWorkHours {
+    /// SATKER | GROUP | USER
+    pub source: &'static str,
+    pub override_id: Option<Uuid>,
+    pub mode: WorkPatternMode,
+    pub work_start: NaiveTime,
+    pub work_end: NaiveTime,
+    pub core_start: Option<NaiveTime>,
+    pub core_end: Option<NaiveTime>,
+    pub required_minutes: Option<i32>,
+    /// Jumlah segmen check-in/out yang diizinkan per hari.
+    pub max_segments: i16,
+    /// Batas total menit istirahat per hari; kelebihan dihitung terlambat.
+    pub max_break_minutes: Option<i16>,
+}
+
+impl EffectiveWorkHours {
+    /// Batas terlambat: FIXED = work_start, FLEXI = core_start.
+    pub fn late_after(&self) -> NaiveTime {
+        match self.mode {
+            WorkPatternMode::Fixed => self.work_start,
+            WorkPatternMode::Flexi => self.core_start.unwrap_or(self.work_start),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct EffectiveWorkPatternDto {
+    pub user_id: Uuid,
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub hours: EffectiveWorkHours,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkPatternOverrideResp {
+    pub status: &'static str,
+    pub data: WorkPatternOverrideDto,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkPatternOverridesResp {
+    pub status: &'static str,
+    pub data: Vec<WorkPatternOverrideDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EffectiveWorkPatternResp {
+    pub status: &'static str,
+    pub data: EffectiveWorkPatternDto,
+}
+
+/// Override yang sudah divalidasi, siap disimpan.
+#[derive(Debug, Clone)]
+pub struct WorkPatternOverrideInput {
+    pub user_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub effective_from: NaiveDate,
+    pub effective_to: Option<NaiveDate>,
+    pub mode: WorkPatternMode,
+    pub work_start: NaiveTime,
+    pub work_end: NaiveTime,
+    pub core_start: Option<NaiveTime>,
+    pub core_end: Option<NaiveTime>,
+    pub required_minutes: Option<i32>,
+    pub max_segments: i16,
+    pub max_break_minutes: Option<i16>,
+    pub note: Option<String>,
+}
+
+/// Override yang berlaku untuk satu user (langsung atau lewat kelompok).
+#[derive(Debug, Clone)]
+pub struct UserWorkPatternOverrideRow {
+    pub id: Uuid,
+    /// None = override langsung ke user.
+    pub group_id: Option<Uuid>,
+    pub effective_from: NaiveDate,
+    pub effective_to: Option<NaiveDate>,
+    pub mode: WorkPatternMode,
+    pub work_start: NaiveTime,
+    pub work_end: NaiveTime,
+    pub core_start: Option<NaiveTime>,
+    pub core_end: Option<NaiveTime>,
+    pub required_minutes: Option<i32>,
+    pub max_segments: i16,
+    pub max_break_minutes: Option<i16>,
+}
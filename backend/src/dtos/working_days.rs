@@ -27,6 +27,7 @@ pub struct WorkingDayDto {
     pub expected_start: Option<String>,
     pub expected_end: Option<String>,
     pub note: Option<String>,
+    pub is_manual: bool,
 }
 
 impl WorkingDayDto {
@@ -38,6 +39,7 @@ impl WorkingDayDto {
             expected_start: r.expected_start.map(|t| t.format("%H:%M:%S").to_string()),
             expected_end: r.expected_end.map(|t| t.format("%H:%M:%S").to_string()),
             note: r.note.clone(),
+            is_manual: r.is_manual,
         }
     }
 
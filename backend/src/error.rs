@@ -28,17 +28,15 @@ pub enum ErrorMessage {
     InvalidToken,
     ServerError,
     WrongCredentials,
-    EmailAlreadyExists,
     UserNoLongerExists,
     SatkerNoLonger,
     TokenNotProvided,
-    PermissionDenied,
     ForbiddenRequest,
 }
 
-impl ToString for ErrorMessage {
-    fn to_string(&self) -> String {
-        self.to_str().to_owned()
+impl fmt::Display for ErrorMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_str())
     }
 }
 
@@ -61,18 +59,12 @@ impl ErrorMessage {
             }
             ErrorMessage::ServerError => "An internal server error occurred.".to_string(),
             ErrorMessage::WrongCredentials => "The provided credentials are incorrect.".to_string(),
-            ErrorMessage::EmailAlreadyExists => {
-                "An account with this email already exists.".to_string()
-            }
             ErrorMessage::UserNoLongerExists => "The user account no longer exists.".to_string(),
             ErrorMessage::SatkerNoLonger => "Satker not longer exists.".to_string(),
             ErrorMessage::ForbiddenRequest => {
                 "Forbidden, anda tidak berhak mengakses request ini.".to_string()
             }
             ErrorMessage::TokenNotProvided => "Authentication token was not provided.".to_string(),
-            ErrorMessage::PermissionDenied => {
-                "You do not have permission to perform this action.".to_string()
-            }
         }
     }
 }
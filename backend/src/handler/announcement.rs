@@ -1,8 +1,7 @@
-use axum::extract::{Path, Query};
+use axum::extract::{Multipart, Path};
 use axum::response::IntoResponse;
 use axum::routing::{delete, get, post, put};
 use axum::{Extension, Json, Router};
-use serde::Deserialize;
 use serde_json::json;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -10,14 +9,15 @@ use uuid::Uuid;
 use crate::AppState;
 use crate::auth::rbac::UserRole;
 use crate::database::announcement::AnnouncementRepo;
-use crate::dtos::announcement::{CreateAnnouncementReq, UpdateAnnouncementReq};
+use crate::dtos::announcement::{
+    AnnouncementDto, CreateAnnouncementReq, NewAnnouncementAttachment, UpdateAnnouncementReq,
+};
 use crate::error::HttpError;
 use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::announcement::{ensure_can_manage_announcement, normalize_announcement_body};
+use crate::services::upload::{ANNOUNCEMENT_FOLDER, save_announcement_document_upload};
 
-#[derive(Debug, Deserialize)]
-struct ListManageQuery {
-    include_inactive: Option<bool>,
-}
+const MAX_ATTACHMENTS_PER_ANNOUNCEMENT: i64 = 10;
 
 pub fn announcement_handler() -> Router {
     Router::new()
@@ -28,6 +28,23 @@ pub fn announcement_handler() -> Router {
         .route("/", post(create))
         .route("/{id}", put(update))
         .route("/{id}", delete(deactivate))
+        .route("/{id}/attachments", post(upload_attachment))
+        .route(
+            "/{id}/attachments/{attachment_id}",
+            delete(delete_attachment),
+        )
+}
+
+async fn find_existing(app_state: &AppState, id: Uuid) -> Result<AnnouncementDto, HttpError> {
+    let existing = app_state
+        .db_client
+        .find_announcement_by_id(id)
+        .await
+        .map_err(|_| HttpError::server_error("Server error".to_string()))?;
+
+    existing.ok_or(HttpError::bad_request(
+        "Pengumuman tidak ditemukan".to_string(),
+    ))
 }
 
 async fn list_visible(
@@ -46,7 +63,6 @@ async fn list_visible(
 async fn list_manageable(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(auth): Extension<AuthMiddleware>,
-    Query(_q): Query<ListManageQuery>,
 ) -> Result<impl IntoResponse, HttpError> {
     // Allow admins & heads to view list, but only admins can create/update.
     if !matches!(
@@ -77,13 +93,13 @@ async fn create(
     }
 
     req.title = req.title.trim().to_string();
-    req.body = req.body.trim().to_string();
     if req.title.is_empty() {
         return Err(HttpError::bad_request("Title wajib diisi".to_string()));
     }
-    if req.body.is_empty() {
-        return Err(HttpError::bad_request("Body wajib diisi".to_string()));
-    }
+    let (body, body_format, body_text) =
+        normalize_announcement_body(&req.body, req.body_format.as_deref())?;
+    req.body = body;
+    req.body_format = Some(body_format);
 
     // Enforce scope rules
     let scope = req.scope.as_str();
@@ -114,7 +130,7 @@ async fn create(
 
     let id = app_state
         .db_client
-        .create_announcement(auth.user_claims.user_id, req)
+        .create_announcement(auth.user_claims.user_id, req, body_text)
         .await
         .map_err(|e| {
             // Most likely constraint errors
@@ -134,28 +150,16 @@ async fn update(
         return Err(HttpError::unauthorized("Unauthorized".to_string()));
     }
 
-    let existing = app_state
-        .db_client
-        .find_announcement_by_id(id)
-        .await
-        .map_err(|_| HttpError::server_error("Server error".to_string()))?;
-
-    let existing = existing.ok_or(HttpError::bad_request(
-        "Pengumuman tidak ditemukan".to_string(),
-    ))?;
+    let existing = find_existing(&app_state, id).await?;
 
     // SATKER_ADMIN can only update SATKER announcements in own satker, not GLOBAL
+    ensure_can_manage_announcement(
+        &auth.user_claims,
+        &existing.scope,
+        existing.satker_id,
+        "mengubah",
+    )?;
     if auth.user_claims.role == UserRole::SatkerAdmin {
-        if existing.scope == "GLOBAL" {
-            return Err(HttpError::unauthorized(
-                "SATKER_ADMIN tidak boleh mengubah pengumuman GLOBAL".to_string(),
-            ));
-        }
-        if existing.satker_id != Some(auth.user_claims.satker_id) {
-            return Err(HttpError::unauthorized(
-                "Tidak boleh mengubah pengumuman satker lain".to_string(),
-            ));
-        }
         // Force scope SATKER and satker_id own
         req.scope = Some("SATKER".into());
         req.satker_id = Some(auth.user_claims.satker_id);
@@ -168,11 +172,16 @@ async fn update(
         }
         req.title = Some(title.trim().to_string());
     }
-    if let Some(body) = &req.body {
-        if body.trim().is_empty() {
-            return Err(HttpError::bad_request("Body wajib diisi".to_string()));
-        }
-        req.body = Some(body.trim().to_string());
+
+    // Body & format saling bergantung: kalau salah satu berubah, render ulang fallback plain-text.
+    let mut body_text = None;
+    if req.body.is_some() || req.body_format.is_some() {
+        let raw_body = req.body.as_deref().unwrap_or(&existing.body);
+        let format = req.body_format.as_deref().unwrap_or(&existing.body_format);
+        let (body, body_format, text) = normalize_announcement_body(raw_body, Some(format))?;
+        req.body = Some(body);
+        req.body_format = Some(body_format);
+        body_text = Some(text);
     }
 
     // If changing scope, enforce consistency
@@ -199,7 +208,7 @@ async fn update(
 
     app_state
         .db_client
-        .update_announcement(id, req)
+        .update_announcement(id, req, body_text)
         .await
         .map_err(|e| HttpError::bad_request(format!("Gagal update: {e}")))?;
 
@@ -211,38 +220,138 @@ async fn deactivate(
     Extension(auth): Extension<AuthMiddleware>,
     Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse, HttpError> {
-    if !auth.user_claims.role.is_admin() {
-        return Err(HttpError::unauthorized("Unauthorized".to_string()));
-    }
+    let existing = find_existing(&app_state, id).await?;
+    ensure_can_manage_announcement(
+        &auth.user_claims,
+        &existing.scope,
+        existing.satker_id,
+        "menghapus",
+    )?;
 
-    let existing = app_state
+    app_state
         .db_client
-        .find_announcement_by_id(id)
+        .deactivate_announcement(id)
         .await
         .map_err(|_| HttpError::server_error("Server error".to_string()))?;
 
-    let existing = existing.ok_or(HttpError::bad_request(
-        "Pengumuman tidak ditemukan".to_string(),
-    ))?;
+    Ok(Json(json!({ "status": "success", "data": "ok" })))
+}
 
-    if auth.user_claims.role == UserRole::SatkerAdmin {
-        if existing.scope == "GLOBAL" {
-            return Err(HttpError::unauthorized(
-                "SATKER_ADMIN tidak boleh menghapus pengumuman GLOBAL".to_string(),
-            ));
-        }
-        if existing.satker_id != Some(auth.user_claims.satker_id) {
-            return Err(HttpError::unauthorized(
-                "Tidak boleh menghapus pengumuman satker lain".to_string(),
-            ));
-        }
+// POST /api/announcements/{id}/attachments (multipart, field "file": pdf/jpg/png)
+async fn upload_attachment(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(auth): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    mp: Multipart,
+) -> Result<impl IntoResponse, HttpError> {
+    let existing = find_existing(&app_state, id).await?;
+    ensure_can_manage_announcement(
+        &auth.user_claims,
+        &existing.scope,
+        existing.satker_id,
+        "mengubah",
+    )?;
+
+    // cek cepat sebelum upload; batas sebenarnya dijaga lagi saat insert
+    let count = app_state
+        .db_client
+        .count_announcement_attachments(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if count >= MAX_ATTACHMENTS_PER_ANNOUNCEMENT {
+        return Err(attachment_limit_error());
     }
 
+    let stored =
+        save_announcement_document_upload(&app_state.storage, auth.user_claims.user_id, mp).await?;
+
+    let object_key = stored.object_key.clone();
+    let row = match app_state
+        .db_client
+        .add_announcement_attachment(
+            NewAnnouncementAttachment {
+                announcement_id: id,
+                object_key: stored.object_key,
+                file_name: stored.file_name,
+                content_type: stored.content_type,
+                size_bytes: stored.size_bytes,
+                uploaded_by: auth.user_claims.user_id,
+            },
+            MAX_ATTACHMENTS_PER_ANNOUNCEMENT,
+        )
+        .await
+    {
+        Ok(Some(row)) => row,
+        result => {
+            // jangan tinggalkan file yatim kalau row DB tidak dibuat
+            if let Err(del) = app_state
+                .storage
+                .delete(ANNOUNCEMENT_FOLDER, &object_key)
+                .await
+            {
+                tracing::warn!(
+                    "gagal menghapus lampiran yatim {}: {}",
+                    object_key,
+                    del.message
+                );
+            }
+            return Err(match result {
+                Err(e) => HttpError::server_error(e.to_string()),
+                _ => attachment_limit_error(),
+            });
+        }
+    };
+
+    Ok(Json(json!({ "status": "success", "data": row })))
+}
+
+fn attachment_limit_error() -> HttpError {
+    HttpError::bad_request(format!(
+        "Maksimal {} lampiran per pengumuman",
+        MAX_ATTACHMENTS_PER_ANNOUNCEMENT
+    ))
+}
+
+async fn delete_attachment(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(auth): Extension<AuthMiddleware>,
+    Path((id, attachment_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, HttpError> {
+    let existing = find_existing(&app_state, id).await?;
+    ensure_can_manage_announcement(
+        &auth.user_claims,
+        &existing.scope,
+        existing.satker_id,
+        "mengubah",
+    )?;
+
+    let attachment = app_state
+        .db_client
+        .find_announcement_attachment(id, attachment_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::bad_request(
+            "Lampiran tidak ditemukan".to_string(),
+        ))?;
+
     app_state
         .db_client
-        .deactivate_announcement(id)
+        .delete_announcement_attachment(attachment.id)
         .await
-        .map_err(|_| HttpError::server_error("Server error".to_string()))?;
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    // best effort: file fisik ikut dihapus, row DB sudah jadi sumber kebenaran
+    if let Err(e) = app_state
+        .storage
+        .delete(ANNOUNCEMENT_FOLDER, &attachment.object_key)
+        .await
+    {
+        tracing::warn!(
+            "gagal menghapus lampiran {}: {}",
+            attachment.object_key,
+            e.message
+        );
+    }
 
     Ok(Json(json!({ "status": "success", "data": "ok" })))
 }
@@ -1,24 +1,46 @@
 use crate::AppState;
+use crate::auth::rbac::UserRole;
 use crate::constants::{AttendanceEventType, AttendanceLeaveType};
-use crate::database::attendance::{AddAttendanceEvent, AttendanceEventRepo};
+use crate::database::attendance::{
+    AddAttendanceEvent, AttendanceEventRepo, insert_attendance_event,
+};
 use crate::database::attendance_apel::AttendanceApelRepo;
-use crate::database::attendance_session::AttendanceSessionRepo;
+use crate::database::attendance_away::AttendanceAwayRepo;
+use crate::database::attendance_session::{
+    AttendanceSessionRepo, close_attendance_segment, open_attendance_segment,
+};
 use crate::database::duty_schedule::DutyScheduleRepo;
 use crate::database::geofence::GeofenceRepo;
 use crate::database::leave_request::LeaveRequestRepo;
-use crate::database::user_device::UserDeviceRepo;
-use crate::database::work_pattern::{WorkPatternRepo, pick_effective_pattern};
+use crate::database::user::UserRepo;
 use crate::dtos::attendance::{
     AttendanceDto, AttendanceRekapDto, AttendanceRekapDtoQuery, AttendanceRekapDtoResp,
     AttendanceRekapsDtoResp, AttendanceReq, AttendanceResp, AttendanceSessionTodayDto,
     AttendanceSessionTodayResp,
 };
 use crate::dtos::attendance_apel::{AttendanceApelHistoryQuery, AttendanceApelHistoryResp};
+use crate::dtos::attendance_session::{AttendanceAwayPeriodDto, AttendanceSegmentDto};
 use crate::dtos::duty_schedule::DutyScheduleDto;
+use crate::dtos::geofence_marker::{LOCATION_PROOF_GPS, ProximityMarkerMatch};
+use crate::dtos::settings::AttendanceRules;
+use crate::dtos::user_device::DeviceInfo;
 use crate::error::HttpError;
 use crate::handler::attendance_admin::attendance_admin_handler;
+use crate::handler::attendance_away::{break_end, break_start, field_in, field_out};
 use crate::handler::attendance_challenge::{anti_teleport_check, validate_and_use_challenge};
+use crate::handler::attendance_rekap::attendance_rekap_handler;
 use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::attendance_anomaly::{
+    AnomalyCheck, detect_attendance_anomalies, flag_attendance_event, load_selfie_meta,
+};
+use crate::services::face_verification::verify_attendance_face;
+use crate::services::location_proof::match_proximity_marker;
+use crate::services::metrics::{
+    RejectReason, record_attendance_rejection, record_attendance_result,
+};
+use crate::services::user_device::ensure_device_owned;
+use crate::services::work_pattern::resolve_work_hours;
+use crate::utils::attendance_rules_cache::get_attendance_rules_cached;
 use crate::utils::fungsi::haversine_m;
 use crate::utils::timezone_cache::get_timezone_cached;
 use axum::extract::Query;
@@ -35,19 +57,18 @@ pub fn attendance_handler() -> Router {
     Router::new()
         .route("/check-in", post(check_in))
         .route("/check-out", post(check_out))
+        .route("/break-start", post(break_start))
+        .route("/break-end", post(break_end))
+        .route("/field-out", post(field_out))
+        .route("/field-in", post(field_in))
         .route("/session-today", get(get_attendance_session_today))
         .route("/get", get(find_attendance))
         .route("/list", get(list_attendances))
         .route("/apel/history", get(get_attendance_apel_history))
         .nest("/admin", attendance_admin_handler())
+        .nest("/rekap", attendance_rekap_handler())
 }
 
-const EARLY_CHECKIN_HOURS: i64 = 2;
-const DUTY_GRACE_HOURS: i64 = 6;
-const DUTY_MAX_CHECKOUT_HOURS: i64 = 24;
-
-const DUTY_CARD_GRACE_MINUTES: i64 = 30;
-
 fn local_day_bounds_utc<Tz: TimeZone>(
     tz: &Tz,
     date: NaiveDate,
@@ -93,13 +114,15 @@ async fn find_active_duty_now(
         .find(|d| d.start_at <= now && d.end_at > now))
 }
 
-/// Untuk attendance card (UX): anggap duty masih relevan sampai `DUTY_CARD_GRACE_MINUTES`
-/// setelah end_at, supaya tombol check-out tidak hilang tepat saat shift selesai.
+/// Untuk attendance card (UX): anggap duty masih relevan sampai `grace_minutes`
+/// (aturan `duty_card_grace_minutes`) setelah end_at, supaya tombol check-out
+/// tidak hilang tepat saat shift selesai.
 async fn find_duty_now_for_card(
     app_state: &Arc<AppState>,
     satker_id: Uuid,
     user_id: Uuid,
     now: chrono::DateTime<Utc>,
+    grace_minutes: i64,
 ) -> Result<Option<DutyScheduleDto>, HttpError> {
     let from = now - Duration::days(2);
     let to = now + Duration::days(2);
@@ -109,7 +132,7 @@ async fn find_duty_now_for_card(
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
-    let grace = Duration::minutes(DUTY_CARD_GRACE_MINUTES);
+    let grace = Duration::minutes(grace_minutes);
     Ok(items
         .into_iter()
         .find(|d| d.start_at <= now && (d.end_at + grace) > now))
@@ -131,31 +154,39 @@ async fn find_duty_by_local_date(
     Ok(items.into_iter().min_by_key(|d| d.start_at))
 }
 
+/// Window jam kerja user (override user/kelompok atau pattern satker).
+/// FLEXI: rentang paling awal mulai s/d paling akhir selesai.
 async fn get_work_window_local(
     app_state: &Arc<AppState>,
     satker_id: Uuid,
+    user_id: Uuid,
     work_date: NaiveDate,
 ) -> Result<(NaiveTime, NaiveTime), HttpError> {
-    let patterns = app_state
-        .db_client
-        .list_work_patterns(satker_id)
-        .await
-        .map_err(|e| HttpError::server_error(e.to_string()))?;
-    let p = pick_effective_pattern(&patterns, work_date).ok_or_else(|| {
-        HttpError::bad_request("work pattern belum diset untuk satker ini".to_string())
-    })?;
-    Ok((p.work_start, p.work_end))
+    let hours = resolve_work_hours(&app_state.db_client, satker_id, user_id, work_date).await?;
+    Ok((hours.work_start, hours.work_end))
 }
 
 pub async fn check_in(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(user_claims): Extension<AuthMiddleware>,
     Json(payload): Json<AttendanceReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let res = process_check_in(app_state, user_claims, payload).await;
+    record_attendance_result(AttendanceEventType::CheckIn, &res);
+    res
+}
+
+async fn process_check_in(
+    app_state: Arc<AppState>,
+    user_claims: AuthMiddleware,
+    payload: AttendanceReq,
 ) -> Result<impl IntoResponse, HttpError> {
     payload
         .validate()
         .map_err(|e| HttpError::bad_request(e.to_string()))?;
 
+    let rules = get_attendance_rules_cached(&app_state, user_claims.user_claims.satker_id).await?;
+
     /*
     validate_and_use_challenge(&app_state, &user_claims.user_claims, payload.challenge_id)
         .await
@@ -168,21 +199,24 @@ pub async fn check_in(
         .clone()
         .ok_or(HttpError::bad_request("device_id wajib".to_string()))?;
 
-    app_state
-        .db_client
-        .ensure_device_bound_first_user(
-            user_claims.user_claims.user_id,
-            &device_id,
-            payload.device_model.clone(),
-            payload.android_version.clone(),
-            payload.app_build.clone(),
-            payload.client_version.clone(),
-        )
-        .await
-        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+    ensure_device_owned(
+        &app_state.db_client,
+        AttendanceEventType::CheckIn,
+        user_claims.user_claims.user_id,
+        &DeviceInfo {
+            device_id: device_id.clone(),
+            device_model: payload.device_model.clone(),
+            android_version: payload.android_version.clone(),
+            app_build: payload.app_build.clone(),
+            client_version: payload.client_version.clone(),
+            public_key: payload.public_key.clone(),
+        },
+    )
+    .await?;
 
     // ✅ reject mock
     if payload.is_mock.unwrap_or(false) {
+        record_attendance_rejection(AttendanceEventType::CheckIn, RejectReason::MockLocation);
         return Err(HttpError::bad_request(
             "mock location terdeteksi".to_string(),
         ));
@@ -195,25 +229,41 @@ pub async fn check_in(
         }
     }*/
 
+    // Wi-Fi / BLE terdaftar: akurasi GPS tidak lagi jadi syarat
+    let proximity = match_proximity_marker(
+        &app_state.db_client,
+        &rules,
+        user_claims.user_claims.satker_id,
+        payload.wifi_scans.as_deref(),
+        payload.ble_scans.as_deref(),
+    )
+    .await?;
+
     // ✅ reject accuracy too poor
-    if let Some(acc) = payload.accuracy_meters
-        && acc > 50.0
+    if proximity.is_none()
+        && let Some(acc) = payload.accuracy_meters
+        && acc > rules.max_accuracy_meters
     {
+        record_attendance_rejection(AttendanceEventType::CheckIn, RejectReason::Accuracy);
         return Err(HttpError::bad_request(
             "akurasi lokasi terlalu rendah, silakan coba lagi".to_string(),
         ));
     }
 
-    validate_and_use_challenge(
+    let attestation = validate_and_use_challenge(
         &app_state,
+        &rules,
         &user_claims.user_claims,
-        payload.challenge_id,
+        AttendanceEventType::CheckIn,
+        &payload,
         &device_id, // ✅ bind device
     )
     .await?;
 
     anti_teleport_check(
         &app_state,
+        &rules,
+        AttendanceEventType::CheckIn,
         user_claims.user_claims.user_id,
         &device_id,
         payload.latitude,
@@ -221,18 +271,27 @@ pub async fn check_in(
     )
     .await?;
 
-    let nearest = nearest_geofence(
+    let face_check = verify_attendance_face(
+        &app_state,
+        &rules,
+        AttendanceEventType::CheckIn,
+        user_claims.user_claims.user_id,
+        payload.selfie_object_key.as_deref(),
+    )
+    .await?;
+
+    let (geofence_id, distance_m, radius_m, out_of_fence) = resolve_event_fence(
         &app_state,
+        &rules,
         user_claims.user_claims.satker_id,
         payload.latitude,
         payload.longitude,
+        proximity.as_ref(),
     )
-    .await
-    .map_err(|e| HttpError::bad_request(e.to_string()))?;
-
-    let (geofence_id, distance_m, radius_m) = nearest.ok_or(HttpError::bad_request(
-        "geofence aktif belum diset untuk satker ini".to_string(),
-    ))?;
+    .await?;
+    let location_proof = proximity
+        .as_ref()
+        .map_or(LOCATION_PROOF_GPS.to_string(), |m| m.kind.clone());
 
     /*if distance_m > radius_m as f64 {
         return Err(HttpError::bad_request(format!(
@@ -241,13 +300,15 @@ pub async fn check_in(
         )));
     }*/
 
-    let out_of_fence = distance_m > radius_m as f64;
-
     let mut leave_type = payload.leave_type.unwrap_or(AttendanceLeaveType::Normal);
     let leave_notes = payload.leave_notes.clone();
 
     if out_of_fence {
         if matches!(leave_type, AttendanceLeaveType::Normal) {
+            record_attendance_rejection(
+                AttendanceEventType::CheckIn,
+                RejectReason::OutsideGeofence,
+            );
             return Err(HttpError::bad_request(format!(
                 "di luar geofence: jarak {:.1}m > radius {}m. Pilih jenis izin/dinas terlebih dahulu",
                 distance_m, radius_m
@@ -302,7 +363,7 @@ pub async fn check_in(
             && sess_y.check_in_at.is_some()
             && sess_y.check_out_at.is_none()
         {
-            let grace_until = duty_yesterday.end_at + Duration::hours(DUTY_GRACE_HOURS);
+            let grace_until = duty_yesterday.end_at + Duration::hours(rules.duty_grace_hours);
             if now <= grace_until {
                 return Err(HttpError::bad_request(format!(
                     "anda belum check-out dari jadwal dinas yang kemarin ({} - {})",
@@ -314,7 +375,7 @@ pub async fn check_in(
     }
 
     // 4) Duty schedule hari ini yang "upcoming" juga harus diperlakukan sebagai konteks duty,
-    // supaya user bisa check-in dalam window EARLY_CHECKIN_HOURS sebelum start_at.
+    // supaya user bisa check-in dalam window `early_checkin_hours` sebelum start_at.
     // Contoh: duty 20:00, user check-in 19:00 -> harus boleh (bukan kena jam kerja normal).
     let duty_today = if duty_active.is_none() {
         find_duty_by_local_date(
@@ -334,9 +395,9 @@ pub async fn check_in(
         Some(d) => Some(d),
         None => {
             if let Some(d) = duty_today {
-                let earliest = d.start_at - Duration::hours(EARLY_CHECKIN_HOURS);
+                let earliest = d.start_at - Duration::hours(rules.early_checkin_hours);
                 // allow check-in from (start_at - early) up to (end_at + grace)
-                let latest = d.end_at + Duration::hours(DUTY_GRACE_HOURS);
+                let latest = d.end_at + Duration::hours(rules.duty_grace_hours);
                 if now >= earliest && now <= latest {
                     Some(d)
                 } else {
@@ -357,6 +418,9 @@ pub async fn check_in(
         };
 
     // Already checked in for the target work_date?
+    // Pola kerja dengan max_segments > 1 (shift terpisah / tugas lapangan) boleh check-in lagi
+    // setelah segmen sebelumnya check-out.
+    let mut is_next_segment = false;
     if let Some(existing) = app_state
         .db_client
         .find_attendance_session_by_user_date(user_claims.user_claims.user_id, target_work_date)
@@ -364,11 +428,40 @@ pub async fn check_in(
         .map_err(|e| HttpError::server_error(e.to_string()))?
         && let Some(ci) = existing.check_in_at
     {
-        let ci_local = ci.with_timezone(&tz);
-        return Err(HttpError::bad_request(format!(
-            "Anda sudah melakukan check in, pada hari ini {}",
-            ci_local.format("%d %b %Y %H:%M")
-        )));
+        let segments = app_state
+            .db_client
+            .list_attendance_segments(existing.id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        let max_segments = resolve_work_hours(
+            &app_state.db_client,
+            user_claims.user_claims.satker_id,
+            user_claims.user_claims.user_id,
+            target_work_date,
+        )
+        .await?
+        .max_segments;
+
+        if max_segments <= 1 {
+            let ci_local = ci.with_timezone(&tz);
+            return Err(HttpError::bad_request(format!(
+                "Anda sudah melakukan check in, pada hari ini {}",
+                ci_local.format("%d %b %Y %H:%M")
+            )));
+        }
+        if let Some(open) = segments.iter().find(|g| g.check_out_at.is_none()) {
+            return Err(HttpError::bad_request(format!(
+                "Anda masih check in sejak {}, silakan check out terlebih dahulu",
+                open.check_in_at.with_timezone(&tz).format("%d %b %Y %H:%M")
+            )));
+        }
+        if segments.len() >= max_segments as usize {
+            return Err(HttpError::bad_request(format!(
+                "batas {} kali check-in per hari sudah tercapai",
+                max_segments
+            )));
+        }
+        is_next_segment = true;
     }
 
     // If not in duty context, enforce work window.
@@ -378,6 +471,7 @@ pub async fn check_in(
         let (work_start, work_end) = get_work_window_local(
             &app_state,
             user_claims.user_claims.satker_id,
+            user_claims.user_claims.user_id,
             target_work_date,
         )
         .await?;
@@ -394,9 +488,10 @@ pub async fn check_in(
             .single()
             .unwrap_or_else(|| tz.from_local_datetime(&end_local).earliest().unwrap());
 
-        let earliest = work_start_dt - Duration::hours(EARLY_CHECKIN_HOURS);
+        let earliest = work_start_dt - Duration::hours(rules.early_checkin_hours);
         work_start_dt_local = Some(work_start_dt);
         if local_now < earliest {
+            record_attendance_rejection(AttendanceEventType::CheckIn, RejectReason::TooEarly);
             return Err(HttpError::bad_request(format!(
                 "anda check-in terlalu dini. paling cepat {}",
                 //earliest.with_timezone(&Utc).to_rfc3339()
@@ -404,6 +499,7 @@ pub async fn check_in(
             )));
         }
         if local_now > work_end_dt {
+            record_attendance_rejection(AttendanceEventType::CheckIn, RejectReason::TooLate);
             return Err(HttpError::bad_request(format!(
                 "anda check-in sudah melewati jam kerja. batas akhir {}",
                 //work_end_dt.with_timezone(&Utc).to_rfc3339()
@@ -423,12 +519,13 @@ pub async fn check_in(
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
     // Keep legacy event-based dedup check (in case session existed without check_in_at).
-    if app_state
-        .db_client
-        .find_attendance_event_by_session(session_id, AttendanceEventType::CheckIn)
-        .await
-        .map_err(|e| HttpError::server_error(e.to_string()))?
-        .is_some()
+    if !is_next_segment
+        && app_state
+            .db_client
+            .find_attendance_event_by_session(session_id, AttendanceEventType::CheckIn)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?
+            .is_some()
     {
         let sess_check_in = app_state
             .db_client
@@ -449,20 +546,51 @@ pub async fn check_in(
         ));
     }
 
-    let updated = app_state
+    let selfie_meta = load_selfie_meta(
+        &app_state.db_client,
+        &app_state.storage,
+        payload.selfie_object_key.as_deref(),
+    )
+    .await;
+    let anomaly_reasons = detect_attendance_anomalies(
+        &app_state.db_client,
+        &rules,
+        &AnomalyCheck {
+            event: AttendanceEventType::CheckIn,
+            session_id,
+            user_id: user_claims.user_claims.user_id,
+            device_id: &device_id,
+            latitude: payload.latitude,
+            longitude: payload.longitude,
+            face_match_score: payload.face_match_score,
+            face_check: face_check.as_ref(),
+            selfie_phash: selfie_meta.as_ref().map(|m| m.phash.as_str()),
+            out_of_fence,
+            leave_type,
+        },
+    )
+    .await?;
+
+    let fence_row = app_state
         .db_client
-        .update_check_in_attendance_session(session_id, Some(now))
+        .find_geofence(geofence_id)
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
-    let fence_row = app_state
+    // segmen & event check-in satu transaksi: gagal insert event = segmen tidak terbuka
+    let mut tx = app_state
         .db_client
-        .find_geofence(geofence_id)
+        .pool
+        .begin()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let opened = open_attendance_segment(&mut tx, session_id, now)
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
     let add_row = AddAttendanceEvent {
         session_id,
+        segment_id: Some(opened.segment_id),
         satker_id: user_claims.user_claims.satker_id,
         user_id: user_claims.user_claims.user_id,
         event_type: AttendanceEventType::CheckIn,
@@ -475,6 +603,13 @@ pub async fn check_in(
         selfie_object_key: payload.selfie_object_key,
         liveness_score: payload.liveness_score,
         face_match_score: payload.face_match_score,
+        server_face_score: face_check.as_ref().and_then(|f| f.score),
+        face_verification_status: face_check.as_ref().map(|f| f.status.to_string()),
+        selfie_captured_at: selfie_meta.as_ref().and_then(|m| m.captured_at),
+        selfie_phash: selfie_meta.map(|m| m.phash),
+        location_proof: Some(location_proof.clone()),
+        proximity_marker_id: proximity.as_ref().map(|m| m.id),
+        attestation: attestation.map(str::to_string),
         device_id: payload.device_id,
         client_version: payload.client_version,
         server_challenge_id: Some(payload.challenge_id),
@@ -483,13 +618,27 @@ pub async fn check_in(
         app_build: payload.app_build,
         attendance_leave_type: leave_type,
         attendance_leave_notes: leave_notes,
+        away_period_id: None,
+        destination: None,
+        purpose: None,
     };
 
-    app_state
-        .db_client
-        .add_attendance_event(add_row)
+    let event_id = insert_attendance_event(&mut tx, add_row)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    tx.commit()
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
+    flag_attendance_event(
+        &app_state.db_client,
+        AttendanceEventType::CheckIn,
+        event_id,
+        session_id,
+        user_claims.user_claims.satker_id,
+        user_claims.user_claims.user_id,
+        &anomaly_reasons,
+    )
+    .await?;
 
     // Optional: record apel (laporan) if requested and eligible.
     // Eligibility rules (backend authority):
@@ -498,10 +647,11 @@ pub async fn check_in(
     // - masih dalam window apel: now <= work_start + 2 jam
     if payload.apel.unwrap_or(false)
         && !out_of_fence
+        && !is_next_segment
         && _duty_context.is_none()
         && let Some(ws) = work_start_dt_local
     {
-        let apel_deadline = ws + Duration::hours(EARLY_CHECKIN_HOURS);
+        let apel_deadline = ws + Duration::hours(rules.early_checkin_hours);
         if local_now <= apel_deadline {
             let _ = app_state
                 .db_client
@@ -518,13 +668,16 @@ pub async fn check_in(
     }
 
     let attendance_dto = AttendanceDto {
-        session_id: updated.id,
-        work_date: updated.work_date,
-        check_in_at: updated.check_in_at,
-        check_out_at: updated.check_out_at,
+        session_id: opened.session.id,
+        work_date: opened.session.work_date,
+        check_in_at: opened.session.check_in_at,
+        check_out_at: opened.session.check_out_at,
         geofence_id: Some(geofence_id),
         distance_to_fence_m: Some(distance_m),
-        geofence_name: Some(fence_row.unwrap().name),
+        geofence_name: fence_row.map(|f| f.name),
+        segment_seq: opened.seq,
+        face_verification: face_check,
+        location_proof,
     };
 
     let response = AttendanceResp {
@@ -539,10 +692,22 @@ pub async fn check_out(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(user_claims): Extension<AuthMiddleware>,
     Json(payload): Json<AttendanceReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let res = process_check_out(app_state, user_claims, payload).await;
+    record_attendance_result(AttendanceEventType::CheckOut, &res);
+    res
+}
+
+async fn process_check_out(
+    app_state: Arc<AppState>,
+    user_claims: AuthMiddleware,
+    payload: AttendanceReq,
 ) -> Result<impl IntoResponse, HttpError> {
     payload
         .validate()
         .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let rules = get_attendance_rules_cached(&app_state, user_claims.user_claims.satker_id).await?;
     /*
     validate_and_use_challenge(&app_state, &user_claims.user_claims, payload.challenge_id)
         .await
@@ -555,21 +720,24 @@ pub async fn check_out(
         .clone()
         .ok_or(HttpError::bad_request("device_id wajib".to_string()))?;
 
-    app_state
-        .db_client
-        .ensure_device_bound_first_user(
-            user_claims.user_claims.user_id,
-            &device_id,
-            payload.device_model.clone(),
-            payload.android_version.clone(),
-            payload.app_build.clone(),
-            payload.client_version.clone(),
-        )
-        .await
-        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+    ensure_device_owned(
+        &app_state.db_client,
+        AttendanceEventType::CheckOut,
+        user_claims.user_claims.user_id,
+        &DeviceInfo {
+            device_id: device_id.clone(),
+            device_model: payload.device_model.clone(),
+            android_version: payload.android_version.clone(),
+            app_build: payload.app_build.clone(),
+            client_version: payload.client_version.clone(),
+            public_key: payload.public_key.clone(),
+        },
+    )
+    .await?;
 
     // ✅ reject mock
     if payload.is_mock.unwrap_or(false) {
+        record_attendance_rejection(AttendanceEventType::CheckOut, RejectReason::MockLocation);
         return Err(HttpError::bad_request(
             "mock location terdeteksi".to_string(),
         ));
@@ -582,25 +750,41 @@ pub async fn check_out(
         }
     }*/
 
+    // Wi-Fi / BLE terdaftar: akurasi GPS tidak lagi jadi syarat
+    let proximity = match_proximity_marker(
+        &app_state.db_client,
+        &rules,
+        user_claims.user_claims.satker_id,
+        payload.wifi_scans.as_deref(),
+        payload.ble_scans.as_deref(),
+    )
+    .await?;
+
     // ✅ reject accuracy too poor
-    if let Some(acc) = payload.accuracy_meters
-        && acc > 50.0
+    if proximity.is_none()
+        && let Some(acc) = payload.accuracy_meters
+        && acc > rules.max_accuracy_meters
     {
+        record_attendance_rejection(AttendanceEventType::CheckOut, RejectReason::Accuracy);
         return Err(HttpError::bad_request(
             "akurasi lokasi terlalu rendah, silakan coba lagi".to_string(),
         ));
     }
 
-    validate_and_use_challenge(
+    let attestation = validate_and_use_challenge(
         &app_state,
+        &rules,
         &user_claims.user_claims,
-        payload.challenge_id,
+        AttendanceEventType::CheckOut,
+        &payload,
         &device_id, // ✅ bind device
     )
     .await?;
 
     anti_teleport_check(
         &app_state,
+        &rules,
+        AttendanceEventType::CheckOut,
         user_claims.user_claims.user_id,
         &device_id,
         payload.latitude,
@@ -608,18 +792,27 @@ pub async fn check_out(
     )
     .await?;
 
-    let nearest = nearest_geofence(
+    let face_check = verify_attendance_face(
+        &app_state,
+        &rules,
+        AttendanceEventType::CheckOut,
+        user_claims.user_claims.user_id,
+        payload.selfie_object_key.as_deref(),
+    )
+    .await?;
+
+    let (geofence_id, distance_m, radius_m, out_of_fence) = resolve_event_fence(
         &app_state,
+        &rules,
         user_claims.user_claims.satker_id,
         payload.latitude,
         payload.longitude,
+        proximity.as_ref(),
     )
-    .await
-    .map_err(|e| HttpError::bad_request(e.to_string()))?;
-
-    let (geofence_id, distance_m, radius_m) = nearest.ok_or(HttpError::bad_request(
-        "geofence aktif belum diset untuk satker ini".to_string(),
-    ))?;
+    .await?;
+    let location_proof = proximity
+        .as_ref()
+        .map_or(LOCATION_PROOF_GPS.to_string(), |m| m.kind.clone());
 
     /* if distance_m > radius_m as f64 {
         return Err(HttpError::bad_request(format!(
@@ -628,13 +821,15 @@ pub async fn check_out(
         )));
     }*/
 
-    let out_of_fence = distance_m > radius_m as f64;
-
     let mut leave_type = payload.leave_type.unwrap_or(AttendanceLeaveType::Normal);
     let leave_notes = payload.leave_notes.clone();
 
     if out_of_fence {
         if matches!(leave_type, AttendanceLeaveType::Normal) {
+            record_attendance_rejection(
+                AttendanceEventType::CheckOut,
+                RejectReason::OutsideGeofence,
+            );
             return Err(HttpError::bad_request(format!(
                 "di luar geofence: jarak {:.1}m > radius {}m. Pilih jenis izin/dinas terlebih dahulu",
                 distance_m, radius_m
@@ -693,11 +888,15 @@ pub async fn check_out(
                 if s.check_in_at.is_none() {
                     return Err(HttpError::bad_request("anda belum check-in".to_string()));
                 }
-                let max_until = duty_active.end_at + Duration::hours(DUTY_MAX_CHECKOUT_HOURS);
+                let max_until = duty_active.end_at + Duration::hours(rules.duty_max_checkout_hours);
                 if now > max_until {
+                    record_attendance_rejection(
+                        AttendanceEventType::CheckOut,
+                        RejectReason::TooLate,
+                    );
                     return Err(HttpError::bad_request(format!(
                         "anda check-out lebih dari {} jam dari shift yang diijinkan",
-                        DUTY_MAX_CHECKOUT_HOURS
+                        rules.duty_max_checkout_hours
                     )));
                 }
                 _target_work_date = duty_work_date;
@@ -731,11 +930,15 @@ pub async fn check_out(
                         return Err(HttpError::bad_request("anda belum check-in".to_string()));
                     }
                     let max_until =
-                        duty_yesterday.end_at + Duration::hours(DUTY_MAX_CHECKOUT_HOURS);
+                        duty_yesterday.end_at + Duration::hours(rules.duty_max_checkout_hours);
                     if now > max_until {
+                        record_attendance_rejection(
+                            AttendanceEventType::CheckOut,
+                            RejectReason::TooLate,
+                        );
                         return Err(HttpError::bad_request(format!(
                             "anda check-out lebih dari {} jam dari shift yang diijinkan",
-                            DUTY_MAX_CHECKOUT_HOURS
+                            rules.duty_max_checkout_hours
                         )));
                     }
                     _target_work_date = duty_work_date;
@@ -758,42 +961,71 @@ pub async fn check_out(
         }
     };
 
-    let attendance_event_row = app_state
+    let selfie_meta = load_selfie_meta(
+        &app_state.db_client,
+        &app_state.storage,
+        payload.selfie_object_key.as_deref(),
+    )
+    .await;
+    let anomaly_reasons = detect_attendance_anomalies(
+        &app_state.db_client,
+        &rules,
+        &AnomalyCheck {
+            event: AttendanceEventType::CheckOut,
+            session_id,
+            user_id: user_claims.user_claims.user_id,
+            device_id: &device_id,
+            latitude: payload.latitude,
+            longitude: payload.longitude,
+            face_match_score: payload.face_match_score,
+            face_check: face_check.as_ref(),
+            selfie_phash: selfie_meta.as_ref().map(|m| m.phash.as_str()),
+            out_of_fence,
+            leave_type,
+        },
+    )
+    .await?;
+
+    let fence_row = app_state
         .db_client
-        .find_attendance_event_by_session(session_id, AttendanceEventType::CheckOut)
+        .find_geofence(geofence_id)
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
-    if attendance_event_row.is_some() {
-        let sess_check_in = app_state
-            .db_client
-            .find_attendance_session(session_id)
-            .await
-            .map_err(|e| HttpError::server_error(e.to_string()))?;
-
-        let session_hour = sess_check_in.check_out_at.unwrap();
-        //let iso = session_hour.to_rfc3339();
-        let iso = session_hour.format("%d %b %Y %H:%M").to_string();
-
-        return Err(HttpError::bad_request(format!(
-            "Anda sudah melakukan check out, pada hari ini {iso}"
-        )));
-    }
-
-    let updated = app_state
+    // segmen & event check-out satu transaksi: gagal insert event = segmen tetap terbuka
+    let mut tx = app_state
         .db_client
-        .update_check_out_attendance_session(session_id, Some(now))
+        .pool
+        .begin()
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
-    let fence_row = app_state
-        .db_client
-        .find_geofence(geofence_id)
+    // Tutup segmen yang masih terbuka; kalau tidak ada berarti sudah check-out.
+    let closed = match close_attendance_segment(&mut tx, session_id, now)
         .await
-        .map_err(|e| HttpError::server_error(e.to_string()))?;
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+    {
+        Some(closed) => closed,
+        None => {
+            let sess = app_state
+                .db_client
+                .find_attendance_session(session_id)
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+            return Err(match sess.check_out_at {
+                Some(co) => HttpError::bad_request(format!(
+                    "Anda sudah melakukan check out, pada hari ini {}",
+                    co.with_timezone(&tz).format("%d %b %Y %H:%M")
+                )),
+                None => HttpError::bad_request("anda belum check-in".to_string()),
+            });
+        }
+    };
 
     let add_row = AddAttendanceEvent {
         session_id,
+        segment_id: Some(closed.segment_id),
         satker_id: user_claims.user_claims.satker_id,
         user_id: user_claims.user_claims.user_id,
         event_type: AttendanceEventType::CheckOut,
@@ -806,6 +1038,13 @@ pub async fn check_out(
         selfie_object_key: payload.selfie_object_key,
         liveness_score: payload.liveness_score,
         face_match_score: payload.face_match_score,
+        server_face_score: face_check.as_ref().and_then(|f| f.score),
+        face_verification_status: face_check.as_ref().map(|f| f.status.to_string()),
+        selfie_captured_at: selfie_meta.as_ref().and_then(|m| m.captured_at),
+        selfie_phash: selfie_meta.map(|m| m.phash),
+        location_proof: Some(location_proof.clone()),
+        proximity_marker_id: proximity.as_ref().map(|m| m.id),
+        attestation: attestation.map(str::to_string),
         device_id: payload.device_id,
         client_version: payload.client_version,
         server_challenge_id: Some(payload.challenge_id),
@@ -814,13 +1053,27 @@ pub async fn check_out(
         app_build: payload.app_build,
         attendance_leave_type: leave_type,
         attendance_leave_notes: leave_notes,
+        away_period_id: None,
+        destination: None,
+        purpose: None,
     };
 
-    app_state
-        .db_client
-        .add_attendance_event(add_row)
+    let event_id = insert_attendance_event(&mut tx, add_row)
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
+    tx.commit()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    flag_attendance_event(
+        &app_state.db_client,
+        AttendanceEventType::CheckOut,
+        event_id,
+        session_id,
+        user_claims.user_claims.satker_id,
+        user_claims.user_claims.user_id,
+        &anomaly_reasons,
+    )
+    .await?;
 
     // Optional: record apel (laporan) if requested and eligible.
     // Eligibility rules (backend authority):
@@ -828,8 +1081,13 @@ pub async fn check_out(
     // - bukan konteks duty schedule (used_duty == false)
     // - masih dalam window apel: now <= work_start + 2 jam
     if payload.apel.unwrap_or(false) && !out_of_fence && !used_duty {
-        let (work_start, _work_end) =
-            get_work_window_local(&app_state, user_claims.user_claims.satker_id, today).await?;
+        let (work_start, _work_end) = get_work_window_local(
+            &app_state,
+            user_claims.user_claims.satker_id,
+            user_claims.user_claims.user_id,
+            today,
+        )
+        .await?;
 
         let start_local = today.and_time(work_start);
         let work_start_dt = tz
@@ -837,7 +1095,7 @@ pub async fn check_out(
             .single()
             .unwrap_or_else(|| tz.from_local_datetime(&start_local).earliest().unwrap());
 
-        let apel_deadline = work_start_dt + Duration::hours(EARLY_CHECKIN_HOURS);
+        let apel_deadline = work_start_dt + Duration::hours(rules.early_checkin_hours);
         if local_now <= apel_deadline {
             let _ = app_state
                 .db_client
@@ -854,13 +1112,16 @@ pub async fn check_out(
     }
 
     let attendance_dto = AttendanceDto {
-        session_id: updated.id,
-        work_date: updated.work_date,
-        check_in_at: updated.check_in_at,
-        check_out_at: updated.check_out_at,
+        session_id: closed.session.id,
+        work_date: closed.session.work_date,
+        check_in_at: closed.session.check_in_at,
+        check_out_at: closed.session.check_out_at,
         geofence_id: Some(geofence_id),
         distance_to_fence_m: Some(distance_m),
-        geofence_name: Some(fence_row.unwrap().name),
+        geofence_name: fence_row.map(|f| f.name),
+        segment_seq: closed.seq,
+        face_verification: face_check,
+        location_proof,
     };
 
     let response = AttendanceResp {
@@ -871,6 +1132,34 @@ pub async fn check_out(
     Ok(Json(response))
 }
 
+async fn session_segments(
+    app_state: &Arc<AppState>,
+    session_id: Option<Uuid>,
+) -> Result<Vec<AttendanceSegmentDto>, HttpError> {
+    match session_id {
+        Some(id) => app_state
+            .db_client
+            .list_attendance_segments(id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string())),
+        None => Ok(Vec::new()),
+    }
+}
+
+async fn session_away_periods(
+    app_state: &Arc<AppState>,
+    session_id: Option<Uuid>,
+) -> Result<Vec<AttendanceAwayPeriodDto>, HttpError> {
+    match session_id {
+        Some(id) => app_state
+            .db_client
+            .list_attendance_away_periods(id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string())),
+        None => Ok(Vec::new()),
+    }
+}
+
 /// Endpoint untuk kebutuhan mobile attendance card.
 ///
 /// Aturan:
@@ -883,6 +1172,7 @@ pub async fn get_attendance_session_today(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(user_claims): Extension<AuthMiddleware>,
 ) -> Result<impl IntoResponse, HttpError> {
+    let rules = get_attendance_rules_cached(&app_state, user_claims.user_claims.satker_id).await?;
     let tz = get_timezone_cached(&app_state).await?;
     let now = Utc::now();
     let local_now = now.with_timezone(&tz);
@@ -917,6 +1207,9 @@ pub async fn get_attendance_session_today(
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?
     {
+        let segments = session_segments(&app_state, Some(s.id)).await?;
+        let away_periods = session_away_periods(&app_state, Some(s.id)).await?;
+
         // Jika user sudah check-in lebih awal untuk duty yang start hari ini (mis. 18:35),
         // tetap tampilkan konteks duty agar user tidak bingung.
         if let Some(duty_today) = find_duty_by_local_date(
@@ -927,7 +1220,7 @@ pub async fn get_attendance_session_today(
             today,
         )
         .await?
-            && duty_today.end_at + Duration::minutes(DUTY_CARD_GRACE_MINUTES) > now
+            && duty_today.end_at + Duration::minutes(rules.duty_card_grace_minutes) > now
         {
             let duty_work_date = duty_today.start_at.with_timezone(&tz).date_naive();
             if duty_work_date == s.work_date {
@@ -940,6 +1233,8 @@ pub async fn get_attendance_session_today(
                         is_duty: true,
                         duty_start_at: Some(duty_today.start_at),
                         duty_end_at: Some(duty_today.end_at),
+                        segments,
+                        away_periods,
                     },
                 };
                 return Ok(Json(resp));
@@ -955,6 +1250,8 @@ pub async fn get_attendance_session_today(
                 is_duty: false,
                 duty_start_at: None,
                 duty_end_at: None,
+                segments,
+                away_periods,
             },
         };
         return Ok(Json(resp));
@@ -967,6 +1264,7 @@ pub async fn get_attendance_session_today(
         user_claims.user_claims.satker_id,
         user_claims.user_claims.user_id,
         now,
+        rules.duty_card_grace_minutes,
     )
     .await?
     {
@@ -977,6 +1275,8 @@ pub async fn get_attendance_session_today(
             .await
             .map_err(|e| HttpError::server_error(e.to_string()))?;
 
+        let segments = session_segments(&app_state, sess.as_ref().map(|s| s.id)).await?;
+        let away_periods = session_away_periods(&app_state, sess.as_ref().map(|s| s.id)).await?;
         let (check_in_at, check_out_at) = sess
             .map(|s| (s.check_in_at, s.check_out_at))
             .unwrap_or((None, None));
@@ -990,6 +1290,8 @@ pub async fn get_attendance_session_today(
                 is_duty: true,
                 duty_start_at: Some(duty_active.start_at),
                 duty_end_at: Some(duty_active.end_at),
+                segments,
+                away_periods,
             },
         };
         return Ok(Json(resp));
@@ -1004,7 +1306,7 @@ pub async fn get_attendance_session_today(
         today,
     )
     .await?
-        && duty_today.end_at + Duration::minutes(DUTY_CARD_GRACE_MINUTES) > now
+        && duty_today.end_at + Duration::minutes(rules.duty_card_grace_minutes) > now
     {
         let duty_work_date = duty_today.start_at.with_timezone(&tz).date_naive();
         let sess = app_state
@@ -1013,6 +1315,8 @@ pub async fn get_attendance_session_today(
             .await
             .map_err(|e| HttpError::server_error(e.to_string()))?;
 
+        let segments = session_segments(&app_state, sess.as_ref().map(|s| s.id)).await?;
+        let away_periods = session_away_periods(&app_state, sess.as_ref().map(|s| s.id)).await?;
         let (check_in_at, check_out_at) = sess
             .map(|s| (s.check_in_at, s.check_out_at))
             .unwrap_or((None, None));
@@ -1026,6 +1330,8 @@ pub async fn get_attendance_session_today(
                 is_duty: true,
                 duty_start_at: Some(duty_today.start_at),
                 duty_end_at: Some(duty_today.end_at),
+                segments,
+                away_periods,
             },
         };
         return Ok(Json(resp));
@@ -1051,7 +1357,7 @@ pub async fn get_attendance_session_today(
         && s.check_in_at.is_some()
         && s.check_out_at.is_none()
         && {
-            let grace_until = duty_yesterday.end_at + Duration::hours(DUTY_GRACE_HOURS);
+            let grace_until = duty_yesterday.end_at + Duration::hours(rules.duty_grace_hours);
             now <= grace_until
         }
     {
@@ -1064,6 +1370,8 @@ pub async fn get_attendance_session_today(
                 is_duty: true,
                 duty_start_at: Some(duty_yesterday.start_at),
                 duty_end_at: Some(duty_yesterday.end_at),
+                segments: session_segments(&app_state, Some(s.id)).await?,
+                away_periods: session_away_periods(&app_state, Some(s.id)).await?,
             },
         };
         return Ok(Json(resp));
@@ -1079,11 +1387,13 @@ pub async fn get_attendance_session_today(
             is_duty: false,
             duty_start_at: None,
             duty_end_at: None,
+            segments: Vec::new(),
+            away_periods: Vec::new(),
         },
     };
     Ok(Json(resp))
 }
-async fn nearest_geofence(
+pub async fn nearest_geofence(
     app_state: &Arc<AppState>,
     satker_id: Uuid,
     lat: f64,
@@ -1113,6 +1423,38 @@ async fn nearest_geofence(
     Ok(best)
 }
 
+/// Geofence acuan check-in/out: geofence milik penanda Wi-Fi/BLE yang cocok, atau geofence
+/// terdekat. Return (geofence_id, jarak, radius, di luar geofence).
+async fn resolve_event_fence(
+    app_state: &Arc<AppState>,
+    rules: &AttendanceRules,
+    satker_id: Uuid,
+    lat: f64,
+    lon: f64,
+    proximity: Option<&ProximityMarkerMatch>,
+) -> Result<(Uuid, f64, i32, bool), HttpError> {
+    if let Some(m) = proximity {
+        let distance_m = haversine_m(lat, lon, m.latitude, m.longitude);
+        // substitusi: penanda cukup sebagai bukti berada di lokasi
+        let out_of_fence = !rules.proximity_substitutes_gps && distance_m > m.radius_meters as f64;
+        return Ok((m.geofence_id, distance_m, m.radius_meters, out_of_fence));
+    }
+
+    let nearest = nearest_geofence(app_state, satker_id, lat, lon)
+        .await
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+    let (geofence_id, distance_m, radius_m) = nearest.ok_or(HttpError::bad_request(
+        "geofence aktif belum diset untuk satker ini".to_string(),
+    ))?;
+
+    Ok((
+        geofence_id,
+        distance_m,
+        radius_m,
+        distance_m > radius_m as f64,
+    ))
+}
+
 /*pub async fn check_in(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(user_claims): Extension<AuthMiddleware>,
@@ -1561,6 +1903,11 @@ pub async fn find_attendance(
         dto.check_out_device_model = row.check_out_device_model;
         dto.check_in_device_name = row.check_in_device_name;
         dto.check_out_device_name = row.check_out_device_name;
+        dto.worked_minutes = row.worked_minutes;
+        dto.segments = row.segments;
+        dto.break_minutes = row.break_minutes;
+        dto.field_task_minutes = row.field_task_minutes;
+        dto.away_periods = row.away_periods;
     } else {
         dto.session_id = Uuid::new_v4();
         dto.work_date = work_date;
@@ -1586,24 +1933,42 @@ pub async fn list_attendances(
         .validate()
         .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
 
-    let rows = if let Some(uid) = query_params.user_id {
-        app_state
-            .db_client
-            .list_attendance_by_user_from_to(uid, query_params.from, query_params.to)
-            .await
-            .map_err(|e| HttpError::server_error(e.to_string()))?
-    } else {
-        app_state
-            .db_client
-            .list_attendance_by_user_from_to(
-                user_claims.user_claims.user_id,
-                query_params.from,
-                query_params.to,
-            )
-            .await
-            .map_err(|e| HttpError::server_error(e.to_string()))?
+    let claims = &user_claims.user_claims;
+    let user_id = match query_params.user_id {
+        Some(uid) if uid != claims.user_id => {
+            // user lain: superadmin bebas, admin/kepala hanya anggota satker sendiri
+            match claims.role {
+                UserRole::Superadmin => {}
+                UserRole::SatkerAdmin | UserRole::SatkerHead => {
+                    let target = app_state
+                        .db_client
+                        .find_user_by_id(uid)
+                        .await
+                        .map_err(|e| HttpError::server_error(e.to_string()))?
+                        .ok_or(HttpError::bad_request("user tidak ditemukan".to_string()))?;
+                    if target.satker_id != claims.satker_id {
+                        return Err(HttpError::unauthorized(
+                            "Hanya boleh melihat absensi anggota satker sendiri".to_string(),
+                        ));
+                    }
+                }
+                UserRole::Member => {
+                    return Err(HttpError::unauthorized(
+                        "Tidak boleh melihat absensi user lain".to_string(),
+                    ));
+                }
+            }
+            uid
+        }
+        _ => claims.user_id,
     };
 
+    let rows = app_state
+        .db_client
+        .list_attendance_by_user_from_to(user_id, query_params.from, query_params.to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
     let response = AttendanceRekapsDtoResp {
         status: "200",
         data: rows,
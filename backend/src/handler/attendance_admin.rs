@@ -1,16 +1,20 @@
 use crate::AppState;
 use crate::auth::rbac::UserRole;
 use crate::constants::{AttendanceEventType, AttendanceLeaveType};
-use crate::database::attendance::{AddAttendanceEvent, AttendanceEventRepo};
+use crate::database::attendance::{AddAttendanceEvent, AttendanceEventRepo, AttendanceListFilter};
 use crate::database::attendance_session::AttendanceSessionRepo;
 use crate::database::user::UserRepo;
 use crate::dtos::attendance::{AttendanceRekapDto, AttendanceRekapDtoResp};
-use crate::dtos::attendance_admin::AttendanceAdminResp;
+use crate::dtos::attendance_admin::{
+    AttendanceAdminListDto, AttendanceAdminListQuery, AttendanceAdminListResp, AttendanceAdminResp,
+    AttendanceListCursor, SortOrder,
+};
 use crate::error::HttpError;
 use crate::middleware::auth_middleware::AuthMiddleware;
-use axum::extract::Path;
+use crate::utils::timezone_cache::get_timezone_cached;
+use axum::extract::{Path, Query};
 use axum::response::IntoResponse;
-use axum::routing::{delete, put};
+use axum::routing::{delete, get, put};
 use axum::{Extension, Json, Router};
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::Deserialize;
@@ -44,6 +48,7 @@ pub struct UpsertAttendanceAdminReq {
 
 pub fn attendance_admin_handler() -> Router {
     Router::new()
+        .route("/list", get(list_admin))
         .route("/{user_id}/{work_date}", put(upsert_admin))
         .route("/{user_id}/{work_date}", delete(delete_admin))
 }
@@ -68,6 +73,107 @@ fn ensure_attendance_admin_access(
     }
 }
 
+/// Listing absensi satker untuk admin/kepala (superadmin: semua satker atau `satker_id`).
+/// Keyset pagination: kirim `next_cursor` sebagai `cursor` dengan sort & filter yang sama.
+pub async fn list_admin(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Query(query): Query<AttendanceAdminListQuery>,
+) -> Result<impl IntoResponse, HttpError> {
+    query
+        .validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let claims = &user_claims.user_claims;
+    let satker_id = match claims.role {
+        UserRole::Superadmin => query.satker_id,
+        UserRole::SatkerAdmin | UserRole::SatkerHead => Some(claims.satker_id),
+        UserRole::Member => {
+            return Err(HttpError::unauthorized(
+                "Hanya SUPERADMIN / SATKER_ADMIN / SATKER_HEAD yang dapat melihat absensi satker"
+                    .to_string(),
+            ));
+        }
+    };
+
+    let tz = get_timezone_cached(&app_state).await?;
+    let tz_name = tz.name();
+    let filter = AttendanceListFilter {
+        satker_id,
+        user_id: query.user_id,
+        from: query.from,
+        to: query.to,
+        status: query.status,
+        late_only: query.late_only.unwrap_or(false),
+        missing_checkout: query.missing_checkout.unwrap_or(false),
+        out_of_geofence: query.out_of_geofence.unwrap_or(false),
+        manual_only: query.manual_only.unwrap_or(false),
+        leave_type: query.leave_type,
+        tz: tz_name,
+        today: Utc::now().with_timezone(&tz).date_naive(),
+    };
+    let limit = query.limit.unwrap_or(50);
+
+    let sort = query.sort.unwrap_or_default();
+    let order = query.order.unwrap_or_default();
+
+    // cursor asing (urutan lain, atau di luar scope) bikin halaman kosong diam-diam, tolak di depan
+    let cursor = match query.cursor.as_deref() {
+        Some(raw) => {
+            let session_id = AttendanceListCursor::decode(raw, sort, order).ok_or_else(|| {
+                HttpError::bad_request("cursor tidak valid untuk sort & order ini".to_string())
+            })?;
+            let exists = app_state
+                .db_client
+                .attendance_admin_cursor_exists(&filter, session_id)
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?;
+            if !exists {
+                return Err(HttpError::bad_request(
+                    "cursor tidak dikenal untuk filter ini".to_string(),
+                ));
+            }
+            Some(session_id)
+        }
+        None => None,
+    };
+
+    // ambil satu baris lebih untuk tahu masih ada halaman berikutnya
+    let mut items = app_state
+        .db_client
+        .list_attendance_admin(&filter, sort, order == SortOrder::Desc, cursor, limit + 1)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let next_cursor = if items.len() as i64 > limit {
+        items.truncate(limit as usize);
+        items.last().map(|r| {
+            AttendanceListCursor {
+                sort,
+                order,
+                session_id: r.session_id,
+            }
+            .encode()
+        })
+    } else {
+        None
+    };
+
+    let counts = app_state
+        .db_client
+        .count_attendance_admin(&filter)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(AttendanceAdminListResp {
+        status: "200",
+        data: AttendanceAdminListDto {
+            items,
+            counts,
+            next_cursor,
+        },
+    }))
+}
+
 pub async fn upsert_admin(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(user_claims): Extension<AuthMiddleware>,
@@ -112,7 +218,7 @@ pub async fn upsert_admin(
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
     // Overwrite times + mark as manual correction
-    app_state
+    let segment_id = app_state
         .db_client
         .admin_set_attendance_session(
             session_id,
@@ -139,6 +245,7 @@ pub async fn upsert_admin(
     if let Some(ts) = payload.check_in_at {
         let add_row = AddAttendanceEvent {
             session_id,
+            segment_id,
             satker_id: user.satker_id,
             user_id,
             event_type: AttendanceEventType::CheckIn,
@@ -151,6 +258,13 @@ pub async fn upsert_admin(
             selfie_object_key: None,
             liveness_score: None,
             face_match_score: None,
+            server_face_score: None,
+            face_verification_status: None,
+            selfie_captured_at: None,
+            selfie_phash: None,
+            location_proof: None,
+            proximity_marker_id: None,
+            attestation: None,
             device_id: payload.device_id.clone(),
             client_version: payload.client_version.clone(),
             device_model: payload.device_model.clone(),
@@ -161,6 +275,9 @@ pub async fn upsert_admin(
                 .check_in_leave_type
                 .unwrap_or(AttendanceLeaveType::Normal),
             attendance_leave_notes: payload.check_in_leave_notes.clone(),
+            away_period_id: None,
+            destination: None,
+            purpose: None,
         };
 
         app_state
@@ -173,6 +290,7 @@ pub async fn upsert_admin(
     if let Some(ts) = payload.check_out_at {
         let add_row = AddAttendanceEvent {
             session_id,
+            segment_id,
             satker_id: user.satker_id,
             user_id,
             event_type: AttendanceEventType::CheckOut,
@@ -185,6 +303,13 @@ pub async fn upsert_admin(
             selfie_object_key: None,
             liveness_score: None,
             face_match_score: None,
+            server_face_score: None,
+            face_verification_status: None,
+            selfie_captured_at: None,
+            selfie_phash: None,
+            location_proof: None,
+            proximity_marker_id: None,
+            attestation: None,
             device_id: payload.device_id.clone(),
             client_version: payload.client_version.clone(),
             device_model: payload.device_model.clone(),
@@ -195,6 +320,9 @@ pub async fn upsert_admin(
                 .check_out_leave_type
                 .unwrap_or(AttendanceLeaveType::Normal),
             attendance_leave_notes: payload.check_out_leave_notes.clone(),
+            away_period_id: None,
+            destination: None,
+            purpose: None,
         };
 
         app_state
@@ -347,7 +475,7 @@ pub async fn upsert_admin(
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
     // Overwrite times + mark as manual correction
-    app_state
+    let segment_id = app_state
         .db_client
         .admin_set_attendance_session(
             session_id,
@@ -374,6 +502,7 @@ pub async fn upsert_admin(
     if let Some(ts) = payload.check_in_at {
         let add_row = AddAttendanceEvent {
             session_id,
+            segment_id,
             satker_id: user.satker_id,
             user_id,
             event_type: AttendanceEventType::CheckIn,
@@ -406,6 +535,7 @@ pub async fn upsert_admin(
     if let Some(ts) = payload.check_out_at {
         let add_row = AddAttendanceEvent {
             session_id,
+            segment_id,
             satker_id: user.satker_id,
             user_id,
             event_type: AttendanceEventType::CheckOut,
@@ -0,0 +1,147 @@
+use axum::extract::{Path, Query};
+use axum::response::IntoResponse;
+use axum::routing::{get, put};
+use axum::{Extension, Json, Router};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::auth::rbac::UserRole;
+use crate::database::attendance_anomaly::AttendanceAnomalyRepo;
+use crate::dtos::SuccessResponse;
+use crate::dtos::attendance_anomaly::{
+    ANOMALY_PENDING, AttendanceAnomaliesResp, AttendanceAnomalyResp, ConfirmAttendanceAnomalyReq,
+    InvalidateAttendanceAnomalyReq, ListAttendanceAnomaliesQuery,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::attendance_anomaly::{AnomalyReviewAction, review_attendance_anomaly};
+use crate::services::authorization::ensure_can_access_satker;
+
+pub fn attendance_anomaly_handler() -> Router {
+    Router::new()
+        .route("/", get(list_anomalies))
+        .route("/{id}", get(get_anomaly))
+        .route("/{id}/confirm", put(confirm_anomaly))
+        .route("/{id}/invalidate", put(invalidate_anomaly))
+}
+
+/// Antrian tinjauan (admin/kepala: satker sendiri; superadmin: semua / filter). Default PENDING.
+pub async fn list_anomalies(
+    Query(query): Query<ListAttendanceAnomaliesQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+    let satker_id = match claims.role {
+        UserRole::Superadmin => query.satker_id,
+        UserRole::SatkerAdmin | UserRole::SatkerHead => Some(claims.satker_id),
+        UserRole::Member => return Err(HttpError::unauthorized("Tidak boleh".to_string())),
+    };
+
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from > to
+    {
+        return Err(HttpError::bad_request("from tidak boleh setelah to"));
+    }
+
+    // status=ALL untuk semua status
+    let status = query
+        .status
+        .map(|v| v.trim().to_uppercase())
+        .unwrap_or_else(|| ANOMALY_PENDING.to_string());
+    let status = (status != "ALL").then_some(status);
+
+    let rows = app_state
+        .db_client
+        .list_attendance_anomalies(
+            satker_id,
+            query.user_id,
+            status.as_deref(),
+            query.from,
+            query.to,
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(AttendanceAnomaliesResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+pub async fn get_anomaly(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let row = app_state
+        .db_client
+        .find_attendance_anomaly(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("anomali absensi tidak ditemukan"))?;
+    ensure_can_access_satker(&user_claims.user_claims, row.satker_id)?;
+
+    Ok(Json(AttendanceAnomalyResp {
+        status: "200",
+        data: row,
+    }))
+}
+
+/// Absensi dinyatakan sah; sesi tidak berubah.
+pub async fn confirm_anomaly(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ConfirmAttendanceAnomalyReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    let note = payload
+        .review_note
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    review_attendance_anomaly(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        id,
+        AnomalyReviewAction::Confirm,
+        note,
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully confirmed attendance".to_string(),
+    }))
+}
+
+/// Absensi dibatalkan; sesi menjadi INVALID.
+pub async fn invalidate_anomaly(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<InvalidateAttendanceAnomalyReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    review_attendance_anomaly(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        id,
+        AnomalyReviewAction::Invalidate,
+        Some(payload.review_note.trim().to_string()),
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully invalidated attendance".to_string(),
+    }))
+}
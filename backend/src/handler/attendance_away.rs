@@ -0,0 +1,319 @@
+//! Istirahat & tugas lapangan (keluar kantor) di dalam segmen check-in/out.
+//! Event divalidasi seperti check-in (device, mock, akurasi, challenge, anti-teleport);
+//! geofence tidak memblokir, tapi jarak & status di luar geofence dicatat untuk review admin.
+
+use crate::AppState;
+use crate::constants::{AttendanceEventType, AttendanceLeaveType};
+use crate::database::attendance::{AddAttendanceEvent, AttendanceEventRepo};
+use crate::database::attendance_away::{AttendanceAwayRepo, AttendanceAwayStart};
+use crate::database::attendance_session::AttendanceSessionRepo;
+use crate::database::geofence::GeofenceRepo;
+use crate::dtos::attendance::{AttendanceAwayDto, AttendanceAwayResp, AttendanceReq};
+use crate::dtos::attendance_session::{AWAY_BREAK, AWAY_FIELD_TASK};
+use crate::dtos::user_device::DeviceInfo;
+use crate::error::HttpError;
+use crate::handler::attendance::nearest_geofence;
+use crate::handler::attendance_challenge::{anti_teleport_check, validate_and_use_challenge};
+use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::metrics::{
+    RejectReason, record_attendance_rejection, record_attendance_result,
+};
+use crate::services::user_device::ensure_device_owned;
+use crate::utils::attendance_rules_cache::get_attendance_rules_cached;
+use crate::utils::timezone_cache::get_timezone_cached;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use chrono::Utc;
+use std::sync::Arc;
+use validator::Validate;
+
+/// Jenis periode away & apakah event ini membuka (true) atau menutupnya.
+fn away_kind(event: AttendanceEventType) -> (&'static str, bool) {
+    match event {
+        AttendanceEventType::BreakStart => (AWAY_BREAK, true),
+        AttendanceEventType::BreakEnd => (AWAY_BREAK, false),
+        AttendanceEventType::FieldOut => (AWAY_FIELD_TASK, true),
+        _ => (AWAY_FIELD_TASK, false),
+    }
+}
+
+fn away_label(kind: &str) -> &'static str {
+    if kind == AWAY_BREAK {
+        "istirahat"
+    } else {
+        "tugas lapangan"
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+pub async fn break_start(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(payload): Json<AttendanceReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let event = AttendanceEventType::BreakStart;
+    let res = process_away(app_state, user_claims, payload, event).await;
+    record_attendance_result(event, &res);
+    res
+}
+
+pub async fn break_end(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(payload): Json<AttendanceReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let event = AttendanceEventType::BreakEnd;
+    let res = process_away(app_state, user_claims, payload, event).await;
+    record_attendance_result(event, &res);
+    res
+}
+
+pub async fn field_out(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(payload): Json<AttendanceReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let event = AttendanceEventType::FieldOut;
+    let res = process_away(app_state, user_claims, payload, event).await;
+    record_attendance_result(event, &res);
+    res
+}
+
+pub async fn field_in(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(payload): Json<AttendanceReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let event = AttendanceEventType::FieldIn;
+    let res = process_away(app_state, user_claims, payload, event).await;
+    record_attendance_result(event, &res);
+    res
+}
+
+async fn process_away(
+    app_state: Arc<AppState>,
+    user_claims: AuthMiddleware,
+    payload: AttendanceReq,
+    event: AttendanceEventType,
+) -> Result<Json<AttendanceAwayResp>, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let rules = get_attendance_rules_cached(&app_state, user_claims.user_claims.satker_id).await?;
+    let (kind, opens) = away_kind(event);
+    let label = away_label(kind);
+
+    // tugas lapangan: tujuan & keperluan wajib saat berangkat
+    let (destination, purpose) = if event == AttendanceEventType::FieldOut {
+        let destination = non_empty(payload.destination.clone())
+            .ok_or_else(|| HttpError::bad_request("tujuan tugas lapangan wajib diisi"))?;
+        let purpose = non_empty(payload.purpose.clone())
+            .ok_or_else(|| HttpError::bad_request("keperluan tugas lapangan wajib diisi"))?;
+        (Some(destination), Some(purpose))
+    } else {
+        (None, None)
+    };
+
+    let device_id = payload
+        .device_id
+        .clone()
+        .ok_or(HttpError::bad_request("device_id wajib".to_string()))?;
+
+    ensure_device_owned(
+        &app_state.db_client,
+        event,
+        user_claims.user_claims.user_id,
+        &DeviceInfo {
+            device_id: device_id.clone(),
+            device_model: payload.device_model.clone(),
+            android_version: payload.android_version.clone(),
+            app_build: payload.app_build.clone(),
+            client_version: payload.client_version.clone(),
+            public_key: payload.public_key.clone(),
+        },
+    )
+    .await?;
+
+    if payload.is_mock.unwrap_or(false) {
+        record_attendance_rejection(event, RejectReason::MockLocation);
+        return Err(HttpError::bad_request(
+            "mock location terdeteksi".to_string(),
+        ));
+    }
+
+    if let Some(acc) = payload.accuracy_meters
+        && acc > rules.max_accuracy_meters
+    {
+        record_attendance_rejection(event, RejectReason::Accuracy);
+        return Err(HttpError::bad_request(
+            "akurasi lokasi terlalu rendah, silakan coba lagi".to_string(),
+        ));
+    }
+
+    let attestation = validate_and_use_challenge(
+        &app_state,
+        &rules,
+        &user_claims.user_claims,
+        event,
+        &payload,
+        &device_id,
+    )
+    .await?;
+
+    anti_teleport_check(
+        &app_state,
+        &rules,
+        event,
+        user_claims.user_claims.user_id,
+        &device_id,
+        payload.latitude,
+        payload.longitude,
+    )
+    .await?;
+
+    let nearest = nearest_geofence(
+        &app_state,
+        user_claims.user_claims.satker_id,
+        payload.latitude,
+        payload.longitude,
+    )
+    .await
+    .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let (geofence_id, distance_m, radius_m) = nearest.ok_or(HttpError::bad_request(
+        "geofence aktif belum diset untuk satker ini".to_string(),
+    ))?;
+    let out_of_fence = distance_m > radius_m as f64;
+
+    let now = Utc::now();
+    let tz = get_timezone_cached(&app_state).await?;
+    let today = now.with_timezone(&tz).date_naive();
+
+    // segmen terbuka: hari ini, atau shift lintas hari yang dimulai kemarin
+    let segment = app_state
+        .db_client
+        .find_open_attendance_segment(
+            user_claims.user_claims.user_id,
+            today - chrono::Days::new(1),
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("anda belum check-in"))?;
+
+    let away_id = if opens {
+        if let Some(open) = app_state
+            .db_client
+            .find_open_attendance_away(segment.session_id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?
+        {
+            return Err(HttpError::bad_request(format!(
+                "anda masih {} sejak {}, silakan akhiri terlebih dahulu",
+                away_label(&open.kind),
+                open.started_at.with_timezone(&tz).format("%d %b %Y %H:%M")
+            )));
+        }
+
+        app_state
+            .db_client
+            .start_attendance_away(AttendanceAwayStart {
+                session_id: segment.session_id,
+                segment_id: segment.segment_id,
+                kind,
+                now,
+                destination: destination.clone(),
+                purpose: purpose.clone(),
+            })
+            .await
+            .map_err(|e| {
+                if e.as_database_error()
+                    .is_some_and(|d| d.is_unique_violation())
+                {
+                    HttpError::bad_request("masih ada istirahat / tugas lapangan yang berjalan")
+                } else {
+                    HttpError::server_error(e.to_string())
+                }
+            })?
+    } else {
+        app_state
+            .db_client
+            .end_attendance_away(segment.session_id, kind, now)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?
+            .ok_or_else(|| HttpError::bad_request(format!("anda tidak sedang {}", label)))?
+    };
+
+    let add_row = AddAttendanceEvent {
+        session_id: segment.session_id,
+        segment_id: Some(segment.segment_id),
+        satker_id: user_claims.user_claims.satker_id,
+        user_id: user_claims.user_claims.user_id,
+        event_type: event,
+        now,
+        latitude: Some(payload.latitude),
+        longitude: Some(payload.longitude),
+        accuracy_meters: payload.accuracy_meters,
+        geofence_id: Some(geofence_id),
+        distance_to_fence_m: Some(distance_m),
+        selfie_object_key: payload.selfie_object_key,
+        liveness_score: payload.liveness_score,
+        face_match_score: payload.face_match_score,
+        server_face_score: None,
+        face_verification_status: None,
+        selfie_captured_at: None,
+        selfie_phash: None,
+        location_proof: None,
+        proximity_marker_id: None,
+        attestation: attestation.map(str::to_string),
+        device_id: payload.device_id,
+        client_version: payload.client_version,
+        server_challenge_id: Some(payload.challenge_id),
+        device_model: payload.device_model,
+        android_version: payload.android_version,
+        app_build: payload.app_build,
+        attendance_leave_type: AttendanceLeaveType::Normal,
+        attendance_leave_notes: None,
+        away_period_id: Some(away_id),
+        destination,
+        purpose,
+    };
+
+    app_state
+        .db_client
+        .add_attendance_event(add_row)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let period = app_state
+        .db_client
+        .find_attendance_away(away_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::server_error("periode away gagal dibaca ulang"))?;
+
+    let fence_row = app_state
+        .db_client
+        .find_geofence(geofence_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(AttendanceAwayResp {
+        status: "200",
+        data: AttendanceAwayDto {
+            session_id: segment.session_id,
+            work_date: segment.work_date,
+            segment_seq: segment.seq,
+            geofence_id: Some(geofence_id),
+            distance_to_fence_m: Some(distance_m),
+            geofence_name: fence_row.map(|g| g.name),
+            out_of_fence,
+            period,
+        },
+    }))
+}
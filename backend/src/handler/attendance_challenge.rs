@@ -1,7 +1,16 @@
 use crate::AppState;
+use crate::constants::AttendanceEventType;
+use crate::dtos::attendance::AttendanceReq;
 use crate::dtos::attendance_challenge::{ChallengeDto, ChallengePayload, ChallengeResp, LastLoc};
+use crate::dtos::settings::AttendanceRules;
 use crate::error::{ErrorMessage, HttpError};
 use crate::middleware::auth_middleware::{AuthMiddleware, UserClaims};
+use crate::services::attestation::verify_request_attestation;
+use crate::services::metrics::{
+    ChallengeFailure, RejectReason, record_attendance_rejection, record_challenge_failure,
+    record_challenge_request, record_redis_error,
+};
+use crate::utils::attendance_rules_cache::get_attendance_rules_cached;
 use axum::http::HeaderMap;
 use axum::response::IntoResponse;
 use axum::routing::post;
@@ -15,6 +24,14 @@ pub fn attendance_challenge_handler() -> Router {
     Router::new().route("/", post(create_challenge))
 }
 
+/// map_err helper: catat error redis (metric `redis_errors_total{op}`) lalu jadikan 500.
+fn redis_err(op: &'static str, msg: &'static str) -> impl FnOnce(redis::RedisError) -> HttpError {
+    move |_| {
+        record_redis_error(op);
+        HttpError::server_error(msg.to_string())
+    }
+}
+
 fn header_string(headers: &HeaderMap, key: &str) -> Option<String> {
     headers
         .get(key)?
@@ -49,39 +66,42 @@ pub async fn create_challenge(
             "terlalu banyak request challenge, coba lagi sebentar".to_string(),
         ));
     }*/
+    let rules = get_attendance_rules_cached(&app_state, user_claims.user_claims.satker_id).await?;
     let mut con = app_state.redis_client.clone();
 
-    // per user: 10/60s
+    // per user: challenge_user_limit_per_min/60s
     let rl_user_key = format!("att_chal_rl:user:{}", user_claims.user_claims.user_id);
     let user_cnt: i64 = con
         .incr(&rl_user_key, 1)
         .await
-        .map_err(|_| HttpError::server_error("Save to redis error".to_string()))?;
+        .map_err(redis_err("challenge_rate_limit", "Save to redis error"))?;
     if user_cnt == 1 {
         let _: () = con
             .expire(&rl_user_key, 60)
             .await
-            .map_err(|_| HttpError::server_error("Save to redis error".to_string()))?;
+            .map_err(redis_err("challenge_rate_limit", "Save to redis error"))?;
     }
-    if user_cnt > 10 {
+    if user_cnt > rules.challenge_user_limit_per_min {
+        record_challenge_request("rate_limited_user");
         return Err(HttpError::too_many_requests(
             "terlalu banyak request challenge, coba lagi sebentar".to_string(),
         ));
     }
 
-    // per device: 6/60s
+    // per device: challenge_device_limit_per_min/60s
     let rl_dev_key = format!("att_chal_rl:dev:{device_id}");
     let dev_cnt: i64 = con
         .incr(&rl_dev_key, 1)
         .await
-        .map_err(|_| HttpError::server_error("Save to redis error".to_string()))?;
+        .map_err(redis_err("challenge_rate_limit", "Save to redis error"))?;
     if dev_cnt == 1 {
         let _: () = con
             .expire(&rl_dev_key, 60)
             .await
-            .map_err(|_| HttpError::server_error("Save to redis error".to_string()))?;
+            .map_err(redis_err("challenge_rate_limit", "Save to redis error"))?;
     }
-    if dev_cnt > 6 {
+    if dev_cnt > rules.challenge_device_limit_per_min {
+        record_challenge_request("rate_limited_device");
         return Err(HttpError::too_many_requests(
             "terlalu banyak request challenge dari device ini, coba lagi sebentar".to_string(),
         ));
@@ -89,7 +109,7 @@ pub async fn create_challenge(
 
     let challenge_id = Uuid::new_v4();
     let nonce = Uuid::new_v4().to_string();
-    let expires_at = Utc::now() + chrono::Duration::seconds(60);
+    let expires_at = Utc::now() + chrono::Duration::seconds(rules.challenge_ttl_secs);
 
     let payload = ChallengePayload {
         user_id: user_claims.user_claims.user_id,
@@ -105,9 +125,13 @@ pub async fn create_challenge(
 
     let mut con = app_state.redis_client.clone();
 
-    let _: () = con.set_ex(key, val, 60).await.map_err(|_| {
-        HttpError::server_error("Failed to save challenge to redis server".to_string())
-    })?;
+    let ttl = rules.challenge_ttl_secs as u64;
+    let _: () = con.set_ex(key, val, ttl).await.map_err(redis_err(
+        "challenge_store",
+        "Failed to save challenge to redis server",
+    ))?;
+
+    record_challenge_request("issued");
 
     let challenge_dto = ChallengeDto {
         challenge_id,
@@ -123,12 +147,17 @@ pub async fn create_challenge(
     Ok(Json(response))
 }
 
+/// Pakai challenge (sekali pakai) lalu verifikasi attestation request terhadap nonce-nya.
+/// Return nama verifier kalau request ter-attestasi.
 pub async fn validate_and_use_challenge(
     app_state: &Arc<AppState>,
+    rules: &AttendanceRules,
     user_claims: &UserClaims,
-    challenge_id: Uuid,
+    event: AttendanceEventType,
+    payload: &AttendanceReq,
     device_id: &str,
-) -> Result<(), HttpError> {
+) -> Result<Option<&'static str>, HttpError> {
+    let challenge_id = payload.challenge_id;
     let key = format!("att_chal:{challenge_id}");
 
     // Lua:
@@ -149,40 +178,53 @@ pub async fn validate_and_use_challenge(
     );
 
     let mut con = app_state.redis_client.clone();
-    let val: Option<String> = script.key(&key).invoke_async(&mut con).await.map_err(|_| {
-        HttpError::server_error("Failed to retrieve challenge from redis server".to_string())
+    let val: Option<String> = script
+        .key(&key)
+        .invoke_async(&mut con)
+        .await
+        .map_err(redis_err(
+            "challenge_consume",
+            "Failed to retrieve challenge from redis server",
+        ))?;
+
+    let val = val.ok_or_else(|| {
+        record_challenge_failure(ChallengeFailure::NotFoundOrUsed);
+        HttpError::bad_request(
+            "challenge tidak ditemukan / sudah expired / sudah dipakai".to_string(),
+        )
     })?;
 
-    let val = val.ok_or(HttpError::bad_request(
-        "challenge tidak ditemukan / sudah expired / sudah dipakai".to_string(),
-    ))?;
-
-    let payload: ChallengePayload = serde_json::from_str(&val)
+    let challenge: ChallengePayload = serde_json::from_str(&val)
         .map_err(|_| HttpError::server_error("Failed to deserialize challenge".to_string()))?;
 
-    if payload.user_id != user_claims.user_id || payload.satker_id != user_claims.satker_id {
+    if challenge.user_id != user_claims.user_id || challenge.satker_id != user_claims.satker_id {
+        record_challenge_failure(ChallengeFailure::UserMismatch);
         return Err(HttpError::unauthorized(
             ErrorMessage::ForbiddenRequest.to_string(),
         ));
     }
 
-    if payload.device_id != device_id {
+    if challenge.device_id != device_id {
+        record_challenge_failure(ChallengeFailure::DeviceMismatch);
         return Err(HttpError::unauthorized(
             ErrorMessage::ForbiddenRequest.to_string(),
         ));
     }
 
-    if payload.exp_unix < Utc::now().timestamp() {
+    if challenge.exp_unix < Utc::now().timestamp() {
+        record_challenge_failure(ChallengeFailure::Expired);
         return Err(HttpError::bad_request(
             "challenge sudah expired".to_string(),
         ));
     }
 
-    Ok(())
+    verify_request_attestation(app_state, rules, event, challenge_id, &challenge, payload).await
 }
 
 pub async fn anti_teleport_check(
     app_state: &Arc<AppState>,
+    rules: &AttendanceRules,
+    event: AttendanceEventType,
     user_id: Uuid,
     device_id: &str,
     lat: f64,
@@ -196,7 +238,7 @@ pub async fn anti_teleport_check(
     let prev_json: Option<String> = con
         .get(&key)
         .await
-        .map_err(|_| HttpError::server_error("redis error".to_string()))?;
+        .map_err(redis_err("teleport_lastloc", "redis error"))?;
 
     if let Some(prev_json) = prev_json
         && let Ok(prev) = serde_json::from_str::<LastLoc>(&prev_json)
@@ -204,16 +246,18 @@ pub async fn anti_teleport_check(
         let dt = (now - prev.ts_unix).max(1);
         let dist_m = crate::utils::fungsi::haversine_m(lat, lon, prev.lat, prev.lon);
 
-        // teleport: > 5km in < 2 minutes
-        if dt < 120 && dist_m > 5000.0 {
+        // teleport: > teleport_distance_m dalam < teleport_window_secs (default 5km / 2 menit)
+        if dt < rules.teleport_window_secs && dist_m > rules.teleport_distance_m {
+            record_attendance_rejection(event, RejectReason::Teleport);
             return Err(HttpError::bad_request(
                 "terdeteksi perpindahan lokasi tidak wajar".to_string(),
             ));
         }
 
-        // speed: > 45 m/s
+        // speed: > max_speed_mps (default 45 m/s)
         let speed = dist_m / (dt as f64);
-        if speed > 45.0 {
+        if speed > rules.max_speed_mps {
+            record_attendance_rejection(event, RejectReason::Teleport);
             return Err(HttpError::bad_request(
                 "terdeteksi kecepatan perpindahan tidak wajar".to_string(),
             ));
@@ -231,7 +275,7 @@ pub async fn anti_teleport_check(
     let _: () = con
         .set_ex(key, val, 86400)
         .await
-        .map_err(|_| HttpError::server_error("redis error".to_string()))?;
+        .map_err(redis_err("teleport_lastloc", "redis error"))?;
 
     Ok(())
 }
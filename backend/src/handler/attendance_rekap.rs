@@ -0,0 +1,78 @@
+use axum::extract::Query;
+use axum::http::header::{CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use std::sync::Arc;
+
+use crate::AppState;
+use crate::auth::rbac::UserRole;
+use crate::dtos::attendance_rekap::{MonthlyRekapQuery, MonthlyRekapResp, RekapFormat};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::attendance_rekap::build_monthly_rekap;
+use crate::services::rekap_export::{
+    rekap_pdf_filename, rekap_xlsx_filename, render_rekap_pdf, render_rekap_xlsx,
+};
+use crate::utils::timezone_cache::get_timezone_cached;
+
+const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+pub fn attendance_rekap_handler() -> Router {
+    Router::new().route("/monthly", get(get_monthly_rekap))
+}
+
+/// Rekap kehadiran bulanan resmi satu satker (admin/kepala: satker sendiri;
+/// superadmin: wajib satker_id). `format=xlsx|pdf` untuk unduhan siap cetak.
+pub async fn get_monthly_rekap(
+    Query(query): Query<MonthlyRekapQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+    let satker_id = match claims.role {
+        UserRole::Superadmin => query.satker_id.ok_or(HttpError::bad_request(
+            "satker_id wajib untuk superadmin".to_string(),
+        ))?,
+        UserRole::SatkerAdmin | UserRole::SatkerHead => claims.satker_id,
+        UserRole::Member => return Err(HttpError::unauthorized("Tidak boleh".to_string())),
+    };
+
+    // IMPORTANT: timezone harus sama dengan rekap absensi (app_settings)
+    let tz = get_timezone_cached(&app_state).await?;
+    let rekap = build_monthly_rekap(&app_state.db_client, tz, satker_id, &query.month).await?;
+    let printed_on = rekap.generated_at.with_timezone(&tz).date_naive();
+
+    let (content_type, filename, body) = match query.format.unwrap_or_default() {
+        RekapFormat::Json => {
+            return Ok(Json(MonthlyRekapResp {
+                status: "200",
+                data: rekap,
+            })
+            .into_response());
+        }
+        RekapFormat::Xlsx => (
+            XLSX_CONTENT_TYPE,
+            rekap_xlsx_filename(&rekap),
+            render_rekap_xlsx(&rekap, printed_on)?,
+        ),
+        RekapFormat::Pdf => (
+            "application/pdf",
+            rekap_pdf_filename(&rekap),
+            render_rekap_pdf(&rekap, printed_on)?,
+        ),
+    };
+
+    Ok((
+        [
+            (CONTENT_TYPE, content_type.to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+            (CACHE_CONTROL, "no-store".to_string()),
+        ],
+        body,
+    )
+        .into_response())
+}
@@ -0,0 +1,152 @@
+use axum::extract::Path;
+use axum::http::header::{CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::IntoResponse;
+use axum::routing::{delete, get};
+use axum::{Extension, Json, Router};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::database::calendar_feed::CalendarFeedRepo;
+use crate::dtos::SuccessResponse;
+use crate::dtos::calendar_feed::{
+    CalendarFeedsResp, CreateCalendarFeedReq, CreatedCalendarFeedDto, CreatedCalendarFeedResp,
+    FEED_SCOPE_SATKER, FEED_SCOPE_USER,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::calendar_feed::{
+    authorize_feed_token, build_calendar_feed, calendar_feed_url, generate_feed_token,
+    resolve_new_feed_scope,
+};
+use crate::utils::timezone_cache::get_timezone_cached;
+
+/// Kelola token feed milik user yang login (butuh JWT).
+pub fn calendar_feed_handler() -> Router {
+    Router::new()
+        .route("/", get(list_calendar_feeds).post(create_calendar_feed))
+        .route("/{id}", delete(revoke_calendar_feed))
+}
+
+/// Endpoint ICS publik untuk klien kalender; autentikasi lewat token di URL.
+pub fn ics_feed_handler() -> Router {
+    Router::new()
+        .route("/users/{token}", get(get_user_ics))
+        .route("/satkers/{token}", get(get_satker_ics))
+}
+
+pub async fn list_calendar_feeds(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let rows = app_state
+        .db_client
+        .list_calendar_feeds(user_claims.user_claims.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(CalendarFeedsResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+pub async fn create_calendar_feed(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(payload): Json<CreateCalendarFeedReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    let claims = &user_claims.user_claims;
+    let (scope, satker_id) = resolve_new_feed_scope(&app_state.db_client, claims, &payload).await?;
+
+    let (token, token_hash) = generate_feed_token();
+    let label = payload
+        .label
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+
+    let id = app_state
+        .db_client
+        .create_calendar_feed(claims.user_id, scope, satker_id, &token_hash, label)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let feed = app_state
+        .db_client
+        .find_calendar_feed(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::server_error("feed kalender gagal dibaca ulang"))?;
+
+    let url = calendar_feed_url(app_state.env.public_base_url.as_deref(), scope, &token);
+
+    Ok(Json(CreatedCalendarFeedResp {
+        status: "200",
+        data: CreatedCalendarFeedDto { feed, token, url },
+    }))
+}
+
+pub async fn revoke_calendar_feed(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let revoked = app_state
+        .db_client
+        .revoke_calendar_feed(id, user_claims.user_claims.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if !revoked {
+        return Err(HttpError::bad_request(
+            "feed kalender tidak ditemukan atau sudah dicabut",
+        ));
+    }
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully revoked calendar feed".to_string(),
+    }))
+}
+
+pub async fn get_user_ics(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, HttpError> {
+    render_ics(&app_state, &token, FEED_SCOPE_USER).await
+}
+
+pub async fn get_satker_ics(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, HttpError> {
+    render_ics(&app_state, &token, FEED_SCOPE_SATKER).await
+}
+
+async fn render_ics(
+    app_state: &AppState,
+    token: &str,
+    scope: &str,
+) -> Result<impl IntoResponse + use<>, HttpError> {
+    let feed = authorize_feed_token(&app_state.db_client, token, scope).await?;
+    let tz = get_timezone_cached(app_state).await?;
+
+    let body = build_calendar_feed(&app_state.db_client, tz, &feed).await?;
+
+    // best-effort, gagal catat tidak boleh menggagalkan feed
+    let _ = app_state.db_client.touch_calendar_feed(feed.id).await;
+
+    Ok((
+        [
+            (CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (CONTENT_DISPOSITION, "inline; filename=\"jadwal.ics\""),
+            (CACHE_CONTROL, "private, max-age=300"),
+        ],
+        body,
+    ))
+}
@@ -0,0 +1,227 @@
+use crate::AppState;
+use crate::auth::rbac::UserRole;
+use crate::database::duty_rotation::DutyRotationRepo;
+use crate::dtos::SuccessResponse;
+use crate::dtos::duty_rotation::{
+    DutyRotationResp, DutyRotationTemplateDto, DutyRotationsResp, GenerateDutyRotationReq,
+    GenerateDutyRotationResp, ListDutyRotationsQuery, UpsertDutyRotationReq,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::authorization::ensure_can_manage_duty_schedules;
+use crate::services::duty_rotation::{
+    build_rotation_definition, ensure_members_in_satker, generate_duty_rotation,
+    map_rotation_save_error,
+};
+use crate::utils::timezone_cache::get_timezone_cached;
+use axum::extract::{Path, Query};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub fn duty_rotation_handler() -> Router {
+    Router::new()
+        .route("/", get(list_duty_rotations).post(create_duty_rotation))
+        .route(
+            "/{id}",
+            get(get_duty_rotation)
+                .put(update_duty_rotation)
+                .delete(delete_duty_rotation),
+        )
+        .route("/{id}/generate", post(generate_duty_rotation_schedules))
+}
+
+async fn load_manageable_rotation(
+    app_state: &AppState,
+    user_claims: &AuthMiddleware,
+    id: Uuid,
+) -> Result<DutyRotationTemplateDto, HttpError> {
+    let template = app_state
+        .db_client
+        .find_duty_rotation(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("template rotasi tidak ditemukan"))?;
+
+    ensure_can_manage_duty_schedules(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        template.satker_id,
+    )
+    .await?;
+
+    Ok(template)
+}
+
+pub async fn list_duty_rotations(
+    Query(query): Query<ListDutyRotationsQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let mut satker_id = query.satker_id;
+
+    // satker scoping: non-superadmin forced to own satker
+    if user_claims.user_claims.role != UserRole::Superadmin {
+        satker_id = Some(user_claims.user_claims.satker_id);
+    }
+
+    let rows = app_state
+        .db_client
+        .list_duty_rotations(satker_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(DutyRotationsResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+pub async fn get_duty_rotation(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let template = app_state
+        .db_client
+        .find_duty_rotation(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("template rotasi tidak ditemukan"))?;
+
+    if user_claims.user_claims.role != UserRole::Superadmin
+        && user_claims.user_claims.satker_id != template.satker_id
+    {
+        return Err(HttpError::unauthorized("forbidden"));
+    }
+
+    Ok(Json(DutyRotationResp {
+        status: "200",
+        data: template,
+    }))
+}
+
+pub async fn create_duty_rotation(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(payload): Json<UpsertDutyRotationReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+
+    // enforce satker for non-superadmin
+    let satker_id = if claims.role == UserRole::Superadmin {
+        payload
+            .satker_id
+            .ok_or_else(|| HttpError::bad_request("satker_id wajib diisi"))?
+    } else {
+        claims.satker_id
+    };
+
+    ensure_can_manage_duty_schedules(&app_state.db_client, claims, satker_id).await?;
+
+    let def = build_rotation_definition(payload)?;
+    ensure_members_in_satker(&app_state.db_client, satker_id, &def).await?;
+
+    let id = app_state
+        .db_client
+        .create_duty_rotation(satker_id, &def, claims.user_id)
+        .await
+        .map_err(map_rotation_save_error)?;
+
+    let template = app_state
+        .db_client
+        .find_duty_rotation(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::server_error("template rotasi gagal dibaca ulang"))?;
+
+    Ok(Json(DutyRotationResp {
+        status: "200",
+        data: template,
+    }))
+}
+
+pub async fn update_duty_rotation(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpsertDutyRotationReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let existing = load_manageable_rotation(&app_state, &user_claims, id).await?;
+
+    // satker template tidak bisa dipindah
+    if payload
+        .satker_id
+        .is_some_and(|satker_id| satker_id != existing.satker_id)
+    {
+        return Err(HttpError::bad_request(
+            "satker template rotasi tidak bisa diubah",
+        ));
+    }
+
+    let def = build_rotation_definition(payload)?;
+    ensure_members_in_satker(&app_state.db_client, existing.satker_id, &def).await?;
+
+    app_state
+        .db_client
+        .update_duty_rotation(id, &def)
+        .await
+        .map_err(map_rotation_save_error)?;
+
+    let template = app_state
+        .db_client
+        .find_duty_rotation(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("template rotasi tidak ditemukan"))?;
+
+    Ok(Json(DutyRotationResp {
+        status: "200",
+        data: template,
+    }))
+}
+
+pub async fn delete_duty_rotation(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    load_manageable_rotation(&app_state, &user_claims, id).await?;
+
+    app_state
+        .db_client
+        .delete_duty_rotation(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully deleted duty rotation".to_string(),
+    }))
+}
+
+pub async fn generate_duty_rotation_schedules(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<GenerateDutyRotationReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let template = load_manageable_rotation(&app_state, &user_claims, id).await?;
+    let tz = get_timezone_cached(&app_state).await?;
+
+    let data = generate_duty_rotation(
+        &app_state.db_client,
+        tz,
+        &template,
+        &payload,
+        user_claims.user_claims.user_id,
+    )
+    .await?;
+
+    Ok(Json(GenerateDutyRotationResp {
+        status: "200",
+        data,
+    }))
+}
@@ -1,6 +1,6 @@
 use crate::AppState;
 use crate::auth::rbac::UserRole;
-use crate::database::duty_schedule::DutyScheduleRepo;
+use crate::database::duty_schedule::{DutyScheduleRepo, NewDutySchedule};
 use crate::database::satker_head::SatkerHeadRepo;
 use crate::dtos::SuccessResponse;
 use crate::dtos::duty_schedule::{
@@ -138,16 +138,16 @@ pub async fn create_duty_schedule(
 
     app_state
         .db_client
-        .create_duty_schedule(
+        .create_duty_schedule(NewDutySchedule {
             satker_id,
-            payload.user_id,
-            payload.start_at,
-            payload.end_at,
+            user_id: payload.user_id,
+            start_at: payload.start_at,
+            end_at: payload.end_at,
             schedule_type,
-            payload.title,
-            payload.note,
-            user_claims.user_claims.user_id,
-        )
+            title: payload.title,
+            note: payload.note,
+            created_by: user_claims.user_claims.user_id,
+        })
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
@@ -12,7 +12,7 @@ use validator::Validate;
 use crate::AppState;
 use crate::auth::rbac::UserRole;
 use crate::database::duty_schedule::DutyScheduleRepo;
-use crate::database::duty_schedule_request::DutyScheduleRequestRepo;
+use crate::database::duty_schedule_request::{DutyScheduleRequestRepo, NewDutyScheduleRequest};
 use crate::database::satker_head::SatkerHeadRepo;
 use crate::database::settings::SettingsRepo;
 use crate::dtos::SuccessResponse;
@@ -198,15 +198,15 @@ pub async fn create_request(
 
     let _id = app_state
         .db_client
-        .create_duty_schedule_request(
+        .create_duty_schedule_request(NewDutyScheduleRequest {
             satker_id,
             user_id,
-            payload.start_at,
-            payload.end_at,
-            payload.schedule_type,
-            payload.title,
-            payload.note,
-        )
+            start_at: payload.start_at,
+            end_at: payload.end_at,
+            schedule_type: payload.schedule_type,
+            title: payload.title,
+            note: payload.note,
+        })
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
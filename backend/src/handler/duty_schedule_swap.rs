@@ -0,0 +1,233 @@
+use axum::extract::{Path, Query};
+use axum::response::IntoResponse;
+use axum::routing::{get, put};
+use axum::{Extension, Json, Router};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::auth::rbac::UserRole;
+use crate::database::duty_schedule_swap::DutyScheduleSwapRepo;
+use crate::database::satker_head::SatkerHeadRepo;
+use crate::dtos::SuccessResponse;
+use crate::dtos::duty_schedule_swap::{
+    CreateDutyScheduleSwapReq, DutyScheduleSwapNoteReq, DutyScheduleSwapResp,
+    DutyScheduleSwapsResp, ListDutyScheduleSwapsQuery, RejectDutyScheduleSwapReq,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::duty_schedule_swap::{
+    SwapAction, approve_duty_schedule_swap, create_duty_schedule_swap, ensure_can_view_swap,
+    respond_duty_schedule_swap,
+};
+
+pub fn duty_schedule_swap_handler() -> Router {
+    Router::new()
+        .route("/", get(list_swaps).post(create_swap))
+        .route("/{id}", get(get_swap))
+        .route("/{id}/accept", put(accept_swap))
+        .route("/{id}/decline", put(decline_swap))
+        .route("/{id}/cancel", put(cancel_swap))
+        .route("/{id}/approve", put(approve_swap))
+        .route("/{id}/reject", put(reject_swap))
+}
+
+pub async fn list_swaps(
+    Query(query): Query<ListDutyScheduleSwapsQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let mut satker_id = query.satker_id;
+    let mut user_id = query.user_id;
+
+    // role scoping
+    match user_claims.user_claims.role {
+        UserRole::Superadmin => {
+            // keep query filters
+        }
+        UserRole::SatkerAdmin | UserRole::SatkerHead => {
+            satker_id = Some(user_claims.user_claims.satker_id);
+        }
+        UserRole::Member => {
+            satker_id = Some(user_claims.user_claims.satker_id);
+            user_id = Some(user_claims.user_claims.user_id);
+        }
+    }
+
+    // satker head must be current head when accessing satker scope (list)
+    if user_claims.user_claims.role == UserRole::SatkerHead {
+        let ok = app_state
+            .db_client
+            .is_current_head_satker(
+                user_claims.user_claims.satker_id,
+                user_claims.user_claims.user_id,
+            )
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        if !ok {
+            return Err(HttpError::unauthorized("forbidden"));
+        }
+    }
+
+    let status = query.status.map(|v| v.trim().to_uppercase());
+
+    let rows = app_state
+        .db_client
+        .list_duty_schedule_swaps(satker_id, user_id, status.as_deref())
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(DutyScheduleSwapsResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+pub async fn get_swap(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let swap = app_state
+        .db_client
+        .find_duty_schedule_swap(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("pengajuan tukar jadwal tidak ditemukan"))?;
+
+    ensure_can_view_swap(&app_state.db_client, &user_claims.user_claims, &swap).await?;
+
+    Ok(Json(DutyScheduleSwapResp {
+        status: "200",
+        data: swap,
+    }))
+}
+
+pub async fn create_swap(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(payload): Json<CreateDutyScheduleSwapReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    let id =
+        create_duty_schedule_swap(&app_state.db_client, &user_claims.user_claims, payload).await?;
+
+    let swap = app_state
+        .db_client
+        .find_duty_schedule_swap(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::server_error("pengajuan tukar jadwal gagal dibaca ulang"))?;
+
+    Ok(Json(DutyScheduleSwapResp {
+        status: "200",
+        data: swap,
+    }))
+}
+
+pub async fn accept_swap(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    respond_duty_schedule_swap(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        id,
+        SwapAction::Accept,
+        None,
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully accepted duty schedule swap".to_string(),
+    }))
+}
+
+pub async fn decline_swap(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<DutyScheduleSwapNoteReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    respond_duty_schedule_swap(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        id,
+        SwapAction::Decline,
+        payload.note,
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully declined duty schedule swap".to_string(),
+    }))
+}
+
+pub async fn cancel_swap(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    respond_duty_schedule_swap(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        id,
+        SwapAction::Cancel,
+        None,
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully canceled duty schedule swap".to_string(),
+    }))
+}
+
+pub async fn approve_swap(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    approve_duty_schedule_swap(&app_state.db_client, &user_claims.user_claims, id).await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully approved duty schedule swap".to_string(),
+    }))
+}
+
+pub async fn reject_swap(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RejectDutyScheduleSwapReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    respond_duty_schedule_swap(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        id,
+        SwapAction::Reject,
+        Some(payload.reject_reason),
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully rejected duty schedule swap".to_string(),
+    }))
+}
@@ -0,0 +1,193 @@
+use axum::extract::{Multipart, Path, Query};
+use axum::response::IntoResponse;
+use axum::routing::{get, put};
+use axum::{Extension, Json, Router};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::auth::rbac::UserRole;
+use crate::database::face_enrollment::FaceEnrollmentRepo;
+use crate::database::user::UserRepo;
+use crate::dtos::SuccessResponse;
+use crate::dtos::face_enrollment::{
+    CreateFaceEnrollmentQuery, FaceEnrollmentResp, FaceEnrollmentsResp, ListFaceEnrollmentsQuery,
+    RejectFaceEnrollmentReq,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::authorization::ensure_can_access_satker;
+use crate::services::face_verification::{
+    FaceEnrollmentAction, approve_face_enrollment, respond_face_enrollment, submit_face_enrollment,
+};
+use crate::services::upload::save_face_photo_upload;
+
+pub fn face_enrollment_handler() -> Router {
+    Router::new()
+        .route("/", get(list_enrollments).post(create_enrollment))
+        .route("/{id}", get(get_enrollment))
+        .route("/{id}/approve", put(approve_enrollment))
+        .route("/{id}/reject", put(reject_enrollment))
+        .route("/{id}/revoke", put(revoke_enrollment))
+}
+
+pub async fn list_enrollments(
+    Query(query): Query<ListFaceEnrollmentsQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+    let mut satker_id = query.satker_id;
+    let mut user_id = query.user_id;
+
+    // role scoping
+    match claims.role {
+        UserRole::Superadmin => {}
+        UserRole::SatkerAdmin | UserRole::SatkerHead => {
+            satker_id = Some(claims.satker_id);
+        }
+        UserRole::Member => {
+            satker_id = None;
+            user_id = Some(claims.user_id);
+        }
+    }
+
+    let status = query.status.map(|v| v.trim().to_uppercase());
+
+    let rows = app_state
+        .db_client
+        .list_face_enrollments(satker_id, user_id, status.as_deref())
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(FaceEnrollmentsResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+pub async fn get_enrollment(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let row = app_state
+        .db_client
+        .find_face_enrollment(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("enrolment wajah tidak ditemukan"))?;
+
+    let claims = &user_claims.user_claims;
+    if row.user_id != claims.user_id {
+        ensure_can_access_satker(claims, row.satker_id)?;
+    }
+
+    Ok(Json(FaceEnrollmentResp {
+        status: "200",
+        data: row,
+    }))
+}
+
+/// Upload foto referensi (multipart, field "file": jpg/png). Anggota untuk diri sendiri;
+/// admin/kepala boleh mengunggah untuk anggota satkernya lewat `?user_id=`.
+pub async fn create_enrollment(
+    Query(query): Query<CreateFaceEnrollmentQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    mp: Multipart,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+    let target_id = query.user_id.unwrap_or(claims.user_id);
+
+    let target = app_state
+        .db_client
+        .find_user_by_id(target_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("user tidak ditemukan"))?;
+    if target.id != claims.user_id {
+        ensure_can_access_satker(claims, target.satker_id)?;
+    }
+
+    let key = save_face_photo_upload(&app_state.storage, target.id, mp).await?;
+    let id = submit_face_enrollment(
+        &app_state.db_client,
+        claims,
+        target.id,
+        target.satker_id,
+        &key,
+    )
+    .await?;
+
+    let row = app_state
+        .db_client
+        .find_face_enrollment(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::server_error("enrolment wajah gagal dibaca ulang"))?;
+
+    Ok(Json(FaceEnrollmentResp {
+        status: "200",
+        data: row,
+    }))
+}
+
+pub async fn approve_enrollment(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    approve_face_enrollment(&app_state, &user_claims.user_claims, id).await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully approved face enrollment".to_string(),
+    }))
+}
+
+pub async fn reject_enrollment(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RejectFaceEnrollmentReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    respond_face_enrollment(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        id,
+        FaceEnrollmentAction::Reject,
+        Some(payload.reject_reason),
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully rejected face enrollment".to_string(),
+    }))
+}
+
+pub async fn revoke_enrollment(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    respond_face_enrollment(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        id,
+        FaceEnrollmentAction::Revoke,
+        None,
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully revoked face enrollment".to_string(),
+    }))
+}
@@ -3,143 +3,180 @@ use axum::{
     Extension, Router,
     extract::Query,
     http::{HeaderValue, StatusCode, header},
-    response::{IntoResponse, Response},
+    response::{IntoResponse, Redirect, Response},
 };
-use std::{path::PathBuf, sync::Arc};
-use tokio::fs;
+use std::sync::Arc;
 
 use crate::AppState;
+use crate::database::announcement::AnnouncementRepo;
+use crate::database::face_enrollment::FaceEnrollmentRepo;
 use crate::error::HttpError;
 use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::announcement::can_view_announcement;
+use crate::services::authorization::ensure_can_access_satker;
+use crate::services::image_pipeline::{ImageVariant, variant_path};
+use crate::services::upload::{ANNOUNCEMENT_FOLDER, FACE_FOLDER, PROFILE_FOLDER, SELFIE_FOLDER};
 
 #[derive(Debug, serde::Deserialize)]
 pub struct SelfieQuery {
     pub key: String,
 }
 
+#[derive(Debug, serde::Deserialize)]
+pub struct SelfieSizeQuery {
+    pub key: String,
+    /// thumb | medium; kosong = ukuran asli.
+    pub size: Option<ImageVariant>,
+}
+
 pub fn files_handler() -> Router {
     Router::new()
         .route("/selfie", get(get_selfie_file))
         .route("/profile", get(get_profile_file))
+        .route("/announcement", get(get_announcement_file))
+        .route("/face", get(get_face_file))
 }
 
-// GET /api/files/selfie?key=local://selfies/2026/01/16/xxx.jpg
-pub async fn get_selfie_file(
-    Extension(app_state): Extension<Arc<AppState>>,
-    Extension(_user_claims): Extension<AuthMiddleware>, // auth wajib
-    Query(q): Query<SelfieQuery>,
+/// Response file inline (bisa ditampilkan di browser / webview) untuk key di dalam `folder`.
+/// Kalau presigned URL aktif (STORAGE_PRESIGN_TTL_SECS) dan backend mendukung, client
+/// di-redirect ke storage; selain itu isi file diteruskan lewat backend.
+async fn stored_file_response(
+    app_state: &AppState,
+    folder: &str,
+    key: &str,
+    content_type: Option<&str>,
+    file_name: Option<&str>,
 ) -> Result<Response, HttpError> {
-    // 1) validasi skema key
-    // hanya izinkan local://selfies/...
-    let prefix = "local://selfies/";
-    if !q.key.starts_with(prefix) {
-        return Err(HttpError::bad_request("key tidak valid".to_string()));
+    let disposition = content_disposition(file_name);
+
+    if let Some(url) =
+        app_state
+            .storage
+            .presigned_url(folder, key, content_type, Some(&disposition))?
+    {
+        let mut resp = Redirect::temporary(&url).into_response();
+        resp.headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        return Ok(resp);
     }
 
-    // 2) ambil path relatif setelah prefix
-    let rel = &q.key[prefix.len()..]; // contoh: 2026/01/16/file.jpg
-
-    // blokir path traversal sederhana
-    if rel.contains("..") || rel.contains('\\') {
-        return Err(HttpError::bad_request("key tidak valid".to_string()));
-    }
-
-    // 3) buat full path: upload_dir/selfies/<rel>
-    let selfies_root: PathBuf = app_state.upload_dir.join("selfies");
-    let full_path = selfies_root.join(rel);
-
-    // 4) canonicalize untuk memastikan masih di dalam folder selfies_root
-    // catatan: canonicalize butuh file ada. kalau file tidak ada => NotFound
-    let canon_root = fs::canonicalize(&selfies_root)
-        .await
-        .map_err(|e| HttpError::server_error(e.to_string()))?;
-
-    let canon_file = match fs::canonicalize(&full_path).await {
-        Ok(p) => p,
-        Err(_) => return Err(HttpError::bad_request("file tidak ditemukan".to_string())),
+    let bytes = app_state
+        .storage
+        .read(folder, key)
+        .await?
+        .ok_or(HttpError::bad_request("file tidak ditemukan".to_string()))?;
+
+    let ct = match content_type {
+        Some(ct) => ct.to_string(),
+        None => mime_guess::from_path(key)
+            .first_or_octet_stream()
+            .to_string(),
     };
 
-    if !canon_file.starts_with(&canon_root) {
-        return Err(HttpError::bad_request("akses file ditolak".to_string()));
-    }
-
-    // 5) read file bytes
-    let bytes = fs::read(&canon_file)
-        .await
-        .map_err(|_| HttpError::bad_request("file tidak ditemukan".to_string()))?;
-
-    let mime = mime_guess::from_path(&canon_file).first_or_octet_stream();
-
-    // kalau tidak pakai mime_guess, hardcode jpg:
-    // let mime = mime::IMAGE_JPEG;
-
     let mut resp = (StatusCode::OK, bytes).into_response();
 
-    // set content-type
-    let ct = mime.to_string();
     resp.headers_mut().insert(
         header::CONTENT_TYPE,
         HeaderValue::from_str(&ct).unwrap_or(HeaderValue::from_static("application/octet-stream")),
     );
-
-    // inline supaya bisa ditampilkan di browser / webview
     resp.headers_mut().insert(
         header::CONTENT_DISPOSITION,
-        HeaderValue::from_static("inline"),
+        HeaderValue::from_str(&disposition).unwrap_or(HeaderValue::from_static("inline")),
     );
 
     Ok(resp)
 }
 
+fn content_disposition(file_name: Option<&str>) -> String {
+    match file_name {
+        Some(name) => {
+            let safe: String = name
+                .chars()
+                .filter(|c| c.is_ascii() && !c.is_ascii_control() && *c != '"' && *c != '\\')
+                .collect();
+            format!("inline; filename=\"{}\"", safe)
+        }
+        None => "inline".to_string(),
+    }
+}
+
+// GET /api/files/selfie?key=local://selfies/2026/01/16/xxx.jpg (atau s3://<bucket>/selfies/...)
+//     &size=thumb|medium (opsional)
+// Selfie lama tidak punya varian; yang dikirim file aslinya.
+pub async fn get_selfie_file(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(_user_claims): Extension<AuthMiddleware>, // auth wajib
+    Query(q): Query<SelfieSizeQuery>,
+) -> Result<Response, HttpError> {
+    if let Some(size) = q.size {
+        let key = variant_path(&q.key, size);
+        if app_state.storage.exists(SELFIE_FOLDER, &key).await? {
+            return stored_file_response(&app_state, SELFIE_FOLDER, &key, None, None).await;
+        }
+    }
+    stored_file_response(&app_state, SELFIE_FOLDER, &q.key, None, None).await
+}
+
 // GET /api/files/profile?key=local://profiles/2026/01/16/xxx.jpg
 pub async fn get_profile_file(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(_user_claims): Extension<AuthMiddleware>,
     Query(q): Query<SelfieQuery>,
 ) -> Result<Response, HttpError> {
-    let prefix = "local://profiles/";
-    if !q.key.starts_with(prefix) {
-        return Err(HttpError::bad_request("key tidak valid".to_string()));
-    }
+    stored_file_response(&app_state, PROFILE_FOLDER, &q.key, None, None).await
+}
 
-    let rel = &q.key[prefix.len()..];
-    if rel.contains("..") || rel.contains('\\') {
-        return Err(HttpError::bad_request("key tidak valid".to_string()));
+// GET /api/files/announcement?key=local://announcements/2026/01/16/xxx.pdf
+// Hanya boleh diakses kalau pengumumannya terlihat oleh user (aturan sama dengan list_visible).
+pub async fn get_announcement_file(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Query(q): Query<SelfieQuery>,
+) -> Result<Response, HttpError> {
+    let access = app_state
+        .db_client
+        .find_announcement_attachment_access(&q.key)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::bad_request("file tidak ditemukan".to_string()))?;
+
+    if !can_view_announcement(
+        &user_claims.user_claims,
+        &access.scope,
+        access.satker_id,
+        access.is_active,
+    ) {
+        return Err(HttpError::unauthorized("akses file ditolak".to_string()));
     }
 
-    let profiles_root: PathBuf = app_state.upload_dir.join("profiles");
-    let full_path = profiles_root.join(rel);
+    stored_file_response(
+        &app_state,
+        ANNOUNCEMENT_FOLDER,
+        &q.key,
+        Some(access.content_type.as_str()),
+        Some(access.file_name.as_str()),
+    )
+    .await
+}
 
-    let canon_root = fs::canonicalize(&profiles_root)
+// GET /api/files/face?key=local://faces/2026/01/16/xxx.jpg
+// Foto referensi wajah: hanya pemilik wajah atau admin satkernya.
+pub async fn get_face_file(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Query(q): Query<SelfieQuery>,
+) -> Result<Response, HttpError> {
+    let enrollment = app_state
+        .db_client
+        .find_face_enrollment_by_key(&q.key)
         .await
-        .map_err(|e| HttpError::server_error(e.to_string()))?;
-
-    let canon_file = match fs::canonicalize(&full_path).await {
-        Ok(p) => p,
-        Err(_) => return Err(HttpError::bad_request("file tidak ditemukan".to_string())),
-    };
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::bad_request("file tidak ditemukan".to_string()))?;
 
-    if !canon_file.starts_with(&canon_root) {
-        return Err(HttpError::bad_request("akses file ditolak".to_string()));
+    let claims = &user_claims.user_claims;
+    if enrollment.user_id != claims.user_id {
+        ensure_can_access_satker(claims, enrollment.satker_id)?;
     }
 
-    let bytes = fs::read(&canon_file)
-        .await
-        .map_err(|_| HttpError::bad_request("file tidak ditemukan".to_string()))?;
-
-    let mime = mime_guess::from_path(&canon_file).first_or_octet_stream();
-    let mut resp = (StatusCode::OK, bytes).into_response();
-
-    let ct = mime.to_string();
-    resp.headers_mut().insert(
-        header::CONTENT_TYPE,
-        HeaderValue::from_str(&ct).unwrap_or(HeaderValue::from_static("application/octet-stream")),
-    );
-
-    resp.headers_mut().insert(
-        header::CONTENT_DISPOSITION,
-        HeaderValue::from_static("inline"),
-    );
-
-    Ok(resp)
+    stored_file_response(&app_state, FACE_FOLDER, &q.key, None, None).await
 }
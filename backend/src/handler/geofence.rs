@@ -1,16 +1,19 @@
 use crate::AppState;
 use crate::auth::rbac::UserRole;
 use crate::database::geofence::GeofenceRepo;
+use crate::database::geofence_marker::GeofenceMarkerRepo;
 use crate::database::satker::SatkerRepo;
 use crate::dtos::SuccessResponse;
 use crate::dtos::geofence::{
     CreateGeofenceReq, GeofenceDto, GeofenceResp, GeofencesResp, UpdateGeofenceReq,
     can_manage_geofence, can_view_geofence,
 };
+use crate::dtos::geofence_marker::{CreateGeofenceMarkerReq, GeofenceMarkersResp};
 use crate::error::{ErrorMessage, HttpError};
 use crate::middleware::auth_middleware::AuthMiddleware;
 use crate::services::catalog::load_satkers_and_ranks;
 use crate::services::geofence::ensure_can_manage_geofence;
+use crate::services::location_proof::normalize_marker_identifier;
 use axum::extract::Path;
 use axum::response::IntoResponse;
 use axum::routing::{delete, get, post, put};
@@ -26,6 +29,11 @@ pub fn geofence_handler() -> Router {
         .route("/delete/{id}", delete(delete_geofence))
         .route("/", get(list_geofence))
         .route("/{id}", get(find_geofence))
+        .route(
+            "/{id}/markers",
+            get(list_geofence_markers).post(create_geofence_marker),
+        )
+        .route("/{id}/markers/{marker_id}", delete(delete_geofence_marker))
 }
 
 pub async fn create_geofence(
@@ -197,3 +205,102 @@ pub async fn find_geofence(
 
     Ok(Json(response))
 }
+
+/// Penanda Wi-Fi/BLE geofence (hanya admin pengelola; identifier tidak dibuka ke member).
+pub async fn list_geofence_markers(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    ensure_can_manage_geofence(&app_state.db_client, &user_claims.user_claims, id).await?;
+
+    let rows = app_state
+        .db_client
+        .list_geofence_markers(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(GeofenceMarkersResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+pub async fn create_geofence_marker(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateGeofenceMarkerReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    ensure_can_manage_geofence(&app_state.db_client, &user_claims.user_claims, id).await?;
+
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let kind = payload.kind.trim().to_uppercase();
+    let identifier = normalize_marker_identifier(&kind, &payload.identifier)?;
+    let label = payload
+        .label
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty());
+
+    app_state
+        .db_client
+        .find_geofence(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::bad_request("Geofence not found.".to_string()))?;
+
+    app_state
+        .db_client
+        .create_geofence_marker(
+            id,
+            &kind,
+            &identifier,
+            label,
+            user_claims.user_claims.user_id,
+        )
+        .await
+        .map_err(|e| {
+            if e.as_database_error()
+                .is_some_and(|d| d.is_unique_violation())
+            {
+                HttpError::bad_request("penanda sudah terdaftar di geofence ini")
+            } else {
+                HttpError::server_error(e.to_string())
+            }
+        })?;
+
+    let response = SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully created geofence marker".to_string(),
+    };
+
+    Ok(Json(response))
+}
+
+pub async fn delete_geofence_marker(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path((id, marker_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, HttpError> {
+    ensure_can_manage_geofence(&app_state.db_client, &user_claims.user_claims, id).await?;
+
+    let deleted = app_state
+        .db_client
+        .delete_geofence_marker(id, marker_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if deleted == 0 {
+        return Err(HttpError::bad_request("penanda tidak ditemukan"));
+    }
+
+    let response = SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully deleted geofence marker".to_string(),
+    };
+
+    Ok(Json(response))
+}
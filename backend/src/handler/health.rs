@@ -0,0 +1,169 @@
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::AppState;
+use crate::database::health::HealthRepo;
+use crate::db::expected_migration_version;
+use crate::dtos::health::{
+    COMPONENT_ERROR, COMPONENT_OK, ComponentStatus, LivenessResp, MigrationStatus, ReadinessChecks,
+    ReadinessResp,
+};
+use crate::services::metrics::record_redis_error;
+
+/// Batas waktu per dependency, supaya probe tidak menggantung saat DB/Redis hang.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+pub fn health_handler() -> Router {
+    Router::new()
+        // legacy: GET /api/health -> "ok"
+        .route("/", get(health))
+        .route("/live", get(liveness))
+        .route("/ready", get(readiness))
+}
+
+async fn health() -> &'static str {
+    "ok"
+}
+
+// GET /api/health/live: proses hidup & bisa melayani request (tanpa cek dependency)
+async fn liveness() -> impl IntoResponse {
+    Json(LivenessResp {
+        status: COMPONENT_OK,
+    })
+}
+
+// GET /api/health/ready: 200 kalau semua dependency sehat, 503 kalau ada yang degraded
+async fn readiness(Extension(app_state): Extension<Arc<AppState>>) -> impl IntoResponse {
+    let (database, migrations, redis, storage) = tokio::join!(
+        timed(check_database(&app_state)),
+        check_migrations(&app_state),
+        timed(check_redis(&app_state)),
+        timed(app_state.storage.check()),
+    );
+
+    let degraded = [&database, &redis, &storage]
+        .iter()
+        .any(|c| c.status == COMPONENT_ERROR)
+        || migrations.status == COMPONENT_ERROR;
+
+    let (code, status) = if degraded {
+        (StatusCode::SERVICE_UNAVAILABLE, "degraded")
+    } else {
+        (StatusCode::OK, COMPONENT_OK)
+    };
+
+    (
+        code,
+        Json(ReadinessResp {
+            status,
+            checks: ReadinessChecks {
+                database,
+                migrations,
+                redis,
+                storage,
+            },
+        }),
+    )
+}
+
+/// Jalankan satu check dengan timeout dan catat latency-nya.
+async fn timed<F>(check: F) -> ComponentStatus
+where
+    F: Future<Output = Result<(), String>>,
+{
+    let start = Instant::now();
+    let result = tokio::time::timeout(CHECK_TIMEOUT, check).await;
+    let latency_ms = start.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(Ok(())) => ComponentStatus {
+            status: COMPONENT_OK,
+            latency_ms,
+            message: None,
+        },
+        Ok(Err(e)) => ComponentStatus {
+            status: COMPONENT_ERROR,
+            latency_ms,
+            message: Some(e),
+        },
+        Err(_) => ComponentStatus {
+            status: COMPONENT_ERROR,
+            latency_ms,
+            message: Some("timeout".to_string()),
+        },
+    }
+}
+
+async fn check_database(app_state: &AppState) -> Result<(), String> {
+    app_state
+        .db_client
+        .ping_db()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn check_migrations(app_state: &AppState) -> MigrationStatus {
+    let expected_version = expected_migration_version();
+
+    let applied = tokio::time::timeout(
+        CHECK_TIMEOUT,
+        app_state.db_client.applied_migration_version(),
+    )
+    .await;
+
+    match applied {
+        Ok(Ok(Some(applied_version))) => {
+            let behind = expected_version.is_some_and(|v| applied_version < v);
+            MigrationStatus {
+                status: if behind {
+                    COMPONENT_ERROR
+                } else {
+                    COMPONENT_OK
+                },
+                applied_version: Some(applied_version),
+                expected_version,
+                message: behind.then(|| "schema database tertinggal dari binary".to_string()),
+            }
+        }
+        // belum ada migration yang diterapkan: schema pasti tidak cocok dengan binary
+        Ok(Ok(None)) => MigrationStatus {
+            status: COMPONENT_ERROR,
+            applied_version: None,
+            expected_version,
+            message: Some("tabel _sqlx_migrations belum ada atau kosong".to_string()),
+        },
+        Ok(Err(e)) => MigrationStatus {
+            status: COMPONENT_ERROR,
+            applied_version: None,
+            expected_version,
+            message: Some(e.to_string()),
+        },
+        Err(_) => MigrationStatus {
+            status: COMPONENT_ERROR,
+            applied_version: None,
+            expected_version,
+            message: Some("timeout".to_string()),
+        },
+    }
+}
+
+async fn check_redis(app_state: &AppState) -> Result<(), String> {
+    let mut con = app_state.redis_client.clone();
+    let pong: String = redis::cmd("PING")
+        .query_async(&mut con)
+        .await
+        .map_err(|e| {
+            record_redis_error("readiness_ping");
+            e.to_string()
+        })?;
+
+    if pong != "PONG" {
+        return Err(format!("unexpected PING reply: {pong}"));
+    }
+    Ok(())
+}
@@ -4,26 +4,34 @@ use crate::constants::HolidayScope;
 use crate::database::holiday::{HolidayRepo, HolidayUpsertItem};
 use crate::dtos::holiday::{
     BulkHolidayReq, BulkHolidayResp, BulkHolidayRespData, DeleteHolidayQuery, DeleteHolidayResp,
-    ListHolidaysQuery, ListHolidaysResp, UpsertHolidayReq, UpsertHolidayResp,
+    HolidayImportOptions, HolidayImportResp, ListHolidaysQuery, ListHolidaysResp, UpsertHolidayReq,
+    UpsertHolidayResp,
 };
 use crate::error::HttpError;
 use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::calendar_regen::enqueue_holiday_regen;
 use crate::services::holiday::{
-    authorize_holiday_scope_access, normalize_holiday_kind_and_half_day,
+    MAX_HOLIDAY_IMPORT_BYTES, authorize_holiday_scope_access, build_holiday_upsert_items,
+    import_holidays, normalize_holiday_kind_and_half_day, parse_holiday_import_file,
 };
-use axum::extract::Query;
+use axum::extract::{Multipart, Query};
 use axum::response::IntoResponse;
 use axum::routing::{get, post};
 use axum::{Extension, Json, Router};
+use std::str::FromStr;
 use std::sync::Arc;
+use uuid::Uuid;
 
 pub fn holiday_handler() -> Router {
-    Router::new().route("/bulk", post(bulk_holidays)).route(
-        "/",
-        get(list_holidays)
-            .put(upsert_holiday)
-            .delete(delete_holiday),
-    )
+    Router::new()
+        .route("/bulk", post(bulk_holidays))
+        .route("/import", post(import_holidays_file))
+        .route(
+            "/",
+            get(list_holidays)
+                .put(upsert_holiday)
+                .delete(delete_holiday),
+        )
 }
 
 pub async fn bulk_holidays(
@@ -43,16 +51,9 @@ pub async fn bulk_holidays(
         payload.satker_id,
     )?;
 
-    let mut items: Vec<HolidayUpsertItem> = Vec::with_capacity(payload.items.len());
-    for it in payload.items {
-        let (kind, half_day_end) = normalize_holiday_kind_and_half_day(it.kind, it.half_day_end)?;
-        items.push(HolidayUpsertItem {
-            holiday_date: it.holiday_date,
-            kind,
-            name: it.name,
-            half_day_end,
-        });
-    }
+    let items = build_holiday_upsert_items(payload.items)?;
+    let from = items.iter().map(|it| it.holiday_date).min();
+    let to = items.iter().map(|it| it.holiday_date).max();
 
     let affected = app_state
         .db_client
@@ -60,6 +61,18 @@ pub async fn bulk_holidays(
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
+    if let Some((from, to)) = from.zip(to) {
+        enqueue_holiday_regen(
+            &app_state.db_client,
+            payload.scope,
+            payload.satker_id,
+            from,
+            to,
+            "HOLIDAY_BULK",
+        )
+        .await;
+    }
+
     Ok(Json(BulkHolidayResp {
         status: "200".to_string(),
         data: BulkHolidayRespData {
@@ -68,6 +81,105 @@ pub async fn bulk_holidays(
     }))
 }
 
+fn parse_bool_field(name: &str, value: &str) -> Result<bool, HttpError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "" | "0" | "false" | "no" => Ok(false),
+        "1" | "true" | "yes" => Ok(true),
+        _ => Err(HttpError::bad_request(format!(
+            "{}: harus true/false",
+            name
+        ))),
+    }
+}
+
+// POST /api/holidays/import (multipart)
+// field: file (.csv / .ics), scope, satker_id, dry_run, regenerate_calendar
+pub async fn import_holidays_file(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    mut mp: Multipart,
+) -> Result<impl IntoResponse, HttpError> {
+    let mut file: Option<(Option<String>, Vec<u8>)> = None;
+    let mut scope: Option<HolidayScope> = None;
+    let mut satker_id: Option<Uuid> = None;
+    let mut dry_run = false;
+    let mut regenerate_calendar = false;
+
+    while let Some(mut field) = mp
+        .next_field()
+        .await
+        .map_err(|e| HttpError::bad_request(e.to_string()))?
+    {
+        let name = field.name().unwrap_or("").to_string();
+        if name == "file" {
+            let file_name = field.file_name().map(|n| n.to_string());
+            let mut data: Vec<u8> = Vec::new();
+            while let Some(chunk) = field
+                .chunk()
+                .await
+                .map_err(|e| HttpError::bad_request(e.to_string()))?
+            {
+                if data.len() + chunk.len() > MAX_HOLIDAY_IMPORT_BYTES {
+                    return Err(HttpError::bad_request(format!(
+                        "file terlalu besar (max {}KB)",
+                        MAX_HOLIDAY_IMPORT_BYTES / 1024
+                    )));
+                }
+                data.extend_from_slice(&chunk);
+            }
+            file = Some((file_name, data));
+            continue;
+        }
+
+        let value = field
+            .text()
+            .await
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+        match name.as_str() {
+            "scope" => {
+                scope = Some(HolidayScope::from_str(value.trim()).map_err(HttpError::bad_request)?)
+            }
+            "satker_id" if !value.trim().is_empty() => {
+                satker_id = Some(
+                    Uuid::parse_str(value.trim())
+                        .map_err(|_| HttpError::bad_request("satker_id: uuid tidak valid"))?,
+                )
+            }
+            "dry_run" => dry_run = parse_bool_field("dry_run", &value)?,
+            "regenerate_calendar" => {
+                regenerate_calendar = parse_bool_field("regenerate_calendar", &value)?
+            }
+            _ => {}
+        }
+    }
+
+    let scope = scope.ok_or_else(|| HttpError::bad_request("scope: wajib diisi"))?;
+    let (file_name, data) = file.ok_or_else(|| HttpError::bad_request("file: wajib diisi"))?;
+
+    // Authz
+    authorize_holiday_scope_access(
+        user_claims.user_claims.role,
+        user_claims.user_claims.satker_id,
+        scope,
+        satker_id,
+    )?;
+
+    let items = parse_holiday_import_file(file_name.as_deref(), &data)?;
+
+    let opts = HolidayImportOptions {
+        scope,
+        satker_id,
+        dry_run,
+        regenerate_calendar,
+    };
+    let data = import_holidays(&app_state.db_client, &opts, items).await?;
+
+    Ok(Json(HolidayImportResp {
+        status: "200".to_string(),
+        data,
+    }))
+}
+
 pub async fn list_holidays(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(user_claims): Extension<AuthMiddleware>,
@@ -139,6 +251,16 @@ pub async fn upsert_holiday(
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
+    enqueue_holiday_regen(
+        &app_state.db_client,
+        payload.scope,
+        payload.satker_id,
+        payload.holiday_date,
+        payload.holiday_date,
+        "HOLIDAY_UPSERT",
+    )
+    .await;
+
     Ok(Json(UpsertHolidayResp {
         status: "200".to_string(),
         data: "ok".to_string(),
@@ -168,6 +290,16 @@ pub async fn delete_holiday(
         return Err(HttpError::bad_request("holiday: tidak ditemukan"));
     }
 
+    enqueue_holiday_regen(
+        &app_state.db_client,
+        q.scope,
+        q.satker_id,
+        q.holiday_date,
+        q.holiday_date,
+        "HOLIDAY_DELETE",
+    )
+    .await;
+
     Ok(Json(DeleteHolidayResp {
         status: "200".to_string(),
         data: "ok".to_string(),
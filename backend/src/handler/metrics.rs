@@ -0,0 +1,41 @@
+use axum::Extension;
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use std::sync::Arc;
+
+use crate::AppState;
+use crate::error::HttpError;
+use crate::services::metrics::{render, set_db_pool_state};
+
+// GET /metrics (Prometheus text format)
+pub async fn get_metrics(
+    Extension(app_state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Response, HttpError> {
+    if let Some(token) = &app_state.env.metrics_token {
+        let provided = headers
+            .get(AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "));
+        if provided != Some(token.as_str()) {
+            return Err(HttpError::unauthorized("Unauthorized".to_string()));
+        }
+    }
+
+    // state pool dibaca saat scrape, bukan di-update per query
+    let pool = &app_state.db_client.pool;
+    set_db_pool_state(
+        pool.size(),
+        pool.num_idle(),
+        pool.options().get_max_connections(),
+    );
+
+    let body = render()?;
+    let mut resp = (StatusCode::OK, body).into_response();
+    resp.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; version=0.0.4; charset=utf-8"),
+    );
+    Ok(resp)
+}
@@ -1,16 +1,27 @@
 pub mod announcement;
 pub mod attendance;
 pub mod attendance_admin;
+pub mod attendance_anomaly;
+pub mod attendance_away;
 pub mod attendance_challenge;
+pub mod attendance_rekap;
 pub mod auth;
+pub mod calendar_feed;
 pub mod dashboard;
+pub mod duty_rotation;
 pub mod duty_schedule;
 pub mod duty_schedule_request;
+pub mod duty_schedule_swap;
+pub mod face_enrollment;
 pub mod files;
 pub mod geofence;
+pub mod health;
 pub mod holiday;
 pub mod leave_request;
+pub mod metrics;
+pub mod overtime;
 pub mod rank;
+pub mod retention;
 pub mod satker;
 pub mod satker_head;
 pub mod schedule;
@@ -18,4 +29,7 @@ pub mod settings;
 pub mod tukin;
 pub mod upload;
 pub mod user;
+pub mod user_device;
+pub mod work_group;
+pub mod work_pattern_override;
 pub mod working_days;
@@ -0,0 +1,307 @@
+use axum::extract::{Path, Query};
+use axum::response::IntoResponse;
+use axum::routing::{get, put};
+use axum::{Extension, Json, Router};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::auth::rbac::UserRole;
+use crate::database::overtime::OvertimeRepo;
+use crate::database::satker_head::SatkerHeadRepo;
+use crate::database::user::UserRepo;
+use crate::dtos::SuccessResponse;
+use crate::dtos::overtime::{
+    ApproveOvertimeClaimReq, CreateOvertimeClaimReq, ListOvertimeClaimsQuery,
+    OvertimeCandidatesQuery, OvertimeCandidatesResp, OvertimeClaimResp, OvertimeClaimsResp,
+    OvertimeRatesResp, OvertimeReportQuery, OvertimeReportResp, RejectOvertimeClaimReq,
+    ReplaceOvertimeRatesReq,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::authorization::ensure_can_access_satker;
+use crate::services::overtime::{
+    OvertimeAction, approve_overtime_claim, compute_overtime_candidates, compute_overtime_report,
+    create_overtime_claim, ensure_can_view_overtime_claim, map_overtime_rates_save_error,
+    respond_overtime_claim, validate_overtime_rates,
+};
+use crate::utils::timezone_cache::get_timezone_cached;
+
+pub fn overtime_handler() -> Router {
+    Router::new()
+        .route("/candidates", get(list_candidates))
+        .route("/claims", get(list_claims).post(create_claim))
+        .route("/claims/{id}", get(get_claim))
+        .route("/claims/{id}/cancel", put(cancel_claim))
+        .route("/claims/{id}/approve", put(approve_claim))
+        .route("/claims/{id}/reject", put(reject_claim))
+        .route("/rates", get(get_rates).put(put_rates))
+        .route("/report", get(get_report))
+}
+
+/// Kandidat lembur (default: diri sendiri); admin/kepala bisa melihat anggota satkernya.
+pub async fn list_candidates(
+    Query(query): Query<OvertimeCandidatesQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+    let user_id = query.user_id.unwrap_or(claims.user_id);
+
+    let user = app_state
+        .db_client
+        .find_user_by_id(user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("User tidak ditemukan"))?;
+    if user.id != claims.user_id {
+        ensure_can_access_satker(claims, user.satker_id)?;
+    }
+
+    let tz = get_timezone_cached(&app_state).await?;
+    let rows =
+        compute_overtime_candidates(&app_state.db_client, tz, &user, query.from, query.to).await?;
+
+    Ok(Json(OvertimeCandidatesResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+pub async fn list_claims(
+    Query(query): Query<ListOvertimeClaimsQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let mut satker_id = query.satker_id;
+    let mut user_id = query.user_id;
+
+    // role scoping
+    match user_claims.user_claims.role {
+        UserRole::Superadmin => {
+            // keep query filters
+        }
+        UserRole::SatkerAdmin | UserRole::SatkerHead => {
+            satker_id = Some(user_claims.user_claims.satker_id);
+        }
+        UserRole::Member => {
+            satker_id = Some(user_claims.user_claims.satker_id);
+            user_id = Some(user_claims.user_claims.user_id);
+        }
+    }
+
+    // satker head must be current head when accessing satker scope (list)
+    if user_claims.user_claims.role == UserRole::SatkerHead {
+        let ok = app_state
+            .db_client
+            .is_current_head_satker(
+                user_claims.user_claims.satker_id,
+                user_claims.user_claims.user_id,
+            )
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        if !ok {
+            return Err(HttpError::unauthorized("forbidden"));
+        }
+    }
+
+    let status = query.status.map(|v| v.trim().to_uppercase());
+
+    let rows = app_state
+        .db_client
+        .list_overtime_claims(satker_id, user_id, status.as_deref(), query.from, query.to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(OvertimeClaimsResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+pub async fn get_claim(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claim = app_state
+        .db_client
+        .find_overtime_claim(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("klaim lembur tidak ditemukan"))?;
+
+    ensure_can_view_overtime_claim(&user_claims.user_claims, &claim)?;
+
+    Ok(Json(OvertimeClaimResp {
+        status: "200",
+        data: claim,
+    }))
+}
+
+/// Ajukan klaim lembur untuk diri sendiri.
+pub async fn create_claim(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(payload): Json<CreateOvertimeClaimReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    let user = app_state
+        .db_client
+        .find_user_by_id(user_claims.user_claims.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("User tidak ditemukan"))?;
+
+    let tz = get_timezone_cached(&app_state).await?;
+    let id = create_overtime_claim(&app_state.db_client, tz, &user, payload).await?;
+
+    let claim = app_state
+        .db_client
+        .find_overtime_claim(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::server_error("klaim lembur gagal dibaca ulang"))?;
+
+    Ok(Json(OvertimeClaimResp {
+        status: "200",
+        data: claim,
+    }))
+}
+
+pub async fn cancel_claim(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    respond_overtime_claim(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        id,
+        OvertimeAction::Cancel,
+        None,
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully canceled overtime claim".to_string(),
+    }))
+}
+
+pub async fn approve_claim(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ApproveOvertimeClaimReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    approve_overtime_claim(&app_state.db_client, &user_claims.user_claims, id, payload).await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully approved overtime claim".to_string(),
+    }))
+}
+
+pub async fn reject_claim(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RejectOvertimeClaimReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    respond_overtime_claim(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        id,
+        OvertimeAction::Reject,
+        Some(payload.reject_reason),
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully rejected overtime claim".to_string(),
+    }))
+}
+
+pub async fn get_rates(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    match user_claims.user_claims.role {
+        UserRole::Superadmin | UserRole::SatkerAdmin | UserRole::SatkerHead => {}
+        _ => return Err(HttpError::unauthorized("Tidak boleh".to_string())),
+    }
+
+    let rows = app_state
+        .db_client
+        .list_overtime_rates()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(OvertimeRatesResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+/// Ganti seluruh tarif lembur (berlaku nasional, hanya superadmin).
+pub async fn put_rates(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(req): Json<ReplaceOvertimeRatesReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    if user_claims.user_claims.role != UserRole::Superadmin {
+        return Err(HttpError::unauthorized("Tidak boleh".to_string()));
+    }
+
+    let rates = validate_overtime_rates(req.rates)?;
+
+    let rows = app_state
+        .db_client
+        .replace_overtime_rates(rates)
+        .await
+        .map_err(map_overtime_rates_save_error)?;
+
+    Ok(Json(OvertimeRatesResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+/// Rekap lembur bulanan (APPROVED x tarif) per satker untuk payroll.
+pub async fn get_report(
+    Query(query): Query<OvertimeReportQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+
+    let satker_id = if claims.role == UserRole::Superadmin {
+        query
+            .satker_id
+            .ok_or_else(|| HttpError::bad_request("satker_id wajib diisi"))?
+    } else {
+        claims.satker_id
+    };
+    ensure_can_access_satker(claims, satker_id)?;
+
+    let report = compute_overtime_report(&app_state.db_client, &query.month, satker_id).await?;
+
+    Ok(Json(OvertimeReportResp {
+        status: "200",
+        data: report,
+    }))
+}
@@ -0,0 +1,292 @@
+use axum::extract::{Path, Query};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::auth::rbac::UserRole;
+use crate::database::retention::RetentionRepo;
+use crate::database::settings::{SETTING_RETENTION_POLICY, SettingsRepo};
+use crate::database::user::UserRepo;
+use crate::dtos::retention::{
+    CreateLegalHoldReq, LegalHoldResp, LegalHoldsResp, ListLegalHoldsQuery, ListRetentionRunsQuery,
+    RETENTION_RUN_MANUAL, RetentionPolicy, RetentionPolicyResp, RetentionReportResp,
+    RetentionRunsResp, RunRetentionQuery, StorageUsageQuery, StorageUsageResp,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::{AuthMiddleware, UserClaims};
+use crate::services::authorization::ensure_can_access_satker;
+use crate::services::retention::{load_retention_policy, run_recorded_retention};
+use crate::utils::timezone_cache::get_timezone_cached;
+
+pub fn retention_handler() -> Router {
+    Router::new()
+        .route("/policy", get(get_policy).put(update_policy))
+        .route("/run", post(run_retention_now))
+        .route("/runs", get(list_runs))
+        .route("/storage-usage", get(get_storage_usage))
+        .route(
+            "/legal-holds",
+            get(list_legal_holds).post(create_legal_hold),
+        )
+        .route("/legal-holds/{id}", get(get_legal_hold))
+        .route("/legal-holds/{id}/release", post(release_legal_hold))
+}
+
+fn ensure_superadmin(claims: &UserClaims) -> Result<(), HttpError> {
+    if claims.role != UserRole::Superadmin {
+        return Err(HttpError::unauthorized(
+            "hanya SUPERADMIN yang boleh mengatur retensi",
+        ));
+    }
+    Ok(())
+}
+
+fn ensure_admin(claims: &UserClaims) -> Result<(), HttpError> {
+    match claims.role {
+        UserRole::Superadmin | UserRole::SatkerAdmin | UserRole::SatkerHead => Ok(()),
+        UserRole::Member => Err(HttpError::unauthorized("Tidak boleh".to_string())),
+    }
+}
+
+/// Periode retensi per kelas objek (null = disimpan selamanya).
+pub async fn get_policy(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    ensure_admin(&user_claims.user_claims)?;
+
+    let policy = load_retention_policy(&app_state.db_client).await?;
+    Ok(Json(RetentionPolicyResp {
+        status: "200",
+        data: policy,
+    }))
+}
+
+/// Ganti seluruh kebijakan retensi (hanya superadmin); perubahan dicatat di audit setting.
+pub async fn update_policy(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(body): Json<RetentionPolicy>,
+) -> Result<impl IntoResponse, HttpError> {
+    ensure_superadmin(&user_claims.user_claims)?;
+    body.validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    let raw = (!body.is_empty())
+        .then(|| serde_json::to_string(&body))
+        .transpose()
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    app_state
+        .db_client
+        .set_setting_audited(
+            SETTING_RETENTION_POLICY,
+            raw.as_deref(),
+            user_claims.user_claims.user_id,
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(RetentionPolicyResp {
+        status: "200",
+        data: body,
+    }))
+}
+
+/// Jalankan retensi sekarang (hanya superadmin). Default dry-run: hanya menghitung.
+pub async fn run_retention_now(
+    Query(query): Query<RunRetentionQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    ensure_superadmin(&user_claims.user_claims)?;
+
+    let report = run_recorded_retention(
+        &app_state.db_client,
+        &app_state.storage,
+        RETENTION_RUN_MANUAL,
+        Some(user_claims.user_claims.user_id),
+        query.dry_run.unwrap_or(true),
+        None,
+    )
+    .await?
+    .ok_or(HttpError::bad_request(
+        "purge retensi lain sedang berjalan, coba lagi nanti",
+    ))?;
+
+    Ok(Json(RetentionReportResp {
+        status: "200",
+        data: report,
+    }))
+}
+
+pub async fn list_runs(
+    Query(query): Query<ListRetentionRunsQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    ensure_superadmin(&user_claims.user_claims)?;
+
+    let rows = app_state
+        .db_client
+        .list_retention_runs(query.limit.unwrap_or(50).clamp(1, 500))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(RetentionRunsResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+/// Pemakaian storage per satker per bulan (admin/kepala: satker sendiri).
+pub async fn get_storage_usage(
+    Query(query): Query<StorageUsageQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+    let satker_id = match claims.role {
+        UserRole::Superadmin => query.satker_id,
+        UserRole::SatkerAdmin | UserRole::SatkerHead => Some(claims.satker_id),
+        UserRole::Member => return Err(HttpError::unauthorized("Tidak boleh".to_string())),
+    };
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from > to
+    {
+        return Err(HttpError::bad_request("from tidak boleh setelah to"));
+    }
+
+    let tz = get_timezone_cached(&app_state).await?;
+    let rows = app_state
+        .db_client
+        .storage_usage(tz.name(), satker_id, query.from, query.to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(StorageUsageResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+/// Legal hold (admin/kepala: satker sendiri; superadmin: semua / filter). Default aktif saja.
+pub async fn list_legal_holds(
+    Query(query): Query<ListLegalHoldsQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+    let satker_id = match claims.role {
+        UserRole::Superadmin => query.satker_id,
+        UserRole::SatkerAdmin | UserRole::SatkerHead => Some(claims.satker_id),
+        UserRole::Member => return Err(HttpError::unauthorized("Tidak boleh".to_string())),
+    };
+
+    let rows = app_state
+        .db_client
+        .list_legal_holds(satker_id, query.user_id, query.active.unwrap_or(true))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(LegalHoldsResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+pub async fn get_legal_hold(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let hold = app_state
+        .db_client
+        .find_legal_hold(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::bad_request("legal hold tidak ditemukan"))?;
+    ensure_can_access_satker(&user_claims.user_claims, hold.satker_id)?;
+
+    Ok(Json(LegalHoldResp {
+        status: "200",
+        data: hold,
+    }))
+}
+
+/// Tahan data user (selfie, foto profil, metadata upload) dari purge retensi.
+pub async fn create_legal_hold(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(payload): Json<CreateLegalHoldReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    let user = app_state
+        .db_client
+        .find_user_by_id(payload.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::bad_request("user tidak ditemukan"))?;
+    ensure_can_access_satker(&user_claims.user_claims, user.satker_id)?;
+
+    let id = app_state
+        .db_client
+        .create_legal_hold(user.satker_id, &payload, user_claims.user_claims.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let hold = app_state
+        .db_client
+        .find_legal_hold(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::server_error("legal hold tidak ditemukan"))?;
+
+    Ok(Json(LegalHoldResp {
+        status: "200",
+        data: hold,
+    }))
+}
+
+/// Lepas legal hold; data user kembali ikut purge retensi berikutnya.
+pub async fn release_legal_hold(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+    let hold = app_state
+        .db_client
+        .find_legal_hold(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::bad_request("legal hold tidak ditemukan"))?;
+    ensure_can_access_satker(claims, hold.satker_id)?;
+
+    let released = app_state
+        .db_client
+        .release_legal_hold(id, claims.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if released == 0 {
+        return Err(HttpError::bad_request("legal hold sudah dilepas"));
+    }
+
+    let hold = app_state
+        .db_client
+        .find_legal_hold(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::server_error("legal hold tidak ditemukan"))?;
+
+    Ok(Json(LegalHoldResp {
+        status: "200",
+        data: hold,
+    }))
+}
@@ -1,27 +1,29 @@
 use crate::AppState;
-use crate::constants::{CalendarDayType, HolidayKind, SUPERUSER_SATKER_ID};
-use crate::database::holiday::HolidayRepo;
+use crate::constants::SUPERUSER_SATKER_ID;
+use crate::database::calendar_regen::CalendarRegenRepo;
 use crate::database::satker::SatkerRepo;
 use crate::database::work_calendar::WorkCalendarRepo;
-use crate::database::work_pattern::{WorkPatternRepo, WorkPatternUpsert, pick_effective_pattern};
+use crate::database::work_pattern::{WorkPatternRepo, WorkPatternUpsert};
 use crate::dtos::SuccessResponse;
 use crate::dtos::satker::{CreateSatkerReq, SatkerDto, SatkerResp, SatkersResp, UpdateSatkerReq};
 use crate::dtos::work_calendar::{
-    GenerateCalendarQuery, GenerateCalendarResp, GenerateCalendarRespData,
+    CalendarRegenJobsResp, GenerateCalendarQuery, GenerateCalendarResp, GenerateCalendarRespData,
 };
 use crate::dtos::work_calendar::{ListCalendarQuery, ListCalendarResp};
 use crate::dtos::work_pattern::{UpsertWorkPatternReq, UpsertWorkPatternResp, WorkPatternsResp};
 use crate::error::HttpError;
 use crate::middleware::auth_middleware::AuthMiddleware;
 use crate::services::authorization::ensure_can_access_satker;
-use crate::services::calendar::{build_holiday_override_map, weekday_is_work};
+use crate::services::calendar::generate_calendar_days;
+use crate::services::calendar_regen::enqueue_work_pattern_regen;
 use crate::services::catalog::load_satkers_and_ranks;
+use crate::services::work_pattern::{parse_max_break_minutes, parse_max_segments};
 use crate::utils::time_parser::{parse_optional_time_field, parse_time_field};
 use axum::extract::{Path, Query};
 use axum::response::IntoResponse;
 use axum::routing::{delete, get, post, put};
 use axum::{Extension, Json, Router};
-use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+use chrono::NaiveDate;
 use std::sync::Arc;
 use uuid::Uuid;
 use validator::Validate;
@@ -40,6 +42,7 @@ pub fn satker_handler() -> Router {
         )
         .route("/{id}/calendar", get(list_calendar_days))
         .route("/{id}/calendar/generate", post(generate_calendar))
+        .route("/{id}/calendar/jobs", get(list_calendar_regen_jobs))
         .route("/create", post(create_satker))
         .route("/update/{id}", put(update_satker))
         .route("/delete/{id}", delete(delete_satker))
@@ -69,6 +72,26 @@ pub async fn list_calendar_days(
     }))
 }
 
+/// Riwayat job generate ulang kalender otomatis (terbaru dulu).
+pub async fn list_calendar_regen_jobs(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(satker_id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    ensure_can_access_satker(&user_claims.user_claims, satker_id)?;
+
+    let rows = app_state
+        .db_client
+        .list_calendar_regen_jobs(satker_id, 20)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(CalendarRegenJobsResp {
+        status: "200".to_string(),
+        data: rows,
+    }))
+}
+
 pub async fn create_satker(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(user_claims): Extension<AuthMiddleware>,
@@ -177,118 +200,6 @@ pub async fn get_satker(
     }))
 }
 
-pub async fn generate_calendar_first(
-    Extension(app_state): Extension<Arc<AppState>>,
-    Extension(user_claims): Extension<AuthMiddleware>,
-    Path(id): Path<Uuid>,
-    Query(q): Query<GenerateCalendarQuery>,
-) -> Result<impl IntoResponse, HttpError> {
-    ensure_can_access_satker(&user_claims.user_claims, id)?;
-
-    if q.to < q.from {
-        return Err(HttpError::bad_request("to: harus >= from"));
-    }
-
-    let patterns = app_state
-        .db_client
-        .list_work_patterns(id)
-        .await
-        .map_err(|e| HttpError::server_error(e.to_string()))?;
-
-    if patterns.is_empty() {
-        return Err(HttpError::bad_request(
-            "satker_work_patterns: belum ada, isi dulu pattern kerja satker",
-        ));
-    }
-
-    let holidays = app_state
-        .db_client
-        .list_holidays(id, q.from, q.to)
-        .await
-        .map_err(|e| HttpError::server_error(e.to_string()))?;
-
-    let holiday_by_date = build_holiday_override_map(holidays);
-
-    let mut date = q.from;
-    let mut generated: i64 = 0;
-    while date <= q.to {
-        let pattern = pick_effective_pattern(&patterns, date).ok_or_else(|| {
-            HttpError::bad_request("work pattern tidak ditemukan untuk tanggal ini")
-        })?;
-
-        let weekday = date.weekday();
-        let is_work = weekday_is_work(&pattern, weekday);
-
-        let mut day_type = if is_work {
-            // default half day rule: if Saturday work and half_day_end present => half day
-            if weekday == Weekday::Sat && pattern.half_day_end.is_some() {
-                CalendarDayType::HalfDay
-            } else {
-                CalendarDayType::Workday
-            }
-        } else {
-            CalendarDayType::Holiday
-        };
-
-        let mut expected_start: Option<NaiveTime> = None;
-        let mut expected_end: Option<NaiveTime> = None;
-        let mut note: Option<String> = None;
-
-        if is_work {
-            expected_start = Some(pattern.work_start);
-            expected_end = Some(if day_type == CalendarDayType::HalfDay {
-                pattern.half_day_end.unwrap_or(pattern.work_end)
-            } else {
-                pattern.work_end
-            });
-        } else {
-            note = Some("LIBUR".to_string());
-        }
-
-        // Apply holiday override (national/satker)
-        if let Some(h) = holiday_by_date.get(&date) {
-            match h.kind {
-                HolidayKind::Holiday => {
-                    day_type = CalendarDayType::Holiday;
-                    expected_start = None;
-                    expected_end = None;
-                }
-                HolidayKind::HalfDay => {
-                    day_type = CalendarDayType::HalfDay;
-                    // If it's a workday, keep start; ensure end is set.
-                    if expected_start.is_none() {
-                        expected_start = Some(pattern.work_start);
-                    }
-                    expected_end = Some(
-                        h.half_day_end
-                            .or(pattern.half_day_end)
-                            .unwrap_or(pattern.work_end),
-                    );
-                }
-            }
-            note = Some(h.name.clone());
-        }
-
-        app_state
-            .db_client
-            .upsert_calendar_day(id, date, day_type, expected_start, expected_end, note)
-            .await
-            .map_err(|e| HttpError::server_error(e.to_string()))?;
-
-        generated += 1;
-        date = date
-            .succ_opt()
-            .ok_or_else(|| HttpError::server_error("invalid date"))?;
-    }
-
-    Ok(Json(GenerateCalendarResp {
-        status: "200".to_string(),
-        data: GenerateCalendarRespData {
-            days_generated: generated,
-        },
-    }))
-}
-
 pub async fn generate_calendar(
     Extension(app_state): Extension<Arc<AppState>>,
     Extension(user_claims): Extension<AuthMiddleware>,
@@ -321,102 +232,7 @@ pub async fn generate_calendar(
         }
     }*/
 
-    // Guard: do not generate absurdly large ranges
-    let max_days: i64 = 370;
-    let days = (q.to - q.from).num_days() + 1;
-    if days > max_days {
-        return Err(HttpError::bad_request(format!(
-            "range terlalu besar (max {} hari)",
-            max_days
-        )));
-    }
-
-    let patterns = app_state
-        .db_client
-        .list_work_patterns(satker_id)
-        .await
-        .map_err(|e| HttpError::server_error(e.to_string()))?;
-
-    if patterns.is_empty() {
-        return Err(HttpError::bad_request(
-            "satker_work_patterns belum diset untuk satker ini",
-        ));
-    }
-
-    let holidays = app_state
-        .db_client
-        .list_holidays(satker_id, q.from, q.to)
-        .await
-        .map_err(|e| HttpError::server_error(e.to_string()))?;
-
-    let holiday_by_date = build_holiday_override_map(holidays);
-
-    let mut generated: i64 = 0;
-    let mut cur = q.from;
-    while cur <= q.to {
-        let weekday = cur.weekday();
-        let pattern = pick_effective_pattern(&patterns, cur).ok_or_else(|| {
-            HttpError::bad_request(format!(
-                "work pattern tidak ditemukan untuk tanggal {}",
-                cur
-            ))
-        })?;
-
-        let mut day_type = if weekday_is_work(&pattern, weekday) {
-            CalendarDayType::Workday
-        } else {
-            CalendarDayType::Holiday
-        };
-
-        let mut expected_start: Option<NaiveTime> = None;
-        let mut expected_end: Option<NaiveTime> = None;
-        let mut note: Option<String> = None;
-
-        if day_type == CalendarDayType::Workday {
-            expected_start = Some(pattern.work_start);
-            expected_end = Some(pattern.work_end);
-            // Half-day rule: if sat_work and pattern has half_day_end and today is Saturday
-            if weekday == Weekday::Sat
-                && let Some(half_end) = pattern.half_day_end
-            {
-                day_type = CalendarDayType::HalfDay;
-                expected_end = Some(half_end);
-            }
-        } else {
-            note = Some("Hari libur".to_string());
-        }
-
-        // Override with holiday table
-        if let Some(h) = holiday_by_date.get(&cur) {
-            match h.kind {
-                HolidayKind::Holiday => {
-                    day_type = CalendarDayType::Holiday;
-                    expected_start = None;
-                    expected_end = None;
-                    note = Some(h.name.clone());
-                }
-                HolidayKind::HalfDay => {
-                    day_type = CalendarDayType::HalfDay;
-                    expected_start = Some(pattern.work_start);
-                    let end = h
-                        .half_day_end
-                        .or(pattern.half_day_end)
-                        .unwrap_or(pattern.work_end);
-                    expected_end = Some(end);
-                    note = Some(h.name.clone());
-                }
-            }
-        }
-
-        app_state
-            .db_client
-            .upsert_calendar_day(satker_id, cur, day_type, expected_start, expected_end, note)
-            .await
-            .map_err(|e| HttpError::server_error(e.to_string()))?;
-
-        generated += 1;
-        cur = cur.succ_opt().unwrap();
-    }
+    let generated = generate_calendar_days(&app_state.db_client, satker_id, q.from, q.to).await?;
 
     Ok(Json(GenerateCalendarResp {
         status: "200".to_string(),
@@ -471,6 +287,9 @@ pub async fn upsert_work_pattern(
         }
     }
 
+    let max_segments = parse_max_segments(payload.max_segments)?;
+    let max_break_minutes = parse_max_break_minutes(payload.max_break_minutes)?;
+
     let item = WorkPatternUpsert {
         effective_from: payload.effective_from,
         mon_work: payload.mon_work,
@@ -483,6 +302,8 @@ pub async fn upsert_work_pattern(
         work_start,
         work_end,
         half_day_end,
+        max_segments,
+        max_break_minutes,
     };
 
     let saved = app_state
@@ -491,6 +312,14 @@ pub async fn upsert_work_pattern(
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
+    enqueue_work_pattern_regen(
+        &app_state.db_client,
+        id,
+        saved.effective_from,
+        "WORK_PATTERN_UPSERT",
+    )
+    .await;
+
     Ok(Json(UpsertWorkPatternResp {
         status: "200".to_string(),
         data: saved,
@@ -541,6 +370,8 @@ pub async fn delete_work_pattern(
         return Err(HttpError::bad_request("work pattern tidak ditemukan"));
     }
 
+    enqueue_work_pattern_regen(&app_state.db_client, id, date, "WORK_PATTERN_DELETE").await;
+
     Ok(Json(SuccessResponse {
         status: "200".to_string(),
         data: "Sukses delete work pattern".to_string(),
@@ -1,6 +1,6 @@
 use crate::AppState;
 use crate::auth::rbac::UserRole;
-use crate::database::schedule::ScheduleRepo;
+use crate::database::schedule::{NewSchedule, ScheduleRepo};
 use crate::dtos::SuccessResponse;
 use crate::dtos::schedule::{CreateScheduleReq, ScheduleQuery, SchedulesResp, can_manage_schedule};
 use crate::error::HttpError;
@@ -47,16 +47,16 @@ pub async fn create_schedule(
 
     app_state
         .db_client
-        .create_schedule(
+        .create_schedule(NewSchedule {
             satker_id,
-            payload.user_id,
-            payload.schedule_date,
-            payload.schedule_type,
+            user_id: payload.user_id,
+            schedule_date: payload.schedule_date,
+            schedule_type: payload.schedule_type,
             start_time,
             end_time,
-            payload.notes,
-            user_claims.user_claims.user_id,
-        )
+            notes: payload.notes,
+            create_by: user_claims.user_claims.user_id,
+        })
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
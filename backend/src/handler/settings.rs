@@ -1,18 +1,46 @@
 use crate::AppState;
-use crate::database::settings::{SETTING_DEFAULT_TIMEZONE, SettingsRepo};
-use crate::dtos::settings::{TimezoneData, TimezoneResp, UpdateTimezoneReq};
+use crate::auth::rbac::UserRole;
+use crate::database::satker::SatkerRepo;
+use crate::database::settings::{
+    SETTING_ATTENDANCE_RULES, SETTING_DEFAULT_TIMEZONE, SettingsRepo, attendance_rules_key,
+};
+use crate::dtos::settings::{
+    AttendanceRules, AttendanceRulesDto, AttendanceRulesOverride, AttendanceRulesQuery,
+    AttendanceRulesResp, SettingAuditQuery, SettingAuditsResp, TimezoneData, TimezoneResp,
+    UpdateTimezoneReq,
+};
 use crate::error::HttpError;
 use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::attendance_rules::{
+    apply_rules_patch, revalidate_satker_overrides, satker_from_key, to_setting_audit_dto,
+    validate_rules_override,
+};
+use crate::services::authorization::ensure_can_access_satker;
+use crate::utils::attendance_rules_cache::set_rules_override_cache;
 use crate::utils::timezone_cache::set_timezone_cache;
-use axum::routing::get;
+use axum::extract::{Path, Query};
+use axum::http::StatusCode;
+use axum::routing::{get, put};
 use axum::{Extension, Json, Router};
 use chrono::{Datelike, Utc};
 use chrono_tz::Tz;
+use serde_json::{Map, Value};
 use std::str::FromStr;
 use std::sync::Arc;
+use uuid::Uuid;
 
 pub fn settings_handler() -> Router {
-    Router::new().route("/timezone", get(get_timezone).put(update_timezone))
+    Router::new()
+        .route("/timezone", get(get_timezone).put(update_timezone))
+        .route(
+            "/attendance-rules",
+            get(get_attendance_rules).put(update_global_attendance_rules),
+        )
+        .route("/attendance-rules/audit", get(list_attendance_rules_audit))
+        .route(
+            "/attendance-rules/satker/{satker_id}",
+            put(update_satker_attendance_rules).delete(delete_satker_attendance_rules),
+        )
 }
 
 pub async fn get_timezone(
@@ -72,9 +100,9 @@ pub async fn update_timezone(
 
     app_state
         .db_client
-        .upsert_setting(
+        .set_setting_audited(
             SETTING_DEFAULT_TIMEZONE,
-            &body.timezone,
+            Some(&body.timezone),
             auth.user_claims.user_id,
         )
         .await
@@ -92,3 +120,250 @@ pub async fn update_timezone(
         },
     }))
 }
+
+fn ensure_superadmin(auth: &AuthMiddleware) -> Result<(), HttpError> {
+    if auth.user_claims.role != UserRole::Superadmin {
+        return Err(HttpError::unauthorized(
+            "hanya SUPERADMIN yang boleh mengubah aturan absensi",
+        ));
+    }
+    Ok(())
+}
+
+/// Override tersimpan langsung dari DB (bukan cache) supaya admin melihat nilai terbaru.
+async fn load_rules_override(
+    app_state: &AppState,
+    satker_id: Option<Uuid>,
+) -> Result<Option<AttendanceRulesOverride>, HttpError> {
+    let key = attendance_rules_key(satker_id);
+    let raw = app_state
+        .db_client
+        .get_setting(&key)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    raw.map(|v| {
+        serde_json::from_str(&v)
+            .map_err(|e| HttpError::server_error(format!("setting {} tidak valid: {}", key, e)))
+    })
+    .transpose()
+}
+
+async fn build_rules_resp(
+    app_state: &AppState,
+    satker_id: Uuid,
+) -> Result<Json<AttendanceRulesResp>, HttpError> {
+    let global = load_rules_override(app_state, None)
+        .await?
+        .unwrap_or_default();
+    let satker = load_rules_override(app_state, Some(satker_id)).await?;
+
+    let defaults = AttendanceRules::default();
+    let mut effective = defaults.clone().apply(&global);
+    if let Some(o) = &satker {
+        effective = effective.apply(o);
+    }
+
+    Ok(Json(AttendanceRulesResp {
+        status: "200",
+        data: AttendanceRulesDto {
+            satker_id,
+            defaults,
+            global,
+            satker,
+            effective,
+        },
+    }))
+}
+
+/// Simpan override (None = hapus) + audit, lalu sinkronkan cache Redis.
+async fn save_rules_override(
+    app_state: &AppState,
+    auth: &AuthMiddleware,
+    satker_id: Option<Uuid>,
+    value: Option<&AttendanceRulesOverride>,
+) -> Result<(), HttpError> {
+    let raw = value
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    app_state
+        .db_client
+        .set_setting_audited(
+            &attendance_rules_key(satker_id),
+            raw.as_deref(),
+            auth.user_claims.user_id,
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    set_rules_override_cache(app_state, satker_id, value).await;
+    Ok(())
+}
+
+/// Aturan check-in/out satker (default, global, khusus satker & efektif).
+pub async fn get_attendance_rules(
+    Query(query): Query<AttendanceRulesQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(auth): Extension<AuthMiddleware>,
+) -> Result<Json<AttendanceRulesResp>, HttpError> {
+    let claims = &auth.user_claims;
+    match claims.role {
+        UserRole::Superadmin | UserRole::SatkerAdmin | UserRole::SatkerHead => {}
+        _ => return Err(HttpError::unauthorized("Tidak boleh".to_string())),
+    }
+
+    let satker_id = query.satker_id.unwrap_or(claims.satker_id);
+    ensure_can_access_satker(claims, satker_id)?;
+
+    build_rules_resp(&app_state, satker_id).await
+}
+
+/// Override khusus satker yang tersimpan, untuk dicek ulang saat aturan global berubah.
+async fn load_satker_rules_overrides(
+    app_state: &AppState,
+) -> Result<Vec<(Uuid, AttendanceRulesOverride)>, HttpError> {
+    let rows = app_state
+        .db_client
+        .list_settings_like(&format!("{}:satker:%", SETTING_ATTENDANCE_RULES))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    rows.into_iter()
+        .filter_map(|(key, raw)| {
+            let satker_id = satker_from_key(&key)?;
+            Some(
+                serde_json::from_str(&raw)
+                    .map(|o| (satker_id, o))
+                    .map_err(|e| {
+                        HttpError::server_error(format!("setting {} tidak valid: {}", key, e))
+                    }),
+            )
+        })
+        .collect()
+}
+
+/// Aturan global untuk semua satker (hanya superadmin). Body = field yang ingin diubah;
+/// field `null` kembali ke default, field yang tidak dikirim tetap memakai nilai tersimpan.
+pub async fn update_global_attendance_rules(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(auth): Extension<AuthMiddleware>,
+    Json(body): Json<Map<String, Value>>,
+) -> Result<Json<AttendanceRulesResp>, HttpError> {
+    ensure_superadmin(&auth)?;
+
+    let stored = load_rules_override(&app_state, None)
+        .await?
+        .unwrap_or_default();
+    let global = apply_rules_patch(&stored, &body)?;
+    validate_rules_override(&global, &AttendanceRules::default())?;
+
+    // override satker yang sudah ada harus tetap konsisten dengan global baru
+    revalidate_satker_overrides(
+        &AttendanceRules::default().apply(&global),
+        &load_satker_rules_overrides(&app_state).await?,
+    )?;
+
+    save_rules_override(&app_state, &auth, None, Some(&global)).await?;
+
+    build_rules_resp(&app_state, auth.user_claims.satker_id).await
+}
+
+/// Aturan khusus satker, menimpa aturan global (hanya superadmin).
+/// Body = field yang ingin diubah; field `null` kembali ikut aturan global,
+/// field yang tidak dikirim tetap memakai nilai tersimpan.
+pub async fn update_satker_attendance_rules(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(auth): Extension<AuthMiddleware>,
+    Path(satker_id): Path<Uuid>,
+    Json(body): Json<Map<String, Value>>,
+) -> Result<Json<AttendanceRulesResp>, HttpError> {
+    ensure_superadmin(&auth)?;
+
+    app_state
+        .db_client
+        .find_satker_by_id(satker_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::new(
+            "Satker tidak ditemukan",
+            StatusCode::NOT_FOUND,
+        ))?;
+
+    let global = load_rules_override(&app_state, None)
+        .await?
+        .unwrap_or_default();
+    let stored = load_rules_override(&app_state, Some(satker_id))
+        .await?
+        .unwrap_or_default();
+    let satker = apply_rules_patch(&stored, &body)?;
+    validate_rules_override(&satker, &AttendanceRules::default().apply(&global))?;
+
+    save_rules_override(&app_state, &auth, Some(satker_id), Some(&satker)).await?;
+
+    build_rules_resp(&app_state, satker_id).await
+}
+
+/// Hapus aturan khusus satker; satker kembali mengikuti aturan global.
+pub async fn delete_satker_attendance_rules(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(auth): Extension<AuthMiddleware>,
+    Path(satker_id): Path<Uuid>,
+) -> Result<Json<AttendanceRulesResp>, HttpError> {
+    ensure_superadmin(&auth)?;
+
+    save_rules_override(&app_state, &auth, Some(satker_id), None).await?;
+
+    build_rules_resp(&app_state, satker_id).await
+}
+
+/// Riwayat perubahan aturan absensi (global & satker).
+/// Admin/kepala satker hanya melihat perubahan global + satkernya sendiri.
+pub async fn list_attendance_rules_audit(
+    Query(query): Query<SettingAuditQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(auth): Extension<AuthMiddleware>,
+) -> Result<Json<SettingAuditsResp>, HttpError> {
+    let claims = &auth.user_claims;
+    let satker_id = match claims.role {
+        UserRole::Superadmin => query.satker_id,
+        UserRole::SatkerAdmin | UserRole::SatkerHead => {
+            let id = query.satker_id.unwrap_or(claims.satker_id);
+            ensure_can_access_satker(claims, id)?;
+            Some(id)
+        }
+        _ => return Err(HttpError::unauthorized("Tidak boleh".to_string())),
+    };
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+
+    let mut rows = match satker_id {
+        Some(id) => {
+            let mut rows = app_state
+                .db_client
+                .list_setting_audits(&attendance_rules_key(Some(id)), limit)
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?;
+            rows.extend(
+                app_state
+                    .db_client
+                    .list_setting_audits(SETTING_ATTENDANCE_RULES, limit)
+                    .await
+                    .map_err(|e| HttpError::server_error(e.to_string()))?,
+            );
+            rows
+        }
+        None => app_state
+            .db_client
+            .list_setting_audits(&format!("{}%", SETTING_ATTENDANCE_RULES), limit)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?,
+    };
+    rows.sort_by_key(|r| std::cmp::Reverse(r.changed_at));
+    rows.truncate(limit as usize);
+
+    Ok(Json(SettingAuditsResp {
+        status: "200",
+        data: rows.into_iter().map(to_setting_audit_dto).collect(),
+    }))
+}
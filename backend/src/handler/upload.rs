@@ -3,10 +3,12 @@ use axum::{Extension, Json, Router, extract::Multipart, response::IntoResponse};
 use std::sync::Arc;
 
 use crate::AppState;
-use crate::dtos::upload::{UploadSelfieData, UploadSelfieResp};
+use crate::database::selfie_upload::SelfieUploadRepo;
+use crate::dtos::upload::{NewSelfieUpload, UploadSelfieData, UploadSelfieResp};
 use crate::error::HttpError;
 use crate::middleware::auth_middleware::AuthMiddleware;
 use crate::services::upload::save_selfie_upload;
+use crate::utils::timezone_cache::get_timezone_cached;
 
 pub fn uploads_handler() -> Router {
     Router::new().route("/selfie", post(upload_selfie))
@@ -17,13 +19,32 @@ pub async fn upload_selfie(
     Extension(user_claims): Extension<AuthMiddleware>,
     mp: Multipart,
 ) -> Result<impl IntoResponse, HttpError> {
-    let key =
-        save_selfie_upload(&app_state.upload_dir, user_claims.user_claims.user_id, mp).await?;
+    let user_id = user_claims.user_claims.user_id;
+    let tz = get_timezone_cached(&app_state).await?;
+    let stored = save_selfie_upload(&app_state.storage, user_id, mp).await?;
+    let captured_at = stored.image.captured_at(tz);
+
+    app_state
+        .db_client
+        .create_selfie_upload(NewSelfieUpload {
+            object_key: &stored.object_key,
+            user_id,
+            width: stored.image.width as i32,
+            height: stored.image.height as i32,
+            size_bytes: stored.size_bytes,
+            phash: &stored.image.phash,
+            captured_at,
+        })
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
 
     Ok(Json(UploadSelfieResp {
         status: "200",
         data: UploadSelfieData {
-            selfie_object_key: key,
+            selfie_object_key: stored.object_key,
+            width: stored.image.width,
+            height: stored.image.height,
+            captured_at,
         },
     }))
 }
@@ -1,5 +1,5 @@
 use crate::AppState;
-use crate::database::user::UserRepo;
+use crate::database::user::{NewUser, UpdateUser, UserRepo};
 use crate::dtos::SuccessResponse;
 use crate::dtos::user::{
     AdminSetPasswordReq, ChangeMyPasswordReq, CreateUserReq, UpdateMyProfileReq, UpdateUserReq,
@@ -88,16 +88,16 @@ pub async fn create_user(
 
     app_state
         .db_client
-        .create_user(
+        .create_user(NewUser {
             satker_id,
             rank_id,
-            payload.nrp,
-            payload.full_name,
-            Some(payload.email),
-            payload.phone,
-            target_role,
-            hash_password,
-        )
+            nrp: payload.nrp,
+            full_name: payload.full_name,
+            email: Some(payload.email),
+            phone: payload.phone,
+            role: target_role,
+            password_hash: hash_password,
+        })
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
@@ -119,12 +119,14 @@ pub async fn update_user(
         .db_client
         .update_user(
             id,
-            payload.satker_id,
-            payload.rank_id,
-            payload.nrp,
-            payload.full_name,
-            payload.email,
-            payload.phone,
+            UpdateUser {
+                satker_id: payload.satker_id,
+                rank_id: payload.rank_id,
+                nrp: payload.nrp,
+                full_name: payload.full_name,
+                email: payload.email,
+                phone: payload.phone,
+            },
         )
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
@@ -320,8 +322,8 @@ pub async fn upload_my_photo(
     Extension(user_claims): Extension<AuthMiddleware>,
     mp: Multipart,
 ) -> Result<impl IntoResponse, HttpError> {
-    let key = save_profile_photo_upload(&app_state.upload_dir, user_claims.user_claims.user_id, mp)
-        .await?;
+    let key =
+        save_profile_photo_upload(&app_state.storage, user_claims.user_claims.user_id, mp).await?;
 
     app_state
         .db_client
@@ -382,7 +384,7 @@ pub async fn admin_upload_photo(
         return Err(HttpError::unauthorized("forbidden"));
     }
 
-    let key = save_profile_photo_upload(&app_state.upload_dir, id, mp).await?;
+    let key = save_profile_photo_upload(&app_state.storage, id, mp).await?;
 
     app_state
         .db_client
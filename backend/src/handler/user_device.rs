@@ -0,0 +1,309 @@
+use axum::extract::{Path, Query};
+use axum::response::IntoResponse;
+use axum::routing::{delete, get, put};
+use axum::{Extension, Json, Router};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::AppState;
+use crate::auth::rbac::UserRole;
+use crate::database::user::UserRepo;
+use crate::database::user_device::UserDeviceRepo;
+use crate::dtos::SuccessResponse;
+use crate::dtos::user_device::{
+    ApproveDeviceBindingReq, CreateDeviceBindingReq, DeviceBindingRequestResp,
+    DeviceBindingRequestsResp, ListDeviceBindingQuery, ListUserDevicesQuery,
+    RejectDeviceBindingReq, TransferUserDeviceReq, UserDeviceResp, UserDevicesResp,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::authorization::ensure_can_access_satker;
+use crate::services::user_device::{
+    DeviceBindingAction, approve_device_binding_request, create_device_binding_request,
+    respond_device_binding_request, transfer_user_device,
+};
+use crate::utils::attendance_rules_cache::get_attendance_rules_cached;
+
+pub fn user_device_handler() -> Router {
+    Router::new()
+        .route("/", get(list_devices))
+        .route("/me", get(list_my_devices))
+        .route("/{id}", delete(unbind_device))
+        .route("/{id}/transfer", put(transfer_device))
+        .route("/requests", get(list_requests).post(create_request))
+        .route("/requests/{id}", get(get_request))
+        .route("/requests/{id}/cancel", put(cancel_request))
+        .route("/requests/{id}/approve", put(approve_request))
+        .route("/requests/{id}/reject", put(reject_request))
+}
+
+/// Device yang ter-bind ke akun sendiri.
+pub async fn list_my_devices(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let rows = app_state
+        .db_client
+        .list_user_devices(None, Some(user_claims.user_claims.user_id))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(UserDevicesResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+/// Device ter-bind di satker (admin/kepala: satker sendiri; superadmin: semua / filter).
+pub async fn list_devices(
+    Query(query): Query<ListUserDevicesQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+    let satker_id = match claims.role {
+        UserRole::Superadmin => query.satker_id,
+        UserRole::SatkerAdmin | UserRole::SatkerHead => Some(claims.satker_id),
+        UserRole::Member => return Err(HttpError::unauthorized("Tidak boleh".to_string())),
+    };
+
+    let rows = app_state
+        .db_client
+        .list_user_devices(satker_id, query.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(UserDevicesResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+/// Lepas binding device; user bisa bind device pertamanya lagi saat check-in berikutnya.
+pub async fn unbind_device(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let device = app_state
+        .db_client
+        .find_user_device_by_id(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("device tidak ditemukan"))?;
+    ensure_can_access_satker(&user_claims.user_claims, device.satker_id)?;
+
+    app_state
+        .db_client
+        .unbind_device(&device.device_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully unbound device".to_string(),
+    }))
+}
+
+/// Pindahkan device ke user lain, mis. HP yang terlanjur dipakai login akun yang salah.
+pub async fn transfer_device(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<TransferUserDeviceReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+
+    let target = app_state
+        .db_client
+        .find_user_by_id(payload.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("user tujuan tidak ditemukan"))?;
+    ensure_can_access_satker(claims, target.satker_id)?;
+
+    let rules = get_attendance_rules_cached(&app_state, target.satker_id).await?;
+    transfer_user_device(
+        &app_state.db_client,
+        claims,
+        id,
+        target.id,
+        rules.max_devices_per_user,
+    )
+    .await?;
+
+    let device = app_state
+        .db_client
+        .find_user_device_by_id(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::server_error("device gagal dibaca ulang"))?;
+
+    Ok(Json(UserDeviceResp {
+        status: "200",
+        data: device,
+    }))
+}
+
+pub async fn list_requests(
+    Query(query): Query<ListDeviceBindingQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+    let mut satker_id = query.satker_id;
+    let mut user_id = query.user_id;
+
+    // role scoping
+    match claims.role {
+        UserRole::Superadmin => {}
+        UserRole::SatkerAdmin | UserRole::SatkerHead => {
+            satker_id = Some(claims.satker_id);
+        }
+        UserRole::Member => {
+            satker_id = Some(claims.satker_id);
+            user_id = Some(claims.user_id);
+        }
+    }
+
+    let status = query.status.map(|v| v.trim().to_uppercase());
+
+    let rows = app_state
+        .db_client
+        .list_device_binding_requests(satker_id, user_id, status.as_deref())
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(DeviceBindingRequestsResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+pub async fn get_request(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let row = app_state
+        .db_client
+        .find_device_binding_request(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("permintaan device tidak ditemukan"))?;
+
+    let claims = &user_claims.user_claims;
+    if row.user_id != claims.user_id {
+        ensure_can_access_satker(claims, row.satker_id)?;
+    }
+
+    Ok(Json(DeviceBindingRequestResp {
+        status: "200",
+        data: row,
+    }))
+}
+
+/// Ajukan device baru (ganti HP, atau device yang sudah ter-bind ke akun lain).
+pub async fn create_request(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(payload): Json<CreateDeviceBindingReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    let id = create_device_binding_request(&app_state.db_client, &user_claims.user_claims, payload)
+        .await?;
+
+    let row = app_state
+        .db_client
+        .find_device_binding_request(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::server_error("permintaan device gagal dibaca ulang"))?;
+
+    Ok(Json(DeviceBindingRequestResp {
+        status: "200",
+        data: row,
+    }))
+}
+
+pub async fn cancel_request(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    respond_device_binding_request(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        id,
+        DeviceBindingAction::Cancel,
+        None,
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully canceled device request".to_string(),
+    }))
+}
+
+pub async fn approve_request(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ApproveDeviceBindingReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    let request = app_state
+        .db_client
+        .find_device_binding_request(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("permintaan device tidak ditemukan"))?;
+    let rules = get_attendance_rules_cached(&app_state, request.satker_id).await?;
+
+    approve_device_binding_request(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        id,
+        payload,
+        rules.max_devices_per_user,
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully approved device request".to_string(),
+    }))
+}
+
+pub async fn reject_request(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RejectDeviceBindingReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    respond_device_binding_request(
+        &app_state.db_client,
+        &user_claims.user_claims,
+        id,
+        DeviceBindingAction::Reject,
+        Some(payload.reject_reason),
+    )
+    .await?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully rejected device request".to_string(),
+    }))
+}
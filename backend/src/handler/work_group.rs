@@ -0,0 +1,235 @@
+use crate::AppState;
+use crate::auth::rbac::UserRole;
+use crate::database::duty_rotation::DutyRotationRepo;
+use crate::database::work_group::WorkGroupRepo;
+use crate::dtos::SuccessResponse;
+use crate::dtos::work_group::{
+    ListWorkGroupsQuery, UpsertWorkGroupReq, WorkGroupDto, WorkGroupResp, WorkGroupsResp,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::authorization::ensure_can_access_satker;
+use axum::extract::{Path, Query};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+pub fn work_group_handler() -> Router {
+    Router::new()
+        .route("/", get(list_work_groups).post(create_work_group))
+        .route(
+            "/{id}",
+            get(get_work_group)
+                .put(update_work_group)
+                .delete(delete_work_group),
+        )
+}
+
+async fn load_work_group(
+    app_state: &AppState,
+    user_claims: &AuthMiddleware,
+    id: Uuid,
+) -> Result<WorkGroupDto, HttpError> {
+    let group = app_state
+        .db_client
+        .find_work_group(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("kelompok kerja tidak ditemukan"))?;
+
+    ensure_can_access_satker(&user_claims.user_claims, group.satker_id)?;
+
+    Ok(group)
+}
+
+/// Validasi payload; return (name, description, member_ids unik).
+async fn validate_work_group(
+    app_state: &AppState,
+    satker_id: Uuid,
+    payload: UpsertWorkGroupReq,
+) -> Result<(String, Option<String>, Vec<Uuid>), HttpError> {
+    payload
+        .validate()
+        .map_err(|e| HttpError::bad_request(format!("validate error: {}", e)))?;
+
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return Err(HttpError::bad_request("name: wajib diisi"));
+    }
+    let description = payload
+        .description
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+
+    let member_ids: Vec<Uuid> = payload
+        .member_ids
+        .into_iter()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    if !member_ids.is_empty() {
+        let outside = app_state
+            .db_client
+            .find_users_outside_satker(satker_id, &member_ids)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        if !outside.is_empty() {
+            let ids: Vec<String> = outside.iter().map(|id| id.to_string()).collect();
+            return Err(HttpError::bad_request(format!(
+                "anggota kelompok bukan user aktif satker ini: {}",
+                ids.join(", ")
+            )));
+        }
+    }
+
+    Ok((name, description, member_ids))
+}
+
+fn map_work_group_save_error(e: sqlx::Error) -> HttpError {
+    if e.as_database_error()
+        .is_some_and(|d| d.is_unique_violation())
+    {
+        return HttpError::bad_request("nama kelompok sudah dipakai di satker ini");
+    }
+    HttpError::server_error(e.to_string())
+}
+
+pub async fn list_work_groups(
+    Query(query): Query<ListWorkGroupsQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+
+    // satker scoping: non-superadmin forced to own satker
+    let satker_id = if claims.role == UserRole::Superadmin {
+        query.satker_id
+    } else {
+        ensure_can_access_satker(claims, claims.satker_id)?;
+        Some(claims.satker_id)
+    };
+
+    let rows = app_state
+        .db_client
+        .list_work_groups(satker_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(WorkGroupsResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+pub async fn get_work_group(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    let group = load_work_group(&app_state, &user_claims, id).await?;
+
+    Ok(Json(WorkGroupResp {
+        status: "200",
+        data: group,
+    }))
+}
+
+pub async fn create_work_group(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(payload): Json<UpsertWorkGroupReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+
+    // enforce satker for non-superadmin
+    let satker_id = if claims.role == UserRole::Superadmin {
+        payload
+            .satker_id
+            .ok_or_else(|| HttpError::bad_request("satker_id wajib diisi"))?
+    } else {
+        claims.satker_id
+    };
+    ensure_can_access_satker(claims, satker_id)?;
+
+    let (name, description, member_ids) =
+        validate_work_group(&app_state, satker_id, payload).await?;
+
+    let id = app_state
+        .db_client
+        .create_work_group(satker_id, &name, description, &member_ids, claims.user_id)
+        .await
+        .map_err(map_work_group_save_error)?;
+
+    let group = app_state
+        .db_client
+        .find_work_group(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::server_error("kelompok kerja gagal dibaca ulang"))?;
+
+    Ok(Json(WorkGroupResp {
+        status: "200",
+        data: group,
+    }))
+}
+
+pub async fn update_work_group(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpsertWorkGroupReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let existing = load_work_group(&app_state, &user_claims, id).await?;
+
+    if payload
+        .satker_id
+        .is_some_and(|satker_id| satker_id != existing.satker_id)
+    {
+        return Err(HttpError::bad_request("satker kelompok tidak bisa diubah"));
+    }
+
+    let (name, description, member_ids) =
+        validate_work_group(&app_state, existing.satker_id, payload).await?;
+
+    app_state
+        .db_client
+        .update_work_group(id, &name, description, &member_ids)
+        .await
+        .map_err(map_work_group_save_error)?;
+
+    let group = app_state
+        .db_client
+        .find_work_group(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("kelompok kerja tidak ditemukan"))?;
+
+    Ok(Json(WorkGroupResp {
+        status: "200",
+        data: group,
+    }))
+}
+
+pub async fn delete_work_group(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    load_work_group(&app_state, &user_claims, id).await?;
+
+    app_state
+        .db_client
+        .delete_work_group(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully deleted work group".to_string(),
+    }))
+}
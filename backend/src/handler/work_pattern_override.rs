@@ -0,0 +1,208 @@
+use crate::AppState;
+use crate::auth::rbac::UserRole;
+use crate::database::user::UserRepo;
+use crate::database::work_pattern_override::WorkPatternOverrideRepo;
+use crate::dtos::SuccessResponse;
+use crate::dtos::work_pattern_override::{
+    EffectiveWorkPatternDto, EffectiveWorkPatternQuery, EffectiveWorkPatternResp,
+    ListWorkPatternOverridesQuery, UpsertWorkPatternOverrideReq, WorkPatternOverrideDto,
+    WorkPatternOverrideResp, WorkPatternOverridesResp,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::AuthMiddleware;
+use crate::services::authorization::ensure_can_access_satker;
+use crate::services::work_pattern::{
+    build_work_pattern_override, map_override_save_error, resolve_override_satker,
+    resolve_work_hours,
+};
+use crate::utils::timezone_cache::get_timezone_cached;
+use axum::extract::{Path, Query};
+use axum::response::IntoResponse;
+use axum::routing::{get, put};
+use axum::{Extension, Json, Router};
+use chrono::Utc;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub fn work_pattern_override_handler() -> Router {
+    Router::new()
+        .route(
+            "/",
+            get(list_work_pattern_overrides).post(create_work_pattern_override),
+        )
+        .route("/effective", get(get_effective_work_pattern))
+        .route(
+            "/{id}",
+            put(update_work_pattern_override).delete(delete_work_pattern_override),
+        )
+}
+
+async fn load_work_pattern_override(
+    app_state: &AppState,
+    user_claims: &AuthMiddleware,
+    id: Uuid,
+) -> Result<WorkPatternOverrideDto, HttpError> {
+    let row = app_state
+        .db_client
+        .find_work_pattern_override(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("override jam kerja tidak ditemukan"))?;
+
+    ensure_can_access_satker(&user_claims.user_claims, row.satker_id)?;
+
+    Ok(row)
+}
+
+pub async fn list_work_pattern_overrides(
+    Query(query): Query<ListWorkPatternOverridesQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+
+    // satker scoping: non-superadmin forced to own satker
+    let satker_id = if claims.role == UserRole::Superadmin {
+        query.satker_id
+    } else {
+        ensure_can_access_satker(claims, claims.satker_id)?;
+        Some(claims.satker_id)
+    };
+
+    let rows = app_state
+        .db_client
+        .list_work_pattern_overrides(satker_id, query.user_id, query.group_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(WorkPatternOverridesResp {
+        status: "200",
+        data: rows,
+    }))
+}
+
+pub async fn create_work_pattern_override(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Json(payload): Json<UpsertWorkPatternOverrideReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+
+    let input = build_work_pattern_override(payload)?;
+    let satker_id = resolve_override_satker(&app_state.db_client, &input).await?;
+    ensure_can_access_satker(claims, satker_id)?;
+
+    let id = app_state
+        .db_client
+        .create_work_pattern_override(satker_id, &input, claims.user_id)
+        .await
+        .map_err(map_override_save_error)?;
+
+    let row = app_state
+        .db_client
+        .find_work_pattern_override(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::server_error("override jam kerja gagal dibaca ulang"))?;
+
+    Ok(Json(WorkPatternOverrideResp {
+        status: "200",
+        data: row,
+    }))
+}
+
+pub async fn update_work_pattern_override(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpsertWorkPatternOverrideReq>,
+) -> Result<impl IntoResponse, HttpError> {
+    let existing = load_work_pattern_override(&app_state, &user_claims, id).await?;
+
+    let input = build_work_pattern_override(payload)?;
+    // target boleh diganti, tapi tetap di satker yang sama
+    if resolve_override_satker(&app_state.db_client, &input).await? != existing.satker_id {
+        return Err(HttpError::bad_request(
+            "target override harus di satker yang sama",
+        ));
+    }
+
+    app_state
+        .db_client
+        .update_work_pattern_override(id, &input)
+        .await
+        .map_err(map_override_save_error)?;
+
+    let row = app_state
+        .db_client
+        .find_work_pattern_override(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("override jam kerja tidak ditemukan"))?;
+
+    Ok(Json(WorkPatternOverrideResp {
+        status: "200",
+        data: row,
+    }))
+}
+
+pub async fn delete_work_pattern_override(
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, HttpError> {
+    load_work_pattern_override(&app_state, &user_claims, id).await?;
+
+    app_state
+        .db_client
+        .delete_work_pattern_override(id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(Json(SuccessResponse {
+        status: "200".to_string(),
+        data: "Successfully deleted work pattern override".to_string(),
+    }))
+}
+
+/// Jam kerja efektif user di satu tanggal (default: diri sendiri, hari ini).
+pub async fn get_effective_work_pattern(
+    Query(query): Query<EffectiveWorkPatternQuery>,
+    Extension(app_state): Extension<Arc<AppState>>,
+    Extension(user_claims): Extension<AuthMiddleware>,
+) -> Result<impl IntoResponse, HttpError> {
+    let claims = &user_claims.user_claims;
+
+    let (user_id, satker_id) = match query.user_id {
+        Some(user_id) if user_id != claims.user_id => {
+            let user = app_state
+                .db_client
+                .find_user_by_id(user_id)
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?
+                .ok_or_else(|| HttpError::bad_request("User tidak ditemukan"))?;
+            ensure_can_access_satker(claims, user.satker_id)?;
+            (user.id, user.satker_id)
+        }
+        _ => (claims.user_id, claims.satker_id),
+    };
+
+    let date = match query.date {
+        Some(date) => date,
+        None => {
+            let tz = get_timezone_cached(&app_state).await?;
+            Utc::now().with_timezone(&tz).date_naive()
+        }
+    };
+
+    let hours = resolve_work_hours(&app_state.db_client, satker_id, user_id, date).await?;
+
+    Ok(Json(EffectiveWorkPatternResp {
+        status: "200",
+        data: EffectiveWorkPatternDto {
+            user_id,
+            date,
+            hours,
+        },
+    }))
+}
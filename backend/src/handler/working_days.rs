@@ -14,6 +14,7 @@ use crate::database::work_calendar::WorkCalendarRepo;
 use crate::dtos::working_days::{
     ListWorkingDaysQuery, UpsertWorkingDayReq, WorkingDayDto, WorkingDayResp, WorkingDaysResp,
 };
+use crate::services::calendar_regen::enqueue_calendar_regen;
 use crate::utils::time_parser::parse_optional_time_field;
 use crate::{AppState, error::HttpError, middleware::auth_middleware::AuthMiddleware};
 
@@ -104,7 +105,7 @@ pub async fn upsert_working_day(
 
     app_state
         .db_client
-        .upsert_calendar_day(
+        .upsert_manual_calendar_day(
             satker_id,
             work_date,
             payload.day_type,
@@ -122,7 +123,7 @@ pub async fn upsert_working_day(
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
     let row = rows
-        .get(0)
+        .first()
         .ok_or_else(|| HttpError::server_error("failed to read back working day"))?;
 
     Ok(Json(WorkingDayResp {
@@ -148,6 +149,16 @@ pub async fn delete_working_day(
         .await
         .map_err(|e| HttpError::server_error(e.to_string()))?;
 
+    // override manual dihapus -> hari kembali mengikuti work pattern / libur
+    enqueue_calendar_regen(
+        &app_state.db_client,
+        satker_id,
+        work_date,
+        Some(work_date),
+        "WORKING_DAY_RESET",
+    )
+    .await;
+
     Ok(Json(StatusOnlyResp { status: "200" }))
 }
 
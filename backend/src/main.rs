@@ -1,11 +1,17 @@
+use crate::cli::{Cli, Command, print_error};
 use crate::config::config::Config;
-use crate::db::DBClient;
+use crate::db::{DBClient, connect_pool};
 use crate::routes::create_router;
+use crate::services::attestation_verifier::{AttestationVerifier, build_attestation_verifier};
+use crate::services::calendar_regen::spawn_calendar_regen_worker;
+use crate::services::face_verifier::{FaceVerifier, build_face_verifier};
+use crate::services::migration::ensure_schema_up_to_date;
+use crate::services::retention::spawn_retention_worker;
+use crate::services::storage::{Storage, build_storage};
 use axum::http::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE};
 use axum::http::{HeaderName, Method};
+use clap::Parser;
 use redis::aio::ConnectionManager;
-use sqlx::postgres::PgPoolOptions;
-use std::path::PathBuf;
 use std::sync::Arc;
 use tower_http::cors::CorsLayer;
 use tracing_subscriber::filter::LevelFilter;
@@ -13,6 +19,7 @@ use tracing_subscriber::filter::LevelFilter;
 // alias runwib="TZ=Asia/Jakarta faketime"
 
 mod auth;
+mod cli;
 mod config;
 mod constants;
 mod database;
@@ -31,24 +38,69 @@ pub struct AppState {
     pub env: Config,
     pub db_client: DBClient,
     pub redis_client: ConnectionManager,
-    pub upload_dir: PathBuf,
+    /// Storage file upload (lokal / S3-compatible).
+    pub storage: Storage,
+    /// None = verifikasi wajah di server tidak aktif.
+    pub face_verifier: Option<Arc<dyn FaceVerifier>>,
+    /// None = attestation request absensi tidak diperiksa.
+    pub attestation_verifier: Option<Arc<dyn AttestationVerifier>>,
 }
 
 #[tokio::main]
 async fn main() {
+    let cli = Cli::parse();
+
+    dotenv::dotenv().ok();
+
+    match cli.command {
+        None | Some(Command::Serve) => serve().await,
+        Some(Command::Migrate(cmd)) => {
+            init_cli_tracing();
+            let db_client = cli_db_client().await;
+            let code = cli::migrate::run(cmd, &db_client).await;
+            std::process::exit(code);
+        }
+        Some(Command::Admin(cmd)) => {
+            init_cli_tracing();
+            let db_client = cli_db_client().await;
+            let code = cli::admin::run(cmd, &db_client).await;
+            std::process::exit(code);
+        }
+    }
+}
+
+/// Subcommand CLI: log ke stderr (level WARN) supaya stdout bersih untuk JSON.
+fn init_cli_tracing() {
     tracing_subscriber::fmt()
-        .with_max_level(LevelFilter::DEBUG)
+        .with_max_level(LevelFilter::WARN)
+        .with_writer(std::io::stderr)
         .init();
+}
 
-    dotenv::dotenv().ok();
+/// Subcommand CLI cukup butuh DATABASE_URL (tanpa Redis/JWT).
+async fn cli_db_client() -> DBClient {
+    let Ok(database_url) = std::env::var("DATABASE_URL") else {
+        print_error("DATABASE_URL must be set in the environment");
+        std::process::exit(1);
+    };
+
+    match connect_pool(&database_url, 2).await {
+        Ok(pool) => DBClient::new(pool),
+        Err(err) => {
+            print_error(&format!("Failed to connect database : {}", err));
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn serve() {
+    tracing_subscriber::fmt()
+        .with_max_level(LevelFilter::DEBUG)
+        .init();
 
     let config = Config::init();
 
-    let pool = match PgPoolOptions::new()
-        .max_connections(10)
-        .connect(&config.database_url)
-        .await
-    {
+    let pool = match connect_pool(&config.database_url, 10).await {
         Ok(pool) => {
             println!("Successfully connected to database");
             pool
@@ -59,6 +111,14 @@ async fn main() {
         }
     };
 
+    let db_client = DBClient::new(pool);
+
+    // jangan jalan di atas schema yang lebih lama dari yang dibutuhkan kode
+    if let Err(err) = ensure_schema_up_to_date(&db_client, config.auto_migrate).await {
+        println!("Database schema check failed : {}", err);
+        std::process::exit(1);
+    }
+
     let redis_pool = match redis::Client::open(config.redis_url.clone()) {
         Ok(redis_pool) => redis_pool,
         Err(err) => {
@@ -86,15 +146,43 @@ async fn main() {
         .allow_credentials(true)
         .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE]);
 
-    let db_client = DBClient::new(pool);
+    let face_verifier = match build_face_verifier(config.face_verifier.as_deref()) {
+        Ok(v) => v,
+        Err(err) => {
+            println!("Failed to init face verifier : {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let attestation_verifier =
+        match build_attestation_verifier(config.attestation_verifier.as_deref()) {
+            Ok(v) => v,
+            Err(err) => {
+                println!("Failed to init attestation verifier : {}", err);
+                std::process::exit(1);
+            }
+        };
+
+    let storage = match build_storage(&config.storage) {
+        Ok(v) => v,
+        Err(err) => {
+            println!("Failed to init storage : {}", err);
+            std::process::exit(1);
+        }
+    };
 
     let app_state = AppState {
         env: config.clone(),
         db_client,
         redis_client,
-        upload_dir: std::path::PathBuf::from("./uploads"),
+        storage,
+        face_verifier,
+        attestation_verifier,
     };
 
+    spawn_calendar_regen_worker(app_state.db_client.clone());
+    spawn_retention_worker(app_state.db_client.clone(), app_state.storage.clone());
+
     let app = create_router(Arc::new(app_state.clone())).layer(cors.clone());
 
     println!("Server is running on http://localhost:{}", config.port);
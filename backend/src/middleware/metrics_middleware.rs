@@ -0,0 +1,29 @@
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::time::Instant;
+
+use crate::services::metrics::record_http_request;
+
+/// Catat jumlah & latency request per route (pola path, bukan path mentah) dan status.
+pub async fn metrics_middleware(req: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = req.method().to_string();
+    // pakai pola route supaya label tidak meledak karena id di path
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let resp = next.run(req).await;
+
+    record_http_request(
+        &method,
+        &route,
+        resp.status().as_u16(),
+        start.elapsed().as_secs_f64(),
+    );
+
+    resp
+}
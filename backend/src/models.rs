@@ -1,7 +1,7 @@
 use crate::auth::rbac::UserRole;
 use crate::constants::{
-    AttendanceEventType, AttendanceLeaveType, AttendanceStatus, CalendarDayType, HolidayKind,
-    HolidayScope, LeaveStatus, LeaveType, ScheduleType,
+    AttendanceEventType, AttendanceLeaveType, CalendarDayType, HolidayKind, HolidayScope,
+    LeaveStatus, LeaveType,
 };
 use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -47,15 +47,6 @@ pub struct Satker {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize, Serialize, sqlx::FromRow, Clone)]
-pub struct SatkerHead {
-    pub id: Uuid,
-    pub satker_id: Uuid,
-    pub user_id: Uuid,
-    pub active_from: NaiveDate,       // NOT NULL DATE
-    pub active_to: Option<NaiveDate>, // Nullable DATE (untuk yang masih menjabat)
-}
-
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
 pub struct LeaveRequest {
     pub id: Uuid,
@@ -86,19 +77,6 @@ pub struct Geofence {
     pub created_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
-pub struct AttendanceSession {
-    pub id: Uuid,
-    pub satker_id: Uuid,
-    pub user_id: Uuid,
-    pub work_date: NaiveDate,
-    pub check_in_at: Option<DateTime<Utc>>, // Option karena bisa null
-    pub check_out_at: Option<DateTime<Utc>>,
-    pub status: AttendanceStatus,
-    pub created_at: Option<DateTime<Utc>>,
-    pub updated_at: Option<DateTime<Utc>>,
-}
-
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
 pub struct AttendanceEvent {
     pub id: Uuid,
@@ -135,36 +113,6 @@ pub struct AttendanceEvent {
     pub attendance_leave_notes: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
-pub struct DutySchedule {
-    pub id: Uuid,
-    pub satker_id: Uuid,
-    pub user_id: Uuid,
-    pub schedule_date: NaiveDate, // SQL: DATE
-
-    pub schedule_type: ScheduleType,
-
-    // Gunakan Option karena start_time dan end_time bisa NULL di DB
-    pub start_time: Option<NaiveTime>, // SQL: TIME
-    pub end_time: Option<NaiveTime>,   // SQL: TIME
-
-    pub notes: Option<String>,
-    pub created_by: Option<Uuid>,
-    pub created_at: DateTime<Utc>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
-pub struct UserDevice {
-    pub id: Uuid,
-    pub user_id: Uuid,
-    pub device_id: Uuid,
-    pub device_model: Option<String>,
-    pub android_model: Option<String>,
-    pub android_version: Option<String>,
-    pub app_build: Option<String>,
-    pub client_version: Option<String>,
-}
-
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow, Clone)]
 pub struct SatkerWorkPattern {
     pub id: Uuid,
@@ -182,6 +130,10 @@ pub struct SatkerWorkPattern {
     pub work_start: NaiveTime,
     pub work_end: NaiveTime,
     pub half_day_end: Option<NaiveTime>,
+    /// Jumlah pasangan check-in/out yang diizinkan per hari (1 = satu pasang).
+    pub max_segments: i16,
+    /// Batas total menit istirahat per hari (None = tanpa batas).
+    pub max_break_minutes: Option<i16>,
     pub created_at: Option<DateTime<Utc>>,
 }
 
@@ -204,4 +156,6 @@ pub struct SatkerCalendarDay {
     pub expected_start: Option<NaiveTime>,
     pub expected_end: Option<NaiveTime>,
     pub note: Option<String>,
+    /// Diubah manual; tidak ditimpa saat kalender di-generate ulang.
+    pub is_manual: bool,
 }
@@ -1,16 +1,25 @@
 use crate::AppState;
 use crate::handler::announcement::announcement_handler;
 use crate::handler::attendance::attendance_handler;
+use crate::handler::attendance_anomaly::attendance_anomaly_handler;
 use crate::handler::attendance_challenge::attendance_challenge_handler;
 use crate::handler::auth::auth_handler;
+use crate::handler::calendar_feed::{calendar_feed_handler, ics_feed_handler};
 use crate::handler::dashboard::dashboard_handler;
+use crate::handler::duty_rotation::duty_rotation_handler;
 use crate::handler::duty_schedule::duty_schedule_handler;
 use crate::handler::duty_schedule_request::duty_schedule_request_handler;
+use crate::handler::duty_schedule_swap::duty_schedule_swap_handler;
+use crate::handler::face_enrollment::face_enrollment_handler;
 use crate::handler::files::files_handler;
 use crate::handler::geofence::geofence_handler;
+use crate::handler::health::health_handler;
 use crate::handler::holiday::holiday_handler;
 use crate::handler::leave_request::leave_request_handler;
+use crate::handler::metrics::get_metrics;
+use crate::handler::overtime::overtime_handler;
 use crate::handler::rank::rank_handler;
+use crate::handler::retention::retention_handler;
 use crate::handler::satker::satker_handler;
 use crate::handler::satker_head::satker_head_handler;
 use crate::handler::schedule::schedule_handler;
@@ -18,17 +27,17 @@ use crate::handler::settings::settings_handler;
 use crate::handler::tukin::tukin_handler;
 use crate::handler::upload::uploads_handler;
 use crate::handler::user::user_handler;
+use crate::handler::user_device::user_device_handler;
+use crate::handler::work_group::work_group_handler;
+use crate::handler::work_pattern_override::work_pattern_override_handler;
 use crate::handler::working_days::working_days_handler;
 use crate::middleware::auth_middleware::auth_middleware;
+use crate::middleware::metrics_middleware::metrics_middleware;
 use axum::routing::get;
 use axum::{Extension, Router, middleware};
 use std::sync::Arc;
 use tower_http::trace::TraceLayer;
 
-async fn health() -> &'static str {
-    "ok"
-}
-
 pub fn create_router(app_state: Arc<AppState>) -> Router {
     let api_router = Router::new()
         .nest("/auth", auth_handler())
@@ -36,6 +45,14 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
             "/users",
             user_handler().layer(middleware::from_fn(auth_middleware)),
         )
+        .nest(
+            "/devices",
+            user_device_handler().layer(middleware::from_fn(auth_middleware)),
+        )
+        .nest(
+            "/face-enrollments",
+            face_enrollment_handler().layer(middleware::from_fn(auth_middleware)),
+        )
         .nest(
             "/satkers",
             satker_handler().layer(middleware::from_fn(auth_middleware)),
@@ -60,6 +77,10 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
             "/attendance",
             attendance_handler().layer(middleware::from_fn(auth_middleware)),
         )
+        .nest(
+            "/attendance-anomalies",
+            attendance_anomaly_handler().layer(middleware::from_fn(auth_middleware)),
+        )
         .nest(
             "/schedules",
             schedule_handler().layer(middleware::from_fn(auth_middleware)),
@@ -68,6 +89,10 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
             "/duty-schedules",
             duty_schedule_handler().layer(middleware::from_fn(auth_middleware)),
         )
+        .nest(
+            "/duty-rotations",
+            duty_rotation_handler().layer(middleware::from_fn(auth_middleware)),
+        )
         .nest(
             "/tukin",
             tukin_handler().layer(middleware::from_fn(auth_middleware)),
@@ -88,6 +113,14 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
             "/working-days",
             working_days_handler().layer(middleware::from_fn(auth_middleware)),
         )
+        .nest(
+            "/work-groups",
+            work_group_handler().layer(middleware::from_fn(auth_middleware)),
+        )
+        .nest(
+            "/work-pattern-overrides",
+            work_pattern_override_handler().layer(middleware::from_fn(auth_middleware)),
+        )
         .nest(
             "/settings",
             settings_handler().layer(middleware::from_fn(auth_middleware)),
@@ -96,6 +129,10 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
             "/ranks",
             rank_handler().layer(middleware::from_fn(auth_middleware)),
         )
+        .nest(
+            "/retention",
+            retention_handler().layer(middleware::from_fn(auth_middleware)),
+        )
         .nest(
             "/announcements",
             announcement_handler().layer(middleware::from_fn(auth_middleware)),
@@ -108,9 +145,28 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
             "/duty-schedule-requests",
             duty_schedule_request_handler().layer(middleware::from_fn(auth_middleware)),
         )
-        .route("/health", get(health))
+        .nest(
+            "/duty-schedule-swaps",
+            duty_schedule_swap_handler().layer(middleware::from_fn(auth_middleware)),
+        )
+        .nest(
+            "/overtime",
+            overtime_handler().layer(middleware::from_fn(auth_middleware)),
+        )
+        .nest(
+            "/calendar-feeds",
+            calendar_feed_handler().layer(middleware::from_fn(auth_middleware)),
+        )
+        // feed ICS untuk klien kalender, autentikasi lewat token di URL (bukan JWT)
+        .nest("/ics", ics_feed_handler())
+        .nest("/health", health_handler())
+        .layer(middleware::from_fn(metrics_middleware))
         .layer(TraceLayer::new_for_http())
-        .layer(Extension(app_state));
+        .layer(Extension(app_state.clone()));
 
-    Router::new().nest("/api", api_router)
+    Router::new()
+        .nest("/api", api_router)
+        // Prometheus scrape endpoint (di luar /api, tidak lewat auth JWT)
+        .route("/metrics", get(get_metrics))
+        .layer(Extension(app_state))
 }
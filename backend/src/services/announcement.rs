@@ -0,0 +1,89 @@
+use uuid::Uuid;
+
+use crate::auth::rbac::UserRole;
+use crate::dtos::announcement::{BODY_FORMAT_MARKDOWN, BODY_FORMAT_PLAIN};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::UserClaims;
+use crate::services::markdown::{markdown_to_plain_text, sanitize_markdown};
+
+/// Normalisasi body pengumuman -> (body, body_format, body_text).
+///
+/// - PLAIN: body disimpan apa adanya (trim), body_text = body.
+/// - MARKDOWN: body disanitasi, body_text = hasil render plain-text.
+pub fn normalize_announcement_body(
+    body: &str,
+    body_format: Option<&str>,
+) -> Result<(String, String, String), HttpError> {
+    let format = body_format
+        .map(|f| f.trim().to_uppercase())
+        .unwrap_or_else(|| BODY_FORMAT_PLAIN.to_string());
+
+    let (body, body_text) = match format.as_str() {
+        BODY_FORMAT_PLAIN => {
+            let b = body.trim().to_string();
+            (b.clone(), b)
+        }
+        BODY_FORMAT_MARKDOWN => {
+            let b = sanitize_markdown(body);
+            let text = markdown_to_plain_text(&b);
+            (b, text)
+        }
+        _ => return Err(HttpError::bad_request("body_format tidak valid")),
+    };
+
+    if body.is_empty() || body_text.is_empty() {
+        return Err(HttpError::bad_request("Body wajib diisi".to_string()));
+    }
+
+    Ok((body, format, body_text))
+}
+
+/// Aturan lihat pengumuman, sama dengan `list_visible_announcements`
+/// (dan `list_manageable_announcements` untuk admin/head yang boleh melihat yang nonaktif).
+pub fn can_view_announcement(
+    claims: &UserClaims,
+    scope: &str,
+    satker_id: Option<Uuid>,
+    is_active: bool,
+) -> bool {
+    if claims.role == UserRole::Superadmin {
+        return true;
+    }
+
+    let in_scope = scope == "GLOBAL" || (scope == "SATKER" && satker_id == Some(claims.satker_id));
+    if !in_scope {
+        return false;
+    }
+
+    is_active || matches!(claims.role, UserRole::SatkerAdmin | UserRole::SatkerHead)
+}
+
+/// SUPERADMIN boleh semua; SATKER_ADMIN hanya pengumuman SATKER miliknya.
+/// `action` dipakai untuk pesan error, mis. "mengubah" / "menghapus".
+pub fn ensure_can_manage_announcement(
+    claims: &UserClaims,
+    scope: &str,
+    satker_id: Option<Uuid>,
+    action: &str,
+) -> Result<(), HttpError> {
+    if !claims.role.is_admin() {
+        return Err(HttpError::unauthorized("Unauthorized".to_string()));
+    }
+
+    if claims.role == UserRole::SatkerAdmin {
+        if scope == "GLOBAL" {
+            return Err(HttpError::unauthorized(format!(
+                "SATKER_ADMIN tidak boleh {} pengumuman GLOBAL",
+                action
+            )));
+        }
+        if satker_id != Some(claims.satker_id) {
+            return Err(HttpError::unauthorized(format!(
+                "Tidak boleh {} pengumuman satker lain",
+                action
+            )));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,238 @@
+//! Absensi mencurigakan: event tetap diterima tapi ditandai (anomali) dan masuk antrian
+//! tinjauan admin satker. Admin mengonfirmasi, atau membatalkan sehingga sesi menjadi INVALID.
+
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::constants::{AttendanceEventType, AttendanceLeaveType, AttendanceStatus};
+use crate::database::attendance_anomaly::{
+    AttendanceAnomalyRepo, lock_attendance_anomaly, set_attendance_session_status,
+    update_attendance_anomaly_status,
+};
+use crate::database::selfie_upload::SelfieUploadRepo;
+use crate::db::DBClient;
+use crate::dtos::attendance_anomaly::{
+    ANOMALY_CONFIRMED, ANOMALY_INVALIDATED, ANOMALY_LOW_FACE_SCORE, ANOMALY_OUT_OF_FENCE_LEAVE,
+    ANOMALY_PENDING, ANOMALY_SAME_COORDINATES, ANOMALY_SELFIE_REUSED, ANOMALY_SHARED_DEVICE,
+};
+use crate::dtos::face_enrollment::{FACE_FLAGGED, FaceCheckDto};
+use crate::dtos::settings::AttendanceRules;
+use crate::dtos::upload::SelfieUploadMeta;
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::UserClaims;
+use crate::services::authorization::ensure_can_access_satker;
+use crate::services::image_pipeline::dhash;
+use crate::services::metrics::record_attendance_flag;
+use crate::services::storage::Storage;
+use crate::services::upload::SELFIE_FOLDER;
+
+/// Data event check-in/out yang dinilai (sebelum event disimpan).
+pub struct AnomalyCheck<'a> {
+    pub event: AttendanceEventType,
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    pub device_id: &'a str,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Skor wajah dari aplikasi.
+    pub face_match_score: Option<f64>,
+    pub face_check: Option<&'a FaceCheckDto>,
+    pub selfie_phash: Option<&'a str>,
+    pub out_of_fence: bool,
+    pub leave_type: AttendanceLeaveType,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AnomalyReviewAction {
+    Confirm,
+    Invalidate,
+}
+
+/// Phash + waktu ambil selfie dari hasil olah upload. Selfie lama (tanpa baris
+/// selfie_uploads) dihitung ulang dari file; None kalau tidak ada / tidak terbaca.
+pub async fn load_selfie_meta(
+    db: &DBClient,
+    storage: &Storage,
+    selfie_object_key: Option<&str>,
+) -> Option<SelfieUploadMeta> {
+    let key = selfie_object_key.map(str::trim).filter(|k| !k.is_empty())?;
+    match db.find_selfie_upload(key).await {
+        Ok(Some(meta)) => return Some(meta),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("metadata selfie {} gagal dibaca: {}", key, e),
+    }
+
+    let bytes = match storage.read(SELFIE_FOLDER, key).await {
+        Ok(bytes) => bytes?,
+        Err(e) => {
+            tracing::warn!(
+                "selfie {} tidak bisa dibaca untuk phash: {}",
+                key,
+                e.message
+            );
+            return None;
+        }
+    };
+
+    let phash = tokio::task::spawn_blocking(move || match image::load_from_memory(&bytes) {
+        Ok(img) => Some(dhash(&img)),
+        Err(e) => {
+            tracing::warn!("selfie tidak bisa dibaca untuk phash: {}", e);
+            None
+        }
+    })
+    .await
+    .ok()
+    .flatten()?;
+
+    Some(SelfieUploadMeta {
+        phash,
+        captured_at: None,
+    })
+}
+
+/// Alasan event ditandai (kosong = bersih).
+pub async fn detect_attendance_anomalies(
+    db: &DBClient,
+    rules: &AttendanceRules,
+    check: &AnomalyCheck<'_>,
+) -> Result<Vec<String>, HttpError> {
+    let now = Utc::now();
+    let mut reasons = Vec::new();
+
+    let low_client_score = check
+        .face_match_score
+        .is_some_and(|s| s < rules.anomaly_face_score_below);
+    let server_flagged = check.face_check.is_some_and(|f| f.status == FACE_FLAGGED);
+    if low_client_score || server_flagged {
+        reasons.push(ANOMALY_LOW_FACE_SCORE.to_string());
+    }
+
+    if rules.anomaly_device_share_days > 0
+        && db
+            .device_used_by_other_user(
+                check.device_id,
+                check.user_id,
+                now - Duration::days(rules.anomaly_device_share_days),
+            )
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?
+    {
+        reasons.push(ANOMALY_SHARED_DEVICE.to_string());
+    }
+
+    if rules.anomaly_selfie_reuse_days > 0
+        && let Some(phash) = check.selfie_phash
+        && db
+            .selfie_phash_used(phash, now - Duration::days(rules.anomaly_selfie_reuse_days))
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?
+    {
+        reasons.push(ANOMALY_SELFIE_REUSED.to_string());
+    }
+
+    if rules.anomaly_same_coords_days > 0 {
+        let previous = db
+            .list_recent_event_coordinates(
+                check.user_id,
+                check.event,
+                check.session_id,
+                rules.anomaly_same_coords_days,
+            )
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        // GPS asli selalu bergeser sedikit; koordinat identik tiap hari = lokasi di-set manual
+        let identical = previous.len() as i64 == rules.anomaly_same_coords_days
+            && previous
+                .iter()
+                .all(|(lat, lon)| *lat == Some(check.latitude) && *lon == Some(check.longitude));
+        if identical {
+            reasons.push(ANOMALY_SAME_COORDINATES.to_string());
+        }
+    }
+
+    if check.out_of_fence && !matches!(check.leave_type, AttendanceLeaveType::Normal) {
+        reasons.push(ANOMALY_OUT_OF_FENCE_LEAVE.to_string());
+    }
+
+    Ok(reasons)
+}
+
+/// Masukkan event ke antrian tinjauan kalau ada alasan.
+pub async fn flag_attendance_event(
+    db: &DBClient,
+    event: AttendanceEventType,
+    event_id: Uuid,
+    session_id: Uuid,
+    satker_id: Uuid,
+    user_id: Uuid,
+    reasons: &[String],
+) -> Result<(), HttpError> {
+    if reasons.is_empty() {
+        return Ok(());
+    }
+
+    db.create_attendance_anomaly(event_id, session_id, satker_id, user_id, event, reasons)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    for reason in reasons {
+        record_attendance_flag(event, reason);
+    }
+    Ok(())
+}
+
+/// Tinjau anomali PENDING. Pembatalan men-set sesi absensi menjadi INVALID.
+pub async fn review_attendance_anomaly(
+    db: &DBClient,
+    claims: &UserClaims,
+    id: Uuid,
+    action: AnomalyReviewAction,
+    review_note: Option<String>,
+) -> Result<(), HttpError> {
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let row = lock_attendance_anomaly(&mut tx, id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("anomali absensi tidak ditemukan"))?;
+
+    ensure_can_access_satker(claims, row.satker_id)?;
+    if row.status != ANOMALY_PENDING {
+        return Err(HttpError::bad_request(format!(
+            "anomali sudah ditinjau ({})",
+            row.status
+        )));
+    }
+
+    let to_status = match action {
+        AnomalyReviewAction::Confirm => ANOMALY_CONFIRMED,
+        AnomalyReviewAction::Invalidate => ANOMALY_INVALIDATED,
+    };
+
+    update_attendance_anomaly_status(
+        &mut tx,
+        id,
+        to_status,
+        claims.user_id,
+        review_note.as_deref(),
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if matches!(action, AnomalyReviewAction::Invalidate) {
+        set_attendance_session_status(&mut tx, row.session_id, AttendanceStatus::Invalid)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(())
+}
@@ -0,0 +1,346 @@
+//! Rekap kehadiran bulanan resmi per satker: satu baris per anggota, satu kode per hari.
+//!
+//! Prioritas kode per hari mengikuti perhitungan tukin: ijin/cuti yang disetujui,
+//! lalu jadwal dinas, lalu hari libur kalender satker, lalu absensi biasa.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::constants::{AttendanceLeaveType, CalendarDayType, LeaveType};
+use crate::database::attendance_rekap::AttendanceRekapRepo;
+use crate::database::duty_rotation::DutyRotationRepo;
+use crate::database::duty_schedule::DutyScheduleRepo;
+use crate::database::satker::SatkerRepo;
+use crate::database::work_calendar::WorkCalendarRepo;
+use crate::database::work_pattern_override::WorkPatternOverrideRepo;
+use crate::db::DBClient;
+use crate::dtos::attendance_rekap::{
+    MonthlyRekapDto, MonthlyRekapMemberDto, RekapCode, RekapSessionRow, RekapTotals,
+};
+use crate::dtos::duty_rotation::UserLeaveSpanRow;
+use crate::dtos::work_pattern_override::UserWorkPatternOverrideRow;
+use crate::error::HttpError;
+use crate::services::tukin::parse_month;
+use crate::services::work_pattern::{hours_from_override, pick_effective_override};
+
+/// Jendela check-in jadwal dinas, sama dengan perhitungan tukin.
+const DUTY_EARLY: Duration = Duration::minutes(30);
+const DUTY_LATE: Duration = Duration::minutes(180);
+
+fn leave_code(tipe: LeaveType) -> RekapCode {
+    match tipe {
+        LeaveType::Ijin => RekapCode::Ijin,
+        LeaveType::Sakit => RekapCode::Sakit,
+        LeaveType::Cuti => RekapCode::Cuti,
+        LeaveType::DinasLuar => RekapCode::DinasLuar,
+    }
+}
+
+fn local_midnight_utc(tz: Tz, date: NaiveDate) -> DateTime<Utc> {
+    let naive = date.and_hms_opt(0, 0, 0).unwrap();
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .unwrap_or_else(|| tz.from_utc_datetime(&naive))
+        .with_timezone(&Utc)
+}
+
+type CalendarMap = HashMap<NaiveDate, (CalendarDayType, Option<NaiveTime>)>;
+type DutyMap = HashMap<(Uuid, NaiveDate), Vec<(DateTime<Utc>, DateTime<Utc>)>>;
+
+/// Data satu anggota selama sebulan untuk menentukan kode harian.
+struct MemberDays<'a> {
+    tz: Tz,
+    today: NaiveDate,
+    now: DateTime<Utc>,
+    calendar: &'a CalendarMap,
+    leaves: Vec<&'a UserLeaveSpanRow>,
+    duties: &'a DutyMap,
+    user_id: Uuid,
+    sessions: &'a [RekapSessionRow],
+    overrides: &'a [UserWorkPatternOverrideRow],
+}
+
+impl MemberDays<'_> {
+    /// None = hari belum selesai (tidak diberi kode).
+    fn code_for(&self, d: NaiveDate) -> Option<RekapCode> {
+        // 1) ijin/cuti yang disetujui menang mutlak
+        if let Some(lr) = self
+            .leaves
+            .iter()
+            .find(|l| d >= l.start_date && d <= l.end_date)
+        {
+            return Some(leave_code(lr.tipe));
+        }
+
+        // 2) jadwal dinas (hari libur tetap wajib): hadir kalau ada check-in di jendelanya
+        if let Some(spans) = self.duties.get(&(self.user_id, d)) {
+            let present = spans.iter().any(|(start, end)| {
+                self.sessions.iter().filter(|s| !s.is_invalid()).any(|s| {
+                    s.check_in_at
+                        .is_some_and(|ci| ci >= *start - DUTY_EARLY && ci <= *end + DUTY_LATE)
+                })
+            });
+            if present {
+                return Some(RekapCode::Dinas);
+            }
+            let finished = spans.iter().all(|(_, end)| *end + DUTY_LATE < self.now);
+            return finished.then_some(RekapCode::Alpa);
+        }
+
+        // 3) libur kalender satker
+        let (day_type, expected_start) = self
+            .calendar
+            .get(&d)
+            .copied()
+            .unwrap_or((CalendarDayType::Workday, None));
+        if day_type == CalendarDayType::Holiday {
+            return Some(RekapCode::Libur);
+        }
+        if d > self.today {
+            return None;
+        }
+
+        // 4) absensi biasa; hari ini belum dianggap alpa selama belum check-in
+        let sess = self.sessions.iter().find(|s| s.work_date == d);
+        // session yang dibatalkan admin (review anomali) = tidak hadir
+        if sess.is_some_and(|s| s.is_invalid()) {
+            return Some(RekapCode::Alpa);
+        }
+        let Some((sess, check_in_at)) = sess.and_then(|s| s.check_in_at.map(|ci| (s, ci))) else {
+            return (d < self.today).then_some(RekapCode::Alpa);
+        };
+
+        match sess.check_in_leave_type {
+            Some(AttendanceLeaveType::JadwalDinas) => return Some(RekapCode::Dinas),
+            // tanpa pengajuan yang disetujui tidak dihitung hadir (sama dengan tukin)
+            Some(
+                AttendanceLeaveType::DinasLuar
+                | AttendanceLeaveType::Ijin
+                | AttendanceLeaveType::Sakit,
+            ) => return Some(RekapCode::Alpa),
+            _ => {}
+        }
+
+        // jam masuk efektif: override user/kelompok, atau expected_start kalender
+        let late_after = match pick_effective_override(self.overrides, d) {
+            Some(o) => Some(hours_from_override(o).late_after()),
+            None => expected_start,
+        };
+        let late = late_after.is_some_and(|t| {
+            let naive = d.and_time(t);
+            let expected = self
+                .tz
+                .from_local_datetime(&naive)
+                .single()
+                .unwrap_or_else(|| self.tz.from_utc_datetime(&naive));
+            check_in_at > expected.with_timezone(&Utc)
+        });
+
+        Some(if late {
+            RekapCode::Terlambat
+        } else {
+            RekapCode::Hadir
+        })
+    }
+}
+
+/// Susun rekap bulan `month` (YYYY-MM) untuk satu satker.
+/// `satker_id` harus sudah di-scope sesuai role pemanggil.
+pub async fn build_monthly_rekap(
+    db: &DBClient,
+    tz: Tz,
+    satker_id: Uuid,
+    month: &str,
+) -> Result<MonthlyRekapDto, HttpError> {
+    let (period_start, period_end_exclusive) = parse_month(month)?;
+    let period_end = period_end_exclusive.pred_opt().unwrap();
+    let today = Utc::now().with_timezone(&tz).date_naive();
+
+    let satker = db
+        .find_satker_by_id(satker_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or(HttpError::bad_request("Satker tidak ditemukan".to_string()))?;
+
+    let members = db
+        .list_rekap_members(satker_id, period_start, period_end)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let calendar = db
+        .list_calendar_days(satker_id, period_start, period_end)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let cal_map: CalendarMap = calendar
+        .into_iter()
+        .map(|d| (d.work_date, (d.day_type, d.expected_start)))
+        .collect();
+
+    let user_ids: Vec<Uuid> = members.iter().map(|m| m.user_id).collect();
+    let leaves = db
+        .list_approved_leaves_for_users(&user_ids, period_start, period_end)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let mut sessions: HashMap<Uuid, Vec<RekapSessionRow>> = HashMap::new();
+    for s in db
+        .list_rekap_sessions(satker_id, period_start, period_end)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+    {
+        sessions.entry(s.user_id).or_default().push(s);
+    }
+
+    // jadwal dinas dikelompokkan per (user, tanggal lokal start_at)
+    let duties = db
+        .list_duty_schedules(
+            Some(satker_id),
+            None,
+            local_midnight_utc(tz, period_start),
+            local_midnight_utc(tz, period_end_exclusive),
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let mut duty_map: DutyMap = HashMap::new();
+    for ds in duties {
+        duty_map
+            .entry((ds.user_id, ds.start_at.with_timezone(&tz).date_naive()))
+            .or_default()
+            .push((ds.start_at, ds.end_at));
+    }
+
+    let dates: Vec<NaiveDate> = period_start
+        .iter_days()
+        .take_while(|d| *d < period_end_exclusive)
+        .collect();
+    let holidays: Vec<NaiveDate> = dates
+        .iter()
+        .copied()
+        .filter(|d| matches!(cal_map.get(d), Some((CalendarDayType::Holiday, _))))
+        .collect();
+
+    let now = Utc::now();
+    let mut rows = Vec::with_capacity(members.len());
+
+    for m in members {
+        let overrides = db
+            .list_user_work_pattern_overrides(m.user_id, period_start, period_end)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        let user_sessions = sessions.remove(&m.user_id).unwrap_or_default();
+
+        let ctx = MemberDays {
+            tz,
+            today,
+            now,
+            calendar: &cal_map,
+            leaves: leaves.iter().filter(|l| l.user_id == m.user_id).collect(),
+            duties: &duty_map,
+            user_id: m.user_id,
+            sessions: &user_sessions,
+            overrides: &overrides,
+        };
+
+        let mut days = Vec::with_capacity(dates.len());
+        let mut totals = RekapTotals::default();
+        for d in &dates {
+            let code = ctx.code_for(*d);
+            if let Some(code) = code {
+                totals.add(code);
+            }
+            days.push(code);
+        }
+
+        rows.push(MonthlyRekapMemberDto {
+            user_id: m.user_id,
+            nrp: m.nrp,
+            full_name: m.full_name,
+            rank_name: m.rank_name,
+            days,
+            totals,
+        });
+    }
+
+    let signatory = db
+        .find_current_satker_head(satker_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(MonthlyRekapDto {
+        satker_id,
+        satker_code: satker.code,
+        satker_name: satker.name,
+        month: month.to_string(),
+        dates,
+        holidays,
+        members: rows,
+        signatory,
+        generated_at: now,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(work_date: NaiveDate, status: &str, check_in_at: DateTime<Utc>) -> RekapSessionRow {
+        RekapSessionRow {
+            user_id: Uuid::nil(),
+            work_date,
+            status: status.to_string(),
+            check_in_at: Some(check_in_at),
+            check_in_leave_type: Some(AttendanceLeaveType::Normal),
+        }
+    }
+
+    fn code(sessions: &[RekapSessionRow], duties: &DutyMap, d: NaiveDate) -> Option<RekapCode> {
+        let calendar = CalendarMap::new();
+        let now = Utc.with_ymd_and_hms(2026, 3, 31, 12, 0, 0).unwrap();
+        MemberDays {
+            tz: chrono_tz::Asia::Jakarta,
+            today: now.date_naive(),
+            now,
+            calendar: &calendar,
+            leaves: Vec::new(),
+            duties,
+            user_id: Uuid::nil(),
+            sessions,
+            overrides: &[],
+        }
+        .code_for(d)
+    }
+
+    #[test]
+    fn invalid_session_is_coded_as_alpa() {
+        let d = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let ci = Utc.with_ymd_and_hms(2026, 3, 2, 0, 30, 0).unwrap();
+        let duties = DutyMap::new();
+
+        assert_eq!(
+            code(&[session(d, "CLOSED", ci)], &duties, d),
+            Some(RekapCode::Hadir)
+        );
+        assert_eq!(
+            code(&[session(d, "INVALID", ci)], &duties, d),
+            Some(RekapCode::Alpa)
+        );
+    }
+
+    #[test]
+    fn invalid_session_does_not_count_for_duty() {
+        let d = NaiveDate::from_ymd_opt(2026, 3, 7).unwrap();
+        let start = Utc.with_ymd_and_hms(2026, 3, 7, 1, 0, 0).unwrap();
+        let duties = DutyMap::from([((Uuid::nil(), d), vec![(start, start + Duration::hours(8))])]);
+
+        assert_eq!(
+            code(&[session(d, "CLOSED", start)], &duties, d),
+            Some(RekapCode::Dinas)
+        );
+        assert_eq!(
+            code(&[session(d, "INVALID", start)], &duties, d),
+            Some(RekapCode::Alpa)
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! Validasi aturan check-in/out (app_settings `attendance_rules` global & per satker).
+
+use crate::database::settings::SETTING_ATTENDANCE_RULES;
+use crate::dtos::settings::{
+    AttendanceRules, AttendanceRulesOverride, SettingAuditDto, SettingAuditRow,
+};
+use crate::error::HttpError;
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+fn check_i64(field: &str, value: Option<i64>, min: i64, max: i64) -> Result<(), HttpError> {
+    match value {
+        Some(v) if !(min..=max).contains(&v) => Err(HttpError::bad_request(format!(
+            "{}: harus {}..{}",
+            field, min, max
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn check_f64(field: &str, value: Option<f64>, min: f64, max: f64) -> Result<(), HttpError> {
+    match value {
+        Some(v) if !v.is_finite() || v < min || v > max => Err(HttpError::bad_request(format!(
+            "{}: harus {}..{}",
+            field, min, max
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Cek rentang tiap field yang diisi, lalu konsistensi aturan efektif terhadap `base`
+/// (default+global untuk satker, default untuk global).
+pub fn validate_rules_override(
+    o: &AttendanceRulesOverride,
+    base: &AttendanceRules,
+) -> Result<(), HttpError> {
+    check_i64("early_checkin_hours", o.early_checkin_hours, 0, 12)?;
+    check_i64("duty_grace_hours", o.duty_grace_hours, 0, 24)?;
+    check_i64("duty_max_checkout_hours", o.duty_max_checkout_hours, 1, 72)?;
+    check_i64("duty_card_grace_minutes", o.duty_card_grace_minutes, 0, 240)?;
+    check_f64("max_accuracy_meters", o.max_accuracy_meters, 5.0, 1000.0)?;
+    check_f64(
+        "teleport_distance_m",
+        o.teleport_distance_m,
+        100.0,
+        100_000.0,
+    )?;
+    check_i64("teleport_window_secs", o.teleport_window_secs, 10, 3600)?;
+    check_f64("max_speed_mps", o.max_speed_mps, 5.0, 350.0)?;
+    check_i64("challenge_ttl_secs", o.challenge_ttl_secs, 15, 600)?;
+    check_i64(
+        "challenge_user_limit_per_min",
+        o.challenge_user_limit_per_min,
+        1,
+        120,
+    )?;
+    check_i64(
+        "challenge_device_limit_per_min",
+        o.challenge_device_limit_per_min,
+        1,
+        120,
+    )?;
+    check_i64("max_devices_per_user", o.max_devices_per_user, 1, 10)?;
+    check_f64("face_reject_below", o.face_reject_below, 0.0, 1.0)?;
+    check_f64("face_accept_at", o.face_accept_at, 0.0, 1.0)?;
+    check_f64(
+        "anomaly_face_score_below",
+        o.anomaly_face_score_below,
+        0.0,
+        1.0,
+    )?;
+    check_i64(
+        "anomaly_device_share_days",
+        o.anomaly_device_share_days,
+        0,
+        365,
+    )?;
+    check_i64(
+        "anomaly_selfie_reuse_days",
+        o.anomaly_selfie_reuse_days,
+        0,
+        365,
+    )?;
+    check_i64(
+        "anomaly_same_coords_days",
+        o.anomaly_same_coords_days,
+        0,
+        30,
+    )?;
+    check_i64(
+        "proximity_min_rssi",
+        o.proximity_min_rssi.map(i64::from),
+        -120,
+        0,
+    )?;
+
+    let effective = base.clone().apply(o);
+    if effective.duty_max_checkout_hours < effective.duty_grace_hours {
+        return Err(HttpError::bad_request(
+            "duty_max_checkout_hours: harus >= duty_grace_hours",
+        ));
+    }
+    if effective.face_reject_below > effective.face_accept_at {
+        return Err(HttpError::bad_request(
+            "face_reject_below: harus <= face_accept_at",
+        ));
+    }
+    if effective.challenge_device_limit_per_min > effective.challenge_user_limit_per_min {
+        return Err(HttpError::bad_request(
+            "challenge_device_limit_per_min: harus <= challenge_user_limit_per_min",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Terapkan body update ke override tersimpan (JSON merge patch): field berisi nilai
+/// menimpa, field `null` dihapus sehingga kembali ikut level di atasnya, field yang
+/// tidak dikirim tetap.
+pub fn apply_rules_patch(
+    stored: &AttendanceRulesOverride,
+    patch: &Map<String, Value>,
+) -> Result<AttendanceRulesOverride, HttpError> {
+    // nama field & tipe dicek dulu, termasuk field yang di-reset
+    serde_json::from_value::<AttendanceRulesOverride>(Value::Object(patch.clone()))
+        .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+    let mut merged = match serde_json::to_value(stored) {
+        Ok(Value::Object(m)) => m,
+        _ => Map::new(),
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            merged.remove(key);
+        } else {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+
+    serde_json::from_value(Value::Object(merged)).map_err(|e| HttpError::bad_request(e.to_string()))
+}
+
+/// Override satker yang sudah ada harus tetap konsisten dengan `base` (default + global baru).
+pub fn revalidate_satker_overrides(
+    base: &AttendanceRules,
+    overrides: &[(Uuid, AttendanceRulesOverride)],
+) -> Result<(), HttpError> {
+    for (satker_id, satker) in overrides {
+        validate_rules_override(satker, base).map_err(|e| {
+            HttpError::bad_request(format!(
+                "aturan khusus satker {} jadi tidak valid: {}",
+                satker_id, e.message
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+/// `attendance_rules:satker:{uuid}` -> Some(uuid); global -> None.
+pub fn satker_from_key(key: &str) -> Option<Uuid> {
+    key.strip_prefix(SETTING_ATTENDANCE_RULES)
+        .and_then(|rest| rest.strip_prefix(":satker:"))
+        .and_then(|id| Uuid::parse_str(id).ok())
+}
+
+fn parse_json(raw: Option<String>) -> Option<serde_json::Value> {
+    raw.map(|v| serde_json::from_str(&v).unwrap_or(serde_json::Value::String(v)))
+}
+
+pub fn to_setting_audit_dto(row: SettingAuditRow) -> SettingAuditDto {
+    SettingAuditDto {
+        satker_id: satker_from_key(&row.key),
+        id: row.id,
+        key: row.key,
+        old_value: parse_json(row.old_value),
+        new_value: parse_json(row.new_value),
+        changed_by: row.changed_by,
+        changed_by_name: row.changed_by_name,
+        changed_at: row.changed_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn patch(v: Value) -> Map<String, Value> {
+        match v {
+            Value::Object(m) => m,
+            _ => panic!("patch harus object"),
+        }
+    }
+
+    fn stored() -> AttendanceRulesOverride {
+        AttendanceRulesOverride {
+            early_checkin_hours: Some(3),
+            max_devices_per_user: Some(4),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn patch_overrides_sent_fields_and_keeps_the_rest() {
+        let merged = apply_rules_patch(
+            &stored(),
+            &patch(json!({ "early_checkin_hours": 1, "face_required": true })),
+        )
+        .unwrap();
+        assert_eq!(merged.early_checkin_hours, Some(1));
+        assert_eq!(merged.face_required, Some(true));
+        assert_eq!(merged.max_devices_per_user, Some(4));
+        assert_eq!(merged.duty_grace_hours, None);
+    }
+
+    #[test]
+    fn patch_null_clears_field() {
+        let merged = apply_rules_patch(
+            &stored(),
+            &patch(json!({ "max_devices_per_user": null, "duty_grace_hours": null })),
+        )
+        .unwrap();
+        assert_eq!(merged.max_devices_per_user, None);
+        assert_eq!(merged.duty_grace_hours, None);
+        assert_eq!(merged.early_checkin_hours, Some(3));
+    }
+
+    #[test]
+    fn patch_rejects_unknown_field_and_wrong_type() {
+        assert!(apply_rules_patch(&stored(), &patch(json!({ "early_checkin": 1 }))).is_err());
+        assert!(apply_rules_patch(&stored(), &patch(json!({ "early_checkin": null }))).is_err());
+        assert!(apply_rules_patch(&stored(), &patch(json!({ "face_required": "ya" }))).is_err());
+    }
+
+    #[test]
+    fn validate_checks_range_and_effective_consistency() {
+        let base = AttendanceRules::default();
+        let out_of_range = AttendanceRulesOverride {
+            max_devices_per_user: Some(0),
+            ..Default::default()
+        };
+        assert!(validate_rules_override(&out_of_range, &base).is_err());
+
+        // face_reject_below 0.9 > face_accept_at default 0.8
+        let inconsistent = AttendanceRulesOverride {
+            face_reject_below: Some(0.9),
+            ..Default::default()
+        };
+        assert!(validate_rules_override(&inconsistent, &base).is_err());
+
+        let ok = AttendanceRulesOverride {
+            face_reject_below: Some(0.9),
+            face_accept_at: Some(0.95),
+            ..Default::default()
+        };
+        assert!(validate_rules_override(&ok, &base).is_ok());
+    }
+
+    #[test]
+    fn global_change_revalidates_existing_satker_overrides() {
+        let satker_id = Uuid::new_v4();
+        let satker = AttendanceRulesOverride {
+            face_reject_below: Some(0.7),
+            ..Default::default()
+        };
+        let overrides = vec![(satker_id, satker)];
+
+        let global_ok = AttendanceRulesOverride {
+            face_accept_at: Some(0.9),
+            ..Default::default()
+        };
+        let base = AttendanceRules::default().apply(&global_ok);
+        assert!(revalidate_satker_overrides(&base, &overrides).is_ok());
+
+        // global menurunkan face_accept_at di bawah face_reject_below milik satker
+        let global_bad = AttendanceRulesOverride {
+            face_accept_at: Some(0.6),
+            ..Default::default()
+        };
+        let base = AttendanceRules::default().apply(&global_bad);
+        let err = revalidate_satker_overrides(&base, &overrides).unwrap_err();
+        assert!(err.message.contains(&satker_id.to_string()));
+    }
+}
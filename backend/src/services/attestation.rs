@@ -0,0 +1,307 @@
+//! Attestation request absensi: aplikasi menandatangani payload + nonce challenge dengan kunci
+//! device, sehingga lokasi, waktu, dan selfie tidak bisa diubah di jalan atau diputar ulang.
+
+use chrono::Utc;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::constants::AttendanceEventType;
+use crate::database::user_device::UserDeviceRepo;
+use crate::dtos::attendance::AttendanceReq;
+use crate::dtos::attendance_challenge::ChallengePayload;
+use crate::dtos::settings::AttendanceRules;
+use crate::error::HttpError;
+use crate::services::attestation_verifier::AttestationContext;
+use crate::services::metrics::{ChallengeFailure, record_challenge_failure};
+
+const MESSAGE_VERSION: &str = "absensi-attest-v3";
+
+/// Hasil scan sebagai JSON `[[id, rssi], ...]` urut (id, rssi), supaya urutan scan di
+/// perangkat tidak berpengaruh dan isi id tidak bisa disusun ulang jadi entri lain.
+fn canonical_scans<'a>(scans: impl Iterator<Item = (&'a str, Option<i32>)>) -> String {
+    let mut entries: Vec<(&str, Option<i32>)> = scans.collect();
+    entries.sort();
+    serde_json::to_string(&entries).unwrap_or_default()
+}
+
+/// Teks bebas sebagai string JSON, supaya baris baru di dalamnya tidak menggeser baris berikutnya.
+fn canonical_text(value: Option<&str>) -> String {
+    serde_json::to_string(value.unwrap_or_default()).unwrap_or_default()
+}
+
+/// Request tanpa attestation hanya diterima kalau aturan tidak mewajibkan dan device belum
+/// punya kunci terdaftar; device yang sudah berkunci wajib selalu menandatangani request.
+fn ensure_attestation_optional(required: bool, has_device_key: bool) -> Result<(), HttpError> {
+    if required || has_device_key {
+        record_challenge_failure(ChallengeFailure::Attestation);
+        return Err(HttpError::bad_request(
+            "attestation wajib, silakan perbarui aplikasi",
+        ));
+    }
+    Ok(())
+}
+
+/// Pesan kanonik yang ditandatangani aplikasi, satu nilai per baris (`\n`):
+/// versi, event, challenge_id, nonce, user_id, device_id, latitude & longitude (7 desimal),
+/// accuracy_meters (2 desimal), is_mock (0/1), selfie_object_key, signed_at (unix ms),
+/// leave_type, wifi_scans `[[bssid, rssi], ...]`, ble_scans `[[beacon_id, rssi], ...]`,
+/// lalu leave_notes, destination, purpose sebagai string JSON (`""` kalau kosong).
+/// Nilai kosong lain ditulis sebagai string kosong; daftar scan kosong ditulis `[]`.
+pub fn attestation_message(
+    event: AttendanceEventType,
+    challenge_id: Uuid,
+    challenge: &ChallengePayload,
+    payload: &AttendanceReq,
+    signed_at: i64,
+) -> String {
+    [
+        MESSAGE_VERSION.to_string(),
+        event.as_str().to_string(),
+        challenge_id.to_string(),
+        challenge.nonce.clone(),
+        challenge.user_id.to_string(),
+        challenge.device_id.clone(),
+        format!("{:.7}", payload.latitude),
+        format!("{:.7}", payload.longitude),
+        payload
+            .accuracy_meters
+            .map(|v| format!("{:.2}", v))
+            .unwrap_or_default(),
+        if payload.is_mock.unwrap_or(false) {
+            "1"
+        } else {
+            "0"
+        }
+        .to_string(),
+        payload.selfie_object_key.clone().unwrap_or_default(),
+        signed_at.to_string(),
+        payload
+            .leave_type
+            .map(|t| t.as_str().to_string())
+            .unwrap_or_default(),
+        canonical_scans(
+            payload
+                .wifi_scans
+                .iter()
+                .flatten()
+                .map(|s| (s.bssid.as_str(), s.rssi)),
+        ),
+        canonical_scans(
+            payload
+                .ble_scans
+                .iter()
+                .flatten()
+                .map(|s| (s.beacon_id.as_str(), s.rssi)),
+        ),
+        canonical_text(payload.leave_notes.as_deref()),
+        canonical_text(payload.destination.as_deref()),
+        canonical_text(payload.purpose.as_deref()),
+    ]
+    .join("\n")
+}
+
+/// Verifikasi attestation request. Return nama verifier kalau request ter-attestasi;
+/// None kalau verifier tidak aktif, atau request tanpa attestation dari device tanpa kunci
+/// terdaftar dan aturan tidak mewajibkan.
+pub async fn verify_request_attestation(
+    app_state: &Arc<AppState>,
+    rules: &AttendanceRules,
+    event: AttendanceEventType,
+    challenge_id: Uuid,
+    challenge: &ChallengePayload,
+    payload: &AttendanceReq,
+) -> Result<Option<&'static str>, HttpError> {
+    let Some(verifier) = app_state.attestation_verifier.as_ref() else {
+        return Ok(None);
+    };
+
+    // kunci hanya tercatat lewat bind pertama / permintaan device yang disetujui admin
+    let device_public_key = app_state
+        .db_client
+        .find_device_public_key(&challenge.device_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let proof = payload
+        .attestation
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty());
+    let Some(proof) = proof else {
+        ensure_attestation_optional(rules.attestation_required, device_public_key.is_some())?;
+        return Ok(None);
+    };
+
+    let signed_at = payload
+        .signed_at
+        .ok_or_else(|| HttpError::bad_request("signed_at wajib diisi bersama attestation"))?;
+    // nonce sudah sekali pakai; batas ini menolak payload yang ditandatangani jauh sebelumnya
+    let skew_ms = (Utc::now().timestamp_millis() - signed_at).abs();
+    if skew_ms > rules.challenge_ttl_secs * 1000 {
+        record_challenge_failure(ChallengeFailure::Attestation);
+        return Err(HttpError::bad_request(
+            "waktu tanda tangan tidak valid, periksa jam perangkat",
+        ));
+    }
+
+    let Some(device_public_key) = device_public_key else {
+        record_challenge_failure(ChallengeFailure::Attestation);
+        return Err(HttpError::bad_request(
+            "device belum mendaftarkan kunci attestation, ajukan permintaan device ke admin satker",
+        ));
+    };
+
+    let message = attestation_message(event, challenge_id, challenge, payload, signed_at);
+    verifier
+        .verify(&AttestationContext {
+            device_public_key: &device_public_key,
+            message: message.as_bytes(),
+            proof,
+        })
+        .await
+        .inspect_err(|_| record_challenge_failure(ChallengeFailure::Attestation))?;
+
+    Ok(Some(verifier.name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn challenge() -> ChallengePayload {
+        ChallengePayload {
+            user_id: Uuid::nil(),
+            satker_id: Uuid::nil(),
+            device_id: "dev-1".to_string(),
+            nonce: "n0nce".to_string(),
+            exp_unix: 0,
+        }
+    }
+
+    fn payload(extra: serde_json::Value) -> AttendanceReq {
+        let mut base = serde_json::json!({
+            "challenge_id": Uuid::nil(),
+            "latitude": -6.2,
+            "longitude": 106.816666,
+        });
+        base.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(base).unwrap()
+    }
+
+    fn message(p: &AttendanceReq) -> String {
+        attestation_message(
+            AttendanceEventType::CheckIn,
+            Uuid::nil(),
+            &challenge(),
+            p,
+            1_700_000_000_000,
+        )
+    }
+
+    #[test]
+    fn message_covers_leave_type_and_scans() {
+        let msg = message(&payload(serde_json::json!({
+            "leave_type": "WFH",
+            "wifi_scans": [{"bssid": "bb:bb", "rssi": -70}, {"bssid": "aa:aa", "rssi": null}],
+            "ble_scans": [{"beacon_id": "b1", "rssi": -60}],
+        })));
+        let lines: Vec<&str> = msg.split('\n').collect();
+
+        assert_eq!(lines[0], "absensi-attest-v3");
+        assert_eq!(
+            &lines[12..15],
+            [
+                "WFH",
+                r#"[["aa:aa",null],["bb:bb",-70]]"#,
+                r#"[["b1",-60]]"#
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_order_does_not_change_message() {
+        let a = payload(serde_json::json!({
+            "wifi_scans": [{"bssid": "aa", "rssi": -50}, {"bssid": "bb", "rssi": -60}],
+        }));
+        let b = payload(serde_json::json!({
+            "wifi_scans": [{"bssid": "bb", "rssi": -60}, {"bssid": "aa", "rssi": -50}],
+        }));
+        assert_eq!(message(&a), message(&b));
+    }
+
+    #[test]
+    fn changed_scans_or_leave_type_change_message() {
+        let base = payload(serde_json::json!({
+            "leave_type": "NORMAL",
+            "wifi_scans": [{"bssid": "aa", "rssi": -50}],
+        }));
+        let other_leave = payload(serde_json::json!({
+            "leave_type": "WFA",
+            "wifi_scans": [{"bssid": "aa", "rssi": -50}],
+        }));
+        let other_rssi = payload(serde_json::json!({
+            "leave_type": "NORMAL",
+            "wifi_scans": [{"bssid": "aa", "rssi": -49}],
+        }));
+        // id yang berisi pemisah tidak boleh bisa dibaca sebagai dua entri
+        let merged = payload(serde_json::json!({
+            "leave_type": "NORMAL",
+            "wifi_scans": [{"bssid": "aa\",-50],[\"bb", "rssi": -60}],
+        }));
+        let split = payload(serde_json::json!({
+            "leave_type": "NORMAL",
+            "wifi_scans": [{"bssid": "aa", "rssi": -50}, {"bssid": "bb", "rssi": -60}],
+        }));
+
+        assert_ne!(message(&base), message(&other_leave));
+        assert_ne!(message(&base), message(&other_rssi));
+        assert_ne!(message(&merged), message(&split));
+        assert_eq!(
+            message(&payload(serde_json::json!({}))).lines().nth(14),
+            Some("[]")
+        );
+    }
+
+    #[test]
+    fn message_covers_leave_notes_and_away_fields() {
+        let base = payload(serde_json::json!({
+            "leave_notes": "sakit",
+            "destination": "Polres",
+            "purpose": "rapat",
+        }));
+        let lines: Vec<String> = message(&base).split('\n').map(str::to_string).collect();
+        assert_eq!(&lines[15..], [r#""sakit""#, r#""Polres""#, r#""rapat""#]);
+        assert_eq!(
+            message(&payload(serde_json::json!({})))
+                .split('\n')
+                .collect::<Vec<_>>()[15..],
+            [r#""""#, r#""""#, r#""""#]
+        );
+
+        for changed in [
+            serde_json::json!({ "leave_notes": "izin", "destination": "Polres", "purpose": "rapat" }),
+            serde_json::json!({ "leave_notes": "sakit", "destination": "Polda", "purpose": "rapat" }),
+            serde_json::json!({ "leave_notes": "sakit", "destination": "Polres", "purpose": "apel" }),
+        ] {
+            assert_ne!(message(&base), message(&payload(changed)));
+        }
+
+        // baris baru di catatan tidak boleh bisa dibaca sebagai field berikutnya
+        let shifted = payload(serde_json::json!({
+            "leave_notes": "sakit\n\"Polres\"",
+            "purpose": "rapat",
+        }));
+        assert_ne!(message(&base), message(&shifted));
+        assert_eq!(message(&shifted).split('\n').count(), 18);
+    }
+
+    #[test]
+    fn missing_attestation_rejected_once_device_has_key() {
+        assert!(ensure_attestation_optional(false, false).is_ok());
+        assert!(ensure_attestation_optional(false, true).is_err());
+        assert!(ensure_attestation_optional(true, false).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! Batas verifikasi attestation request absensi. Implementasi dipilih lewat env
+//! `ATTESTATION_VERIFIER`; kosong = `device_key`, `off` = attestation tidak diperiksa.
+//! Verifier lain (mis. verdict Play Integrity) cukup mengimplementasikan `AttestationVerifier`.
+
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use p256::pkcs8::DecodePublicKey;
+use std::sync::Arc;
+
+use crate::error::HttpError;
+
+/// Data request yang diverifikasi.
+pub struct AttestationContext<'a> {
+    /// Kunci publik device terdaftar (base64 SPKI DER).
+    pub device_public_key: &'a str,
+    /// Pesan kanonik (payload + nonce challenge) yang ditandatangani aplikasi.
+    pub message: &'a [u8],
+    /// Bukti dari aplikasi: signature (device_key) atau token verdict (verifier lain).
+    pub proof: &'a str,
+}
+
+#[async_trait]
+pub trait AttestationVerifier: Send + Sync {
+    /// Nama verifier; dicatat di attendance_events.attestation.
+    fn name(&self) -> &'static str;
+
+    async fn verify(&self, ctx: &AttestationContext<'_>) -> Result<(), HttpError>;
+}
+
+pub fn build_attestation_verifier(
+    name: Option<&str>,
+) -> Result<Option<Arc<dyn AttestationVerifier>>, String> {
+    match name.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+        None | Some("") | Some(DeviceKeyVerifier::NAME) => Ok(Some(Arc::new(DeviceKeyVerifier))),
+        Some("off") => Ok(None),
+        Some(other) => Err(format!("ATTESTATION_VERIFIER tidak dikenal: {}", other)),
+    }
+}
+
+/// Kunci publik device dari aplikasi: base64 X.509 SubjectPublicKeyInfo DER, EC P-256
+/// (`PublicKey.getEncoded()` dari Android Keystore).
+pub fn parse_device_public_key(raw: &str) -> Result<VerifyingKey, HttpError> {
+    let der = STANDARD
+        .decode(raw.trim())
+        .map_err(|_| HttpError::bad_request("public_key bukan base64 yang valid"))?;
+    VerifyingKey::from_public_key_der(&der)
+        .map_err(|_| HttpError::bad_request("public_key harus kunci EC P-256 (SPKI DER)"))
+}
+
+/// Signature ECDSA P-256 / SHA-256 atas pesan kanonik dengan kunci device terdaftar.
+pub struct DeviceKeyVerifier;
+
+impl DeviceKeyVerifier {
+    const NAME: &'static str = "device_key";
+}
+
+#[async_trait]
+impl AttestationVerifier for DeviceKeyVerifier {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn verify(&self, ctx: &AttestationContext<'_>) -> Result<(), HttpError> {
+        let key = parse_device_public_key(ctx.device_public_key)
+            .map_err(|_| HttpError::server_error("kunci attestation device tidak valid"))?;
+
+        let raw = STANDARD
+            .decode(ctx.proof.trim())
+            .map_err(|_| HttpError::bad_request("attestation bukan base64 yang valid"))?;
+        // SHA256withECDSA Android menghasilkan DER; r||s 64 byte juga diterima
+        let signature = if raw.len() == 64 {
+            Signature::from_slice(&raw)
+        } else {
+            Signature::from_der(&raw)
+        }
+        .map_err(|_| HttpError::bad_request("format signature attestation tidak valid"))?;
+
+        key.verify(ctx.message, &signature)
+            .map_err(|_| HttpError::bad_request("signature attestation tidak cocok"))
+    }
+}
@@ -1,4 +1,7 @@
 use crate::auth::rbac::UserRole;
+use crate::database::satker_head::SatkerHeadRepo;
+use crate::db::DBClient;
+use crate::dtos::duty_schedule::can_manage_duty_schedules;
 use crate::error::HttpError;
 use crate::middleware::auth_middleware::UserClaims;
 use uuid::Uuid;
@@ -24,3 +27,27 @@ pub fn ensure_can_access_satker(
         Err(HttpError::unauthorized("forbidden".to_string()))
     }
 }
+
+/// Ensures caller may manage duty schedules of the satker.
+/// SatkerHead must also be the currently assigned head.
+pub async fn ensure_can_manage_duty_schedules(
+    db: &DBClient,
+    claims: &UserClaims,
+    satker_id: Uuid,
+) -> Result<(), HttpError> {
+    if !can_manage_duty_schedules(claims, satker_id) {
+        return Err(HttpError::unauthorized("forbidden"));
+    }
+
+    if claims.role == UserRole::SatkerHead {
+        let ok = db
+            .is_current_head_satker(satker_id, claims.user_id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        if !ok {
+            return Err(HttpError::unauthorized("forbidden"));
+        }
+    }
+
+    Ok(())
+}
@@ -1,7 +1,16 @@
-use crate::constants::HolidayScope;
+use crate::constants::{CalendarDayType, HolidayKind, HolidayScope};
+use crate::database::holiday::HolidayRepo;
+use crate::database::work_calendar::WorkCalendarRepo;
+use crate::database::work_pattern::{WorkPatternRepo, pick_effective_pattern};
+use crate::db::DBClient;
+use crate::error::HttpError;
 use crate::models::{Holiday, SatkerWorkPattern};
-use chrono::{NaiveDate, Weekday};
-use std::collections::HashMap;
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Batas range sekali generate kalender.
+pub const MAX_CALENDAR_GENERATE_DAYS: i64 = 370;
 
 /// Keeps the most specific holiday (SATKER overrides NATIONAL) for each date.
 pub fn build_holiday_override_map(holidays: Vec<Holiday>) -> HashMap<NaiveDate, Holiday> {
@@ -32,3 +41,272 @@ pub fn weekday_is_work(pattern: &SatkerWorkPattern, weekday: Weekday) -> bool {
         Weekday::Sun => pattern.sun_work,
     }
 }
+
+/// Satu hari kalender hasil generate dari work pattern + libur.
+#[derive(Debug, Clone, PartialEq)]
+struct PlannedCalendarDay {
+    work_date: NaiveDate,
+    day_type: CalendarDayType,
+    expected_start: Option<NaiveTime>,
+    expected_end: Option<NaiveTime>,
+    note: Option<String>,
+}
+
+/// Hitung hari kalender [from, to] dari work pattern + override libur.
+/// Tanggal di `manual_dates` (override manual) dilewati supaya tidak ditimpa.
+fn plan_calendar_days(
+    patterns: &[SatkerWorkPattern],
+    holiday_by_date: &HashMap<NaiveDate, Holiday>,
+    manual_dates: &HashSet<NaiveDate>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<PlannedCalendarDay>, HttpError> {
+    let mut days = Vec::new();
+    let mut cur = from;
+    while cur <= to {
+        if manual_dates.contains(&cur) {
+            cur = cur.succ_opt().unwrap();
+            continue;
+        }
+
+        let weekday = cur.weekday();
+        let pattern = pick_effective_pattern(patterns, cur).ok_or_else(|| {
+            HttpError::bad_request(format!(
+                "work pattern tidak ditemukan untuk tanggal {}",
+                cur
+            ))
+        })?;
+
+        let mut day_type = if weekday_is_work(&pattern, weekday) {
+            CalendarDayType::Workday
+        } else {
+            CalendarDayType::Holiday
+        };
+
+        let mut expected_start: Option<NaiveTime> = None;
+        let mut expected_end: Option<NaiveTime> = None;
+        let mut note: Option<String> = None;
+
+        if day_type == CalendarDayType::Workday {
+            expected_start = Some(pattern.work_start);
+            expected_end = Some(pattern.work_end);
+            // Half-day rule: if sat_work and pattern has half_day_end and today is Saturday
+            if weekday == Weekday::Sat
+                && let Some(half_end) = pattern.half_day_end
+            {
+                day_type = CalendarDayType::HalfDay;
+                expected_end = Some(half_end);
+            }
+        } else {
+            note = Some("Hari libur".to_string());
+        }
+
+        // Override with holiday table
+        if let Some(h) = holiday_by_date.get(&cur) {
+            match h.kind {
+                HolidayKind::Holiday => {
+                    day_type = CalendarDayType::Holiday;
+                    expected_start = None;
+                    expected_end = None;
+                    note = Some(h.name.clone());
+                }
+                HolidayKind::HalfDay => {
+                    day_type = CalendarDayType::HalfDay;
+                    expected_start = Some(pattern.work_start);
+                    let end = h
+                        .half_day_end
+                        .or(pattern.half_day_end)
+                        .unwrap_or(pattern.work_end);
+                    expected_end = Some(end);
+                    note = Some(h.name.clone());
+                }
+            }
+        }
+
+        days.push(PlannedCalendarDay {
+            work_date: cur,
+            day_type,
+            expected_start,
+            expected_end,
+            note,
+        });
+        cur = cur.succ_opt().unwrap();
+    }
+
+    Ok(days)
+}
+
+/// Generate ulang `satker_calendar_days` untuk range [from, to] dari work pattern
+/// + override tabel holidays. Return jumlah hari yang di-upsert.
+///
+/// Hari yang di-override manual tidak ditimpa dan tidak dihitung.
+pub async fn generate_calendar_days(
+    db: &DBClient,
+    satker_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<i64, HttpError> {
+    // Guard: do not generate absurdly large ranges
+    let days = (to - from).num_days() + 1;
+    if days > MAX_CALENDAR_GENERATE_DAYS {
+        return Err(HttpError::bad_request(format!(
+            "range terlalu besar (max {} hari)",
+            MAX_CALENDAR_GENERATE_DAYS
+        )));
+    }
+
+    let patterns = db
+        .list_work_patterns(satker_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if patterns.is_empty() {
+        return Err(HttpError::bad_request(
+            "satker_work_patterns belum diset untuk satker ini",
+        ));
+    }
+
+    let holidays = db
+        .list_holidays(satker_id, from, to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let holiday_by_date = build_holiday_override_map(holidays);
+
+    let manual_dates: HashSet<NaiveDate> = db
+        .list_calendar_days(satker_id, from, to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .into_iter()
+        .filter(|d| d.is_manual)
+        .map(|d| d.work_date)
+        .collect();
+
+    let planned = plan_calendar_days(&patterns, &holiday_by_date, &manual_dates, from, to)?;
+
+    // upsert tetap melewati is_manual, untuk override yang dibuat saat generate berjalan
+    for day in &planned {
+        db.upsert_calendar_day(
+            satker_id,
+            day.work_date,
+            day.day_type,
+            day.expected_start,
+            day.expected_end,
+            day.note.clone(),
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    }
+
+    Ok(planned.len() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn pattern() -> SatkerWorkPattern {
+        SatkerWorkPattern {
+            id: Uuid::nil(),
+            satker_id: Uuid::nil(),
+            effective_from: date(2026, 1, 1),
+            mon_work: true,
+            tue_work: true,
+            wed_work: true,
+            thu_work: true,
+            fri_work: true,
+            sat_work: true,
+            sun_work: false,
+            work_start: time(7, 30),
+            work_end: time(15, 30),
+            half_day_end: Some(time(12, 0)),
+            max_segments: 1,
+            max_break_minutes: None,
+            created_at: None,
+        }
+    }
+
+    fn holiday(on: NaiveDate, name: &str) -> Holiday {
+        Holiday {
+            id: Uuid::new_v4(),
+            scope: HolidayScope::National,
+            satker_id: None,
+            holiday_date: on,
+            kind: HolidayKind::Holiday,
+            name: name.to_string(),
+            half_day_end: None,
+        }
+    }
+
+    #[test]
+    fn manual_days_are_not_regenerated() {
+        // Senin 2 Feb - Minggu 8 Feb 2026; Rabu 4 Feb di-override manual
+        let manual = HashSet::from([date(2026, 2, 4)]);
+        let holidays = build_holiday_override_map(vec![holiday(date(2026, 2, 4), "Libur")]);
+        let days = plan_calendar_days(
+            &[pattern()],
+            &holidays,
+            &manual,
+            date(2026, 2, 2),
+            date(2026, 2, 8),
+        )
+        .unwrap();
+
+        let dates: Vec<NaiveDate> = days.iter().map(|d| d.work_date).collect();
+        assert_eq!(dates.len(), 6);
+        assert!(!dates.contains(&date(2026, 2, 4)));
+    }
+
+    #[test]
+    fn pattern_and_holidays_decide_day_type() {
+        let holidays = build_holiday_override_map(vec![holiday(date(2026, 2, 3), "Imlek")]);
+        let days = plan_calendar_days(
+            &[pattern()],
+            &holidays,
+            &HashSet::new(),
+            date(2026, 2, 2),
+            date(2026, 2, 8),
+        )
+        .unwrap();
+        let by_date: HashMap<NaiveDate, &PlannedCalendarDay> =
+            days.iter().map(|d| (d.work_date, d)).collect();
+
+        let monday = by_date[&date(2026, 2, 2)];
+        assert_eq!(monday.day_type, CalendarDayType::Workday);
+        assert_eq!(monday.expected_end, Some(time(15, 30)));
+
+        let holiday = by_date[&date(2026, 2, 3)];
+        assert_eq!(holiday.day_type, CalendarDayType::Holiday);
+        assert_eq!(holiday.note.as_deref(), Some("Imlek"));
+        assert_eq!(holiday.expected_start, None);
+
+        let saturday = by_date[&date(2026, 2, 7)];
+        assert_eq!(saturday.day_type, CalendarDayType::HalfDay);
+        assert_eq!(saturday.expected_end, Some(time(12, 0)));
+
+        assert_eq!(
+            by_date[&date(2026, 2, 8)].day_type,
+            CalendarDayType::Holiday
+        );
+    }
+
+    #[test]
+    fn date_before_first_pattern_is_an_error() {
+        let result = plan_calendar_days(
+            &[pattern()],
+            &HashMap::new(),
+            &HashSet::new(),
+            date(2025, 12, 31),
+            date(2026, 1, 1),
+        );
+        assert!(result.is_err());
+    }
+}
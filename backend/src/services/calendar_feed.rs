@@ -0,0 +1,298 @@
+use axum::http::StatusCode;
+use chrono::{Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::auth::rbac::UserRole;
+use crate::constants::{CalendarDayType, LeaveType, ScheduleType};
+use crate::database::calendar_feed::CalendarFeedRepo;
+use crate::database::duty_schedule::DutyScheduleRepo;
+use crate::db::DBClient;
+use crate::dtos::calendar_feed::{
+    CalendarFeedTokenRow, CreateCalendarFeedReq, FEED_SCOPE_SATKER, FEED_SCOPE_USER,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::UserClaims;
+use crate::services::authorization::ensure_can_manage_duty_schedules;
+use crate::services::duty_rotation::local_midnight_utc;
+use crate::utils::ics::{IcsEvent, IcsTime, render_calendar};
+
+/// Rentang data di feed, relatif terhadap hari ini (zona waktu aplikasi).
+const FEED_PAST_DAYS: u64 = 90;
+const FEED_FUTURE_DAYS: u64 = 365;
+
+/// Domain UID; jangan diubah, klien kalender memakai UID untuk update acara.
+const UID_DOMAIN: &str = "absensi";
+
+fn duty_uid(schedule_id: Uuid) -> String {
+    format!("duty-{}@{}", schedule_id, UID_DOMAIN)
+}
+
+fn leave_uid(leave_id: Uuid) -> String {
+    format!("leave-{}@{}", leave_id, UID_DOMAIN)
+}
+
+/// Libur tidak punya id sendiri per hari; UID diturunkan dari satker + tanggal.
+fn holiday_uid(satker_id: Uuid, work_date: NaiveDate) -> String {
+    format!(
+        "holiday-{}-{}@{}",
+        satker_id,
+        work_date.format("%Y%m%d"),
+        UID_DOMAIN
+    )
+}
+
+/// Token acak 64 hex (2x UUID v4) beserta hash SHA-256 yang disimpan di DB.
+pub fn generate_feed_token() -> (String, String) {
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let hash = hash_feed_token(&token);
+    (token, hash)
+}
+
+pub fn hash_feed_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// URL feed (relatif kalau PUBLIC_BASE_URL kosong).
+pub fn calendar_feed_url(base_url: Option<&str>, scope: &str, token: &str) -> String {
+    let kind = if scope == FEED_SCOPE_SATKER {
+        "satkers"
+    } else {
+        "users"
+    };
+    format!("{}/api/ics/{}/{}.ics", base_url.unwrap_or(""), kind, token)
+}
+
+/// Validasi scope dan hak akses; hasil: (scope, satker_id yang disimpan).
+pub async fn resolve_new_feed_scope(
+    db: &DBClient,
+    claims: &UserClaims,
+    req: &CreateCalendarFeedReq,
+) -> Result<(&'static str, Option<Uuid>), HttpError> {
+    match req.scope.trim().to_uppercase().as_str() {
+        FEED_SCOPE_USER => Ok((FEED_SCOPE_USER, None)),
+        FEED_SCOPE_SATKER => {
+            let satker_id = if claims.role == UserRole::Superadmin {
+                req.satker_id
+                    .ok_or_else(|| HttpError::bad_request("satker_id wajib diisi"))?
+            } else {
+                claims.satker_id
+            };
+            ensure_can_manage_duty_schedules(db, claims, satker_id).await?;
+            Ok((FEED_SCOPE_SATKER, Some(satker_id)))
+        }
+        _ => Err(HttpError::bad_request("scope harus USER atau SATKER")),
+    }
+}
+
+fn feed_not_found() -> HttpError {
+    HttpError::new("feed kalender tidak ditemukan", StatusCode::NOT_FOUND)
+}
+
+/// Cari token aktif untuk endpoint ICS publik. Hak akses dicek ulang terhadap
+/// role/satker pemilik saat ini, jadi token ikut mati kalau pemilik dinonaktifkan
+/// atau tidak lagi mengelola satker tsb.
+pub async fn authorize_feed_token(
+    db: &DBClient,
+    token: &str,
+    scope: &str,
+) -> Result<CalendarFeedTokenRow, HttpError> {
+    let token = token.strip_suffix(".ics").unwrap_or(token);
+    if token.is_empty() || token.len() > 128 {
+        return Err(feed_not_found());
+    }
+
+    let feed = db
+        .find_active_calendar_feed_by_hash(&hash_feed_token(token))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(feed_not_found)?;
+
+    if feed.scope != scope || !feed.owner_is_active {
+        return Err(feed_not_found());
+    }
+
+    if let Some(satker_id) = feed.satker_id {
+        let claims = UserClaims {
+            user_id: feed.user_id,
+            satker_id: feed.owner_satker_id,
+            role: feed.owner_role,
+        };
+        ensure_can_manage_duty_schedules(db, &claims, satker_id).await?;
+    }
+
+    Ok(feed)
+}
+
+fn schedule_type_label(t: ScheduleType) -> &'static str {
+    match t {
+        ScheduleType::Regular => "Dinas",
+        ScheduleType::Shift => "Piket",
+        ScheduleType::OnCall => "Siaga",
+        ScheduleType::Special => "Tugas Khusus",
+    }
+}
+
+fn leave_type_label(t: LeaveType) -> &'static str {
+    match t {
+        LeaveType::Ijin => "Ijin",
+        LeaveType::Sakit => "Sakit",
+        LeaveType::Cuti => "Cuti",
+        LeaveType::DinasLuar => "Dinas Luar",
+    }
+}
+
+/// Render isi feed: jadwal dinas, ijin/cuti disetujui dan hari libur satker.
+pub async fn build_calendar_feed(
+    db: &DBClient,
+    tz: Tz,
+    feed: &CalendarFeedTokenRow,
+) -> Result<String, HttpError> {
+    let (satker_id, user_id) = match feed.satker_id {
+        Some(satker_id) => (satker_id, None),
+        None => (feed.owner_satker_id, Some(feed.user_id)),
+    };
+    // feed satker menampilkan nama anggota di judul acara
+    let with_names = user_id.is_none();
+    // feed user: jadwal & ijin milik user di satker mana pun, libur dari satker saat ini
+    let scope_satker_id = feed.satker_id;
+
+    let today = Utc::now().with_timezone(&tz).date_naive();
+    let from = today
+        .checked_sub_days(Days::new(FEED_PAST_DAYS))
+        .ok_or_else(|| HttpError::server_error("tanggal tidak valid"))?;
+    let to = today
+        .checked_add_days(Days::new(FEED_FUTURE_DAYS))
+        .ok_or_else(|| HttpError::server_error("tanggal tidak valid"))?;
+
+    let schedules = db
+        .list_duty_schedules(
+            scope_satker_id,
+            user_id,
+            local_midnight_utc(tz, from)?,
+            local_midnight_utc(tz, to)?,
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let leaves = db
+        .list_feed_leaves(scope_satker_id, user_id, from, to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let days = db
+        .list_feed_calendar_days(satker_id, from, to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let mut events = Vec::with_capacity(schedules.len() + leaves.len() + days.len());
+
+    for s in schedules {
+        let label = schedule_type_label(s.schedule_type);
+        let title = s
+            .title
+            .clone()
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or_else(|| label.to_string());
+        let summary = if with_names {
+            format!("{} - {}", s.user_full_name, title)
+        } else {
+            title
+        };
+
+        events.push(IcsEvent {
+            uid: duty_uid(s.id),
+            start: IcsTime::At(s.start_at),
+            end: IcsTime::At(s.end_at),
+            summary,
+            description: s.note.filter(|n| !n.trim().is_empty()),
+            categories: Some(label.to_string()),
+            last_modified: s.updated_at,
+        });
+    }
+
+    for l in leaves {
+        let label = leave_type_label(l.tipe);
+        let summary = if with_names {
+            format!("{} - {}", l.user_full_name, label)
+        } else {
+            label.to_string()
+        };
+        let end = l
+            .end_date
+            .succ_opt()
+            .ok_or_else(|| HttpError::server_error("tanggal tidak valid"))?;
+
+        events.push(IcsEvent {
+            uid: leave_uid(l.id),
+            start: IcsTime::Date(l.start_date),
+            end: IcsTime::Date(end),
+            summary,
+            description: l.reason.filter(|r| !r.trim().is_empty()),
+            categories: Some(label.to_string()),
+            last_modified: l.updated_at,
+        });
+    }
+
+    for d in days {
+        let name = d.note.unwrap_or_else(|| "Hari libur".to_string());
+        let (summary, description) = match (d.day_type, d.expected_end) {
+            (CalendarDayType::HalfDay, Some(end)) => (
+                format!("{} (setengah hari)", name),
+                Some(format!("Jam kerja sampai {}", end.format("%H:%M"))),
+            ),
+            (CalendarDayType::HalfDay, None) => (format!("{} (setengah hari)", name), None),
+            _ => (name, None),
+        };
+        let end = d
+            .work_date
+            .succ_opt()
+            .ok_or_else(|| HttpError::server_error("tanggal tidak valid"))?;
+
+        events.push(IcsEvent {
+            uid: holiday_uid(satker_id, d.work_date),
+            start: IcsTime::Date(d.work_date),
+            end: IcsTime::Date(end),
+            summary,
+            description,
+            categories: Some("Hari Libur".to_string()),
+            last_modified: d.updated_at,
+        });
+    }
+
+    let name = match &feed.label {
+        Some(label) if !label.trim().is_empty() => label.as_str(),
+        _ if with_names => "Jadwal Dinas Satker",
+        _ => "Jadwal Dinas Saya",
+    };
+
+    Ok(render_calendar(name, tz.name(), &events))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uids_are_stable_across_renders() {
+        let id = Uuid::parse_str("5f0c2d7e-8d51-4c5e-9a43-8a0f7b1f2c3d").unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 8, 17).unwrap();
+
+        // format UID adalah kontrak dengan klien kalender; perubahan = acara dobel
+        assert_eq!(
+            duty_uid(id),
+            "duty-5f0c2d7e-8d51-4c5e-9a43-8a0f7b1f2c3d@absensi"
+        );
+        assert_eq!(
+            leave_uid(id),
+            "leave-5f0c2d7e-8d51-4c5e-9a43-8a0f7b1f2c3d@absensi"
+        );
+        assert_eq!(
+            holiday_uid(id, date),
+            "holiday-5f0c2d7e-8d51-4c5e-9a43-8a0f7b1f2c3d-20260817@absensi"
+        );
+        assert_eq!(duty_uid(id), duty_uid(id));
+        assert_ne!(duty_uid(id), leave_uid(id));
+    }
+}
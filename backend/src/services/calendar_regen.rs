@@ -0,0 +1,177 @@
+//! Generate ulang `satker_calendar_days` otomatis di background.
+//!
+//! Perubahan work pattern / libur cukup memanggil `enqueue_*`; job disimpan di tabel
+//! `calendar_regen_jobs` (tahan restart) dan diproses worker yang dijalankan saat serve.
+//! Range job dipotong ke kalender yang sudah ter-generate, jadi tahun yang belum
+//! pernah di-generate tidak ikut dibuat. Hari `is_manual` tidak ditimpa.
+
+use chrono::{Days, NaiveDate, Utc};
+use std::time::Duration;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+use crate::constants::HolidayScope;
+use crate::database::calendar_regen::CalendarRegenRepo;
+use crate::database::work_calendar::WorkCalendarRepo;
+use crate::database::work_pattern::WorkPatternRepo;
+use crate::db::DBClient;
+use crate::dtos::work_calendar::ClaimedCalendarRegenJob;
+use crate::error::HttpError;
+use crate::services::calendar::{MAX_CALENDAR_GENERATE_DAYS, generate_calendar_days};
+
+/// Worker tetap polling berkala untuk job dari proses lain (mis. CLI admin).
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Job RUNNING lebih lama dari ini dianggap yatim (proses pengerjanya mati).
+/// Jauh di atas durasi normal satu job, supaya job milik replica lain tidak diambil ulang.
+const STALE_RUNNING_AFTER_MINUTES: i64 = 30;
+
+static REGEN_NOTIFY: Notify = Notify::const_new();
+
+/// Masukkan job ke antrian. Best-effort: perubahan data sumber sudah tersimpan,
+/// jadi gagal enqueue hanya dicatat di log.
+pub async fn enqueue_calendar_regen(
+    db: &DBClient,
+    satker_id: Uuid,
+    from: NaiveDate,
+    to: Option<NaiveDate>,
+    reason: &str,
+) {
+    match db.enqueue_calendar_regen(satker_id, from, to, reason).await {
+        Ok(_) => REGEN_NOTIFY.notify_one(),
+        Err(e) => tracing::warn!(%satker_id, %from, "gagal enqueue generate ulang kalender: {}", e),
+    }
+}
+
+/// Work pattern `effective_from` berubah/dihapus: terdampak sampai pattern berikutnya.
+/// Dipanggil setelah perubahan tersimpan.
+pub async fn enqueue_work_pattern_regen(
+    db: &DBClient,
+    satker_id: Uuid,
+    effective_from: NaiveDate,
+    reason: &str,
+) {
+    let next = match db.list_work_patterns(satker_id).await {
+        Ok(patterns) => patterns
+            .iter()
+            .map(|p| p.effective_from)
+            .filter(|d| *d > effective_from)
+            .min(),
+        Err(e) => {
+            tracing::warn!(%satker_id, "gagal membaca work pattern: {}", e);
+            None
+        }
+    };
+    let to = next.and_then(|d| d.pred_opt());
+
+    enqueue_calendar_regen(db, satker_id, effective_from, to, reason).await;
+}
+
+/// Libur berubah di [from, to]. NATIONAL: semua satker yang kalendernya mencakup range tsb.
+pub async fn enqueue_holiday_regen(
+    db: &DBClient,
+    scope: HolidayScope,
+    satker_id: Option<Uuid>,
+    from: NaiveDate,
+    to: NaiveDate,
+    reason: &str,
+) {
+    let satker_ids = match (scope, satker_id) {
+        (HolidayScope::Satker, Some(satker_id)) => vec![satker_id],
+        _ => match db.list_satkers_with_calendar(from, to).await {
+            Ok(ids) => ids,
+            Err(e) => {
+                tracing::warn!("gagal membaca satker untuk generate ulang kalender: {}", e);
+                return;
+            }
+        },
+    };
+
+    for satker_id in satker_ids {
+        enqueue_calendar_regen(db, satker_id, from, Some(to), reason).await;
+    }
+}
+
+/// Jalankan worker antrian di background (dipanggil sekali saat serve).
+pub fn spawn_calendar_regen_worker(db: DBClient) {
+    tokio::spawn(async move {
+        loop {
+            requeue_stale_jobs(&db).await;
+
+            loop {
+                match db.claim_calendar_regen_job().await {
+                    Ok(Some(job)) => run_calendar_regen_job(&db, job).await,
+                    Ok(None) => break,
+                    Err(e) => {
+                        tracing::warn!("gagal mengambil job kalender: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            tokio::select! {
+                _ = REGEN_NOTIFY.notified() => {}
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+        }
+    });
+}
+
+/// Dicek tiap putaran (bukan hanya saat start) agar job yatim dari replica yang mati
+/// tetap diambil ulang oleh replica lain.
+async fn requeue_stale_jobs(db: &DBClient) {
+    let started_before = Utc::now() - chrono::Duration::minutes(STALE_RUNNING_AFTER_MINUTES);
+    match db.requeue_running_calendar_regen_jobs(started_before).await {
+        Ok(n) if n > 0 => tracing::info!("{} job kalender RUNNING dikembalikan ke antrian", n),
+        Ok(_) => {}
+        Err(e) => tracing::warn!("gagal requeue job kalender: {}", e),
+    }
+}
+
+async fn run_calendar_regen_job(db: &DBClient, job: ClaimedCalendarRegenJob) {
+    let (generated, error) = match regenerate_range(db, &job).await {
+        Ok(n) => (n, None),
+        Err(e) => (0, Some(e.message)),
+    };
+
+    if let Some(err) = &error {
+        tracing::warn!(satker_id = %job.satker_id, "generate ulang kalender gagal: {}", err);
+    }
+
+    if let Err(e) = db
+        .finish_calendar_regen_job(job.id, generated, error.as_deref())
+        .await
+    {
+        tracing::warn!(job_id = %job.id, "gagal menyimpan status job kalender: {}", e);
+    }
+}
+
+/// Generate ulang irisan range job dengan kalender yang sudah ada, per potongan
+/// MAX_CALENDAR_GENERATE_DAYS.
+async fn regenerate_range(db: &DBClient, job: &ClaimedCalendarRegenJob) -> Result<i64, HttpError> {
+    let Some((first, last)) = db
+        .calendar_bounds(job.satker_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+    else {
+        return Ok(0);
+    };
+
+    let from = job.from_date.max(first);
+    let to = job.to_date.map_or(last, |to| to.min(last));
+
+    let mut generated = 0;
+    let mut chunk_from = from;
+    while chunk_from <= to {
+        let chunk_to = chunk_from
+            .checked_add_days(Days::new(MAX_CALENDAR_GENERATE_DAYS as u64 - 1))
+            .map_or(to, |d| d.min(to));
+        generated += generate_calendar_days(db, job.satker_id, chunk_from, chunk_to).await?;
+        let Some(next) = chunk_to.succ_opt() else {
+            break;
+        };
+        chunk_from = next;
+    }
+
+    Ok(generated)
+}
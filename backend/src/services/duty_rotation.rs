@@ -0,0 +1,574 @@
+//! Template rotasi piket: validasi definisi dan generate `duty_schedules`.
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::constants::ScheduleType;
+use crate::database::duty_rotation::DutyRotationRepo;
+use crate::db::DBClient;
+use crate::dtos::duty_rotation::{
+    DutyRotationTemplateDto, GenerateDutyRotationData, GenerateDutyRotationReq,
+    OCCURRENCE_EXISTING, OCCURRENCE_LEAVE, OCCURRENCE_NEW, OCCURRENCE_OVERLAP, RotationDefinition,
+    RotationOccurrenceDto, RotationScheduleInsert, RotationSlotInput, RotationTeamInput,
+    UpsertDutyRotationReq,
+};
+use crate::error::HttpError;
+use crate::utils::time_parser::parse_time_field;
+
+/// Batas range sekali generate jadwal dari template.
+pub const MAX_ROTATION_GENERATE_DAYS: i64 = 92;
+pub const MAX_ROTATION_CYCLE_DAYS: i32 = 62;
+/// Durasi slot maksimal (menit), sama dengan constraint di DB.
+pub const MAX_ROTATION_SLOT_MINUTES: i32 = 2880;
+
+/// Validasi request dan ubah ke definisi yang siap disimpan.
+pub fn build_rotation_definition(
+    req: UpsertDutyRotationReq,
+) -> Result<RotationDefinition, HttpError> {
+    let name = req.name.trim().to_string();
+    if name.is_empty() {
+        return Err(HttpError::bad_request("nama template wajib diisi"));
+    }
+
+    if !(1..=MAX_ROTATION_CYCLE_DAYS).contains(&req.cycle_days) {
+        return Err(HttpError::bad_request(format!(
+            "cycle_days harus 1..{}",
+            MAX_ROTATION_CYCLE_DAYS
+        )));
+    }
+
+    if req.teams.is_empty() {
+        return Err(HttpError::bad_request("minimal 1 regu"));
+    }
+
+    let mut team_index: HashMap<String, usize> = HashMap::new();
+    let mut teams = Vec::with_capacity(req.teams.len());
+    for team in req.teams {
+        let team_name = team.name.trim().to_string();
+        if team_name.is_empty() {
+            return Err(HttpError::bad_request("nama regu wajib diisi"));
+        }
+        if team_index
+            .insert(team_name.to_lowercase(), teams.len())
+            .is_some()
+        {
+            return Err(HttpError::bad_request(format!(
+                "nama regu duplikat: {}",
+                team_name
+            )));
+        }
+
+        let mut seen = HashSet::new();
+        let member_ids = team
+            .member_ids
+            .into_iter()
+            .filter(|id| seen.insert(*id))
+            .collect();
+
+        teams.push(RotationTeamInput {
+            name: team_name,
+            member_ids,
+        });
+    }
+
+    if req.slots.is_empty() {
+        return Err(HttpError::bad_request("minimal 1 slot jadwal"));
+    }
+
+    let mut slots = Vec::with_capacity(req.slots.len());
+    for (i, slot) in req.slots.into_iter().enumerate() {
+        let idx = team_index
+            .get(&slot.team.trim().to_lowercase())
+            .copied()
+            .ok_or_else(|| {
+                HttpError::bad_request(format!(
+                    "slots[{}]: regu '{}' tidak ada di daftar regu",
+                    i, slot.team
+                ))
+            })?;
+
+        if slot.day_index < 0 || slot.day_index >= req.cycle_days {
+            return Err(HttpError::bad_request(format!(
+                "slots[{}]: day_index harus 0..{}",
+                i,
+                req.cycle_days - 1
+            )));
+        }
+
+        if !(1..=MAX_ROTATION_SLOT_MINUTES).contains(&slot.duration_minutes) {
+            return Err(HttpError::bad_request(format!(
+                "slots[{}]: duration_minutes harus 1..{}",
+                i, MAX_ROTATION_SLOT_MINUTES
+            )));
+        }
+
+        let start_time = parse_time_field(&slot.start_time, &format!("slots[{}].start_time", i))?;
+
+        slots.push(RotationSlotInput {
+            team_index: idx,
+            day_index: slot.day_index,
+            start_time,
+            duration_minutes: slot.duration_minutes,
+            schedule_type: slot.schedule_type.unwrap_or(ScheduleType::Shift),
+            title: slot
+                .title
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty()),
+        });
+    }
+
+    Ok(RotationDefinition {
+        name,
+        cycle_days: req.cycle_days,
+        anchor_date: req.anchor_date,
+        is_active: req.is_active.unwrap_or(true),
+        teams,
+        slots,
+    })
+}
+
+/// Semua anggota regu harus user aktif di satker template.
+pub async fn ensure_members_in_satker(
+    db: &DBClient,
+    satker_id: Uuid,
+    def: &RotationDefinition,
+) -> Result<(), HttpError> {
+    let user_ids: Vec<Uuid> = def
+        .teams
+        .iter()
+        .flat_map(|t| t.member_ids.iter().copied())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+
+    let outside = db
+        .find_users_outside_satker(satker_id, &user_ids)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if !outside.is_empty() {
+        let ids: Vec<String> = outside.iter().map(|id| id.to_string()).collect();
+        return Err(HttpError::bad_request(format!(
+            "anggota regu bukan user aktif satker ini: {}",
+            ids.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+/// Pesan error yang lebih jelas untuk pelanggaran unique index nama template/regu.
+pub fn map_rotation_save_error(e: sqlx::Error) -> HttpError {
+    if e.as_database_error()
+        .is_some_and(|d| d.is_unique_violation())
+    {
+        return HttpError::bad_request("nama template sudah dipakai di satker ini");
+    }
+    HttpError::server_error(e.to_string())
+}
+
+type TimeSpan = (DateTime<Utc>, DateTime<Utc>);
+
+struct PlannedOccurrence {
+    local_date: NaiveDate,
+    user_id: Uuid,
+    user_full_name: String,
+    user_nrp: String,
+    team_name: String,
+    start_at: DateTime<Utc>,
+    end_at: DateTime<Utc>,
+    schedule_type: ScheduleType,
+    title: Option<String>,
+}
+
+/// Jabarkan slot x anggota regu untuk setiap tanggal lokal di [from, to], urut start_at lalu user.
+/// Jam slot dibaca di zona `tz`; jam lokal yang tidak ada (loncatan DST) dilewati.
+fn plan_rotation_occurrences(
+    tz: Tz,
+    template: &DutyRotationTemplateDto,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<PlannedOccurrence> {
+    let teams: HashMap<Uuid, usize> = template
+        .teams
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t.id, i))
+        .collect();
+    let mut planned: Vec<PlannedOccurrence> = Vec::new();
+    let mut date = from;
+    while date <= to {
+        let cycle_idx = (date - template.anchor_date)
+            .num_days()
+            .rem_euclid(template.cycle_days as i64) as i32;
+
+        for slot in template.slots.iter().filter(|s| s.day_index == cycle_idx) {
+            let Some(team) = teams.get(&slot.team_id).map(|&i| &template.teams[i]) else {
+                continue;
+            };
+            // jam lokal yang tidak ada (loncatan DST) dilewati
+            let Some(start_at) = tz
+                .from_local_datetime(&date.and_time(slot.start_time))
+                .earliest()
+                .map(|dt| dt.with_timezone(&Utc))
+            else {
+                continue;
+            };
+            let end_at = start_at + Duration::minutes(slot.duration_minutes as i64);
+
+            for member in &team.members {
+                planned.push(PlannedOccurrence {
+                    local_date: date,
+                    user_id: member.user_id,
+                    user_full_name: member.full_name.clone(),
+                    user_nrp: member.nrp.clone(),
+                    team_name: team.name.clone(),
+                    start_at,
+                    end_at,
+                    schedule_type: slot.schedule_type,
+                    title: slot.title.clone(),
+                });
+            }
+        }
+
+        date += Duration::days(1);
+    }
+    planned.sort_by(|a, b| a.start_at.cmp(&b.start_at).then(a.user_id.cmp(&b.user_id)));
+    planned
+}
+
+/// Hitung jadwal dari template untuk [from, to] (tanggal lokal), lalu simpan kalau bukan dry-run.
+///
+/// - Jadwal yang sudah pernah dibuat template ini (user + start_at sama) tidak dibuat ulang.
+/// - Bentrok dengan jadwal lain / cuti yang disetujui dilaporkan dan tidak dibuat.
+/// - `replace_existing`: jadwal template ini di range yang sudah tidak sesuai template
+///   (dan belum mulai) dihapus.
+pub async fn generate_duty_rotation(
+    db: &DBClient,
+    tz: Tz,
+    template: &DutyRotationTemplateDto,
+    req: &GenerateDutyRotationReq,
+    created_by: Uuid,
+) -> Result<GenerateDutyRotationData, HttpError> {
+    if req.to < req.from {
+        return Err(HttpError::bad_request(
+            "Tanggal end tidak boleh lebih awal dari tanggal start",
+        ));
+    }
+    let days = (req.to - req.from).num_days() + 1;
+    if days > MAX_ROTATION_GENERATE_DAYS {
+        return Err(HttpError::bad_request(format!(
+            "range maksimal {} hari",
+            MAX_ROTATION_GENERATE_DAYS
+        )));
+    }
+    if !req.dry_run && !template.is_active {
+        return Err(HttpError::bad_request("template rotasi tidak aktif"));
+    }
+
+    let range_start = local_midnight_utc(tz, req.from)?;
+    let range_end = local_midnight_utc(tz, req.to + Duration::days(1))?;
+
+    // 1) Jabarkan slot x anggota regu untuk setiap tanggal
+    let planned = plan_rotation_occurrences(tz, template, req.from, req.to);
+
+    // 2) Ambil jadwal & cuti yang relevan
+    let user_ids: Vec<Uuid> = planned
+        .iter()
+        .map(|p| p.user_id)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let window_end = planned
+        .iter()
+        .map(|p| p.end_at)
+        .max()
+        .map_or(range_end, |end| end.max(range_end));
+
+    let spans = db
+        .list_schedule_spans(&user_ids, template.id, range_start, window_end)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let leaves = db
+        .list_approved_leaves_for_users(&user_ids, req.from, req.to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    // 3) Jadwal template ini yang sudah tidak sesuai definisi sekarang
+    let planned_keys: HashMap<(Uuid, DateTime<Utc>), DateTime<Utc>> = planned
+        .iter()
+        .map(|p| ((p.user_id, p.start_at), p.end_at))
+        .collect();
+    let now = Utc::now();
+    let stale_ids: HashSet<Uuid> = if req.replace_existing {
+        spans
+            .iter()
+            .filter(|s| {
+                s.rotation_template_id == Some(template.id)
+                    && s.start_at >= range_start
+                    && s.start_at < range_end
+                    && s.start_at > now
+                    && planned_keys.get(&s.rotation_key()) != Some(&s.end_at)
+            })
+            .map(|s| s.id)
+            .collect()
+    } else {
+        HashSet::new()
+    };
+
+    let existing_by_key: HashMap<(Uuid, DateTime<Utc>), Uuid> = spans
+        .iter()
+        .filter(|s| s.rotation_template_id == Some(template.id) && !stale_ids.contains(&s.id))
+        .map(|s| (s.rotation_key(), s.id))
+        .collect();
+
+    // 4) Klasifikasi setiap occurrence
+    let mut accepted: HashMap<Uuid, Vec<TimeSpan>> = HashMap::new();
+    let mut occurrences = Vec::with_capacity(planned.len());
+    let mut inserts = Vec::new();
+    let (mut existing, mut overlaps, mut leave_conflicts) = (0_i64, 0_i64, 0_i64);
+
+    for p in planned {
+        let mut status = OCCURRENCE_NEW;
+        let mut conflict_schedule_id = None;
+        let mut leave_type = None;
+
+        if let Some(id) = existing_by_key.get(&(p.user_id, p.start_at)) {
+            status = OCCURRENCE_EXISTING;
+            conflict_schedule_id = Some(*id);
+            existing += 1;
+        } else if let Some(leave) = leaves.iter().find(|l| {
+            l.user_id == p.user_id && l.start_date <= p.local_date && p.local_date <= l.end_date
+        }) {
+            status = OCCURRENCE_LEAVE;
+            leave_type = Some(leave.tipe);
+            leave_conflicts += 1;
+        } else if let Some(span) = spans.iter().find(|s| {
+            s.user_id == p.user_id
+                && !stale_ids.contains(&s.id)
+                && s.start_at < p.end_at
+                && s.end_at > p.start_at
+        }) {
+            status = OCCURRENCE_OVERLAP;
+            conflict_schedule_id = Some(span.id);
+            overlaps += 1;
+        } else if accepted
+            .get(&p.user_id)
+            .is_some_and(|list| list.iter().any(|(s, e)| *s < p.end_at && *e > p.start_at))
+        {
+            // bentrok dengan occurrence lain dari template ini (mis. user ada di 2 regu)
+            status = OCCURRENCE_OVERLAP;
+            overlaps += 1;
+        }
+
+        if status == OCCURRENCE_NEW {
+            accepted
+                .entry(p.user_id)
+                .or_default()
+                .push((p.start_at, p.end_at));
+            inserts.push(RotationScheduleInsert {
+                user_id: p.user_id,
+                start_at: p.start_at,
+                end_at: p.end_at,
+                schedule_type: p.schedule_type,
+                title: p.title.clone(),
+            });
+        }
+
+        occurrences.push(RotationOccurrenceDto {
+            user_id: p.user_id,
+            user_full_name: p.user_full_name,
+            user_nrp: p.user_nrp,
+            team_name: p.team_name,
+            start_at: p.start_at,
+            end_at: p.end_at,
+            schedule_type: p.schedule_type,
+            title: p.title,
+            status,
+            conflict_schedule_id,
+            leave_type,
+        });
+    }
+
+    let mut removed_schedule_ids: Vec<Uuid> = stale_ids.into_iter().collect();
+    removed_schedule_ids.sort();
+
+    let (created, removed) = if req.dry_run {
+        (inserts.len() as i64, removed_schedule_ids.len() as i64)
+    } else {
+        db.apply_duty_rotation(
+            template.satker_id,
+            template.id,
+            &removed_schedule_ids,
+            &inserts,
+            created_by,
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+    };
+
+    Ok(GenerateDutyRotationData {
+        dry_run: req.dry_run,
+        from: req.from,
+        to: req.to,
+        created,
+        existing,
+        overlaps,
+        leave_conflicts,
+        removed,
+        removed_schedule_ids,
+        occurrences,
+    })
+}
+
+pub fn local_midnight_utc(tz: Tz, date: NaiveDate) -> Result<DateTime<Utc>, HttpError> {
+    let midnight = date
+        .and_hms_opt(0, 0, 0)
+        .ok_or_else(|| HttpError::bad_request("tanggal tidak valid"))?;
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok_or_else(|| HttpError::bad_request("tanggal tidak valid"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dtos::duty_rotation::{RotationSlotDto, RotationTeamDto, RotationTeamMemberDto};
+    use chrono::NaiveTime;
+    use sqlx::types::Json;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn template(
+        cycle_days: i32,
+        anchor_date: NaiveDate,
+        members: usize,
+        slots: &[(i32, &str, i32)],
+    ) -> DutyRotationTemplateDto {
+        let team_id = Uuid::new_v4();
+        DutyRotationTemplateDto {
+            id: Uuid::new_v4(),
+            satker_id: Uuid::nil(),
+            satker_code: "S".into(),
+            satker_name: "Satker".into(),
+            name: "Piket".into(),
+            cycle_days,
+            anchor_date,
+            is_active: true,
+            teams: Json(vec![RotationTeamDto {
+                id: team_id,
+                name: "A".into(),
+                sort_order: 0,
+                members: (0..members)
+                    .map(|i| RotationTeamMemberDto {
+                        user_id: Uuid::from_u128(i as u128 + 1),
+                        full_name: format!("User {}", i),
+                        nrp: format!("{}", i),
+                    })
+                    .collect(),
+            }]),
+            slots: Json(
+                slots
+                    .iter()
+                    .map(|(day_index, start, minutes)| RotationSlotDto {
+                        id: Uuid::new_v4(),
+                        team_id,
+                        team_name: "A".into(),
+                        day_index: *day_index,
+                        start_time: NaiveTime::parse_from_str(start, "%H:%M").unwrap(),
+                        duration_minutes: *minutes,
+                        schedule_type: ScheduleType::Shift,
+                        title: None,
+                    })
+                    .collect(),
+            ),
+            created_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn cycle_continues_across_month_boundary_and_before_anchor() {
+        let t = template(3, date(2026, 1, 30), 1, &[(0, "08:00", 60)]);
+        let dates: Vec<NaiveDate> =
+            plan_rotation_occurrences(Tz::UTC, &t, date(2026, 1, 27), date(2026, 2, 6))
+                .iter()
+                .map(|p| p.local_date)
+                .collect();
+        assert_eq!(
+            dates,
+            [
+                date(2026, 1, 27),
+                date(2026, 1, 30),
+                date(2026, 2, 2),
+                date(2026, 2, 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn slot_time_is_local_and_overnight_slot_crosses_month() {
+        let t = template(
+            1,
+            date(2026, 1, 1),
+            1,
+            &[(0, "06:00", 60), (0, "22:00", 720)],
+        );
+        let planned =
+            plan_rotation_occurrences(Tz::Asia__Jakarta, &t, date(2026, 1, 31), date(2026, 1, 31));
+        assert_eq!(planned.len(), 2);
+
+        // 06:00 WIB = 23:00 UTC hari sebelumnya, tanggal lokal tetap 31 Jan
+        assert_eq!(planned[0].local_date, date(2026, 1, 31));
+        assert_eq!(planned[0].start_at, utc("2026-01-30T23:00:00Z"));
+
+        assert_eq!(planned[1].start_at, utc("2026-01-31T15:00:00Z"));
+        assert_eq!(planned[1].end_at, utc("2026-02-01T03:00:00Z"));
+    }
+
+    #[test]
+    fn dst_gap_is_skipped_and_overlap_uses_earliest() {
+        let t = template(1, date(2026, 1, 1), 1, &[(0, "02:30", 60)]);
+        let tz = Tz::Europe__Berlin;
+
+        assert!(plan_rotation_occurrences(tz, &t, date(2026, 3, 29), date(2026, 3, 29)).is_empty());
+
+        let fall_back = plan_rotation_occurrences(tz, &t, date(2026, 10, 25), date(2026, 10, 25));
+        assert_eq!(fall_back.len(), 1);
+        assert_eq!(fall_back[0].start_at, utc("2026-10-25T00:30:00Z"));
+    }
+
+    #[test]
+    fn every_member_gets_each_slot_sorted_by_start() {
+        let t = template(
+            2,
+            date(2026, 5, 1),
+            2,
+            &[(1, "20:00", 60), (1, "08:00", 60)],
+        );
+        let planned = plan_rotation_occurrences(Tz::UTC, &t, date(2026, 5, 1), date(2026, 5, 2));
+        let got: Vec<(DateTime<Utc>, Uuid)> =
+            planned.iter().map(|p| (p.start_at, p.user_id)).collect();
+        assert_eq!(
+            got,
+            [
+                (utc("2026-05-02T08:00:00Z"), Uuid::from_u128(1)),
+                (utc("2026-05-02T08:00:00Z"), Uuid::from_u128(2)),
+                (utc("2026-05-02T20:00:00Z"), Uuid::from_u128(1)),
+                (utc("2026-05-02T20:00:00Z"), Uuid::from_u128(2)),
+            ]
+        );
+    }
+}
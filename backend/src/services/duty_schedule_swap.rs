@@ -0,0 +1,580 @@
+//! Tukar / oper jadwal piket antar anggota satker.
+//!
+//! Alur status:
+//! PENDING_PARTNER -> (rekan terima) ACCEPTED -> (admin/kepala setuju) APPROVED
+//! PENDING_PARTNER -> DECLINED (rekan tolak) / CANCELED (pemohon batal)
+//! ACCEPTED -> REJECTED (admin/kepala tolak) / CANCELED (pemohon batal)
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::database::duty_schedule::{DutyScheduleRepo, has_overlap_in};
+use crate::database::duty_schedule_swap::{
+    DutyScheduleSwapRepo, lock_duty_schedule_swap, lock_swap_schedule, reassign_duty_schedule,
+    update_duty_schedule_swap_status,
+};
+use crate::database::user::UserRepo;
+use crate::db::DBClient;
+use crate::dtos::duty_schedule_swap::{
+    CreateDutyScheduleSwapReq, DutyScheduleSwapDto, SWAP_ACCEPTED, SWAP_APPROVED, SWAP_CANCELED,
+    SWAP_DECLINED, SWAP_PENDING_PARTNER, SWAP_REJECTED, SwapLockRow, SwapScheduleRow,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::UserClaims;
+use crate::services::authorization::ensure_can_manage_duty_schedules;
+
+/// Aksi selain approve (approve punya alurnya sendiri karena memindahkan jadwal).
+#[derive(Debug, Clone, Copy)]
+pub enum SwapAction {
+    Accept,
+    Decline,
+    Cancel,
+    Reject,
+}
+
+impl SwapAction {
+    fn name(self) -> &'static str {
+        match self {
+            SwapAction::Accept => "ACCEPTED",
+            SwapAction::Decline => "DECLINED",
+            SwapAction::Cancel => "CANCELED",
+            SwapAction::Reject => "REJECTED",
+        }
+    }
+
+    fn verb(self) -> &'static str {
+        match self {
+            SwapAction::Accept => "diterima",
+            SwapAction::Decline => "ditolak",
+            SwapAction::Cancel => "dibatalkan",
+            SwapAction::Reject => "di-reject",
+        }
+    }
+
+    fn allowed_statuses(self) -> &'static [&'static str] {
+        match self {
+            SwapAction::Accept | SwapAction::Decline => &[SWAP_PENDING_PARTNER],
+            SwapAction::Cancel => &[SWAP_PENDING_PARTNER, SWAP_ACCEPTED],
+            SwapAction::Reject => &[SWAP_PENDING_PARTNER, SWAP_ACCEPTED],
+        }
+    }
+
+    fn to_status(self) -> &'static str {
+        match self {
+            SwapAction::Accept => SWAP_ACCEPTED,
+            SwapAction::Decline => SWAP_DECLINED,
+            SwapAction::Cancel => SWAP_CANCELED,
+            SwapAction::Reject => SWAP_REJECTED,
+        }
+    }
+
+    fn ensure_allowed_from(self, status: &str) -> Result<(), HttpError> {
+        if !self.allowed_statuses().contains(&status) {
+            return Err(HttpError::bad_request(format!(
+                "pengajuan dengan status {} tidak bisa {}",
+                status,
+                self.verb()
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn ensure_approvable(status: &str) -> Result<(), HttpError> {
+    if status != SWAP_ACCEPTED {
+        return Err(HttpError::bad_request(
+            "hanya pengajuan yang sudah diterima rekan (ACCEPTED) yang bisa di-approve",
+        ));
+    }
+    Ok(())
+}
+
+/// Peserta (pemohon/rekan) atau pengelola jadwal satker boleh melihat pengajuan.
+pub async fn ensure_can_view_swap(
+    db: &DBClient,
+    claims: &UserClaims,
+    swap: &DutyScheduleSwapDto,
+) -> Result<(), HttpError> {
+    if claims.user_id == swap.requester_id || claims.user_id == swap.counterpart_id {
+        return Ok(());
+    }
+    ensure_can_manage_duty_schedules(db, claims, swap.satker_id).await
+}
+
+fn ensure_swappable(schedule: &SwapScheduleRow, label: &str) -> Result<(), HttpError> {
+    if schedule.start_at <= Utc::now() {
+        return Err(HttpError::bad_request(format!(
+            "{} sudah dimulai, tidak bisa ditukar",
+            label
+        )));
+    }
+    Ok(())
+}
+
+/// Ajukan tukar (atau oper) jadwal milik pemohon ke rekan satu satker.
+pub async fn create_duty_schedule_swap(
+    db: &DBClient,
+    claims: &UserClaims,
+    req: CreateDutyScheduleSwapReq,
+) -> Result<Uuid, HttpError> {
+    let own = db
+        .find_swap_schedule(req.schedule_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("jadwal dinas tidak ditemukan"))?;
+    if own.user_id != claims.user_id {
+        return Err(HttpError::unauthorized("forbidden"));
+    }
+    ensure_swappable(&own, "jadwal dinas")?;
+
+    if req.counterpart_id == claims.user_id {
+        return Err(HttpError::bad_request(
+            "tidak bisa tukar jadwal dengan diri sendiri",
+        ));
+    }
+    db.find_user_by_satker(req.counterpart_id, own.satker_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("rekan bukan anggota aktif satker yang sama"))?;
+
+    let other = match req.counterpart_schedule_id {
+        Some(id) => {
+            let row = db
+                .find_swap_schedule(id)
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?
+                .ok_or_else(|| HttpError::bad_request("jadwal dinas rekan tidak ditemukan"))?;
+            if row.user_id != req.counterpart_id {
+                return Err(HttpError::bad_request(
+                    "jadwal dinas rekan bukan milik rekan tersebut",
+                ));
+            }
+            ensure_swappable(&row, "jadwal dinas rekan")?;
+            Some(row)
+        }
+        None => None,
+    };
+
+    for schedule_id in std::iter::once(own.id).chain(other.as_ref().map(|s| s.id)) {
+        let open = db
+            .has_open_swap_for_schedule(schedule_id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        if open {
+            return Err(HttpError::bad_request(
+                "jadwal dinas masih ada di pengajuan tukar lain yang belum selesai",
+            ));
+        }
+    }
+
+    // Cek awal supaya pengajuan yang pasti bentrok tidak perlu diproses; dicek ulang saat approve.
+    let counterpart_overlap = db
+        .has_overlap(
+            req.counterpart_id,
+            own.start_at,
+            own.end_at,
+            other.as_ref().map(|s| s.id),
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if counterpart_overlap {
+        return Err(HttpError::bad_request(
+            "jadwal dinas overlap dengan jadwal dinas rekan yang sudah ada",
+        ));
+    }
+    if let Some(other) = &other {
+        let requester_overlap = db
+            .has_overlap(claims.user_id, other.start_at, other.end_at, Some(own.id))
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        if requester_overlap {
+            return Err(HttpError::bad_request(
+                "jadwal dinas rekan overlap dengan jadwal dinas Anda yang sudah ada",
+            ));
+        }
+    }
+
+    let note = req
+        .note
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+
+    db.create_duty_schedule_swap(
+        own.satker_id,
+        &own,
+        req.counterpart_id,
+        other.as_ref(),
+        note,
+    )
+    .await
+    .map_err(|e| {
+        if e.as_database_error()
+            .is_some_and(|d| d.is_unique_violation())
+        {
+            HttpError::bad_request(
+                "jadwal dinas masih ada di pengajuan tukar lain yang belum selesai",
+            )
+        } else {
+            HttpError::server_error(e.to_string())
+        }
+    })
+}
+
+async fn ensure_can_act(
+    db: &DBClient,
+    claims: &UserClaims,
+    swap: &SwapLockRow,
+    action: SwapAction,
+) -> Result<(), HttpError> {
+    match action {
+        SwapAction::Accept | SwapAction::Decline => {
+            if claims.user_id != swap.counterpart_id {
+                return Err(HttpError::unauthorized("forbidden"));
+            }
+        }
+        SwapAction::Cancel => {
+            if claims.user_id != swap.requester_id {
+                return Err(HttpError::unauthorized("forbidden"));
+            }
+        }
+        SwapAction::Reject => {
+            ensure_can_manage_duty_schedules(db, claims, swap.satker_id).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Terima / tolak (rekan), batalkan (pemohon), atau tolak (admin/kepala satker).
+pub async fn respond_duty_schedule_swap(
+    db: &DBClient,
+    claims: &UserClaims,
+    id: Uuid,
+    action: SwapAction,
+    note: Option<String>,
+) -> Result<(), HttpError> {
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let swap = lock_duty_schedule_swap(&mut tx, id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("pengajuan tukar jadwal tidak ditemukan"))?;
+
+    ensure_can_act(db, claims, &swap, action).await?;
+
+    action.ensure_allowed_from(&swap.status)?;
+
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    update_duty_schedule_swap_status(
+        &mut tx,
+        &swap,
+        claims.user_id,
+        action.name(),
+        action.to_status(),
+        note.as_deref(),
+        matches!(action, SwapAction::Reject),
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Setujui tukar jadwal: user_id kedua jadwal ditukar dalam satu transaksi.
+/// Kalau jadwal sudah berubah atau bentrok, pengajuan otomatis ditolak.
+pub async fn approve_duty_schedule_swap(
+    db: &DBClient,
+    claims: &UserClaims,
+    id: Uuid,
+) -> Result<(), HttpError> {
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let swap = lock_duty_schedule_swap(&mut tx, id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("pengajuan tukar jadwal tidak ditemukan"))?;
+
+    ensure_can_manage_duty_schedules(db, claims, swap.satker_id).await?;
+
+    ensure_approvable(&swap.status)?;
+
+    let problem = check_swap_still_valid(&mut tx, &swap)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if let Some(reason) = problem {
+        // auto reject (jadwal tidak diubah)
+        update_duty_schedule_swap_status(
+            &mut tx,
+            &swap,
+            claims.user_id,
+            "AUTO_REJECTED",
+            SWAP_REJECTED,
+            Some(reason.as_str()),
+            true,
+        )
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        return Err(HttpError::bad_request(reason));
+    }
+
+    reassign_duty_schedule(&mut tx, swap.requester_schedule_id, swap.counterpart_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if let Some(schedule_id) = swap.counterpart_schedule_id {
+        reassign_duty_schedule(&mut tx, schedule_id, swap.requester_id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+    }
+
+    update_duty_schedule_swap_status(
+        &mut tx,
+        &swap,
+        claims.user_id,
+        "APPROVED",
+        SWAP_APPROVED,
+        None,
+        true,
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Jadwal yang sudah dikunci harus masih sama seperti saat diajukan dan belum dimulai.
+fn swap_schedule_problem(
+    swap: &SwapLockRow,
+    own: &SwapScheduleRow,
+    other: Option<&SwapScheduleRow>,
+    now: DateTime<Utc>,
+) -> Option<String> {
+    if own.user_id != swap.requester_id
+        || own.start_at != swap.requester_start_at
+        || own.end_at != swap.requester_end_at
+    {
+        return Some("jadwal dinas pemohon sudah berubah sejak diajukan".to_string());
+    }
+    if let Some(row) = other
+        && (row.user_id != swap.counterpart_id
+            || Some(row.start_at) != swap.counterpart_start_at
+            || Some(row.end_at) != swap.counterpart_end_at)
+    {
+        return Some("jadwal dinas rekan sudah berubah sejak diajukan".to_string());
+    }
+    if own.start_at <= now || other.is_some_and(|s| s.start_at <= now) {
+        return Some("jadwal dinas sudah dimulai, tidak bisa ditukar".to_string());
+    }
+    None
+}
+
+/// Alasan penolakan dari hasil cek overlap setelah jadwal ditukar.
+fn swap_overlap_problem(counterpart_overlap: bool, requester_overlap: bool) -> Option<String> {
+    if counterpart_overlap {
+        return Some("jadwal dinas overlap dengan jadwal dinas rekan yang sudah ada".to_string());
+    }
+    if requester_overlap {
+        return Some(
+            "jadwal dinas rekan overlap dengan jadwal dinas pemohon yang sudah ada".to_string(),
+        );
+    }
+    None
+}
+
+/// Kunci kedua jadwal lalu pastikan masih sama seperti saat diajukan dan tidak bentrok.
+/// Return alasan penolakan kalau tidak valid lagi.
+async fn check_swap_still_valid(
+    conn: &mut sqlx::PgConnection,
+    swap: &SwapLockRow,
+) -> Result<Option<String>, sqlx::Error> {
+    let Some(own) = lock_swap_schedule(conn, swap.requester_schedule_id).await? else {
+        return Ok(Some("jadwal dinas pemohon sudah dihapus".to_string()));
+    };
+
+    let other = match swap.counterpart_schedule_id {
+        Some(schedule_id) => {
+            let Some(row) = lock_swap_schedule(conn, schedule_id).await? else {
+                return Ok(Some("jadwal dinas rekan sudah dihapus".to_string()));
+            };
+            Some(row)
+        }
+        None => None,
+    };
+
+    if let Some(problem) = swap_schedule_problem(swap, &own, other.as_ref(), Utc::now()) {
+        return Ok(Some(problem));
+    }
+
+    let counterpart_overlap = has_overlap_in(
+        conn,
+        swap.counterpart_id,
+        own.start_at,
+        own.end_at,
+        other.as_ref().map(|s| s.id),
+    )
+    .await?;
+    let requester_overlap = match &other {
+        Some(other) => {
+            has_overlap_in(
+                conn,
+                swap.requester_id,
+                other.start_at,
+                other.end_at,
+                Some(own.id),
+            )
+            .await?
+        }
+        None => false,
+    };
+
+    Ok(swap_overlap_problem(counterpart_overlap, requester_overlap))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    const ALL_STATUSES: [&str; 6] = [
+        SWAP_PENDING_PARTNER,
+        SWAP_ACCEPTED,
+        SWAP_APPROVED,
+        SWAP_REJECTED,
+        SWAP_DECLINED,
+        SWAP_CANCELED,
+    ];
+
+    fn allowed_from(action: SwapAction) -> Vec<&'static str> {
+        ALL_STATUSES
+            .into_iter()
+            .filter(|s| action.ensure_allowed_from(s).is_ok())
+            .collect()
+    }
+
+    #[test]
+    fn partner_only_answers_pending_requests() {
+        assert_eq!(allowed_from(SwapAction::Accept), [SWAP_PENDING_PARTNER]);
+        assert_eq!(allowed_from(SwapAction::Decline), [SWAP_PENDING_PARTNER]);
+        assert_eq!(SwapAction::Accept.to_status(), SWAP_ACCEPTED);
+        assert_eq!(SwapAction::Decline.to_status(), SWAP_DECLINED);
+    }
+
+    #[test]
+    fn cancel_and_reject_only_before_final_status() {
+        for action in [SwapAction::Cancel, SwapAction::Reject] {
+            assert_eq!(allowed_from(action), [SWAP_PENDING_PARTNER, SWAP_ACCEPTED]);
+        }
+        assert_eq!(SwapAction::Cancel.to_status(), SWAP_CANCELED);
+        assert_eq!(SwapAction::Reject.to_status(), SWAP_REJECTED);
+    }
+
+    #[test]
+    fn only_accepted_request_can_be_approved() {
+        let approvable: Vec<&str> = ALL_STATUSES
+            .into_iter()
+            .filter(|s| ensure_approvable(s).is_ok())
+            .collect();
+        assert_eq!(approvable, [SWAP_ACCEPTED]);
+    }
+
+    fn fixture(now: DateTime<Utc>) -> (SwapLockRow, SwapScheduleRow, SwapScheduleRow) {
+        let (requester, counterpart) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        let own = SwapScheduleRow {
+            id: Uuid::from_u128(10),
+            satker_id: Uuid::nil(),
+            user_id: requester,
+            start_at: now + Duration::days(1),
+            end_at: now + Duration::days(1) + Duration::hours(8),
+        };
+        let other = SwapScheduleRow {
+            id: Uuid::from_u128(20),
+            satker_id: Uuid::nil(),
+            user_id: counterpart,
+            start_at: now + Duration::days(2),
+            end_at: now + Duration::days(2) + Duration::hours(8),
+        };
+        let swap = SwapLockRow {
+            id: Uuid::new_v4(),
+            satker_id: Uuid::nil(),
+            requester_id: requester,
+            requester_schedule_id: own.id,
+            requester_start_at: own.start_at,
+            requester_end_at: own.end_at,
+            counterpart_id: counterpart,
+            counterpart_schedule_id: Some(other.id),
+            counterpart_start_at: Some(other.start_at),
+            counterpart_end_at: Some(other.end_at),
+            status: SWAP_ACCEPTED.to_string(),
+        };
+        (swap, own, other)
+    }
+
+    #[test]
+    fn approve_rejects_changed_or_started_schedules() {
+        let now = Utc::now();
+        let (swap, own, other) = fixture(now);
+        assert_eq!(swap_schedule_problem(&swap, &own, Some(&other), now), None);
+
+        let moved = SwapScheduleRow {
+            end_at: own.end_at + Duration::hours(1),
+            ..own.clone()
+        };
+        assert!(
+            swap_schedule_problem(&swap, &moved, Some(&other), now)
+                .unwrap()
+                .contains("pemohon sudah berubah")
+        );
+
+        let reassigned = SwapScheduleRow {
+            user_id: Uuid::from_u128(3),
+            ..other.clone()
+        };
+        assert!(
+            swap_schedule_problem(&swap, &own, Some(&reassigned), now)
+                .unwrap()
+                .contains("rekan sudah berubah")
+        );
+
+        let later = own.start_at + Duration::minutes(1);
+        assert!(
+            swap_schedule_problem(&swap, &own, Some(&other), later)
+                .unwrap()
+                .contains("sudah dimulai")
+        );
+    }
+
+    #[test]
+    fn approve_rejects_overlap_after_swap() {
+        assert_eq!(swap_overlap_problem(false, false), None);
+        assert!(
+            swap_overlap_problem(true, false)
+                .unwrap()
+                .contains("jadwal dinas rekan yang sudah ada")
+        );
+        assert!(
+            swap_overlap_problem(false, true)
+                .unwrap()
+                .contains("jadwal dinas pemohon yang sudah ada")
+        );
+    }
+}
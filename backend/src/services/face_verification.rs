@@ -0,0 +1,415 @@
+//! Enrolment foto referensi wajah (disetujui admin satker) dan verifikasi selfie
+//! check-in/out di server terhadap template hasil enrolment.
+
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::constants::AttendanceEventType;
+use crate::database::face_enrollment::{
+    FaceEnrollmentRepo, lock_face_enrollment, refresh_user_face_template,
+    update_face_enrollment_status,
+};
+use crate::db::DBClient;
+use crate::dtos::face_enrollment::{
+    FACE_ACCEPTED, FACE_ENROLLMENT_APPROVED, FACE_ENROLLMENT_PENDING, FACE_ENROLLMENT_REJECTED,
+    FACE_ENROLLMENT_REVOKED, FACE_FLAGGED, FACE_NO_SELFIE, FACE_NOT_ENROLLED, FaceCheckDto,
+};
+use crate::dtos::settings::AttendanceRules;
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::UserClaims;
+use crate::services::authorization::ensure_can_access_satker;
+use crate::services::face_verifier::FaceVerifier;
+use crate::services::metrics::{RejectReason, record_attendance_rejection};
+use crate::services::storage::Storage;
+use crate::services::upload::{FACE_FOLDER, SELFIE_FOLDER};
+
+/// Keputusan admin atas enrolment.
+#[derive(Debug, Clone, Copy)]
+pub enum FaceEnrollmentAction {
+    Reject,
+    Revoke,
+}
+
+async fn read_stored_image(
+    storage: &Storage,
+    folder: &str,
+    key: &str,
+) -> Result<Vec<u8>, HttpError> {
+    if storage.resolve(folder, key).is_none() {
+        return Err(HttpError::bad_request("key foto tidak valid"));
+    }
+    storage
+        .read(folder, key)
+        .await?
+        .ok_or_else(|| HttpError::bad_request("file foto tidak ditemukan"))
+}
+
+/// Simpan enrolment baru (PENDING) untuk foto yang sudah diupload.
+pub async fn submit_face_enrollment(
+    db: &DBClient,
+    claims: &UserClaims,
+    user_id: Uuid,
+    satker_id: Uuid,
+    photo_object_key: &str,
+) -> Result<Uuid, HttpError> {
+    if user_id != claims.user_id {
+        ensure_can_access_satker(claims, satker_id)?;
+    }
+
+    db.create_face_enrollment(user_id, satker_id, photo_object_key, claims.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))
+}
+
+/// Setujui enrolment: buat template lewat verifier aktif lalu perbarui hash template user.
+pub async fn approve_face_enrollment(
+    app_state: &AppState,
+    claims: &UserClaims,
+    id: Uuid,
+) -> Result<(), HttpError> {
+    let verifier = app_state
+        .face_verifier
+        .as_ref()
+        .ok_or_else(|| HttpError::bad_request("verifikasi wajah tidak aktif (FACE_VERIFIER)"))?;
+
+    let mut tx = app_state
+        .db_client
+        .pool
+        .begin()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let row = lock_face_enrollment(&mut tx, id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("enrolment wajah tidak ditemukan"))?;
+
+    ensure_can_access_satker(claims, row.satker_id)?;
+    if row.status != FACE_ENROLLMENT_PENDING {
+        return Err(HttpError::bad_request(format!(
+            "enrolment berstatus {}, tidak bisa disetujui",
+            row.status
+        )));
+    }
+
+    let image = read_stored_image(&app_state.storage, FACE_FOLDER, &row.photo_object_key).await?;
+    let template = verifier.enroll(&image).await?;
+
+    update_face_enrollment_status(
+        &mut tx,
+        id,
+        FACE_ENROLLMENT_APPROVED,
+        claims.user_id,
+        Some(&template),
+        Some(verifier.name()),
+        None,
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    refresh_user_face_template(&mut tx, row.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Tolak enrolment PENDING, atau cabut enrolment PENDING/APPROVED.
+pub async fn respond_face_enrollment(
+    db: &DBClient,
+    claims: &UserClaims,
+    id: Uuid,
+    action: FaceEnrollmentAction,
+    reject_reason: Option<String>,
+) -> Result<(), HttpError> {
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let row = lock_face_enrollment(&mut tx, id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("enrolment wajah tidak ditemukan"))?;
+
+    ensure_can_access_satker(claims, row.satker_id)?;
+
+    let to_status = match action {
+        FaceEnrollmentAction::Reject => {
+            if row.status != FACE_ENROLLMENT_PENDING {
+                return Err(HttpError::bad_request(format!(
+                    "enrolment berstatus {}, tidak bisa ditolak",
+                    row.status
+                )));
+            }
+            FACE_ENROLLMENT_REJECTED
+        }
+        FaceEnrollmentAction::Revoke => {
+            if row.status != FACE_ENROLLMENT_PENDING && row.status != FACE_ENROLLMENT_APPROVED {
+                return Err(HttpError::bad_request(format!(
+                    "enrolment berstatus {}, tidak bisa dicabut",
+                    row.status
+                )));
+            }
+            FACE_ENROLLMENT_REVOKED
+        }
+    };
+
+    update_face_enrollment_status(
+        &mut tx,
+        id,
+        to_status,
+        claims.user_id,
+        None,
+        None,
+        reject_reason.as_deref(),
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if row.status == FACE_ENROLLMENT_APPROVED {
+        refresh_user_face_template(&mut tx, row.user_id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Cocokkan selfie event dengan template user.
+///
+/// - `None`: verifier tidak aktif, event disimpan tanpa hasil verifikasi.
+/// - skor < `face_reject_below` => ditolak; < `face_accept_at` => FLAGGED untuk ditinjau.
+/// - tanpa selfie / belum enrol: ditolak kalau `face_required`, selain itu dicatat saja.
+pub async fn verify_attendance_face(
+    app_state: &AppState,
+    rules: &AttendanceRules,
+    event: AttendanceEventType,
+    user_id: Uuid,
+    selfie_object_key: Option<&str>,
+) -> Result<Option<FaceCheckDto>, HttpError> {
+    let Some(verifier) = app_state.face_verifier.as_ref() else {
+        return Ok(None);
+    };
+
+    let Some(key) = selfie_object_key.map(str::trim).filter(|k| !k.is_empty()) else {
+        if rules.face_required {
+            record_attendance_rejection(event, RejectReason::FaceMismatch);
+            return Err(HttpError::bad_request(
+                "selfie wajib untuk verifikasi wajah",
+            ));
+        }
+        return Ok(Some(FaceCheckDto {
+            status: FACE_NO_SELFIE,
+            score: None,
+        }));
+    };
+
+    // selfie harus hasil upload user ini sendiri (nama file diawali user_id)
+    let owned = key
+        .rsplit('/')
+        .next()
+        .is_some_and(|name| name.starts_with(&format!("{}_", user_id)));
+    if !owned {
+        return Err(HttpError::bad_request("selfie bukan milik user ini"));
+    }
+
+    let templates = app_state
+        .db_client
+        .list_face_templates(user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .into_iter()
+        .filter(|t| t.verifier.as_deref() == Some(verifier.name()))
+        .collect::<Vec<_>>();
+
+    if templates.is_empty() {
+        if rules.face_required {
+            record_attendance_rejection(event, RejectReason::FaceMismatch);
+            return Err(HttpError::bad_request(
+                "foto referensi wajah belum disetujui, hubungi admin satker",
+            ));
+        }
+        return Ok(Some(FaceCheckDto {
+            status: FACE_NOT_ENROLLED,
+            score: None,
+        }));
+    }
+
+    let image = read_stored_image(&app_state.storage, SELFIE_FOLDER, key).await?;
+    let templates = templates
+        .into_iter()
+        .map(|t| t.template)
+        .collect::<Vec<_>>();
+    score_selfie(verifier.as_ref(), &templates, &image, rules, event)
+        .await
+        .map(Some)
+}
+
+/// Skor terbaik selfie terhadap semua template, lalu putuskan dengan ambang aturan:
+/// di bawah `face_reject_below` ditolak, di bawah `face_accept_at` ditandai untuk review.
+async fn score_selfie(
+    verifier: &dyn FaceVerifier,
+    templates: &[String],
+    image: &[u8],
+    rules: &AttendanceRules,
+    event: AttendanceEventType,
+) -> Result<FaceCheckDto, HttpError> {
+    let mut best = 0.0f64;
+    for t in templates {
+        best = best.max(verifier.compare(t, image).await?);
+    }
+
+    if best < rules.face_reject_below {
+        record_attendance_rejection(event, RejectReason::FaceMismatch);
+        return Err(HttpError::bad_request(format!(
+            "wajah tidak cocok dengan foto referensi (skor {:.2})",
+            best
+        )));
+    }
+
+    let status = if best < rules.face_accept_at {
+        FACE_FLAGGED
+    } else {
+        FACE_ACCEPTED
+    };
+    Ok(FaceCheckDto {
+        status,
+        score: Some(best),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::face_verifier::StubFaceVerifier;
+
+    /// Byte 0..63 berulang: histogram stub terisi di 16 bin pertama.
+    fn reference_image() -> Vec<u8> {
+        (0..4000).map(|i| (i % 64) as u8).collect()
+    }
+
+    /// `shifted` byte pertama dipindah ke bin lain: makin banyak, makin rendah skornya.
+    fn altered_image(shifted: usize) -> Vec<u8> {
+        (0..4000)
+            .map(|i| {
+                if i < shifted {
+                    64 + (i % 64) as u8
+                } else {
+                    (i % 64) as u8
+                }
+            })
+            .collect()
+    }
+
+    async fn check(
+        rules: &AttendanceRules,
+        templates: &[String],
+        image: &[u8],
+    ) -> Result<FaceCheckDto, HttpError> {
+        score_selfie(
+            &StubFaceVerifier,
+            templates,
+            image,
+            rules,
+            AttendanceEventType::CheckIn,
+        )
+        .await
+    }
+
+    async fn enroll(image: &[u8]) -> String {
+        StubFaceVerifier.enroll(image).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn identical_selfie_is_accepted() {
+        let rules = AttendanceRules::default();
+        let templates = vec![enroll(&reference_image()).await];
+
+        let res = check(&rules, &templates, &reference_image()).await.unwrap();
+        assert_eq!(res.status, FACE_ACCEPTED);
+        assert_eq!(res.score, Some(1.0));
+    }
+
+    #[tokio::test]
+    async fn score_between_thresholds_is_flagged() {
+        let rules = AttendanceRules::default();
+        let templates = vec![enroll(&reference_image()).await];
+
+        let res = check(&rules, &templates, &altered_image(2000))
+            .await
+            .unwrap();
+        let score = res.score.unwrap();
+        assert_eq!(res.status, FACE_FLAGGED);
+        assert!(score >= rules.face_reject_below && score < rules.face_accept_at);
+    }
+
+    #[tokio::test]
+    async fn score_below_reject_threshold_is_rejected() {
+        let rules = AttendanceRules::default();
+        let templates = vec![enroll(&reference_image()).await];
+        let other: Vec<u8> = (0..4000).map(|i| 128 + (i % 64) as u8).collect();
+
+        let err = check(&rules, &templates, &other).await.unwrap_err();
+        assert_eq!(err.status, axum::http::StatusCode::BAD_REQUEST);
+        assert!(err.message.contains("tidak cocok"));
+    }
+
+    #[tokio::test]
+    async fn thresholds_follow_rules() {
+        let templates = vec![enroll(&reference_image()).await];
+        let selfie = altered_image(1000);
+
+        let default = check(&AttendanceRules::default(), &templates, &selfie)
+            .await
+            .unwrap();
+        assert_eq!(default.status, FACE_ACCEPTED);
+        let score = default.score.unwrap();
+        assert!(score < 1.0);
+
+        let strict_accept = AttendanceRules {
+            face_accept_at: 0.99,
+            ..AttendanceRules::default()
+        };
+        let res = check(&strict_accept, &templates, &selfie).await.unwrap();
+        assert_eq!(res.status, FACE_FLAGGED);
+
+        let strict_reject = AttendanceRules {
+            face_reject_below: 0.99,
+            face_accept_at: 0.99,
+            ..AttendanceRules::default()
+        };
+        assert!(check(&strict_reject, &templates, &selfie).await.is_err());
+
+        // skor tepat di ambang tidak ditolak
+        let at_threshold = AttendanceRules {
+            face_reject_below: score,
+            face_accept_at: score,
+            ..AttendanceRules::default()
+        };
+        let res = check(&at_threshold, &templates, &selfie).await.unwrap();
+        assert_eq!(res.status, FACE_ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn best_template_wins() {
+        let rules = AttendanceRules::default();
+        let templates = vec![
+            enroll(&altered_image(2000)).await,
+            enroll(&reference_image()).await,
+        ];
+
+        let res = check(&rules, &templates, &reference_image()).await.unwrap();
+        assert_eq!(res.status, FACE_ACCEPTED);
+        assert_eq!(res.score, Some(1.0));
+    }
+}
@@ -0,0 +1,108 @@
+//! Batas layanan pencocokan wajah. Implementasi dipilih lewat env `FACE_VERIFIER`;
+//! kosong / `off` = verifikasi wajah di server tidak aktif.
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+use crate::error::HttpError;
+
+#[async_trait]
+pub trait FaceVerifier: Send + Sync {
+    /// Nama verifier; disimpan bersama template supaya template verifier lain tidak dipakai.
+    fn name(&self) -> &'static str;
+
+    /// Buat template dari foto referensi.
+    async fn enroll(&self, image: &[u8]) -> Result<String, HttpError>;
+
+    /// Skor kemiripan selfie terhadap template, 0.0 (beda) .. 1.0 (sama).
+    async fn compare(&self, template: &str, image: &[u8]) -> Result<f64, HttpError>;
+}
+
+pub fn build_face_verifier(name: Option<&str>) -> Result<Option<Arc<dyn FaceVerifier>>, String> {
+    match name.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+        None | Some("") | Some("off") => Ok(None),
+        Some(StubFaceVerifier::NAME) => {
+            tracing::warn!(
+                "FACE_VERIFIER=stub: hanya untuk development/test, bukan pengenalan wajah"
+            );
+            Ok(Some(Arc::new(StubFaceVerifier)))
+        }
+        Some(other) => Err(format!("FACE_VERIFIER tidak dikenal: {}", other)),
+    }
+}
+
+const STUB_BINS: usize = 64;
+
+/// Verifier lokal deterministik (tanpa model wajah) untuk development & test:
+/// file yang sama persis = 1.0, selain itu korelasi histogram byte kedua file (0..1).
+pub struct StubFaceVerifier;
+
+impl StubFaceVerifier {
+    const NAME: &'static str = "stub";
+
+    fn histogram(image: &[u8]) -> [f64; STUB_BINS] {
+        let mut bins = [0f64; STUB_BINS];
+        for b in image {
+            bins[(*b as usize) * STUB_BINS / 256] += 1.0;
+        }
+        let total = image.len().max(1) as f64;
+        bins.iter_mut().for_each(|v| *v /= total);
+        bins
+    }
+
+    fn correlation(a: &[f64], b: &[f64]) -> f64 {
+        let n = a.len() as f64;
+        let (ma, mb) = (a.iter().sum::<f64>() / n, b.iter().sum::<f64>() / n);
+        let (mut cov, mut va, mut vb) = (0.0, 0.0, 0.0);
+        for (x, y) in a.iter().zip(b) {
+            cov += (x - ma) * (y - mb);
+            va += (x - ma).powi(2);
+            vb += (y - mb).powi(2);
+        }
+        if va == 0.0 || vb == 0.0 {
+            return 0.0;
+        }
+        (cov / (va.sqrt() * vb.sqrt())).clamp(0.0, 1.0)
+    }
+}
+
+#[async_trait]
+impl FaceVerifier for StubFaceVerifier {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    async fn enroll(&self, image: &[u8]) -> Result<String, HttpError> {
+        if image.is_empty() {
+            return Err(HttpError::bad_request("foto referensi kosong"));
+        }
+        let digest = format!("{:x}", Sha256::digest(image));
+        let bins = Self::histogram(image)
+            .iter()
+            .map(|v| format!("{:.6}", v))
+            .collect::<Vec<_>>()
+            .join(",");
+        Ok(format!("{}|{}", digest, bins))
+    }
+
+    async fn compare(&self, template: &str, image: &[u8]) -> Result<f64, HttpError> {
+        let (digest, bins) = template
+            .split_once('|')
+            .ok_or_else(|| HttpError::server_error("template wajah tidak valid"))?;
+        if digest == format!("{:x}", Sha256::digest(image)) {
+            return Ok(1.0);
+        }
+
+        let reference = bins
+            .split(',')
+            .map(|v| v.parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| HttpError::server_error("template wajah tidak valid"))?;
+        if reference.len() != STUB_BINS {
+            return Err(HttpError::server_error("template wajah tidak valid"));
+        }
+
+        Ok(Self::correlation(&reference, &Self::histogram(image)))
+    }
+}
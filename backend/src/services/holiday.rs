@@ -1,9 +1,28 @@
 use crate::auth::rbac::UserRole;
 use crate::constants::{HolidayKind, HolidayScope};
+use crate::database::holiday::{
+    HolidayUpsertItem, insert_holiday_in, lock_scope_holidays, update_holiday_in,
+};
+use crate::database::work_calendar::WorkCalendarRepo;
+use crate::db::DBClient;
+use crate::dtos::holiday::{
+    BulkHolidayItem, CalendarRegenerateDto, HolidayImportData, HolidayImportItemDto,
+    HolidayImportOptions, IMPORT_CHANGED, IMPORT_CONFLICT, IMPORT_NEW, IMPORT_UNCHANGED,
+};
 use crate::error::HttpError;
-use chrono::NaiveTime;
+use crate::models::Holiday;
+use crate::services::calendar::{MAX_CALENDAR_GENERATE_DAYS, generate_calendar_days};
+use crate::services::calendar_regen::enqueue_holiday_regen;
+use crate::utils::ics::parse_events;
+use chrono::{NaiveDate, NaiveTime};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
+/// Batas ukuran file import libur (CSV / ICS).
+pub const MAX_HOLIDAY_IMPORT_BYTES: usize = 1024 * 1024;
+/// Acara ICS multi-hari dipecah per tanggal; lebih dari ini dianggap bukan libur.
+const MAX_ICS_EVENT_DAYS: i64 = 31;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HolidayScopeAccess {
     National,
@@ -83,3 +102,329 @@ pub fn normalize_holiday_kind_and_half_day(
 
     Ok((kind, half_day_end))
 }
+
+/// Validasi & konversi item bulk (request JSON / file import) ke item upsert.
+pub fn build_holiday_upsert_items(
+    items: Vec<BulkHolidayItem>,
+) -> Result<Vec<HolidayUpsertItem>, HttpError> {
+    let mut out: Vec<HolidayUpsertItem> = Vec::with_capacity(items.len());
+    for it in items {
+        let (kind, half_day_end) = normalize_holiday_kind_and_half_day(it.kind, it.half_day_end)?;
+        out.push(HolidayUpsertItem {
+            holiday_date: it.holiday_date,
+            kind,
+            name: it.name,
+            half_day_end,
+        });
+    }
+    Ok(out)
+}
+
+/// Parse file CSV libur. Header wajib: `holiday_date,name[,kind][,half_day_end]`.
+/// Contoh baris: `2026-03-20,Hari Raya Idul Fitri,HOLIDAY,`
+pub fn parse_holiday_csv(data: &[u8]) -> Result<Vec<BulkHolidayItem>, HttpError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(data);
+
+    let mut items = Vec::new();
+    for (idx, row) in reader.deserialize::<BulkHolidayItem>().enumerate() {
+        // +2: baris 1 adalah header
+        let item = row.map_err(|e| HttpError::bad_request(format!("baris {}: {}", idx + 2, e)))?;
+        items.push(item);
+    }
+    Ok(items)
+}
+
+/// Parse file ICS libur (mis. ekspor kalender libur nasional). Semua acara jadi kind HOLIDAY;
+/// acara multi-hari dipecah per tanggal (DTEND eksklusif).
+pub fn parse_holiday_ics(data: &[u8]) -> Result<Vec<BulkHolidayItem>, HttpError> {
+    let text =
+        std::str::from_utf8(data).map_err(|_| HttpError::bad_request("file ics harus UTF-8"))?;
+    let events = parse_events(text).map_err(HttpError::bad_request)?;
+
+    let mut items = Vec::new();
+    for ev in events {
+        let end = ev.end.filter(|end| *end > ev.start);
+        let days = end.map(|end| (end - ev.start).num_days()).unwrap_or(1);
+        if days > MAX_ICS_EVENT_DAYS {
+            return Err(HttpError::bad_request(format!(
+                "acara \"{}\" ({}) lebih dari {} hari",
+                ev.summary, ev.start, MAX_ICS_EVENT_DAYS
+            )));
+        }
+
+        for date in ev.start.iter_days().take(days as usize) {
+            items.push(BulkHolidayItem {
+                holiday_date: date,
+                name: ev.summary.clone(),
+                kind: None,
+                half_day_end: None,
+            });
+        }
+    }
+    Ok(items)
+}
+
+/// Pilih parser dari ekstensi file; tanpa ekstensi dikenali, cek isi BEGIN:VCALENDAR.
+pub fn parse_holiday_import_file(
+    file_name: Option<&str>,
+    data: &[u8],
+) -> Result<Vec<BulkHolidayItem>, HttpError> {
+    let ext = file_name
+        .and_then(|n| n.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+
+    let is_ics = match ext.as_deref() {
+        Some("ics") | Some("ical") => true,
+        Some("csv") => false,
+        _ => data
+            .trim_ascii_start()
+            .get(..15)
+            .is_some_and(|head| head.eq_ignore_ascii_case(b"BEGIN:VCALENDAR")),
+    };
+
+    if is_ics {
+        parse_holiday_ics(data)
+    } else {
+        parse_holiday_csv(data)
+    }
+}
+
+fn same_holiday(existing: &Holiday, item: &HolidayUpsertItem) -> bool {
+    existing.kind == item.kind
+        && existing.name == item.name
+        && existing.half_day_end == item.half_day_end
+}
+
+fn import_item_dto(
+    item: &HolidayUpsertItem,
+    action: &'static str,
+    existing: Option<&Holiday>,
+    reason: Option<&str>,
+) -> HolidayImportItemDto {
+    HolidayImportItemDto {
+        holiday_date: item.holiday_date,
+        name: item.name.clone(),
+        kind: item.kind,
+        half_day_end: item.half_day_end,
+        action,
+        existing_name: existing.map(|h| h.name.clone()),
+        existing_kind: existing.map(|h| h.kind),
+        existing_half_day_end: existing.and_then(|h| h.half_day_end),
+        reason: reason.map(|r| r.to_string()),
+    }
+}
+
+/// Bandingkan isi file dengan tabel holidays (scope yang sama), lalu terapkan
+/// item NEW/CHANGED kecuali dry_run. Item CONFLICT tidak pernah diterapkan.
+pub async fn import_holidays(
+    db: &DBClient,
+    opts: &HolidayImportOptions,
+    items: Vec<BulkHolidayItem>,
+) -> Result<HolidayImportData, HttpError> {
+    if items.is_empty() {
+        return Err(HttpError::bad_request("file tidak berisi item libur"));
+    }
+
+    let mut items = build_holiday_upsert_items(items)?;
+    for it in items.iter_mut() {
+        it.name = it.name.trim().to_string();
+        if it.name.is_empty() {
+            return Err(HttpError::bad_request(format!(
+                "nama libur tanggal {} wajib diisi",
+                it.holiday_date
+            )));
+        }
+    }
+
+    // kelompokkan per tanggal; baris identik di file dianggap satu
+    let mut by_date: BTreeMap<NaiveDate, Vec<HolidayUpsertItem>> = BTreeMap::new();
+    for it in items {
+        let list = by_date.entry(it.holiday_date).or_default();
+        let dup = list
+            .iter()
+            .any(|x| x.kind == it.kind && x.name == it.name && x.half_day_end == it.half_day_end);
+        if !dup {
+            list.push(it);
+        }
+    }
+
+    let (Some(from), Some(to)) = (
+        by_date.keys().next().copied(),
+        by_date.keys().next_back().copied(),
+    ) else {
+        return Err(HttpError::bad_request("file tidak berisi item libur"));
+    };
+
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let existing_rows = lock_scope_holidays(&mut tx, opts.scope, opts.satker_id, from, to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let mut existing_by_date: BTreeMap<NaiveDate, Vec<Holiday>> = BTreeMap::new();
+    for h in existing_rows {
+        existing_by_date.entry(h.holiday_date).or_default().push(h);
+    }
+
+    let mut out = Vec::new();
+    let mut inserts: Vec<HolidayUpsertItem> = Vec::new();
+    let mut updates: Vec<(Uuid, HolidayUpsertItem)> = Vec::new();
+    let (mut unchanged, mut conflicts) = (0i64, 0i64);
+
+    for (date, list) in by_date {
+        let existing = existing_by_date
+            .get(&date)
+            .map(|v| v.as_slice())
+            .unwrap_or(&[]);
+
+        if list.len() > 1 {
+            for it in &list {
+                out.push(import_item_dto(
+                    it,
+                    IMPORT_CONFLICT,
+                    existing.first(),
+                    Some("tanggal ganda di file dengan isi berbeda"),
+                ));
+                conflicts += 1;
+            }
+            continue;
+        }
+
+        let Some(it) = list.into_iter().next() else {
+            continue;
+        };
+        match existing {
+            [] => {
+                out.push(import_item_dto(&it, IMPORT_NEW, None, None));
+                inserts.push(it);
+            }
+            [h] if same_holiday(h, &it) => {
+                out.push(import_item_dto(&it, IMPORT_UNCHANGED, Some(h), None));
+                unchanged += 1;
+            }
+            [h] => {
+                out.push(import_item_dto(&it, IMPORT_CHANGED, Some(h), None));
+                updates.push((h.id, it));
+            }
+            [h, ..] => {
+                out.push(import_item_dto(
+                    &it,
+                    IMPORT_CONFLICT,
+                    Some(h),
+                    Some("sudah ada lebih dari satu data libur di tanggal ini"),
+                ));
+                conflicts += 1;
+            }
+        }
+    }
+
+    let touched: Vec<NaiveDate> = inserts
+        .iter()
+        .map(|it| it.holiday_date)
+        .chain(updates.iter().map(|(_, it)| it.holiday_date))
+        .collect();
+    let regen_range = touched
+        .iter()
+        .min()
+        .copied()
+        .zip(touched.iter().max().copied());
+
+    if opts.regenerate_calendar
+        && let Some((regen_from, regen_to)) = regen_range
+        && (regen_to - regen_from).num_days() + 1 > MAX_CALENDAR_GENERATE_DAYS
+    {
+        return Err(HttpError::bad_request(format!(
+            "range tanggal yang berubah terlalu besar untuk generate ulang kalender (max {} hari)",
+            MAX_CALENDAR_GENERATE_DAYS
+        )));
+    }
+
+    let mut data = HolidayImportData {
+        dry_run: opts.dry_run,
+        scope: opts.scope,
+        satker_id: opts.satker_id,
+        created: inserts.len() as i64,
+        changed: updates.len() as i64,
+        unchanged,
+        conflicts,
+        items: out,
+        calendar: Vec::new(),
+    };
+
+    if opts.dry_run {
+        return Ok(data);
+    }
+
+    for it in &inserts {
+        insert_holiday_in(&mut tx, opts.scope, opts.satker_id, it)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+    }
+    for (id, it) in &updates {
+        update_holiday_in(&mut tx, *id, it)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    if let Some((regen_from, regen_to)) = regen_range {
+        if opts.regenerate_calendar {
+            data.calendar = regenerate_holiday_calendars(db, opts, regen_from, regen_to).await?;
+        } else {
+            enqueue_holiday_regen(
+                db,
+                opts.scope,
+                opts.satker_id,
+                regen_from,
+                regen_to,
+                "HOLIDAY_IMPORT",
+            )
+            .await;
+        }
+    }
+
+    Ok(data)
+}
+
+/// Generate ulang kalender satker yang terdampak. Libur sudah tersimpan, jadi
+/// kegagalan per satker (mis. work pattern belum diset) dilaporkan, bukan dibatalkan.
+async fn regenerate_holiday_calendars(
+    db: &DBClient,
+    opts: &HolidayImportOptions,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<CalendarRegenerateDto>, HttpError> {
+    let satker_ids = match opts.satker_id {
+        Some(satker_id) => vec![satker_id],
+        // libur nasional: hanya satker yang kalendernya sudah pernah di-generate
+        None => db
+            .list_satkers_with_calendar(from, to)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?,
+    };
+
+    let mut out = Vec::with_capacity(satker_ids.len());
+    for satker_id in satker_ids {
+        let (generated, error) = match generate_calendar_days(db, satker_id, from, to).await {
+            Ok(n) => (n, None),
+            Err(e) => (0, Some(e.message)),
+        };
+        out.push(CalendarRegenerateDto {
+            satker_id,
+            from,
+            to,
+            generated,
+            error,
+        });
+    }
+    Ok(out)
+}
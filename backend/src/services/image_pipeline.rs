@@ -0,0 +1,206 @@
+//! Pengolahan foto upload: decode sungguhan (bukan percaya content-type), luruskan orientasi
+//! EXIF, buang semua metadata (GPS, device, dll) dengan encode ulang ke JPEG, buat varian
+//! ukuran kecil, dan hitung perceptual hash. Waktu ambil foto dari EXIF disimpan terpisah.
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use exif::{In, Tag, Value};
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits, RgbImage};
+use serde::Deserialize;
+use std::io::Cursor;
+
+use crate::error::HttpError;
+
+const JPEG_QUALITY: u8 = 85;
+/// Foto kamera HP tidak lebih dari ini; gambar lebih besar ditolak sebelum dialokasi.
+const MAX_IMAGE_SIDE: u32 = 8000;
+const MAX_DECODE_ALLOC: u64 = 256 * 1024 * 1024;
+
+/// Varian ukuran foto; disimpan di samping file asli dengan akhiran `_<nama>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageVariant {
+    /// Sisi terpanjang 160px (daftar / tabel rekap).
+    Thumb,
+    /// Sisi terpanjang 640px (preview dialog).
+    Medium,
+}
+
+impl ImageVariant {
+    pub fn name(self) -> &'static str {
+        match self {
+            ImageVariant::Thumb => "thumb",
+            ImageVariant::Medium => "medium",
+        }
+    }
+
+    fn max_side(self) -> u32 {
+        match self {
+            ImageVariant::Thumb => 160,
+            ImageVariant::Medium => 640,
+        }
+    }
+}
+
+pub const SELFIE_VARIANTS: [ImageVariant; 2] = [ImageVariant::Thumb, ImageVariant::Medium];
+
+/// Key / path varian: `.../x.jpg` -> `.../x_thumb.jpg`.
+pub fn variant_path(path: &str, variant: ImageVariant) -> String {
+    let file_start = path.rfind('/').map(|i| i + 1).unwrap_or(0);
+    match path[file_start..].rfind('.') {
+        Some(dot) => {
+            let (stem, ext) = path.split_at(file_start + dot);
+            format!("{}_{}{}", stem, variant.name(), ext)
+        }
+        None => format!("{}_{}", path, variant.name()),
+    }
+}
+
+pub struct ProcessedImage {
+    /// JPEG tanpa metadata, orientasi sudah diluruskan.
+    pub jpeg: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    /// dHash 64-bit (hex).
+    pub phash: String,
+    pub variants: Vec<(ImageVariant, Vec<u8>)>,
+    /// EXIF DateTimeOriginal (jam lokal kamera) + OffsetTimeOriginal kalau ada.
+    captured_local: Option<NaiveDateTime>,
+    captured_offset: Option<FixedOffset>,
+}
+
+impl ProcessedImage {
+    /// Waktu ambil foto; tanpa offset di EXIF, jam kamera dianggap zona waktu aplikasi.
+    pub fn captured_at(&self, tz: Tz) -> Option<DateTime<Utc>> {
+        let local = self.captured_local?;
+        match self.captured_offset {
+            Some(offset) => offset
+                .from_local_datetime(&local)
+                .single()
+                .map(|dt| dt.with_timezone(&Utc)),
+            None => tz
+                .from_local_datetime(&local)
+                .earliest()
+                .map(|dt| dt.with_timezone(&Utc)),
+        }
+    }
+}
+
+/// Decode + normalisasi foto upload (CPU-bound, panggil lewat `spawn_blocking`).
+pub fn process_image(bytes: &[u8], variants: &[ImageVariant]) -> Result<ProcessedImage, HttpError> {
+    let invalid = || HttpError::bad_request("file bukan gambar jpg/png yang valid");
+
+    let reader = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|_| invalid())?;
+    if !matches!(reader.format(), Some(ImageFormat::Jpeg | ImageFormat::Png)) {
+        return Err(invalid());
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_SIDE);
+    limits.max_image_height = Some(MAX_IMAGE_SIDE);
+    limits.max_alloc = Some(MAX_DECODE_ALLOC);
+    let mut reader = reader;
+    reader.limits(limits);
+
+    let mut decoder = reader.into_decoder().map_err(|_| invalid())?;
+    let exif = decoder.exif_metadata().ok().flatten();
+    let orientation = decoder.orientation().map_err(|_| invalid())?;
+    let mut img = DynamicImage::from_decoder(decoder).map_err(|_| invalid())?;
+    img.apply_orientation(orientation);
+
+    let (captured_local, captured_offset) = exif.map(exif_capture_time).unwrap_or_default();
+
+    let rgb = flatten_to_rgb(&img);
+    let jpeg = encode_jpeg(&rgb)?;
+    let phash = dhash(&img);
+
+    let mut out_variants = Vec::with_capacity(variants.len());
+    for variant in variants {
+        let max = variant.max_side();
+        let bytes = if rgb.width() <= max && rgb.height() <= max {
+            jpeg.clone()
+        } else {
+            let small = DynamicImage::ImageRgb8(rgb.clone()).resize(max, max, FilterType::Triangle);
+            encode_jpeg(&small.to_rgb8())?
+        };
+        out_variants.push((*variant, bytes));
+    }
+
+    Ok(ProcessedImage {
+        width: rgb.width(),
+        height: rgb.height(),
+        jpeg,
+        phash,
+        variants: out_variants,
+        captured_local,
+        captured_offset,
+    })
+}
+
+/// Piksel transparan (PNG) ditimpa ke latar putih, bukan hitam.
+fn flatten_to_rgb(img: &DynamicImage) -> RgbImage {
+    if !img.color().has_alpha() {
+        return img.to_rgb8();
+    }
+    let rgba = img.to_rgba8();
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |c: u8| ((c as u16 * a as u16 + 255 * (255 - a as u16)) / 255) as u8;
+        image::Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+fn encode_jpeg(rgb: &RgbImage) -> Result<Vec<u8>, HttpError> {
+    let mut out = Vec::new();
+    rgb.write_with_encoder(JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY))
+        .map_err(|e| HttpError::server_error(format!("gagal encode jpeg: {}", e)))?;
+    Ok(out)
+}
+
+fn exif_capture_time(raw: Vec<u8>) -> (Option<NaiveDateTime>, Option<FixedOffset>) {
+    let Ok(exif) = exif::Reader::new().read_raw(raw) else {
+        return (None, None);
+    };
+    let ascii = |tag: Tag| -> Option<Vec<u8>> {
+        match &exif.get_field(tag, In::PRIMARY)?.value {
+            Value::Ascii(parts) => parts.first().cloned(),
+            _ => None,
+        }
+    };
+
+    let Some(mut dt) = ascii(Tag::DateTimeOriginal)
+        .or_else(|| ascii(Tag::DateTime))
+        .and_then(|v| exif::DateTime::from_ascii(&v).ok())
+    else {
+        return (None, None);
+    };
+    if let Some(offset) = ascii(Tag::OffsetTimeOriginal).or_else(|| ascii(Tag::OffsetTime)) {
+        let _ = dt.parse_offset(&offset);
+    }
+
+    let local = NaiveDate::from_ymd_opt(dt.year as i32, dt.month as u32, dt.day as u32)
+        .and_then(|d| d.and_hms_opt(dt.hour as u32, dt.minute as u32, dt.second as u32));
+    let offset = dt
+        .offset
+        .and_then(|minutes| FixedOffset::east_opt(minutes as i32 * 60));
+    (local, offset)
+}
+
+/// dHash 64-bit (hex): 9x8 grayscale, tiap bit = piksel lebih gelap dari tetangga kanannya.
+pub fn dhash(img: &DynamicImage) -> String {
+    let small = image::imageops::resize(&img.to_luma8(), 9, 8, FilterType::Triangle);
+    let mut bits: u64 = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            bits <<= 1;
+            if small.get_pixel(x, y)[0] < small.get_pixel(x + 1, y)[0] {
+                bits |= 1;
+            }
+        }
+    }
+    format!("{:016x}", bits)
+}
@@ -0,0 +1,95 @@
+//! Bukti lokasi alternatif: BSSID Wi-Fi / beacon BLE terdaftar per geofence. Dipakai di gedung
+//! dengan GPS buruk; aturan `proximity_substitutes_gps` menentukan apakah kecocokan penanda
+//! menggantikan GPS atau hanya melengkapinya.
+
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::database::geofence_marker::GeofenceMarkerRepo;
+use crate::db::DBClient;
+use crate::dtos::attendance::{BleScan, WifiScan};
+use crate::dtos::geofence_marker::{MARKER_BLE, MARKER_WIFI, ProximityMarkerMatch};
+use crate::dtos::settings::AttendanceRules;
+use crate::error::HttpError;
+
+/// "AA-BB-CC-DD-EE-FF" / "aa:bb:cc:dd:ee:ff" -> "aa:bb:cc:dd:ee:ff".
+pub fn normalize_bssid(raw: &str) -> Option<String> {
+    let v = raw.trim().to_lowercase().replace('-', ":");
+    let octets: Vec<&str> = v.split(':').collect();
+    let valid = octets.len() == 6
+        && octets
+            .iter()
+            .all(|o| o.len() == 2 && o.chars().all(|c| c.is_ascii_hexdigit()));
+    valid.then_some(v)
+}
+
+/// Identifier beacon dibandingkan apa adanya (huruf kecil), mis. "uuid:major:minor".
+pub fn normalize_beacon_id(raw: &str) -> Option<String> {
+    let v = raw.trim().to_lowercase();
+    let valid = !v.is_empty()
+        && v.len() <= 100
+        && v.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == ':' || c == '-');
+    valid.then_some(v)
+}
+
+/// Normalisasi identifier penanda saat didaftarkan admin.
+pub fn normalize_marker_identifier(kind: &str, raw: &str) -> Result<String, HttpError> {
+    match kind {
+        MARKER_WIFI => normalize_bssid(raw)
+            .ok_or_else(|| HttpError::bad_request("BSSID tidak valid (format aa:bb:cc:dd:ee:ff)")),
+        MARKER_BLE => normalize_beacon_id(raw)
+            .ok_or_else(|| HttpError::bad_request("identifier beacon tidak valid")),
+        _ => Err(HttpError::bad_request("kind harus WIFI atau BLE")),
+    }
+}
+
+/// Penanda satker yang cocok dengan hasil scan; kalau lebih dari satu, sinyal terkuat.
+/// Scan dengan RSSI di bawah `proximity_min_rssi` diabaikan.
+pub async fn match_proximity_marker(
+    db: &DBClient,
+    rules: &AttendanceRules,
+    satker_id: Uuid,
+    wifi_scans: Option<&[WifiScan]>,
+    ble_scans: Option<&[BleScan]>,
+) -> Result<Option<ProximityMarkerMatch>, HttpError> {
+    let strong_enough = |rssi: Option<i32>| rssi.is_none_or(|r| r >= rules.proximity_min_rssi);
+
+    let mut rssi_by_id: HashMap<(String, String), i32> = HashMap::new();
+    for scan in wifi_scans.unwrap_or_default() {
+        if strong_enough(scan.rssi)
+            && let Some(id) = normalize_bssid(&scan.bssid)
+        {
+            rssi_by_id.insert((MARKER_WIFI.to_string(), id), scan.rssi.unwrap_or(i32::MIN));
+        }
+    }
+    for scan in ble_scans.unwrap_or_default() {
+        if strong_enough(scan.rssi)
+            && let Some(id) = normalize_beacon_id(&scan.beacon_id)
+        {
+            rssi_by_id.insert((MARKER_BLE.to_string(), id), scan.rssi.unwrap_or(i32::MIN));
+        }
+    }
+    if rssi_by_id.is_empty() {
+        return Ok(None);
+    }
+
+    let ids_of = |kind: &str| -> Vec<String> {
+        rssi_by_id
+            .keys()
+            .filter(|(k, _)| *k == kind)
+            .map(|(_, id)| id.clone())
+            .collect()
+    };
+    let matches = db
+        .find_matching_proximity_markers(satker_id, &ids_of(MARKER_WIFI), &ids_of(MARKER_BLE))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(matches.into_iter().max_by_key(|m| {
+        rssi_by_id
+            .get(&(m.kind.clone(), m.identifier.clone()))
+            .copied()
+            .unwrap_or(i32::MIN)
+    }))
+}
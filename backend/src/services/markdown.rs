@@ -0,0 +1,304 @@
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use std::ops::Range;
+
+/// Skema link yang boleh dipertahankan di body Markdown.
+const SAFE_LINK_SCHEMES: [&str; 4] = ["http:", "https:", "mailto:", "tel:"];
+
+/// Batas pengulangan sanitasi sampai hasilnya stabil.
+const MAX_SANITIZE_PASSES: usize = 8;
+
+fn parser_options() -> Options {
+    let mut opts = Options::empty();
+    opts.insert(Options::ENABLE_STRIKETHROUGH);
+    opts.insert(Options::ENABLE_TABLES);
+    opts
+}
+
+fn is_safe_url(url: &str) -> bool {
+    let lower = url.trim().to_ascii_lowercase();
+    match lower.find(':') {
+        // relatif / anchor (tidak punya skema) -> aman
+        None => true,
+        Some(idx) => {
+            // ':' setelah '/', '?' atau '#' bukan skema (mis. "/a?b=c:d")
+            if lower[..idx].contains(['/', '?', '#']) {
+                return true;
+            }
+            SAFE_LINK_SCHEMES.iter().any(|s| lower.starts_with(s))
+        }
+    }
+}
+
+/// Escape karakter Markdown/HTML supaya teks pengganti link tidak bisa
+/// membentuk tag HTML, entity, link, atau code span baru.
+fn escape_markdown_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '<' | '>' | '&' | '[' | ']' | '!') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Sanitasi body Markdown sebelum disimpan:
+/// - raw HTML (block/inline) dibuang,
+/// - link/gambar dengan skema berbahaya (javascript:, data:, dst.) diganti teksnya saja (di-escape),
+/// - reference definition dengan skema berbahaya dibuang.
+///
+/// Diulang sampai hasilnya tidak berubah lagi (mis. definisi ganda dengan label yang sama).
+/// Hasilnya tetap Markdown (bukan HTML) supaya web & Android bisa render sendiri.
+pub fn sanitize_markdown(src: &str) -> String {
+    let mut current = sanitize_markdown_pass(src);
+    for _ in 1..MAX_SANITIZE_PASSES {
+        let next = sanitize_markdown_pass(&current);
+        if next == current {
+            break;
+        }
+        current = next;
+    }
+    current
+}
+
+fn sanitize_markdown_pass(src: &str) -> String {
+    let mut replacements: Vec<(Range<usize>, String)> = Vec::new();
+    // link/gambar tidak aman yang sedang dibuka: (range, teks yang dikumpulkan)
+    let mut unsafe_link: Option<(Range<usize>, String, usize)> = None;
+
+    let parser = Parser::new_ext(src, parser_options());
+    for (_, def) in parser.reference_definitions().iter() {
+        if !is_safe_url(&def.dest) {
+            replacements.push((def.span.clone(), String::new()));
+        }
+    }
+
+    for (event, range) in parser.into_offset_iter() {
+        if let Some((_, text, depth)) = unsafe_link.as_mut() {
+            match &event {
+                Event::Start(Tag::Link { .. }) | Event::Start(Tag::Image { .. }) => *depth += 1,
+                Event::End(TagEnd::Link) | Event::End(TagEnd::Image) => {
+                    if *depth == 0 {
+                        if let Some((r, t, _)) = unsafe_link.take() {
+                            replacements.push((r, escape_markdown_text(&t)));
+                        }
+                        continue;
+                    }
+                    *depth -= 1;
+                }
+                Event::Text(t) | Event::Code(t) => text.push_str(t),
+                Event::SoftBreak | Event::HardBreak => text.push(' '),
+                _ => {}
+            }
+            continue;
+        }
+
+        match event {
+            Event::Html(_) | Event::InlineHtml(_) => replacements.push((range, String::new())),
+            Event::Start(Tag::Link { dest_url, .. })
+            | Event::Start(Tag::Image { dest_url, .. })
+                if !is_safe_url(&dest_url) =>
+            {
+                unsafe_link = Some((range, String::new(), 0));
+            }
+            _ => {}
+        }
+    }
+
+    replacements.sort_by_key(|(r, _)| r.start);
+
+    let mut out = String::with_capacity(src.len());
+    let mut cursor = 0usize;
+    for (range, replacement) in replacements {
+        if range.start < cursor {
+            // overlap (mis. html di dalam range yang sudah diganti)
+            continue;
+        }
+        out.push_str(&src[cursor..range.start]);
+        out.push_str(&replacement);
+        cursor = range.end;
+    }
+    out.push_str(&src[cursor..]);
+
+    out.trim().to_string()
+}
+
+/// Render Markdown menjadi plain-text (fallback untuk klien yang tidak render Markdown).
+pub fn markdown_to_plain_text(src: &str) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut list_stack: Vec<Option<u64>> = Vec::new();
+    let mut link_stack: Vec<String> = Vec::new();
+
+    for event in Parser::new_ext(src, parser_options()) {
+        match event {
+            Event::Text(t) | Event::Code(t) => out.push_str(&t),
+            Event::SoftBreak => out.push(' '),
+            Event::HardBreak => out.push('\n'),
+            Event::Rule => out.push_str("\n----------\n"),
+            Event::TaskListMarker(done) => out.push_str(if done { "[x] " } else { "[ ] " }),
+            Event::Start(Tag::List(first)) => list_stack.push(first),
+            Event::End(TagEnd::List(_)) => {
+                list_stack.pop();
+                out.push_str(if list_stack.is_empty() { "\n\n" } else { "\n" });
+            }
+            Event::Start(Tag::Item) => {
+                if !out.is_empty() && !out.ends_with('\n') {
+                    out.push('\n');
+                }
+                let indent = "  ".repeat(list_stack.len().saturating_sub(1));
+                out.push_str(&indent);
+                match list_stack.last_mut() {
+                    Some(Some(n)) => {
+                        out.push_str(&format!("{}. ", n));
+                        *n += 1;
+                    }
+                    _ => out.push_str("- "),
+                }
+            }
+            Event::Start(Tag::Link { dest_url, .. }) => link_stack.push(dest_url.to_string()),
+            Event::End(TagEnd::Link) => {
+                if let Some(url) = link_stack.pop()
+                    && !url.is_empty()
+                    && !out.ends_with(url.as_str())
+                {
+                    out.push_str(&format!(" ({})", url));
+                }
+            }
+            Event::End(TagEnd::TableCell) => out.push('\t'),
+            Event::End(TagEnd::TableRow) | Event::End(TagEnd::TableHead) => out.push('\n'),
+            Event::End(TagEnd::Paragraph)
+            | Event::End(TagEnd::Heading(_))
+            | Event::End(TagEnd::CodeBlock)
+            | Event::End(TagEnd::BlockQuote(_)) => out.push_str("\n\n"),
+            _ => {}
+        }
+    }
+
+    // rapikan: maksimal satu baris kosong berturut-turut, buang spasi di akhir baris
+    let mut cleaned = String::with_capacity(out.len());
+    let mut blank_run = 0;
+    for line in out.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_run += 1;
+            if blank_run > 1 {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        cleaned.push_str(line);
+        cleaned.push('\n');
+    }
+
+    cleaned.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse ulang hasil sanitasi: tidak boleh ada HTML mentah atau link/gambar tidak aman.
+    fn assert_clean(md: &str) {
+        let parser = Parser::new_ext(md, parser_options());
+        for (label, def) in parser.reference_definitions().iter() {
+            assert!(is_safe_url(&def.dest), "refdef {label} lolos: {md}");
+        }
+        for event in parser {
+            match event {
+                Event::Html(h) | Event::InlineHtml(h) => panic!("html lolos {h:?}: {md}"),
+                Event::Start(Tag::Link { dest_url, .. })
+                | Event::Start(Tag::Image { dest_url, .. }) => {
+                    assert!(is_safe_url(&dest_url), "link {dest_url} lolos: {md}")
+                }
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn unsafe_schemes_keep_text_only() {
+        assert_eq!(sanitize_markdown("[klik](javascript:alert(1))"), "klik");
+        assert_eq!(sanitize_markdown("[klik](JavaScript:alert(1))"), "klik");
+        assert_eq!(
+            sanitize_markdown("[x](data:text/html;base64,PHNjcmlwdD4=)"),
+            "x"
+        );
+        assert_eq!(
+            sanitize_markdown("![foto](data:image/png;base64,AAAA)"),
+            "foto"
+        );
+        assert_eq!(sanitize_markdown("![](vbscript:x)"), "");
+    }
+
+    #[test]
+    fn safe_relative_and_anchor_links_are_kept() {
+        for md in [
+            "[situs](https://polri.go.id)",
+            "[surel](mailto:humas@polri.go.id)",
+            "[relatif](/pengumuman/1)",
+            "[anchor](#lampiran)",
+            "[query](?a=b:c)",
+            "![logo](https://polri.go.id/logo.png)",
+        ] {
+            assert_eq!(sanitize_markdown(md), md);
+        }
+    }
+
+    #[test]
+    fn nested_links_and_images() {
+        // gambar tidak aman di dalam link aman: gambar diganti alt-nya, link tetap
+        let out = sanitize_markdown("[![alt](javascript:x)](https://a.id)");
+        assert_eq!(out, "[alt](https://a.id)");
+        assert_clean(&out);
+
+        // gambar aman di dalam link tidak aman: seluruhnya jadi teks
+        let out = sanitize_markdown("[![alt](https://a.id/x.png) teks](javascript:x)");
+        assert_eq!(out, "alt teks");
+        assert_clean(&out);
+    }
+
+    #[test]
+    fn entity_escaped_html_in_link_text_stays_text() {
+        let out = sanitize_markdown("[&lt;img src=x onerror=alert(1)&gt;](javascript:x)");
+        assert_eq!(out, r"\<img src=x onerror=alert(1)\>");
+        assert_clean(&out);
+
+        let out = sanitize_markdown(r"[\<script\>alert(1)\</script\>](javascript:x)");
+        assert_eq!(out, r"\<script\>alert(1)\</script\>");
+        assert_clean(&out);
+
+        // teks hasil escape tidak boleh membentuk link / code span baru
+        let out = sanitize_markdown(r"[\[a\]\(javascript:x\) `kode`](javascript:y)");
+        assert_clean(&out);
+        assert_eq!(markdown_to_plain_text(&out), "[a](javascript:x) kode");
+    }
+
+    #[test]
+    fn raw_html_is_dropped() {
+        let out = sanitize_markdown("halo <script>alert(1)</script> dunia\n\n<div>blok</div>");
+        assert_clean(&out);
+        assert!(!out.contains('<'));
+    }
+
+    #[test]
+    fn unsafe_reference_definitions_are_removed() {
+        let out = sanitize_markdown("[klik][r]\n\n[r]: javascript:alert(1)");
+        assert_eq!(out, "klik");
+        assert!(!out.contains("javascript"));
+
+        // definisi ganda dengan label sama: yang kedua ikut dibuang
+        let out = sanitize_markdown("[r]: javascript:a\n[r]: javascript:b\n\nteks");
+        assert_eq!(out, "teks");
+
+        let md = "[situs][r]\n\n[r]: https://polri.go.id";
+        assert_eq!(sanitize_markdown(md), md);
+    }
+
+    #[test]
+    fn sanitize_is_idempotent() {
+        let src = "# Judul\n\n[&lt;b&gt;](javascript:x) dan [ok](https://a.id)\n\n<i>x</i>";
+        let once = sanitize_markdown(src);
+        assert_eq!(sanitize_markdown(&once), once);
+    }
+}
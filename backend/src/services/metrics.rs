@@ -0,0 +1,253 @@
+//! Prometheus metrics (registry global, di-expose lewat GET /metrics).
+
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use std::sync::LazyLock;
+
+use crate::constants::AttendanceEventType;
+use crate::error::HttpError;
+
+/// Alasan penolakan check-in/check-out (label `reason`).
+#[derive(Debug, Clone, Copy)]
+pub enum RejectReason {
+    MockLocation,
+    Accuracy,
+    Teleport,
+    OutsideGeofence,
+    TooEarly,
+    TooLate,
+    DeviceNotOwned,
+    FaceMismatch,
+}
+
+impl RejectReason {
+    fn as_label(self) -> &'static str {
+        match self {
+            RejectReason::MockLocation => "mock_location",
+            RejectReason::Accuracy => "accuracy",
+            RejectReason::Teleport => "teleport",
+            RejectReason::OutsideGeofence => "outside_geofence",
+            RejectReason::TooEarly => "too_early",
+            RejectReason::TooLate => "too_late",
+            RejectReason::DeviceNotOwned => "device_not_owned",
+            RejectReason::FaceMismatch => "face_mismatch",
+        }
+    }
+}
+
+/// Alasan challenge gagal dipakai (label `reason`).
+#[derive(Debug, Clone, Copy)]
+pub enum ChallengeFailure {
+    /// tidak ada di redis: sudah dipakai (replay) atau TTL habis
+    NotFoundOrUsed,
+    UserMismatch,
+    DeviceMismatch,
+    Expired,
+    /// attestation tidak ada (padahal wajib) / signature tidak valid
+    Attestation,
+}
+
+impl ChallengeFailure {
+    fn as_label(self) -> &'static str {
+        match self {
+            ChallengeFailure::NotFoundOrUsed => "not_found_or_used",
+            ChallengeFailure::UserMismatch => "user_mismatch",
+            ChallengeFailure::DeviceMismatch => "device_mismatch",
+            ChallengeFailure::Expired => "expired",
+            ChallengeFailure::Attestation => "attestation",
+        }
+    }
+}
+
+struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    db_pool_size: IntGauge,
+    db_pool_idle: IntGauge,
+    db_pool_max: IntGauge,
+    redis_errors: IntCounterVec,
+    attendance_requests: IntCounterVec,
+    attendance_rejections: IntCounterVec,
+    attendance_flags: IntCounterVec,
+    challenge_requests: IntCounterVec,
+    challenge_failures: IntCounterVec,
+}
+
+fn counter_vec(registry: &Registry, name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+    let c = IntCounterVec::new(Opts::new(name, help), labels).expect("valid counter opts");
+    registry
+        .register(Box::new(c.clone()))
+        .expect("metric registered once");
+    c
+}
+
+fn gauge(registry: &Registry, name: &str, help: &str) -> IntGauge {
+    let g = IntGauge::new(name, help).expect("valid gauge opts");
+    registry
+        .register(Box::new(g.clone()))
+        .expect("metric registered once");
+    g
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(|| {
+    let registry = Registry::new();
+
+    let http_duration = HistogramVec::new(
+        HistogramOpts::new(
+            "http_request_duration_seconds",
+            "HTTP request latency per route and status",
+        ),
+        &["method", "route", "status"],
+    )
+    .expect("valid histogram opts");
+    registry
+        .register(Box::new(http_duration.clone()))
+        .expect("metric registered once");
+
+    Metrics {
+        http_requests: counter_vec(
+            &registry,
+            "http_requests_total",
+            "HTTP requests per route and status",
+            &["method", "route", "status"],
+        ),
+        http_duration,
+        db_pool_size: gauge(
+            &registry,
+            "db_pool_connections",
+            "Open Postgres connections in the pool",
+        ),
+        db_pool_idle: gauge(
+            &registry,
+            "db_pool_idle_connections",
+            "Idle Postgres connections in the pool",
+        ),
+        db_pool_max: gauge(
+            &registry,
+            "db_pool_max_connections",
+            "Configured maximum Postgres pool size",
+        ),
+        redis_errors: counter_vec(
+            &registry,
+            "redis_errors_total",
+            "Failed Redis operations",
+            &["op"],
+        ),
+        attendance_requests: counter_vec(
+            &registry,
+            "attendance_requests_total",
+            "Check-in/check-out requests by result (success, rejected, error)",
+            &["event", "result"],
+        ),
+        attendance_rejections: counter_vec(
+            &registry,
+            "attendance_rejections_total",
+            "Check-in/check-out rejections by reason",
+            &["event", "reason"],
+        ),
+        attendance_flags: counter_vec(
+            &registry,
+            "attendance_flags_total",
+            "Accepted check-in/check-out flagged for review, by reason",
+            &["event", "reason"],
+        ),
+        challenge_requests: counter_vec(
+            &registry,
+            "attendance_challenge_requests_total",
+            "Challenge issuance requests by result",
+            &["result"],
+        ),
+        challenge_failures: counter_vec(
+            &registry,
+            "attendance_challenge_failures_total",
+            "Challenge validation failures by reason",
+            &["reason"],
+        ),
+        registry,
+    }
+});
+
+fn event_label(event: AttendanceEventType) -> &'static str {
+    match event {
+        AttendanceEventType::CheckIn => "check_in",
+        AttendanceEventType::CheckOut => "check_out",
+        AttendanceEventType::BreakStart => "break_start",
+        AttendanceEventType::BreakEnd => "break_end",
+        AttendanceEventType::FieldOut => "field_out",
+        AttendanceEventType::FieldIn => "field_in",
+    }
+}
+
+pub fn record_http_request(method: &str, route: &str, status: u16, elapsed_secs: f64) {
+    let status = status.to_string();
+    let labels = [method, route, status.as_str()];
+    METRICS.http_requests.with_label_values(&labels).inc();
+    METRICS
+        .http_duration
+        .with_label_values(&labels)
+        .observe(elapsed_secs);
+}
+
+pub fn record_redis_error(op: &str) {
+    METRICS.redis_errors.with_label_values(&[op]).inc();
+}
+
+pub fn record_attendance_rejection(event: AttendanceEventType, reason: RejectReason) {
+    METRICS
+        .attendance_rejections
+        .with_label_values(&[event_label(event), reason.as_label()])
+        .inc();
+}
+
+/// `reason` = kode anomali (LOW_FACE_SCORE, SHARED_DEVICE, ...) dalam huruf kecil.
+pub fn record_attendance_flag(event: AttendanceEventType, reason: &str) {
+    METRICS
+        .attendance_flags
+        .with_label_values(&[event_label(event), reason.to_ascii_lowercase().as_str()])
+        .inc();
+}
+
+/// success = 2xx, rejected = 4xx (ditolak validasi/aturan), error = 5xx.
+pub fn record_attendance_result<T>(event: AttendanceEventType, result: &Result<T, HttpError>) {
+    let label = match result {
+        Ok(_) => "success",
+        Err(e) if e.status.is_client_error() => "rejected",
+        Err(_) => "error",
+    };
+    METRICS
+        .attendance_requests
+        .with_label_values(&[event_label(event), label])
+        .inc();
+}
+
+/// result: "issued", "rate_limited_user", "rate_limited_device".
+pub fn record_challenge_request(result: &str) {
+    METRICS
+        .challenge_requests
+        .with_label_values(&[result])
+        .inc();
+}
+
+pub fn record_challenge_failure(reason: ChallengeFailure) {
+    METRICS
+        .challenge_failures
+        .with_label_values(&[reason.as_label()])
+        .inc();
+}
+
+pub fn set_db_pool_state(size: u32, idle: usize, max: u32) {
+    METRICS.db_pool_size.set(i64::from(size));
+    METRICS.db_pool_idle.set(idle as i64);
+    METRICS.db_pool_max.set(i64::from(max));
+}
+
+/// Render semua metric ke Prometheus text format.
+pub fn render() -> Result<String, HttpError> {
+    let mut buf = Vec::new();
+    TextEncoder::new()
+        .encode(&METRICS.registry.gather(), &mut buf)
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    String::from_utf8(buf).map_err(|e| HttpError::server_error(e.to_string()))
+}
@@ -0,0 +1,159 @@
+//! Migration schema: status, apply, baseline, dan pengecekan saat boot.
+
+use std::collections::HashMap;
+
+use crate::database::migration::MigrationRepo;
+use crate::db::{DBClient, MIGRATOR, expected_migration_version};
+use crate::dtos::migration::{BaselineResult, MigrationInfo, MigrationReport};
+
+const BASELINE_HINT: &str = "schema sudah ada tapi belum tercatat di _sqlx_migrations; \
+     jalankan `backend migrate baseline --version <versi terakhir yang sudah diterapkan>`";
+
+/// Bandingkan migration yang di-embed dengan isi `_sqlx_migrations` (tanpa mengubah apa pun).
+pub async fn migration_report(db: &DBClient) -> Result<MigrationReport, sqlx::Error> {
+    let tracked = db.has_migration_table().await?;
+    let applied = if tracked {
+        db.list_applied_migrations().await?
+    } else {
+        Vec::new()
+    };
+    let untracked_schema = !tracked && db.has_app_schema().await?;
+
+    let applied_by_version: HashMap<i64, _> = applied.iter().map(|m| (m.version, m)).collect();
+
+    let mut pending = Vec::new();
+    let mut modified = Vec::new();
+    for m in MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+    {
+        match applied_by_version.get(&m.version) {
+            None => pending.push(MigrationInfo {
+                version: m.version,
+                description: m.description.to_string(),
+            }),
+            Some(row) if row.success && row.checksum != m.checksum.as_ref() => {
+                modified.push(m.version)
+            }
+            Some(_) => {}
+        }
+    }
+
+    let failed: Vec<i64> = applied
+        .iter()
+        .filter(|m| !m.success)
+        .map(|m| m.version)
+        .collect();
+    let unknown: Vec<i64> = applied
+        .iter()
+        .filter(|m| !MIGRATOR.version_exists(m.version))
+        .map(|m| m.version)
+        .collect();
+    let applied_version = applied
+        .iter()
+        .filter(|m| m.success)
+        .map(|m| m.version)
+        .max();
+
+    let up_to_date =
+        !untracked_schema && pending.is_empty() && modified.is_empty() && failed.is_empty();
+
+    Ok(MigrationReport {
+        expected_version: expected_migration_version(),
+        applied_version,
+        tracked,
+        untracked_schema,
+        pending,
+        modified,
+        failed,
+        unknown,
+        up_to_date,
+    })
+}
+
+/// Terapkan semua migration yang belum jalan.
+pub async fn run_migrations(db: &DBClient) -> Result<MigrationReport, String> {
+    let report = migration_report(db).await.map_err(|e| e.to_string())?;
+    if report.untracked_schema {
+        return Err(BASELINE_HINT.to_string());
+    }
+
+    MIGRATOR
+        .run(&db.pool)
+        .await
+        .map_err(|e| format!("migration gagal: {e}"))?;
+
+    migration_report(db).await.map_err(|e| e.to_string())
+}
+
+/// Tandai migration s.d. `version` sebagai sudah diterapkan, tanpa menjalankan SQL-nya.
+/// Dipakai sekali untuk database lama yang migration-nya dulu diterapkan manual.
+pub async fn baseline_migrations(db: &DBClient, version: i64) -> Result<BaselineResult, String> {
+    if !MIGRATOR.version_exists(version) {
+        return Err(format!("versi migration {version} tidak dikenal"));
+    }
+
+    db.ensure_migration_table()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut recorded = Vec::new();
+    let mut already_recorded = Vec::new();
+    for m in MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration() && m.version <= version)
+    {
+        let inserted = db
+            .insert_baseline_migration(m.version, &m.description, &m.checksum)
+            .await
+            .map_err(|e| e.to_string())?;
+        if inserted {
+            recorded.push(m.version);
+        } else {
+            already_recorded.push(m.version);
+        }
+    }
+
+    Ok(BaselineResult {
+        version,
+        recorded,
+        already_recorded,
+    })
+}
+
+/// Dipanggil sebelum server jalan: terapkan migration (kalau `auto_migrate`),
+/// lalu tolak start kalau schema masih tertinggal dari yang dibutuhkan binary.
+pub async fn ensure_schema_up_to_date(db: &DBClient, auto_migrate: bool) -> Result<(), String> {
+    let report = if auto_migrate {
+        run_migrations(db).await?
+    } else {
+        migration_report(db).await.map_err(|e| e.to_string())?
+    };
+
+    if report.up_to_date {
+        return Ok(());
+    }
+
+    if report.untracked_schema {
+        return Err(BASELINE_HINT.to_string());
+    }
+    if !report.failed.is_empty() {
+        return Err(format!(
+            "migration gagal/dirty: {:?}, perbaiki manual lalu hapus barisnya dari _sqlx_migrations",
+            report.failed
+        ));
+    }
+    if !report.modified.is_empty() {
+        return Err(format!(
+            "file migration {:?} berubah setelah diterapkan (checksum beda)",
+            report.modified
+        ));
+    }
+
+    let pending: Vec<i64> = report.pending.iter().map(|m| m.version).collect();
+    Err(format!(
+        "schema database tertinggal (applied {:?}, expected {:?}, pending {:?}); \
+         jalankan `backend migrate run` atau set AUTO_MIGRATE=true",
+        report.applied_version, report.expected_version, pending
+    ))
+}
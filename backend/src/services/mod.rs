@@ -1,8 +1,33 @@
+pub mod announcement;
+pub mod attendance_anomaly;
+pub mod attendance_rekap;
+pub mod attendance_rules;
+pub mod attestation;
+pub mod attestation_verifier;
 pub mod authorization;
 pub mod calendar;
+pub mod calendar_feed;
+pub mod calendar_regen;
 pub mod catalog;
+pub mod duty_rotation;
+pub mod duty_schedule_swap;
+pub mod face_verification;
+pub mod face_verifier;
 pub mod geofence;
 pub mod holiday;
+pub mod image_pipeline;
 pub mod leave_request;
+pub mod location_proof;
+pub mod markdown;
+pub mod metrics;
+pub mod migration;
+pub mod overtime;
+pub mod rekap_export;
+pub mod retention;
+pub mod storage;
+pub mod storage_migration;
+pub mod tukin;
 pub mod upload;
 pub mod user;
+pub mod user_device;
+pub mod work_pattern;
@@ -0,0 +1,811 @@
+//! Lembur (overtime).
+//!
+//! Kandidat lembur dihitung dari segmen attendance session + satker_calendar_days:
+//! - hari kerja / setengah hari: menit kerja setelah jam pulang (atau akhir jadwal dinas)
+//! - hari libur rutin (WEEKEND) / libur nasional-satker (HOLIDAY): seluruh menit kerja
+//!
+//! Alur klaim: SUBMITTED -> APPROVED / REJECTED (kepala satker), SUBMITTED -> CANCELED (pemohon).
+
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::auth::rbac::UserRole;
+use crate::constants::{CalendarDayType, HolidayKind};
+use crate::database::attendance::AttendanceEventRepo;
+use crate::database::duty_schedule::DutyScheduleRepo;
+use crate::database::holiday::HolidayRepo;
+use crate::database::overtime::{
+    OvertimeRepo, lock_overtime_claim, set_overtime_approved_minutes, update_overtime_claim_status,
+};
+use crate::database::satker_head::SatkerHeadRepo;
+use crate::database::work_calendar::WorkCalendarRepo;
+use crate::database::work_pattern_override::WorkPatternOverrideRepo;
+use crate::db::DBClient;
+use crate::dtos::attendance_session::AttendanceSegmentDto;
+use crate::dtos::overtime::{
+    ApproveOvertimeClaimReq, CreateOvertimeClaimReq, OVERTIME_APPROVED, OVERTIME_CANCELED,
+    OVERTIME_DAY_KINDS, OVERTIME_HOLIDAY, OVERTIME_REJECTED, OVERTIME_SUBMITTED, OVERTIME_WEEKDAY,
+    OVERTIME_WEEKEND, OvertimeCandidateDto, OvertimeClaimDto, OvertimeClaimInsert,
+    OvertimeKindTotalDto, OvertimeRateDto, OvertimeRateInput, OvertimeReportDto,
+    OvertimeReportRowDto,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::UserClaims;
+use crate::models::{Holiday, User};
+use crate::services::authorization::can_access_satker;
+use crate::services::calendar::build_holiday_override_map;
+use crate::services::tukin::parse_month;
+use crate::services::work_pattern::pick_effective_override;
+
+/// Aksi selain approve (approve punya alurnya sendiri karena menetapkan menit).
+#[derive(Debug, Clone, Copy)]
+pub enum OvertimeAction {
+    Cancel,
+    Reject,
+}
+
+impl OvertimeAction {
+    fn name(self) -> &'static str {
+        match self {
+            OvertimeAction::Cancel => "CANCELED",
+            OvertimeAction::Reject => "REJECTED",
+        }
+    }
+
+    fn verb(self) -> &'static str {
+        match self {
+            OvertimeAction::Cancel => "dibatalkan",
+            OvertimeAction::Reject => "di-reject",
+        }
+    }
+
+    fn to_status(self) -> &'static str {
+        match self {
+            OvertimeAction::Cancel => OVERTIME_CANCELED,
+            OvertimeAction::Reject => OVERTIME_REJECTED,
+        }
+    }
+}
+
+fn local_to_utc(tz: Tz, date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Jenis hari lembur: libur dari tabel holidays = HOLIDAY, libur rutin = WEEKEND.
+fn overtime_day_kind(day_type: CalendarDayType, holiday: Option<&Holiday>) -> &'static str {
+    match day_type {
+        CalendarDayType::Holiday => {
+            if holiday.is_some_and(|h| h.kind == HolidayKind::Holiday) {
+                OVERTIME_HOLIDAY
+            } else {
+                OVERTIME_WEEKEND
+            }
+        }
+        CalendarDayType::Workday | CalendarDayType::HalfDay => OVERTIME_WEEKDAY,
+    }
+}
+
+/// Batas mulai lembur hari kerja: akhir jadwal dinas, atau jam pulang (override pola kerja
+/// hanya berlaku untuk hari kerja penuh; hari setengah tetap dari kalender).
+fn overtime_threshold(
+    tz: Tz,
+    date: NaiveDate,
+    day_type: CalendarDayType,
+    calendar_end: Option<NaiveTime>,
+    override_end: Option<NaiveTime>,
+    duty_end: Option<DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    if let Some(end) = duty_end {
+        return Some(end);
+    }
+    let expected_end = match day_type {
+        CalendarDayType::Workday => override_end.or(calendar_end),
+        _ => calendar_end,
+    };
+    expected_end.and_then(|t| local_to_utc(tz, date, t))
+}
+
+/// Menit kerja segmen setelah `threshold_at` (seluruh segmen kalau None).
+/// Segmen tanpa check-out ditutup di akhir jadwal dinas yang sudah selesai, selain itu dilewati.
+/// Return (mulai, selesai, menit) kalau ada menit lembur.
+fn overtime_window(
+    segments: &[AttendanceSegmentDto],
+    threshold_at: Option<DateTime<Utc>>,
+    duty_end: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, DateTime<Utc>, i64)> {
+    let mut minutes = 0i64;
+    let mut start_at: Option<DateTime<Utc>> = None;
+    let mut end_at: Option<DateTime<Utc>> = None;
+
+    for seg in segments {
+        let seg_end = match seg.check_out_at {
+            Some(t) => t,
+            None => match duty_end {
+                Some(end) if end <= now => end.max(seg.check_in_at),
+                _ => continue,
+            },
+        };
+        let seg_start = match threshold_at {
+            Some(t) => seg.check_in_at.max(t),
+            None => seg.check_in_at,
+        };
+        if seg_end <= seg_start {
+            continue;
+        }
+
+        minutes += (seg_end - seg_start).num_minutes();
+        start_at = Some(start_at.map_or(seg_start, |s| s.min(seg_start)));
+        end_at = Some(end_at.map_or(seg_end, |e| e.max(seg_end)));
+    }
+
+    match (start_at, end_at) {
+        (Some(start_at), Some(end_at)) if minutes > 0 => Some((start_at, end_at, minutes)),
+        _ => None,
+    }
+}
+
+/// Hitung kandidat lembur user di range tanggal (inklusif).
+/// Segmen yang belum check-out hanya dihitung kalau ada jadwal dinas yang sudah selesai
+/// (check-out dinas tidak wajib); ditutup di akhir jadwal dinas.
+pub async fn compute_overtime_candidates(
+    db: &DBClient,
+    tz: Tz,
+    user: &User,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<OvertimeCandidateDto>, HttpError> {
+    if to < from {
+        return Err(HttpError::bad_request("to: harus >= from"));
+    }
+    if (to - from).num_days() > 92 {
+        return Err(HttpError::bad_request("range tanggal maksimal 93 hari"));
+    }
+
+    let calendar = db
+        .list_calendar_days(user.satker_id, from, to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let cal_map: HashMap<NaiveDate, _> = calendar.into_iter().map(|d| (d.work_date, d)).collect();
+
+    let holidays = db
+        .list_holidays(user.satker_id, from, to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let holiday_by_date = build_holiday_override_map(holidays);
+
+    let overrides = db
+        .list_user_work_pattern_overrides(user.id, from, to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let sessions = db
+        .list_attendance_by_user_from_to(user.id, from, to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let from_dt = local_to_utc(tz, from, NaiveTime::MIN)
+        .ok_or_else(|| HttpError::bad_request("from: tanggal tidak valid"))?;
+    let to_dt = to
+        .succ_opt()
+        .and_then(|d| local_to_utc(tz, d, NaiveTime::MIN))
+        .ok_or_else(|| HttpError::bad_request("to: tanggal tidak valid"))?;
+    let duty_schedules = db
+        .list_duty_schedules(Some(user.satker_id), Some(user.id), from_dt, to_dt)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let duty_by_date: HashMap<NaiveDate, _> = duty_schedules
+        .into_iter()
+        .map(|ds| (ds.start_at.with_timezone(&tz).date_naive(), ds))
+        .collect();
+
+    let claims: HashMap<NaiveDate, (Uuid, String)> = db
+        .list_open_overtime_claims(user.id, from, to)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .into_iter()
+        .map(|(d, id, status)| (d, (id, status)))
+        .collect();
+
+    let now = Utc::now();
+    let mut out = Vec::new();
+
+    // session INVALID = tidak hadir, tidak ada jam kerja yang bisa diklaim
+    for sess in sessions.into_iter().filter(|s| !s.is_invalid()) {
+        let d = sess.work_date;
+        // kalender belum di-generate: tidak bisa menentukan jenis hari
+        let Some(cal) = cal_map.get(&d) else {
+            continue;
+        };
+        let duty = duty_by_date.get(&d);
+
+        let day_kind = overtime_day_kind(cal.day_type, holiday_by_date.get(&d));
+
+        let threshold_at = if day_kind == OVERTIME_WEEKDAY {
+            let threshold = overtime_threshold(
+                tz,
+                d,
+                cal.day_type,
+                cal.expected_end,
+                pick_effective_override(&overrides, d).map(|o| o.work_end),
+                duty.map(|ds| ds.end_at),
+            );
+            let Some(threshold) = threshold else {
+                continue;
+            };
+            Some(threshold)
+        } else {
+            None
+        };
+
+        let Some((start_at, end_at, minutes)) = overtime_window(
+            &sess.segments.0,
+            threshold_at,
+            duty.map(|ds| ds.end_at),
+            now,
+        ) else {
+            continue;
+        };
+
+        let claim = claims.get(&d);
+        out.push(OvertimeCandidateDto {
+            work_date: d,
+            day_kind,
+            session_id: sess.session_id,
+            duty_schedule_id: duty.map(|ds| ds.id),
+            threshold_at,
+            start_at,
+            end_at,
+            minutes,
+            claim_id: claim.map(|(id, _)| *id),
+            claim_status: claim.map(|(_, status)| status.clone()),
+        });
+    }
+
+    out.sort_by_key(|c| c.work_date);
+    Ok(out)
+}
+
+/// Ajukan klaim lembur untuk satu hari; menit diambil dari kandidat yang dihitung ulang.
+pub async fn create_overtime_claim(
+    db: &DBClient,
+    tz: Tz,
+    user: &User,
+    req: CreateOvertimeClaimReq,
+) -> Result<Uuid, HttpError> {
+    let today = Utc::now().with_timezone(&tz).date_naive();
+    if req.work_date > today {
+        return Err(HttpError::bad_request(
+            "work_date: tidak bisa mengajukan lembur untuk tanggal yang akan datang",
+        ));
+    }
+
+    let candidate = compute_overtime_candidates(db, tz, user, req.work_date, req.work_date)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| HttpError::bad_request("tidak ada kandidat lembur di tanggal tersebut"))?;
+
+    if candidate.claim_id.is_some() {
+        return Err(HttpError::bad_request(
+            "sudah ada klaim lembur aktif di tanggal tersebut",
+        ));
+    }
+
+    let candidate_minutes = i32::try_from(candidate.minutes)
+        .map_err(|_| HttpError::bad_request("menit kandidat lembur tidak valid"))?;
+    let claimed_minutes = req.minutes.unwrap_or(candidate_minutes);
+    if claimed_minutes < 1 || claimed_minutes > candidate_minutes {
+        return Err(HttpError::bad_request(format!(
+            "minutes: harus 1..{} menit (kandidat lembur)",
+            candidate_minutes
+        )));
+    }
+
+    let note = req
+        .note
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+
+    db.create_overtime_claim(&OvertimeClaimInsert {
+        satker_id: user.satker_id,
+        user_id: user.id,
+        session_id: candidate.session_id,
+        duty_schedule_id: candidate.duty_schedule_id,
+        work_date: candidate.work_date,
+        day_kind: candidate.day_kind,
+        start_at: candidate.start_at,
+        end_at: candidate.end_at,
+        candidate_minutes,
+        claimed_minutes,
+        note,
+    })
+    .await
+    .map_err(|e| {
+        if e.as_database_error()
+            .is_some_and(|d| d.is_unique_violation())
+        {
+            HttpError::bad_request("sudah ada klaim lembur aktif di tanggal tersebut")
+        } else {
+            HttpError::server_error(e.to_string())
+        }
+    })
+}
+
+/// Pemohon atau admin/kepala satker (dan superadmin) boleh melihat klaim.
+pub fn ensure_can_view_overtime_claim(
+    claims: &UserClaims,
+    claim: &OvertimeClaimDto,
+) -> Result<(), HttpError> {
+    if claims.user_id == claim.user_id || can_access_satker(claims, claim.satker_id) {
+        return Ok(());
+    }
+    Err(HttpError::unauthorized("forbidden"))
+}
+
+/// Keputusan klaim lembur: superadmin atau kepala satker yang sedang menjabat.
+/// Kepala satker tidak bisa memutus klaimnya sendiri.
+pub async fn ensure_can_decide_overtime(
+    db: &DBClient,
+    claims: &UserClaims,
+    satker_id: Uuid,
+    claim_user_id: Uuid,
+) -> Result<(), HttpError> {
+    match claims.role {
+        UserRole::Superadmin => Ok(()),
+        UserRole::SatkerHead if claims.satker_id == satker_id => {
+            if claims.user_id == claim_user_id {
+                return Err(HttpError::unauthorized(
+                    "klaim lembur sendiri harus diputus atasan / superadmin",
+                ));
+            }
+            let ok = db
+                .is_current_head_satker(satker_id, claims.user_id)
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?;
+            if !ok {
+                return Err(HttpError::unauthorized("forbidden"));
+            }
+            Ok(())
+        }
+        _ => Err(HttpError::unauthorized("forbidden")),
+    }
+}
+
+/// Batalkan (pemohon) atau tolak (kepala satker) klaim yang masih SUBMITTED.
+pub async fn respond_overtime_claim(
+    db: &DBClient,
+    claims: &UserClaims,
+    id: Uuid,
+    action: OvertimeAction,
+    note: Option<String>,
+) -> Result<(), HttpError> {
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let claim = lock_overtime_claim(&mut tx, id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("klaim lembur tidak ditemukan"))?;
+
+    match action {
+        OvertimeAction::Cancel => {
+            if claims.user_id != claim.user_id {
+                return Err(HttpError::unauthorized("forbidden"));
+            }
+        }
+        OvertimeAction::Reject => {
+            ensure_can_decide_overtime(db, claims, claim.satker_id, claim.user_id).await?;
+        }
+    }
+
+    if claim.status != OVERTIME_SUBMITTED {
+        return Err(HttpError::bad_request(format!(
+            "klaim dengan status {} tidak bisa {}",
+            claim.status,
+            action.verb()
+        )));
+    }
+
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    update_overtime_claim_status(
+        &mut tx,
+        &claim,
+        claims.user_id,
+        action.name(),
+        action.to_status(),
+        note.as_deref(),
+        matches!(action, OvertimeAction::Reject),
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Setujui klaim lembur; menit yang disetujui boleh lebih kecil dari yang diklaim.
+pub async fn approve_overtime_claim(
+    db: &DBClient,
+    claims: &UserClaims,
+    id: Uuid,
+    req: ApproveOvertimeClaimReq,
+) -> Result<(), HttpError> {
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let claim = lock_overtime_claim(&mut tx, id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("klaim lembur tidak ditemukan"))?;
+
+    ensure_can_decide_overtime(db, claims, claim.satker_id, claim.user_id).await?;
+
+    if claim.status != OVERTIME_SUBMITTED {
+        return Err(HttpError::bad_request(format!(
+            "klaim dengan status {} tidak bisa di-approve",
+            claim.status
+        )));
+    }
+
+    let approved_minutes = req.approved_minutes.unwrap_or(claim.claimed_minutes);
+    if approved_minutes < 1 || approved_minutes > claim.claimed_minutes {
+        return Err(HttpError::bad_request(format!(
+            "approved_minutes: harus 1..{} menit",
+            claim.claimed_minutes
+        )));
+    }
+
+    set_overtime_approved_minutes(&mut tx, claim.id, approved_minutes)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let note = req
+        .note
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+    update_overtime_claim_status(
+        &mut tx,
+        &claim,
+        claims.user_id,
+        "APPROVED",
+        OVERTIME_APPROVED,
+        note.as_deref(),
+        true,
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Normalisasi & validasi tabel tarif sebelum disimpan.
+pub fn validate_overtime_rates(
+    rates: Vec<OvertimeRateInput>,
+) -> Result<Vec<OvertimeRateInput>, HttpError> {
+    let mut seen = std::collections::HashSet::new();
+    let mut out = Vec::with_capacity(rates.len());
+
+    for (i, mut r) in rates.into_iter().enumerate() {
+        r.day_kind = r.day_kind.trim().to_uppercase();
+        if !OVERTIME_DAY_KINDS.contains(&r.day_kind.as_str()) {
+            return Err(HttpError::bad_request(format!(
+                "rates[{}].day_kind: harus salah satu dari {}",
+                i,
+                OVERTIME_DAY_KINDS.join(", ")
+            )));
+        }
+        if r.hourly_rate < 0 {
+            return Err(HttpError::bad_request(format!(
+                "rates[{}].hourly_rate: tidak boleh negatif",
+                i
+            )));
+        }
+        if r.max_minutes_per_day.is_some_and(|m| m <= 0) {
+            return Err(HttpError::bad_request(format!(
+                "rates[{}].max_minutes_per_day: harus > 0",
+                i
+            )));
+        }
+        if !seen.insert((r.rank_id, r.day_kind.clone())) {
+            return Err(HttpError::bad_request(format!(
+                "rates[{}]: tarif untuk pangkat & day_kind yang sama sudah ada",
+                i
+            )));
+        }
+        out.push(r);
+    }
+
+    Ok(out)
+}
+
+pub fn map_overtime_rates_save_error(e: sqlx::Error) -> HttpError {
+    if e.as_database_error()
+        .is_some_and(|d| d.is_foreign_key_violation())
+    {
+        return HttpError::bad_request("rank_id: pangkat tidak ditemukan");
+    }
+    HttpError::server_error(e.to_string())
+}
+
+/// Tarif untuk pangkat & jenis hari; fallback ke tarif default (rank_id NULL).
+fn pick_rate<'a>(
+    rates: &'a [OvertimeRateDto],
+    rank_id: Option<Uuid>,
+    day_kind: &str,
+) -> Option<&'a OvertimeRateDto> {
+    rates
+        .iter()
+        .find(|r| rank_id.is_some() && r.rank_id == rank_id && r.day_kind == day_kind)
+        .or_else(|| {
+            rates
+                .iter()
+                .find(|r| r.rank_id.is_none() && r.day_kind == day_kind)
+        })
+}
+
+/// Rekap lembur APPROVED satu satker untuk satu bulan, sudah dikalikan tarif.
+pub async fn compute_overtime_report(
+    db: &DBClient,
+    month: &str,
+    satker_id: Uuid,
+) -> Result<OvertimeReportDto, HttpError> {
+    let (start, end_exclusive) = parse_month(month)?;
+    let end = end_exclusive.pred_opt().unwrap();
+
+    let rates = db
+        .list_overtime_rates()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let approved = db
+        .list_approved_overtime(satker_id, start, end)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let mut rows: Vec<OvertimeReportRowDto> = Vec::new();
+    let mut index: HashMap<Uuid, usize> = HashMap::new();
+
+    for a in approved {
+        let i = *index.entry(a.user_id).or_insert_with(|| {
+            rows.push(OvertimeReportRowDto {
+                user_id: a.user_id,
+                full_name: a.full_name.clone(),
+                nrp: a.nrp.clone(),
+                rank_code: a.rank_code.clone(),
+                rank_name: a.rank_name.clone(),
+                weekday: OvertimeKindTotalDto::default(),
+                weekend: OvertimeKindTotalDto::default(),
+                holiday: OvertimeKindTotalDto::default(),
+                total_minutes: 0,
+                total_amount: 0,
+            });
+            rows.len() - 1
+        });
+        let row = &mut rows[i];
+
+        let total = match a.day_kind.as_str() {
+            OVERTIME_WEEKEND => &mut row.weekend,
+            OVERTIME_HOLIDAY => &mut row.holiday,
+            _ => &mut row.weekday,
+        };
+
+        let rate = pick_rate(&rates, a.rank_id, &a.day_kind);
+        let minutes = i64::from(a.approved_minutes);
+        let paid = rate
+            .and_then(|r| r.max_minutes_per_day)
+            .map_or(minutes, |cap| minutes.min(i64::from(cap)));
+
+        total.days += 1;
+        total.minutes += minutes;
+        total.paid_minutes += paid;
+        total.hourly_rate = rate.map_or(0, |r| r.hourly_rate);
+    }
+
+    let mut grand_total = 0i64;
+    for row in &mut rows {
+        for total in [&mut row.weekday, &mut row.weekend, &mut row.holiday] {
+            total.amount = total.paid_minutes * total.hourly_rate / 60;
+        }
+        row.total_minutes = row.weekday.minutes + row.weekend.minutes + row.holiday.minutes;
+        row.total_amount = row.weekday.amount + row.weekend.amount + row.holiday.amount;
+        grand_total += row.total_amount;
+    }
+
+    Ok(OvertimeReportDto {
+        month: month.to_string(),
+        satker_id,
+        rows,
+        total_amount: grand_total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TZ: Tz = Tz::Asia__Jakarta;
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    /// Jam lokal (WIB) tanggal `d` Maret 2026.
+    fn at(d: u32, h: u32, m: u32) -> DateTime<Utc> {
+        local_to_utc(TZ, date(d), time(h, m)).unwrap()
+    }
+
+    fn seg(
+        check_in_at: DateTime<Utc>,
+        check_out_at: Option<DateTime<Utc>>,
+    ) -> AttendanceSegmentDto {
+        AttendanceSegmentDto {
+            seq: 1,
+            check_in_at,
+            check_out_at,
+            worked_minutes: None,
+            check_in_geofence_name: None,
+            check_out_geofence_name: None,
+            check_in_attendance_leave_type: None,
+            check_out_attendance_leave_type: None,
+        }
+    }
+
+    fn holiday(kind: HolidayKind) -> Holiday {
+        Holiday {
+            id: Uuid::nil(),
+            scope: crate::constants::HolidayScope::National,
+            satker_id: None,
+            holiday_date: date(2),
+            kind,
+            name: "Libur".into(),
+            half_day_end: None,
+        }
+    }
+
+    #[test]
+    fn day_kind_follows_calendar_and_holiday_table() {
+        assert_eq!(
+            overtime_day_kind(CalendarDayType::Workday, None),
+            OVERTIME_WEEKDAY
+        );
+        assert_eq!(
+            overtime_day_kind(CalendarDayType::HalfDay, None),
+            OVERTIME_WEEKDAY
+        );
+        assert_eq!(
+            overtime_day_kind(CalendarDayType::Holiday, None),
+            OVERTIME_WEEKEND
+        );
+        assert_eq!(
+            overtime_day_kind(
+                CalendarDayType::Holiday,
+                Some(&holiday(HolidayKind::Holiday))
+            ),
+            OVERTIME_HOLIDAY
+        );
+    }
+
+    #[test]
+    fn threshold_prefers_duty_then_override_on_full_workday() {
+        let cal_end = Some(time(15, 30));
+        let override_end = Some(time(16, 0));
+
+        assert_eq!(
+            overtime_threshold(TZ, date(2), CalendarDayType::Workday, cal_end, None, None),
+            Some(at(2, 15, 30))
+        );
+        assert_eq!(
+            overtime_threshold(
+                TZ,
+                date(2),
+                CalendarDayType::Workday,
+                cal_end,
+                override_end,
+                None
+            ),
+            Some(at(2, 16, 0))
+        );
+        // hari setengah tidak memakai override
+        assert_eq!(
+            overtime_threshold(
+                TZ,
+                date(7),
+                CalendarDayType::HalfDay,
+                Some(time(12, 0)),
+                override_end,
+                None
+            ),
+            Some(at(7, 12, 0))
+        );
+        assert_eq!(
+            overtime_threshold(
+                TZ,
+                date(2),
+                CalendarDayType::Workday,
+                cal_end,
+                override_end,
+                Some(at(2, 20, 0))
+            ),
+            Some(at(2, 20, 0))
+        );
+        assert_eq!(
+            overtime_threshold(TZ, date(2), CalendarDayType::Workday, None, None, None),
+            None
+        );
+    }
+
+    #[test]
+    fn only_minutes_after_threshold_count() {
+        let now = at(31, 0, 0);
+        let segments = [
+            seg(at(2, 7, 30), Some(at(2, 12, 0))),
+            seg(at(2, 13, 0), Some(at(2, 17, 45))),
+        ];
+        assert_eq!(
+            overtime_window(&segments, Some(at(2, 15, 30)), None, now),
+            Some((at(2, 15, 30), at(2, 17, 45), 135))
+        );
+
+        // pulang tepat / sebelum jam pulang: bukan kandidat
+        let on_time = [seg(at(2, 7, 30), Some(at(2, 15, 30)))];
+        assert_eq!(
+            overtime_window(&on_time, Some(at(2, 15, 30)), None, now),
+            None
+        );
+    }
+
+    #[test]
+    fn holiday_counts_whole_segments() {
+        let now = at(31, 0, 0);
+        let segments = [
+            seg(at(2, 8, 0), Some(at(2, 10, 0))),
+            seg(at(2, 11, 0), Some(at(2, 12, 30))),
+        ];
+        assert_eq!(
+            overtime_window(&segments, None, None, now),
+            Some((at(2, 8, 0), at(2, 12, 30), 210))
+        );
+    }
+
+    #[test]
+    fn open_segment_closes_at_finished_duty_end() {
+        let duty_end = at(2, 22, 0);
+        let open = [seg(at(2, 14, 0), None)];
+
+        assert_eq!(
+            overtime_window(&open, Some(at(2, 20, 0)), Some(duty_end), at(3, 0, 0)),
+            Some((at(2, 20, 0), duty_end, 120))
+        );
+        // dinas belum selesai atau tanpa dinas: segmen terbuka belum dihitung
+        assert_eq!(
+            overtime_window(&open, Some(at(2, 20, 0)), Some(duty_end), at(2, 21, 0)),
+            None
+        );
+        assert_eq!(
+            overtime_window(&open, Some(at(2, 15, 30)), None, at(3, 0, 0)),
+            None
+        );
+    }
+}
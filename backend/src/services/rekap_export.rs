@@ -0,0 +1,548 @@
+//! Export rekap kehadiran bulanan ke XLSX dan PDF siap cetak (A4 landscape).
+
+use chrono::{Datelike, NaiveDate};
+use printpdf::path::PaintMode;
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference,
+    Rect, Rgb,
+};
+use rust_xlsxwriter::{Color as XlsxColor, Format, FormatAlign, FormatBorder, Workbook};
+use std::collections::HashSet;
+
+use crate::dtos::attendance_rekap::{MonthlyRekapDto, RekapCode};
+use crate::error::HttpError;
+
+const BULAN: [&str; 12] = [
+    "Januari",
+    "Februari",
+    "Maret",
+    "April",
+    "Mei",
+    "Juni",
+    "Juli",
+    "Agustus",
+    "September",
+    "Oktober",
+    "November",
+    "Desember",
+];
+
+const TITLE: &str = "REKAPITULASI KEHADIRAN BULANAN";
+
+fn tanggal_indonesia(d: NaiveDate) -> String {
+    format!("{} {} {}", d.day(), BULAN[d.month0() as usize], d.year())
+}
+
+fn bulan_indonesia(d: NaiveDate) -> String {
+    format!("{} {}", BULAN[d.month0() as usize], d.year())
+}
+
+fn legend() -> String {
+    let items: Vec<String> = RekapCode::ALL
+        .iter()
+        .map(|c| format!("{} = {}", c.code(), c.label()))
+        .collect();
+    format!("Keterangan: {}", items.join(", "))
+}
+
+/// Baris blok tanda tangan: (teks, tebal). Baris kosong = ruang tanda tangan.
+fn signature_lines(rekap: &MonthlyRekapDto, printed_on: NaiveDate) -> Vec<(String, bool)> {
+    let mut lines = vec![
+        (tanggal_indonesia(printed_on), false),
+        (format!("Kepala {}", rekap.satker_name), false),
+        (String::new(), false),
+        (String::new(), false),
+        (String::new(), false),
+    ];
+    match &rekap.signatory {
+        Some(head) => {
+            lines.push((head.full_name.clone(), true));
+            lines.push((
+                match &head.rank_name {
+                    Some(rank) => format!("{} NRP {}", rank, head.nrp),
+                    None => format!("NRP {}", head.nrp),
+                },
+                false,
+            ));
+        }
+        None => lines.push(("(..............................)".to_string(), false)),
+    }
+    lines
+}
+
+fn file_stem(rekap: &MonthlyRekapDto) -> String {
+    let code: String = rekap
+        .satker_code
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("rekap-absensi-{}-{}", code, rekap.month)
+}
+
+pub fn rekap_xlsx_filename(rekap: &MonthlyRekapDto) -> String {
+    format!("{}.xlsx", file_stem(rekap))
+}
+
+pub fn rekap_pdf_filename(rekap: &MonthlyRekapDto) -> String {
+    format!("{}.pdf", file_stem(rekap))
+}
+
+fn xlsx_err(e: rust_xlsxwriter::XlsxError) -> HttpError {
+    HttpError::server_error(format!("gagal membuat xlsx: {}", e))
+}
+
+pub fn render_rekap_xlsx(
+    rekap: &MonthlyRekapDto,
+    printed_on: NaiveDate,
+) -> Result<Vec<u8>, HttpError> {
+    let holidays: HashSet<NaiveDate> = rekap.holidays.iter().copied().collect();
+    let n_days = rekap.dates.len() as u16;
+    let first_day_col: u16 = 4;
+    let first_total_col = first_day_col + n_days;
+    let last_col = first_total_col + RekapCode::ALL.len() as u16 - 1;
+
+    let title = Format::new()
+        .set_bold()
+        .set_font_size(14)
+        .set_align(FormatAlign::Center);
+    let subtitle = Format::new().set_align(FormatAlign::Center);
+    let header = Format::new()
+        .set_bold()
+        .set_align(FormatAlign::Center)
+        .set_align(FormatAlign::VerticalCenter)
+        .set_border(FormatBorder::Thin)
+        .set_background_color(XlsxColor::RGB(0xD9E1F2));
+    let header_holiday = header
+        .clone()
+        .set_background_color(XlsxColor::RGB(0xD9D9D9));
+    let cell = Format::new()
+        .set_align(FormatAlign::Center)
+        .set_border(FormatBorder::Thin);
+    let cell_holiday = cell.clone().set_background_color(XlsxColor::RGB(0xEDEDED));
+    let cell_left = Format::new().set_border(FormatBorder::Thin);
+    let total = cell.clone().set_bold();
+    let sign = Format::new().set_align(FormatAlign::Center);
+    let sign_bold = sign
+        .clone()
+        .set_bold()
+        .set_underline(rust_xlsxwriter::FormatUnderline::Single);
+
+    let mut workbook = Workbook::new();
+    let ws = workbook.add_worksheet();
+    ws.set_name(format!("Rekap {}", rekap.month))
+        .map_err(xlsx_err)?;
+    ws.set_landscape();
+    ws.set_paper_size(9); // A4
+    ws.set_print_fit_to_pages(1, 0);
+    ws.set_margins(0.4, 0.4, 0.5, 0.5, 0.3, 0.3);
+
+    let month_start = rekap.dates.first().copied().unwrap_or_default();
+    ws.merge_range(0, 0, 0, last_col, TITLE, &title)
+        .map_err(xlsx_err)?;
+    ws.merge_range(
+        1,
+        0,
+        1,
+        last_col,
+        &format!("{} ({})", rekap.satker_name, rekap.satker_code),
+        &subtitle,
+    )
+    .map_err(xlsx_err)?;
+    ws.merge_range(
+        2,
+        0,
+        2,
+        last_col,
+        &format!("Bulan {}", bulan_indonesia(month_start)),
+        &subtitle,
+    )
+    .map_err(xlsx_err)?;
+
+    // header 2 baris
+    let (h1, h2) = (4, 5);
+    for (col, label, width) in [
+        (0, "No", 4.0),
+        (1, "Nama", 28.0),
+        (2, "Pangkat", 14.0),
+        (3, "NRP", 12.0),
+    ] {
+        ws.merge_range(h1, col, h2, col, label, &header)
+            .map_err(xlsx_err)?;
+        ws.set_column_width(col, width).map_err(xlsx_err)?;
+    }
+    if n_days > 0 {
+        ws.merge_range(
+            h1,
+            first_day_col,
+            h1,
+            first_total_col - 1,
+            "Tanggal",
+            &header,
+        )
+        .map_err(xlsx_err)?;
+    }
+    ws.merge_range(h1, first_total_col, h1, last_col, "Jumlah", &header)
+        .map_err(xlsx_err)?;
+    for (i, d) in rekap.dates.iter().enumerate() {
+        let col = first_day_col + i as u16;
+        let fmt = if holidays.contains(d) {
+            &header_holiday
+        } else {
+            &header
+        };
+        ws.write_number_with_format(h2, col, d.day(), fmt)
+            .map_err(xlsx_err)?;
+        ws.set_column_width(col, 3.6).map_err(xlsx_err)?;
+    }
+    for (i, code) in RekapCode::ALL.iter().enumerate() {
+        let col = first_total_col + i as u16;
+        ws.write_string_with_format(h2, col, code.code(), &header)
+            .map_err(xlsx_err)?;
+        ws.set_column_width(col, 4.2).map_err(xlsx_err)?;
+    }
+    ws.set_freeze_panes(h2 + 1, first_day_col)
+        .map_err(xlsx_err)?;
+    ws.set_repeat_rows(h1, h2).map_err(xlsx_err)?;
+
+    let mut row = h2 + 1;
+    for (no, m) in rekap.members.iter().enumerate() {
+        ws.write_number_with_format(row, 0, (no + 1) as u32, &cell)
+            .map_err(xlsx_err)?;
+        ws.write_string_with_format(row, 1, &m.full_name, &cell_left)
+            .map_err(xlsx_err)?;
+        ws.write_string_with_format(row, 2, m.rank_name.as_deref().unwrap_or(""), &cell_left)
+            .map_err(xlsx_err)?;
+        ws.write_string_with_format(row, 3, &m.nrp, &cell_left)
+            .map_err(xlsx_err)?;
+        for (i, (d, code)) in rekap.dates.iter().zip(&m.days).enumerate() {
+            let fmt = if holidays.contains(d) {
+                &cell_holiday
+            } else {
+                &cell
+            };
+            ws.write_string_with_format(
+                row,
+                first_day_col + i as u16,
+                code.map(|c| c.code()).unwrap_or(""),
+                fmt,
+            )
+            .map_err(xlsx_err)?;
+        }
+        for (i, code) in RekapCode::ALL.iter().enumerate() {
+            ws.write_number_with_format(
+                row,
+                first_total_col + i as u16,
+                m.totals.get(*code),
+                &total,
+            )
+            .map_err(xlsx_err)?;
+        }
+        row += 1;
+    }
+
+    row += 1;
+    ws.write_string(row, 0, legend()).map_err(xlsx_err)?;
+
+    // blok tanda tangan di sisi kanan
+    row += 2;
+    let sign_first = last_col.saturating_sub(12).max(first_day_col);
+    for (text, bold) in signature_lines(rekap, printed_on) {
+        let fmt = if bold { &sign_bold } else { &sign };
+        ws.merge_range(row, sign_first, row, last_col, &text, fmt)
+            .map_err(xlsx_err)?;
+        row += 1;
+    }
+
+    workbook.save_to_buffer().map_err(xlsx_err)
+}
+
+// ---------------------------------------------------------------------------
+// PDF
+// ---------------------------------------------------------------------------
+
+const PAGE_W: f32 = 297.0;
+const PAGE_H: f32 = 210.0;
+const MARGIN: f32 = 10.0;
+const ROW_H: f32 = 4.6;
+const HEADER_H: f32 = 9.0;
+const COL_NO: f32 = 7.0;
+const COL_NAME: f32 = 44.0;
+const COL_RANK: f32 = 18.0;
+const COL_NRP: f32 = 20.0;
+const COL_TOTAL: f32 = 6.0;
+const FONT_TABLE: f32 = 6.0;
+/// Tinggi legenda + blok tanda tangan di bawah tabel.
+const FOOTER_H: f32 = 50.0;
+
+fn pdf_err(e: printpdf::Error) -> HttpError {
+    HttpError::server_error(format!("gagal membuat pdf: {}", e))
+}
+
+/// Perkiraan lebar teks Helvetica (mm); cukup untuk rata tengah & memotong nama.
+fn text_width(text: &str, size_pt: f32) -> f32 {
+    let em: f32 = text
+        .chars()
+        .map(|c| if c.is_ascii_uppercase() { 0.68 } else { 0.52 })
+        .sum();
+    em * size_pt * 0.3528
+}
+
+fn fit_text(text: &str, size_pt: f32, width: f32) -> String {
+    if text_width(text, size_pt) <= width {
+        return text.to_string();
+    }
+    let mut out: String = text.to_string();
+    while !out.is_empty() && text_width(&format!("{}..", out), size_pt) > width {
+        out.pop();
+    }
+    format!("{}..", out.trim_end())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CellStyle {
+    Center,
+    Left,
+    /// Rata tengah dengan arsiran (hari libur).
+    Shaded,
+}
+
+impl CellStyle {
+    fn day(holiday: bool) -> Self {
+        if holiday {
+            CellStyle::Shaded
+        } else {
+            CellStyle::Center
+        }
+    }
+}
+
+struct PdfCanvas {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+}
+
+impl PdfCanvas {
+    fn new_page(&mut self) {
+        let (page, layer) = self.doc.add_page(Mm(PAGE_W), Mm(PAGE_H), "rekap");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.layer.set_outline_thickness(0.4);
+    }
+
+    /// `y` diukur dari atas halaman (baseline teks).
+    fn text(&self, text: &str, size: f32, x: f32, y: f32, bold: bool) {
+        let font = if bold { &self.bold } else { &self.regular };
+        self.layer.use_text(text, size, Mm(x), Mm(PAGE_H - y), font);
+    }
+
+    fn text_center(&self, text: &str, size: f32, x: f32, w: f32, y: f32, bold: bool) {
+        let tx = x + ((w - text_width(text, size)) / 2.0).max(0.0);
+        self.text(text, size, tx, y, bold);
+    }
+
+    /// Sel tabel; `top` dari atas halaman. Teks di tengah secara vertikal.
+    fn cell(&self, text: &str, x: f32, top: f32, w: f32, h: f32, style: CellStyle) {
+        let rect = Rect::new(Mm(x), Mm(PAGE_H - top - h), Mm(x + w), Mm(PAGE_H - top));
+        if style == CellStyle::Shaded {
+            self.layer
+                .set_fill_color(Color::Rgb(Rgb::new(0.85, 0.85, 0.85, None)));
+            self.layer.add_rect(rect.with_mode(PaintMode::Fill));
+            self.layer
+                .set_fill_color(Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None)));
+        }
+        self.layer.add_rect(rect.with_mode(PaintMode::Stroke));
+
+        if text.is_empty() {
+            return;
+        }
+        let baseline = top + h / 2.0 + FONT_TABLE * 0.3528 * 0.35;
+        if style != CellStyle::Left {
+            self.text_center(text, FONT_TABLE, x, w, baseline, false);
+        } else {
+            self.text(
+                &fit_text(text, FONT_TABLE, w - 1.6),
+                FONT_TABLE,
+                x + 0.8,
+                baseline,
+                false,
+            );
+        }
+    }
+}
+
+struct PdfColumns {
+    day_w: f32,
+    x_days: f32,
+    x_totals: f32,
+}
+
+fn draw_table_header(
+    c: &PdfCanvas,
+    rekap: &MonthlyRekapDto,
+    holidays: &HashSet<NaiveDate>,
+    cols: &PdfColumns,
+    top: f32,
+) {
+    let mut x = MARGIN;
+    for (label, w) in [
+        ("No", COL_NO),
+        ("Nama", COL_NAME),
+        ("Pangkat", COL_RANK),
+        ("NRP", COL_NRP),
+    ] {
+        c.cell(label, x, top, w, HEADER_H, CellStyle::Center);
+        x += w;
+    }
+    for (i, d) in rekap.dates.iter().enumerate() {
+        let x = cols.x_days + i as f32 * cols.day_w;
+        c.cell(
+            &d.day().to_string(),
+            x,
+            top,
+            cols.day_w,
+            HEADER_H,
+            CellStyle::day(holidays.contains(d)),
+        );
+    }
+    for (i, code) in RekapCode::ALL.iter().enumerate() {
+        let x = cols.x_totals + i as f32 * COL_TOTAL;
+        c.cell(code.code(), x, top, COL_TOTAL, HEADER_H, CellStyle::Center);
+    }
+}
+
+pub fn render_rekap_pdf(
+    rekap: &MonthlyRekapDto,
+    printed_on: NaiveDate,
+) -> Result<Vec<u8>, HttpError> {
+    let holidays: HashSet<NaiveDate> = rekap.holidays.iter().copied().collect();
+    let month_start = rekap.dates.first().copied().unwrap_or_default();
+
+    let (doc, page, layer) = PdfDocument::new(
+        format!("{} {} {}", TITLE, rekap.satker_code, rekap.month),
+        Mm(PAGE_W),
+        Mm(PAGE_H),
+        "rekap",
+    );
+    let regular = doc
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(pdf_err)?;
+    let bold = doc
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(pdf_err)?;
+    let layer = doc.get_page(page).get_layer(layer);
+    layer.set_outline_thickness(0.4);
+    let mut c = PdfCanvas {
+        doc,
+        layer,
+        regular,
+        bold,
+    };
+
+    let fixed_w = COL_NO + COL_NAME + COL_RANK + COL_NRP;
+    let totals_w = COL_TOTAL * RekapCode::ALL.len() as f32;
+    let n_days = rekap.dates.len().max(1) as f32;
+    let day_w = ((PAGE_W - 2.0 * MARGIN - fixed_w - totals_w) / n_days).min(6.0);
+    let cols = PdfColumns {
+        day_w,
+        x_days: MARGIN + fixed_w,
+        x_totals: MARGIN + fixed_w + day_w * rekap.dates.len() as f32,
+    };
+    let table_w = cols.x_totals + totals_w - MARGIN;
+
+    c.text_center(TITLE, 12.0, MARGIN, table_w, 16.0, true);
+    c.text_center(
+        &format!("{} ({})", rekap.satker_name, rekap.satker_code),
+        10.0,
+        MARGIN,
+        table_w,
+        22.0,
+        false,
+    );
+    c.text_center(
+        &format!("Bulan {}", bulan_indonesia(month_start)),
+        10.0,
+        MARGIN,
+        table_w,
+        27.0,
+        false,
+    );
+
+    let mut top = 32.0;
+    draw_table_header(&c, rekap, &holidays, &cols, top);
+    top += HEADER_H;
+
+    for (no, m) in rekap.members.iter().enumerate() {
+        if top + ROW_H > PAGE_H - MARGIN {
+            c.new_page();
+            top = MARGIN;
+            draw_table_header(&c, rekap, &holidays, &cols, top);
+            top += HEADER_H;
+        }
+
+        let mut x = MARGIN;
+        c.cell(
+            &(no + 1).to_string(),
+            x,
+            top,
+            COL_NO,
+            ROW_H,
+            CellStyle::Center,
+        );
+        x += COL_NO;
+        c.cell(&m.full_name, x, top, COL_NAME, ROW_H, CellStyle::Left);
+        x += COL_NAME;
+        c.cell(
+            m.rank_name.as_deref().unwrap_or(""),
+            x,
+            top,
+            COL_RANK,
+            ROW_H,
+            CellStyle::Left,
+        );
+        x += COL_RANK;
+        c.cell(&m.nrp, x, top, COL_NRP, ROW_H, CellStyle::Left);
+
+        for (i, (d, code)) in rekap.dates.iter().zip(&m.days).enumerate() {
+            c.cell(
+                code.map(|c| c.code()).unwrap_or(""),
+                cols.x_days + i as f32 * cols.day_w,
+                top,
+                cols.day_w,
+                ROW_H,
+                CellStyle::day(holidays.contains(d)),
+            );
+        }
+        for (i, code) in RekapCode::ALL.iter().enumerate() {
+            c.cell(
+                &m.totals.get(*code).to_string(),
+                cols.x_totals + i as f32 * COL_TOTAL,
+                top,
+                COL_TOTAL,
+                ROW_H,
+                CellStyle::Center,
+            );
+        }
+        top += ROW_H;
+    }
+
+    if top + FOOTER_H > PAGE_H - MARGIN {
+        c.new_page();
+        top = MARGIN;
+    }
+
+    c.text(&legend(), 7.0, MARGIN, top + 5.0, false);
+
+    // blok tanda tangan rata tengah di sisi kanan
+    let sign_w = 80.0;
+    let sign_x = MARGIN + table_w - sign_w;
+    let mut y = top + 13.0;
+    for (text, bold) in signature_lines(rekap, printed_on) {
+        if !text.is_empty() {
+            c.text_center(&text, 9.0, sign_x, sign_w, y, bold);
+        }
+        y += 4.5;
+    }
+
+    c.doc.save_to_bytes().map_err(pdf_err)
+}
@@ -0,0 +1,345 @@
+//! Retensi file upload (selfie absensi, foto profil) beserta referensinya di DB.
+//!
+//! Periode retensi per kelas objek disimpan di app_settings (`retention_policy`) dan
+//! dijalankan worker background; data yang ditahan legal hold (atau anomalinya belum
+//! ditinjau) tidak ikut dihapus.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::database::attendance::AttendanceEventRepo;
+use crate::database::retention::RetentionRepo;
+use crate::database::selfie_upload::SelfieUploadRepo;
+use crate::database::settings::{SETTING_RETENTION_POLICY, SettingsRepo};
+use crate::db::DBClient;
+use crate::dtos::admin::SelfiePurgeReport;
+use crate::dtos::retention::{
+    ProfilePhotoPurgeReport, RETENTION_RUN_SCHEDULED, RetentionClass, RetentionPolicy,
+    RetentionReport,
+};
+use crate::error::HttpError;
+use crate::services::image_pipeline::{SELFIE_VARIANTS, variant_path};
+use crate::services::storage::Storage;
+use crate::services::upload::{PROFILE_FOLDER, SELFIE_FOLDER, upload_path_date};
+
+/// Worker mengecek tiap jam, tapi purge sungguhan paling sering sekali per RUN_EVERY.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+const STARTUP_DELAY: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+const RUN_EVERY: Duration = Duration::hours(24);
+
+/// Key selfie beserta key varian thumb/medium-nya.
+fn with_variants(keys: &[String]) -> HashSet<String> {
+    keys.iter()
+        .flat_map(|key| {
+            std::iter::once(key.clone())
+                .chain(SELFIE_VARIANTS.iter().map(|v| variant_path(key, *v)))
+        })
+        .collect()
+}
+
+/// Hapus selfie event absensi sebelum `before`:
+/// 1) kosongkan `selfie_object_key` di DB (tandai `selfie_purged_at`), lalu hapus objeknya
+///    beserta varian thumb/medium,
+/// 2) sapu objek di folder harian lama yang tersisa (upload yang tidak pernah dipakai event),
+///    di semua backend storage yang terkonfigurasi,
+/// 3) hapus metadata upload (selfie_uploads) sebelum `before`.
+///
+/// Event yang ditahan (legal hold / anomali PENDING) dan upload milik user dengan legal hold
+/// aktif dilewati. `dry_run = true` hanya menghitung tanpa mengubah apa pun.
+pub async fn purge_selfies(
+    db: &DBClient,
+    storage: &Storage,
+    before: DateTime<Utc>,
+    dry_run: bool,
+) -> Result<SelfiePurgeReport, HttpError> {
+    let mut report = SelfiePurgeReport {
+        before,
+        dry_run,
+        ..Default::default()
+    };
+
+    let held_keys = db
+        .list_held_selfie_keys_before(before)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    report.events_held = held_keys.len() as i64;
+    let held_users: Vec<String> = db
+        .list_held_user_ids()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .into_iter()
+        .map(|id| id.to_string())
+        .collect();
+
+    let keys = if dry_run {
+        db.list_selfie_keys_before(before).await
+    } else {
+        db.clear_selfie_keys_before(before).await
+    }
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+    report.events_cleared = keys.len();
+
+    for key in &keys {
+        let result = if dry_run {
+            storage.exists(SELFIE_FOLDER, key).await
+        } else {
+            storage.delete(SELFIE_FOLDER, key).await
+        };
+        match result {
+            Ok(true) => report.files_deleted += 1,
+            Ok(false) => report.files_missing += 1,
+            Err(e) => report.errors.push(format!("{}: {}", key, e.message)),
+        }
+        if !dry_run {
+            for variant in SELFIE_VARIANTS {
+                let variant_key = variant_path(key, variant);
+                if let Err(e) = storage.delete(SELFIE_FOLDER, &variant_key).await {
+                    report
+                        .errors
+                        .push(format!("{}: {}", variant_key, e.message));
+                }
+            }
+        }
+    }
+
+    // Objek yang masih ditahan tidak boleh ikut tersapu; saat dry-run objek yang
+    // direferensikan event juga tidak dihitung lagi sebagai orphan.
+    let mut protected = with_variants(&held_keys);
+    if dry_run {
+        protected.extend(with_variants(&keys));
+    }
+
+    // Folder harian diberi jeda 1 hari: upload jam 23:59 bisa dipakai event setelah cutoff.
+    let sweep_before = (before - Duration::days(1)).date_naive();
+    let prefix = format!("{}/", SELFIE_FOLDER);
+    for backend in storage.readers() {
+        let paths = match backend.list(&prefix).await {
+            Ok(paths) => paths,
+            Err(e) => {
+                report
+                    .errors
+                    .push(format!("{}: {}", backend.name(), e.message));
+                continue;
+            }
+        };
+
+        let mut swept_dates: HashSet<NaiveDate> = HashSet::new();
+        for path in paths {
+            let Some(date) = upload_path_date(&path).filter(|d| *d < sweep_before) else {
+                continue;
+            };
+            if protected.contains(backend.key_for(&path).as_str()) {
+                continue;
+            }
+            // nama file upload diawali user_id pemiliknya
+            let file_name = path.rsplit('/').next().unwrap_or_default();
+            if held_users
+                .iter()
+                .any(|id| file_name.starts_with(id.as_str()))
+            {
+                continue;
+            }
+            if !dry_run && let Err(e) = backend.delete(&path).await {
+                report.errors.push(format!("{}: {}", path, e.message));
+                continue;
+            }
+            swept_dates.insert(date);
+            report.orphan_files_removed += 1;
+        }
+        report.orphan_dirs_removed += swept_dates.len();
+    }
+
+    report.upload_records_deleted = if dry_run {
+        db.count_selfie_uploads_before(before)
+            .await
+            .map(|n| n as u64)
+    } else {
+        db.delete_selfie_uploads_before(before).await
+    }
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(report)
+}
+
+/// Hapus foto profil user yang sudah nonaktif sejak sebelum `before` (kecuali ditahan).
+pub async fn purge_profile_photos(
+    db: &DBClient,
+    storage: &Storage,
+    before: DateTime<Utc>,
+    dry_run: bool,
+) -> Result<ProfilePhotoPurgeReport, HttpError> {
+    let mut report = ProfilePhotoPurgeReport {
+        before,
+        dry_run,
+        users_held: db
+            .count_held_profile_photos(before)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?,
+        ..Default::default()
+    };
+
+    let photos = db
+        .list_expired_profile_photos(before)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    for (user_id, key) in photos {
+        if dry_run {
+            match storage.exists(PROFILE_FOLDER, &key).await {
+                Ok(true) => report.files_deleted += 1,
+                Ok(false) => report.files_missing += 1,
+                Err(e) => report.errors.push(format!("{}: {}", key, e.message)),
+            }
+            report.users_cleared += 1;
+            continue;
+        }
+
+        // key dikosongkan dulu; kalau user sudah ganti foto, baris ini dilewati
+        match db.clear_profile_photo_key(user_id, &key).await {
+            Ok(0) => continue,
+            Ok(_) => report.users_cleared += 1,
+            Err(e) => {
+                report.errors.push(format!("{}: {}", key, e));
+                continue;
+            }
+        }
+        match storage.delete(PROFILE_FOLDER, &key).await {
+            Ok(true) => report.files_deleted += 1,
+            Ok(false) => report.files_missing += 1,
+            Err(e) => report.errors.push(format!("{}: {}", key, e.message)),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Kebijakan retensi tersimpan; belum diset = semua objek disimpan selamanya.
+pub async fn load_retention_policy(db: &DBClient) -> Result<RetentionPolicy, HttpError> {
+    let raw = db
+        .get_setting(SETTING_RETENTION_POLICY)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    match raw {
+        Some(raw) => serde_json::from_str(&raw).map_err(|e| {
+            HttpError::server_error(format!(
+                "setting {} tidak valid: {}",
+                SETTING_RETENTION_POLICY, e
+            ))
+        }),
+        None => Ok(RetentionPolicy::default()),
+    }
+}
+
+/// Satu putaran retensi untuk semua kelas objek yang punya periode retensi.
+pub async fn run_retention(
+    db: &DBClient,
+    storage: &Storage,
+    policy: &RetentionPolicy,
+    dry_run: bool,
+) -> Result<RetentionReport, HttpError> {
+    let now = Utc::now();
+    let mut report = RetentionReport {
+        dry_run,
+        ..Default::default()
+    };
+
+    if let Some(days) = policy.days(RetentionClass::Selfie) {
+        report.selfies =
+            Some(purge_selfies(db, storage, now - Duration::days(days), dry_run).await?);
+    }
+    if let Some(days) = policy.days(RetentionClass::ProfilePhoto) {
+        report.profile_photos =
+            Some(purge_profile_photos(db, storage, now - Duration::days(days), dry_run).await?);
+    }
+
+    Ok(report)
+}
+
+/// `run_retention` yang dicatat di retention_runs. None kalau tidak dijalankan karena purge
+/// lain sedang berjalan (atau, bila `not_since` diisi, sudah jalan setelah waktu itu).
+pub async fn run_recorded_retention(
+    db: &DBClient,
+    storage: &Storage,
+    kind: &str,
+    requested_by: Option<Uuid>,
+    dry_run: bool,
+    not_since: Option<DateTime<Utc>>,
+) -> Result<Option<RetentionReport>, HttpError> {
+    let policy = load_retention_policy(db).await?;
+
+    let Some(run_id) = db
+        .claim_retention_run(kind, requested_by, dry_run, not_since)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+
+    let result = run_retention(db, storage, &policy, dry_run).await;
+    let (report, error) = match &result {
+        Ok(report) => (serde_json::to_value(report).ok(), None),
+        Err(e) => (None, Some(e.message.as_str())),
+    };
+    if let Err(e) = db.finish_retention_run(run_id, report, error).await {
+        tracing::warn!(%run_id, "gagal menyimpan hasil retensi: {}", e);
+    }
+
+    result.map(Some)
+}
+
+/// Jalankan purge retensi berkala di background (dipanggil sekali saat serve).
+pub fn spawn_retention_worker(db: DBClient, storage: Storage) {
+    tokio::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+
+        loop {
+            match load_retention_policy(&db).await {
+                Ok(policy) if policy.is_empty() => {}
+                Ok(_) => {
+                    let not_since = Utc::now() - RUN_EVERY;
+                    match run_recorded_retention(
+                        &db,
+                        &storage,
+                        RETENTION_RUN_SCHEDULED,
+                        None,
+                        false,
+                        Some(not_since),
+                    )
+                    .await
+                    {
+                        Ok(Some(report)) => log_retention_report(&report),
+                        Ok(None) => {}
+                        Err(e) => tracing::warn!("purge retensi gagal: {}", e.message),
+                    }
+                }
+                Err(e) => tracing::warn!("kebijakan retensi tidak terbaca: {}", e.message),
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+fn log_retention_report(report: &RetentionReport) {
+    if let Some(s) = &report.selfies {
+        tracing::info!(
+            events_cleared = s.events_cleared,
+            events_held = s.events_held,
+            files_deleted = s.files_deleted,
+            orphan_files_removed = s.orphan_files_removed,
+            errors = s.errors.len(),
+            "retensi selfie selesai"
+        );
+    }
+    if let Some(p) = &report.profile_photos {
+        tracing::info!(
+            users_cleared = p.users_cleared,
+            users_held = p.users_held,
+            files_deleted = p.files_deleted,
+            errors = p.errors.len(),
+            "retensi foto profil selesai"
+        );
+    }
+}
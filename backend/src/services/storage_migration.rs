@@ -0,0 +1,203 @@
+//! Pindahkan objek `local://` ke backend storage utama (mis. S3) dan tulis ulang key di DB.
+//! Aman diulang: baris yang sudah pindah tidak lagi berawalan `local://`.
+
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::database::object_ref::ObjectRefRepo;
+use crate::db::DBClient;
+use crate::dtos::admin::StorageMigrationReport;
+use crate::dtos::upload::ObjectRefSource;
+use crate::error::HttpError;
+use crate::services::image_pipeline::{SELFIE_VARIANTS, variant_path};
+use crate::services::storage::{BACKEND_LOCAL, LOCAL_SCHEME, ObjectStore, Storage};
+use crate::services::upload::{ANNOUNCEMENT_FOLDER, FACE_FOLDER, PROFILE_FOLDER, SELFIE_FOLDER};
+
+fn source_folder(source: ObjectRefSource) -> &'static str {
+    match source {
+        ObjectRefSource::AttendanceSelfie | ObjectRefSource::SelfieUpload => SELFIE_FOLDER,
+        ObjectRefSource::ProfilePhoto => PROFILE_FOLDER,
+        ObjectRefSource::FacePhoto => FACE_FOLDER,
+        ObjectRefSource::AnnouncementAttachment => ANNOUNCEMENT_FOLDER,
+    }
+}
+
+/// Salin setiap objek `local://` yang direferensikan DB ke backend utama (path sama), lalu
+/// ganti key barisnya. Varian thumb/medium selfie ikut disalin. File lokal baru dihapus
+/// (`delete_local`) setelah semua sumber selesai, karena satu key bisa dipakai lebih dari satu baris.
+pub async fn migrate_local_objects(
+    db: &DBClient,
+    storage: &Storage,
+    batch_size: i64,
+    dry_run: bool,
+    delete_local: bool,
+) -> Result<StorageMigrationReport, HttpError> {
+    let target = storage.primary().name();
+    if target == BACKEND_LOCAL {
+        return Err(HttpError::bad_request(
+            "STORAGE_BACKEND masih local, set STORAGE_BACKEND=s3 dulu",
+        ));
+    }
+    if batch_size < 1 {
+        return Err(HttpError::bad_request("--batch-size minimal 1"));
+    }
+
+    let mut report = StorageMigrationReport {
+        target,
+        dry_run,
+        ..Default::default()
+    };
+    // key lama -> key baru
+    let mut moved: HashMap<String, String> = HashMap::new();
+    // key yang masih direferensikan baris gagal update; file lokalnya jangan dihapus
+    let mut keep_local: HashSet<String> = HashSet::new();
+
+    for source in ObjectRefSource::ALL {
+        let folder = source_folder(source);
+        let mut after: Option<Uuid> = None;
+        loop {
+            let refs = db
+                .list_object_refs_with_prefix(source, LOCAL_SCHEME, after, batch_size)
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?;
+            let Some(last) = refs.last() else {
+                break;
+            };
+            after = Some(last.id);
+
+            for r in refs {
+                let new_key = match moved.get(&r.object_key) {
+                    Some(key) => key.clone(),
+                    None => {
+                        let Some((backend, path)) = storage.resolve(folder, &r.object_key) else {
+                            report
+                                .errors
+                                .push(format!("{}: key tidak valid", r.object_key));
+                            continue;
+                        };
+                        // dry-run cukup cek keberadaan file
+                        let found = if dry_run {
+                            backend.exists(path).await.map(|ok| ok.then(Vec::new))
+                        } else {
+                            backend.get(path).await
+                        };
+                        let bytes = match found {
+                            Ok(Some(bytes)) => bytes,
+                            Ok(None) => {
+                                report.files_missing += 1;
+                                continue;
+                            }
+                            Err(e) => {
+                                report
+                                    .errors
+                                    .push(format!("{}: {}", r.object_key, e.message));
+                                continue;
+                            }
+                        };
+
+                        let new_key = if dry_run {
+                            storage.primary().key_for(path)
+                        } else {
+                            if folder == SELFIE_FOLDER {
+                                report.objects_copied += copy_selfie_variants(
+                                    storage,
+                                    backend,
+                                    path,
+                                    &mut report.errors,
+                                )
+                                .await;
+                            }
+                            let content_type = r.content_type.clone().unwrap_or_else(|| {
+                                mime_guess::from_path(path)
+                                    .first_or_octet_stream()
+                                    .to_string()
+                            });
+                            match storage.put(path, bytes, &content_type).await {
+                                Ok(key) => key,
+                                Err(e) => {
+                                    report
+                                        .errors
+                                        .push(format!("{}: {}", r.object_key, e.message));
+                                    continue;
+                                }
+                            }
+                        };
+                        report.objects_copied += 1;
+                        moved.insert(r.object_key.clone(), new_key.clone());
+                        new_key
+                    }
+                };
+
+                if dry_run {
+                    report.references_updated += 1;
+                    continue;
+                }
+                match db
+                    .replace_object_ref(source, r.id, &r.object_key, &new_key)
+                    .await
+                {
+                    Ok(n) => report.references_updated += n,
+                    Err(e) => {
+                        report.errors.push(format!("{}: {}", r.object_key, e));
+                        keep_local.insert(r.object_key);
+                    }
+                }
+            }
+        }
+    }
+
+    if delete_local
+        && !dry_run
+        && let Some(local) = storage.readers().iter().find(|b| b.name() == BACKEND_LOCAL)
+    {
+        for old_key in moved.keys().filter(|k| !keep_local.contains(*k)) {
+            let Some(path) = local.path_of(old_key) else {
+                continue;
+            };
+            let mut paths: Vec<String> = if path.starts_with(&format!("{}/", SELFIE_FOLDER)) {
+                SELFIE_VARIANTS
+                    .iter()
+                    .map(|v| variant_path(path, *v))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            paths.push(path.to_string());
+            for path in paths {
+                match local.delete(&path).await {
+                    Ok(true) => report.local_files_deleted += 1,
+                    Ok(false) => {}
+                    Err(e) => report.errors.push(format!("{}: {}", path, e.message)),
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Salin varian selfie yang ada ke backend utama; selfie lama memang tidak punya varian.
+async fn copy_selfie_variants(
+    storage: &Storage,
+    source: &dyn ObjectStore,
+    path: &str,
+    errors: &mut Vec<String>,
+) -> usize {
+    let mut copied = 0;
+    for variant in SELFIE_VARIANTS {
+        let copy_path = variant_path(path, variant);
+        let result = match source.get(&copy_path).await {
+            Ok(Some(bytes)) => storage
+                .put(&copy_path, bytes, "image/jpeg")
+                .await
+                .map(|_| ()),
+            Ok(None) => continue,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => copied += 1,
+            Err(e) => errors.push(format!("{}: {}", copy_path, e.message)),
+        }
+    }
+    copied
+}
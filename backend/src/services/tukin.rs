@@ -0,0 +1,917 @@
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde_json::json;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::constants::{AttendanceEventType, CalendarDayType, LeaveType};
+use crate::database::attendance::AttendanceEventRepo;
+use crate::database::duty_schedule::DutyScheduleRepo;
+use crate::database::tukin::{LeaveSpanRow, TukinCalculationUpsert, TukinRepo};
+use crate::database::user::UserRepo;
+use crate::database::work_calendar::WorkCalendarRepo;
+use crate::database::work_pattern::{WorkPatternRepo, pick_effective_pattern};
+use crate::database::work_pattern_override::WorkPatternOverrideRepo;
+use crate::db::DBClient;
+use crate::dtos::attendance::AttendanceRekapDto;
+use crate::dtos::attendance_session::AttendanceSegmentDto;
+use crate::dtos::tukin::{TukinCalculationRowDto, TukinUserSummaryDto};
+use crate::dtos::work_pattern_override::EffectiveWorkHours;
+use crate::error::HttpError;
+use crate::models::SatkerWorkPattern;
+use crate::services::work_pattern::{
+    hours_from_override, pick_effective_override, required_minutes_for_day,
+};
+
+pub fn parse_month(month: &str) -> Result<(NaiveDate, NaiveDate), HttpError> {
+    // month: YYYY-MM
+    let parts: Vec<&str> = month.split('-').collect();
+    if parts.len() != 2 {
+        return Err(HttpError::bad_request(
+            "Format month harus YYYY-MM".to_string(),
+        ));
+    }
+    let year: i32 = parts[0]
+        .parse()
+        .map_err(|_| HttpError::bad_request("Tahun tidak valid".to_string()))?;
+    let m: u32 = parts[1]
+        .parse()
+        .map_err(|_| HttpError::bad_request("Bulan tidak valid".to_string()))?;
+    if !(1..=12).contains(&m) {
+        return Err(HttpError::bad_request("Bulan harus 01..12".to_string()));
+    }
+
+    let start = NaiveDate::from_ymd_opt(year, m, 1).ok_or(HttpError::bad_request(
+        "Tanggal start tidak valid".to_string(),
+    ))?;
+    let (ny, nm) = if m == 12 {
+        (year + 1, 1)
+    } else {
+        (year, m + 1)
+    };
+    let end_exclusive = NaiveDate::from_ymd_opt(ny, nm, 1).ok_or(HttpError::bad_request(
+        "Tanggal end tidak valid".to_string(),
+    ))?;
+    Ok((start, end_exclusive))
+}
+
+fn date_range_inclusive(start: NaiveDate, end_exclusive: NaiveDate) -> Vec<NaiveDate> {
+    let mut out = Vec::new();
+    let mut d = start;
+    while d < end_exclusive {
+        out.push(d);
+        d = d.succ_opt().unwrap();
+    }
+    out
+}
+
+/// Session per work_date; session INVALID dibuang sehingga harinya dihitung tidak hadir.
+fn sessions_by_work_date(
+    sessions: Vec<AttendanceRekapDto>,
+) -> HashMap<NaiveDate, AttendanceRekapDto> {
+    sessions
+        .into_iter()
+        .filter(|s| !s.is_invalid())
+        .map(|s| (s.work_date, s))
+        .collect()
+}
+
+/// Batas istirahat hari itu: dari override jam kerja user/kelompok kalau ada,
+/// selain itu dari work pattern satker.
+fn max_break_for_day(
+    hours: Option<&EffectiveWorkHours>,
+    work_patterns: &[SatkerWorkPattern],
+    d: NaiveDate,
+) -> Option<i16> {
+    match hours {
+        Some(h) => h.max_break_minutes,
+        None => pick_effective_pattern(work_patterns, d).and_then(|p| p.max_break_minutes),
+    }
+}
+
+/// Istirahat melebihi batas: (menit lebih, potongan %) dengan potongan
+/// `penalty_per_minute_pct` per menit, maksimal `max_daily_penalty_pct`.
+/// None kalau tidak ada batas atau belum ada istirahat yang selesai.
+fn break_excess(
+    cap: Option<i16>,
+    taken: Option<i64>,
+    penalty_per_minute_pct: f64,
+    max_daily_penalty_pct: f64,
+) -> Option<(i64, f64)> {
+    let (Some(cap), Some(taken)) = (cap, taken) else {
+        return None;
+    };
+    let excess = (taken - i64::from(cap)).max(0);
+    let penalty_pct = (excess as f64 * penalty_per_minute_pct).min(max_daily_penalty_pct);
+    Some((excess, penalty_pct))
+}
+
+/// Kredit kehadiran dari session: hadir butuh check-in, tanpa check-out kena potongan.
+fn attendance_credit(sess: &AttendanceRekapDto, missing_checkout_penalty_pct: f64) -> f64 {
+    match (sess.check_in_at, sess.check_out_at) {
+        (None, _) => 0.0,
+        (Some(_), Some(_)) => 1.0,
+        (Some(_), None) => (1.0 - (missing_checkout_penalty_pct / 100.0)).max(0.0),
+    }
+}
+
+fn session_segments(sess: Option<&AttendanceRekapDto>) -> Vec<AttendanceSegmentDto> {
+    sess.map(|s| s.segments.0.clone()).unwrap_or_default()
+}
+
+fn leave_credit_for_date(
+    leaves: &[LeaveSpanRow],
+    rules: &HashMap<LeaveType, f64>,
+    d: NaiveDate,
+) -> Option<(LeaveType, f64)> {
+    for lr in leaves {
+        if d >= lr.start_date && d <= lr.end_date {
+            let credit = rules.get(&lr.tipe).copied().unwrap_or(0.0);
+            return Some((lr.tipe, credit));
+        }
+    }
+    None
+}
+
+/// Hitung ringkasan tukin per user untuk satu bulan.
+/// `satker_id`/`user_id` harus sudah di-scope sesuai role pemanggil.
+pub async fn compute_tukin_summaries(
+    db: &DBClient,
+    tz: Tz,
+    month: String,
+    satker_id: Option<Uuid>,
+    user_id: Option<Uuid>,
+) -> Result<Vec<TukinUserSummaryDto>, HttpError> {
+    let (period_start, period_end_exclusive) = parse_month(&month)?;
+
+    // Determine users
+    let users = if let Some(uid) = user_id {
+        let u = db
+            .find_user_by_id(uid)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?
+            .ok_or(HttpError::bad_request("User tidak ditemukan".to_string()))?;
+
+        if let Some(sid) = satker_id
+            && u.satker_id != sid
+        {
+            return Err(HttpError::unauthorized(
+                "User tidak sesuai satker".to_string(),
+            ));
+        }
+
+        vec![u]
+    } else {
+        let sid = satker_id.ok_or(HttpError::bad_request(
+            "satker_id wajib untuk melihat banyak user".to_string(),
+        ))?;
+        db.get_user_by_satker_id(sid)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?
+    };
+
+    if users.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let sid_for_policy = satker_id.unwrap_or(users[0].satker_id);
+
+    let policy = db
+        .find_active_tukin_policy(sid_for_policy, period_start)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let leave_rules_vec = db
+        .list_leave_rules(policy.id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let mut leave_rules: HashMap<LeaveType, f64> = HashMap::new();
+    for r in leave_rules_vec {
+        leave_rules.insert(r.leave_type, r.credit);
+    }
+
+    // Calendar days for satker
+    let calendar_from = period_start;
+    let calendar_to_inclusive = period_end_exclusive.pred_opt().unwrap();
+    let calendar_days = db
+        .list_calendar_days(sid_for_policy, calendar_from, calendar_to_inclusive)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let mut cal_map: HashMap<
+        NaiveDate,
+        (
+            CalendarDayType,
+            Option<chrono::NaiveTime>,
+            Option<chrono::NaiveTime>,
+        ),
+    > = HashMap::new();
+    for d in calendar_days {
+        cal_map.insert(d.work_date, (d.day_type, d.expected_start, d.expected_end));
+    }
+
+    let dates = date_range_inclusive(period_start, period_end_exclusive);
+
+    // work pattern satker: batas istirahat kalau user tidak punya override
+    let work_patterns = db
+        .list_work_patterns(sid_for_policy)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    // grace window untuk event duty schedule
+    let _grace_in = Duration::minutes(30);
+    let _grace_out = Duration::minutes(180);
+
+    let mut result: Vec<TukinUserSummaryDto> = Vec::new();
+
+    for u in users {
+        let leaves = db
+            .list_approved_leaves_by_user(
+                u.id,
+                period_start,
+                period_end_exclusive.pred_opt().unwrap(),
+            )
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        // NOTE: sessions ini sudah “rekap harian” (work_date), biasanya berasal dari logic rekap absensi
+        let sessions = db
+            .list_attendance_by_user_from_to(
+                u.id,
+                period_start,
+                period_end_exclusive.pred_opt().unwrap(),
+            )
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        let sess_map = sessions_by_work_date(sessions);
+
+        // duty schedule range: [start_local, end_exclusive_local) converted to UTC
+        let from_dt: DateTime<Utc> = tz
+            .from_local_datetime(&period_start.and_hms_opt(0, 0, 0).unwrap())
+            .single()
+            .unwrap()
+            .with_timezone(&Utc);
+        let to_dt: DateTime<Utc> = tz
+            .from_local_datetime(&period_end_exclusive.and_hms_opt(0, 0, 0).unwrap())
+            .single()
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let duty_schedules = db
+            .list_duty_schedules(Some(u.satker_id), Some(u.id), from_dt, to_dt)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        let mut duty_by_date: HashMap<NaiveDate, crate::dtos::duty_schedule::DutyScheduleDto> =
+            HashMap::new();
+        for ds in duty_schedules {
+            // mapping default: pakai tanggal start_at (local date) kalau start_at sudah UTC
+            // (kalau start_at tersimpan UTC, ini date_naive() akan UTC-date; untuk akurat,
+            //  sebaiknya ds.start_at di-convert dulu ke tz. Tapi kita pertahankan sesuai yang kamu pakai).
+            duty_by_date.insert(ds.start_at.with_timezone(&tz).date_naive(), ds);
+        }
+
+        let base_tukin = db
+            .get_user_base_tukin(u.id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        let mut expected_units = 0.0f64;
+        let mut earned_credit = 0.0f64;
+
+        let mut present_days = 0i32;
+        let mut absent_days = 0i32;
+        let mut missing_checkout_days = 0i32;
+        let mut duty_present = 0i32;
+        let mut duty_absent = 0i32;
+        let mut total_late_minutes: i64 = 0;
+        let mut total_short_minutes: i64 = 0;
+        let mut total_break_excess_minutes: i64 = 0;
+
+        // override jam kerja user/kelompok (kalau ada) menggantikan jam dari kalender satker
+        let work_overrides = db
+            .list_user_work_pattern_overrides(
+                u.id,
+                period_start,
+                period_end_exclusive.pred_opt().unwrap(),
+            )
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        let mut days: Vec<crate::dtos::tukin::TukinDayBreakdownDto> = Vec::new();
+
+        for d in &dates {
+            let (day_type, expected_start, _expected_end) =
+                cal_map
+                    .get(d)
+                    .copied()
+                    .unwrap_or((CalendarDayType::Workday, None, None));
+
+            // sessions rekap (work_date)
+            let sess = sess_map.get(d);
+
+            // event leave type (fallback), dari rekap attendance
+            let event_leave_raw = sess
+                .and_then(|s| s.check_in_attendance_leave_type)
+                .or_else(|| sess.and_then(|s| s.check_out_attendance_leave_type));
+
+            // =========================================================
+            // 1) APPROVED LEAVE REQUEST (MENANG MUTLAK)
+            // =========================================================
+            let leave_from_requests = leave_credit_for_date(&leaves, &leave_rules, *d);
+            if let Some((lr_type, lr_credit)) = leave_from_requests {
+                expected_units += 1.0;
+
+                // optional: kalau user tetap absen, boleh "max" dengan credit hadir
+                let mut credit_present = 0.0;
+                let mut check_in_at = None;
+                let mut check_out_at = None;
+
+                if let Some(sess) = sess {
+                    check_in_at = sess.check_in_at;
+                    check_out_at = sess.check_out_at;
+
+                    credit_present = attendance_credit(sess, policy.missing_checkout_penalty_pct);
+                    if sess.check_in_at.is_some() && sess.check_out_at.is_none() {
+                        missing_checkout_days += 1;
+                    }
+                }
+
+                let credit = credit_present.max(lr_credit);
+                earned_credit += credit;
+
+                if credit > 0.0 {
+                    present_days += 1;
+                } else {
+                    absent_days += 1;
+                }
+
+                days.push(crate::dtos::tukin::TukinDayBreakdownDto {
+                    work_date: *d,
+                    expected_unit: 1.0,
+                    earned_credit: credit,
+                    is_duty_schedule: false,
+                    duty_schedule_id: None,
+                    check_in_at,
+                    check_out_at,
+                    late_minutes: None, // ✅ no late untuk approved leave
+                    short_minutes: None,
+                    worked_minutes: sess.and_then(|s| s.worked_minutes),
+                    segments: session_segments(sess),
+                    break_minutes: sess.and_then(|s| s.break_minutes),
+                    break_excess_minutes: None,
+                    leave_type: Some(lr_type),
+                    leave_credit: Some(lr_credit),
+                    note: Some(format!("{:?}", lr_type).to_uppercase()),
+                });
+
+                continue;
+            }
+
+            // =========================================================
+            // 2) DUTY SCHEDULE (WAJIB CHECK-IN, HOLIDAY TETAP WAJIB)
+            // =========================================================
+            if let Some(ds) = duty_by_date.get(d) {
+                expected_units += 1.0;
+
+                let window_start = ds.start_at - Duration::minutes(30);
+                let window_end = ds.end_at + Duration::minutes(180);
+
+                // ✅ Untuk duty schedule: check-out tidak wajib.
+                // Tapi kalau user memang check-out, tetap tampilkan.
+                // Dan untuk present/earned, kita utamakan data session (work_date) agar tidak miss karena window/shift.
+                let mut check_in_at = sess.and_then(|s| s.check_in_at);
+                let mut check_out_at = sess.and_then(|s| s.check_out_at);
+
+                // Fallback: kalau session belum ada / tidak ketemu, cari event check-in dalam window duty.
+                if check_in_at.is_none() {
+                    let ci = db
+                        .find_first_event_in_range(
+                            u.id,
+                            AttendanceEventType::CheckIn,
+                            window_start,
+                            window_end,
+                        )
+                        .await
+                        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+                    if let Some(ci) = ci {
+                        check_in_at = Some(ci.occurred_at);
+                    }
+                }
+
+                // Optional display: kalau checkout belum ada di session, coba cari event checkout dalam window.
+                if check_out_at.is_none() {
+                    let co = db
+                        .find_first_event_in_range(
+                            u.id,
+                            AttendanceEventType::CheckOut,
+                            window_start,
+                            window_end,
+                        )
+                        .await
+                        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+                    if let Some(co) = co {
+                        check_out_at = Some(co.occurred_at);
+                    }
+                }
+
+                let credit = if check_in_at.is_some() { 1.0 } else { 0.0 };
+
+                if credit > 0.0 {
+                    duty_present += 1;
+                    present_days += 1;
+                } else {
+                    duty_absent += 1;
+                    absent_days += 1;
+                }
+
+                earned_credit += credit;
+
+                days.push(crate::dtos::tukin::TukinDayBreakdownDto {
+                    work_date: *d,
+                    expected_unit: 1.0,
+                    earned_credit: credit,
+                    is_duty_schedule: true,
+                    duty_schedule_id: Some(ds.id),
+                    check_in_at,
+                    check_out_at,
+                    late_minutes: None, // ✅ no late
+                    short_minutes: None,
+                    worked_minutes: sess.and_then(|s| s.worked_minutes),
+                    segments: session_segments(sess),
+                    break_minutes: sess.and_then(|s| s.break_minutes),
+                    break_excess_minutes: None,
+                    leave_type: None,
+                    leave_credit: None,
+                    note: Some("DUTY_SCHEDULE".to_string()),
+                });
+
+                continue;
+            }
+
+            // =========================================================
+            // Kalau bukan leave_request & bukan duty_schedule:
+            // Kalau HOLIDAY -> ignore (expected_unit 0)
+            // =========================================================
+            if day_type == CalendarDayType::Holiday {
+                days.push(crate::dtos::tukin::TukinDayBreakdownDto {
+                    work_date: *d,
+                    expected_unit: 0.0,
+                    earned_credit: 0.0,
+                    is_duty_schedule: false,
+                    duty_schedule_id: None,
+                    check_in_at: None,
+                    check_out_at: None,
+                    late_minutes: None,
+                    short_minutes: None,
+                    worked_minutes: None,
+                    segments: Vec::new(),
+                    break_minutes: None,
+                    break_excess_minutes: None,
+                    leave_type: None,
+                    leave_credit: None,
+                    note: Some("HOLIDAY_IGNORED".to_string()),
+                });
+                continue;
+            }
+
+            // =========================================================
+            // 3) EVENT attendance_leave_type (fallback)
+            // =========================================================
+            if let Some(ev) = event_leave_raw {
+                match ev {
+                    // 3a) JADWAL_DINAS -> treated like duty schedule
+                    crate::constants::AttendanceLeaveType::JadwalDinas => {
+                        expected_units += 1.0;
+
+                        let check_in_at = sess.and_then(|s| s.check_in_at);
+
+                        let credit = if check_in_at.is_some() { 1.0 } else { 0.0 };
+
+                        earned_credit += credit;
+
+                        if credit > 0.0 {
+                            present_days += 1;
+                        } else {
+                            absent_days += 1;
+                        }
+
+                        days.push(crate::dtos::tukin::TukinDayBreakdownDto {
+                            work_date: *d,
+                            expected_unit: 1.0,
+                            earned_credit: credit,
+                            is_duty_schedule: false,
+                            duty_schedule_id: None,
+                            check_in_at,
+                            check_out_at: None,
+                            late_minutes: None, // ✅ no late untuk jadwal_dinas
+                            short_minutes: None,
+                            worked_minutes: sess.and_then(|s| s.worked_minutes),
+                            segments: session_segments(sess),
+                            break_minutes: sess.and_then(|s| s.break_minutes),
+                            break_excess_minutes: None,
+                            leave_type: None,
+                            leave_credit: None,
+                            note: Some("JADWAL_DINAS".to_string()),
+                        });
+
+                        continue;
+                    }
+
+                    // 3b) WFH/WFA -> treated as normal hadir (late + missing checkout berlaku)
+                    crate::constants::AttendanceLeaveType::Wfh
+                    | crate::constants::AttendanceLeaveType::Wfa => {
+                        // lanjut ke NORMAL logic di bawah,
+                        // tapi note akan jadi WFH/WFA.
+                    }
+
+                    // 3c) DINAS_LUAR / IJIN / SAKIT -> tanpa leave_request approved -> earn 0, no late
+                    crate::constants::AttendanceLeaveType::DinasLuar
+                    | crate::constants::AttendanceLeaveType::Ijin
+                    | crate::constants::AttendanceLeaveType::Sakit => {
+                        expected_units += 1.0;
+
+                        earned_credit += 0.0;
+                        absent_days += 1;
+
+                        let note = match ev {
+                            crate::constants::AttendanceLeaveType::DinasLuar => "DINAS_LUAR",
+                            crate::constants::AttendanceLeaveType::Ijin => "IJIN",
+                            crate::constants::AttendanceLeaveType::Sakit => "SAKIT",
+                            _ => "EVENT",
+                        };
+
+                        days.push(crate::dtos::tukin::TukinDayBreakdownDto {
+                            work_date: *d,
+                            expected_unit: 1.0,
+                            earned_credit: 0.0,
+                            is_duty_schedule: false,
+                            duty_schedule_id: None,
+                            check_in_at: sess.and_then(|s| s.check_in_at),
+                            check_out_at: sess.and_then(|s| s.check_out_at),
+                            late_minutes: None, // ✅ no late
+                            short_minutes: None,
+                            worked_minutes: sess.and_then(|s| s.worked_minutes),
+                            segments: session_segments(sess),
+                            break_minutes: sess.and_then(|s| s.break_minutes),
+                            break_excess_minutes: None,
+                            leave_type: None,
+                            leave_credit: None,
+                            note: Some(note.to_string()),
+                        });
+
+                        continue;
+                    }
+
+                    crate::constants::AttendanceLeaveType::Normal => {
+                        // jatuh ke NORMAL
+                    }
+                }
+            }
+
+            // =========================================================
+            // 4) NORMAL (termasuk WFH/WFA, karena lanjut ke sini)
+            // =========================================================
+            expected_units += 1.0;
+
+            let mut credit_present = 0.0;
+            let mut check_in_at = None;
+            let mut check_out_at = None;
+            let mut late_minutes = None;
+            let mut short_minutes = None;
+            let mut break_excess_minutes = None;
+
+            let hours = pick_effective_override(&work_overrides, *d).map(hours_from_override);
+            let late_after = match &hours {
+                Some(h) => Some(h.late_after()),
+                None => expected_start,
+            };
+            let max_break_minutes = max_break_for_day(hours.as_ref(), &work_patterns, *d);
+
+            if let Some(sess) = sess {
+                check_in_at = sess.check_in_at;
+                check_out_at = sess.check_out_at;
+
+                if let Some(ci) = sess.check_in_at {
+                    // hitung late berdasarkan jam masuk efektif (timezone app_settings):
+                    // override user/kelompok, atau calendar expected_start
+                    if let Some(es) = late_after {
+                        let naive = d.and_time(es);
+                        let expected_local = tz
+                            .from_local_datetime(&naive)
+                            .single()
+                            .unwrap_or_else(|| tz.from_utc_datetime(&naive));
+                        let expected_dt = expected_local.with_timezone(&Utc);
+
+                        let lm = (ci - expected_dt).num_minutes();
+                        let lm = if lm > 0 { lm } else { 0 };
+                        late_minutes = Some(lm);
+                        total_late_minutes += lm;
+                    }
+
+                    // FLEXI: durasi kerja minimal
+                    if let (Some(h), Some(co)) = (&hours, sess.check_out_at)
+                        && let Some(required) = required_minutes_for_day(h, day_type)
+                    {
+                        // durasi dari segmen (shift terpisah), fallback pasangan check-in/out
+                        let worked = sess.worked_minutes.unwrap_or((co - ci).num_minutes());
+                        let sm = (required - worked).max(0);
+                        short_minutes = Some(sm);
+                        total_short_minutes += sm;
+                    }
+
+                    // hadir butuh check-in; checkout optional (missing checkout kena penalty)
+                    credit_present = attendance_credit(sess, policy.missing_checkout_penalty_pct);
+                    if sess.check_out_at.is_none() {
+                        missing_checkout_days += 1;
+                    }
+
+                    // istirahat melebihi batas: dihitung terlambat + potongan per menit
+                    if let Some((excess, penalty_pct)) = break_excess(
+                        max_break_minutes,
+                        sess.break_minutes,
+                        policy.late_penalty_per_minute_pct,
+                        policy.max_daily_penalty_pct,
+                    ) {
+                        if excess > 0 {
+                            late_minutes = Some(late_minutes.unwrap_or(0) + excess);
+                            total_late_minutes += excess;
+                            total_break_excess_minutes += excess;
+                            credit_present = (credit_present - penalty_pct / 100.0).max(0.0);
+                        }
+                        break_excess_minutes = Some(excess);
+                    }
+                }
+            }
+
+            let credit = credit_present;
+            earned_credit += credit;
+
+            if credit > 0.0 {
+                present_days += 1;
+            } else {
+                absent_days += 1;
+            }
+
+            // note: kalau WFH/WFA, tampilkan itu, selain itu pakai day_type
+            let note = match event_leave_raw {
+                Some(crate::constants::AttendanceLeaveType::Wfh) => "WFH".to_string(),
+                Some(crate::constants::AttendanceLeaveType::Wfa) => "WFA".to_string(),
+                _ => format!("{:?}", day_type).to_uppercase(),
+            };
+
+            days.push(crate::dtos::tukin::TukinDayBreakdownDto {
+                work_date: *d,
+                expected_unit: 1.0,
+                earned_credit: credit,
+                is_duty_schedule: false,
+                duty_schedule_id: None,
+                check_in_at,
+                check_out_at,
+                late_minutes,
+                short_minutes,
+                worked_minutes: sess.and_then(|s| s.worked_minutes),
+                segments: session_segments(sess),
+                break_minutes: sess.and_then(|s| s.break_minutes),
+                break_excess_minutes,
+                leave_type: None,
+                leave_credit: None,
+                note: Some(note),
+            });
+        }
+
+        let ratio = if expected_units > 0.0 {
+            earned_credit / expected_units
+        } else {
+            0.0
+        };
+        let final_tukin = ((base_tukin as f64) * ratio).round() as i64;
+
+        result.push(TukinUserSummaryDto {
+            user_id: u.id,
+            satker_id: u.satker_id,
+            nrp: u.nrp,
+            full_name: u.full_name,
+            month: month.clone(),
+            policy_id: policy.id,
+            base_tukin,
+            expected_units,
+            earned_credit,
+            attendance_ratio: ratio,
+            final_tukin,
+            present_days,
+            absent_days,
+            missing_checkout_days,
+            duty_present,
+            duty_absent,
+            total_late_minutes,
+            total_short_minutes,
+            total_break_excess_minutes,
+            days,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Hitung & simpan tukin bulan `month` (YYYY-MM) ke tabel cache, lalu kembalikan hasilnya.
+/// Kalau `force = false` dan cache sudah ada, cache dikembalikan apa adanya.
+pub async fn generate_tukin_calculations(
+    db: &DBClient,
+    tz: Tz,
+    month: &str,
+    satker_id_scoped: Option<Uuid>,
+    user_id_scoped: Option<Uuid>,
+    force: bool,
+) -> Result<Vec<TukinCalculationRowDto>, HttpError> {
+    let (month_start, _) = parse_month(month)?;
+
+    // kalau tidak force dan cache ada, kembalikan cache
+    if !force {
+        let cached = db
+            .list_tukin_calculations(month_start, satker_id_scoped, user_id_scoped)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        if !cached.is_empty() {
+            return Ok(cached);
+        }
+    }
+
+    let summaries =
+        compute_tukin_summaries(db, tz, month.to_string(), satker_id_scoped, user_id_scoped)
+            .await?;
+
+    // upsert semua (hasil upsert Dto mentah tidak dipakai)
+    for s in summaries {
+        let breakdown = json!({
+            "month": s.month,
+            "present_days": s.present_days,
+            "absent_days": s.absent_days,
+            "missing_checkout_days": s.missing_checkout_days,
+            "duty_present": s.duty_present,
+            "duty_absent": s.duty_absent,
+            "total_late_minutes": s.total_late_minutes,
+            "total_short_minutes": s.total_short_minutes,
+            "total_break_excess_minutes": s.total_break_excess_minutes,
+            "days": s.days,
+        });
+
+        db.upsert_tukin_calculation(TukinCalculationUpsert {
+            month: month_start,
+            satker_id: s.satker_id,
+            user_id: s.user_id,
+            policy_id: s.policy_id,
+            base_tukin: s.base_tukin,
+            expected_units: s.expected_units,
+            earned_credit: s.earned_credit,
+            attendance_ratio: s.attendance_ratio,
+            final_tukin: s.final_tukin,
+            breakdown,
+        })
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    }
+
+    // ✅ ambil ulang dari cache pakai JOIN (RowDto) supaya nama/NRP/pangkat muncul
+    db.list_tukin_calculations(month_start, satker_id_scoped, user_id_scoped)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::work_pattern::hours_from_satker_pattern;
+
+    fn session(work_date: NaiveDate, status: &str) -> AttendanceRekapDto {
+        let check_in_at = tz_utc(work_date, 0, 55);
+        AttendanceRekapDto {
+            work_date,
+            status: status.to_string(),
+            check_in_at: Some(check_in_at),
+            check_out_at: Some(check_in_at + Duration::hours(8)),
+            ..Default::default()
+        }
+    }
+
+    fn tz_utc(d: NaiveDate, h: u32, m: u32) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&d.and_hms_opt(h, m, 0).unwrap())
+    }
+
+    /// Kredit satu hari kerja normal seperti di `compute_tukin_summaries`.
+    fn day_credit(sessions: Vec<AttendanceRekapDto>, d: NaiveDate) -> f64 {
+        sessions_by_work_date(sessions)
+            .get(&d)
+            .map(|s| attendance_credit(s, 25.0))
+            .unwrap_or(0.0)
+    }
+
+    #[test]
+    fn invalidated_session_counts_as_absent() {
+        let d = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+
+        assert_eq!(day_credit(vec![session(d, "CLOSED")], d), 1.0);
+        assert_eq!(day_credit(vec![session(d, "INVALID")], d), 0.0);
+    }
+
+    #[test]
+    fn invalidation_lowers_attendance_ratio() {
+        let days: Vec<NaiveDate> = (2..=6)
+            .map(|day| NaiveDate::from_ymd_opt(2026, 3, day).unwrap())
+            .collect();
+        let ratio = |sessions: Vec<AttendanceRekapDto>| {
+            let map = sessions_by_work_date(sessions);
+            let earned: f64 = days
+                .iter()
+                .map(|d| {
+                    map.get(d)
+                        .map(|s| attendance_credit(s, 25.0))
+                        .unwrap_or(0.0)
+                })
+                .sum();
+            earned / days.len() as f64
+        };
+
+        let before = days.iter().map(|d| session(*d, "CLOSED")).collect();
+        let after = days
+            .iter()
+            .enumerate()
+            .map(|(i, d)| session(*d, if i == 0 { "INVALID" } else { "CLOSED" }))
+            .collect();
+
+        assert_eq!(ratio(before), 1.0);
+        assert_eq!(ratio(after), 0.8);
+    }
+
+    #[test]
+    fn missing_checkout_is_penalised() {
+        let d = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let mut open = session(d, "OPEN");
+        open.check_out_at = None;
+
+        assert_eq!(attendance_credit(&open, 25.0), 0.75);
+        open.check_in_at = None;
+        assert_eq!(attendance_credit(&open, 25.0), 0.0);
+    }
+
+    fn pattern(effective_from: NaiveDate, max_break_minutes: Option<i16>) -> SatkerWorkPattern {
+        SatkerWorkPattern {
+            id: Uuid::nil(),
+            satker_id: Uuid::nil(),
+            effective_from,
+            mon_work: true,
+            tue_work: true,
+            wed_work: true,
+            thu_work: true,
+            fri_work: true,
+            sat_work: false,
+            sun_work: false,
+            work_start: chrono::NaiveTime::from_hms_opt(7, 30, 0).unwrap(),
+            work_end: chrono::NaiveTime::from_hms_opt(15, 30, 0).unwrap(),
+            half_day_end: None,
+            max_segments: 2,
+            max_break_minutes,
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn max_break_comes_from_override_then_satker_pattern() {
+        let march = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let patterns = [
+            pattern(NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(), Some(60)),
+            pattern(march, Some(45)),
+        ];
+        let d = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+
+        assert_eq!(max_break_for_day(None, &patterns, d), Some(45));
+        assert_eq!(
+            max_break_for_day(
+                None,
+                &patterns,
+                NaiveDate::from_ymd_opt(2026, 2, 2).unwrap()
+            ),
+            Some(60)
+        );
+
+        // override menggantikan pattern, termasuk override tanpa batas istirahat
+        let limited = hours_from_satker_pattern(&pattern(march, Some(30)));
+        let unlimited = hours_from_satker_pattern(&pattern(march, None));
+        assert_eq!(max_break_for_day(Some(&limited), &patterns, d), Some(30));
+        assert_eq!(max_break_for_day(Some(&unlimited), &patterns, d), None);
+    }
+
+    #[test]
+    fn break_over_cap_is_penalised_per_minute_up_to_daily_max() {
+        assert_eq!(break_excess(Some(60), Some(75), 0.5, 5.0), Some((15, 5.0)));
+        assert_eq!(break_excess(Some(60), Some(64), 0.5, 5.0), Some((4, 2.0)));
+        assert_eq!(break_excess(Some(60), Some(45), 0.5, 5.0), Some((0, 0.0)));
+        // tanpa batas atau tanpa istirahat yang selesai: tidak dinilai
+        assert_eq!(break_excess(None, Some(120), 0.5, 5.0), None);
+        assert_eq!(break_excess(Some(60), None, 0.5, 5.0), None);
+    }
+}
@@ -1,79 +1,236 @@
 use axum::extract::Multipart;
-use chrono::{Datelike, Utc};
-use std::path::{Path, PathBuf};
-use tokio::{fs, io::AsyncWriteExt};
+use chrono::{Datelike, NaiveDate, Utc};
 use uuid::Uuid;
 
 use crate::error::HttpError;
+use crate::services::image_pipeline::{
+    ImageVariant, ProcessedImage, SELFIE_VARIANTS, process_image, variant_path,
+};
+use crate::services::storage::Storage;
 
 const FILE_FIELD_NAME: &str = "file";
 const SELFIE_MAX_BYTES: usize = 3 * 1024 * 1024; // 3MB
+const FACE_PHOTO_MAX_BYTES: usize = 3 * 1024 * 1024; // 3MB
 const PROFILE_PHOTO_MAX_BYTES: usize = 2 * 1024 * 1024; // 2MB
+const ANNOUNCEMENT_DOC_MAX_BYTES: usize = 10 * 1024 * 1024; // 10MB
 
 const IMAGE_CONTENT_TYPES: [&str; 3] = ["image/jpeg", "image/jpg", "image/png"];
+const DOCUMENT_CONTENT_TYPES: [&str; 4] =
+    ["application/pdf", "image/jpeg", "image/jpg", "image/png"];
+
+pub const ANNOUNCEMENT_FOLDER: &str = "announcements";
+pub const SELFIE_FOLDER: &str = "selfies";
+pub const FACE_FOLDER: &str = "faces";
+pub const PROFILE_FOLDER: &str = "profiles";
 
 enum ExtensionPolicy {
     Fixed(&'static str),
-    PngOrJpeg {
-        default_ext: &'static str,
-        png_ext: &'static str,
-    },
+    /// Dokumen: ekstensi mengikuti content-type (pdf/png/jpg).
+    Document,
+}
+
+/// Hasil simpan file upload (key + metadata untuk disimpan di DB).
+#[derive(Debug, Clone)]
+pub struct StoredUpload {
+    pub object_key: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+}
+
+/// Foto yang sudah dinormalisasi (lihat `image_pipeline`) beserta variannya.
+pub struct StoredImage {
+    pub object_key: String,
+    pub size_bytes: i64,
+    pub image: ProcessedImage,
 }
 
+/// File upload yang sudah dibaca, belum disimpan.
+struct ReceivedUpload {
+    path: String,
+    file_name: String,
+    content_type: String,
+    bytes: Vec<u8>,
+}
+
+/// Selfie absensi: JPEG tanpa metadata + varian thumb/medium.
 pub async fn save_selfie_upload(
-    upload_dir: &Path,
+    storage: &Storage,
     user_id: Uuid,
     mp: Multipart,
-) -> Result<String, HttpError> {
+) -> Result<StoredImage, HttpError> {
     save_image_upload(
-        upload_dir,
-        "selfies",
+        storage,
+        SELFIE_FOLDER,
         user_id,
         mp,
         SELFIE_MAX_BYTES,
-        ExtensionPolicy::Fixed("jpg"),
+        &SELFIE_VARIANTS,
     )
     .await
 }
 
 pub async fn save_profile_photo_upload(
-    upload_dir: &Path,
+    storage: &Storage,
     user_id: Uuid,
     mp: Multipart,
 ) -> Result<String, HttpError> {
     save_image_upload(
-        upload_dir,
-        "profiles",
+        storage,
+        PROFILE_FOLDER,
         user_id,
         mp,
         PROFILE_PHOTO_MAX_BYTES,
-        ExtensionPolicy::PngOrJpeg {
-            default_ext: "jpg",
-            png_ext: "png",
-        },
+        &[],
+    )
+    .await
+    .map(|u| u.object_key)
+}
+
+/// Foto referensi wajah (enrolment); `user_id` = pemilik wajah, bukan pengunggah.
+pub async fn save_face_photo_upload(
+    storage: &Storage,
+    user_id: Uuid,
+    mp: Multipart,
+) -> Result<String, HttpError> {
+    save_image_upload(storage, FACE_FOLDER, user_id, mp, FACE_PHOTO_MAX_BYTES, &[])
+        .await
+        .map(|u| u.object_key)
+}
+
+/// Lampiran pengumuman (surat edaran): PDF atau gambar, max 10MB.
+pub async fn save_announcement_document_upload(
+    storage: &Storage,
+    user_id: Uuid,
+    mp: Multipart,
+) -> Result<StoredUpload, HttpError> {
+    save_upload(
+        storage,
+        ANNOUNCEMENT_FOLDER,
+        user_id,
+        mp,
+        ANNOUNCEMENT_DOC_MAX_BYTES,
+        &DOCUMENT_CONTENT_TYPES,
+        ExtensionPolicy::Document,
     )
     .await
 }
 
+/// Tipe file dari magic bytes (PDF/PNG/JPEG); None kalau bukan salah satunya.
+fn sniff_document_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else {
+        None
+    }
+}
+
+fn document_ext(content_type: Option<&str>) -> &'static str {
+    match content_type {
+        Some("application/pdf") => "pdf",
+        Some("image/png") => "png",
+        _ => "jpg",
+    }
+}
+
+async fn save_upload(
+    storage: &Storage,
+    folder: &str,
+    user_id: Uuid,
+    mp: Multipart,
+    max_bytes: usize,
+    allowed_content_types: &[&str],
+    ext_policy: ExtensionPolicy,
+) -> Result<StoredUpload, HttpError> {
+    let upload = receive_upload(
+        folder,
+        user_id,
+        mp,
+        max_bytes,
+        allowed_content_types,
+        ext_policy,
+    )
+    .await?;
+
+    // content-type dari client tidak dipercaya: isi file harus cocok
+    if sniff_document_type(&upload.bytes) != Some(upload.content_type.as_str()) {
+        return Err(HttpError::bad_request(
+            "isi file tidak sesuai dengan tipenya (harus pdf/jpg/png)".to_string(),
+        ));
+    }
+
+    let size_bytes = upload.bytes.len() as i64;
+    let object_key = storage
+        .put(&upload.path, upload.bytes, &upload.content_type)
+        .await?;
+
+    Ok(StoredUpload {
+        object_key,
+        file_name: upload.file_name,
+        content_type: upload.content_type,
+        size_bytes,
+    })
+}
+
+/// Foto: isi file di-decode sungguhan lalu disimpan sebagai JPEG bersih (+ varian).
 async fn save_image_upload(
-    upload_dir: &Path,
+    storage: &Storage,
+    folder: &str,
+    user_id: Uuid,
+    mp: Multipart,
+    max_bytes: usize,
+    variants: &'static [ImageVariant],
+) -> Result<StoredImage, HttpError> {
+    let upload = receive_upload(
+        folder,
+        user_id,
+        mp,
+        max_bytes,
+        &IMAGE_CONTENT_TYPES,
+        ExtensionPolicy::Fixed("jpg"),
+    )
+    .await?;
+
+    let bytes = upload.bytes;
+    let mut image = tokio::task::spawn_blocking(move || process_image(&bytes, variants))
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))??;
+
+    // varian dulu: begitu key utama ada, varian pasti sudah bisa diminta
+    for (variant, bytes) in std::mem::take(&mut image.variants) {
+        storage
+            .put(&variant_path(&upload.path, variant), bytes, "image/jpeg")
+            .await?;
+    }
+    let jpeg = std::mem::take(&mut image.jpeg);
+    let size_bytes = jpeg.len() as i64;
+    let object_key = storage.put(&upload.path, jpeg, "image/jpeg").await?;
+
+    Ok(StoredImage {
+        object_key,
+        size_bytes,
+        image,
+    })
+}
+
+async fn receive_upload(
     folder: &str,
     user_id: Uuid,
     mut mp: Multipart,
     max_bytes: usize,
+    allowed_content_types: &[&str],
     ext_policy: ExtensionPolicy,
-) -> Result<String, HttpError> {
+) -> Result<ReceivedUpload, HttpError> {
     let now = Utc::now();
     let date_path = format!("{:04}/{:02}/{:02}", now.year(), now.month(), now.day());
-    let base_dir: PathBuf = upload_dir.join(folder).join(date_path);
-
-    fs::create_dir_all(&base_dir)
-        .await
-        .map_err(|e| HttpError::server_error(e.to_string()))?;
 
     let file_id = Uuid::new_v4();
 
-    let mut saved_path: Option<PathBuf> = None;
+    let mut saved: Option<ReceivedUpload> = None;
 
     while let Some(field) = mp
         .next_field()
@@ -87,68 +244,83 @@ async fn save_image_upload(
 
         let content_type = field.content_type();
         if let Some(ct) = content_type {
-            let ok = IMAGE_CONTENT_TYPES.contains(&ct);
+            let ok = allowed_content_types.contains(&ct);
             if !ok {
-                return Err(HttpError::bad_request("file harus jpg/png".to_string()));
+                let msg = match ext_policy {
+                    ExtensionPolicy::Document => "file harus pdf/jpg/png",
+                    _ => "file harus jpg/png",
+                };
+                return Err(HttpError::bad_request(msg.to_string()));
             }
+        } else if matches!(ext_policy, ExtensionPolicy::Document) {
+            // dokumen wajib punya content-type agar ekstensi & mime saat diunduh konsisten
+            return Err(HttpError::bad_request(
+                "content-type file wajib diisi".to_string(),
+            ));
         }
 
         let ext = match &ext_policy {
             ExtensionPolicy::Fixed(ext) => *ext,
-            ExtensionPolicy::PngOrJpeg {
-                default_ext,
-                png_ext,
-            } => {
-                if content_type == Some("image/png") {
-                    *png_ext
-                } else {
-                    *default_ext
-                }
-            }
+            ExtensionPolicy::Document => document_ext(content_type),
         };
+        let stored_content_type = content_type
+            .unwrap_or("image/jpeg")
+            .replace("image/jpg", "image/jpeg");
+        let original_name = field
+            .file_name()
+            .map(sanitize_file_name)
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| format!("{}.{}", file_id, ext));
 
         let filename = format!("{}_{}.{}", user_id, file_id, ext);
-        let full_path = base_dir.join(&filename);
-
-        let mut f = fs::File::create(&full_path)
-            .await
-            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        let path = format!("{}/{}/{}", folder, date_path, filename);
 
-        let mut total: usize = 0;
+        // dibaca penuh ke memori (maks beberapa MB) lalu ditulis sekali ke storage
+        let mut bytes: Vec<u8> = Vec::new();
         let mut field = field;
         while let Some(chunk) = field
             .chunk()
             .await
             .map_err(|e| HttpError::bad_request(e.to_string()))?
         {
-            total += chunk.len();
-            if total > max_bytes {
-                let _ = fs::remove_file(&full_path).await;
+            if bytes.len() + chunk.len() > max_bytes {
                 return Err(HttpError::bad_request(format!(
                     "file terlalu besar (max {}MB)",
                     max_bytes / (1024 * 1024)
                 )));
             }
-            f.write_all(&chunk)
-                .await
-                .map_err(|e| HttpError::server_error(e.to_string()))?;
+            bytes.extend_from_slice(&chunk);
         }
 
-        f.flush()
-            .await
-            .map_err(|e| HttpError::server_error(e.to_string()))?;
-
-        saved_path = Some(full_path);
+        saved = Some(ReceivedUpload {
+            path,
+            file_name: original_name,
+            content_type: stored_content_type,
+            bytes,
+        });
         break;
     }
 
-    let full_path = saved_path.ok_or(HttpError::bad_request("part file wajib".to_string()))?;
+    saved.ok_or(HttpError::bad_request("part file wajib".to_string()))
+}
 
-    let rel = full_path
-        .strip_prefix(upload_dir)
-        .unwrap_or(&full_path)
-        .to_string_lossy()
-        .replace('\\', "/");
+/// Nama file asli hanya untuk ditampilkan: buang path & karakter kontrol.
+fn sanitize_file_name(raw: &str) -> String {
+    let base = raw.rsplit(['/', '\\']).next().unwrap_or(raw);
+    base.chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(200)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
 
-    Ok(format!("local://{}", rel))
+/// Tanggal upload dari path `<folder>/YYYY/MM/DD/<file>`; None kalau bukan pola tsb.
+pub fn upload_path_date(path: &str) -> Option<NaiveDate> {
+    let mut parts = path.split('/').skip(1);
+    let year = parts.next()?.parse::<i32>().ok()?;
+    let month = parts.next()?.parse::<u32>().ok()?;
+    let day = parts.next()?.parse::<u32>().ok()?;
+    parts.next()?;
+    NaiveDate::from_ymd_opt(year, month, day)
 }
@@ -0,0 +1,444 @@
+//! Kepemilikan device absensi: device pertama ter-bind otomatis, device berikutnya
+//! (ganti HP / device yang terlanjur dipakai akun lain) lewat permintaan yang disetujui admin.
+
+use uuid::Uuid;
+
+use crate::constants::AttendanceEventType;
+use crate::database::user_device::{
+    UserDeviceRepo, assign_user_device, count_user_devices, delete_user_device,
+    lock_device_binding_request, lock_user_devices, update_device_binding_status, user_has_device,
+};
+use crate::db::DBClient;
+use crate::dtos::user_device::{
+    ApproveDeviceBindingReq, CreateDeviceBindingReq, DEVICE_REQUEST_APPROVED,
+    DEVICE_REQUEST_CANCELED, DEVICE_REQUEST_PENDING, DEVICE_REQUEST_REJECTED, DeviceInfo,
+};
+use crate::error::HttpError;
+use crate::middleware::auth_middleware::UserClaims;
+use crate::services::attestation_verifier::parse_device_public_key;
+use crate::services::authorization::ensure_can_access_satker;
+use crate::services::metrics::{RejectReason, record_attendance_rejection};
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Kunci attestation dari aplikasi harus kunci EC P-256 yang valid sebelum disimpan.
+fn ensure_valid_public_key(info: &DeviceInfo) -> Result<(), HttpError> {
+    if let Some(key) = &info.public_key {
+        parse_device_public_key(key)?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum DeviceOwnership {
+    /// Sudah ter-bind ke user ini.
+    Owned,
+    /// Ter-bind ke akun lain.
+    OtherUser,
+    /// Belum ter-bind ke siapa pun; boleh bind otomatis kalau ini device pertama user.
+    Unbound,
+}
+
+fn device_ownership(owner: Option<Uuid>, user_id: Uuid) -> DeviceOwnership {
+    match owner {
+        Some(owner) if owner == user_id => DeviceOwnership::Owned,
+        Some(_) => DeviceOwnership::OtherUser,
+        None => DeviceOwnership::Unbound,
+    }
+}
+
+/// `replace_device_id` harus device lain milik user, bukan device yang sedang diajukan.
+fn ensure_replace_differs(requested: &str, replace: Option<&str>) -> Result<(), HttpError> {
+    if replace == Some(requested) {
+        return Err(HttpError::bad_request(
+            "replace_device_id: tidak boleh sama dengan device yang diajukan",
+        ));
+    }
+    Ok(())
+}
+
+/// Batas device saat approve. Pendaftaran kunci untuk device yang sudah milik user
+/// (`already_bound`) tidak menambah jumlah device.
+fn ensure_device_capacity(
+    already_bound: bool,
+    count: i64,
+    max_devices: i64,
+) -> Result<(), HttpError> {
+    if !already_bound && count >= max_devices {
+        return Err(HttpError::bad_request(format!(
+            "user sudah memiliki {} device (maksimal {}), isi replace_device_id untuk mengganti device lama",
+            count, max_devices
+        )));
+    }
+    Ok(())
+}
+
+/// Pastikan device milik user yang absen. Device pertama user di-bind otomatis;
+/// device lain ditolak dan harus diajukan lewat permintaan device baru.
+pub async fn ensure_device_owned(
+    db: &DBClient,
+    event: AttendanceEventType,
+    user_id: Uuid,
+    info: &DeviceInfo,
+) -> Result<(), HttpError> {
+    ensure_valid_public_key(info)?;
+
+    let bound = db
+        .find_user_device(&info.device_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    match device_ownership(bound.map(|d| d.user_id), user_id) {
+        DeviceOwnership::Owned => {
+            db.touch_user_device(info)
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?;
+            Ok(())
+        }
+        DeviceOwnership::OtherUser => {
+            record_attendance_rejection(event, RejectReason::DeviceNotOwned);
+            Err(HttpError::bad_request(
+                "device ini terdaftar atas akun lain, ajukan permintaan device baru ke admin satker",
+            ))
+        }
+        DeviceOwnership::Unbound => {
+            let bound = db
+                .bind_first_user_device(user_id, info)
+                .await
+                .map_err(|e| HttpError::server_error(e.to_string()))?;
+            if bound {
+                return Ok(());
+            }
+            record_attendance_rejection(event, RejectReason::DeviceNotOwned);
+            Err(HttpError::bad_request(
+                "device belum terdaftar untuk akun anda, ajukan permintaan device baru ke admin satker",
+            ))
+        }
+    }
+}
+
+/// Ajukan device baru untuk diri sendiri.
+pub async fn create_device_binding_request(
+    db: &DBClient,
+    claims: &UserClaims,
+    req: CreateDeviceBindingReq,
+) -> Result<Uuid, HttpError> {
+    let info = DeviceInfo {
+        device_id: req.device_id.trim().to_string(),
+        device_model: non_empty(req.device_model),
+        android_version: non_empty(req.android_version),
+        app_build: non_empty(req.app_build),
+        client_version: non_empty(req.client_version),
+        public_key: non_empty(req.public_key),
+    };
+    if info.device_id.is_empty() {
+        return Err(HttpError::bad_request("device_id wajib"));
+    }
+    ensure_valid_public_key(&info)?;
+
+    let bound = db
+        .find_user_device(&info.device_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let previous_user_id = match bound {
+        // device lama tanpa kunci attestation: kunci didaftarkan lewat persetujuan admin
+        Some(d)
+            if d.user_id == claims.user_id
+                && d.public_key_registered_at.is_none()
+                && info.public_key.is_some() =>
+        {
+            None
+        }
+        Some(d) if d.user_id == claims.user_id => {
+            return Err(HttpError::bad_request(
+                "device sudah terdaftar untuk akun anda",
+            ));
+        }
+        Some(d) => Some(d.user_id),
+        None => None,
+    };
+
+    let reason = non_empty(req.reason);
+    db.create_device_binding_request(
+        claims.user_id,
+        claims.satker_id,
+        &info,
+        reason.as_deref(),
+        previous_user_id,
+    )
+    .await
+    .map_err(|e| {
+        if e.as_database_error()
+            .is_some_and(|d| d.is_unique_violation())
+        {
+            HttpError::bad_request("permintaan untuk device ini masih menunggu persetujuan")
+        } else {
+            HttpError::server_error(e.to_string())
+        }
+    })
+}
+
+/// Setujui permintaan device baru. Kalau user sudah mencapai `max_devices`,
+/// `replace_device_id` (device lama user) wajib diisi dan ikut dilepas.
+pub async fn approve_device_binding_request(
+    db: &DBClient,
+    claims: &UserClaims,
+    id: Uuid,
+    req: ApproveDeviceBindingReq,
+    max_devices: i64,
+) -> Result<(), HttpError> {
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let request = lock_device_binding_request(&mut tx, id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("permintaan device tidak ditemukan"))?;
+
+    ensure_can_access_satker(claims, request.satker_id)?;
+
+    if request.status != DEVICE_REQUEST_PENDING {
+        return Err(HttpError::bad_request(format!(
+            "permintaan dengan status {} tidak bisa di-approve",
+            request.status
+        )));
+    }
+
+    lock_user_devices(&mut tx, request.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let replace = non_empty(req.replace_device_id);
+    ensure_replace_differs(&request.device_id, replace.as_deref())?;
+    if let Some(old) = &replace {
+        let removed = delete_user_device(&mut tx, request.user_id, old)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+        if !removed {
+            return Err(HttpError::bad_request(
+                "replace_device_id: device tidak terdaftar untuk user ini",
+            ));
+        }
+    }
+
+    let already_bound = user_has_device(&mut tx, request.user_id, &request.device_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let count = count_user_devices(&mut tx, request.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    ensure_device_capacity(already_bound, count, max_devices)?;
+
+    let info = DeviceInfo {
+        device_id: request.device_id.clone(),
+        device_model: request.device_model.clone(),
+        android_version: request.android_version.clone(),
+        app_build: request.app_build.clone(),
+        client_version: request.client_version.clone(),
+        public_key: request.public_key.clone(),
+    };
+    assign_user_device(&mut tx, request.user_id, &info, claims.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    update_device_binding_status(
+        &mut tx,
+        request.id,
+        DEVICE_REQUEST_APPROVED,
+        claims.user_id,
+        None,
+        replace.as_deref(),
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum DeviceBindingAction {
+    Cancel,
+    Reject,
+}
+
+/// Batalkan (pemohon) atau tolak (admin satker) permintaan yang masih PENDING.
+pub async fn respond_device_binding_request(
+    db: &DBClient,
+    claims: &UserClaims,
+    id: Uuid,
+    action: DeviceBindingAction,
+    reject_reason: Option<String>,
+) -> Result<(), HttpError> {
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let request = lock_device_binding_request(&mut tx, id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("permintaan device tidak ditemukan"))?;
+
+    let (to_status, verb) = match action {
+        DeviceBindingAction::Cancel => {
+            if claims.user_id != request.user_id {
+                return Err(HttpError::unauthorized("forbidden"));
+            }
+            (DEVICE_REQUEST_CANCELED, "dibatalkan")
+        }
+        DeviceBindingAction::Reject => {
+            ensure_can_access_satker(claims, request.satker_id)?;
+            (DEVICE_REQUEST_REJECTED, "ditolak")
+        }
+    };
+
+    if request.status != DEVICE_REQUEST_PENDING {
+        return Err(HttpError::bad_request(format!(
+            "permintaan dengan status {} tidak bisa {}",
+            request.status, verb
+        )));
+    }
+
+    let reject_reason = non_empty(reject_reason);
+    update_device_binding_status(
+        &mut tx,
+        request.id,
+        to_status,
+        claims.user_id,
+        reject_reason.as_deref(),
+        None,
+    )
+    .await
+    .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Pindahkan device ke user lain (admin). User tujuan tidak boleh melebihi `max_devices`.
+/// Info device (model, versi, build) dan kunci attestation ikut pindah: kunci milik perangkat,
+/// bukan akun, jadi user tujuan tidak perlu mendaftarkan kunci lagi.
+pub async fn transfer_user_device(
+    db: &DBClient,
+    claims: &UserClaims,
+    device_row_id: Uuid,
+    to_user_id: Uuid,
+    max_devices: i64,
+) -> Result<(), HttpError> {
+    let device = db
+        .find_user_device_by_id(device_row_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .ok_or_else(|| HttpError::bad_request("device tidak ditemukan"))?;
+    ensure_can_access_satker(claims, device.satker_id)?;
+
+    if device.user_id == to_user_id {
+        return Err(HttpError::bad_request("device sudah milik user tersebut"));
+    }
+
+    let mut tx = db
+        .pool
+        .begin()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    lock_user_devices(&mut tx, to_user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    let count = count_user_devices(&mut tx, to_user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if count >= max_devices {
+        return Err(HttpError::bad_request(format!(
+            "user tujuan sudah memiliki {} device (maksimal {})",
+            count, max_devices
+        )));
+    }
+
+    // public_key None: upsert mempertahankan kunci & waktu pendaftarannya yang tersimpan
+    let info = DeviceInfo {
+        device_id: device.device_id,
+        device_model: device.device_model,
+        android_version: device.android_version,
+        app_build: device.app_build,
+        client_version: device.client_version,
+        public_key: None,
+    };
+    assign_user_device(&mut tx, to_user_id, &info, claims.user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_device_is_unbound_and_may_auto_bind() {
+        let user = Uuid::new_v4();
+        assert_eq!(device_ownership(None, user), DeviceOwnership::Unbound);
+        assert_eq!(device_ownership(Some(user), user), DeviceOwnership::Owned);
+    }
+
+    #[test]
+    fn device_bound_to_another_user_is_not_owned() {
+        let user = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        assert_eq!(
+            device_ownership(Some(other), user),
+            DeviceOwnership::OtherUser
+        );
+    }
+
+    #[test]
+    fn approve_without_replace_at_max_devices_is_rejected() {
+        let err = ensure_device_capacity(false, 2, 2).unwrap_err();
+        assert!(err.message.contains("replace_device_id"));
+        assert!(ensure_device_capacity(false, 1, 2).is_ok());
+    }
+
+    #[test]
+    fn replace_device_must_differ_from_requested() {
+        assert!(ensure_replace_differs("hp-baru", Some("hp-baru")).is_err());
+        assert!(ensure_replace_differs("hp-baru", Some("hp-lama")).is_ok());
+        assert!(ensure_replace_differs("hp-baru", None).is_ok());
+    }
+
+    #[test]
+    fn key_registration_on_bound_device_does_not_count_toward_limit() {
+        assert!(ensure_device_capacity(true, 2, 2).is_ok());
+    }
+
+    #[test]
+    fn invalid_public_key_is_rejected() {
+        let info = DeviceInfo {
+            device_id: "hp-1".into(),
+            public_key: Some("bukan-kunci".into()),
+            ..Default::default()
+        };
+        assert!(ensure_valid_public_key(&info).is_err());
+        assert!(ensure_valid_public_key(&DeviceInfo::default()).is_ok());
+    }
+}
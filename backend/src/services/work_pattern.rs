@@ -0,0 +1,263 @@
+//! Jam kerja efektif per user: override user > override kelompok > work pattern satker.
+//! Override hanya mengganti jam; hari kerja/libur tetap dari kalender satker.
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::constants::{CalendarDayType, WorkPatternMode};
+use crate::database::user::UserRepo;
+use crate::database::work_group::WorkGroupRepo;
+use crate::database::work_pattern::{WorkPatternRepo, pick_effective_pattern};
+use crate::database::work_pattern_override::WorkPatternOverrideRepo;
+use crate::db::DBClient;
+use crate::dtos::work_pattern_override::{
+    EffectiveWorkHours, PATTERN_SOURCE_GROUP, PATTERN_SOURCE_SATKER, PATTERN_SOURCE_USER,
+    UpsertWorkPatternOverrideReq, UserWorkPatternOverrideRow, WorkPatternOverrideInput,
+};
+use crate::error::HttpError;
+use crate::models::SatkerWorkPattern;
+use crate::utils::time_parser::{parse_optional_time_field, parse_time_field};
+
+/// Batas atas segmen check-in/out per hari (sama dengan CHECK di DB).
+const MAX_SEGMENTS_LIMIT: i16 = 10;
+
+/// Default 1 (satu pasang check-in/out per hari).
+pub fn parse_max_segments(value: Option<i16>) -> Result<i16, HttpError> {
+    let v = value.unwrap_or(1);
+    if !(1..=MAX_SEGMENTS_LIMIT).contains(&v) {
+        return Err(HttpError::bad_request(format!(
+            "max_segments: harus 1..{}",
+            MAX_SEGMENTS_LIMIT
+        )));
+    }
+    Ok(v)
+}
+
+/// Batas atas menit istirahat per hari (sama dengan CHECK di DB).
+const MAX_BREAK_MINUTES_LIMIT: i16 = 480;
+
+/// Kosong = tanpa batas istirahat.
+pub fn parse_max_break_minutes(value: Option<i16>) -> Result<Option<i16>, HttpError> {
+    match value {
+        Some(v) if !(1..=MAX_BREAK_MINUTES_LIMIT).contains(&v) => Err(HttpError::bad_request(
+            format!("max_break_minutes: harus 1..{}", MAX_BREAK_MINUTES_LIMIT),
+        )),
+        _ => Ok(value),
+    }
+}
+
+pub fn build_work_pattern_override(
+    payload: UpsertWorkPatternOverrideReq,
+) -> Result<WorkPatternOverrideInput, HttpError> {
+    if payload.user_id.is_some() == payload.group_id.is_some() {
+        return Err(HttpError::bad_request(
+            "isi salah satu: user_id atau group_id",
+        ));
+    }
+    if let Some(to) = payload.effective_to
+        && to < payload.effective_from
+    {
+        return Err(HttpError::bad_request(
+            "effective_to: harus >= effective_from",
+        ));
+    }
+
+    let mode = payload.mode.unwrap_or(WorkPatternMode::Fixed);
+    let work_start = parse_time_field(&payload.work_start, "work_start")?;
+    let work_end = parse_time_field(&payload.work_end, "work_end")?;
+    if work_end <= work_start {
+        return Err(HttpError::bad_request("work_end: harus > work_start"));
+    }
+
+    let core_start = parse_optional_time_field(payload.core_start.as_deref(), "core_start")?;
+    let core_end = parse_optional_time_field(payload.core_end.as_deref(), "core_end")?;
+
+    let (core_start, core_end, required_minutes) = match mode {
+        WorkPatternMode::Fixed => {
+            if core_start.is_some() || core_end.is_some() || payload.required_minutes.is_some() {
+                return Err(HttpError::bad_request(
+                    "core_start/core_end/required_minutes hanya untuk mode FLEXI",
+                ));
+            }
+            (None, None, None)
+        }
+        WorkPatternMode::Flexi => {
+            let core_start = core_start
+                .ok_or_else(|| HttpError::bad_request("core_start: wajib untuk mode FLEXI"))?;
+            let core_end = core_end
+                .ok_or_else(|| HttpError::bad_request("core_end: wajib untuk mode FLEXI"))?;
+            let required = payload.required_minutes.ok_or_else(|| {
+                HttpError::bad_request("required_minutes: wajib untuk mode FLEXI")
+            })?;
+
+            if core_start < work_start || core_end > work_end {
+                return Err(HttpError::bad_request(
+                    "jam inti harus berada di antara work_start dan work_end",
+                ));
+            }
+            if core_end <= core_start {
+                return Err(HttpError::bad_request("core_end: harus > core_start"));
+            }
+
+            let core_minutes = (core_end - core_start).num_minutes();
+            let band_minutes = (work_end - work_start).num_minutes();
+            if i64::from(required) < core_minutes || i64::from(required) > band_minutes {
+                return Err(HttpError::bad_request(format!(
+                    "required_minutes: harus {}..{} menit",
+                    core_minutes, band_minutes
+                )));
+            }
+            (Some(core_start), Some(core_end), Some(required))
+        }
+    };
+
+    let max_segments = parse_max_segments(payload.max_segments)?;
+    let max_break_minutes = parse_max_break_minutes(payload.max_break_minutes)?;
+
+    let note = payload
+        .note
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+
+    Ok(WorkPatternOverrideInput {
+        user_id: payload.user_id,
+        group_id: payload.group_id,
+        effective_from: payload.effective_from,
+        effective_to: payload.effective_to,
+        mode,
+        work_start,
+        work_end,
+        core_start,
+        core_end,
+        required_minutes,
+        max_segments,
+        max_break_minutes,
+        note,
+    })
+}
+
+/// Satker override = satker user / kelompok target-nya.
+pub async fn resolve_override_satker(
+    db: &DBClient,
+    input: &WorkPatternOverrideInput,
+) -> Result<Uuid, HttpError> {
+    if let Some(group_id) = input.group_id {
+        let group = db
+            .find_work_group(group_id)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))?
+            .ok_or_else(|| HttpError::bad_request("group_id: kelompok tidak ditemukan"))?;
+        return Ok(group.satker_id);
+    }
+
+    let user_id = input
+        .user_id
+        .ok_or_else(|| HttpError::bad_request("isi salah satu: user_id atau group_id"))?;
+    let user = db
+        .find_user_by_id(user_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .filter(|u| u.is_active)
+        .ok_or_else(|| HttpError::bad_request("user_id: user aktif tidak ditemukan"))?;
+
+    Ok(user.satker_id)
+}
+
+pub fn map_override_save_error(e: sqlx::Error) -> HttpError {
+    if e.as_database_error()
+        .is_some_and(|d| d.is_unique_violation())
+    {
+        return HttpError::bad_request(
+            "sudah ada override jam kerja dengan effective_from yang sama",
+        );
+    }
+    HttpError::server_error(e.to_string())
+}
+
+/// Override yang berlaku di `date`: override user menang atas kelompok,
+/// lalu effective_from terbaru. `rows` urut ASC by effective_from.
+pub fn pick_effective_override(
+    rows: &[UserWorkPatternOverrideRow],
+    date: NaiveDate,
+) -> Option<&UserWorkPatternOverrideRow> {
+    let mut covering = rows
+        .iter()
+        .filter(|o| o.effective_from <= date && o.effective_to.is_none_or(|to| to >= date));
+
+    covering
+        .clone()
+        .rfind(|o| o.group_id.is_none())
+        .or_else(|| covering.next_back())
+}
+
+pub fn hours_from_override(o: &UserWorkPatternOverrideRow) -> EffectiveWorkHours {
+    EffectiveWorkHours {
+        source: if o.group_id.is_some() {
+            PATTERN_SOURCE_GROUP
+        } else {
+            PATTERN_SOURCE_USER
+        },
+        override_id: Some(o.id),
+        mode: o.mode,
+        work_start: o.work_start,
+        work_end: o.work_end,
+        core_start: o.core_start,
+        core_end: o.core_end,
+        required_minutes: o.required_minutes,
+        max_segments: o.max_segments,
+        max_break_minutes: o.max_break_minutes,
+    }
+}
+
+pub fn hours_from_satker_pattern(p: &SatkerWorkPattern) -> EffectiveWorkHours {
+    EffectiveWorkHours {
+        source: PATTERN_SOURCE_SATKER,
+        override_id: None,
+        mode: WorkPatternMode::Fixed,
+        work_start: p.work_start,
+        work_end: p.work_end,
+        core_start: None,
+        core_end: None,
+        required_minutes: None,
+        max_segments: p.max_segments,
+        max_break_minutes: p.max_break_minutes,
+    }
+}
+
+/// Durasi kerja wajib (menit) di hari tsb untuk mode FLEXI; hari setengah dihitung separuh.
+pub fn required_minutes_for_day(
+    hours: &EffectiveWorkHours,
+    day_type: CalendarDayType,
+) -> Option<i64> {
+    let required = i64::from(hours.required_minutes?);
+    Some(match day_type {
+        CalendarDayType::HalfDay => required / 2,
+        _ => required,
+    })
+}
+
+/// Jam kerja efektif user di satu tanggal (dipakai check-in dan apel).
+pub async fn resolve_work_hours(
+    db: &DBClient,
+    satker_id: Uuid,
+    user_id: Uuid,
+    date: NaiveDate,
+) -> Result<EffectiveWorkHours, HttpError> {
+    let overrides = db
+        .list_user_work_pattern_overrides(user_id, date, date)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    if let Some(o) = pick_effective_override(&overrides, date) {
+        return Ok(hours_from_override(o));
+    }
+
+    let patterns = db
+        .list_work_patterns(satker_id)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    let p = pick_effective_pattern(&patterns, date).ok_or_else(|| {
+        HttpError::bad_request("work pattern belum diset untuk satker ini".to_string())
+    })?;
+
+    Ok(hours_from_satker_pattern(&p))
+}
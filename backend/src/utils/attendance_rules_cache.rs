@@ -0,0 +1,90 @@
+use crate::AppState;
+use crate::database::settings::{SettingsRepo, attendance_rules_key};
+use crate::dtos::settings::{AttendanceRules, AttendanceRulesOverride};
+use crate::error::HttpError;
+use crate::services::metrics::record_redis_error;
+use redis::AsyncCommands;
+use uuid::Uuid;
+
+const RULES_CACHE_TTL_SECS: u64 = 300; // 5 minutes
+
+fn cache_key(setting_key: &str) -> String {
+    format!("app:settings:{setting_key}")
+}
+
+fn parse_override(setting_key: &str, raw: &str) -> AttendanceRulesOverride {
+    serde_json::from_str(raw).unwrap_or_else(|e| {
+        tracing::warn!("setting {setting_key} tidak valid, pakai default: {e}");
+        AttendanceRulesOverride::default()
+    })
+}
+
+/// Override aturan absensi (global bila `satker_id` None) dari Redis dengan fallback DB.
+/// Setting yang belum ada di-cache sebagai `{}` supaya tidak selalu jatuh ke DB.
+pub async fn get_rules_override_cached(
+    app_state: &AppState,
+    satker_id: Option<Uuid>,
+) -> Result<AttendanceRulesOverride, HttpError> {
+    let setting_key = attendance_rules_key(satker_id);
+    let key = cache_key(&setting_key);
+
+    {
+        let mut conn = app_state.redis_client.clone();
+        match conn.get::<_, Option<String>>(&key).await {
+            Ok(Some(raw)) => return Ok(parse_override(&setting_key, &raw)),
+            Ok(None) => {}
+            Err(_) => record_redis_error("attendance_rules_cache"),
+        }
+    }
+
+    let raw = app_state
+        .db_client
+        .get_setting(&setting_key)
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?
+        .unwrap_or_else(|| "{}".to_string());
+
+    {
+        let mut conn = app_state.redis_client.clone();
+        if conn
+            .set_ex::<_, _, ()>(&key, &raw, RULES_CACHE_TTL_SECS)
+            .await
+            .is_err()
+        {
+            record_redis_error("attendance_rules_cache");
+        }
+    }
+
+    Ok(parse_override(&setting_key, &raw))
+}
+
+/// Aturan check-in/out efektif satker: default < global < satker.
+pub async fn get_attendance_rules_cached(
+    app_state: &AppState,
+    satker_id: Uuid,
+) -> Result<AttendanceRules, HttpError> {
+    let global = get_rules_override_cached(app_state, None).await?;
+    let satker = get_rules_override_cached(app_state, Some(satker_id)).await?;
+    Ok(AttendanceRules::default().apply(&global).apply(&satker))
+}
+
+/// Update Redis cache setelah aturan diubah admin (None = setting dihapus).
+pub async fn set_rules_override_cache(
+    app_state: &AppState,
+    satker_id: Option<Uuid>,
+    value: Option<&AttendanceRulesOverride>,
+) {
+    let raw = value
+        .and_then(|v| serde_json::to_string(v).ok())
+        .unwrap_or_else(|| "{}".to_string());
+    let key = cache_key(&attendance_rules_key(satker_id));
+
+    let mut conn = app_state.redis_client.clone();
+    if conn
+        .set_ex::<_, _, ()>(&key, raw, RULES_CACHE_TTL_SECS)
+        .await
+        .is_err()
+    {
+        record_redis_error("attendance_rules_cache");
+    }
+}
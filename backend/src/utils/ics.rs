@@ -0,0 +1,332 @@
+//! iCalendar (RFC 5545) minimal: penulis untuk feed kalender dan pembaca untuk import libur.
+//! Waktu ditulis dalam UTC (akhiran Z) sehingga tidak perlu blok VTIMEZONE;
+//! acara seharian memakai VALUE=DATE dengan DTEND eksklusif.
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+const PRODID: &str = "-//Absensi//Jadwal Dinas//ID";
+/// Batas panjang baris (octet, tanpa CRLF).
+const MAX_LINE_OCTETS: usize = 75;
+
+pub enum IcsTime {
+    At(DateTime<Utc>),
+    /// Acara seharian.
+    Date(NaiveDate),
+}
+
+pub struct IcsEvent {
+    /// Harus stabil antar-fetch agar klien memperbarui acara yang sama.
+    pub uid: String,
+    pub start: IcsTime,
+    /// Untuk `IcsTime::Date`, tanggal setelah hari terakhir (eksklusif).
+    pub end: IcsTime,
+    pub summary: String,
+    pub description: Option<String>,
+    pub categories: Option<String>,
+    pub last_modified: DateTime<Utc>,
+}
+
+pub fn render_calendar(name: &str, timezone: &str, events: &[IcsEvent]) -> String {
+    let mut out = String::new();
+
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{}", PRODID));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, "METHOD:PUBLISH");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(name)));
+    push_line(&mut out, &format!("X-WR-TIMEZONE:{}", timezone));
+
+    for ev in events {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", ev.uid));
+        // DTSTAMP = versi terakhir data, bukan waktu fetch, agar output stabil
+        push_line(
+            &mut out,
+            &format!("DTSTAMP:{}", format_utc(ev.last_modified)),
+        );
+        push_line(&mut out, &format_time("DTSTART", &ev.start));
+        push_line(&mut out, &format_time("DTEND", &ev.end));
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(&ev.summary)));
+        if let Some(desc) = &ev.description {
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape_text(desc)));
+        }
+        if let Some(cat) = &ev.categories {
+            push_line(&mut out, &format!("CATEGORIES:{}", escape_text(cat)));
+        }
+        push_line(
+            &mut out,
+            &format!("LAST-MODIFIED:{}", format_utc(ev.last_modified)),
+        );
+        push_line(&mut out, "TRANSP:OPAQUE");
+        push_line(&mut out, "END:VEVENT");
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+fn format_utc(dt: DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn format_time(prop: &str, t: &IcsTime) -> String {
+    match t {
+        IcsTime::At(dt) => format!("{}:{}", prop, format_utc(*dt)),
+        IcsTime::Date(d) => format!("{};VALUE=DATE:{}", prop, d.format("%Y%m%d")),
+    }
+}
+
+/// Escape TEXT value: backslash, titik koma, koma dan baris baru.
+fn escape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Tulis satu content line dengan folding per 75 octet (tidak memotong karakter UTF-8).
+fn push_line(out: &mut String, line: &str) {
+    let mut used = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if used + len > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // spasi lanjutan ikut dihitung
+            used = 1;
+        }
+        out.push(c);
+        used += len;
+    }
+    out.push_str("\r\n");
+}
+
+/// VEVENT hasil parse; hanya bagian tanggal yang dipakai (jam & zona diabaikan).
+#[derive(Debug)]
+pub struct IcsParsedEvent {
+    pub start: NaiveDate,
+    /// Eksklusif, seperti DTEND di file. None = satu hari.
+    pub end: Option<NaiveDate>,
+    pub summary: String,
+}
+
+/// Baca semua VEVENT dari file .ics. Error berisi nomor baris (setelah unfolding).
+pub fn parse_events(text: &str) -> Result<Vec<IcsParsedEvent>, String> {
+    let mut events = Vec::new();
+    let mut in_event = false;
+    let mut start: Option<NaiveDate> = None;
+    let mut end: Option<NaiveDate> = None;
+    let mut summary: Option<String> = None;
+    let mut saw_calendar = false;
+
+    for (idx, line) in unfold_lines(text).iter().enumerate() {
+        let line_no = idx + 1;
+        let Some((head, value)) = line.split_once(':') else {
+            continue;
+        };
+        // nama properti sebelum parameter (;VALUE=DATE, ;TZID=...)
+        let name = head.split(';').next().unwrap_or("").to_ascii_uppercase();
+
+        match name.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VCALENDAR") => saw_calendar = true,
+            "BEGIN" if value.eq_ignore_ascii_case("VEVENT") => {
+                in_event = true;
+                start = None;
+                end = None;
+                summary = None;
+            }
+            "END" if value.eq_ignore_ascii_case("VEVENT") => {
+                if !in_event {
+                    return Err(format!("baris {}: END:VEVENT tanpa BEGIN", line_no));
+                }
+                in_event = false;
+                let start = start
+                    .take()
+                    .ok_or_else(|| format!("baris {}: VEVENT tanpa DTSTART", line_no))?;
+                events.push(IcsParsedEvent {
+                    start,
+                    end: end.take(),
+                    summary: summary.take().unwrap_or_default(),
+                });
+            }
+            "DTSTART" if in_event => {
+                start = Some(
+                    parse_ics_date(value)
+                        .ok_or_else(|| format!("baris {}: DTSTART tidak valid", line_no))?,
+                );
+            }
+            "DTEND" if in_event => {
+                end = Some(
+                    parse_ics_date(value)
+                        .ok_or_else(|| format!("baris {}: DTEND tidak valid", line_no))?,
+                );
+            }
+            "SUMMARY" if in_event => summary = Some(unescape_text(value)),
+            _ => {}
+        }
+    }
+
+    if !saw_calendar {
+        return Err("bukan file iCalendar (BEGIN:VCALENDAR tidak ditemukan)".to_string());
+    }
+    if in_event {
+        return Err("VEVENT tidak ditutup (END:VEVENT)".to_string());
+    }
+
+    Ok(events)
+}
+
+/// Gabungkan baris lanjutan (diawali spasi / tab) ke baris sebelumnya.
+fn unfold_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        if let Some(rest) = raw.strip_prefix([' ', '\t'])
+            && let Some(last) = lines.last_mut()
+        {
+            last.push_str(rest);
+            continue;
+        }
+        if !raw.is_empty() {
+            lines.push(raw.to_string());
+        }
+    }
+    lines
+}
+
+/// `20260101` atau `20260101T070000[Z]` -> tanggal.
+fn parse_ics_date(value: &str) -> Option<NaiveDate> {
+    let date = value.trim().get(..8)?;
+    NaiveDate::parse_from_str(date, "%Y%m%d").ok()
+}
+
+fn unescape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sample_event(uid: &str, summary: &str) -> IcsEvent {
+        IcsEvent {
+            uid: uid.to_string(),
+            start: IcsTime::Date(NaiveDate::from_ymd_opt(2026, 8, 17).unwrap()),
+            end: IcsTime::Date(NaiveDate::from_ymd_opt(2026, 8, 18).unwrap()),
+            summary: summary.to_string(),
+            description: None,
+            categories: None,
+            last_modified: Utc.with_ymd_and_hms(2026, 8, 1, 3, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn push_line_folds_at_75_octets_without_splitting_utf8() {
+        // 'é' 2 octet, '日' 3 octet, '🎉' 4 octet: batas 75 jatuh di tengah karakter
+        let line = format!("SUMMARY:{}", "aé日🎉".repeat(30));
+        let mut out = String::new();
+        push_line(&mut out, &line);
+
+        assert!(out.ends_with("\r\n"));
+        let physical: Vec<&str> = out.trim_end_matches("\r\n").split("\r\n").collect();
+        assert!(physical.len() > 1);
+        for (i, l) in physical.iter().enumerate() {
+            assert!(l.len() <= MAX_LINE_OCTETS, "baris {} {} octet", i, l.len());
+            if i > 0 {
+                assert!(l.starts_with(' '));
+            }
+        }
+        assert_eq!(unfold_lines(&out), vec![line]);
+    }
+
+    #[test]
+    fn push_line_short_line_is_not_folded() {
+        let line = "X".repeat(MAX_LINE_OCTETS);
+        let mut out = String::new();
+        push_line(&mut out, &line);
+        assert_eq!(out, format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn escape_text_escapes_special_characters() {
+        assert_eq!(escape_text("a\\b;c,d\r\ne"), r"a\\b\;c\,d\ne");
+        assert_eq!(unescape_text(r"a\\b\;c\,d\ne\Nf"), "a\\b;c,d\ne\nf");
+    }
+
+    #[test]
+    fn unfold_accepts_tab_and_bare_lf() {
+        let text = "BEGIN:VCALENDAR\nSUMMARY:Hari\n\tRaya\r\n Idul\r\nEND:VCALENDAR\n";
+        assert_eq!(
+            unfold_lines(text),
+            vec!["BEGIN:VCALENDAR", "SUMMARY:HariRayaIdul", "END:VCALENDAR"]
+        );
+    }
+
+    #[test]
+    fn render_then_parse_round_trips_summary_and_dates() {
+        let summary = format!(
+            "Libur; Hari Kemerdekaan, RI \\ 17 Agustus\nupacara {}",
+            "Bendera Merah Putih 🇮🇩 ".repeat(5).trim_end()
+        );
+        let text = render_calendar(
+            "Kalender, Uji",
+            "Asia/Jakarta",
+            &[sample_event("u1", &summary)],
+        );
+
+        let events = parse_events(&text).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary, summary);
+        assert_eq!(
+            events[0].start,
+            NaiveDate::from_ymd_opt(2026, 8, 17).unwrap()
+        );
+        assert_eq!(events[0].end, NaiveDate::from_ymd_opt(2026, 8, 18));
+    }
+
+    #[test]
+    fn render_is_deterministic_and_keeps_uid() {
+        let uid = "duty-5f0c2d7e-8d51-4c5e-9a43-8a0f7b1f2c3d@absensi";
+        let events = [sample_event(uid, "Piket")];
+
+        let first = render_calendar("Jadwal", "Asia/Jakarta", &events);
+        let second = render_calendar("Jadwal", "Asia/Jakarta", &events);
+        assert_eq!(first, second);
+
+        let uids: Vec<String> = unfold_lines(&first)
+            .into_iter()
+            .filter_map(|l| l.strip_prefix("UID:").map(str::to_string))
+            .collect();
+        assert_eq!(uids, vec![uid.to_string()]);
+    }
+
+    #[test]
+    fn parse_events_reports_unclosed_event() {
+        let err =
+            parse_events("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nDTSTART:20260101\r\n").unwrap_err();
+        assert!(err.contains("END:VEVENT"));
+        assert!(parse_events("BEGIN:VEVENT\r\nEND:VEVENT\r\n").is_err());
+    }
+}
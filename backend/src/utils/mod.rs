@@ -1,4 +1,6 @@
+pub mod attendance_rules_cache;
 pub mod fungsi;
+pub mod ics;
 pub mod password;
 pub mod time_parser;
 pub mod timezone_cache;
@@ -1,6 +1,8 @@
 use crate::AppState;
 use crate::database::settings::SettingsRepo;
+use crate::db::DBClient;
 use crate::error::HttpError;
+use crate::services::metrics::record_redis_error;
 use chrono_tz::Tz;
 use redis::AsyncCommands;
 
@@ -18,34 +20,49 @@ pub async fn get_timezone_cached(app_state: &AppState) -> Result<Tz, HttpError>
     // 1) Try Redis cache (best-effort)
     {
         let mut conn = app_state.redis_client.clone();
-        if let Ok(Some(tz_str)) = conn.get::<_, Option<String>>(TZ_CACHE_KEY).await {
-            return Ok(parse_tz_or_default(&tz_str));
+        match conn.get::<_, Option<String>>(TZ_CACHE_KEY).await {
+            Ok(Some(tz_str)) => return Ok(parse_tz_or_default(&tz_str)),
+            Ok(None) => {}
+            Err(_) => record_redis_error("timezone_cache"),
         }
     }
 
     // 2) Fallback to DB
-    let tz_str = app_state
-        .db_client
-        .get_timezone_value()
-        .await
-        .map_err(|e| HttpError::server_error(e.to_string()))?;
-    let tz = parse_tz_or_default(&tz_str);
+    let tz = get_timezone_from_db(&app_state.db_client).await?;
+    let tz_str = tz.name().to_string();
 
     // 3) Fill Redis cache (best-effort)
     {
         let mut conn = app_state.redis_client.clone();
-        let _ = conn
+        if conn
             .set_ex::<_, _, ()>(TZ_CACHE_KEY, tz_str, TZ_CACHE_TTL_SECS)
-            .await;
+            .await
+            .is_err()
+        {
+            record_redis_error("timezone_cache");
+        }
     }
 
     Ok(tz)
 }
 
+/// Timezone operasional langsung dari DB (tanpa Redis), mis. untuk CLI admin.
+pub async fn get_timezone_from_db(db: &DBClient) -> Result<Tz, HttpError> {
+    let tz_str = db
+        .get_timezone_value()
+        .await
+        .map_err(|e| HttpError::server_error(e.to_string()))?;
+    Ok(parse_tz_or_default(&tz_str))
+}
+
 /// Update Redis cache after SUPERADMIN updates timezone.
 pub async fn set_timezone_cache(app_state: &AppState, tz_str: &str) {
     let mut conn = app_state.redis_client.clone();
-    let _ = conn
+    if conn
         .set_ex::<_, _, ()>(TZ_CACHE_KEY, tz_str, TZ_CACHE_TTL_SECS)
-        .await;
+        .await
+        .is_err()
+    {
+        record_redis_error("timezone_cache");
+    }
 }